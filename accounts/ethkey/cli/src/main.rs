@@ -27,11 +27,12 @@ extern crate threadpool;
 extern crate serde_derive;
 
 use std::num::ParseIntError;
+use std::time::{Duration, Instant};
 use std::{env, fmt, process, io, sync};
 
 use docopt::Docopt;
-use ethkey::{KeyPair, Random, Brain, BrainPrefix, Prefix, Error as EthkeyError, Generator, sign, verify_public, verify_address, brain_recover};
-use rustc_hex::{FromHex, FromHexError};
+use ethkey::{KeyPair, Random, Brain, BrainPrefix, Error as EthkeyError, Generator, Address, Keccak256, sign, verify_public, verify_address, brain_recover};
+use rustc_hex::FromHexError;
 
 const USAGE: &'static str = r#"
 Parity Ethereum keys generator.
@@ -41,6 +42,7 @@ Usage:
     ethkey info <secret-or-phrase> [options]
     ethkey generate random [options]
     ethkey generate prefix <prefix> [options]
+    ethkey generate suffix <suffix> [options]
     ethkey sign <secret> <message>
     ethkey verify public <public> <signature> <message>
     ethkey verify address <address> <signature> <message>
@@ -53,11 +55,16 @@ Options:
     -p, --public       Display only the public key.
     -a, --address      Display only the address.
     -b, --brain        Use parity brain wallet algorithm. Not recommended.
+    -c, --checksum     Match the prefix or suffix case-sensitively against
+                       the EIP-55 checksummed address.
+    -t, --threads NUM  Number of threads used to search for vanity address.
+                       Defaults to the number of CPU cores.
 
 Commands:
     info               Display public key and address of the secret.
     generate random    Generates new random Ethereum key.
     generate prefix    Random generation, but address must start with a prefix ("vanity address").
+    generate suffix    Random generation, but address must end with a suffix.
     sign               Sign message using a secret key.
     verify             Verify signer of the signature by public key or address.
     recover            Try to find brain phrase matching given address from partial phrase.
@@ -69,12 +76,14 @@ struct Args {
 	cmd_generate: bool,
 	cmd_random: bool,
 	cmd_prefix: bool,
+	cmd_suffix: bool,
 	cmd_sign: bool,
 	cmd_verify: bool,
 	cmd_public: bool,
 	cmd_address: bool,
 	cmd_recover: bool,
 	arg_prefix: String,
+	arg_suffix: String,
 	arg_secret: String,
 	arg_secret_or_phrase: String,
 	arg_known_phrase: String,
@@ -86,6 +95,8 @@ struct Args {
 	flag_public: bool,
 	flag_address: bool,
 	flag_brain: bool,
+	flag_checksum: bool,
+	flag_threads: Option<usize>,
 }
 
 #[derive(Debug)]
@@ -215,25 +226,36 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 			} else {
 				(Random.generate()?, None)
 			}
-		} else if args.cmd_prefix {
-			let prefix = args.arg_prefix.from_hex()?;
+		} else if args.cmd_prefix || args.cmd_suffix {
+			let pattern = if args.cmd_prefix {
+				Pattern::new(Position::Prefix, &args.arg_prefix, args.flag_checksum)?
+			} else {
+				Pattern::new(Position::Suffix, &args.arg_suffix, args.flag_checksum)?
+			};
+			let pattern = sync::Arc::new(pattern);
 			let brain = args.flag_brain;
-			in_threads(move || {
-				let iterations = 1024;
-				let prefix = prefix.clone();
+			let attempts = sync::Arc::new(sync::atomic::AtomicUsize::new(0));
+			let progress = attempts.clone();
+			in_threads(args.flag_threads, Some(progress), move || {
+				let pattern = pattern.clone();
+				let attempts = attempts.clone();
 				move || {
-					let prefix = prefix.clone();
-					let res = if brain {
-						let mut brain = BrainPrefix::new(prefix, iterations, BRAIN_WORDS);
-						let result = brain.generate();
-						let phrase = format!("recovery phrase: {}", brain.phrase());
-						result.map(|keypair| (keypair, Some(phrase)))
-					} else {
-						let result = Prefix::new(prefix, iterations).generate();
-						result.map(|res| (res, None))
-					};
-
-					Ok(res.map(Some).unwrap_or(None))
+					for _ in 0..VANITY_BATCH_SIZE {
+						let (keypair, phrase) = if brain {
+							let phrase = parity_wordlist::random_phrase(BRAIN_WORDS);
+							let keypair = Brain::new(phrase.clone()).generate().expect("Brain wallet generator is infallible; qed");
+							(keypair, Some(format!("recovery phrase: {}", phrase)))
+						} else {
+							(Random.generate()?, None)
+						};
+
+						if pattern.matches(&keypair.address()) {
+							return Ok(Some((keypair, phrase)));
+						}
+					}
+
+					attempts.fetch_add(VANITY_BATCH_SIZE, sync::atomic::Ordering::Relaxed);
+					Ok(None)
 				}
 			})?
 		} else {
@@ -262,7 +284,7 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 		let display_mode = DisplayMode::new(&args);
 		let known_phrase = args.arg_known_phrase;
 		let address = args.arg_address.parse().map_err(|_| EthkeyError::InvalidAddress)?;
-		let (phrase, keypair) = in_threads(move || {
+		let (phrase, keypair) = in_threads(None, None, move || {
 			let mut it = brain_recover::PhrasesIterator::from_known_phrase(&known_phrase, BRAIN_WORDS);
 			move || {
 				let mut i = 0;
@@ -289,6 +311,58 @@ fn execute<S, I>(command: I) -> Result<String, Error> where I: IntoIterator<Item
 }
 
 const BRAIN_WORDS: usize = 12;
+/// Number of keys generated between checks whether another thread has already found a match.
+const VANITY_BATCH_SIZE: usize = 256;
+/// How often the search rate is reported.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Part of the address which has to match the vanity pattern.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Position {
+	Prefix,
+	Suffix,
+}
+
+/// Vanity address pattern.
+#[derive(Debug, PartialEq)]
+struct Pattern {
+	position: Position,
+	hex: String,
+	checksum: bool,
+}
+
+impl Pattern {
+	/// Creates a new pattern. Unless `checksum` is set, the pattern is case-insensitive.
+	fn new(position: Position, pattern: &str, checksum: bool) -> Result<Self, EthkeyError> {
+		if pattern.len() > 40 || !pattern.chars().all(|c| c.is_digit(16)) {
+			return Err(EthkeyError::Custom(format!("Invalid address pattern: {}", pattern)));
+		}
+
+		Ok(Pattern {
+			position,
+			hex: if checksum { pattern.to_owned() } else { pattern.to_lowercase() },
+			checksum,
+		})
+	}
+
+	fn matches(&self, address: &Address) -> bool {
+		let address = if self.checksum { to_checksum(address) } else { format!("{:x}", address) };
+		match self.position {
+			Position::Prefix => address.starts_with(&self.hex),
+			Position::Suffix => address.ends_with(&self.hex),
+		}
+	}
+}
+
+/// Formats the address with EIP-55 mixed-case checksum, without `0x` prefix.
+fn to_checksum(address: &Address) -> String {
+	let hex = format!("{:x}", address);
+	let hash: [u8; 32] = hex.as_bytes().keccak256();
+	hex.chars().enumerate().map(|(i, c)| {
+		let nibble = if i % 2 == 0 { hash[i / 2] >> 4 } else { hash[i / 2] & 0x0f };
+		if nibble >= 8 { c.to_ascii_uppercase() } else { c }
+	}).collect()
+}
 
 fn validate_phrase(phrase: &str) -> String {
 	match Brain::validate_phrase(phrase, BRAIN_WORDS) {
@@ -297,13 +371,16 @@ fn validate_phrase(phrase: &str) -> String {
 	}
 }
 
-fn in_threads<F, X, O>(prepare: F) -> Result<O, EthkeyError> where
+fn in_threads<F, X, O>(threads: Option<usize>, attempts: Option<sync::Arc<sync::atomic::AtomicUsize>>, prepare: F) -> Result<O, EthkeyError> where
 	O: Send + 'static,
 	X: Send + 'static,
 	F: Fn() -> X,
 	X: FnMut() -> Result<Option<O>, EthkeyError>,
 {
-	let pool = threadpool::Builder::new().build();
+	let pool = match threads {
+		Some(threads) if threads > 0 => threadpool::Builder::new().num_threads(threads).build(),
+		_ => threadpool::Builder::new().build(),
+	};
 
 	let (tx, rx) = sync::mpsc::sync_channel(1);
 	let is_done = sync::Arc::new(sync::atomic::AtomicBool::default());
@@ -330,9 +407,25 @@ fn in_threads<F, X, O>(prepare: F) -> Result<O, EthkeyError> where
 		});
 	}
 
-	if let Ok(solution) = rx.recv() {
-		is_done.store(true, sync::atomic::Ordering::SeqCst);
-		return solution;
+	// workers hold the remaining senders
+	drop(tx);
+
+	let started = Instant::now();
+	loop {
+		match rx.recv_timeout(PROGRESS_INTERVAL) {
+			Ok(solution) => {
+				is_done.store(true, sync::atomic::Ordering::SeqCst);
+				return solution;
+			},
+			Err(sync::mpsc::RecvTimeoutError::Timeout) => {
+				if let Some(ref attempts) = attempts {
+					let attempts = attempts.load(sync::atomic::Ordering::Relaxed) as u64;
+					let elapsed = started.elapsed().as_secs();
+					eprintln!("Tried {} keys ({} keys/s)", attempts, attempts / elapsed.max(1));
+				}
+			},
+			Err(sync::mpsc::RecvTimeoutError::Disconnected) => break,
+		}
 	}
 
 	Err(EthkeyError::Custom("No results found.".into()))
@@ -340,7 +433,7 @@ fn in_threads<F, X, O>(prepare: F) -> Result<O, EthkeyError> where
 
 #[cfg(test)]
 mod tests {
-	use super::execute;
+	use super::{execute, Pattern, Position, to_checksum};
 
 	#[test]
 	fn info() {
@@ -448,4 +541,53 @@ address: 006e27b6a72e1f34c626762f3c4761547aff1421".to_owned();
 		let expected = "false".to_owned();
 		assert_eq!(execute(command).unwrap(), expected);
 	}
+
+	#[test]
+	fn checksum() {
+		let address = "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".parse().unwrap();
+		assert_eq!(to_checksum(&address), "5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+
+		let address = "fb6916095ca1df60bb79ce92ce3ea74c37c5d359".parse().unwrap();
+		assert_eq!(to_checksum(&address), "fB6916095ca1df60bB79Ce92cE3Ea74c37c5d359");
+	}
+
+	#[test]
+	fn pattern_matches() {
+		let address = "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".parse().unwrap();
+
+		assert!(Pattern::new(Position::Prefix, "5aae", false).unwrap().matches(&address));
+		assert!(Pattern::new(Position::Prefix, "5AAE", false).unwrap().matches(&address));
+		assert!(!Pattern::new(Position::Prefix, "beaed", false).unwrap().matches(&address));
+		assert!(Pattern::new(Position::Suffix, "beaed", false).unwrap().matches(&address));
+		assert!(Pattern::new(Position::Suffix, "BEAED", false).unwrap().matches(&address));
+
+		assert!(Pattern::new(Position::Prefix, "5aAe", true).unwrap().matches(&address));
+		assert!(!Pattern::new(Position::Prefix, "5aae", true).unwrap().matches(&address));
+		assert!(Pattern::new(Position::Suffix, "BeAed", true).unwrap().matches(&address));
+		assert!(!Pattern::new(Position::Suffix, "beaed", true).unwrap().matches(&address));
+
+		assert!(Pattern::new(Position::Prefix, "xyz", false).is_err());
+		assert!(Pattern::new(Position::Prefix, &"0".repeat(41), false).is_err());
+	}
+
+	#[test]
+	fn generate_prefix() {
+		let command = vec!["ethkey", "generate", "prefix", "a", "--address", "--threads", "2"]
+			.into_iter()
+			.map(Into::into)
+			.collect::<Vec<String>>();
+
+		assert!(execute(command).unwrap().starts_with("a"));
+	}
+
+	#[test]
+	fn generate_suffix() {
+		let command = vec!["ethkey", "generate", "suffix", "F", "--address", "--checksum"]
+			.into_iter()
+			.map(Into::into)
+			.collect::<Vec<String>>();
+
+		let address = execute(command).unwrap();
+		assert!(to_checksum(&address.parse().unwrap()).ends_with("F"));
+	}
 }
@@ -16,11 +16,16 @@
 
 //! Extended keys
 
+use std::fmt;
+use std::str::FromStr;
 use secret::Secret;
 use Public;
 use ethereum_types::H256;
 pub use self::derivation::Error as DerivationError;
 
+/// Offset of the hardened indices, as defined in BIP-32.
+pub const HARDENED_OFFSET: u32 = 1 << 31;
+
 /// Represents label that can be stored as a part of key derivation
 pub trait Label {
 	/// Length of the data that label occupies
@@ -60,6 +65,69 @@ impl From<u32> for Derivation<u32> {
 	}
 }
 
+/// BIP-32 derivation path, i.e. `m/44'/60'/0'/0/0`.
+/// Hardened indices are stored with `HARDENED_OFFSET` added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath {
+	indices: Vec<u32>,
+}
+
+impl DerivationPath {
+	/// New derivation path from raw indices.
+	pub fn new(indices: Vec<u32>) -> Self {
+		DerivationPath { indices }
+	}
+
+	/// Raw indices of the path, hardened ones include `HARDENED_OFFSET`.
+	pub fn indices(&self) -> &[u32] {
+		&self.indices
+	}
+}
+
+impl FromStr for DerivationPath {
+	type Err = DerivationError;
+
+	fn from_str(path: &str) -> Result<Self, Self::Err> {
+		let mut parts = path.trim().split('/');
+		match parts.next() {
+			Some("m") | Some("M") => {},
+			_ => return Err(DerivationError::InvalidPath),
+		}
+
+		let indices = parts.map(|part| {
+			let (index, hardened) = match part.chars().last() {
+				Some('\'') | Some('h') | Some('H') => (&part[..part.len() - 1], true),
+				_ => (part, false),
+			};
+			// `u32::from_str` accepts a leading `+`, which is not valid in a path.
+			if !index.chars().all(|c| c.is_ascii_digit()) {
+				return Err(DerivationError::InvalidPath);
+			}
+			let index: u32 = index.parse().map_err(|_| DerivationError::InvalidPath)?;
+			if index >= HARDENED_OFFSET {
+				return Err(DerivationError::InvalidPath);
+			}
+			Ok(if hardened { index + HARDENED_OFFSET } else { index })
+		}).collect::<Result<_, _>>()?;
+
+		Ok(DerivationPath::new(indices))
+	}
+}
+
+impl fmt::Display for DerivationPath {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "m")?;
+		for index in &self.indices {
+			if *index >= HARDENED_OFFSET {
+				write!(f, "/{}'", index - HARDENED_OFFSET)?;
+			} else {
+				write!(f, "/{}", index)?;
+			}
+		}
+		Ok(())
+	}
+}
+
 impl Label for H256 {
 	fn len() -> usize { 32 }
 
@@ -104,10 +172,23 @@ impl ExtendedSecret {
 		ExtendedSecret::with_code(derived_secret, next_chain_code)
 	}
 
+	/// Derive new private key following the whole derivation path.
+	pub fn derive_path(&self, path: &DerivationPath) -> ExtendedSecret {
+		path.indices().iter().fold(
+			ExtendedSecret::with_code(self.secret.clone(), self.chain_code),
+			|secret, index| secret.derive((*index).into()),
+		)
+	}
+
 	/// Private key component of the extended key.
 	pub fn as_raw(&self) -> &Secret {
 		&self.secret
 	}
+
+	/// Chain code component of the extended key.
+	pub fn chain_code(&self) -> &H256 {
+		&self.chain_code
+	}
 }
 
 /// Extended public key, allows deterministic derivation of subsequent keys.
@@ -201,6 +282,16 @@ impl ExtendedKeyPair {
 			secret: derived,
 		})
 	}
+
+	/// Derive new key pair following the whole derivation path, e.g. `m/44'/60'/0'/0/0` (BIP-32).
+	pub fn derive_path(&self, path: &DerivationPath) -> Result<Self, DerivationError> {
+		let derived = self.secret.derive_path(path);
+
+		Ok(ExtendedKeyPair {
+			public: ExtendedPublic::from_secret(&derived)?,
+			secret: derived,
+		})
+	}
 }
 
 // Derivation functions for private and public keys
@@ -221,6 +312,7 @@ mod derivation {
 		InvalidPoint,
 		MissingIndex,
 		InvalidSeed,
+		InvalidPath,
 	}
 
 	// Deterministic derivation of the key using secp256k1 elliptic curve.
@@ -375,8 +467,10 @@ mod derivation {
 
 #[cfg(test)]
 mod tests {
-	use super::{ExtendedSecret, ExtendedPublic, ExtendedKeyPair};
+	use super::{ExtendedSecret, ExtendedPublic, ExtendedKeyPair, DerivationPath, HARDENED_OFFSET};
 	use secret::Secret;
+	use keypair::KeyPair;
+	use rustc_hex::FromHex;
 	use std::str::FromStr;
 	use ethereum_types::{H128, H256};
 	use super::{derivation, Derivation};
@@ -498,4 +592,60 @@ mod tests {
 				.expect("Private should be decoded ok")
 		);
 	}
+
+	#[test]
+	fn test_vector_1_path() {
+		// BIP-32 test vector 1, chain m/0'/1/2'/2/1000000000
+		let cases = [
+			("m", "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35"),
+			("m/0'", "edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea"),
+			("m/0'/1", "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368"),
+			("m/0'/1/2'", "cbce0d719ecf7431d88e6a89fa1483e02e35092af60c042b1df2ff59fa424dca"),
+			("m/0'/1/2'/2", "0f479245fb19a38a1954c5c7c0ebab2f9bdfd96a17563ef28a6a4b1a2a764ef4"),
+			("m/0'/1/2'/2/1000000000", "471b76e389e528d6de6d816857e012c5455051cad6660850e58372a6c3e6e7c8"),
+		];
+
+		for &(path, private) in cases.iter() {
+			let path: DerivationPath = path.parse().expect("Path should be valid");
+			test_extended(
+				|secret| secret.derive_path(&path),
+				H256::from_str(private).expect("Private should be decoded ok")
+			);
+		}
+	}
+
+	#[test]
+	fn test_bip44_ethereum_path() {
+		// seed of the "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about" mnemonic
+		let seed = "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4"
+			.from_hex()
+			.expect("Seed should be valid hex");
+		let master = ExtendedKeyPair::with_seed(&seed).expect("Seed should be valid");
+
+		let first = master.derive_path(&"m/44'/60'/0'/0/0".parse().unwrap()).unwrap();
+		let first = KeyPair::from_secret(first.secret().as_raw().clone()).unwrap();
+		assert_eq!(first.address(), "9858effd232b4033e47d90003d41ec34ecaeda94".into());
+
+		let second = master.derive_path(&"m/44'/60'/0'/0/1".parse().unwrap()).unwrap();
+		let second = KeyPair::from_secret(second.secret().as_raw().clone()).unwrap();
+		assert_eq!(second.address(), "6fac4d18c912343bf86fa7049364dd4e424ab9c0".into());
+	}
+
+	#[test]
+	fn derivation_path_parsing() {
+		let path: DerivationPath = "m/44'/60'/0'/0/7".parse().unwrap();
+		assert_eq!(path.indices(), &[44 + HARDENED_OFFSET, 60 + HARDENED_OFFSET, HARDENED_OFFSET, 0, 7]);
+		assert_eq!(path.to_string(), "m/44'/60'/0'/0/7");
+
+		let path: DerivationPath = "m/44h/60H/1".parse().unwrap();
+		assert_eq!(path.to_string(), "m/44'/60'/1");
+		assert_eq!("m".parse::<DerivationPath>().unwrap().indices().len(), 0);
+
+		assert!("".parse::<DerivationPath>().is_err());
+		assert!("44'/60'".parse::<DerivationPath>().is_err());
+		assert!("m/".parse::<DerivationPath>().is_err());
+		assert!("m/x".parse::<DerivationPath>().is_err());
+		assert!("m/+1".parse::<DerivationPath>().is_err());
+		assert!("m/2147483648".parse::<DerivationPath>().is_err());
+	}
 }
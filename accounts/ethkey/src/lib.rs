@@ -25,6 +25,7 @@ extern crate parity_wordlist;
 #[macro_use]
 extern crate quick_error;
 extern crate rand;
+extern crate rayon;
 extern crate rustc_hex;
 extern crate secp256k1;
 extern crate serde;
@@ -48,6 +49,7 @@ mod random;
 mod signature;
 mod secret;
 mod extended;
+mod mnemonic;
 
 pub mod brain_recover;
 pub mod crypto;
@@ -57,14 +59,16 @@ pub use self::parity_wordlist::Error as WordlistError;
 pub use self::brain::Brain;
 pub use self::brain_prefix::BrainPrefix;
 pub use self::error::Error;
+pub use self::keccak::Keccak256;
 pub use self::keypair::{KeyPair, public_to_address};
 pub use self::math::public_is_valid;
 pub use self::password::Password;
 pub use self::prefix::Prefix;
 pub use self::random::Random;
-pub use self::signature::{sign, verify_public, verify_address, recover, Signature};
+pub use self::signature::{sign, verify_public, verify_address, recover, recover_batch, Signature};
 pub use self::secret::Secret;
-pub use self::extended::{ExtendedPublic, ExtendedSecret, ExtendedKeyPair, DerivationError, Derivation};
+pub use self::extended::{ExtendedPublic, ExtendedSecret, ExtendedKeyPair, DerivationError, Derivation, DerivationPath, HARDENED_OFFSET};
+pub use self::mnemonic::{Mnemonic, MnemonicError};
 
 use ethereum_types::H256;
 
@@ -0,0 +1,299 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! BIP-39 mnemonic phrases
+//! https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki
+
+use std::{fmt, io, error};
+use std::num::NonZeroU32;
+use memzero::Memzero;
+use parity_crypto::{digest, pbkdf2};
+use rand::Rng;
+use rand::os::OsRng;
+use extended::{ExtendedKeyPair, DerivationError, DerivationPath, HARDENED_OFFSET};
+use Secret;
+
+/// Number of PBKDF2 rounds used to compute the seed.
+const SEED_ROUNDS: u32 = 2048;
+
+lazy_static! {
+	/// Official english wordlist, sorted.
+	static ref WORDS: Vec<&'static str> = include_str!("bip39_english.txt").lines().collect();
+}
+
+/// Mnemonic error.
+#[derive(Debug)]
+pub enum MnemonicError {
+	/// Phrase has to consist of 12, 15, 18, 21 or 24 words.
+	InvalidWordCount(usize),
+	/// Word is not a part of the wordlist.
+	UnknownWord(String),
+	/// Phrase checksum doesn't match.
+	InvalidChecksum,
+	/// Key derivation failed.
+	Derivation(DerivationError),
+	/// Could not obtain randomness.
+	Io(io::Error),
+}
+
+impl fmt::Display for MnemonicError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			MnemonicError::InvalidWordCount(count) => write!(f, "Invalid mnemonic length: {} words, expected 12, 15, 18, 21 or 24", count),
+			MnemonicError::UnknownWord(ref word) => write!(f, "Unknown mnemonic word: {}", word),
+			MnemonicError::InvalidChecksum => write!(f, "Invalid mnemonic checksum, the phrase is mistyped"),
+			MnemonicError::Derivation(ref e) => write!(f, "Key derivation failed: {:?}", e),
+			MnemonicError::Io(ref e) => write!(f, "I/O error: {}", e),
+		}
+	}
+}
+
+impl error::Error for MnemonicError {
+	fn description(&self) -> &str {
+		"Mnemonic error"
+	}
+}
+
+impl From<DerivationError> for MnemonicError {
+	fn from(e: DerivationError) -> Self {
+		MnemonicError::Derivation(e)
+	}
+}
+
+impl From<io::Error> for MnemonicError {
+	fn from(e: io::Error) -> Self {
+		MnemonicError::Io(e)
+	}
+}
+
+/// Valid BIP-39 mnemonic phrase.
+#[derive(Clone, PartialEq)]
+pub struct Mnemonic {
+	phrase: String,
+}
+
+impl fmt::Debug for Mnemonic {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Mnemonic(***)")
+	}
+}
+
+impl Mnemonic {
+	/// Generates a random phrase with given number of words.
+	pub fn random(words: usize) -> Result<Self, MnemonicError> {
+		let len = entropy_len(words).ok_or(MnemonicError::InvalidWordCount(words))?;
+		let mut entropy = Memzero::from(vec![0u8; len]);
+		OsRng::new()?.fill_bytes(&mut entropy);
+		Self::from_entropy(&entropy)
+	}
+
+	/// Creates a phrase encoding given entropy (16 to 32 bytes, multiple of 4).
+	pub fn from_entropy(entropy: &[u8]) -> Result<Self, MnemonicError> {
+		let words = entropy.len() * 3 / 4;
+		if entropy_len(words) != Some(entropy.len()) {
+			return Err(MnemonicError::InvalidWordCount(words));
+		}
+
+		let checksum = digest::sha256(entropy);
+		let mut bits = Memzero::from(entropy.to_vec());
+		bits.push(checksum[0]);
+
+		let phrase = (0..words)
+			.map(|i| WORDS[read_index(&bits, i * 11)])
+			.collect::<Vec<_>>()
+			.join(" ");
+
+		Ok(Mnemonic { phrase })
+	}
+
+	/// Parses and validates the phrase.
+	pub fn from_phrase(phrase: &str) -> Result<Self, MnemonicError> {
+		let words: Vec<_> = phrase.split_whitespace().map(str::to_lowercase).collect();
+		let len = entropy_len(words.len()).ok_or(MnemonicError::InvalidWordCount(words.len()))?;
+
+		let mut bits = Memzero::from(vec![0u8; len + 1]);
+		for (i, word) in words.iter().enumerate() {
+			let index = WORDS.binary_search(&word.as_str())
+				.map_err(|_| MnemonicError::UnknownWord(word.clone()))?;
+			write_index(&mut bits, i * 11, index);
+		}
+
+		let checksum_bits = words.len() / 3;
+		let mask = (0xff00u16 >> checksum_bits) as u8;
+		if digest::sha256(&bits[..len])[0] & mask != bits[len] {
+			return Err(MnemonicError::InvalidChecksum);
+		}
+
+		Ok(Mnemonic { phrase: words.join(" ") })
+	}
+
+	/// Normalized phrase.
+	pub fn phrase(&self) -> &str {
+		&self.phrase
+	}
+
+	/// Computes the 64-byte seed. Any passphrase results in a valid (but different) seed.
+	/// Non-ASCII passphrases are expected to be NFKD normalized.
+	pub fn seed(&self, passphrase: &str) -> Memzero<Vec<u8>> {
+		let mut seed = [0u8; 64];
+		let salt = format!("mnemonic{}", passphrase);
+		let rounds = NonZeroU32::new(SEED_ROUNDS).expect("2048 > 0; qed");
+		pbkdf2::sha512(rounds, pbkdf2::Salt(salt.as_bytes()), pbkdf2::Secret(self.phrase.as_bytes()), &mut seed);
+		let result = Memzero::from(seed.to_vec());
+		seed.iter_mut().for_each(|b| *b = 0);
+		result
+	}
+
+	/// Derives the secret of `index`-th account at the standard `m/44'/60'/0'/0/index` path.
+	pub fn derive_secret(&self, passphrase: &str, index: u32) -> Result<Secret, MnemonicError> {
+		if index >= HARDENED_OFFSET {
+			return Err(DerivationError::InvalidPath.into());
+		}
+		let path = DerivationPath::new(vec![44 + HARDENED_OFFSET, 60 + HARDENED_OFFSET, HARDENED_OFFSET, 0, index]);
+		let master = ExtendedKeyPair::with_seed(&self.seed(passphrase))?;
+		Ok(master.derive_path(&path)?.secret().as_raw().clone())
+	}
+}
+
+/// Length of the entropy in bytes for given number of words.
+fn entropy_len(words: usize) -> Option<usize> {
+	match words {
+		12 | 15 | 18 | 21 | 24 => Some(words * 4 / 3),
+		_ => None,
+	}
+}
+
+/// Reads 11-bit word index starting at given bit.
+fn read_index(bits: &[u8], offset: usize) -> usize {
+	(offset..offset + 11).fold(0, |index, pos| (index << 1) | ((bits[pos / 8] >> (7 - pos % 8)) & 1) as usize)
+}
+
+/// Writes 11-bit word index starting at given bit.
+fn write_index(bits: &mut [u8], offset: usize, index: usize) {
+	for (i, pos) in (offset..offset + 11).enumerate() {
+		if index & (1 << (10 - i)) != 0 {
+			bits[pos / 8] |= 1 << (7 - pos % 8);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use rustc_hex::{FromHex, ToHex};
+	use keypair::KeyPair;
+	use super::{Mnemonic, MnemonicError, WORDS};
+
+	// Official test vectors, all seeds use `TREZOR` passphrase.
+	const VECTORS: &[(&str, &str, &str)] = &[
+		(
+			"00000000000000000000000000000000",
+			"abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+			"c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+		),
+		(
+			"7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+			"legal winner thank year wave sausage worth useful legal winner thank yellow",
+			"2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+		),
+		(
+			"80808080808080808080808080808080",
+			"letter advice cage absurd amount doctor acoustic avoid letter advice cage above",
+			"d71de856f81a8acc65e6fc851a38d4d7ec216fd0796d0a6827a3ad6ed5511a30fa280f12eb2e47ed2ac03b5c462a0358d18d69fe4f985ec81778c1b370b652a8",
+		),
+		(
+			"ffffffffffffffffffffffffffffffff",
+			"zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong",
+			"ac27495480225222079d7be181583751e86f571027b0497b5b5d11218e0a8a13332572917f0f8e5a589620c6f15b11c61dee327651a14c34e18231052e48c069",
+		),
+		(
+			"9e885d952ad362caeb4efe34a8e91bd2",
+			"ozone drill grab fiber curtain grace pudding thank cruise elder eight picnic",
+			"274ddc525802f7c828d8ef7ddbcdc5304e87ac3535913611fbbfa986d0c9e5476c91689f9c8a54fd55bd38606aa6a8595ad213d4c9c9f9aca3fb217069a41028",
+		),
+		(
+			"0000000000000000000000000000000000000000000000000000000000000000",
+			"abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art",
+			"bda85446c68413707090a52022edd26a1c9462295029f2e60cd7c4f2bbd3097170af7a4d73245cafa9c3cca8d561a7c3de6f5d4a10be8ed2a5e608d68f92fcc8",
+		),
+		(
+			"ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+			"zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+			"dd48c104698c30cfe2b6142103248622fb7bb0ff692eebb00089b32d22484e1613912f0a5b694407be899ffd31ed3992c456cdf60f5d4564b8ba3f05a69890ad",
+		),
+	];
+
+	#[test]
+	fn should_load_wordlist() {
+		assert_eq!(WORDS.len(), 2048);
+		assert_eq!(WORDS[0], "abandon");
+		assert_eq!(WORDS[2047], "zoo");
+		assert!(WORDS.windows(2).all(|w| w[0] < w[1]));
+	}
+
+	#[test]
+	fn should_match_official_vectors() {
+		for &(entropy, phrase, seed) in VECTORS {
+			let mnemonic = Mnemonic::from_entropy(&entropy.from_hex().unwrap()).unwrap();
+			assert_eq!(mnemonic.phrase(), phrase);
+			assert_eq!(Mnemonic::from_phrase(phrase).unwrap(), mnemonic);
+			assert_eq!(mnemonic.seed("TREZOR").to_hex(), seed);
+		}
+	}
+
+	#[test]
+	fn should_generate_random_phrases() {
+		for &words in &[12, 24] {
+			let mnemonic = Mnemonic::random(words).unwrap();
+			assert_eq!(mnemonic.phrase().split(' ').count(), words);
+			assert_eq!(Mnemonic::from_phrase(mnemonic.phrase()).unwrap(), mnemonic);
+		}
+		match Mnemonic::random(13) {
+			Err(MnemonicError::InvalidWordCount(13)) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn should_reject_invalid_phrases() {
+		match Mnemonic::from_phrase("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon") {
+			Err(MnemonicError::InvalidChecksum) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		match Mnemonic::from_phrase("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandonn") {
+			Err(MnemonicError::UnknownWord(ref word)) if word == "abandonn" => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		match Mnemonic::from_phrase("abandon abandon about") {
+			Err(MnemonicError::InvalidWordCount(3)) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn should_normalize_phrase() {
+		let mnemonic = Mnemonic::from_phrase("  Abandon abandon abandon abandon abandon abandon\nabandon abandon abandon abandon abandon  ABOUT ").unwrap();
+		assert_eq!(mnemonic.phrase(), VECTORS[0].1);
+	}
+
+	#[test]
+	fn should_derive_ethereum_accounts() {
+		let mnemonic = Mnemonic::from_phrase(VECTORS[0].1).unwrap();
+		let secret = mnemonic.derive_secret("", 0).unwrap();
+		assert_eq!(KeyPair::from_secret(secret).unwrap().address(), "9858effd232b4033e47d90003d41ec34ecaeda94".into());
+		assert!(mnemonic.derive_secret("", 1).unwrap() != mnemonic.derive_secret("", 0).unwrap());
+		assert!(mnemonic.derive_secret("TREZOR", 0).unwrap() != mnemonic.derive_secret("", 0).unwrap());
+	}
+}
@@ -21,6 +21,7 @@ use std::str::FromStr;
 use std::hash::{Hash, Hasher};
 use secp256k1::{Message as SecpMessage, RecoverableSignature, RecoveryId, Error as SecpError};
 use secp256k1::key::{SecretKey, PublicKey};
+use rayon::prelude::*;
 use rustc_hex::{ToHex, FromHex};
 use ethereum_types::{H520, H256};
 use {Secret, Public, SECP256K1, Error, Message, public_to_address, Address};
@@ -237,11 +238,19 @@ pub fn recover(signature: &Signature, message: &Message) -> Result<Public, Error
 	Ok(public)
 }
 
+/// Recovers public keys of many signatures in parallel.
+/// Results are returned in the same order as the input.
+pub fn recover_batch(items: &[(Signature, Message)]) -> Vec<Result<Public, Error>> {
+	items.par_iter()
+		.map(|&(ref signature, ref message)| recover(signature, message))
+		.collect()
+}
+
 #[cfg(test)]
 mod tests {
 	use std::str::FromStr;
 	use {Generator, Random, Message};
-	use super::{sign, verify_public, verify_address, recover, Signature};
+	use super::{sign, verify_public, verify_address, recover, recover_batch, Signature};
 
 	#[test]
 	fn vrs_conversion() {
@@ -291,4 +300,43 @@ mod tests {
 		let signature = sign(keypair.secret(), &message).unwrap();
 		assert!(verify_address(&keypair.address(), &signature, &message).unwrap());
 	}
+
+	#[test]
+	fn recover_batch_matches_single_recover() {
+		let items: Vec<_> = (0..64u64).map(|i| {
+			let keypair = Random.generate().unwrap();
+			let message = Message::from(i);
+			(sign(keypair.secret(), &message).unwrap(), message)
+		}).collect();
+
+		let recovered = recover_batch(&items);
+
+		assert_eq!(recovered.len(), items.len());
+		for (&(ref signature, ref message), public) in items.iter().zip(recovered) {
+			assert_eq!(public.unwrap(), recover(signature, message).unwrap());
+		}
+	}
+
+	#[test]
+	fn recover_batch_keeps_errors_in_place() {
+		let keypair = Random.generate().unwrap();
+		let message = Message::default();
+		let valid = sign(keypair.secret(), &message).unwrap();
+		let mut invalid_v = valid.clone();
+		invalid_v[64] = 5;
+		let zero = Signature::default();
+
+		let recovered = recover_batch(&[
+			(valid.clone(), message),
+			(invalid_v, message),
+			(valid, message),
+			(zero, message),
+		]);
+
+		assert_eq!(recovered.len(), 4);
+		assert_eq!(recovered[0].as_ref().unwrap(), keypair.public());
+		assert!(recovered[1].is_err());
+		assert_eq!(recovered[2].as_ref().unwrap(), keypair.public());
+		assert!(recovered[3].is_err());
+	}
 }
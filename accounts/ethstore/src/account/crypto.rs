@@ -15,13 +15,12 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::str;
-use std::num::NonZeroU32;
 use ethkey::{Password, Secret};
 use {json, Error, crypto};
 use crypto::Keccak256;
 use random::Random;
 use smallvec::SmallVec;
-use account::{Cipher, Kdf, Aes128Ctr, Pbkdf2, Prf};
+use account::{Cipher, Kdf, KdfConfig, Aes128Ctr, Pbkdf2, Scrypt, Prf};
 
 /// Encrypted data
 #[derive(Debug, PartialEq, Clone)]
@@ -74,19 +73,38 @@ impl From<Crypto> for String {
 
 impl Crypto {
 	/// Encrypt account secret
-	pub fn with_secret(secret: &Secret, password: &Password, iterations: NonZeroU32) -> Result<Self, crypto::Error> {
-		Crypto::with_plain(&*secret, password, iterations)
+	pub fn with_secret(secret: &Secret, password: &Password, kdf: &KdfConfig) -> Result<Self, crypto::Error> {
+		Crypto::with_plain(&*secret, password, kdf)
 	}
 
 	/// Encrypt custom plain data
-	pub fn with_plain(plain: &[u8], password: &Password, iterations: NonZeroU32) -> Result<Self, crypto::Error> {
+	pub fn with_plain(plain: &[u8], password: &Password, kdf: &KdfConfig) -> Result<Self, crypto::Error> {
 		let salt: [u8; 32] = Random::random();
 		let iv: [u8; 16] = Random::random();
 
 		// two parts of derived key
 		// DK = [ DK[0..15] DK[16..31] ] = [derived_left_bits, derived_right_bits]
-		let (derived_left_bits, derived_right_bits) =
-			crypto::derive_key_iterations(password.as_bytes(), &salt, iterations);
+		let (derived_left_bits, derived_right_bits, kdf) = match *kdf {
+			KdfConfig::Pbkdf2(iterations) => {
+				let (left, right) = crypto::derive_key_iterations(password.as_bytes(), &salt, iterations);
+				(left, right, Kdf::Pbkdf2(Pbkdf2 {
+					dklen: crypto::KEY_LENGTH as u32,
+					salt: salt.to_vec(),
+					c: iterations,
+					prf: Prf::HmacSha256,
+				}))
+			},
+			KdfConfig::Scrypt { n, r, p } => {
+				let (left, right) = crypto::scrypt::derive_key(password.as_bytes(), &salt, n, p, r)?;
+				(left, right, Kdf::Scrypt(Scrypt {
+					dklen: crypto::KEY_LENGTH as u32,
+					salt: salt.to_vec(),
+					n: n,
+					r: r,
+					p: p,
+				}))
+			},
+		};
 
 		// preallocated (on-stack in case of `Secret`) buffer to hold cipher
 		// length = length(plain) as we are using CTR-approach
@@ -104,12 +122,7 @@ impl Crypto {
 				iv: iv,
 			}),
 			ciphertext: ciphertext.into_vec(),
-			kdf: Kdf::Pbkdf2(Pbkdf2 {
-				dklen: crypto::KEY_LENGTH as u32,
-				salt: salt.to_vec(),
-				c: iterations,
-				prf: Prf::HmacSha256,
-			}),
+			kdf: kdf,
 			mac: mac,
 		})
 	}
@@ -160,17 +173,18 @@ impl Crypto {
 #[cfg(test)]
 mod tests {
 	use ethkey::{Generator, Random};
-	use super::{Crypto, Error, NonZeroU32};
+	use std::num::NonZeroU32;
+	use super::{Crypto, Error, KdfConfig};
 
 	lazy_static! {
-		static ref ITERATIONS: NonZeroU32 = NonZeroU32::new(10240).expect("10240 > 0; qed");
+		static ref KDF: KdfConfig = KdfConfig::Pbkdf2(NonZeroU32::new(10240).expect("10240 > 0; qed"));
 	}
 
 	#[test]
 	fn crypto_with_secret_create() {
 		let keypair = Random.generate().unwrap();
 		let passwd = "this is sparta".into();
-		let crypto = Crypto::with_secret(keypair.secret(), &passwd, *ITERATIONS).unwrap();
+		let crypto = Crypto::with_secret(keypair.secret(), &passwd, &KDF).unwrap();
 		let secret = crypto.secret(&passwd).unwrap();
 		assert_eq!(keypair.secret(), &secret);
 	}
@@ -178,7 +192,7 @@ mod tests {
 	#[test]
 	fn crypto_with_secret_invalid_password() {
 		let keypair = Random.generate().unwrap();
-		let crypto = Crypto::with_secret(keypair.secret(), &"this is sparta".into(), *ITERATIONS).unwrap();
+		let crypto = Crypto::with_secret(keypair.secret(), &"this is sparta".into(), &KDF).unwrap();
 		assert_matches!(crypto.secret(&"this is sparta!".into()), Err(Error::InvalidPassword))
 	}
 
@@ -186,7 +200,7 @@ mod tests {
 	fn crypto_with_null_plain_data() {
 		let original_data = b"";
 		let passwd = "this is sparta".into();
-		let crypto = Crypto::with_plain(&original_data[..], &passwd, *ITERATIONS).unwrap();
+		let crypto = Crypto::with_plain(&original_data[..], &passwd, &KDF).unwrap();
 		let decrypted_data = crypto.decrypt(&passwd).unwrap();
 		assert_eq!(original_data[..], *decrypted_data);
 	}
@@ -195,7 +209,7 @@ mod tests {
 	fn crypto_with_tiny_plain_data() {
 		let original_data = b"{}";
 		let passwd = "this is sparta".into();
-		let crypto = Crypto::with_plain(&original_data[..], &passwd, *ITERATIONS).unwrap();
+		let crypto = Crypto::with_plain(&original_data[..], &passwd, &KDF).unwrap();
 		let decrypted_data = crypto.decrypt(&passwd).unwrap();
 		assert_eq!(original_data[..], *decrypted_data);
 	}
@@ -204,8 +218,31 @@ mod tests {
 	fn crypto_with_huge_plain_data() {
 		let original_data: Vec<_> = (1..65536).map(|i| (i % 256) as u8).collect();
 		let passwd = "this is sparta".into();
-		let crypto = Crypto::with_plain(&original_data, &passwd, *ITERATIONS).unwrap();
+		let crypto = Crypto::with_plain(&original_data, &passwd, &KDF).unwrap();
 		let decrypted_data = crypto.decrypt(&passwd).unwrap();
 		assert_eq!(&original_data, &decrypted_data);
 	}
+
+	#[test]
+	fn crypto_with_scrypt() {
+		let keypair = Random.generate().unwrap();
+		let passwd = "this is sparta".into();
+		for &(n, r, p) in &[(1024, 8, 1), (4096, 1, 2), (16384, 8, 1)] {
+			let kdf = KdfConfig::scrypt(n, r, p).unwrap();
+			let crypto = Crypto::with_secret(keypair.secret(), &passwd, &kdf).unwrap();
+			assert_eq!(keypair.secret(), &crypto.secret(&passwd).unwrap());
+			assert_matches!(crypto.secret(&"this is sparta!".into()), Err(Error::InvalidPassword));
+		}
+	}
+
+	#[test]
+	fn crypto_with_custom_pbkdf2_iterations() {
+		let keypair = Random.generate().unwrap();
+		let passwd = "this is sparta".into();
+		for &c in &[1, 1024, 65536] {
+			let kdf = KdfConfig::Pbkdf2(NonZeroU32::new(c).unwrap());
+			let crypto = Crypto::with_secret(keypair.secret(), &passwd, &kdf).unwrap();
+			assert_eq!(keypair.secret(), &crypto.secret(&passwd).unwrap());
+		}
+	}
 }
@@ -16,6 +16,10 @@
 
 use json;
 use std::num::NonZeroU32;
+use Error;
+
+/// Maximal amount of memory (in bytes) scrypt may use to derive a key for a new keyfile.
+pub const MAX_SCRYPT_MEMORY: u64 = 1024 * 1024 * 1024;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Prf {
@@ -45,6 +49,47 @@ pub enum Kdf {
 	Scrypt(Scrypt),
 }
 
+/// Key derivation function (and its parameters) used to encrypt new keys.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum KdfConfig {
+	/// PBKDF2-HMAC-SHA256 with given number of iterations.
+	Pbkdf2(NonZeroU32),
+	/// Scrypt with given CPU/memory cost (`n`), block size (`r`) and parallelization (`p`).
+	Scrypt {
+		/// CPU/memory cost, must be a power of two.
+		n: u32,
+		/// Block size.
+		r: u32,
+		/// Parallelization.
+		p: u32,
+	},
+}
+
+impl KdfConfig {
+	/// Create scrypt configuration, validating the parameters.
+	pub fn scrypt(n: u32, r: u32, p: u32) -> Result<Self, Error> {
+		if n < 2 || !n.is_power_of_two() {
+			return Err(Error::InvalidKdfParams("scrypt N must be a power of two greater than 1".into()));
+		}
+		if r == 0 || p == 0 {
+			return Err(Error::InvalidKdfParams("scrypt r and p must be greater than 0".into()));
+		}
+		if r as u64 * p as u64 >= 1 << 30 {
+			return Err(Error::InvalidKdfParams("scrypt r * p must be less than 2^30".into()));
+		}
+
+		let memory = 128 * r as u64 * n as u64;
+		if memory > MAX_SCRYPT_MEMORY {
+			return Err(Error::InvalidKdfParams(format!(
+				"scrypt N and r would require {} MiB of memory, at most {} MiB is allowed",
+				memory / 1024 / 1024, MAX_SCRYPT_MEMORY / 1024 / 1024,
+			)));
+		}
+
+		Ok(KdfConfig::Scrypt { n, r, p })
+	}
+}
+
 impl From<json::Prf> for Prf {
 	fn from(json: json::Prf) -> Self {
 		match json {
@@ -124,3 +169,19 @@ impl Into<json::Kdf> for Kdf {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::KdfConfig;
+
+	#[test]
+	fn should_validate_scrypt_params() {
+		assert_eq!(KdfConfig::scrypt(1 << 18, 8, 1).unwrap(), KdfConfig::Scrypt { n: 1 << 18, r: 8, p: 1 });
+		assert!(KdfConfig::scrypt(0, 8, 1).is_err());
+		assert!(KdfConfig::scrypt(1, 8, 1).is_err());
+		assert!(KdfConfig::scrypt(1000, 8, 1).is_err());
+		assert!(KdfConfig::scrypt(1024, 0, 1).is_err());
+		assert!(KdfConfig::scrypt(1024, 8, 0).is_err());
+		assert!(KdfConfig::scrypt(1 << 30, 8, 1).is_err());
+	}
+}
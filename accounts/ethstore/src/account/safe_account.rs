@@ -14,10 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use ethkey::{self, KeyPair, sign, Address, Password, Signature, Message, Public, Secret};
+use ethkey::{self, KeyPair, ExtendedKeyPair, sign, Address, Password, Signature, Message, Public, Secret};
+use ethereum_types::H256;
 use ethkey::crypto::ecdh::agree;
 use {json, Error};
-use account::Version;
+use account::{KdfConfig, Version};
 use crypto;
 use super::crypto::Crypto;
 use std::num::NonZeroU32;
@@ -39,17 +40,20 @@ pub struct SafeAccount {
 	pub name: String,
 	/// Account metadata
 	pub meta: String,
+	/// Chain code of hierarchical deterministic account.
+	pub chain_code: Option<Crypto>,
 }
 
 impl Into<json::KeyFile> for SafeAccount {
 	fn into(self) -> json::KeyFile {
+		let meta = self.meta_with_chain_code();
 		json::KeyFile {
 			id: From::from(self.id),
 			version: self.version.into(),
 			address: Some(self.address.into()),
 			crypto: self.crypto.into(),
 			name: Some(self.name.into()),
-			meta: Some(self.meta.into()),
+			meta: Some(meta),
 		}
 	}
 }
@@ -60,18 +64,43 @@ impl SafeAccount {
 		keypair: &KeyPair,
 		id: [u8; 16],
 		password: &Password,
-		iterations: NonZeroU32,
+		kdf: &KdfConfig,
 		name: String,
 		meta: String
 	) -> Result<Self, crypto::Error> {
 		Ok(SafeAccount {
 			id: id,
 			version: Version::V3,
-			crypto: Crypto::with_secret(keypair.secret(), password, iterations)?,
+			crypto: Crypto::with_secret(keypair.secret(), password, kdf)?,
 			address: keypair.address(),
 			filename: None,
 			name: name,
 			meta: meta,
+			chain_code: None,
+		})
+	}
+
+	/// Create a new hierarchical deterministic account.
+	/// Chain code of the key is kept along with the secret, so that child keys
+	/// can be derived according to BIP-32.
+	pub fn create_extended(
+		keypair: &ExtendedKeyPair,
+		id: [u8; 16],
+		password: &Password,
+		kdf: &KdfConfig,
+		name: String,
+		meta: String
+	) -> Result<Self, crypto::Error> {
+		let secret = keypair.secret();
+		Ok(SafeAccount {
+			id: id,
+			version: Version::V3,
+			crypto: Crypto::with_secret(secret.as_raw(), password, kdf)?,
+			address: ethkey::public_to_address(keypair.public().public()),
+			filename: None,
+			name: name,
+			meta: meta,
+			chain_code: Some(Crypto::with_plain(&*secret.chain_code(), password, kdf)?),
 		})
 	}
 
@@ -83,6 +112,8 @@ impl SafeAccount {
 	/// Providing password is required for `json::KeyFile`s with no address.
 	pub fn from_file(json: json::KeyFile, filename: Option<String>, password: &Option<Password>) -> Result<Self, Error> {
 		let crypto = Crypto::from(json.crypto);
+		let (meta, chain_code) = json::take_chain_code_from_json_meta(json.meta.unwrap_or("{}".to_owned()))
+			.map_err(|e| Error::InvalidKeyFile(format!("Invalid chain code: {:?}", e)))?;
 		let address = match (password, &json.address) {
 			(None, Some(json_address)) => json_address.into(),
 			(None, None) => Err(Error::Custom(
@@ -113,7 +144,8 @@ impl SafeAccount {
 			crypto,
 			filename,
 			name: json.name.unwrap_or(String::new()),
-			meta: json.meta.unwrap_or("{}".to_owned()),
+			meta,
+			chain_code: chain_code.map(Into::into),
 		})
 	}
 
@@ -139,11 +171,11 @@ impl SafeAccount {
 	pub fn into_vault_file(self, iterations: NonZeroU32, password: &Password) -> Result<json::VaultKeyFile, Error> {
 		let meta_plain = json::VaultKeyMeta {
 			address: self.address.into(),
+			meta: Some(self.meta_with_chain_code()),
 			name: Some(self.name),
-			meta: Some(self.meta),
 		};
 		let meta_plain = meta_plain.write().map_err(|e| Error::Custom(format!("{:?}", e)))?;
-		let meta_crypto = Crypto::with_plain(&meta_plain, password, iterations)?;
+		let meta_crypto = Crypto::with_plain(&meta_plain, password, &KdfConfig::Pbkdf2(iterations))?;
 
 		Ok(json::VaultKeyFile {
 			id: self.id.into(),
@@ -153,6 +185,15 @@ impl SafeAccount {
 		})
 	}
 
+	// chain code is stored in the meta field, so that key files remain standard V3 key files.
+	fn meta_with_chain_code(&self) -> String {
+		match self.chain_code {
+			Some(ref chain_code) => json::insert_chain_code_to_json_meta(&self.meta, chain_code.clone().into())
+				.expect("meta of accounts with chain code is always a JSON object; qed"),
+			None => self.meta.clone(),
+		}
+	}
+
 	/// Sign a message.
 	pub fn sign(&self, password: &Password, message: &Message) -> Result<Signature, Error> {
 		let secret = self.crypto.secret(password)?;
@@ -177,17 +218,38 @@ impl SafeAccount {
 		Ok(KeyPair::from_secret(secret)?.public().clone())
 	}
 
+	/// Extended key pair of the account, used for derivation.
+	/// Accounts without stored chain code derive it from the secret itself.
+	pub fn extended_key_pair(&self, password: &Password) -> Result<ExtendedKeyPair, Error> {
+		let secret = self.crypto.secret(password)?;
+		match self.chain_code {
+			Some(ref chain_code) => {
+				let chain_code = chain_code.decrypt(password)?;
+				if chain_code.len() != 32 {
+					return Err(Error::InvalidKeyFile("Invalid chain code".into()));
+				}
+				Ok(ExtendedKeyPair::with_secret(secret, H256::from_slice(&chain_code)))
+			},
+			None => Ok(ExtendedKeyPair::new(secret)),
+		}
+	}
+
 	/// Change account's password.
-	pub fn change_password(&self, old_password: &Password, new_password: &Password, iterations: NonZeroU32) -> Result<Self, Error> {
+	pub fn change_password(&self, old_password: &Password, new_password: &Password, kdf: &KdfConfig) -> Result<Self, Error> {
 		let secret = self.crypto.secret(old_password)?;
+		let chain_code = match self.chain_code {
+			Some(ref chain_code) => Some(Crypto::with_plain(&chain_code.decrypt(old_password)?, new_password, kdf)?),
+			None => None,
+		};
 		let result = SafeAccount {
 			id: self.id.clone(),
 			version: self.version.clone(),
-			crypto: Crypto::with_secret(&secret, new_password, iterations)?,
+			crypto: Crypto::with_secret(&secret, new_password, kdf)?,
 			address: self.address.clone(),
 			filename: self.filename.clone(),
 			name: self.name.clone(),
 			meta: self.meta.clone(),
+			chain_code: chain_code,
 		};
 		Ok(result)
 	}
@@ -200,11 +262,11 @@ impl SafeAccount {
 
 #[cfg(test)]
 mod tests {
-	use ethkey::{Generator, Random, verify_public, Message};
-	use super::{SafeAccount, NonZeroU32};
+	use ethkey::{Generator, Random, ExtendedKeyPair, verify_public, Message};
+	use super::{SafeAccount, KdfConfig, NonZeroU32};
 
 	lazy_static! {
-		static ref ITERATIONS: NonZeroU32 = NonZeroU32::new(10240).expect("10240 > 0; qed");
+		static ref KDF: KdfConfig = KdfConfig::Pbkdf2(NonZeroU32::new(10240).expect("10240 > 0; qed"));
 	}
 
 
@@ -213,7 +275,7 @@ mod tests {
 		let keypair = Random.generate().unwrap();
 		let password = "hello world".into();
 		let message = Message::default();
-		let account = SafeAccount::create(&keypair, [0u8; 16], &password, *ITERATIONS, "Test".to_owned(), "{}".to_owned());
+		let account = SafeAccount::create(&keypair, [0u8; 16], &password, &KDF, "Test".to_owned(), "{}".to_owned());
 		let signature = account.unwrap().sign(&password, &message).unwrap();
 		assert!(verify_public(keypair.public(), &signature, &message).unwrap());
 	}
@@ -224,11 +286,25 @@ mod tests {
 		let first_password = "hello world".into();
 		let sec_password = "this is sparta".into();
 		let message = Message::default();
-		let account = SafeAccount::create(&keypair, [0u8; 16], &first_password, *ITERATIONS, "Test".to_owned(), "{}".to_owned()).unwrap();
-		let new_account = account.change_password(&first_password, &sec_password, *ITERATIONS).unwrap();
+		let account = SafeAccount::create(&keypair, [0u8; 16], &first_password, &KDF, "Test".to_owned(), "{}".to_owned()).unwrap();
+		let new_account = account.change_password(&first_password, &sec_password, &KDF).unwrap();
 		assert!(account.sign(&first_password, &message).is_ok());
 		assert!(account.sign(&sec_password, &message).is_err());
 		assert!(new_account.sign(&first_password, &message).is_err());
 		assert!(new_account.sign(&sec_password, &message).is_ok());
 	}
+
+	#[test]
+	fn extended_key_pair_survives_password_change() {
+		let keypair = ExtendedKeyPair::with_seed(b"some seed of sufficient length!!").unwrap();
+		let first_password = "hello world".into();
+		let sec_password = "this is sparta".into();
+		let account = SafeAccount::create_extended(&keypair, [0u8; 16], &first_password, &KDF, "Test".to_owned(), "{}".to_owned()).unwrap();
+		let new_account = account.change_password(&first_password, &sec_password, &KDF).unwrap();
+
+		let restored = new_account.extended_key_pair(&sec_password).unwrap();
+		assert_eq!(restored.secret().as_raw(), keypair.secret().as_raw());
+		assert_eq!(restored.secret().chain_code(), keypair.secret().chain_code());
+		assert!(new_account.extended_key_pair(&first_password).is_err());
+	}
 }
@@ -18,6 +18,8 @@ use std::{fs, io};
 use std::io::Write;
 use std::path::{PathBuf, Path};
 use std::collections::HashMap;
+use std::time::SystemTime;
+use parking_lot::Mutex;
 use time;
 use {json, SafeAccount, Error};
 use json::Uuid;
@@ -34,6 +36,14 @@ const IGNORED_FILES: &'static [&'static str] = &[
 	"vault.json",
 ];
 
+/// Size and modification time of a key file.
+type FileStamp = (u64, SystemTime);
+
+fn file_stamp(path: &Path) -> Option<FileStamp> {
+	let metadata = fs::metadata(path).ok()?;
+	Some((metadata.len(), metadata.modified().ok()?))
+}
+
 /// Find a unique filename that does not exist using four-letter random suffix.
 pub fn find_unique_filename_using_random_suffix(parent_path: &Path, original_filename: &str) -> io::Result<String> {
 	let mut path = parent_path.join(original_filename);
@@ -116,6 +126,8 @@ pub trait KeyFileManager: Send + Sync {
 pub struct DiskDirectory<T> where T: KeyFileManager {
 	path: PathBuf,
 	key_manager: T,
+	/// Successfully parsed key files, re-read only when their stamp changes.
+	loaded: Mutex<HashMap<PathBuf, (FileStamp, SafeAccount)>>,
 }
 
 /// Keys file manager for root keys directory
@@ -146,6 +158,7 @@ impl<T> DiskDirectory<T> where T: KeyFileManager {
 		DiskDirectory {
 			path: path.as_ref().to_path_buf(),
 			key_manager: key_manager,
+			loaded: Mutex::new(HashMap::new()),
 		}
 	}
 
@@ -181,34 +194,63 @@ impl<T> DiskDirectory<T> where T: KeyFileManager {
 		Ok(hasher.finish())
 	}
 
-	fn last_modification_date(&self) -> Result<u64, Error> {
-		use std::time::{Duration, UNIX_EPOCH};
-		let duration = fs::metadata(&self.path)?.modified()?.duration_since(UNIX_EPOCH).unwrap_or(Duration::default());
-		let timestamp = duration.as_secs() ^ (duration.subsec_nanos() as u64);
-		Ok(timestamp)
+	/// Hash of names, sizes and modification times of all key files.
+	/// Changes whenever a key file is added, removed or modified.
+	pub fn files_stamp(&self) -> Result<u64, Error> {
+		use std::collections::hash_map::DefaultHasher;
+		use std::hash::{Hash, Hasher};
+
+		let mut hasher = DefaultHasher::new();
+		let mut files = self.files()?;
+		files.sort();
+		for file in files {
+			file.hash(&mut hasher);
+			file_stamp(&file).hash(&mut hasher);
+		}
+
+		Ok(hasher.finish())
 	}
 
 	/// all accounts found in keys directory
 	fn files_content(&self) -> Result<HashMap<PathBuf, SafeAccount>, Error> {
-		// it's not done using one iterator cause
-		// there is an issue with rustc and it takes tooo much time to compile
 		let paths = self.files()?;
-		Ok(paths
-			.into_iter()
-			.filter_map(|path| {
-				let filename = Some(path.file_name().and_then(|n| n.to_str()).expect("Keys have valid UTF8 names only.").to_owned());
-				fs::File::open(path.clone())
-					.map_err(Into::into)
-					.and_then(|file| self.key_manager.read(filename, file))
-					.map_err(|err| {
-						warn!("Invalid key file: {:?} ({})", path, err);
-						err
-					})
-					.map(|account| (path, account))
-					.ok()
-			})
-			.collect()
-		)
+		let mut loaded = self.loaded.lock();
+		let mut accounts = HashMap::new();
+		for path in paths {
+			let stamp = file_stamp(&path);
+			let cached = match (stamp, loaded.get(&path)) {
+				(Some(stamp), Some(&(cached_stamp, ref account))) if stamp == cached_stamp => Some(account.clone()),
+				_ => None,
+			};
+
+			let account = match cached {
+				Some(account) => account,
+				None => {
+					let filename = Some(path.file_name().and_then(|n| n.to_str()).expect("Keys have valid UTF8 names only.").to_owned());
+					let account = fs::File::open(path.clone())
+						.map_err(Into::into)
+						.and_then(|file| self.key_manager.read(filename, file));
+					match account {
+						Ok(account) => {
+							if let Some(stamp) = stamp {
+								loaded.insert(path.clone(), (stamp, account.clone()));
+							}
+							account
+						},
+						Err(err) => {
+							// not cached, so files still being written are picked up once complete
+							warn!("Invalid key file: {:?} ({})", path, err);
+							loaded.remove(&path);
+							continue;
+						},
+					}
+				},
+			};
+			accounts.insert(path, account);
+		}
+
+		loaded.retain(|path, _| accounts.contains_key(path));
+		Ok(accounts)
 	}
 
 	/// insert account with given filename. if the filename is a duplicate of any stored account and dedup is set to
@@ -241,6 +283,7 @@ impl<T> DiskDirectory<T> where T: KeyFileManager {
 			file.sync_all()?;
 		}
 
+		self.loaded.lock().remove(&keyfile_path);
 		Ok(account)
 	}
 
@@ -280,7 +323,10 @@ impl<T> KeyDirectory for DiskDirectory<T> where T: KeyFileManager {
 		// remove it
 		match to_remove {
 			None => Err(Error::InvalidAccount),
-			Some((path, _)) => fs::remove_file(path).map_err(From::from)
+			Some((path, _)) => {
+				self.loaded.lock().remove(&path);
+				fs::remove_file(path).map_err(From::from)
+			},
 		}
 	}
 
@@ -291,7 +337,7 @@ impl<T> KeyDirectory for DiskDirectory<T> where T: KeyFileManager {
 	}
 
 	fn unique_repr(&self) -> Result<u64, Error> {
-		self.last_modification_date()
+		self.files_stamp()
 	}
 }
 
@@ -306,6 +352,11 @@ impl<T> VaultKeyDirectoryProvider for DiskDirectory<T> where T: KeyFileManager {
 		Ok(Box::new(vault_dir))
 	}
 
+	fn rename(&self, name: &str, new_name: &str, key: VaultKey) -> Result<Box<VaultKeyDirectory>, Error> {
+		let vault_dir = VaultDiskDirectory::rename(&self.path, name, new_name, key)?;
+		Ok(Box::new(vault_dir))
+	}
+
 	fn list_vaults(&self) -> Result<Vec<String>, Error> {
 		Ok(fs::read_dir(&self.path)?
 			.filter_map(|e| e.ok().map(|e| e.path()))
@@ -358,7 +409,7 @@ mod test {
 	use std::{env, fs};
 	use std::num::NonZeroU32;
 	use super::{KeyDirectory, RootDiskDirectory, VaultKey};
-	use account::SafeAccount;
+	use account::{SafeAccount, KdfConfig};
 	use ethkey::{Random, Generator};
 	use self::tempdir::TempDir;
 
@@ -376,7 +427,7 @@ mod test {
 		let directory = RootDiskDirectory::create(dir.clone()).unwrap();
 
 		// when
-		let account = SafeAccount::create(&keypair, [0u8; 16], &password, *ITERATIONS, "Test".to_owned(), "{}".to_owned());
+		let account = SafeAccount::create(&keypair, [0u8; 16], &password, &KdfConfig::Pbkdf2(*ITERATIONS), "Test".to_owned(), "{}".to_owned());
 		let res = directory.insert(account.unwrap());
 
 		// then
@@ -397,7 +448,7 @@ mod test {
 		let directory = RootDiskDirectory::create(dir.clone()).unwrap();
 
 		// when
-		let account = SafeAccount::create(&keypair, [0u8; 16], &password, *ITERATIONS, "Test".to_owned(), "{}".to_owned()).unwrap();
+		let account = SafeAccount::create(&keypair, [0u8; 16], &password, &KdfConfig::Pbkdf2(*ITERATIONS), "Test".to_owned(), "{}".to_owned()).unwrap();
 		let filename = "test".to_string();
 		let dedup = true;
 
@@ -482,7 +533,7 @@ mod test {
 
 		let keypair = Random.generate().unwrap();
 		let password = "test pass".into();
-		let account = SafeAccount::create(&keypair, [0u8; 16], &password, *ITERATIONS, "Test".to_owned(), "{}".to_owned());
+		let account = SafeAccount::create(&keypair, [0u8; 16], &password, &KdfConfig::Pbkdf2(*ITERATIONS), "Test".to_owned(), "{}".to_owned());
 		directory.insert(account.unwrap()).expect("Account should be inserted ok");
 
 		let new_hash = directory.files_hash().expect("New files hash should be calculated ok");
@@ -23,6 +23,7 @@ use {SafeAccount, Error};
 
 mod disk;
 mod memory;
+mod multi;
 mod vault;
 
 /// `VaultKeyDirectory::set_key` error
@@ -69,6 +70,8 @@ pub trait VaultKeyDirectoryProvider {
 	fn create(&self, name: &str, key: VaultKey) -> Result<Box<VaultKeyDirectory>, Error>;
 	/// Open existing vault with given key
 	fn open(&self, name: &str, key: VaultKey) -> Result<Box<VaultKeyDirectory>, Error>;
+	/// Rename existing vault, checking that given key matches
+	fn rename(&self, name: &str, new_name: &str, key: VaultKey) -> Result<Box<VaultKeyDirectory>, Error>;
 	/// List all vaults
 	fn list_vaults(&self) -> Result<Vec<String>, Error>;
 	/// Get vault meta
@@ -93,7 +96,10 @@ pub trait VaultKeyDirectory: KeyDirectory {
 
 pub use self::disk::{RootDiskDirectory, DiskKeyFileManager, KeyFileManager};
 pub use self::memory::MemoryDirectory;
+pub use self::multi::MultiDirectory;
 pub use self::vault::VaultDiskDirectory;
+pub(crate) use self::disk::{replace_file_with_permissions_to_owner, find_unique_filename_using_random_suffix};
+pub(crate) use self::vault::VAULT_FILE_NAME;
 
 impl VaultKey {
 	/// Create new vault key
@@ -0,0 +1,160 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use parking_lot::RwLock;
+use ethkey::Address;
+
+use {SafeAccount, Error};
+use super::{KeyDirectory, VaultKeyDirectoryProvider};
+
+/// Writable keys directory combined with additional read-only directories.
+///
+/// Accounts from read-only directories can be listed and used for signing, but not modified or removed.
+/// New accounts always go to the primary directory, which also wins if an address is present in both.
+pub struct MultiDirectory {
+	primary: Box<KeyDirectory>,
+	read_only: Vec<Box<KeyDirectory>>,
+	/// Addresses of accounts loaded from read-only directories.
+	read_only_accounts: RwLock<HashSet<Address>>,
+}
+
+impl MultiDirectory {
+	/// Create new directory from the primary one and read-only directories.
+	pub fn new(primary: Box<KeyDirectory>, read_only: Vec<Box<KeyDirectory>>) -> Self {
+		MultiDirectory {
+			primary,
+			read_only,
+			read_only_accounts: RwLock::new(HashSet::new()),
+		}
+	}
+
+	fn check_writable(&self, account: &SafeAccount) -> Result<(), Error> {
+		match self.read_only_accounts.read().contains(&account.address) {
+			true => Err(Error::ReadOnlyStore),
+			false => Ok(()),
+		}
+	}
+}
+
+impl KeyDirectory for MultiDirectory {
+	fn load(&self) -> Result<Vec<SafeAccount>, Error> {
+		let mut accounts = self.primary.load()?;
+		let primary: HashSet<Address> = accounts.iter().map(|account| account.address).collect();
+		let mut read_only = HashSet::new();
+
+		for dir in &self.read_only {
+			for account in dir.load()? {
+				if primary.contains(&account.address) {
+					warn!(target: "ethstore", "Account {:?} from read-only directory {:?} is shadowed by the primary keys directory", account.address, dir.path());
+					continue;
+				}
+				read_only.insert(account.address);
+				accounts.push(account);
+			}
+		}
+
+		*self.read_only_accounts.write() = read_only;
+		Ok(accounts)
+	}
+
+	fn update(&self, account: SafeAccount) -> Result<SafeAccount, Error> {
+		self.check_writable(&account)?;
+		self.primary.update(account)
+	}
+
+	fn insert(&self, account: SafeAccount) -> Result<SafeAccount, Error> {
+		self.primary.insert(account)
+	}
+
+	fn remove(&self, account: &SafeAccount) -> Result<(), Error> {
+		self.check_writable(account)?;
+		self.primary.remove(account)
+	}
+
+	fn path(&self) -> Option<&PathBuf> {
+		self.primary.path()
+	}
+
+	fn as_vault_provider(&self) -> Option<&VaultKeyDirectoryProvider> {
+		self.primary.as_vault_provider()
+	}
+
+	fn unique_repr(&self) -> Result<u64, Error> {
+		let mut repr = self.primary.unique_repr()?;
+		for dir in &self.read_only {
+			repr = repr.rotate_left(1) ^ dir.unique_repr()?;
+		}
+		Ok(repr)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::num::NonZeroU32;
+	use ethkey::{Generator, Random};
+	use accounts_dir::{KeyDirectory, MemoryDirectory};
+	use {SafeAccount, KdfConfig, Error};
+	use super::MultiDirectory;
+
+	fn account() -> SafeAccount {
+		let keypair = Random.generate().unwrap();
+		let kdf = KdfConfig::Pbkdf2(NonZeroU32::new(1024).expect("1024 > 0; qed"));
+		SafeAccount::create(&keypair, [0u8; 16], &"test".into(), &kdf, "".into(), "{}".into()).unwrap()
+	}
+
+	#[test]
+	fn should_list_accounts_from_all_directories() {
+		// given
+		let primary = MemoryDirectory::default();
+		let read_only = MemoryDirectory::default();
+		let shared = account();
+		let both = account();
+		primary.insert(both.clone()).unwrap();
+		read_only.insert(shared.clone()).unwrap();
+		read_only.insert(SafeAccount { name: "read-only copy".into(), ..both.clone() }).unwrap();
+
+		// when
+		let dir = MultiDirectory::new(Box::new(primary), vec![Box::new(read_only)]);
+		let accounts = dir.load().unwrap();
+
+		// then
+		assert_eq!(accounts.len(), 2);
+		assert!(accounts.contains(&shared));
+		assert!(accounts.contains(&both));
+	}
+
+	#[test]
+	fn should_refuse_to_modify_read_only_accounts() {
+		// given
+		let read_only = MemoryDirectory::default();
+		let shared = account();
+		read_only.insert(shared.clone()).unwrap();
+		let dir = MultiDirectory::new(Box::new(MemoryDirectory::default()), vec![Box::new(read_only)]);
+		dir.load().unwrap();
+
+		// when
+		let new = dir.insert(account()).unwrap();
+
+		// then
+		assert_matches!(dir.update(shared.clone()), Err(Error::ReadOnlyStore));
+		assert_matches!(dir.remove(&shared), Err(Error::ReadOnlyStore));
+		assert_eq!(dir.load().unwrap().len(), 2);
+		dir.remove(&new).unwrap();
+		assert_eq!(dir.load().unwrap(), vec![shared]);
+	}
+}
@@ -19,7 +19,7 @@ use std::path::{PathBuf, Path};
 use parking_lot::Mutex;
 use {json, SafeAccount, Error};
 use crypto::Keccak256;
-use super::super::account::Crypto;
+use super::super::account::{Crypto, KdfConfig};
 use super::{KeyDirectory, VaultKeyDirectory, VaultKey, SetKeyError};
 use super::disk::{self, DiskDirectory, KeyFileManager};
 
@@ -72,6 +72,32 @@ impl VaultDiskDirectory {
 		Ok(DiskDirectory::new(vault_dir_path, VaultKeyFileManager::new(name, key, &meta)))
 	}
 
+	/// Rename existing vault directory, re-encrypting vault file with given key
+	pub fn rename<P>(root: P, name: &str, new_name: &str, key: VaultKey) -> Result<Self, Error> where P: AsRef<Path> {
+		// check that vault directory exists && new vault directory does not exist
+		let vault_dir_path = make_vault_dir_path(&root, name, true)?;
+		let new_vault_dir_path = make_vault_dir_path(&root, new_name, true)?;
+		if !vault_dir_path.is_dir() {
+			return Err(Error::VaultNotFound);
+		}
+		if new_vault_dir_path.exists() {
+			return Err(Error::CreationFailed);
+		}
+
+		// check that passed key matches vault file
+		let meta = read_vault_file(&vault_dir_path, Some(&key))?;
+
+		// directory is moved atomically => vault is either renamed or left untouched
+		fs::rename(&vault_dir_path, &new_vault_dir_path)?;
+		if let Err(err) = create_vault_file(&new_vault_dir_path, &key, &meta) {
+			// vault file is rewritten via temporary file => original file is still in place
+			let _ = fs::rename(&new_vault_dir_path, &vault_dir_path); // can't do anything with this
+			return Err(err);
+		}
+
+		Ok(DiskDirectory::new(new_vault_dir_path, VaultKeyFileManager::new(new_name, key, &meta)))
+	}
+
 	/// Read vault meta without actually opening the vault
 	pub fn meta_at<P>(root: P, name: &str) -> Result<String, Error> where P: AsRef<Path> {
 		// check that vault directory exists
@@ -235,7 +261,7 @@ fn check_vault_name(name: &str) -> bool {
 /// Vault can be empty, but still must be pluggable => we store vault password in separate file
 fn create_vault_file<P>(vault_dir_path: P, key: &VaultKey, meta: &str) -> Result<(), Error> where P: AsRef<Path> {
 	let password_hash = key.password.as_bytes().keccak256();
-	let crypto = Crypto::with_plain(&password_hash, &key.password, key.iterations)?;
+	let crypto = Crypto::with_plain(&password_hash, &key.password, &KdfConfig::Pbkdf2(key.iterations))?;
 
 	let vault_file_path = vault_dir_path.as_ref().join(VAULT_FILE_NAME);
 	let temp_vault_file_name = disk::find_unique_filename_using_random_suffix(vault_dir_path.as_ref(), &VAULT_TEMP_FILE_NAME)?;
@@ -446,4 +472,39 @@ mod test {
 		// then
 		assert!(vault.is_err());
 	}
+
+	#[test]
+	fn vault_directory_can_be_renamed() {
+		// given
+		let temp_path = TempDir::new("").unwrap();
+		let key = VaultKey::new(&"password".into(), *ITERATIONS);
+		let dir: PathBuf = temp_path.path().into();
+		VaultDiskDirectory::create(&dir, "vault", key.clone()).unwrap();
+
+		// when
+		let vault = VaultDiskDirectory::rename(&dir, "vault", "renamed", key.clone());
+
+		// then
+		assert!(vault.is_ok());
+		assert!(!dir.join("vault").exists());
+		assert!(VaultDiskDirectory::at(&dir, "renamed", key).is_ok());
+	}
+
+	#[test]
+	fn vault_directory_cannot_be_renamed_with_wrong_password() {
+		// given
+		let temp_path = TempDir::new("").unwrap();
+		let key = VaultKey::new(&"password".into(), *ITERATIONS);
+		let wrong_key = VaultKey::new(&"password1".into(), *ITERATIONS);
+		let dir: PathBuf = temp_path.path().into();
+		VaultDiskDirectory::create(&dir, "vault", key.clone()).unwrap();
+
+		// when
+		let vault = VaultDiskDirectory::rename(&dir, "vault", "renamed", wrong_key);
+
+		// then
+		assert!(vault.is_err());
+		assert!(!dir.join("renamed").exists());
+		assert!(VaultDiskDirectory::at(&dir, "vault", key).is_ok());
+	}
 }
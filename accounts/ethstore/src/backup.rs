@@ -0,0 +1,375 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Encrypted backups of the whole keys directory.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use ethkey::{Address, Password};
+use serde_json;
+
+use accounts_dir::{replace_file_with_permissions_to_owner, find_unique_filename_using_random_suffix};
+use {json, Crypto, KdfConfig, Error};
+
+/// Version of the backup archive format.
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Encrypted archive as written to disk.
+#[derive(Serialize, Deserialize)]
+struct Archive {
+	version: u32,
+	crypto: json::Crypto,
+}
+
+/// Decrypted archive payload.
+#[derive(Serialize, Deserialize)]
+struct Contents {
+	version: u32,
+	/// File contents by path relative to the keys directory.
+	files: BTreeMap<String, String>,
+}
+
+/// Creates an encrypted archive of all key files, vaults and metadata in `path`.
+pub fn backup(path: &Path, password: &Password, kdf: &KdfConfig) -> Result<Vec<u8>, Error> {
+	let mut files = BTreeMap::new();
+	for relative in list_files(path)? {
+		let contents = fs::read_to_string(path.join(&relative))
+			.map_err(|e| Error::Custom(format!("Could not read {}: {}", relative, e)))?;
+		files.insert(relative, contents);
+	}
+
+	let contents = Contents { version: BACKUP_VERSION, files };
+	let plain = serde_json::to_vec(&contents).map_err(|e| Error::Custom(format!("{}", e)))?;
+	let archive = Archive {
+		version: BACKUP_VERSION,
+		crypto: Crypto::with_plain(&plain, password, kdf)?.into(),
+	};
+
+	serde_json::to_vec(&archive).map_err(|e| Error::Custom(format!("{}", e)))
+}
+
+/// Restores an archive created with `backup` into `path`.
+/// Fails if any of the archived accounts or vaults already exists, unless `force` is set.
+/// With `force` an existing vault is replaced as a whole by the archived one.
+/// Metadata files already present in `path` are only replaced when `force` is set.
+/// Returns addresses of the restored root accounts.
+pub fn restore(path: &Path, archive: &[u8], password: &Password, force: bool) -> Result<Vec<Address>, Error> {
+	let archive: Archive = serde_json::from_slice(archive)
+		.map_err(|e| Error::Custom(format!("Invalid backup archive: {}", e)))?;
+	if archive.version > BACKUP_VERSION {
+		return Err(Error::Custom(format!("Unsupported backup version {}", archive.version)));
+	}
+
+	let plain = Crypto::from(archive.crypto).decrypt(password)?;
+	let contents: Contents = serde_json::from_slice(&plain)
+		.map_err(|_| Error::Custom("Backup archive is corrupted".into()))?;
+	if contents.version != archive.version {
+		return Err(Error::Custom("Backup archive is corrupted".into()));
+	}
+
+	// root files by name and vault files by vault name and file name
+	let mut root_files = BTreeMap::new();
+	let mut vaults = BTreeMap::new();
+	for (relative, data) in &contents.files {
+		validate_path(relative)?;
+		let mut components = relative.splitn(2, '/');
+		match (components.next(), components.next()) {
+			(Some(vault), Some(file)) => {
+				vaults.entry(vault).or_insert_with(BTreeMap::new).insert(file, data);
+			},
+			_ => {
+				root_files.insert(relative.as_str(), data);
+			},
+		}
+	}
+
+	// Vault key files keep their address encrypted with the vault key,
+	// so vaults are considered the same if their names match.
+	let existing = existing_accounts(path)?;
+	let mut conflicting_files = Vec::new();
+	let mut restored = Vec::new();
+	for (relative, data) in &root_files {
+		if let Some(address) = root_key_address(relative, data) {
+			if let Some(existing_path) = existing.get(&address) {
+				conflicting_files.push(existing_path.clone());
+			}
+			restored.push(address);
+		}
+	}
+	let conflicting_vaults = vaults.keys().filter(|vault| path.join(vault).exists()).count();
+
+	if !conflicting_files.is_empty() || conflicting_vaults > 0 {
+		if !force {
+			return Err(Error::Custom(format!("{} account(s) and {} vault(s) from the backup already exist. Use --force to overwrite them.",
+				conflicting_files.len(), conflicting_vaults)));
+		}
+		for file in conflicting_files {
+			fs::remove_file(file)?;
+		}
+	}
+
+	fs::create_dir_all(path)?;
+	for (relative, data) in &root_files {
+		let mut target = path.join(relative);
+		if target.exists() {
+			if root_key_address(relative, data).is_some() {
+				// a different account happens to use the same file name
+				let filename = find_unique_filename_using_random_suffix(path, relative)?;
+				target = path.join(filename);
+			} else if !force {
+				debug!(target: "ethstore", "Keeping existing {}", relative);
+				continue;
+			}
+		}
+
+		write_file(&target, data)?;
+	}
+
+	for (vault, files) in &vaults {
+		restore_vault(path, vault, files)?;
+	}
+
+	Ok(restored)
+}
+
+/// Writes the vault into a staging directory first and moves it into place once it's complete,
+/// replacing the existing vault with the same name.
+fn restore_vault(path: &Path, vault: &str, files: &BTreeMap<&str, &String>) -> Result<(), Error> {
+	let target = path.join(vault);
+	let staging = path.join(format!(".{}.restoring", vault));
+	let replaced = path.join(format!(".{}.replaced", vault));
+	for dir in &[&staging, &replaced] {
+		if dir.exists() {
+			fs::remove_dir_all(dir)?;
+		}
+	}
+
+	fs::create_dir(&staging)?;
+	for (file, data) in files {
+		write_file(&staging.join(file), data)?;
+	}
+
+	if !target.exists() {
+		fs::rename(&staging, &target)?;
+		return Ok(());
+	}
+
+	fs::rename(&target, &replaced)?;
+	if let Err(e) = fs::rename(&staging, &target) {
+		// put the existing vault back
+		fs::rename(&replaced, &target)?;
+		return Err(e.into());
+	}
+	fs::remove_dir_all(&replaced)?;
+	Ok(())
+}
+
+fn write_file(target: &Path, data: &str) -> Result<(), Error> {
+	let mut file = replace_file_with_permissions_to_owner(target)?;
+	file.write_all(data.as_bytes())?;
+	file.flush()?;
+	Ok(())
+}
+
+/// Lists files in the keys directory and its vault subdirectories.
+fn list_files(path: &Path) -> Result<Vec<String>, Error> {
+	let mut files = Vec::new();
+	for entry in fs::read_dir(path)? {
+		let entry = entry?;
+		let name = match entry.file_name().into_string() {
+			Ok(name) => name,
+			Err(_) => continue,
+		};
+		if name.starts_with('.') || name.to_lowercase() == "thumbs.db" {
+			continue;
+		}
+
+		if entry.file_type()?.is_dir() {
+			for vault_entry in fs::read_dir(entry.path())? {
+				let vault_entry = vault_entry?;
+				match vault_entry.file_name().into_string() {
+					Ok(ref file) if !file.starts_with('.') && vault_entry.file_type()?.is_file() =>
+						files.push(format!("{}/{}", name, file)),
+					_ => {},
+				}
+			}
+		} else if entry.file_type()?.is_file() {
+			files.push(name);
+		}
+	}
+	Ok(files)
+}
+
+/// Only plain `file` or `vault/file` paths are allowed in the archive.
+/// Hidden files are never archived, so these are rejected too.
+fn validate_path(relative: &str) -> Result<(), Error> {
+	let components: Vec<_> = Path::new(relative).components().collect();
+	let valid = !components.is_empty() && components.len() <= 2 && components.iter().all(|c| match *c {
+		Component::Normal(name) => !name.to_string_lossy().starts_with('.'),
+		_ => false,
+	});
+
+	match valid {
+		true => Ok(()),
+		false => Err(Error::Custom(format!("Invalid path in backup archive: {}", relative))),
+	}
+}
+
+fn root_key_address(relative: &str, data: &str) -> Option<Address> {
+	if relative.contains('/') {
+		return None;
+	}
+	json::KeyFile::load(data.as_bytes()).ok().and_then(|key| key.address.as_ref().map(Into::into))
+}
+
+/// Addresses of the root accounts in `path` mapped to their key files.
+fn existing_accounts(path: &Path) -> Result<HashMap<Address, PathBuf>, Error> {
+	if !path.exists() {
+		return Ok(HashMap::new());
+	}
+
+	let mut accounts = HashMap::new();
+	for relative in list_files(path)? {
+		let file = path.join(&relative);
+		if let Ok(data) = fs::read_to_string(&file) {
+			if let Some(address) = root_key_address(&relative, &data) {
+				accounts.insert(address, file);
+			}
+		}
+	}
+	Ok(accounts)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::num::NonZeroU32;
+	use tempdir::TempDir;
+	use ethkey::{Generator, Random};
+	use accounts_dir::RootDiskDirectory;
+	use secret_store::{SimpleSecretStore, SecretStore, SecretVaultRef};
+	use {EthStore, KdfConfig, Error};
+	use super::{backup, restore};
+
+	fn kdf() -> KdfConfig {
+		KdfConfig::Pbkdf2(NonZeroU32::new(1024).unwrap())
+	}
+
+	fn store(path: &::std::path::Path) -> EthStore {
+		EthStore::open(Box::new(RootDiskDirectory::create(path).unwrap())).unwrap()
+	}
+
+	#[test]
+	fn should_backup_and_restore_accounts_and_vaults() {
+		// given
+		let source = TempDir::new("").unwrap();
+		let store1 = store(source.path());
+		let root = store1.insert_account(SecretVaultRef::Root, Random.generate().unwrap().secret().clone(), &"pass".into()).unwrap();
+		store1.create_vault("vault", &"vault_pass".into()).unwrap();
+		let vault = store1.insert_account(SecretVaultRef::Vault("vault".into()), Random.generate().unwrap().secret().clone(), &"pass".into()).unwrap();
+		fs::write(source.path().join("address_book.json"), "{}").unwrap();
+
+		// when
+		let archive = backup(source.path(), &"backup".into(), &kdf()).unwrap();
+		let target = TempDir::new("").unwrap();
+		let restored = restore(target.path(), &archive, &"backup".into(), false).unwrap();
+
+		// then
+		assert_eq!(restored, vec![root.address]);
+		assert!(target.path().join("address_book.json").exists());
+		let store2 = store(target.path());
+		assert_eq!(store2.accounts().unwrap(), vec![root.clone()]);
+		store2.open_vault("vault", &"vault_pass".into()).unwrap();
+		assert!(store2.test_password(&vault, &"pass".into()).unwrap());
+		assert!(store2.test_password(&root, &"pass".into()).unwrap());
+	}
+
+	#[test]
+	fn should_refuse_to_overwrite_existing_accounts() {
+		// given
+		let source = TempDir::new("").unwrap();
+		let account = store(source.path()).insert_account(SecretVaultRef::Root, Random.generate().unwrap().secret().clone(), &"pass".into()).unwrap();
+		let archive = backup(source.path(), &"backup".into(), &kdf()).unwrap();
+
+		// when
+		let result = restore(source.path(), &archive, &"backup".into(), false);
+
+		// then
+		assert_matches!(result, Err(Error::Custom(_)));
+		assert_eq!(restore(source.path(), &archive, &"backup".into(), true).unwrap(), vec![account.address]);
+		assert_eq!(store(source.path()).accounts().unwrap(), vec![account]);
+	}
+
+	#[test]
+	fn should_detect_tampering() {
+		// given
+		let source = TempDir::new("").unwrap();
+		store(source.path()).insert_account(SecretVaultRef::Root, Random.generate().unwrap().secret().clone(), &"pass".into()).unwrap();
+		let archive = backup(source.path(), &"backup".into(), &kdf()).unwrap();
+		let target = TempDir::new("").unwrap();
+
+		// when
+		let mut archive: ::serde_json::Value = ::serde_json::from_slice(&archive).unwrap();
+		let ciphertext = archive["crypto"]["ciphertext"].as_str().unwrap().to_owned();
+		let flipped = if ciphertext.starts_with('0') { "1" } else { "0" };
+		archive["crypto"]["ciphertext"] = format!("{}{}", flipped, &ciphertext[1..]).into();
+
+		// then
+		assert_matches!(restore(target.path(), &::serde_json::to_vec(&archive).unwrap(), &"backup".into(), false), Err(Error::InvalidPassword));
+		archive["version"] = 2.into();
+		assert_matches!(restore(target.path(), &::serde_json::to_vec(&archive).unwrap(), &"backup".into(), false), Err(Error::Custom(_)));
+		assert_eq!(fs::read_dir(target.path()).unwrap().count(), 0);
+	}
+
+	#[test]
+	fn should_replace_existing_vault_only_with_force() {
+		// given
+		let source = TempDir::new("").unwrap();
+		let store1 = store(source.path());
+		store1.create_vault("vault", &"vault_pass".into()).unwrap();
+		let archived = store1.insert_account(SecretVaultRef::Vault("vault".into()), Random.generate().unwrap().secret().clone(), &"pass".into()).unwrap();
+		let archive = backup(source.path(), &"backup".into(), &kdf()).unwrap();
+
+		let target = TempDir::new("").unwrap();
+		let store2 = store(target.path());
+		store2.create_vault("vault", &"other_pass".into()).unwrap();
+		let existing = store2.insert_account(SecretVaultRef::Vault("vault".into()), Random.generate().unwrap().secret().clone(), &"pass".into()).unwrap();
+		drop(store2);
+
+		// when
+		let result = restore(target.path(), &archive, &"backup".into(), false);
+
+		// then
+		assert_matches!(result, Err(Error::Custom(_)));
+		let store2 = store(target.path());
+		store2.open_vault("vault", &"other_pass".into()).unwrap();
+		assert!(store2.test_password(&existing, &"pass".into()).unwrap());
+		drop(store2);
+
+		// when
+		restore(target.path(), &archive, &"backup".into(), true).unwrap();
+
+		// then
+		let store2 = store(target.path());
+		assert_matches!(store2.open_vault("vault", &"other_pass".into()), Err(_));
+		store2.open_vault("vault", &"vault_pass".into()).unwrap();
+		assert!(store2.test_password(&archived, &"pass".into()).unwrap());
+		assert_eq!(fs::read_dir(target.path()).unwrap().count(), 1);
+	}
+}
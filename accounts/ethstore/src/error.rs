@@ -16,7 +16,7 @@
 
 use std::fmt;
 use std::io::Error as IoError;
-use ethkey::{self, Error as EthKeyError};
+use ethkey::{self, Address, Error as EthKeyError};
 use crypto::{self, Error as EthCryptoError};
 use ethkey::DerivationError;
 
@@ -47,6 +47,12 @@ pub enum Error {
 	VaultNotFound,
 	/// Account creation failed.
 	CreationFailed,
+	/// Invalid key derivation function parameters.
+	InvalidKdfParams(String),
+	/// Account with given address already exists.
+	AlreadyExists(Address),
+	/// Account belongs to a read-only keys directory.
+	ReadOnlyStore,
 	/// `EthKey` error
 	EthKey(EthKeyError),
 	/// `ethkey::crypto::Error`
@@ -74,6 +80,9 @@ impl fmt::Display for Error {
 			Error::InvalidVaultName => "Invalid vault name".into(),
 			Error::VaultNotFound => "Vault not found".into(),
 			Error::CreationFailed => "Account creation failed".into(),
+			Error::InvalidKdfParams(ref reason) => format!("Invalid key derivation parameters: {}", reason),
+			Error::AlreadyExists(ref address) => format!("Account {:?} already exists", address),
+			Error::ReadOnlyStore => "Account is in a read-only store".into(),
 			Error::EthKey(ref err) => err.to_string(),
 			Error::EthKeyCrypto(ref err) => err.to_string(),
 			Error::EthCrypto(ref err) => err.to_string(),
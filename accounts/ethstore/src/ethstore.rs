@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::num::NonZeroU32;
 use std::mem;
 use std::path::PathBuf;
@@ -24,9 +24,10 @@ use std::time::{Instant, Duration};
 use random::Random;
 use ethkey::{self, Signature, Password, Address, Message, Secret, Public, KeyPair, ExtendedKeyPair};
 use accounts_dir::{KeyDirectory, VaultKeyDirectory, VaultKey, SetKeyError};
-use account::SafeAccount;
+use account::{SafeAccount, KdfConfig};
 use presale::PresaleWallet;
 use json::{self, Uuid, OpaqueKeyFile};
+use serde_json;
 use {import, Error, SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation, OpaqueSecret};
 
 
@@ -48,8 +49,13 @@ impl EthStore {
 
 	/// Open a new account store with given key directory backend and custom number of iterations.
 	pub fn open_with_iterations(directory: Box<KeyDirectory>, iterations: NonZeroU32) -> Result<Self, Error> {
+		Self::open_with_kdf(directory, KdfConfig::Pbkdf2(iterations))
+	}
+
+	/// Open a new account store with given key directory backend and custom key derivation function for new keys.
+	pub fn open_with_kdf(directory: Box<KeyDirectory>, kdf: KdfConfig) -> Result<Self, Error> {
 		Ok(EthStore {
-			store: EthMultiStore::open_with_iterations(directory, iterations)?,
+			store: EthMultiStore::open_with_kdf(directory, kdf)?,
 		})
 	}
 
@@ -67,6 +73,27 @@ impl EthStore {
 		let mut accounts = self.store.get_accounts(account)?.into_iter();
 		accounts.next().ok_or(Error::InvalidAccount)
 	}
+
+	fn presale_to_account(&self, json: &[u8], password: &Password) -> Result<SafeAccount, Error> {
+		let json_wallet = json::PresaleWallet::load(json).map_err(|_| Error::InvalidKeyFile("Invalid JSON format".to_owned()))?;
+		let wallet = PresaleWallet::from(json_wallet);
+		let keypair = wallet.decrypt(password).map_err(|_| Error::InvalidPassword)?;
+		let id: [u8; 16] = Random::random();
+		Ok(SafeAccount::create(&keypair, id, password, &self.store.kdf, "".to_owned(), "{}".to_owned())?)
+	}
+
+	fn wallet_to_account(&self, json: &[u8], password: &Password, gen_id: bool) -> Result<SafeAccount, Error> {
+		let json_keyfile = json::KeyFile::load(json).map_err(|_| Error::InvalidKeyFile("Invalid JSON format".to_owned()))?;
+		let mut safe_account = SafeAccount::from_file(json_keyfile, None, &None)?;
+
+		if gen_id {
+			safe_account.id = Random::random();
+		}
+
+		let secret = safe_account.crypto.secret(password).map_err(|_| Error::InvalidPassword)?;
+		safe_account.address = KeyPair::from_secret(secret)?.address();
+		Ok(safe_account)
+	}
 }
 
 impl SimpleSecretStore for EthStore {
@@ -80,6 +107,10 @@ impl SimpleSecretStore for EthStore {
 		self.store.insert_derived(vault, account_ref, password, derivation)
 	}
 
+	fn insert_hd_seed(&self, vault: SecretVaultRef, seed: &[u8], password: &Password) -> Result<StoreAccountRef, Error> {
+		self.store.insert_hd_seed(vault, seed, password)
+	}
+
 	fn generate_derived(&self, account_ref: &StoreAccountRef, password: &Password, derivation: Derivation) -> Result<Address, Error> {
 		self.store.generate_derived(account_ref, password, derivation)
 	}
@@ -96,8 +127,8 @@ impl SimpleSecretStore for EthStore {
 		self.store.change_password(account, old_password, new_password)
 	}
 
-	fn export_account(&self, account: &StoreAccountRef, password: &Password) -> Result<OpaqueKeyFile, Error> {
-		self.store.export_account(account, password)
+	fn export_account(&self, account: &StoreAccountRef, password: &Password, new_password: Option<&Password>) -> Result<OpaqueKeyFile, Error> {
+		self.store.export_account(account, password, new_password)
 	}
 
 	fn remove_account(&self, account: &StoreAccountRef, password: &Password) -> Result<(), Error> {
@@ -147,6 +178,10 @@ impl SimpleSecretStore for EthStore {
 		self.store.change_vault_password(name, new_password)
 	}
 
+	fn rename_vault(&self, name: &str, new_name: &str) -> Result<(), Error> {
+		self.store.rename_vault(name, new_name)
+	}
+
 	fn change_account_vault(&self, vault: SecretVaultRef, account: StoreAccountRef) -> Result<StoreAccountRef, Error> {
 		self.store.change_account_vault(vault, account)
 	}
@@ -172,19 +207,25 @@ impl SecretStore for EthStore {
 		self.insert_account(vault, keypair.secret().clone(), password)
 	}
 
-	fn import_wallet(&self, vault: SecretVaultRef, json: &[u8], password: &Password, gen_id: bool) -> Result<StoreAccountRef, Error> {
-		let json_keyfile = json::KeyFile::load(json).map_err(|_| Error::InvalidKeyFile("Invalid JSON format".to_owned()))?;
-		let mut safe_account = SafeAccount::from_file(json_keyfile, None, &None)?;
-
-		if gen_id {
-			safe_account.id = Random::random();
-		}
+	fn import_presales(&self, vault: SecretVaultRef, wallets: &[(&[u8], &Password)]) -> Vec<Result<StoreAccountRef, Error>> {
+		let accounts = wallets.iter()
+			.map(|&(json, password)| self.presale_to_account(json, password))
+			.collect();
+		self.store.import_batch(vault, accounts)
+	}
 
-		let secret = safe_account.crypto.secret(password).map_err(|_| Error::InvalidPassword)?;
-		safe_account.address = KeyPair::from_secret(secret)?.address();
+	fn import_wallet(&self, vault: SecretVaultRef, json: &[u8], password: &Password, gen_id: bool) -> Result<StoreAccountRef, Error> {
+		let safe_account = self.wallet_to_account(json, password, gen_id)?;
 		self.store.import(vault, safe_account)
 	}
 
+	fn import_wallets(&self, vault: SecretVaultRef, wallets: &[(&[u8], &Password)], gen_id: bool) -> Vec<Result<StoreAccountRef, Error>> {
+		let accounts = wallets.iter()
+			.map(|&(json, password)| self.wallet_to_account(json, password, gen_id))
+			.collect();
+		self.store.import_batch(vault, accounts)
+	}
+
 	fn test_password(&self, account: &StoreAccountRef, password: &Password) -> Result<bool, Error> {
 		let account = self.get(account)?;
 		Ok(account.check_password(password))
@@ -228,6 +269,10 @@ impl SecretStore for EthStore {
 
 	fn set_meta(&self, account_ref: &StoreAccountRef, meta: String) -> Result<(), Error> {
 		let old = self.get(account_ref)?;
+		// chain code of hierarchical deterministic accounts is stored in meta.
+		if old.chain_code.is_some() && !meta.is_empty() && !serde_json::from_str::<serde_json::Value>(&meta).map_or(false, |m| m.is_object()) {
+			return Err(Error::Custom("Meta of hierarchical deterministic accounts is expected to be a serialized JSON object".into()));
+		}
 		let mut safe_account = old.clone();
 		safe_account.meta = meta;
 
@@ -239,6 +284,10 @@ impl SecretStore for EthStore {
 		self.store.dir.path().cloned().unwrap_or_else(PathBuf::new)
 	}
 
+	fn refresh(&self) -> Result<bool, Error> {
+		self.store.reload_if_changed()
+	}
+
 	fn list_geth_accounts(&self, testnet: bool) -> Vec<Address> {
 		import::read_geth_accounts(testnet)
 	}
@@ -263,6 +312,7 @@ impl SecretStore for EthStore {
 /// Similar to `EthStore` but may store many accounts (with different passwords) for the same `Address`
 pub struct EthMultiStore {
 	dir: Box<KeyDirectory>,
+	kdf: KdfConfig,
 	iterations: NonZeroU32,
 	// order lock: cache, then vaults
 	cache: RwLock<BTreeMap<StoreAccountRef, Vec<SafeAccount>>>,
@@ -284,9 +334,21 @@ impl EthMultiStore {
 
 	/// Open new multi-accounts store with given key directory backend and custom number of iterations for new keys.
 	pub fn open_with_iterations(directory: Box<KeyDirectory>, iterations: NonZeroU32) -> Result<Self, Error> {
+		Self::open_with_kdf(directory, KdfConfig::Pbkdf2(iterations))
+	}
+
+	/// Open new multi-accounts store with given key directory backend and custom key derivation function for new keys.
+	///
+	/// Vault keys are always derived with PBKDF2, using the configured number of iterations if PBKDF2 is used for accounts too.
+	pub fn open_with_kdf(directory: Box<KeyDirectory>, kdf: KdfConfig) -> Result<Self, Error> {
+		let iterations = match kdf {
+			KdfConfig::Pbkdf2(iterations) => iterations,
+			KdfConfig::Scrypt { .. } => *KEY_ITERATIONS,
+		};
 		let store = EthMultiStore {
 			dir: directory,
 			vaults: Mutex::new(HashMap::new()),
+			kdf: kdf,
 			iterations: iterations,
 			cache: Default::default(),
 			timestamp: Mutex::new(Timestamp {
@@ -310,19 +372,30 @@ impl EthMultiStore {
 		self.timestamp.lock().refresh_time = time;
 	}
 
-	fn reload_if_changed(&self) -> Result<(), Error> {
+	/// Reloads accounts if refresh time has passed and any key file (including open vaults) changed.
+	/// Returns `true` if accounts were reloaded.
+	fn reload_if_changed(&self) -> Result<bool, Error> {
 		let mut last_timestamp = self.timestamp.lock();
 		let now = Instant::now();
 		if now - last_timestamp.last_checked > last_timestamp.refresh_time {
-			let dir_hash = Some(self.dir.unique_repr()?);
+			let dir_hash = Some(self.dir_hash()?);
 			last_timestamp.last_checked = now;
 			if last_timestamp.dir_hash == dir_hash {
-				return Ok(())
+				return Ok(false)
 			}
 			self.reload_accounts()?;
 			last_timestamp.dir_hash = dir_hash;
+			return Ok(true)
 		}
-		Ok(())
+		Ok(false)
+	}
+
+	fn dir_hash(&self) -> Result<u64, Error> {
+		let mut hash = self.dir.unique_repr()?;
+		for vault in self.vaults.lock().values() {
+			hash = hash.rotate_left(1) ^ vault.unique_repr()?;
+		}
+		Ok(hash)
 	}
 
 	fn reload_accounts(&self) -> Result<(), Error> {
@@ -397,6 +470,35 @@ impl EthMultiStore {
 		Ok(account_ref)
 	}
 
+	/// Writes many accounts to the key directory at once, refreshing the cache only after all of them were written.
+	/// Accounts with addresses already present in the target vault (or earlier in the batch) are rejected.
+	fn import_batch(&self, vault: SecretVaultRef, accounts: Vec<Result<SafeAccount, Error>>) -> Vec<Result<StoreAccountRef, Error>> {
+		let mut seen = BTreeSet::new();
+		let results = accounts.into_iter()
+			.map(|account| {
+				let account = account?;
+				let account_ref = StoreAccountRef::new(vault.clone(), account.address.clone());
+				if self.cache.read().contains_key(&account_ref) || !seen.insert(account_ref.clone()) {
+					return Err(Error::AlreadyExists(account.address));
+				}
+
+				match vault {
+					SecretVaultRef::Root => self.dir.insert(account)?,
+					SecretVaultRef::Vault(ref vault_name) => self.vaults.lock().get_mut(vault_name).ok_or(Error::VaultNotFound)?.insert(account)?,
+				};
+				Ok(account_ref)
+			})
+			.collect::<Vec<_>>();
+
+		if results.iter().any(Result::is_ok) {
+			if let Err(e) = self.reload_accounts() {
+				warn!(target: "ethstore", "Failed to reload accounts after import: {}", e);
+			}
+		}
+
+		results
+	}
+
 	fn update(&self, account_ref: &StoreAccountRef, old: SafeAccount, new: SafeAccount) -> Result<(), Error> {
 		// save to file
 		let account = match account_ref.vault {
@@ -442,8 +544,7 @@ impl EthMultiStore {
 		return Ok(());
 	}
 
-	fn generate(&self, secret: Secret, derivation: Derivation) -> Result<ExtendedKeyPair, Error> {
-		let mut extended = ExtendedKeyPair::new(secret);
+	fn generate(&self, mut extended: ExtendedKeyPair, derivation: Derivation) -> Result<ExtendedKeyPair, Error> {
 		match derivation {
 			Derivation::Hierarchical(path) => {
 				for path_item in path {
@@ -464,7 +565,14 @@ impl SimpleSecretStore for EthMultiStore {
 	fn insert_account(&self, vault: SecretVaultRef, secret: Secret, password: &Password) -> Result<StoreAccountRef, Error> {
 		let keypair = KeyPair::from_secret(secret).map_err(|_| Error::CreationFailed)?;
 		let id: [u8; 16] = Random::random();
-		let account = SafeAccount::create(&keypair, id, password, self.iterations, "".to_owned(), "{}".to_owned())?;
+		let account = SafeAccount::create(&keypair, id, password, &self.kdf, "".to_owned(), "{}".to_owned())?;
+		self.import(vault, account)
+	}
+
+	fn insert_hd_seed(&self, vault: SecretVaultRef, seed: &[u8], password: &Password) -> Result<StoreAccountRef, Error> {
+		let keypair = ExtendedKeyPair::with_seed(seed)?;
+		let id: [u8; 16] = Random::random();
+		let account = SafeAccount::create_extended(&keypair, id, password, &self.kdf, "".to_owned(), "{}".to_owned())?;
 		self.import(vault, account)
 	}
 
@@ -473,7 +581,7 @@ impl SimpleSecretStore for EthMultiStore {
 	{
 		let accounts = self.get_matching(account_ref, password)?;
 		for account in accounts {
-			let extended = self.generate(account.crypto.secret(password)?, derivation)?;
+			let extended = self.generate(account.extended_key_pair(password)?, derivation)?;
 			return self.insert_account(vault, extended.secret().as_raw().clone(), password);
 		}
 		Err(Error::InvalidPassword)
@@ -484,7 +592,7 @@ impl SimpleSecretStore for EthMultiStore {
 	{
 		let accounts = self.get_matching(&account_ref, password)?;
 		for account in accounts {
-			let extended = self.generate(account.crypto.secret(password)?, derivation)?;
+			let extended = self.generate(account.extended_key_pair(password)?, derivation)?;
 			return Ok(ethkey::public_to_address(extended.public().public()));
 		}
 		Err(Error::InvalidPassword)
@@ -495,7 +603,7 @@ impl SimpleSecretStore for EthMultiStore {
 	{
 		let accounts = self.get_matching(&account_ref, password)?;
 		for account in accounts {
-			let extended = self.generate(account.crypto.secret(password)?, derivation)?;
+			let extended = self.generate(account.extended_key_pair(password)?, derivation)?;
 			let secret = extended.secret().as_raw();
 			return Ok(ethkey::sign(&secret, message)?)
 		}
@@ -543,15 +651,26 @@ impl SimpleSecretStore for EthMultiStore {
 
 		for account in accounts {
 			// Change password
-			let new_account = account.change_password(old_password, new_password, self.iterations)?;
+			let new_account = account.change_password(old_password, new_password, &self.kdf)?;
 			self.update(account_ref, account, new_account)?;
 		}
 
 		Ok(())
 	}
 
-	fn export_account(&self, account_ref: &StoreAccountRef, password: &Password) -> Result<OpaqueKeyFile, Error> {
-		self.get_matching(account_ref, password)?.into_iter().nth(0).map(Into::into).ok_or(Error::InvalidPassword)
+	fn export_account(&self, account_ref: &StoreAccountRef, password: &Password, new_password: Option<&Password>) -> Result<OpaqueKeyFile, Error> {
+		let mut account = self.get_matching(account_ref, password)?.into_iter().nth(0).ok_or(Error::InvalidPassword)?;
+		if let Some(new_password) = new_password {
+			account = account.change_password(password, new_password, &self.kdf)?;
+		}
+
+		// vault name is only meaningful within this store
+		if let SecretVaultRef::Vault(_) = account_ref.vault {
+			account.meta = json::remove_vault_name_from_json_meta(&account.meta)
+				.map_err(|err| Error::Custom(format!("{:?}", err)))?;
+		}
+
+		Ok(account.into())
 	}
 
 	fn sign(&self, account: &StoreAccountRef, password: &Password, message: &Message) -> Result<Signature, Error> {
@@ -657,6 +776,20 @@ impl SimpleSecretStore for EthMultiStore {
 		}
 	}
 
+	fn rename_vault(&self, name: &str, new_name: &str) -> Result<(), Error> {
+		{ // lock border
+			let mut vaults = self.vaults.lock();
+			let key = vaults.get(name).map(|v| v.key()).ok_or(Error::VaultNotFound)?;
+			let vault_provider = self.dir.as_vault_provider().ok_or(Error::VaultsAreNotSupported)?;
+			let vault = vault_provider.rename(name, new_name, key)?;
+			vaults.remove(name);
+			vaults.insert(new_name.to_owned(), vault);
+		}
+
+		// accounts are cached with references to the old vault
+		self.reload_accounts()
+	}
+
 	fn change_account_vault(&self, vault: SecretVaultRef, account_ref: StoreAccountRef) -> Result<StoreAccountRef, Error> {
 		if account_ref.vault == vault {
 			return Ok(account_ref);
@@ -695,12 +828,17 @@ impl SimpleSecretStore for EthMultiStore {
 mod tests {
 	extern crate tempdir;
 
-	use accounts_dir::{KeyDirectory, MemoryDirectory, RootDiskDirectory};
-	use ethkey::{Random, Generator, KeyPair};
+	use std::fs;
+	use std::time::Duration;
+	use accounts_dir::{KeyDirectory, MemoryDirectory, MultiDirectory, RootDiskDirectory};
+	use ethkey::{Random, Generator, KeyPair, DerivationPath};
+	use rustc_hex::FromHex;
 	use secret_store::{SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation};
 	use super::{EthStore, EthMultiStore};
 	use self::tempdir::TempDir;
+	use Error;
 	use ethereum_types::H256;
+	use serde_json;
 
 	fn keypair() -> KeyPair {
 		Random.generate().unwrap()
@@ -1006,6 +1144,34 @@ mod tests {
 		assert_eq!(store.accounts().unwrap().len(), 1);
 	}
 
+	#[test]
+	fn should_rename_opened_vault() {
+		// given
+		let mut dir = RootDiskDirectoryGuard::new();
+		let store = EthStore::open(dir.key_dir.take().unwrap()).unwrap();
+		let name = "vault"; let new_name = "renamed"; let password = "password".into();
+		let keypair = keypair();
+
+		// when
+		store.create_vault(name, &password).unwrap();
+		store.insert_account(SecretVaultRef::Vault(name.to_owned()), keypair.secret().clone(), &password).unwrap();
+		store.rename_vault(name, new_name).unwrap();
+
+		// then
+		assert_eq!(store.list_opened_vaults().unwrap(), vec![new_name.to_owned()]);
+		assert_eq!(store.list_vaults().unwrap(), vec![new_name.to_owned()]);
+		assert_eq!(store.accounts().unwrap(), vec![StoreAccountRef::vault(new_name, keypair.address())]);
+		assert_eq!(store.meta(&StoreAccountRef::vault(new_name, keypair.address())).unwrap(), r#"{"vault":"renamed"}"#);
+
+		// and when
+		store.close_vault(new_name).unwrap();
+
+		// then
+		store.open_vault(name, &password).unwrap_err();
+		store.open_vault(new_name, &password).unwrap();
+		assert_eq!(store.accounts().unwrap().len(), 1);
+	}
+
 	#[test]
 	fn should_have_different_passwords_for_vault_secret_and_meta() {
 		// given
@@ -1101,6 +1267,32 @@ mod tests {
 		assert!(store.sign(&derived, &"test".into(), &Default::default()).is_ok(), "Second password should work for second store.");
 	}
 
+	#[test]
+	fn should_derive_bip44_accounts_from_hd_seed() {
+		// given a seed of the "abandon abandon ... about" mnemonic stored on disk
+		let seed: Vec<u8> = "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4".from_hex().unwrap();
+		let mut dir = RootDiskDirectoryGuard::new();
+		let store = EthStore::open(dir.key_dir.take().unwrap()).unwrap();
+		let root = store.insert_hd_seed(SecretVaultRef::Root, &seed, &"test".into()).unwrap();
+		let path: DerivationPath = "m/44'/60'/0'/0/0".parse().unwrap();
+
+		// when deriving without saving
+		let derived = store.generate_derived(&root, &"test".into(), Derivation::from(&path)).unwrap();
+
+		// then the address matches BIP-44 test vector
+		assert_eq!(derived, "9858effd232b4033e47d90003d41ec34ecaeda94".into());
+
+		// when the chain code is reloaded from disk and the account is saved
+		store.store.reload_accounts().unwrap();
+		let saved = store.insert_derived(SecretVaultRef::Root, &root, &"test".into(), Derivation::from(&path)).unwrap();
+
+		// then it's a regular account usable for signing
+		assert_eq!(saved.address, derived);
+		let message = Default::default();
+		let signature = store.sign(&saved, &"test".into(), &message).unwrap();
+		assert_eq!(store.sign_derived(&root, &"test".into(), Derivation::from(&path), &message).unwrap(), signature);
+	}
+
 	#[test]
 	fn should_save_meta_when_setting_before_password() {
 		// given
@@ -1126,9 +1318,236 @@ mod tests {
 		let address = store.insert_account(SecretVaultRef::Root, keypair.secret().clone(), &"test".into()).unwrap();
 
 		// when
-		let exported = store.export_account(&address, &"test".into());
+		let exported = store.export_account(&address, &"test".into(), None);
 
 		// then
 		assert!(exported.is_ok(), "Should export single account: {:?}", exported);
 	}
+
+	#[test]
+	fn should_export_vault_account_with_new_password() {
+		// given
+		let mut dir = RootDiskDirectoryGuard::new();
+		let store = EthStore::open(dir.key_dir.take().unwrap()).unwrap();
+		let name = "vault"; let password = "password".into(); let new_password = "new_password".into();
+		let keypair = keypair();
+		store.create_vault(name, &password).unwrap();
+		let account = store.insert_account(SecretVaultRef::Vault(name.to_owned()), keypair.secret().clone(), &password).unwrap();
+
+		// when
+		store.export_account(&account, &new_password, None).unwrap_err();
+		let exported = store.export_account(&account, &password, Some(&new_password)).unwrap();
+		let exported = serde_json::to_vec(&exported).unwrap();
+
+		// then
+		let other = EthStore::open(Box::new(MemoryDirectory::default())).unwrap();
+		let imported = other.import_wallet(SecretVaultRef::Root, &exported, &new_password, false).unwrap();
+		assert_eq!(imported.address, keypair.address());
+		assert_eq!(other.meta(&imported).unwrap(), "{}");
+		assert!(other.test_password(&imported, &new_password).unwrap());
+	}
+
+	#[test]
+	fn should_export_hd_account_as_standard_key_file() {
+		// given
+		let seed = [42u8; 64];
+		let store = EthStore::open(Box::new(MemoryDirectory::default())).unwrap();
+		let root = store.insert_hd_seed(SecretVaultRef::Root, &seed, &"test".into()).unwrap();
+		let path: DerivationPath = "m/44'/60'/0'/0/0".parse().unwrap();
+		let derived = store.generate_derived(&root, &"test".into(), Derivation::from(&path)).unwrap();
+
+		// when
+		let exported = store.export_account(&root, &"test".into(), Some(&"new".into())).unwrap();
+		let exported = serde_json::to_value(&exported).unwrap();
+
+		// then only the standard fields are present, chain code is kept in meta
+		let mut fields: Vec<_> = exported.as_object().unwrap().keys().cloned().collect();
+		fields.sort();
+		assert_eq!(fields, vec!["address", "crypto", "id", "meta", "name", "version"]);
+
+		let other = EthStore::open(Box::new(MemoryDirectory::default())).unwrap();
+		let imported = other.import_wallet(SecretVaultRef::Root, &serde_json::to_vec(&exported).unwrap(), &"new".into(), false).unwrap();
+		assert_eq!(other.meta(&imported).unwrap(), "{}");
+		assert_eq!(other.generate_derived(&imported, &"new".into(), Derivation::from(&path)).unwrap(), derived);
+	}
+
+	#[test]
+	fn should_create_accounts_with_configured_kdf() {
+		use std::num::NonZeroU32;
+		use account::KdfConfig;
+
+		let kdfs = vec![
+			KdfConfig::Pbkdf2(NonZeroU32::new(1024).unwrap()),
+			KdfConfig::scrypt(1024, 8, 1).unwrap(),
+			KdfConfig::scrypt(4096, 4, 2).unwrap(),
+		];
+
+		for kdf in kdfs {
+			// given
+			let mut dir = RootDiskDirectoryGuard::new();
+			let store = EthStore::open_with_kdf(dir.key_dir.take().unwrap(), kdf).unwrap();
+			let keypair = keypair();
+			let password = "test".into();
+
+			// when
+			let account = store.insert_account(SecretVaultRef::Root, keypair.secret().clone(), &password).unwrap();
+
+			// then
+			assert_eq!(&store.public(&account, &password).unwrap(), keypair.public());
+			assert!(!store.test_password(&account, &"wrong".into()).unwrap());
+		}
+	}
+
+	#[test]
+	fn should_load_keyfiles_regardless_of_kdf_settings() {
+		use account::KdfConfig;
+
+		// given
+		let dir = TempDir::new("").unwrap();
+		let keypair = keypair();
+		let password = "test".into();
+		{
+			let store = EthStore::open_with_kdf(
+				Box::new(RootDiskDirectory::create(dir.path()).unwrap()),
+				KdfConfig::scrypt(1024, 8, 1).unwrap(),
+			).unwrap();
+			store.insert_account(SecretVaultRef::Root, keypair.secret().clone(), &password).unwrap();
+		}
+
+		// when
+		let store = EthStore::open(Box::new(RootDiskDirectory::create(dir.path()).unwrap())).unwrap();
+
+		// then
+		let account = StoreAccountRef::root(keypair.address());
+		assert_eq!(store.accounts().unwrap(), vec![account.clone()]);
+		assert!(store.test_password(&account, &password).unwrap());
+	}
+
+	#[test]
+	fn should_import_wallets_in_batch() {
+		// given
+		let store = store();
+		let wallet1 = include_bytes!("../tests/res/ciphertext/30.json");
+		let wallet2 = include_bytes!("../tests/res/ciphertext/31.json");
+		let password = "foo".into();
+		let wrong_password = "bar".into();
+
+		// when
+		let results = store.import_wallets(SecretVaultRef::Root, &[
+			(&wallet1[..], &password),
+			(&wallet2[..], &wrong_password),
+			(&b"not a keyfile"[..], &password),
+			(&wallet1[..], &password),
+		], false);
+
+		// then
+		let imported = StoreAccountRef::root("31e9d1e6d844bd3a536800ef8d8be6a9975db509".into());
+		assert_eq!(results.len(), 4);
+		assert_eq!(results[0].as_ref().unwrap(), &imported);
+		assert_matches!(results[1], Err(Error::InvalidPassword));
+		assert_matches!(results[2], Err(Error::InvalidKeyFile(_)));
+		assert_matches!(results[3], Err(Error::AlreadyExists(_)));
+		assert_eq!(store.accounts().unwrap(), vec![imported]);
+	}
+
+	#[test]
+	fn should_import_presale_wallets_in_batch() {
+		// given
+		let store = store();
+		let wallet = br#"{
+			"encseed": "137103c28caeebbcea5d7f95edb97a289ded151b72159137cb7b2671f394f54cff8c121589dcb373e267225547b3c71cbdb54f6e48ec85cd549f96cf0dedb3bc0a9ac6c79b9c426c5878ca2c9d06ff42a23cb648312fc32ba83649de0928e066",
+			"ethaddr": "ede84640d1a1d3e06902048e67aa7db8d52c2ce1",
+			"email": "123@gmail.com",
+			"btcaddr": "1JvqEc6WLhg6GnyrLBe2ztPAU28KRfuseH"
+		}"#;
+
+		// when
+		let results = store.import_presales(SecretVaultRef::Root, &[
+			(&wallet[..], &"124".into()),
+			(&wallet[..], &"123".into()),
+			(&wallet[..], &"123".into()),
+		]);
+
+		// then
+		let imported = StoreAccountRef::root("ede84640d1a1d3e06902048e67aa7db8d52c2ce1".into());
+		assert_matches!(results[0], Err(Error::InvalidPassword));
+		assert_eq!(results[1].as_ref().unwrap(), &imported);
+		assert_matches!(results[2], Err(Error::AlreadyExists(_)));
+		assert_eq!(store.accounts().unwrap(), vec![imported.clone()]);
+		assert!(store.test_password(&imported, &"123".into()).unwrap());
+	}
+
+	#[test]
+	fn should_pick_up_external_keyfile_changes() {
+		// given
+		let temp_path = TempDir::new("").unwrap();
+		let store = EthStore::open(Box::new(RootDiskDirectory::create(temp_path.path()).unwrap())).unwrap();
+		store.set_refresh_time(Duration::from_secs(0));
+		let external = EthStore::open(Box::new(RootDiskDirectory::create(temp_path.path()).unwrap())).unwrap();
+		let account = external.insert_account(SecretVaultRef::Root, keypair().secret().clone(), &"test".into()).unwrap();
+		let file = fs::read_dir(temp_path.path()).unwrap().next().unwrap().unwrap().path();
+		let contents = fs::read(&file).unwrap();
+		fs::remove_file(&file).unwrap();
+		store.refresh().unwrap();
+		assert!(store.accounts().unwrap().is_empty());
+
+		// when [file is being copied]
+		fs::write(&file, &contents[..contents.len() / 2]).unwrap();
+
+		// then
+		assert!(store.refresh().unwrap());
+		assert!(store.accounts().unwrap().is_empty());
+
+		// when [copy is complete]
+		fs::write(&file, &contents).unwrap();
+
+		// then
+		assert!(store.refresh().unwrap());
+		assert_eq!(store.accounts().unwrap(), vec![account.clone()]);
+		assert!(!store.refresh().unwrap());
+
+		// when [file is modified]
+		external.set_name(&account, "renamed".into()).unwrap();
+
+		// then
+		assert!(store.refresh().unwrap());
+		assert_eq!(store.name(&account).unwrap(), "renamed");
+
+		// when [file is removed]
+		fs::remove_file(&file).unwrap();
+
+		// then
+		assert!(store.refresh().unwrap());
+		assert!(store.accounts().unwrap().is_empty());
+	}
+
+	#[test]
+	fn should_sign_with_read_only_accounts_but_not_modify_them() {
+		// given
+		let shared = TempDir::new("").unwrap();
+		let local = TempDir::new("").unwrap();
+		let account = EthStore::open(Box::new(RootDiskDirectory::create(shared.path()).unwrap())).unwrap()
+			.insert_account(SecretVaultRef::Root, keypair().secret().clone(), &"test".into()).unwrap();
+		let dir = MultiDirectory::new(
+			Box::new(RootDiskDirectory::create(local.path()).unwrap()),
+			vec![Box::new(RootDiskDirectory::at(shared.path()))],
+		);
+		let store = EthStore::open(Box::new(dir)).unwrap();
+
+		// when
+		let new = store.insert_account(SecretVaultRef::Root, keypair().secret().clone(), &"test".into()).unwrap();
+
+		// then
+		assert_eq!(store.accounts().unwrap().len(), 2);
+		assert!(store.sign(&account, &"test".into(), &Default::default()).is_ok());
+		assert_matches!(store.set_name(&account, "name".into()), Err(Error::ReadOnlyStore));
+		assert_matches!(store.change_password(&account, &"test".into(), &"new".into()), Err(Error::ReadOnlyStore));
+		assert_matches!(store.remove_account(&account, &"test".into()), Err(Error::ReadOnlyStore));
+		assert!(store.test_password(&account, &"test".into()).unwrap());
+		assert_eq!(fs::read_dir(shared.path()).unwrap().count(), 1);
+		assert_eq!(fs::read_dir(local.path()).unwrap().count(), 1);
+		store.set_name(&new, "name".into()).unwrap();
+		store.remove_account(&new, &"test".into()).unwrap();
+		assert_eq!(store.accounts().unwrap(), vec![account]);
+	}
 }
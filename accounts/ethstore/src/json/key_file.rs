@@ -19,8 +19,13 @@ use std::io::{Read, Write};
 use serde::{Serialize, Serializer, Deserialize, Deserializer};
 use serde::de::{Error, Visitor, MapAccess, DeserializeOwned};
 use serde_json;
+use serde_json::error;
+use serde_json::value::Value;
 use super::{Uuid, Version, Crypto, H160};
 
+/// Meta key name for the encrypted chain code of hierarchical deterministic keys
+const CHAIN_CODE_META_KEY: &'static str = "chaincrypto";
+
 /// Public opaque type representing serializable `KeyFile`.
 #[derive(Debug, PartialEq)]
 pub struct OpaqueKeyFile {
@@ -171,6 +176,37 @@ impl<'a> Visitor<'a> for KeyFileVisitor {
 	}
 }
 
+/// Insert encrypted chain code to the JSON meta field.
+/// Keeping it in meta leaves the key file a standard V3 key file.
+pub fn insert_chain_code_to_json_meta(meta: &str, chain_code: Crypto) -> Result<String, error::Error> {
+	let mut meta = if meta.is_empty() {
+		Value::Object(serde_json::Map::new())
+	} else {
+		serde_json::from_str(meta)?
+	};
+
+	if let Some(meta_obj) = meta.as_object_mut() {
+		meta_obj.insert(CHAIN_CODE_META_KEY.to_owned(), serde_json::to_value(chain_code)?);
+		serde_json::to_string(meta_obj)
+	} else {
+		Err(error::Error::custom("Meta is expected to be a serialized JSON object"))
+	}
+}
+
+/// Take encrypted chain code out of the JSON meta field.
+/// Meta which is not a JSON object can't contain it and is returned as is.
+pub fn take_chain_code_from_json_meta(meta: String) -> Result<(String, Option<Crypto>), error::Error> {
+	let mut meta_obj = match serde_json::from_str(&meta) {
+		Ok(Value::Object(meta_obj)) => meta_obj,
+		_ => return Ok((meta, None)),
+	};
+
+	match meta_obj.remove(CHAIN_CODE_META_KEY) {
+		Some(chain_code) => Ok((serde_json::to_string(&meta_obj)?, Some(serde_json::from_value(chain_code)?))),
+		None => Ok((meta, None)),
+	}
+}
+
 impl KeyFile {
 	pub fn load<R>(reader: R) -> Result<Self, serde_json::Error> where R: Read {
 		serde_json::from_reader(reader)
@@ -186,6 +222,7 @@ mod tests {
 	use std::str::FromStr;
 	use serde_json;
 	use json::{KeyFile, Uuid, Version, Crypto, Cipher, Aes128Ctr, Kdf, Scrypt};
+	use super::{insert_chain_code_to_json_meta, take_chain_code_from_json_meta};
 
 	#[test]
 	fn basic_keyfile() {
@@ -321,4 +358,33 @@ mod tests {
 
 		assert_eq!(file, deserialized);
 	}
+
+	fn chain_code() -> Crypto {
+		Crypto {
+			cipher: Cipher::Aes128Ctr(Aes128Ctr {
+				iv: "0b1b5ef3c1e7ea1fba1ad6e7f9ae7b6c".into(),
+			}),
+			ciphertext: "3cb87e9c3a1e0c1df4ae0ac05a13da2a38a69446d3c1cde43b2a5a4a4a9ff7ac".into(),
+			kdf: Kdf::Scrypt(Scrypt {
+				n: 262144,
+				dklen: 32,
+				p: 1,
+				r: 8,
+				salt: "9f6d2b0bde9a2a1f0c7bd4ea8ad1c4b2d8d7c2c1a4a5e2f6c7b8a9d0e1f2a3b4".into(),
+			}),
+			mac: "5b1a6e0d2c3f4a5b6c7d8e9f0a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b".into(),
+		}
+	}
+
+	#[test]
+	fn chain_code_in_json_meta() {
+		let meta = insert_chain_code_to_json_meta(r#"{"description":"HD wallet"}"#, chain_code()).unwrap();
+		let (meta, restored) = take_chain_code_from_json_meta(meta).unwrap();
+		assert_eq!(meta, r#"{"description":"HD wallet"}"#);
+		assert_eq!(restored, Some(chain_code()));
+
+		// meta which isn't a JSON object is kept as is
+		assert_eq!(take_chain_code_from_json_meta("plain".into()).unwrap(), ("plain".to_owned(), None));
+		assert!(insert_chain_code_to_json_meta("plain", chain_code()).is_err());
+	}
 }
@@ -51,6 +51,7 @@ pub mod accounts_dir;
 pub mod ethkey;
 
 mod account;
+mod backup;
 mod json;
 
 mod error;
@@ -60,7 +61,8 @@ mod presale;
 mod random;
 mod secret_store;
 
-pub use self::account::{SafeAccount, Crypto};
+pub use self::account::{SafeAccount, Crypto, KdfConfig, MAX_SCRYPT_MEMORY};
+pub use self::backup::{backup, restore, BACKUP_VERSION};
 pub use self::error::Error;
 pub use self::ethstore::{EthStore, EthMultiStore};
 pub use self::import::{import_account, import_accounts, read_geth_accounts};
@@ -17,7 +17,7 @@
 use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 use std::cmp::Ordering;
-use ethkey::{Address, Message, Signature, Secret, Password, Public};
+use ethkey::{Address, Message, Signature, Secret, Password, Public, DerivationPath, HARDENED_OFFSET};
 use Error;
 use json::{Uuid, OpaqueKeyFile};
 use ethereum_types::H256;
@@ -59,10 +59,12 @@ pub trait SimpleSecretStore: Send + Sync {
 	fn insert_account(&self, vault: SecretVaultRef, secret: Secret, password: &Password) -> Result<StoreAccountRef, Error>;
 	/// Inserts new derived account to the store (or vault) with given password.
 	fn insert_derived(&self, vault: SecretVaultRef, account_ref: &StoreAccountRef, password: &Password, derivation: Derivation) -> Result<StoreAccountRef, Error>;
+	/// Inserts new hierarchical deterministic account, with master key generated from the seed (BIP-32).
+	fn insert_hd_seed(&self, vault: SecretVaultRef, seed: &[u8], password: &Password) -> Result<StoreAccountRef, Error>;
 	/// Changes accounts password.
 	fn change_password(&self, account: &StoreAccountRef, old_password: &Password, new_password: &Password) -> Result<(), Error>;
-	/// Exports key details for account.
-	fn export_account(&self, account: &StoreAccountRef, password: &Password) -> Result<OpaqueKeyFile, Error>;
+	/// Exports key details for account, optionally re-encrypted with `new_password`.
+	fn export_account(&self, account: &StoreAccountRef, password: &Password, new_password: Option<&Password>) -> Result<OpaqueKeyFile, Error>;
 	/// Entirely removes account from the store and underlying storage.
 	fn remove_account(&self, account: &StoreAccountRef, password: &Password) -> Result<(), Error>;
 	/// Generates new derived account.
@@ -94,6 +96,8 @@ pub trait SimpleSecretStore: Send + Sync {
 	fn list_opened_vaults(&self) -> Result<Vec<String>, Error>;
 	/// Change vault password
 	fn change_vault_password(&self, name: &str, new_password: &Password) -> Result<(), Error>;
+	/// Rename opened vault
+	fn rename_vault(&self, name: &str, new_name: &str) -> Result<(), Error>;
 	/// Cnage account' vault
 	fn change_account_vault(&self, vault: SecretVaultRef, account: StoreAccountRef) -> Result<StoreAccountRef, Error>;
 	/// Get vault metadata string.
@@ -115,8 +119,14 @@ pub trait SecretStore: SimpleSecretStore {
 
 	/// Imports presale wallet
 	fn import_presale(&self, vault: SecretVaultRef, json: &[u8], password: &Password) -> Result<StoreAccountRef, Error>;
+	/// Imports many presale wallets at once, each with its own password.
+	/// Every wallet is processed independently and the result for each of them is returned.
+	fn import_presales(&self, vault: SecretVaultRef, wallets: &[(&[u8], &Password)]) -> Vec<Result<StoreAccountRef, Error>>;
 	/// Imports existing JSON wallet
 	fn import_wallet(&self, vault: SecretVaultRef, json: &[u8], password: &Password, gen_id: bool) -> Result<StoreAccountRef, Error>;
+	/// Imports many existing JSON wallets at once, each with its own password.
+	/// Every wallet is processed independently and the result for each of them is returned.
+	fn import_wallets(&self, vault: SecretVaultRef, wallets: &[(&[u8], &Password)], gen_id: bool) -> Vec<Result<StoreAccountRef, Error>>;
 	/// Copies account between stores and vaults.
 	fn copy_account(&self, new_store: &SimpleSecretStore, new_vault: SecretVaultRef, account: &StoreAccountRef, password: &Password, new_password: &Password) -> Result<(), Error>;
 	/// Checks if password matches given account.
@@ -139,6 +149,9 @@ pub trait SecretStore: SimpleSecretStore {
 
 	/// Returns local path of the store.
 	fn local_path(&self) -> PathBuf;
+	/// Reloads accounts if the refresh time has passed and key files were added, removed or modified.
+	/// Returns `true` if accounts were reloaded.
+	fn refresh(&self) -> Result<bool, Error>;
 	/// Lists all found geth accounts.
 	fn list_geth_accounts(&self, testnet: bool) -> Vec<Address>;
 	/// Imports geth accounts to the store/vault.
@@ -188,3 +201,12 @@ pub enum Derivation {
 	/// Hash derivation, hard.
 	HardHash(H256),
 }
+
+impl<'a> From<&'a DerivationPath> for Derivation {
+	fn from(path: &'a DerivationPath) -> Self {
+		Derivation::Hierarchical(path.indices().iter().map(|index| IndexDerivation {
+			soft: *index < HARDENED_OFFSET,
+			index: *index,
+		}).collect())
+	}
+}
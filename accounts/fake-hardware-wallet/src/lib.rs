@@ -27,6 +27,7 @@ pub struct WalletInfo {
 	pub address: Address,
 	pub name: String,
 	pub manufacturer: String,
+	pub serial: String,
 }
 
 #[derive(Debug)]
@@ -85,6 +86,14 @@ impl HardwareWalletManager {
 	pub fn pin_matrix_ack(&self, _: &str, _: &str) -> Result<bool, Error> { 
 		Err(Error::NoWallet)
 	}
+
+	pub fn list_passphrase_requests(&self) -> Result<Vec<String>, Error> {
+		Err(Error::NoWallet)
+	}
+
+	pub fn passphrase_ack(&self, _: &str, _: &str) -> Result<bool, Error> {
+		Err(Error::NoWallet)
+	}
 	
 	pub fn sign_transaction(&self, _address: &Address, _transaction: &TransactionInfo, _rlp_transaction: &[u8]) -> Result<Signature, Error> { 
 		Err(Error::NoWallet) }
@@ -85,8 +85,8 @@ pub trait Wallet<'a> {
 	/// Get wallet info.
 	fn get_wallet(&self, address: &Address) -> Option<WalletInfo>;
 
-	/// Generate ethereum address for a Wallet
-	fn get_address(&self, device: &hidapi::HidDevice) -> Result<Option<Address>, Self::Error>;
+	/// Generate ethereum address for a Wallet at `path`
+	fn get_address(&self, device: &hidapi::HidDevice, path: &str) -> Result<Option<Address>, Self::Error>;
 
 	/// Open a device using `device path`
 	/// Note, f - is a closure that borrows HidResult<HidDevice>
@@ -98,6 +98,35 @@ pub trait Wallet<'a> {
 		where F: Fn() -> Result<R, &'static str>;
 }
 
+/// Packet-level communication with a device.
+/// Implemented by `hidapi::HidDevice` and abstracted to allow testing the wallet protocols.
+trait Transport {
+	/// Write a single packet to the device.
+	fn write(&self, data: &[u8]) -> Result<usize, hidapi::HidError>;
+
+	/// Read a single packet from the device, waiting at most `timeout` milliseconds (-1 for blocking read).
+	fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> Result<usize, hidapi::HidError>;
+
+	/// Read a single packet from the device, blocking until it's available.
+	fn read(&self, buf: &mut [u8]) -> Result<usize, hidapi::HidError> {
+		self.read_timeout(buf, -1)
+	}
+}
+
+impl Transport for hidapi::HidDevice {
+	fn write(&self, data: &[u8]) -> Result<usize, hidapi::HidError> {
+		hidapi::HidDevice::write(self, data)
+	}
+
+	fn read_timeout(&self, buf: &mut [u8], timeout: i32) -> Result<usize, hidapi::HidError> {
+		hidapi::HidDevice::read_timeout(self, buf, timeout)
+	}
+
+	fn read(&self, buf: &mut [u8]) -> Result<usize, hidapi::HidError> {
+		hidapi::HidDevice::read(self, buf)
+	}
+}
+
 /// Hardware wallet error.
 #[derive(Debug)]
 pub enum Error {
@@ -297,12 +326,12 @@ impl HardwareWalletManager {
 		}
 	}
 
-	/// Sign a message with the wallet (only supported by Ledger)
+	/// Sign a personal message with the wallet managing `address`
 	pub fn sign_message(&self, address: &Address, msg: &[u8]) -> Result<Signature, Error> {
 		if self.ledger.get_wallet(address).is_some() {
 			Ok(self.ledger.sign_message(address, msg)?)
 		} else if self.trezor.get_wallet(address).is_some() {
-			Err(Error::TrezorDevice(trezor::Error::NoSigningMessage))
+			Ok(self.trezor.sign_message(address, msg)?)
 		} else {
 			Err(Error::KeyNotFound)
 		}
@@ -311,7 +340,7 @@ impl HardwareWalletManager {
 	/// Sign transaction data with wallet managing `address`.
 	pub fn sign_transaction(&self, address: &Address, t_info: &TransactionInfo, encoded_transaction: &[u8]) -> Result<Signature, Error> {
 		if self.ledger.get_wallet(address).is_some() {
-			Ok(self.ledger.sign_transaction(address, encoded_transaction)?)
+			Ok(self.ledger.sign_transaction(address, (encoded_transaction, t_info.chain_id))?)
 		} else if self.trezor.get_wallet(address).is_some() {
 			Ok(self.trezor.sign_transaction(address, t_info)?)
 		} else {
@@ -325,6 +354,18 @@ impl HardwareWalletManager {
 	pub fn pin_matrix_ack(&self, path: &str, pin: &str) -> Result<bool, Error> {
 		self.trezor.pin_matrix_ack(path, pin).map_err(Error::TrezorDevice)
 	}
+
+	/// Return a list of paths to hardware wallets waiting for a passphrase to be entered on the host
+	/// This is only applicable to Trezor because Ledger doesn't support passphrases
+	pub fn list_passphrase_requests(&self) -> Result<Vec<String>, Error> {
+		Ok(self.trezor.passphrase_requests())
+	}
+
+	/// Send a passphrase to a device at a certain path to open the hidden wallet
+	/// This is only applicable to Trezor because Ledger doesn't support passphrases
+	pub fn passphrase_ack(&self, path: &str, passphrase: &str) -> Result<bool, Error> {
+		self.trezor.passphrase_ack(path, passphrase).map_err(Error::TrezorDevice)
+	}
 }
 
 impl Drop for HardwareWalletManager {
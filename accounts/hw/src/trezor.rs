@@ -14,12 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Trezor hardware wallet module. Supports Trezor v1.
+//! Trezor hardware wallet module. Supports Trezor v1, including passphrase-protected wallets.
 //! See <http://doc.satoshilabs.com/trezor-tech/api-protobuf.html>
 //! and <https://github.com/trezor/trezor-common/blob/master/protob/protocol.md>
 //! for protocol details.
 
 use std::cmp::{min, max};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use std::fmt;
@@ -30,8 +31,9 @@ use hidapi;
 use libusb;
 use parking_lot::{Mutex, RwLock};
 use protobuf::{self, Message, ProtobufEnum};
-use super::{DeviceDirection, WalletInfo, TransactionInfo, KeyPath, Wallet, Device, is_valid_hid_device};
-use trezor_sys::messages::{EthereumAddress, PinMatrixAck, MessageType, EthereumTxRequest, EthereumSignTx, EthereumGetAddress, EthereumTxAck, ButtonAck};
+use super::{DeviceDirection, WalletInfo, TransactionInfo, KeyPath, Transport, Wallet, Device, is_valid_hid_device};
+use trezor_sys::messages::{EthereumAddress, PinMatrixAck, MessageType, EthereumTxRequest, EthereumSignTx, EthereumGetAddress, EthereumTxAck, ButtonAck,
+	Initialize, PassphraseRequest, PassphraseAck, PassphraseStateRequest, PassphraseStateAck, EthereumSignMessage, EthereumMessageSignature};
 
 /// Trezor v1 vendor ID
 const TREZOR_VID: u16 = 0x534c;
@@ -58,14 +60,14 @@ pub enum Error {
 	BadMessageType,
 	/// Trying to read from a closed device at the given path
 	LockedDevice(String),
-	/// Signing messages are not supported by Trezor
-	NoSigningMessage,
 	/// No device arrived
 	NoDeviceArrived,
 	/// No device left
 	NoDeviceLeft,
 	/// Invalid PID or VID
 	InvalidDevice,
+	/// Device at the given path is waiting for a passphrase to be entered on the host
+	PassphraseRequired(String),
 }
 
 impl fmt::Display for Error {
@@ -78,10 +80,10 @@ impl fmt::Display for Error {
 			Error::UserCancel => write!(f, "Operation has been cancelled"),
 			Error::BadMessageType => write!(f, "Bad Message Type in RPC call"),
 			Error::LockedDevice(ref s) => write!(f, "Device is locked, needs PIN to perform operations: {}", s),
-			Error::NoSigningMessage=> write!(f, "Signing messages are not supported by Trezor"),
 			Error::NoDeviceArrived => write!(f, "No device arrived"),
 			Error::NoDeviceLeft => write!(f, "No device left"),
 			Error::InvalidDevice => write!(f, "Device with non-supported product ID or vendor ID was detected"),
+			Error::PassphraseRequired(ref s) => write!(f, "Device needs a passphrase to open the wallet: {}", s),
 		}
 	}
 }
@@ -110,6 +112,17 @@ pub struct Manager {
 	devices: RwLock<Vec<Device>>,
 	locked_devices: RwLock<Vec<String>>,
 	key_path: RwLock<KeyPath>,
+	sessions: Sessions,
+}
+
+/// Passphrase sessions of the connected devices, keyed by device path
+#[derive(Default)]
+struct Sessions {
+	/// Devices waiting for a passphrase to be entered on the host
+	pending: RwLock<Vec<String>>,
+	/// Session state returned by the devices once the passphrase has been entered,
+	/// used to resume the session without asking for the passphrase again
+	states: RwLock<HashMap<String, Vec<u8>>>,
 }
 
 /// HID Version used for the Trezor device
@@ -126,6 +139,7 @@ impl Manager {
 			devices: RwLock::new(Vec::new()),
 			locked_devices: RwLock::new(Vec::new()),
 			key_path: RwLock::new(KeyPath::Ethereum),
+			sessions: Sessions::default(),
 		})
 	}
 
@@ -136,37 +150,104 @@ impl Manager {
 			let t = MessageType::MessageType_PinMatrixAck;
 			let mut m = PinMatrixAck::new();
 			m.set_pin(pin.to_string());
-			self.send_device_message(&device, t, &m)?;
-			let (resp_type, _) = self.read_device_response(&device)?;
-			match resp_type {
+			Self::send_device_message(&device, t, &m)?;
+			match Self::read_response(&device, device_path, &self.sessions, None) {
 				// Getting an Address back means it's unlocked, this is undocumented behavior
-				MessageType::MessageType_EthereumAddress => Ok(true),
+				Ok((MessageType::MessageType_EthereumAddress, _)) => Ok(true),
+				// The PIN was accepted, but the wallet is protected by a passphrase that has to be entered on the host
+				Err(Error::PassphraseRequired(_)) => Ok(true),
 				// Getting anything else means we didn't unlock it
-				_ => Ok(false),
-
+				Ok(_) => Ok(false),
+				Err(e) => Err(e),
 			}
 		};
 		self.update_devices(DeviceDirection::Arrived)?;
 		unlocked
 	}
 
+	/// Paths of the devices waiting for a passphrase to be entered on the host
+	pub fn passphrase_requests(&self) -> Vec<String> {
+		self.sessions.pending.read().clone()
+	}
+
+	/// Send a passphrase to the device at a certain path to open the (possibly hidden) wallet
+	pub fn passphrase_ack(&self, device_path: &str, passphrase: &str) -> Result<bool, Error> {
+		let opened = {
+			let usb = self.usb.lock();
+			let device = self.open_path(|| usb.open_path(&device_path))?;
+			Self::request_address(&device, device_path, self.derivation_path(), &self.sessions, Some(passphrase))
+				.map(|address| address.is_some())
+		};
+		self.update_devices(DeviceDirection::Arrived)?;
+		opened
+	}
+
+	/// Sign a personal message, the device adds the `\x19Ethereum Signed Message:\n` prefix itself
+	pub fn sign_message(&self, address: &Address, msg: &[u8]) -> Result<Signature, Error> {
+		let usb = self.usb.lock();
+		let devices = self.devices.read();
+		let device = devices.iter().find(|d| &d.info.address == address).ok_or(Error::KeyNotFound)?;
+		let handle = self.open_path(|| usb.open_path(&device.path))?;
+		Self::resume_session(&handle, &device.path, &self.sessions)?;
+		Self::sign_message_with(&handle, &device.path, self.derivation_path(), msg, &self.sessions)
+	}
+
+	fn sign_message_with<T: Transport>(handle: &T, path: &str, derivation_path: Vec<u32>, msg: &[u8], sessions: &Sessions) -> Result<Signature, Error> {
+		let mut message = EthereumSignMessage::new();
+		message.set_address_n(derivation_path);
+		message.set_message(msg.to_vec());
+		Self::send_device_message(handle, MessageType::MessageType_EthereumSignMessage, &message)?;
+
+		loop {
+			let (resp_type, bytes) = Self::read_response(handle, path, sessions, None)?;
+			match resp_type {
+				MessageType::MessageType_Cancel => return Err(Error::UserCancel),
+				// Waits for the user to confirm the message on the device
+				MessageType::MessageType_ButtonRequest => {
+					Self::send_device_message(handle, MessageType::MessageType_ButtonAck, &ButtonAck::new())?;
+				}
+				MessageType::MessageType_EthereumMessageSignature => {
+					let resp: EthereumMessageSignature = protobuf::core::parse_from_bytes(&bytes)?;
+					let signature = resp.get_signature();
+					if signature.len() != 65 {
+						return Err(Error::Protocol("Signature packet size mismatch"));
+					}
+					let r = H256::from_slice(&signature[0..32]);
+					let s = H256::from_slice(&signature[32..64]);
+					// v is returned as v + 27
+					let v = if signature[64] < 27 { signature[64] } else { signature[64] - 27 };
+					return Ok(Signature::from_rsv(&r, &s, v));
+				}
+				MessageType::MessageType_Failure => return Err(Error::Protocol("Last message sent to Trezor failed")),
+				_ => return Err(Error::Protocol("Unexpected response from Trezor device.")),
+			}
+		}
+	}
+
+	fn derivation_path(&self) -> Vec<u32> {
+		match *self.key_path.read() {
+			KeyPath::Ethereum => ETH_DERIVATION_PATH.to_vec(),
+			KeyPath::EthereumClassic => ETC_DERIVATION_PATH.to_vec(),
+		}
+	}
+
 	fn u256_to_be_vec(&self, val: &U256) -> Vec<u8> {
 		let mut buf = [0_u8; 32];
 		val.to_big_endian(&mut buf);
 		buf.iter().skip_while(|x| **x == 0).cloned().collect()
 	}
 
-	fn signing_loop(&self, handle: &hidapi::HidDevice, chain_id: &Option<u64>, data: &[u8]) -> Result<Signature, Error> {
-		let (resp_type, bytes) = self.read_device_response(&handle)?;
+	fn signing_loop<T: Transport>(&self, handle: &T, path: &str, chain_id: &Option<u64>, data: &[u8]) -> Result<Signature, Error> {
+		let (resp_type, bytes) = Self::read_response(handle, path, &self.sessions, None)?;
 		match resp_type {
 			MessageType::MessageType_Cancel => Err(Error::UserCancel),
 			MessageType::MessageType_ButtonRequest => {
-				self.send_device_message(handle, MessageType::MessageType_ButtonAck, &ButtonAck::new())?;
+				Self::send_device_message(handle, MessageType::MessageType_ButtonAck, &ButtonAck::new())?;
 				// Signing loop goes back to the top and reading blocks
 				// for up to 5 minutes waiting for response from the device
 				// if the user doesn't click any button within 5 minutes you
 				// get a signing error and the device sort of locks up on the signing screen
-				self.signing_loop(handle, chain_id, data)
+				self.signing_loop(handle, path, chain_id, data)
 			}
 			MessageType::MessageType_EthereumTxRequest => {
 				let resp: EthereumTxRequest = protobuf::core::parse_from_bytes(&bytes)?;
@@ -174,8 +255,8 @@ impl Manager {
 					let mut msg = EthereumTxAck::new();
 					let len = resp.get_data_length() as usize;
 					msg.set_data_chunk(data[..len].to_vec());
-					self.send_device_message(handle, MessageType::MessageType_EthereumTxAck, &msg)?;
-					self.signing_loop(handle, chain_id, &data[len..])
+					Self::send_device_message(handle, MessageType::MessageType_EthereumTxAck, &msg)?;
+					self.signing_loop(handle, path, chain_id, &data[len..])
 				} else {
 					let v = resp.get_signature_v();
 					let r = H256::from_slice(resp.get_signature_r());
@@ -199,12 +280,97 @@ impl Manager {
 		}
 	}
 
-	fn send_device_message(&self, device: &hidapi::HidDevice, msg_type: MessageType, msg: &Message) -> Result<usize, Error> {
+	/// Start a new session on the device, resuming a previous one if its `state` is given
+	fn initialize<T: Transport>(device: &T, state: Option<&[u8]>) -> Result<(), Error> {
+		let mut message = Initialize::new();
+		if let Some(state) = state {
+			message.set_state(state.to_vec());
+		}
+		Self::send_device_message(device, MessageType::MessageType_Initialize, &message)?;
+		match Self::read_device_response(device)?.0 {
+			MessageType::MessageType_Features => Ok(()),
+			_ => Err(Error::Protocol("Unexpected response to Initialize from Trezor device")),
+		}
+	}
+
+	/// Resume the passphrase session cached for the device at `path`, if any
+	fn resume_session<T: Transport>(device: &T, path: &str, sessions: &Sessions) -> Result<(), Error> {
+		let state = sessions.states.read().get(path).cloned();
+		Self::initialize(device, state.as_ref().map(|s| &s[..]))
+	}
+
+	/// Read the response to the last message, going through the passphrase flow if the device asks for it.
+	/// When the passphrase has to be entered on the host and none is given, the device is marked
+	/// as waiting for a passphrase and `Error::PassphraseRequired` is returned.
+	fn read_response<T: Transport>(device: &T, path: &str, sessions: &Sessions, passphrase: Option<&str>) -> Result<(MessageType, Vec<u8>), Error> {
+		loop {
+			let (resp_type, bytes) = Self::read_device_response(device)?;
+			match resp_type {
+				MessageType::MessageType_PassphraseRequest => {
+					let request: PassphraseRequest = protobuf::core::parse_from_bytes(&bytes)?;
+					let mut ack = PassphraseAck::new();
+					// Passphrase entered on the device is acknowledged with an empty message
+					if !request.get_on_device() {
+						match passphrase {
+							Some(passphrase) => ack.set_passphrase(passphrase.to_owned()),
+							None => {
+								sessions.states.write().remove(path);
+								let mut pending = sessions.pending.write();
+								if !pending.iter().any(|p| p == path) {
+									pending.push(path.to_owned());
+								}
+								return Err(Error::PassphraseRequired(path.to_owned()));
+							}
+						}
+					}
+					Self::send_device_message(device, MessageType::MessageType_PassphraseAck, &ack)?;
+				}
+				MessageType::MessageType_PassphraseStateRequest => {
+					let request: PassphraseStateRequest = protobuf::core::parse_from_bytes(&bytes)?;
+					sessions.states.write().insert(path.to_owned(), request.get_state().to_vec());
+					sessions.pending.write().retain(|p| p != path);
+					Self::send_device_message(device, MessageType::MessageType_PassphraseStateAck, &PassphraseStateAck::new())?;
+				}
+				_ => return Ok((resp_type, bytes)),
+			}
+		}
+	}
+
+	/// Request the address at `derivation_path`. If a `passphrase` is given a new session
+	/// is started so that it's used to open the wallet, otherwise the cached session is resumed.
+	fn request_address<T: Transport>(
+		device: &T,
+		path: &str,
+		derivation_path: Vec<u32>,
+		sessions: &Sessions,
+		passphrase: Option<&str>,
+	) -> Result<Option<Address>, Error> {
+		match passphrase {
+			Some(_) => Self::initialize(device, None)?,
+			None => Self::resume_session(device, path, sessions)?,
+		}
+
+		let mut message = EthereumGetAddress::new();
+		message.set_address_n(derivation_path);
+		message.set_show_display(false);
+		Self::send_device_message(device, MessageType::MessageType_EthereumGetAddress, &message)?;
+
+		let (resp_type, bytes) = Self::read_response(device, path, sessions, passphrase)?;
+		match resp_type {
+			MessageType::MessageType_EthereumAddress => {
+				let response: EthereumAddress = protobuf::core::parse_from_bytes(&bytes)?;
+				Ok(Some(From::from(response.get_address())))
+			}
+			_ => Ok(None),
+		}
+	}
+
+	fn send_device_message<T: Transport>(device: &T, msg_type: MessageType, msg: &Message) -> Result<usize, Error> {
 		let msg_id = msg_type as u16;
 		let mut message = msg.write_to_bytes()?;
 		let msg_size = message.len();
 		let mut data = Vec::new();
-		let hid_version = self.probe_hid_version(device)?;
+		let hid_version = Self::probe_hid_version(device)?;
 		// Magic constants
 		data.push(b'#');
 		data.push(b'#');
@@ -232,7 +398,7 @@ impl Manager {
 		Ok(total_written)
 	}
 
-	fn probe_hid_version(&self, device: &hidapi::HidDevice) -> Result<HidVersion, Error> {
+	fn probe_hid_version<T: Transport>(device: &T) -> Result<HidVersion, Error> {
 		let mut buf2 = [0xFF_u8; 65];
 		buf2[0] = 0;
 		buf2[1] = 63;
@@ -247,11 +413,15 @@ impl Manager {
 		}
 	}
 
-	fn read_device_response(&self, device: &hidapi::HidDevice) -> Result<(MessageType, Vec<u8>), Error> {
+	fn read_device_response<T: Transport>(device: &T) -> Result<(MessageType, Vec<u8>), Error> {
 		let protocol_err = Error::Protocol(&"Unexpected wire response from Trezor Device");
 		let mut buf = vec![0; 64];
 
+		// Waits up to 5 minutes for the user to respond on the device
 		let first_chunk = device.read_timeout(&mut buf, 300_000)?;
+		if first_chunk == 0 {
+			return Err(Error::Protocol("Timed out waiting for response from Trezor Device"));
+		}
 		if first_chunk < 9 || buf[0] != b'?' || buf[1] != b'#' || buf[2] != b'#' {
 			return Err(protocol_err);
 		}
@@ -277,12 +447,11 @@ impl<'a> Wallet<'a> for Manager {
 		let devices = self.devices.read();
 		let device = devices.iter().find(|d| &d.info.address == address).ok_or(Error::KeyNotFound)?;
 		let handle = self.open_path(|| usb.open_path(&device.path))?;
+		Self::resume_session(&handle, &device.path, &self.sessions)?;
+
 		let msg_type = MessageType::MessageType_EthereumSignTx;
 		let mut message = EthereumSignTx::new();
-		match *self.key_path.read() {
-			KeyPath::Ethereum => message.set_address_n(ETH_DERIVATION_PATH.to_vec()),
-			KeyPath::EthereumClassic => message.set_address_n(ETC_DERIVATION_PATH.to_vec()),
-		}
+		message.set_address_n(self.derivation_path());
 		message.set_nonce(self.u256_to_be_vec(&t_info.nonce));
 		message.set_gas_limit(self.u256_to_be_vec(&t_info.gas_limit));
 		message.set_gas_price(self.u256_to_be_vec(&t_info.gas_price));
@@ -299,9 +468,9 @@ impl<'a> Wallet<'a> for Manager {
 			message.set_chain_id(c_id as u32);
 		}
 
-		self.send_device_message(&handle, msg_type, &message)?;
+		Self::send_device_message(&handle, msg_type, &message)?;
 
-		self.signing_loop(&handle, &t_info.chain_id, &t_info.data[first_chunk_length..])
+		self.signing_loop(&handle, &device.path, &t_info.chain_id, &t_info.data[first_chunk_length..])
 	}
 
 	fn set_key_path(&self, key_path: KeyPath) {
@@ -332,6 +501,11 @@ impl<'a> Wallet<'a> for Manager {
 		let num_curr_devices = detected_devices.len();
 		*self.devices.write() = detected_devices;
 
+		// Forget the sessions of the devices that have been disconnected
+		let is_connected = |path: &str| devices.iter().any(|d| d.path == path);
+		self.sessions.pending.write().retain(|path| is_connected(path));
+		self.sessions.states.write().retain(|path, _| is_connected(path));
+
 		match device_direction {
 			DeviceDirection::Arrived => {
 				if num_curr_devices > num_prev_devices {
@@ -355,7 +529,7 @@ impl<'a> Wallet<'a> for Manager {
 		let manufacturer = dev_info.manufacturer_string.clone().unwrap_or_else(|| "Unknown".to_owned());
 		let name = dev_info.product_string.clone().unwrap_or_else(|| "Unknown".to_owned());
 		let serial = dev_info.serial_number.clone().unwrap_or_else(|| "Unknown".to_owned());
-		match self.get_address(&handle) {
+		match self.get_address(&handle, &dev_info.path) {
 			Ok(Some(addr)) => {
 				Ok(Device {
 					path: dev_info.path.clone(),
@@ -384,24 +558,8 @@ impl<'a> Wallet<'a> for Manager {
 		self.devices.read().iter().find(|d| &d.info.address == address).map(|d| d.info.clone())
 	}
 
-	fn get_address(&self, device: &hidapi::HidDevice) -> Result<Option<Address>, Error> {
-		let typ = MessageType::MessageType_EthereumGetAddress;
-		let mut message = EthereumGetAddress::new();
-		match *self.key_path.read() {
-			KeyPath::Ethereum => message.set_address_n(ETH_DERIVATION_PATH.to_vec()),
-			KeyPath::EthereumClassic => message.set_address_n(ETC_DERIVATION_PATH.to_vec()),
-		}
-		message.set_show_display(false);
-		self.send_device_message(&device, typ, &message)?;
-
-		let (resp_type, bytes) = self.read_device_response(&device)?;
-		match resp_type {
-			MessageType::MessageType_EthereumAddress => {
-				let response: EthereumAddress = protobuf::core::parse_from_bytes(&bytes)?;
-				Ok(Some(From::from(response.get_address())))
-			}
-			_ => Ok(None),
-		}
+	fn get_address(&self, device: &hidapi::HidDevice, path: &str) -> Result<Option<Address>, Error> {
+		Self::request_address(device, path, self.derivation_path(), &self.sessions, None)
 	}
 
 	fn open_path<R, F>(&self, f: F) -> Result<R, Error>
@@ -461,3 +619,237 @@ fn test_signature() {
 	let signature = manager.trezor.sign_transaction(&addr, &t_info);
 	assert!(signature.is_ok());
 }
+
+#[cfg(test)]
+mod tests {
+	use std::cell::RefCell;
+	use std::collections::VecDeque;
+	use trezor_sys::messages::{Features, ButtonRequest};
+	use super::*;
+
+	const PATH: &str = "0001:0002:00";
+
+	/// Scripted device answering each message with a single response.
+	struct MockTransport {
+		exchanges: RefCell<VecDeque<(MessageType, Vec<u8>, MessageType, Vec<u8>)>>,
+		request: RefCell<Option<(MessageType, usize, Vec<u8>)>>,
+		responses: RefCell<VecDeque<Vec<u8>>>,
+	}
+
+	impl MockTransport {
+		fn new() -> Self {
+			MockTransport {
+				exchanges: RefCell::new(VecDeque::new()),
+				request: RefCell::new(None),
+				responses: RefCell::new(VecDeque::new()),
+			}
+		}
+
+		fn expect(self, request_type: MessageType, request: &Message, response_type: MessageType, response: &Message) -> Self {
+			self.exchanges.borrow_mut().push_back((
+				request_type,
+				request.write_to_bytes().unwrap(),
+				response_type,
+				response.write_to_bytes().unwrap(),
+			));
+			self
+		}
+
+		fn assert_done(&self) {
+			assert!(self.exchanges.borrow().is_empty(), "Not all messages have been sent");
+		}
+
+		fn respond(&self, msg_type: MessageType, payload: &[u8]) {
+			let msg_id = msg_type as u16;
+			let size = payload.len();
+			let mut data = vec![b'#', b'#', (msg_id >> 8) as u8, msg_id as u8, (size >> 24) as u8, (size >> 16) as u8, (size >> 8) as u8, size as u8];
+			data.extend_from_slice(payload);
+			for chunk in data.chunks(63) {
+				let mut packet = vec![b'?'];
+				packet.extend_from_slice(chunk);
+				packet.resize(64, 0);
+				self.responses.borrow_mut().push_back(packet);
+			}
+		}
+	}
+
+	impl Transport for MockTransport {
+		fn write(&self, data: &[u8]) -> Result<usize, hidapi::HidError> {
+			// HID v2 packets, prefixed with the report number
+			assert_eq!(data.len(), 65);
+			let mut request = self.request.borrow_mut();
+			match request.take() {
+				Some((msg_type, size, mut payload)) => {
+					payload.extend_from_slice(&data[2..]);
+					*request = Some((msg_type, size, payload));
+				}
+				None if data[2] == b'#' && data[3] == b'#' => {
+					let msg_type = MessageType::from_i32((data[4] as i32) << 8 | data[5] as i32).expect("Unknown message type");
+					let size = (data[6] as usize) << 24 | (data[7] as usize) << 16 | (data[8] as usize) << 8 | data[9] as usize;
+					*request = Some((msg_type, size, data[10..].to_vec()));
+				}
+				// HID version probe
+				None => return Ok(data.len()),
+			}
+
+			let complete = request.as_ref().map_or(false, |&(_, size, ref payload)| payload.len() >= size);
+			if complete {
+				let (msg_type, size, mut payload) = request.take().unwrap();
+				payload.truncate(size);
+				let (expected_type, expected, response_type, response) = self.exchanges.borrow_mut().pop_front().expect("Unexpected message");
+				assert_eq!(msg_type, expected_type);
+				assert_eq!(payload, expected);
+				self.respond(response_type, &response);
+			}
+			Ok(data.len())
+		}
+
+		fn read_timeout(&self, buf: &mut [u8], _timeout: i32) -> Result<usize, hidapi::HidError> {
+			match self.responses.borrow_mut().pop_front() {
+				Some(packet) => {
+					buf[..packet.len()].copy_from_slice(&packet);
+					Ok(packet.len())
+				}
+				// the device hasn't responded in time
+				None => Ok(0),
+			}
+		}
+	}
+
+	fn initialize(state: Option<&[u8]>) -> Initialize {
+		let mut message = Initialize::new();
+		if let Some(state) = state {
+			message.set_state(state.to_vec());
+		}
+		message
+	}
+
+	fn get_address() -> EthereumGetAddress {
+		let mut message = EthereumGetAddress::new();
+		message.set_address_n(ETH_DERIVATION_PATH.to_vec());
+		message.set_show_display(false);
+		message
+	}
+
+	fn address(address: &Address) -> EthereumAddress {
+		let mut message = EthereumAddress::new();
+		message.set_address(address.to_vec());
+		message
+	}
+
+	fn passphrase_request(on_device: bool) -> PassphraseRequest {
+		let mut message = PassphraseRequest::new();
+		message.set_on_device(on_device);
+		message
+	}
+
+	fn passphrase_ack(passphrase: Option<&str>) -> PassphraseAck {
+		let mut message = PassphraseAck::new();
+		if let Some(passphrase) = passphrase {
+			message.set_passphrase(passphrase.to_owned());
+		}
+		message
+	}
+
+	fn state_request(state: &[u8]) -> PassphraseStateRequest {
+		let mut message = PassphraseStateRequest::new();
+		message.set_state(state.to_vec());
+		message
+	}
+
+	#[test]
+	fn passphrase_entered_on_device() {
+		let sessions = Sessions::default();
+		let expected = Address::from(1337);
+		let device = MockTransport::new()
+			.expect(MessageType::MessageType_Initialize, &initialize(None), MessageType::MessageType_Features, &Features::new())
+			.expect(MessageType::MessageType_EthereumGetAddress, &get_address(), MessageType::MessageType_PassphraseRequest, &passphrase_request(true))
+			.expect(MessageType::MessageType_PassphraseAck, &passphrase_ack(None), MessageType::MessageType_PassphraseStateRequest, &state_request(&[1, 2, 3]))
+			.expect(MessageType::MessageType_PassphraseStateAck, &PassphraseStateAck::new(), MessageType::MessageType_EthereumAddress, &address(&expected));
+
+		let address = Manager::request_address(&device, PATH, ETH_DERIVATION_PATH.to_vec(), &sessions, None).unwrap();
+
+		device.assert_done();
+		assert_eq!(address, Some(expected));
+		assert!(sessions.pending.read().is_empty());
+		assert_eq!(sessions.states.read().get(PATH), Some(&vec![1, 2, 3]));
+	}
+
+	#[test]
+	fn passphrase_entered_on_host() {
+		let sessions = Sessions::default();
+		let expected = Address::from(1337);
+		let device = MockTransport::new()
+			.expect(MessageType::MessageType_Initialize, &initialize(None), MessageType::MessageType_Features, &Features::new())
+			.expect(MessageType::MessageType_EthereumGetAddress, &get_address(), MessageType::MessageType_PassphraseRequest, &passphrase_request(false));
+
+		match Manager::request_address(&device, PATH, ETH_DERIVATION_PATH.to_vec(), &sessions, None) {
+			Err(Error::PassphraseRequired(ref path)) if path == PATH => {},
+			other => panic!("Expected passphrase request, got {:?}", other),
+		}
+		device.assert_done();
+		assert_eq!(*sessions.pending.read(), vec![PATH.to_owned()]);
+
+		let device = device
+			.expect(MessageType::MessageType_Initialize, &initialize(None), MessageType::MessageType_Features, &Features::new())
+			.expect(MessageType::MessageType_EthereumGetAddress, &get_address(), MessageType::MessageType_PassphraseRequest, &passphrase_request(false))
+			.expect(MessageType::MessageType_PassphraseAck, &passphrase_ack(Some("hidden")), MessageType::MessageType_PassphraseStateRequest, &state_request(&[4, 5, 6]))
+			.expect(MessageType::MessageType_PassphraseStateAck, &PassphraseStateAck::new(), MessageType::MessageType_EthereumAddress, &address(&expected));
+
+		let address = Manager::request_address(&device, PATH, ETH_DERIVATION_PATH.to_vec(), &sessions, Some("hidden")).unwrap();
+
+		device.assert_done();
+		assert_eq!(address, Some(expected));
+		assert!(sessions.pending.read().is_empty());
+		assert_eq!(sessions.states.read().get(PATH), Some(&vec![4, 5, 6]));
+	}
+
+	#[test]
+	fn signs_personal_message() {
+		let sessions = Sessions::default();
+		let mut request = EthereumSignMessage::new();
+		request.set_address_n(ETH_DERIVATION_PATH.to_vec());
+		request.set_message(b"hello".to_vec());
+		let mut response = EthereumMessageSignature::new();
+		let mut signature = vec![0x11; 32];
+		signature.extend_from_slice(&[0x22; 32]);
+		signature.push(28);
+		response.set_signature(signature);
+		let device = MockTransport::new()
+			.expect(MessageType::MessageType_EthereumSignMessage, &request, MessageType::MessageType_ButtonRequest, &ButtonRequest::new())
+			.expect(MessageType::MessageType_ButtonAck, &ButtonAck::new(), MessageType::MessageType_EthereumMessageSignature, &response);
+
+		let signature = Manager::sign_message_with(&device, PATH, ETH_DERIVATION_PATH.to_vec(), b"hello", &sessions).unwrap();
+
+		device.assert_done();
+		assert_eq!(signature.v(), 1);
+		assert_eq!(signature.r(), &[0x11; 32]);
+		assert_eq!(signature.s(), &[0x22; 32]);
+	}
+
+	#[test]
+	fn times_out_waiting_for_confirmation() {
+		let sessions = Sessions::default();
+		let device = MockTransport::new();
+
+		match Manager::read_response(&device, PATH, &sessions, None) {
+			Err(Error::Protocol(_)) => {},
+			other => panic!("Expected timeout, got {:?}", other),
+		}
+	}
+
+	#[test]
+	fn resumes_cached_session() {
+		let sessions = Sessions::default();
+		sessions.states.write().insert(PATH.to_owned(), vec![4, 5, 6]);
+		let expected = Address::from(1337);
+		let device = MockTransport::new()
+			.expect(MessageType::MessageType_Initialize, &initialize(Some(&[4, 5, 6])), MessageType::MessageType_Features, &Features::new())
+			.expect(MessageType::MessageType_EthereumGetAddress, &get_address(), MessageType::MessageType_EthereumAddress, &address(&expected));
+
+		let address = Manager::request_address(&device, PATH, ETH_DERIVATION_PATH.to_vec(), &sessions, None).unwrap();
+
+		device.assert_done();
+		assert_eq!(address, Some(expected));
+	}
+}
@@ -42,6 +42,8 @@ pub enum Unlock {
 pub struct AccountData {
 	pub unlock: Unlock,
 	pub password: Password,
+	/// When the account was unlocked or last used.
+	pub last_used: Instant,
 }
 
 /// Collected account metadata
@@ -19,6 +19,8 @@ use std::fmt;
 use ethstore::{Error as SSError};
 use hardware_wallet::{Error as HardwareError};
 
+use crate::PolicyViolation;
+
 /// Signing error
 #[derive(Debug)]
 pub enum SignError {
@@ -30,6 +32,8 @@ pub enum SignError {
 	Hardware(HardwareError),
 	/// Low-level error from store
 	SStore(SSError),
+	/// Transaction is not allowed by the account policy.
+	PolicyViolation(PolicyViolation),
 }
 
 impl fmt::Display for SignError {
@@ -39,6 +43,7 @@ impl fmt::Display for SignError {
 			SignError::NotFound => write!(f, "Account does not exist"),
 			SignError::Hardware(ref e) => write!(f, "{}", e),
 			SignError::SStore(ref e) => write!(f, "{}", e),
+			SignError::PolicyViolation(ref e) => write!(f, "{}", e),
 		}
 	}
 }
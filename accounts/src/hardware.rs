@@ -0,0 +1,83 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Hardware wallets used by the account provider.
+
+use ethkey::{Address, Signature};
+use hardware_wallet::{Error as HardwareError, HardwareWalletManager, TransactionInfo, WalletInfo};
+
+/// Hardware wallet operations used by the account provider.
+/// Implemented by `HardwareWalletManager`, abstracted to allow testing signing flows without a device.
+pub trait HardwareWallets: Send + Sync {
+	/// List connected wallets that are ready to be used.
+	fn list_wallets(&self) -> Vec<WalletInfo>;
+
+	/// Return a list of paths to locked hardware wallets.
+	fn list_locked_wallets(&self) -> Result<Vec<String>, HardwareError>;
+
+	/// Get connected wallet info.
+	fn wallet_info(&self, address: &Address) -> Option<WalletInfo>;
+
+	/// Sign a personal message with the wallet managing `address`.
+	fn sign_message(&self, address: &Address, msg: &[u8]) -> Result<Signature, HardwareError>;
+
+	/// Sign transaction data with the wallet managing `address`.
+	fn sign_transaction(&self, address: &Address, t_info: &TransactionInfo, encoded_transaction: &[u8]) -> Result<Signature, HardwareError>;
+
+	/// Send a pin to a device at a certain path to unlock it.
+	fn pin_matrix_ack(&self, path: &str, pin: &str) -> Result<bool, HardwareError>;
+
+	/// Return a list of paths to hardware wallets waiting for a passphrase.
+	fn list_passphrase_requests(&self) -> Result<Vec<String>, HardwareError>;
+
+	/// Send a passphrase to a device at a certain path to open the hidden wallet.
+	fn passphrase_ack(&self, path: &str, passphrase: &str) -> Result<bool, HardwareError>;
+}
+
+impl HardwareWallets for HardwareWalletManager {
+	fn list_wallets(&self) -> Vec<WalletInfo> {
+		HardwareWalletManager::list_wallets(self)
+	}
+
+	fn list_locked_wallets(&self) -> Result<Vec<String>, HardwareError> {
+		HardwareWalletManager::list_locked_wallets(self)
+	}
+
+	fn wallet_info(&self, address: &Address) -> Option<WalletInfo> {
+		HardwareWalletManager::wallet_info(self, address)
+	}
+
+	fn sign_message(&self, address: &Address, msg: &[u8]) -> Result<Signature, HardwareError> {
+		HardwareWalletManager::sign_message(self, address, msg)
+	}
+
+	fn sign_transaction(&self, address: &Address, t_info: &TransactionInfo, encoded_transaction: &[u8]) -> Result<Signature, HardwareError> {
+		HardwareWalletManager::sign_transaction(self, address, t_info, encoded_transaction)
+	}
+
+	fn pin_matrix_ack(&self, path: &str, pin: &str) -> Result<bool, HardwareError> {
+		HardwareWalletManager::pin_matrix_ack(self, path, pin)
+	}
+
+	fn list_passphrase_requests(&self) -> Result<Vec<String>, HardwareError> {
+		HardwareWalletManager::list_passphrase_requests(self)
+	}
+
+	fn passphrase_ack(&self, path: &str, passphrase: &str) -> Result<bool, HardwareError> {
+		HardwareWalletManager::passphrase_ack(self, path, passphrase)
+	}
+}
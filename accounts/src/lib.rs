@@ -20,16 +20,18 @@
 
 mod account_data;
 mod error;
+mod hardware;
+mod policy;
 mod stores;
 
 #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
 extern crate fake_hardware_wallet as hardware_wallet;
 
 use self::account_data::{Unlock, AccountData};
-use self::stores::AddressBook;
+use self::stores::{AddressBook, PolicyStore};
 
-use std::collections::HashMap;
-use std::time::{Instant, Duration};
+use std::collections::{HashMap, HashSet};
+use std::time::{Instant, Duration, SystemTime, UNIX_EPOCH};
 
 use common_types::transaction::{Action, Transaction};
 use ethkey::{Address, Message, Public, Secret, Password, Random, Generator};
@@ -41,12 +43,14 @@ use ethstore::{
 use log::{warn, debug};
 use parking_lot::RwLock;
 
-pub use ethkey::Signature;
+pub use ethkey::{DerivationPath, Signature};
 pub use ethstore::{Derivation, IndexDerivation, KeyFile, Error};
-pub use hardware_wallet::{Error as HardwareError, HardwareWalletManager, KeyPath, TransactionInfo};
+pub use hardware_wallet::{Error as HardwareError, HardwareWalletManager, KeyPath, TransactionInfo, WalletInfo};
 
 pub use self::account_data::AccountMeta;
 pub use self::error::SignError;
+pub use self::hardware::HardwareWallets;
+pub use self::policy::{AccountPolicy, PolicyViolation, RefusedTransaction};
 
 type AccountToken = Password;
 
@@ -61,6 +65,10 @@ pub struct AccountProviderSettings {
 	pub unlock_keep_secret: bool,
 	/// Disallowed accounts.
 	pub blacklisted_accounts: Vec<Address>,
+	/// Lock unlocked accounts that haven't been used for this long.
+	pub autolock: Option<Duration>,
+	/// Exempt permanently unlocked accounts from `autolock`.
+	pub allow_permanent_unlock: bool,
 }
 
 /// Account management.
@@ -72,17 +80,23 @@ pub struct AccountProvider {
 	unlocked: RwLock<HashMap<StoreAccountRef, AccountData>>,
 	/// Address book.
 	address_book: RwLock<AddressBook>,
+	/// Signing policies.
+	policies: RwLock<PolicyStore>,
 	/// Accounts on disk
 	sstore: Box<SecretStore>,
 	/// Accounts unlocked with rolling tokens
 	transient_sstore: EthMultiStore,
 	/// Accounts in hardware wallets.
-	hardware_store: Option<HardwareWalletManager>,
+	hardware_store: Option<Box<HardwareWallets>>,
 	/// When unlocking account permanently we additionally keep a raw secret in memory
 	/// to increase the performance of transaction signing.
 	unlock_keep_secret: bool,
 	/// Disallowed accounts.
 	blacklisted_accounts: Vec<Address>,
+	/// Idle period after which unlocked accounts are locked again.
+	autolock: Option<Duration>,
+	/// Permanently unlocked accounts are never locked automatically.
+	allow_permanent_unlock: bool,
 }
 
 fn transient_sstore() -> EthMultiStore {
@@ -98,7 +112,7 @@ impl AccountProvider {
 			match HardwareWalletManager::new() {
 				Ok(manager) => {
 					manager.set_key_path(if settings.hardware_wallet_classic_key { KeyPath::EthereumClassic } else { KeyPath::Ethereum });
-					hardware_store = Some(manager)
+					hardware_store = Some(Box::new(manager) as Box<HardwareWallets>)
 				},
 				Err(e) => debug!("Error initializing hardware wallets: {}", e),
 			}
@@ -117,15 +131,20 @@ impl AccountProvider {
 			address_book.remove(*addr);
 		}
 
+		let policies = PolicyStore::new(&sstore.local_path());
+
 		AccountProvider {
 			unlocked_secrets: RwLock::new(HashMap::new()),
 			unlocked: RwLock::new(HashMap::new()),
 			address_book: RwLock::new(address_book),
+			policies: RwLock::new(policies),
 			sstore: sstore,
 			transient_sstore: transient_sstore(),
 			hardware_store: hardware_store,
 			unlock_keep_secret: settings.unlock_keep_secret,
 			blacklisted_accounts: settings.blacklisted_accounts,
+			autolock: settings.autolock,
+			allow_permanent_unlock: settings.allow_permanent_unlock,
 		}
 	}
 
@@ -135,14 +154,24 @@ impl AccountProvider {
 			unlocked_secrets: RwLock::new(HashMap::new()),
 			unlocked: RwLock::new(HashMap::new()),
 			address_book: RwLock::new(AddressBook::transient()),
+			policies: RwLock::new(PolicyStore::transient()),
 			sstore: Box::new(EthStore::open(Box::new(MemoryDirectory::default())).expect("MemoryDirectory load always succeeds; qed")),
 			transient_sstore: transient_sstore(),
 			hardware_store: None,
 			unlock_keep_secret: false,
 			blacklisted_accounts: vec![],
+			autolock: None,
+			allow_permanent_unlock: false,
 		}
 	}
 
+	/// Uses given hardware wallets instead of the devices connected to this machine.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn with_hardware_wallets(mut self, wallets: Box<HardwareWallets>) -> Self {
+		self.hardware_store = Some(wallets);
+		self
+	}
+
 	/// Creates new random account.
 	pub fn new_account(&self, password: &Password) -> Result<Address, Error> {
 		self.new_account_and_public(password).map(|d| d.0)
@@ -168,6 +197,17 @@ impl AccountProvider {
 		Ok(account.address)
 	}
 
+	/// Inserts new hierarchical deterministic account with master key generated from given seed.
+	/// Child accounts can be derived from it using BIP-32 derivation paths.
+	pub fn insert_hd_seed(&self, seed: &[u8], password: &Password) -> Result<Address, Error> {
+		let account = self.sstore.insert_hd_seed(SecretVaultRef::Root, seed, password)?;
+		if self.blacklisted_accounts.contains(&account.address) {
+			self.sstore.remove_account(&account, password)?;
+			return Err(Error::InvalidAccount.into());
+		}
+		Ok(account.address)
+	}
+
 	/// Generates new derived account based on the existing one
 	/// If password is not provided, account must be unlocked
 	/// New account will be created with the same password (if save: true)
@@ -198,6 +238,32 @@ impl AccountProvider {
 		Ok(Address::from(account.address).into())
 	}
 
+	/// Import many presale wallets at once, each with its own password.
+	/// Returns the result of importing every single wallet.
+	pub fn import_presales(&self, wallets: &[(&[u8], &Password)]) -> Vec<Result<Address, Error>> {
+		self.sstore.import_presales(SecretVaultRef::Root, wallets)
+			.into_iter()
+			.map(|account| account.map(|account| account.address))
+			.collect()
+	}
+
+	/// Import many wallets at once, each with its own password.
+	/// Returns the result of importing every single wallet.
+	pub fn import_wallets(&self, wallets: &[(&[u8], &Password)], gen_id: bool) -> Vec<Result<Address, Error>> {
+		self.sstore.import_wallets(SecretVaultRef::Root, wallets, gen_id)
+			.into_iter()
+			.zip(wallets)
+			.map(|(account, &(_, password))| {
+				let account = account?;
+				if self.blacklisted_accounts.contains(&account.address) {
+					self.sstore.remove_account(&account, password)?;
+					return Err(Error::InvalidAccount);
+				}
+				Ok(account.address)
+			})
+			.collect()
+	}
+
 	/// Checks whether an account with a given address is present.
 	pub fn has_account(&self, address: Address) -> bool {
 		self.sstore.account_ref(&address).is_ok() && !self.blacklisted_accounts.contains(&address)
@@ -247,6 +313,24 @@ impl AccountProvider {
 		}
 	}
 
+	/// Returns the paths to hardware wallets waiting for a passphrase
+	pub fn hardware_passphrase_requests(&self) -> Result<Vec<String>, SignError> {
+		match self.hardware_store.as_ref().map(|h| h.list_passphrase_requests()) {
+			None => Err(SignError::NotFound),
+			Some(Err(e)) => Err(SignError::Hardware(e)),
+			Some(Ok(s)) => Ok(s),
+		}
+	}
+
+	/// Provide a passphrase to a hardware wallet on USB path to open the wallet it protects
+	pub fn hardware_passphrase_ack(&self, path: &str, passphrase: &str) -> Result<bool, SignError> {
+		match self.hardware_store.as_ref().map(|h| h.passphrase_ack(path, passphrase)) {
+			None => Err(SignError::NotFound),
+			Some(Err(e)) => Err(SignError::Hardware(e)),
+			Some(Ok(s)) => Ok(s),
+		}
+	}
+
 	/// Returns each address along with metadata.
 	pub fn addresses_info(&self) -> HashMap<Address, AccountMeta> {
 		self.address_book.read().get()
@@ -326,6 +410,63 @@ impl AccountProvider {
 		Ok(())
 	}
 
+	/// Returns signing policy of given account.
+	pub fn account_policy(&self, address: &Address) -> Option<AccountPolicy> {
+		self.policies.read().get(address)
+	}
+
+	/// Sets signing policy of given account. Empty policy removes all restrictions.
+	pub fn set_account_policy(&self, address: Address, policy: AccountPolicy) -> Result<(), Error> {
+		if !self.is_hardware_address(&address) {
+			self.sstore.account_ref(&address)?;
+		}
+		self.policies.write().set(address, policy);
+		Ok(())
+	}
+
+	/// Returns transactions of given account refused by its signing policy, oldest first.
+	/// These are not part of the local transactions of the miner, as they were never signed.
+	pub fn policy_violations(&self, address: &Address) -> Vec<RefusedTransaction> {
+		self.policies.read().violations(address)
+	}
+
+	/// Checks if the account is allowed to sign given transaction, journaling the violation if it isn't.
+	fn check_transaction_policy(&self, address: &Address, transaction: &Transaction, chain_id: Option<u64>) -> Result<(), SignError> {
+		let policy = match self.policies.read().get(address) {
+			Some(policy) => policy,
+			None => return Ok(()),
+		};
+
+		policy.check(transaction).map_err(|e| {
+			warn!(target: "own_tx", "Transaction from {:?} refused by account policy (nonce {}): {}", address, transaction.nonce, e);
+			let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+			self.policies.write().record_violation(*address, RefusedTransaction {
+				hash: transaction.hash(chain_id),
+				nonce: transaction.nonce,
+				reason: e.to_string(),
+				timestamp,
+			});
+			SignError::PolicyViolation(e)
+		})
+	}
+
+	/// Signs transaction if it's allowed by the account policy. If password is not provided the account must be unlocked.
+	pub fn sign_transaction(&self, address: Address, password: Option<Password>, transaction: &Transaction, chain_id: Option<u64>)
+		-> Result<Signature, SignError>
+	{
+		self.check_transaction_policy(&address, transaction, chain_id)?;
+		self.sign(address, password, transaction.hash(chain_id))
+	}
+
+	/// Signs transaction with given token if it's allowed by the account policy.
+	/// Returns a token to use in next operation for this account.
+	pub fn sign_transaction_with_token(&self, address: Address, token: AccountToken, transaction: &Transaction, chain_id: Option<u64>)
+		-> Result<(Signature, AccountToken), SignError>
+	{
+		self.check_transaction_policy(&address, transaction, chain_id)?;
+		self.sign_with_token(address, token, transaction.hash(chain_id))
+	}
+
 	/// Returns `true` if the password for `account` is `password`. `false` if not.
 	pub fn test_password(&self, address: &Address, password: &Password) -> Result<bool, Error> {
 		self.sstore.test_password(&self.sstore.account_ref(&address)?, password)
@@ -343,9 +484,12 @@ impl AccountProvider {
 		self.sstore.change_password(&self.sstore.account_ref(address)?, &password, &new_password)
 	}
 
-	/// Exports an account for given address.
-	pub fn export_account(&self, address: &Address, password: Password) -> Result<KeyFile, Error> {
-		self.sstore.export_account(&self.sstore.account_ref(address)?, &password)
+	/// Exports an account for given address, optionally re-encrypted with `new_password`.
+	pub fn export_account(&self, address: &Address, password: Password, new_password: Option<Password>) -> Result<KeyFile, Error> {
+		if self.is_hardware_address(address) {
+			return Err(Error::Custom("Hardware wallet accounts can't be exported".into()));
+		}
+		self.sstore.export_account(&self.sstore.account_ref(address)?, &password, new_password.as_ref())
 	}
 
 	/// Helper method used for unlocking accounts.
@@ -373,6 +517,7 @@ impl AccountProvider {
 		let data = AccountData {
 			unlock: unlock,
 			password: password,
+			last_used: Instant::now(),
 		};
 
 		unlocked.insert(account, data);
@@ -380,8 +525,17 @@ impl AccountProvider {
 	}
 
 	fn password(&self, account: &StoreAccountRef) -> Result<Password, SignError> {
+		let now = Instant::now();
 		let mut unlocked = self.unlocked.write();
 		let data = unlocked.get(account).ok_or(SignError::NotUnlocked)?.clone();
+		if self.is_idle(&data, now) {
+			unlocked.remove(account).expect("data exists: so key must exist: qed");
+			self.unlocked_secrets.write().remove(account);
+			return Err(SignError::NotUnlocked);
+		}
+		if let Some(data) = unlocked.get_mut(account) {
+			data.last_used = now;
+		}
 		if let Unlock::OneTime = data.unlock {
 			unlocked.remove(account).expect("data exists: so key must exist: qed");
 		}
@@ -394,6 +548,52 @@ impl AccountProvider {
 		Ok(data.password)
 	}
 
+	/// Checks if the account hasn't been used for longer than the autolock period.
+	fn is_idle(&self, data: &AccountData, now: Instant) -> bool {
+		if self.allow_permanent_unlock && data.unlock == Unlock::Perm {
+			return false;
+		}
+		self.autolock.map_or(false, |timeout| now.duration_since(data.last_used) > timeout)
+	}
+
+	/// Locks accounts that haven't been used for longer than the autolock period.
+	/// Returns addresses of the accounts that got locked.
+	pub fn lock_idle_accounts(&self) -> Vec<Address> {
+		if self.autolock.is_none() {
+			return Vec::new();
+		}
+
+		let now = Instant::now();
+		let mut unlocked = self.unlocked.write();
+		let idle: Vec<StoreAccountRef> = unlocked.iter()
+			.filter(|&(_, data)| self.is_idle(data, now))
+			.map(|(account, _)| account.clone())
+			.collect();
+
+		let mut unlocked_secrets = self.unlocked_secrets.write();
+		for account in &idle {
+			unlocked.remove(account);
+			unlocked_secrets.remove(account);
+		}
+
+		idle.into_iter().map(|account| account.address).collect()
+	}
+
+	/// Picks up key files added, removed or modified outside of this provider.
+	/// Unlocked accounts whose key files are gone are locked.
+	/// Returns `true` if accounts were reloaded.
+	pub fn refresh_accounts(&self) -> Result<bool, Error> {
+		if !self.sstore.refresh()? {
+			return Ok(false);
+		}
+
+		let accounts: HashSet<StoreAccountRef> = self.sstore.accounts()?.into_iter().collect();
+		let mut unlocked = self.unlocked.write();
+		unlocked.retain(|account, _| accounts.contains(account));
+		self.unlocked_secrets.write().retain(|account, _| accounts.contains(account));
+		Ok(true)
+	}
+
 	/// Unlocks account permanently.
 	pub fn unlock_account_permanently(&self, account: Address, password: Password) -> Result<(), Error> {
 		self.unlock_account(account, password, Unlock::Perm)
@@ -429,15 +629,15 @@ impl AccountProvider {
 	/// Signs the message. If password is not provided the account must be unlocked.
 	pub fn sign(&self, address: Address, password: Option<Password>, message: Message) -> Result<Signature, SignError> {
 		let account = self.sstore.account_ref(&address)?;
-		match self.unlocked_secrets.read().get(&account) {
-			Some(secret) => {
-				Ok(self.sstore.sign_with_secret(&secret, &message)?)
-			},
-			None => {
-				let password = password.map(Ok).unwrap_or_else(|| self.password(&account))?;
-				Ok(self.sstore.sign(&account, &password, &message)?)
+		// looking up the password renews the idle timer (or locks the account if it expired)
+		if self.unlocked_secrets.read().contains_key(&account) && self.password(&account).is_ok() {
+			if let Some(secret) = self.unlocked_secrets.read().get(&account) {
+				return Ok(self.sstore.sign_with_secret(&secret, &message)?);
 			}
 		}
+
+		let password = password.map(Ok).unwrap_or_else(|| self.password(&account))?;
+		Ok(self.sstore.sign(&account, &password, &message)?)
 	}
 
 	/// Signs message using the derived secret. If password is not provided the account must be unlocked.
@@ -555,6 +755,12 @@ impl AccountProvider {
 			.map_err(Into::into)
 	}
 
+	/// Rename opened vault.
+	pub fn rename_vault(&self, name: &str, new_name: &str) -> Result<(), Error> {
+		self.sstore.rename_vault(name, new_name)
+			.map_err(Into::into)
+	}
+
 	/// Change vault of the given address.
 	pub fn change_vault(&self, address: Address, new_vault: &str) -> Result<(), Error> {
 		let new_vault_ref = if new_vault.is_empty() { SecretVaultRef::Root } else { SecretVaultRef::Vault(new_vault.to_owned()) };
@@ -599,6 +805,7 @@ impl AccountProvider {
 			data: transaction.data.to_vec(),
 			chain_id: chain_id,
 		};
+		self.check_transaction_policy(address, transaction, chain_id)?;
 		match self.hardware_store.as_ref().map(|s| s.sign_transaction(&address, &t_info, rlp_encoded_transaction)) {
 			None | Some(Err(HardwareError::KeyNotFound)) => Err(SignError::NotFound),
 			Some(Err(e)) => Err(From::from(e)),
@@ -609,11 +816,14 @@ impl AccountProvider {
 
 #[cfg(test)]
 mod tests {
-	use super::{AccountProvider, Unlock};
+	use super::{AccountProvider, Unlock, DerivationPath, AccountPolicy, PolicyViolation, SignError};
 	use std::time::{Duration, Instant};
+	use common_types::transaction::{Action, Transaction};
 	use ethkey::{Generator, Random, Address};
-	use ethstore::{StoreAccountRef, Derivation};
+	use ethstore::{EthStore, StoreAccountRef, Derivation};
+	use ethstore::accounts_dir::RootDiskDirectory;
 	use ethereum_types::H256;
+	use tempdir::TempDir;
 
 	#[test]
 	fn unlock_account_temp() {
@@ -695,6 +905,27 @@ mod tests {
 			"Signed messages should match");
 	}
 
+	#[test]
+	fn hd_account_derive_and_sign() {
+		let ap = AccountProvider::transient_provider();
+		let root = ap.insert_hd_seed(b"some seed of sufficient length!!", &"base".into())
+			.expect("Valid seed should be inserted");
+		let path: DerivationPath = "m/44'/60'/0'/0/3".parse().unwrap();
+
+		let derived_addr = ap.derive_account(&root, Some("base".into()), Derivation::from(&path), true)
+			.expect("Derivation should not fail");
+		assert!(ap.derive_account(&root, Some("base".into()), Derivation::from(&path), false).unwrap() == derived_addr,
+			"Derivation should be deterministic");
+
+		let msg = Default::default();
+		let signed_msg1 = ap.sign(derived_addr, Some("base".into()), msg)
+			.expect("Saved derived account should be usable for signing");
+		let signed_msg2 = ap.sign_derived(&root, Some("base".into()), Derivation::from(&path), msg)
+			.expect("Derived signing should not fail");
+
+		assert_eq!(signed_msg1, signed_msg2, "Signed messages should match");
+	}
+
 	#[test]
 	fn unlock_account_perm() {
 		let kp = Random.generate().unwrap();
@@ -721,6 +952,120 @@ mod tests {
 		assert!(ap.sign(kp.address(), None, Default::default()).is_err());
 	}
 
+	#[test]
+	fn unlocked_account_autolock_renews_on_use() {
+		let kp = Random.generate().unwrap();
+		let mut ap = AccountProvider::transient_provider();
+		ap.autolock = Some(Duration::from_secs(60));
+		assert!(ap.insert_account(kp.secret().clone(), &"test".into()).is_ok());
+		assert!(ap.unlock_account_permanently(kp.address(), "test".into()).is_ok());
+		let account = StoreAccountRef::root(kp.address());
+
+		// used just before the timeout
+		ap.unlocked.write().get_mut(&account).unwrap().last_used = Instant::now() - Duration::from_secs(59);
+		assert!(ap.sign(kp.address(), None, Default::default()).is_ok());
+		assert!(ap.lock_idle_accounts().is_empty());
+		assert!(ap.is_unlocked(&kp.address()));
+
+		// idle past the timeout
+		ap.unlocked.write().get_mut(&account).unwrap().last_used = Instant::now() - Duration::from_secs(61);
+		assert_eq!(ap.lock_idle_accounts(), vec![kp.address()]);
+		assert!(!ap.is_unlocked(&kp.address()));
+		assert!(ap.sign(kp.address(), None, Default::default()).is_err());
+	}
+
+	#[test]
+	fn should_refresh_accounts_changed_on_disk() {
+		let temp = TempDir::new("").unwrap();
+		let open = || {
+			let store = EthStore::open(Box::new(RootDiskDirectory::create(temp.path()).unwrap())).unwrap();
+			store.set_refresh_time(Duration::from_secs(0));
+			AccountProvider::new(Box::new(store), Default::default())
+		};
+		let ap = open();
+		let external = open();
+		let kp = Random.generate().unwrap();
+
+		// key file added
+		external.insert_account(kp.secret().clone(), &"test".into()).unwrap();
+		assert!(ap.refresh_accounts().unwrap());
+		assert_eq!(ap.accounts().unwrap(), vec![kp.address()]);
+		assert!(!ap.refresh_accounts().unwrap());
+		assert!(ap.unlock_account_permanently(kp.address(), "test".into()).is_ok());
+
+		// key file removed
+		external.kill_account(&kp.address(), &"test".into()).unwrap();
+		assert!(ap.refresh_accounts().unwrap());
+		assert!(ap.accounts().unwrap().is_empty());
+		assert!(!ap.is_unlocked(&kp.address()));
+	}
+
+	#[test]
+	fn unlocked_account_autolock_expires() {
+		let kp = Random.generate().unwrap();
+		let mut ap = AccountProvider::transient_provider();
+		ap.autolock = Some(Duration::from_secs(60));
+		ap.unlock_keep_secret = true;
+		assert!(ap.insert_account(kp.secret().clone(), &"test".into()).is_ok());
+		assert!(ap.unlock_account_permanently(kp.address(), "test".into()).is_ok());
+		let account = StoreAccountRef::root(kp.address());
+
+		// expired accounts are locked on use even before the sweep
+		ap.unlocked.write().get_mut(&account).unwrap().last_used = Instant::now() - Duration::from_secs(61);
+		assert!(ap.sign(kp.address(), None, Default::default()).is_err());
+		assert!(!ap.is_unlocked(&kp.address()));
+
+		// permanent unlocks are exempt when allowed
+		ap.allow_permanent_unlock = true;
+		assert!(ap.unlock_account_permanently(kp.address(), "test".into()).is_ok());
+		ap.unlocked.write().get_mut(&account).unwrap().last_used = Instant::now() - Duration::from_secs(61);
+		assert!(ap.lock_idle_accounts().is_empty());
+		assert!(ap.sign(kp.address(), None, Default::default()).is_ok());
+	}
+
+	#[test]
+	fn should_refuse_transactions_violating_policy() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		let policy = AccountPolicy {
+			max_value: Some(100.into()),
+			..Default::default()
+		};
+		assert!(ap.set_account_policy(kp.address(), policy.clone()).is_err(), "Unknown accounts can't have a policy");
+		assert!(ap.insert_account(kp.secret().clone(), &"test".into()).is_ok());
+		assert!(ap.set_account_policy(kp.address(), policy.clone()).is_ok());
+		assert_eq!(ap.account_policy(&kp.address()), Some(policy));
+
+		let mut transaction = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 21_000.into(),
+			action: Action::Call(1.into()),
+			value: 100.into(),
+			data: vec![],
+		};
+		assert!(ap.sign_transaction(kp.address(), Some("test".into()), &transaction, None).is_ok());
+		transaction.value = 101.into();
+		match ap.sign_transaction(kp.address(), Some("test".into()), &transaction, None) {
+			Err(SignError::PolicyViolation(PolicyViolation::Value { .. })) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+		match ap.sign_transaction_with_token(kp.address(), "test".into(), &transaction, Some(1)) {
+			Err(SignError::PolicyViolation(PolicyViolation::Value { .. })) => {},
+			other => panic!("Unexpected result: {:?}", other),
+		}
+
+		let violations = ap.policy_violations(&kp.address());
+		assert_eq!(violations.len(), 2);
+		assert_eq!(violations[0].hash, transaction.hash(None));
+		assert_eq!(violations[1].hash, transaction.hash(Some(1)));
+		assert_eq!(violations[1].reason, "Value 101 exceeds the limit of 100");
+
+		assert!(ap.set_account_policy(kp.address(), AccountPolicy::default()).is_ok());
+		assert_eq!(ap.account_policy(&kp.address()), None);
+		assert!(ap.sign_transaction(kp.address(), Some("test".into()), &transaction, None).is_ok());
+	}
+
 	#[test]
 	fn should_sign_and_return_token() {
 		// given
@@ -0,0 +1,223 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-account signing policies.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use common_types::transaction::{Action, Transaction};
+use ethereum_types::{H256, U256};
+use ethkey::Address;
+use serde_derive::{Serialize, Deserialize};
+use serde_json;
+
+/// Restrictions on transactions an account is allowed to sign.
+/// Every constraint left as `None` is not enforced.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccountPolicy {
+	/// Allowed recipients. Contract creation is refused if set.
+	pub recipients: Option<Vec<Address>>,
+	/// Maximum value per transaction.
+	pub max_value: Option<U256>,
+	/// Maximum gas price.
+	pub max_gas_price: Option<U256>,
+	/// Allowed methods (first 4 bytes of transaction data).
+	pub methods: Option<Vec<[u8; 4]>>,
+}
+
+/// Reason for refusing to sign a transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicyViolation {
+	/// Recipient is not whitelisted (`None` for contract creation).
+	Recipient(Option<Address>),
+	/// Transaction value is above the cap.
+	Value {
+		/// Maximal allowed value.
+		max: U256,
+		/// Value of the transaction.
+		got: U256,
+	},
+	/// Gas price is above the cap.
+	GasPrice {
+		/// Maximal allowed gas price.
+		max: U256,
+		/// Gas price of the transaction.
+		got: U256,
+	},
+	/// Called method is not whitelisted.
+	Method(Vec<u8>),
+}
+
+/// Transaction refused by an account policy, as recorded in the violations journal.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RefusedTransaction {
+	/// Hash of the unsigned transaction.
+	pub hash: H256,
+	/// Nonce of the transaction.
+	pub nonce: U256,
+	/// Violated constraint.
+	pub reason: String,
+	/// Time of the refusal (seconds since unix epoch).
+	pub timestamp: u64,
+}
+
+impl RefusedTransaction {
+	/// Read a hash map of Address -> refused transactions
+	pub fn read<R>(reader: R) -> Result<HashMap<Address, Vec<Self>>, serde_json::Error> where
+		R: ::std::io::Read,
+	{
+		serde_json::from_reader(reader)
+	}
+
+	/// Write a hash map of Address -> refused transactions
+	pub fn write<W>(m: &HashMap<Address, Vec<Self>>, writer: &mut W) -> Result<(), serde_json::Error> where
+		W: ::std::io::Write,
+	{
+		serde_json::to_writer(writer, m)
+	}
+}
+
+impl fmt::Display for PolicyViolation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			PolicyViolation::Recipient(Some(ref to)) => write!(f, "Recipient {:?} is not allowed", to),
+			PolicyViolation::Recipient(None) => write!(f, "Contract creation is not allowed"),
+			PolicyViolation::Value { ref max, ref got } => write!(f, "Value {} exceeds the limit of {}", got, max),
+			PolicyViolation::GasPrice { ref max, ref got } => write!(f, "Gas price {} exceeds the limit of {}", got, max),
+			PolicyViolation::Method(ref selector) => write!(f, "Method 0x{} is not allowed",
+				selector.iter().map(|b| format!("{:02x}", b)).collect::<String>()),
+		}
+	}
+}
+
+impl AccountPolicy {
+	/// Returns true if the policy doesn't restrict anything.
+	pub fn is_empty(&self) -> bool {
+		*self == AccountPolicy::default()
+	}
+
+	/// Checks if the transaction is allowed by this policy.
+	pub fn check(&self, transaction: &Transaction) -> Result<(), PolicyViolation> {
+		if let Some(ref recipients) = self.recipients {
+			match transaction.action {
+				Action::Call(ref to) if recipients.contains(to) => {},
+				Action::Call(ref to) => return Err(PolicyViolation::Recipient(Some(*to))),
+				Action::Create => return Err(PolicyViolation::Recipient(None)),
+			}
+		}
+
+		if let Some(max) = self.max_value {
+			if transaction.value > max {
+				return Err(PolicyViolation::Value { max, got: transaction.value });
+			}
+		}
+
+		if let Some(max) = self.max_gas_price {
+			if transaction.gas_price > max {
+				return Err(PolicyViolation::GasPrice { max, got: transaction.gas_price });
+			}
+		}
+
+		if let Some(ref methods) = self.methods {
+			let selector = &transaction.data[..::std::cmp::min(4, transaction.data.len())];
+			if !methods.iter().any(|method| &method[..] == selector) {
+				return Err(PolicyViolation::Method(selector.to_vec()));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Read a hash map of Address -> AccountPolicy
+	pub fn read<R>(reader: R) -> Result<HashMap<Address, Self>, serde_json::Error> where
+		R: ::std::io::Read,
+	{
+		serde_json::from_reader(reader)
+	}
+
+	/// Write a hash map of Address -> AccountPolicy
+	pub fn write<W>(m: &HashMap<Address, Self>, writer: &mut W) -> Result<(), serde_json::Error> where
+		W: ::std::io::Write,
+	{
+		serde_json::to_writer(writer, m)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn transaction(to: Option<Address>, value: u64, gas_price: u64, data: Vec<u8>) -> Transaction {
+		Transaction {
+			nonce: 0.into(),
+			gas_price: gas_price.into(),
+			gas: 21_000.into(),
+			action: to.map_or(Action::Create, Action::Call),
+			value: value.into(),
+			data,
+		}
+	}
+
+	#[test]
+	fn should_allow_anything_by_default() {
+		let policy = AccountPolicy::default();
+		assert!(policy.is_empty());
+		assert_eq!(policy.check(&transaction(None, 1_000, 1_000, vec![1, 2, 3, 4, 5])), Ok(()));
+	}
+
+	#[test]
+	fn should_check_recipients() {
+		let policy = AccountPolicy {
+			recipients: Some(vec![1.into()]),
+			..Default::default()
+		};
+		assert_eq!(policy.check(&transaction(Some(1.into()), 0, 0, vec![])), Ok(()));
+		assert_eq!(policy.check(&transaction(Some(2.into()), 0, 0, vec![])), Err(PolicyViolation::Recipient(Some(2.into()))));
+		assert_eq!(policy.check(&transaction(None, 0, 0, vec![])), Err(PolicyViolation::Recipient(None)));
+	}
+
+	#[test]
+	fn should_check_value() {
+		let policy = AccountPolicy {
+			max_value: Some(100.into()),
+			..Default::default()
+		};
+		assert_eq!(policy.check(&transaction(Some(1.into()), 100, 0, vec![])), Ok(()));
+		assert_eq!(policy.check(&transaction(Some(1.into()), 101, 0, vec![])), Err(PolicyViolation::Value { max: 100.into(), got: 101.into() }));
+	}
+
+	#[test]
+	fn should_check_gas_price() {
+		let policy = AccountPolicy {
+			max_gas_price: Some(20.into()),
+			..Default::default()
+		};
+		assert_eq!(policy.check(&transaction(Some(1.into()), 0, 20, vec![])), Ok(()));
+		assert_eq!(policy.check(&transaction(Some(1.into()), 0, 21, vec![])), Err(PolicyViolation::GasPrice { max: 20.into(), got: 21.into() }));
+	}
+
+	#[test]
+	fn should_check_methods() {
+		let policy = AccountPolicy {
+			methods: Some(vec![[0xa9, 0x05, 0x9c, 0xbb]]),
+			..Default::default()
+		};
+		assert_eq!(policy.check(&transaction(Some(1.into()), 0, 0, vec![0xa9, 0x05, 0x9c, 0xbb, 0x00])), Ok(()));
+		assert_eq!(policy.check(&transaction(Some(1.into()), 0, 0, vec![0x09, 0x5e, 0xa7, 0xb3])), Err(PolicyViolation::Method(vec![0x09, 0x5e, 0xa7, 0xb3])));
+		assert_eq!(policy.check(&transaction(Some(1.into()), 0, 0, vec![])), Err(PolicyViolation::Method(vec![])));
+	}
+}
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Address Book Store
+//! Address Book and Account Policy Stores
 
 use std::{fs, fmt, hash, ops};
 use std::collections::HashMap;
@@ -23,7 +23,10 @@ use std::path::{Path, PathBuf};
 use ethkey::Address;
 use log::{trace, warn};
 
-use crate::AccountMeta;
+use crate::{AccountMeta, AccountPolicy, RefusedTransaction};
+
+/// Number of refused transactions journaled per account.
+const MAX_JOURNALED_VIOLATIONS: usize = 64;
 
 /// Disk-backed map from Address to String. Uses JSON.
 pub struct AddressBook {
@@ -83,6 +86,72 @@ impl AddressBook {
 	}
 }
 
+/// Disk-backed map from Address to its signing policy and the journal
+/// of transactions refused by it. Uses JSON.
+///
+/// Refused transactions are never signed, so they can't be kept in the local transactions
+/// journal of the miner, which only holds signed transactions. They are journaled here,
+/// next to the policies, instead.
+pub struct PolicyStore {
+	cache: DiskMap<Address, AccountPolicy>,
+	journal: DiskMap<Address, Vec<RefusedTransaction>>,
+}
+
+impl PolicyStore {
+	/// Creates new policy store at given directory.
+	pub fn new(path: &Path) -> Self {
+		let mut r = PolicyStore {
+			cache: DiskMap::new(path, "account_policies.json"),
+			journal: DiskMap::new(path, "account_policy_violations.json"),
+		};
+		r.cache.revert(AccountPolicy::read);
+		r.journal.revert(RefusedTransaction::read);
+		r
+	}
+
+	/// Creates transient policy store (no changes are saved to disk).
+	pub fn transient() -> Self {
+		PolicyStore {
+			cache: DiskMap::transient(),
+			journal: DiskMap::transient(),
+		}
+	}
+
+	/// Returns policy of given address.
+	pub fn get(&self, a: &Address) -> Option<AccountPolicy> {
+		self.cache.get(a).cloned()
+	}
+
+	/// Sets policy of given address. Empty policy removes the entry.
+	pub fn set(&mut self, a: Address, policy: AccountPolicy) {
+		if policy.is_empty() {
+			self.cache.remove(&a);
+		} else {
+			self.cache.insert(a, policy);
+		}
+		self.cache.save(AccountPolicy::write);
+	}
+
+	/// Returns transactions of given address refused by its policy, oldest first.
+	pub fn violations(&self, a: &Address) -> Vec<RefusedTransaction> {
+		self.journal.get(a).cloned().unwrap_or_default()
+	}
+
+	/// Records a transaction refused by the policy of given address.
+	/// Only the most recent `MAX_JOURNALED_VIOLATIONS` entries are kept.
+	pub fn record_violation(&mut self, a: Address, refused: RefusedTransaction) {
+		{
+			let entries = self.journal.entry(a).or_insert_with(Vec::new);
+			entries.push(refused);
+			if entries.len() > MAX_JOURNALED_VIOLATIONS {
+				let excess = entries.len() - MAX_JOURNALED_VIOLATIONS;
+				entries.drain(..excess);
+			}
+		}
+		self.journal.save(RefusedTransaction::write);
+	}
+}
+
 /// Disk-serializable HashMap
 #[derive(Debug)]
 struct DiskMap<K: hash::Hash + Eq, V> {
@@ -153,10 +222,11 @@ impl<K: hash::Hash + Eq, V> DiskMap<K, V> {
 
 #[cfg(test)]
 mod tests {
-	use super::AddressBook;
+	use super::{AddressBook, PolicyStore, MAX_JOURNALED_VIOLATIONS};
 	use std::collections::HashMap;
 	use tempdir::TempDir;
 	use crate::account_data::AccountMeta;
+	use crate::policy::{AccountPolicy, RefusedTransaction};
 
 	#[test]
 	fn should_save_and_reload_address_book() {
@@ -186,4 +256,45 @@ mod tests {
 			(3, AccountMeta{name: "Three".to_owned(), meta: "{}".to_owned(), uuid: None}),
 		].into_iter().map(|(a, b)| (a.into(), b)).collect::<HashMap<_, _>>());
 	}
+
+	#[test]
+	fn should_save_and_reload_policies() {
+		let tempdir = TempDir::new("").unwrap();
+		let mut p = PolicyStore::new(tempdir.path());
+		let policy = AccountPolicy {
+			recipients: Some(vec![2.into()]),
+			max_value: Some(100.into()),
+			max_gas_price: Some(20.into()),
+			methods: Some(vec![[0xa9, 0x05, 0x9c, 0xbb]]),
+		};
+		p.set(1.into(), policy.clone());
+		p.set(3.into(), policy.clone());
+		p.set(3.into(), AccountPolicy::default());
+
+		let p = PolicyStore::new(tempdir.path());
+		assert_eq!(p.get(&1.into()), Some(policy));
+		assert_eq!(p.get(&3.into()), None);
+	}
+
+	#[test]
+	fn should_journal_most_recent_violations() {
+		let tempdir = TempDir::new("").unwrap();
+		let mut p = PolicyStore::new(tempdir.path());
+		let refused = |nonce: u64| RefusedTransaction {
+			hash: nonce.into(),
+			nonce: nonce.into(),
+			reason: "Contract creation is not allowed".into(),
+			timestamp: 0,
+		};
+		for nonce in 0..MAX_JOURNALED_VIOLATIONS as u64 + 2 {
+			p.record_violation(1.into(), refused(nonce));
+		}
+
+		let p = PolicyStore::new(tempdir.path());
+		let violations = p.violations(&1.into());
+		assert_eq!(violations.len(), MAX_JOURNALED_VIOLATIONS);
+		assert_eq!(violations[0], refused(2));
+		assert_eq!(violations[MAX_JOURNALED_VIOLATIONS - 1], refused(MAX_JOURNALED_VIOLATIONS as u64 + 1));
+		assert!(p.violations(&2.into()).is_empty());
+	}
 }
@@ -42,3 +42,12 @@ pub struct BestAncientBlock {
 	/// Best block number.
 	pub number: BlockNumber,
 }
+
+/// Latest block of the canonical chain marked as finalized by the consensus engine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FinalizedBlock {
+	/// Finalized block hash.
+	pub hash: H256,
+	/// Finalized block number.
+	pub number: BlockNumber,
+}
@@ -31,14 +31,14 @@ use common_types::engines::epoch::{Transition as EpochTransition, PendingTransit
 use common_types::header::{Header, ExtendedHeader};
 use common_types::log_entry::{LogEntry, LocalizedLogEntry};
 use common_types::receipt::Receipt;
-use common_types::transaction::LocalizedTransaction;
+use common_types::transaction::{LocalizedTransaction, UnverifiedTransaction};
 use common_types::tree_route::TreeRoute;
 use common_types::view;
 use common_types::views::{BlockView, HeaderView};
 use ethcore_db::cache_manager::CacheManager;
-use ethcore_db::keys::{BlockReceipts, BlockDetails, TransactionAddress, EPOCH_KEY_PREFIX, EpochTransitions};
-use ethcore_db::{self as db, Writable, Readable, CacheUpdatePolicy};
-use ethereum_types::{H256, Bloom, BloomRef, U256};
+use ethcore_db::keys::{BlockReceipts, BlockDetails, BlockNumberKey, TransactionAddress, EPOCH_KEY_PREFIX, EpochTransitions};
+use ethcore_db::{self as db, Key, Writable, Readable, CacheUpdatePolicy};
+use ethereum_types::{H256, H264, Bloom, BloomRef, U256};
 use heapsize::HeapSizeOf;
 use itertools::Itertools;
 use kvdb::{DBTransaction, KeyValueDB};
@@ -46,10 +46,10 @@ use log::{trace, warn, info};
 use parity_bytes::Bytes;
 use parking_lot::{Mutex, RwLock};
 use rayon::prelude::*;
-use rlp::RlpStream;
+use rlp::{Rlp, RlpStream};
 use rlp_compress::{compress, decompress, blocks_swapper};
 
-use crate::best_block::{BestBlock, BestAncientBlock};
+use crate::best_block::{BestBlock, BestAncientBlock, FinalizedBlock};
 use crate::block_info::{BlockInfo, BlockLocation, BranchBecomingCanonChainData};
 use crate::update::{ExtrasUpdate, ExtrasInsert};
 use crate::{CacheSize, ImportRoute, Config};
@@ -214,6 +214,8 @@ pub struct BlockChain {
 	// Stores best block of the first uninterrupted sequence of blocks. `None` if there are no gaps.
 	// Only updated with `insert_unordered_block`.
 	best_ancient_block: RwLock<Option<BestAncientBlock>>,
+	// Stores the latest finalized block. `None` if the engine didn't finalize any block yet.
+	finalized_block: RwLock<Option<FinalizedBlock>>,
 	// Stores the last block of the last sequence of blocks. `None` if there are no gaps.
 	// This is calculated on start and does not get updated.
 	first_block: Option<H256>,
@@ -234,6 +236,7 @@ pub struct BlockChain {
 
 	pending_best_ancient_block: RwLock<Option<Option<BestAncientBlock>>>,
 	pending_best_block: RwLock<Option<BestBlock>>,
+	pending_finalized_block: RwLock<Option<FinalizedBlock>>,
 	pending_block_hashes: RwLock<HashMap<BlockNumber, H256>>,
 	pending_block_details: RwLock<HashMap<H256, BlockDetails>>,
 	pending_transaction_addresses: RwLock<HashMap<H256, Option<TransactionAddress>>>,
@@ -519,6 +522,22 @@ impl<'a> Iterator for EpochTransitionIter<'a> {
 	}
 }
 
+/// Result of rolling back an inconsistent best block with `BlockChain::rollback_inconsistent_head`.
+#[derive(Debug, PartialEq)]
+pub struct HeadRollback {
+	/// Number and hash of the new best block.
+	pub best: (BlockNumber, H256),
+	/// Number and hash of every discarded block, starting with the old best block.
+	pub discarded: Vec<(BlockNumber, H256)>,
+}
+
+/// Key of the traces of the block with given hash in `COL_TRACE`, as written by the trace database.
+fn block_traces_key(hash: &H256) -> H264 {
+	let mut result = H264::default();
+	result[1..].copy_from_slice(hash);
+	result
+}
+
 impl BlockChain {
 	/// Create new instance of blockchain from given Genesis.
 	pub fn new(config: Config, genesis: &[u8], db: Arc<BlockChainDB>) -> BlockChain {
@@ -534,6 +553,7 @@ impl BlockChain {
 				block: encoded::Block::new(genesis.into()),
 			}),
 			best_ancient_block: RwLock::new(None),
+			finalized_block: RwLock::new(None),
 			block_headers: RwLock::new(HashMap::new()),
 			block_bodies: RwLock::new(HashMap::new()),
 			block_details: RwLock::new(HashMap::new()),
@@ -544,6 +564,7 @@ impl BlockChain {
 			cache_man: Mutex::new(cache_man),
 			pending_best_ancient_block: RwLock::new(None),
 			pending_best_block: RwLock::new(None),
+			pending_finalized_block: RwLock::new(None),
 			pending_block_hashes: RwLock::new(HashMap::new()),
 			pending_block_details: RwLock::new(HashMap::new()),
 			pending_transaction_addresses: RwLock::new(HashMap::new()),
@@ -601,6 +622,16 @@ impl BlockChain {
 			};
 		}
 
+		{
+			// Fetch the finalized block, unless it was rolled back
+			let finalized_block = bc.db.key_value().get(db::COL_EXTRA, b"finalized")
+				.expect("Low level database error when fetching 'finalized' block. Some issue with disk?")
+				.map(|h| H256::from_slice(&h))
+				.and_then(|hash| bc.block_number(&hash).map(|number| FinalizedBlock { hash, number }));
+
+			*bc.finalized_block.write() = finalized_block;
+		}
+
 		{
 			let best_block_number = bc.best_block.read().header.number();
 			// Fetch first and best ancient block details
@@ -662,6 +693,131 @@ impl BlockChain {
 		bc
 	}
 
+	/// Checks that the best block recorded in the database is fully readable: its header, body and
+	/// receipts must be present and decodable and `has_state` must hold for its state root.
+	///
+	/// If that's not the case, walks back along the canonical chain at most `max_depth` blocks
+	/// looking for the most recent consistent block, marks it as the best block and removes the
+	/// discarded blocks and everything indexing them from the database.
+	/// Returns `Ok(None)` if no rollback was necessary.
+	///
+	/// Must be called before the `BlockChain` is opened on top of `db`.
+	pub fn rollback_inconsistent_head<F>(db: &BlockChainDB, max_depth: u64, has_state: F) -> Result<Option<HeadRollback>, String> where
+		F: Fn(&H256) -> bool,
+	{
+		let kv = db.key_value();
+		let mut hash = match kv.get(db::COL_EXTRA, b"best").map_err(|e| format!("Low level database error: {}", e))? {
+			Some(best) => H256::from_slice(&best),
+			None => return Ok(None),
+		};
+
+		let mut discarded = Vec::new();
+		loop {
+			let details: BlockDetails = kv.get(db::COL_EXTRA, &<H256 as Key<BlockDetails>>::key(&hash))
+				.map_err(|e| format!("Low level database error: {}", e))?
+				.and_then(|v| ::rlp::decode(&v).ok())
+				.ok_or_else(|| format!("Details of block {:?} are missing, unable to find its parent", hash))?;
+
+			match Self::check_block_consistency(&**kv, &hash, details.number, &has_state) {
+				Ok(()) if discarded.is_empty() => return Ok(None),
+				Ok(()) => break,
+				Err(reason) => {
+					warn!(target: "blockchain", "Block #{} ({:?}) is inconsistent: {}", details.number, hash, reason);
+					if details.number == 0 {
+						return Err("Genesis block is inconsistent".into());
+					}
+					if discarded.len() as u64 >= max_depth {
+						return Err(format!("No consistent block found within {} blocks of the best block", max_depth));
+					}
+					discarded.push((details.number, hash));
+					hash = details.parent;
+				}
+			}
+		}
+
+		let best_number = discarded.last().map(|&(number, _)| number - 1)
+			.expect("loop only breaks with at least one discarded block; qed");
+
+		let mut batch = DBTransaction::with_capacity(discarded.len() * 6 + 2);
+		for &(number, ref hash) in &discarded {
+			// transactions can only be unindexed if the body is still readable.
+			let transactions = kv.get(db::COL_BODIES, hash).ok().and_then(|b| b)
+				.and_then(|b| Rlp::new(&decompress(&b, blocks_swapper())).list_at::<UnverifiedTransaction>(0).ok())
+				.unwrap_or_default();
+			for transaction in transactions {
+				Writable::delete::<TransactionAddress, H264>(&mut batch, db::COL_EXTRA, &transaction.hash());
+			}
+
+			batch.delete(db::COL_HEADERS, hash);
+			batch.delete(db::COL_BODIES, hash);
+			batch.delete(db::COL_TRACE, &block_traces_key(hash));
+			Writable::delete::<BlockDetails, H264>(&mut batch, db::COL_EXTRA, hash);
+			Writable::delete::<BlockReceipts, H264>(&mut batch, db::COL_EXTRA, hash);
+			Writable::delete::<H256, BlockNumberKey>(&mut batch, db::COL_EXTRA, &number);
+		}
+
+		// the new best block is the parent of the oldest discarded block.
+		let oldest_discarded = discarded.last().map(|&(_, hash)| hash)
+			.expect("loop only breaks with at least one discarded block; qed");
+		let mut best_details: BlockDetails = kv.get(db::COL_EXTRA, &<H256 as Key<BlockDetails>>::key(&hash))
+			.map_err(|e| format!("Low level database error: {}", e))?
+			.and_then(|v| ::rlp::decode(&v).ok())
+			.ok_or_else(|| format!("Details of block {:?} are missing", hash))?;
+		best_details.children.retain(|child| *child != oldest_discarded);
+		batch.write(db::COL_EXTRA, &hash, &best_details);
+		batch.put(db::COL_EXTRA, b"best", &hash);
+
+		// discarded blocks must not match any log or trace filters. the blooms live outside of
+		// the key-value database, so they are reset before the batch is written: if writing it
+		// fails the head is still inconsistent and the rollback is retried on the next start.
+		let empty_blooms = vec![Bloom::default(); discarded.len()];
+		db.blooms().insert_blooms(best_number + 1, empty_blooms.iter())
+			.and_then(|_| db.trace_blooms().insert_blooms(best_number + 1, empty_blooms.iter()))
+			.map_err(|e| format!("Low level database error when resetting blooms: {}", e))?;
+
+		kv.write(batch).map_err(|e| format!("Low level database error: {}", e))?;
+		kv.flush().map_err(|e| format!("Low level database error: {}", e))?;
+
+		Ok(Some(HeadRollback {
+			best: (best_number, hash),
+			discarded,
+		}))
+	}
+
+	/// Verifies that the header, body and receipts of the given block are readable
+	/// and that its state is available.
+	fn check_block_consistency<F>(kv: &KeyValueDB, hash: &H256, number: BlockNumber, has_state: &F) -> Result<(), &'static str> where
+		F: Fn(&H256) -> bool,
+	{
+		let header = kv.get(db::COL_HEADERS, hash).ok().and_then(|h| h)
+			.and_then(|h| ::rlp::decode::<Header>(&decompress(&h, blocks_swapper())).ok())
+			.ok_or("header is missing or corrupted")?;
+		if header.hash() != *hash || header.number() != number {
+			return Err("header does not match the block hash");
+		}
+
+		kv.get(db::COL_BODIES, hash).ok().and_then(|b| b)
+			.and_then(|b| {
+				let body = decompress(&b, blocks_swapper());
+				let rlp = Rlp::new(&body);
+				rlp.list_at::<UnverifiedTransaction>(0).and_then(|_| rlp.list_at::<Header>(1)).ok()
+			})
+			.ok_or("body is missing or corrupted")?;
+
+		// the genesis block doesn't have receipts stored.
+		if number != 0 {
+			kv.get(db::COL_EXTRA, &<H256 as Key<BlockReceipts>>::key(hash)).ok().and_then(|r| r)
+				.and_then(|r| ::rlp::decode::<BlockReceipts>(&r).ok())
+				.ok_or("receipts are missing or corrupted")?;
+		}
+
+		if !has_state(header.state_root()) {
+			return Err("state root is missing from the state database");
+		}
+
+		Ok(())
+	}
+
 	/// Returns true if the given parent block has given child
 	/// (though not necessarily a part of the canon chain).
 	fn is_known_child(&self, parent: &H256, hash: &H256) -> bool {
@@ -1103,6 +1259,10 @@ impl BlockChain {
 			block,
 		}, true);
 
+		if extras.is_finalized {
+			self.note_finalized(batch, info.hash, info.number);
+		}
+
 		ImportRoute::from(info)
 	}
 
@@ -1146,10 +1306,23 @@ impl BlockChain {
 		let mut block_details = self.block_details(&block_hash)?;
 		block_details.is_finalized = true;
 
+		let number = block_details.number;
 		self.update_block_details(batch, block_hash, block_details);
+		self.note_finalized(batch, block_hash, number);
 		Some(())
 	}
 
+	// advance the latest finalized block if the given one is newer.
+	fn note_finalized(&self, batch: &mut DBTransaction, hash: H256, number: BlockNumber) {
+		let mut pending_finalized_block = self.pending_finalized_block.write();
+		let latest = pending_finalized_block.or_else(|| *self.finalized_block.read());
+
+		if latest.map_or(true, |block| block.number < number) {
+			batch.put(db::COL_EXTRA, b"finalized", &hash);
+			*pending_finalized_block = Some(FinalizedBlock { hash, number });
+		}
+	}
+
 	/// Prepares extras block detail update.
 	fn update_block_details(&self, batch: &mut DBTransaction, block_hash: H256, block_details: BlockDetails) {
 		let mut details_map = HashMap::new();
@@ -1201,12 +1374,14 @@ impl BlockChain {
 	pub fn commit(&self) {
 		let mut pending_best_ancient_block = self.pending_best_ancient_block.write();
 		let mut pending_best_block = self.pending_best_block.write();
+		let mut pending_finalized_block = self.pending_finalized_block.write();
 		let mut pending_write_hashes = self.pending_block_hashes.write();
 		let mut pending_block_details = self.pending_block_details.write();
 		let mut pending_write_txs = self.pending_transaction_addresses.write();
 
 		let mut best_block = self.best_block.write();
 		let mut best_ancient_block = self.best_ancient_block.write();
+		let mut finalized_block = self.finalized_block.write();
 		let mut write_block_details = self.block_details.write();
 		let mut write_hashes = self.block_hashes.write();
 		let mut write_txs = self.transaction_addresses.write();
@@ -1218,6 +1393,10 @@ impl BlockChain {
 		if let Some(block) = pending_best_block.take() {
 			*best_block = block;
 		}
+		// update finalized block
+		if let Some(block) = pending_finalized_block.take() {
+			*finalized_block = Some(block);
+		}
 
 		let pending_txs = mem::replace(&mut *pending_write_txs, HashMap::new());
 		let (retracted_txs, enacted_txs) = pending_txs.into_iter().partition::<HashMap<_, _>, _>(|&(_, ref value)| value.is_none());
@@ -1465,6 +1644,16 @@ impl BlockChain {
 		self.best_block.read().header.number()
 	}
 
+	/// Get the hash of the latest finalized block, if any block was finalized.
+	pub fn finalized_block_hash(&self) -> Option<H256> {
+		self.finalized_block.read().map(|block| block.hash)
+	}
+
+	/// Get the number of the latest finalized block, if any block was finalized.
+	pub fn finalized_block_number(&self) -> Option<BlockNumber> {
+		self.finalized_block.read().map(|block| block.number)
+	}
+
 	/// Get best block timestamp.
 	pub fn best_block_timestamp(&self) -> u64 {
 		self.best_block.read().header.timestamp()
@@ -2378,6 +2567,93 @@ mod tests {
 		assert_eq!(bc.best_block_number(), 5);
 	}
 
+	#[test]
+	fn should_rollback_inconsistent_head() {
+		let t1 = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Create,
+			value: 100.into(),
+			data: vec![],
+		}.sign(&secret(), None);
+		let t1_hash = t1.hash();
+		let bloom = Bloom::from(0x42);
+
+		let genesis = BlockBuilder::genesis();
+		let next_4 = genesis.add_blocks(4);
+		let b5 = next_4.add_block_with(|| BlockOptions {
+			bloom,
+			transactions: vec![t1.clone()],
+			..Default::default()
+		});
+		let generator = BlockGenerator::new(vec![next_4, b5]);
+
+		let db = new_db();
+		let hashes = {
+			let bc = new_chain(genesis.last().encoded(), db.clone());
+			let mut hashes = Vec::new();
+			for block in generator {
+				hashes.push(block.hash());
+				insert_block(&db, &bc, block.encoded(), vec![]);
+			}
+			assert_eq!(bc.best_block_number(), 5);
+			assert!(bc.transaction_address(&t1_hash).is_some());
+			assert_eq!(bc.blocks_with_bloom(Some(&bloom), 0, 5), vec![5]);
+			hashes
+		};
+
+		// a consistent head is left untouched.
+		assert_eq!(BlockChain::rollback_inconsistent_head(&*db, 10, |_| true), Ok(None));
+
+		// corrupt the receipts of the best block.
+		let mut batch = db.key_value().transaction();
+		batch.put(db::COL_EXTRA, &<H256 as Key<BlockReceipts>>::key(&hashes[4]), &[0xde, 0xad]);
+		batch.put(db::COL_TRACE, &block_traces_key(&hashes[3]), &[0xc0]);
+		batch.put(db::COL_TRACE, &block_traces_key(&hashes[4]), &[0xc0]);
+		db.key_value().write(batch).unwrap();
+
+		// not allowed to go back far enough.
+		assert!(BlockChain::rollback_inconsistent_head(&*db, 0, |_| true).is_err());
+
+		let rollback = BlockChain::rollback_inconsistent_head(&*db, 10, |_| true).unwrap().unwrap();
+		assert_eq!(rollback.best, (4, hashes[3]));
+		assert_eq!(rollback.discarded, vec![(5, hashes[4])]);
+
+		let bc = new_chain(genesis.last().encoded(), db.clone());
+		assert_eq!(bc.best_block_number(), 4);
+		assert_eq!(bc.best_block_hash(), hashes[3]);
+		assert_eq!(bc.block_hash(5), None);
+		assert!(bc.block_header_data(&hashes[4]).is_none());
+		assert!(bc.block_details(&hashes[4]).is_none());
+		assert_eq!(bc.block_details(&hashes[3]).unwrap().children, vec![]);
+		assert_eq!(bc.transaction_address(&t1_hash), None);
+		assert!(bc.blocks_with_bloom(Some(&bloom), 0, 5).is_empty());
+		assert!(db.key_value().get(db::COL_TRACE, &block_traces_key(&hashes[3])).unwrap().is_some());
+		assert!(db.key_value().get(db::COL_TRACE, &block_traces_key(&hashes[4])).unwrap().is_none());
+	}
+
+	#[test]
+	fn should_not_rollback_past_genesis() {
+		let genesis = BlockBuilder::genesis();
+		let next_3 = genesis.add_blocks(3);
+		let generator = BlockGenerator::new(iter::once(next_3));
+
+		let db = new_db();
+		{
+			let bc = new_chain(genesis.last().encoded(), db.clone());
+			for block in generator {
+				insert_block(&db, &bc, block.encoded(), vec![]);
+			}
+		}
+
+		// no state available at all.
+		assert!(BlockChain::rollback_inconsistent_head(&*db, 10, |_| false).is_err());
+
+		let bc = new_chain(genesis.last().encoded(), db.clone());
+		assert_eq!(bc.best_block_number(), 3);
+	}
+
 	#[test]
 	fn epoch_transitions_iter() {
 		use common_types::engines::epoch::Transition as EpochTransition;
@@ -2498,4 +2774,39 @@ mod tests {
 			assert_eq!(bc.epoch_transition_for(fork_hash).unwrap().block_number, 0);
 		}
 	}
+
+	#[test]
+	fn tracks_latest_finalized_block() {
+		let genesis = BlockBuilder::genesis();
+		let next_3 = genesis.add_blocks(3);
+		let generator = BlockGenerator::new(iter::once(next_3));
+
+		let db = new_db();
+		let hashes = {
+			let bc = new_chain(genesis.last().encoded(), db.clone());
+			let mut hashes = Vec::new();
+			for block in generator {
+				hashes.push(block.hash());
+				insert_block(&db, &bc, block.encoded(), vec![]);
+			}
+			assert_eq!(bc.finalized_block_hash(), None);
+
+			let mut batch = db.key_value().transaction();
+			bc.mark_finalized(&mut batch, hashes[1]).unwrap();
+			// finalizing an older block doesn't move the finalized block back.
+			bc.mark_finalized(&mut batch, hashes[0]).unwrap();
+			db.key_value().write(batch).unwrap();
+			assert_eq!(bc.finalized_block_hash(), None);
+
+			bc.commit();
+			assert_eq!(bc.finalized_block_hash(), Some(hashes[1]));
+			assert_eq!(bc.finalized_block_number(), Some(2));
+			assert!(bc.block_details(&hashes[0]).unwrap().is_finalized);
+			hashes
+		};
+
+		let bc = new_chain(genesis.last().encoded(), db.clone());
+		assert_eq!(bc.finalized_block_hash(), Some(hashes[1]));
+		assert_eq!(bc.finalized_block_number(), Some(2));
+	}
 }
@@ -18,15 +18,19 @@
 //!
 //! Stores ancient block headers, bodies, receipts, and total difficulties.
 //! Furthermore, stores a "gas price corpus" of relative recency, which is a sorted
-//! vector of all gas prices from a recent range of blocks.
+//! vector of all gas prices from a recent range of blocks, computed at a given best block,
+//! and results of proved read-only executions.
 
 use std::time::{Instant, Duration};
 
+use bytes::Bytes;
 use common_types::encoded;
 use common_types::BlockNumber;
 use common_types::receipt::Receipt;
-use ethereum_types::{H256, U256};
+use ethcore::executed::ExecutionResult;
+use ethereum_types::{Address, H256, U256};
 use heapsize::HeapSizeOf;
+use lru_cache::LruCache;
 use memory_cache::MemoryLruCache;
 use stats::Corpus;
 
@@ -43,6 +47,8 @@ pub struct CacheSizes {
 	pub receipts: usize,
 	/// Maximum size, in bytes, of cached chain score for the block.
 	pub chain_score: usize,
+	/// Maximum number of cached execution results.
+	pub executions: usize,
 }
 
 impl Default for CacheSizes {
@@ -54,10 +60,35 @@ impl Default for CacheSizes {
 			bodies: 20 * MB,
 			receipts: 10 * MB,
 			chain_score: 7 * MB,
+			executions: 128,
 		}
 	}
 }
 
+/// Parameters of a read-only execution at a specific block.
+///
+/// Gas, gas price and nonce only take part when given explicitly,
+/// otherwise they are derived from the block the same way each time.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExecutionKey {
+	/// Hash of the block the execution is done at.
+	pub block_hash: H256,
+	/// Sender of the transaction.
+	pub from: Address,
+	/// Recipient of the transaction, `None` for contract creation.
+	pub to: Option<Address>,
+	/// Value transferred.
+	pub value: U256,
+	/// Transaction data.
+	pub data: Bytes,
+	/// Gas provided.
+	pub gas: Option<U256>,
+	/// Gas price.
+	pub gas_price: Option<U256>,
+	/// Nonce of the sender.
+	pub nonce: Option<U256>,
+}
+
 /// The light client data cache.
 ///
 /// Note that almost all getter methods take `&mut self` due to the necessity to update
@@ -69,7 +100,8 @@ pub struct Cache {
 	bodies: MemoryLruCache<H256, encoded::Body>,
 	receipts: MemoryLruCache<H256, Vec<Receipt>>,
 	chain_score: MemoryLruCache<H256, U256>,
-	corpus: Option<(Corpus<U256>, Instant)>,
+	executions: LruCache<ExecutionKey, ExecutionResult>,
+	corpus: Option<(Corpus<U256>, H256, Instant)>,
 	corpus_expiration: Duration,
 }
 
@@ -82,6 +114,7 @@ impl Cache {
 			bodies: MemoryLruCache::new(sizes.bodies),
 			receipts: MemoryLruCache::new(sizes.receipts),
 			chain_score: MemoryLruCache::new(sizes.chain_score),
+			executions: LruCache::new(sizes.executions),
 			corpus: None,
 			corpus_expiration,
 		}
@@ -112,6 +145,11 @@ impl Cache {
 		self.chain_score.get_mut(hash).map(|h| *h)
 	}
 
+	/// Query result of a read-only execution.
+	pub fn execution(&mut self, key: &ExecutionKey) -> Option<ExecutionResult> {
+		self.executions.get_mut(key).cloned()
+	}
+
 	/// Cache the given header.
 	pub fn insert_block_header(&mut self, hash: H256, hdr: encoded::Header) {
 		self.headers.insert(hash, hdr);
@@ -137,12 +175,18 @@ impl Cache {
 		self.chain_score.insert(hash, score);
 	}
 
-	/// Get gas price corpus, if recent enough.
-	pub fn gas_price_corpus(&self) -> Option<Corpus<U256>> {
+	/// Cache the result of a read-only execution.
+	/// Only results verified against the state of `key.block_hash` should be cached.
+	pub fn insert_execution(&mut self, key: ExecutionKey, result: ExecutionResult) {
+		self.executions.insert(key, result);
+	}
+
+	/// Get gas price corpus, if it was computed at `best_block` and is recent enough.
+	pub fn gas_price_corpus(&self, best_block: &H256) -> Option<Corpus<U256>> {
 		let now = Instant::now();
 
-		self.corpus.as_ref().and_then(|&(ref corpus, ref tm)| {
-			if *tm + self.corpus_expiration >= now {
+		self.corpus.as_ref().and_then(|&(ref corpus, ref block, ref tm)| {
+			if block == best_block && *tm + self.corpus_expiration >= now {
 				Some(corpus.clone())
 			} else {
 				None
@@ -150,9 +194,9 @@ impl Cache {
 		})
 	}
 
-	/// Set the cached gas price corpus.
-	pub fn set_gas_price_corpus(&mut self, corpus: Corpus<U256>) {
-		self.corpus = Some((corpus, Instant::now()))
+	/// Set the cached gas price corpus computed at `best_block`.
+	pub fn set_gas_price_corpus(&mut self, best_block: H256, corpus: Corpus<U256>) {
+		self.corpus = Some((corpus, best_block, Instant::now()))
 	}
 
 	/// Get the memory used.
@@ -174,7 +218,8 @@ impl HeapSizeOf for Cache {
 
 #[cfg(test)]
 mod tests {
-	use super::Cache;
+	use super::{Cache, CacheSizes, ExecutionKey};
+	use ethcore::executed::Executed;
 	use std::time::Duration;
 
 	#[test]
@@ -182,13 +227,72 @@ mod tests {
 		let duration = Duration::from_secs(20);
 		let mut cache = Cache::new(Default::default(), duration.clone());
 
-		cache.set_gas_price_corpus(vec![].into());
-		assert_eq!(cache.gas_price_corpus(), Some(vec![].into()));
+		cache.set_gas_price_corpus(1.into(), vec![].into());
+		assert_eq!(cache.gas_price_corpus(&1.into()), Some(vec![].into()));
 
 		{
-			let corpus_time = &mut cache.corpus.as_mut().unwrap().1;
+			let corpus_time = &mut cache.corpus.as_mut().unwrap().2;
 			*corpus_time = *corpus_time - duration;
 		}
-		assert!(cache.gas_price_corpus().is_none());
+		assert!(cache.gas_price_corpus(&1.into()).is_none());
+	}
+
+	#[test]
+	fn corpus_is_cached_per_best_block() {
+		let mut cache = Cache::new(Default::default(), Duration::from_secs(20));
+
+		cache.set_gas_price_corpus(1.into(), vec![1.into()].into());
+		assert_eq!(cache.gas_price_corpus(&1.into()), Some(vec![1.into()].into()));
+		assert!(cache.gas_price_corpus(&2.into()).is_none());
+
+		cache.set_gas_price_corpus(2.into(), vec![2.into()].into());
+		assert!(cache.gas_price_corpus(&1.into()).is_none());
+		assert_eq!(cache.gas_price_corpus(&2.into()), Some(vec![2.into()].into()));
+	}
+
+	#[test]
+	fn executions_are_cached_per_block() {
+		fn key(block_hash: u64) -> ExecutionKey {
+			ExecutionKey {
+				block_hash: block_hash.into(),
+				from: Default::default(),
+				to: Some(1.into()),
+				value: 0.into(),
+				data: vec![0x70, 0xa0, 0x82, 0x31],
+				gas: None,
+				gas_price: None,
+				nonce: None,
+			}
+		}
+
+		let executed = Executed {
+			exception: None,
+			gas: 60_000.into(),
+			gas_used: 21_000.into(),
+			refunded: 0.into(),
+			cumulative_gas_used: 21_000.into(),
+			logs: Vec::new(),
+			contracts_created: Vec::new(),
+			output: vec![1],
+			trace: Vec::new(),
+			vm_trace: None,
+			state_diff: None,
+		};
+
+		let sizes = CacheSizes { executions: 2, ..Default::default() };
+		let mut cache = Cache::new(sizes, Duration::from_secs(20));
+
+		cache.insert_execution(key(1), Ok(executed.clone()));
+		assert_eq!(cache.execution(&key(1)), Some(Ok(executed.clone())));
+		assert!(cache.execution(&key(2)).is_none());
+		assert!(cache.execution(&ExecutionKey { gas: Some(21_000.into()), ..key(1) }).is_none());
+
+		// least recently used results are evicted.
+		cache.insert_execution(key(2), Ok(executed.clone()));
+		assert!(cache.execution(&key(1)).is_some());
+		cache.insert_execution(key(3), Ok(executed.clone()));
+		assert!(cache.execution(&key(2)).is_none());
+		assert_eq!(cache.execution(&key(1)), Some(Ok(executed.clone())));
+		assert_eq!(cache.execution(&key(3)), Some(Ok(executed)));
 	}
 }
@@ -17,11 +17,14 @@
 //! Canonical hash trie definitions and helper functions.
 //!
 //! Each CHT is a trie mapping block numbers to canonical hashes and total difficulty.
-//! One is generated for every `SIZE` blocks, allowing us to discard those blocks in
+//! One is generated for every `size` blocks, allowing us to discard those blocks in
 //! favor of the trie root. When the "ancient" blocks need to be accessed, we simply
 //! request an inclusion proof of a specific block number against the trie with the
 //! root has. A correct proof implies that the claimed block is identical to the one
 //! we discarded.
+//!
+//! Public chains use CHTs of `SIZE` blocks, custom chains may set any other
+//! power of two through the `chtSize` parameter of their chain specification.
 
 use common_types::ids::BlockId;
 use ethereum_types::{H256, U256};
@@ -48,9 +51,15 @@ macro_rules! val {
 	}}
 }
 
-/// The size of each CHT.
+/// The default size of each CHT.
 pub const SIZE: u64 = 2048;
 
+/// Check whether CHTs of given size can be generated.
+/// The size has to be a power of two.
+pub fn is_valid_size(size: u64) -> bool {
+	size.is_power_of_two() && size < usize::max_value() as u64
+}
+
 /// A canonical hash trie. This is generic over any database it can query.
 /// See module docs for more details.
 #[derive(Debug, Clone)]
@@ -58,6 +67,7 @@ pub struct CHT<DB: HashDB<KeccakHasher, DBValue>> {
 	db: DB,
 	root: H256, // the root of this CHT.
 	number: u64,
+	size: u64,
 }
 
 impl<DB: HashDB<KeccakHasher, DBValue>> CHT<DB> {
@@ -67,11 +77,14 @@ impl<DB: HashDB<KeccakHasher, DBValue>> CHT<DB> {
 	/// Query the number of the CHT.
 	pub fn number(&self) -> u64 { self.number }
 
+	/// Query the number of blocks in the CHT.
+	pub fn size(&self) -> u64 { self.size }
+
 	/// Generate an inclusion proof for the entry at a specific block.
 	/// Nodes before level `from_level` will be omitted.
 	/// Returns an error on an incomplete trie, and `Ok(None)` on an unprovable request.
 	pub fn prove(&self, num: u64, from_level: u32) -> ethtrie::Result<Option<Vec<Bytes>>> {
-		if block_to_cht_number(num) != Some(self.number) { return Ok(None) }
+		if block_to_cht_number(num, self.size) != Some(self.number) { return Ok(None) }
 
 		let mut recorder = Recorder::with_depth(from_level);
 		let db: &HashDB<_,_> = &self.db;
@@ -92,23 +105,23 @@ pub struct BlockInfo {
 	pub total_difficulty: U256,
 }
 
-/// Build an in-memory CHT from a closure which provides necessary information
-/// about blocks. If the fetcher ever fails to provide the info, the CHT
+/// Build an in-memory CHT of `size` blocks from a closure which provides necessary
+/// information about blocks. If the fetcher ever fails to provide the info, the CHT
 /// will not be generated.
-pub fn build<F>(cht_num: u64, mut fetcher: F) -> Option<CHT<MemoryDB<KeccakHasher, DBValue>>>
+pub fn build<F>(cht_num: u64, size: u64, mut fetcher: F) -> Option<CHT<MemoryDB<KeccakHasher, DBValue>>>
 	where F: FnMut(BlockId) -> Option<BlockInfo>
 {
 	let mut db = new_memory_db();
 
 	// start from the last block by number and work backwards.
-	let last_num = start_number(cht_num + 1) - 1;
+	let last_num = start_number(cht_num + 1, size) - 1;
 	let mut id = BlockId::Number(last_num);
 
 	let mut root = H256::default();
 
 	{
 		let mut t = TrieDBMut::new(&mut db, &mut root);
-		for blk_num in (0..SIZE).map(|n| last_num - n) {
+		for blk_num in (0..size).map(|n| last_num - n) {
 			let info = match fetcher(id) {
 				Some(info) => info,
 				None => return None,
@@ -124,23 +137,24 @@ pub fn build<F>(cht_num: u64, mut fetcher: F) -> Option<CHT<MemoryDB<KeccakHashe
 		db,
 		root,
 		number: cht_num,
+		size,
 	})
 }
 
 /// Compute a CHT root from an iterator of (hash, td) pairs. Fails if shorter than
-/// SIZE items. The items are assumed to proceed sequentially from `start_number(cht_num)`.
+/// `size` items. The items are assumed to proceed sequentially from `start_number(cht_num, size)`.
 /// Discards the trie's nodes.
-pub fn compute_root<I>(cht_num: u64, iterable: I) -> Option<H256>
+pub fn compute_root<I>(cht_num: u64, size: u64, iterable: I) -> Option<H256>
 	where I: IntoIterator<Item=(H256, U256)>
 {
-	let mut v = Vec::with_capacity(SIZE as usize);
-	let start_num = start_number(cht_num) as usize;
+	let mut v = Vec::with_capacity(size as usize);
+	let start_num = start_number(cht_num, size) as usize;
 
-	for (i, (h, td)) in iterable.into_iter().take(SIZE as usize).enumerate() {
+	for (i, (h, td)) in iterable.into_iter().take(size as usize).enumerate() {
 		v.push((key!(i + start_num), val!(h, td)))
 	}
 
-	if v.len() == SIZE as usize {
+	if v.len() == size as usize {
 		Some(::triehash::trie_root(v))
 	} else {
 		None
@@ -171,12 +185,12 @@ pub fn check_proof(proof: &[Bytes], num: u64, root: H256) -> Option<(H256, U256)
 	}
 }
 
-/// Convert a block number to a CHT number.
+/// Convert a block number to a number of the CHT of given size.
 /// Returns `None` for `block_num` == 0, `Some` otherwise.
-pub fn block_to_cht_number(block_num: u64) -> Option<u64> {
+pub fn block_to_cht_number(block_num: u64, size: u64) -> Option<u64> {
 	match block_num {
 		0 => None,
-		n => Some((n - 1) / SIZE),
+		n => Some((n - 1) / size),
 	}
 }
 
@@ -186,30 +200,87 @@ pub fn block_to_cht_number(block_num: u64) -> Option<u64> {
 /// More generally: CHT N includes block (1 + N*SIZE)...((N+1)*SIZE).
 /// This is because the genesis hash is assumed to be known
 /// and including it would be redundant.
-pub fn start_number(cht_num: u64) -> u64 {
-	(cht_num * SIZE) + 1
+pub fn start_number(cht_num: u64, size: u64) -> u64 {
+	(cht_num * size) + 1
 }
 
 #[cfg(test)]
 mod tests {
+	use common_types::ids::BlockId;
+	use ethereum_types::{H256, U256};
+	use super::{SIZE, BlockInfo, is_valid_size, build, compute_root, check_proof};
+
 	#[test]
 	fn size_is_lt_usize() {
 		// to ensure safe casting on the target platform.
 		assert!(::cht::SIZE < usize::max_value() as u64)
 	}
 
+	#[test]
+	fn only_powers_of_two_are_valid_sizes() {
+		assert!(is_valid_size(SIZE));
+		assert!(is_valid_size(1));
+		assert!(is_valid_size(64));
+		assert!(!is_valid_size(0));
+		assert!(!is_valid_size(1000));
+	}
+
 	#[test]
 	fn block_to_cht_number() {
-		assert!(::cht::block_to_cht_number(0).is_none());
-		assert_eq!(::cht::block_to_cht_number(1).unwrap(), 0);
-		assert_eq!(::cht::block_to_cht_number(::cht::SIZE + 1).unwrap(), 1);
-		assert_eq!(::cht::block_to_cht_number(::cht::SIZE).unwrap(), 0);
+		assert!(::cht::block_to_cht_number(0, SIZE).is_none());
+		assert_eq!(::cht::block_to_cht_number(1, SIZE).unwrap(), 0);
+		assert_eq!(::cht::block_to_cht_number(SIZE + 1, SIZE).unwrap(), 1);
+		assert_eq!(::cht::block_to_cht_number(SIZE, SIZE).unwrap(), 0);
+	}
+
+	#[test]
+	fn block_to_cht_number_custom_size() {
+		assert!(::cht::block_to_cht_number(0, 64).is_none());
+		assert_eq!(::cht::block_to_cht_number(1, 64).unwrap(), 0);
+		assert_eq!(::cht::block_to_cht_number(64, 64).unwrap(), 0);
+		assert_eq!(::cht::block_to_cht_number(65, 64).unwrap(), 1);
+		assert_eq!(::cht::block_to_cht_number(SIZE + 1, 64).unwrap(), 32);
 	}
 
 	#[test]
 	fn start_number() {
-		assert_eq!(::cht::start_number(0), 1);
-		assert_eq!(::cht::start_number(1), ::cht::SIZE + 1);
-		assert_eq!(::cht::start_number(2), ::cht::SIZE * 2 + 1);
+		assert_eq!(::cht::start_number(0, SIZE), 1);
+		assert_eq!(::cht::start_number(1, SIZE), SIZE + 1);
+		assert_eq!(::cht::start_number(2, SIZE), SIZE * 2 + 1);
+	}
+
+	#[test]
+	fn start_number_custom_size() {
+		assert_eq!(::cht::start_number(0, 64), 1);
+		assert_eq!(::cht::start_number(1, 64), 65);
+		assert_eq!(::cht::start_number(32, 64), SIZE + 1);
+	}
+
+	#[test]
+	fn build_and_prove_custom_size() {
+		const CUSTOM_SIZE: u64 = 64;
+
+		// block `n` has hash `n + 1`.
+		let hash = |n: u64| H256::from(n + 1);
+		let fetcher = |id| {
+			let n = match id {
+				BlockId::Number(n) => n,
+				BlockId::Hash(h) => h.low_u64() - 1,
+				_ => return None,
+			};
+			Some(BlockInfo { hash: hash(n), parent_hash: hash(n - 1), total_difficulty: U256::from(n) })
+		};
+
+		let cht = build(1, CUSTOM_SIZE, fetcher).unwrap();
+		assert_eq!(cht.size(), CUSTOM_SIZE);
+
+		let root = compute_root(1, CUSTOM_SIZE, (65..129).map(|n| (hash(n), U256::from(n)))).unwrap();
+		assert_eq!(cht.root(), root);
+		assert!(compute_root(1, CUSTOM_SIZE, (65..128).map(|n| (hash(n), U256::from(n)))).is_none());
+
+		let proof = cht.prove(100, 0).unwrap().unwrap();
+		assert_eq!(check_proof(&proof, 100, root), Some((hash(100), U256::from(100))));
+		assert!(cht.prove(64, 0).unwrap().is_none());
+		assert!(cht.prove(129, 0).unwrap().is_none());
 	}
 }
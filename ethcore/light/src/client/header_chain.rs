@@ -56,6 +56,26 @@ const CURRENT_KEY: &[u8] = &*b"best_and_latest";
 /// Key storing the last canonical epoch transition.
 const LAST_CANONICAL_TRANSITION: &[u8] = &*b"canonical_transition";
 
+/// Key storing the size of CHTs in the database.
+/// Databases created without it contain CHTs of the default size.
+const CHT_SIZE_KEY: &[u8] = &*b"cht_size";
+
+/// Key storing the checkpoint the database was synchronized from, if any.
+const CHECKPOINT_KEY: &[u8] = &*b"checkpoint";
+
+/// The checkpoint a header chain started synchronizing from.
+#[derive(Debug, Clone, PartialEq, RlpEncodable, RlpDecodable)]
+pub struct Checkpoint {
+	/// Hash of the checkpoint block.
+	pub hash: H256,
+	/// Number of the checkpoint block.
+	pub number: u64,
+	/// Total difficulty including the checkpoint block.
+	pub total_difficulty: U256,
+	/// Number of CHT roots covering the blocks before the checkpoint.
+	pub cht_count: usize,
+}
+
 /// Information about a block.
 #[derive(Debug, Clone)]
 pub struct BlockDescriptor {
@@ -171,6 +191,17 @@ fn transition_key(block_hash: H256) -> H264 {
 	key
 }
 
+fn epoch_proof_cache_key(block_hash: H256) -> H264 {
+	const LEADING: u8 = 3;
+
+	let mut key = H264::default();
+
+	key[0] = LEADING;
+	key.0[1..].copy_from_slice(&block_hash.0[..]);
+
+	key
+}
+
 // encode last canonical transition entry: header and proof.
 fn encode_canonical_transition(header: &Header, proof: &[u8]) -> Vec<u8> {
 	let mut stream = RlpStream::new_list(2);
@@ -205,10 +236,13 @@ pub struct HeaderChain {
 	db: Arc<KeyValueDB>,
 	col: Option<u32>,
 	cache: Arc<Mutex<Cache>>,
+	cht_size: u64,
+	checkpoint: Option<Checkpoint>,
 }
 
 impl HeaderChain {
 	/// Create a new header chain given this genesis block and database to read from.
+	/// Fails if the database contains CHTs of a size different from the one in the spec.
 	pub fn new(
 		db: Arc<KeyValueDB>,
 		col: Option<u32>,
@@ -216,14 +250,38 @@ impl HeaderChain {
 		cache: Arc<Mutex<Cache>>,
 		allow_hs: HardcodedSync,
 	) -> Result<Self, Error> {
+		let cht_size = spec.params().cht_size;
+		if !cht::is_valid_size(cht_size) {
+			bail!(format!("Invalid CHT size {}: must be a power of two", cht_size));
+		}
+
 		let mut live_epoch_proofs = ::std::collections::HashMap::default();
 
 		let genesis = ::rlp::encode(&spec.genesis_header());
 		let decoded_header = spec.genesis_header();
 
 		let chain = if let Some(current) = db.get(col, CURRENT_KEY)? {
+			let db_cht_size = match db.get(col, CHT_SIZE_KEY)? {
+				Some(size) => ::rlp::decode(&size).expect("decoding db value failed"),
+				None => cht::SIZE,
+			};
+			if db_cht_size != cht_size {
+				bail!(format!("Database contains CHTs of size {}, but the chain specification uses size {}", db_cht_size, cht_size));
+			}
+
 			let curr : BestAndLatest = ::rlp::decode(&current).expect("decoding db value failed");
 
+			let checkpoint: Option<Checkpoint> = db.get(col, CHECKPOINT_KEY)?
+				.map(|raw| ::rlp::decode(&raw).expect("decoding db value failed"));
+
+			// the database keeps synchronizing from wherever it started.
+			if let (&Some(ref hardcoded_sync), HardcodedSync::Allow) = (&spec.hardcoded_sync, allow_hs) {
+				if checkpoint.as_ref().map(|c| c.hash) != Some(hardcoded_sync.header.hash()) {
+					info!(target: "chain", "Ignoring checkpoint at block #{}: the database is already initialized",
+						hardcoded_sync.header.number());
+				}
+			}
+
 			let mut cur_number = curr.latest_num;
 			let mut candidates = BTreeMap::new();
 
@@ -273,10 +331,12 @@ impl HeaderChain {
 				db,
 				col,
 				cache,
+				cht_size,
+				checkpoint,
 			}
 
 		} else {
-			let chain = HeaderChain {
+			let mut chain = HeaderChain {
 				genesis_header: encoded::Header::new(genesis),
 				best_block: RwLock::new(BlockDescriptor {
 					hash: decoded_header.hash(),
@@ -288,6 +348,8 @@ impl HeaderChain {
 				db: db.clone(),
 				col,
 				cache,
+				cht_size,
+				checkpoint: None,
 			};
 
 			// insert the hardcoded sync into the database.
@@ -308,15 +370,24 @@ impl HeaderChain {
 												hardcoded_sync.total_difficulty, None)?;
 
 				// check that we have enough hardcoded CHT roots. avoids panicking later.
-				let cht_num = cht::block_to_cht_number(decoded_header_num - 1)
+				let cht_num = cht::block_to_cht_number(decoded_header_num - 1, cht_size)
 					.expect("specs provided a hardcoded block with height 0");
 				if cht_num >= hardcoded_sync.chts.len() as u64 {
 					warn!(target: "chain", "specs didn't provide enough CHT roots for its \
 											hardcoded block ; falling back to non-hardcoded sync \
 											mode");
 				} else {
+					let checkpoint = Checkpoint {
+						hash: decoded_header.hash(),
+						number: decoded_header_num,
+						total_difficulty: hardcoded_sync.total_difficulty,
+						cht_count: hardcoded_sync.chts.len(),
+					};
+					batch.put(col, CHECKPOINT_KEY, &::rlp::encode(&checkpoint));
+
 					db.write_buffered(batch);
 					chain.apply_pending(pending);
+					chain.checkpoint = Some(checkpoint);
 				}
 			}
 
@@ -335,6 +406,13 @@ impl HeaderChain {
 			}
 		}
 
+		// remember the size of CHTs once the database is known to be usable with it.
+		if chain.db.get(col, CHT_SIZE_KEY)?.is_none() {
+			let mut batch = chain.db.transaction();
+			batch.put(col, CHT_SIZE_KEY, &::rlp::encode(&cht_size));
+			chain.db.write(batch)?;
+		}
+
 		Ok(chain)
 	}
 
@@ -422,6 +500,10 @@ impl HeaderChain {
 
 			// fix ordering of era before writing.
 			if total_difficulty > cur_era.candidates[0].total_difficulty {
+				if cur_era.canonical_hash != hash {
+					self.invalidate_epoch_proof(transaction, cur_era.canonical_hash);
+				}
+
 				let cur_pos = cur_era.candidates.len() - 1;
 				cur_era.candidates.swap(cur_pos, 0);
 				cur_era.canonical_hash = hash;
@@ -461,12 +543,17 @@ impl HeaderChain {
 				let canon_pos = entry.candidates.iter().position(|x| x.hash == canon_hash)
 					.expect("blocks are only inserted if parent is present; or this is the block we just added; qed");
 
+				// proofs fetched for blocks which are no longer canonical can't be trusted anymore.
+				if entry.canonical_hash != canon_hash {
+					self.invalidate_epoch_proof(transaction, entry.canonical_hash);
+				}
+
 				// move the new canonical entry to the front and set the
 				// era's canonical hash.
 				entry.candidates.swap(0, canon_pos);
 				entry.canonical_hash = canon_hash;
 
-				// what about reorgs > cht::SIZE + HISTORY?
+				// what about reorgs > cht_size + HISTORY?
 				// resetting to the last block of a given CHT should be possible.
 				canon_hash = entry.candidates[0].parent_hash;
 
@@ -486,8 +573,8 @@ impl HeaderChain {
 
 			// produce next CHT root if it's time.
 			let earliest_era = *candidates.keys().next().expect("at least one era just created; qed");
-			if earliest_era + HISTORY + cht::SIZE <= number {
-				let cht_num = cht::block_to_cht_number(earliest_era)
+			if earliest_era + HISTORY + self.cht_size <= number {
+				let cht_num = cht::block_to_cht_number(earliest_era, self.cht_size)
 					.expect("fails only for number == 0; genesis never imported; qed");
 
 				let mut last_canonical_transition = None;
@@ -506,6 +593,8 @@ impl HeaderChain {
 
 						// prune old blocks and epoch proofs.
 						for ancient in &era_entry.candidates {
+							transaction.delete(self.col, &*epoch_proof_cache_key(ancient.hash));
+
 							let maybe_transition = live_epoch_proofs.remove(&ancient.hash);
 							if let Some(epoch_transition) = maybe_transition {
 								transaction.delete(self.col, &*transition_key(ancient.hash));
@@ -532,7 +621,7 @@ impl HeaderChain {
 						let canon = &era_entry.candidates[0];
 						(canon.hash, canon.total_difficulty)
 					};
-					cht::compute_root(cht_num, ::itertools::repeat_call(iter))
+					cht::compute_root(cht_num, self.cht_size, ::itertools::repeat_call(iter))
 						.expect("fails only when too few items; this is checked; qed")
 				};
 
@@ -570,7 +659,7 @@ impl HeaderChain {
 				Some(cht) => cht,
 				None if cht_num != 0 => {
 					// end of the iteration
-					let h_num = 1 + cht_num as u64 * cht::SIZE;
+					let h_num = cht::start_number(cht_num as u64, self.cht_size);
 					let header = if let Some(header) = self.block_header(BlockId::Number(h_num)) {
 						header
 					} else {
@@ -729,7 +818,7 @@ impl HeaderChain {
 
 	/// Get the nth CHT root, if it's been computed.
 	///
-	/// CHT root 0 is from block `1..2048`.
+	/// With the default size, CHT root 0 is from block `1..2048`.
 	/// CHT root 1 is from block `2049..4096`
 	/// and so on.
 	///
@@ -745,6 +834,16 @@ impl HeaderChain {
 		}
 	}
 
+	/// Get the number of blocks in each CHT.
+	pub fn cht_size(&self) -> u64 {
+		self.cht_size
+	}
+
+	/// Get the checkpoint the database started synchronizing from, if any.
+	pub fn checkpoint(&self) -> Option<Checkpoint> {
+		self.checkpoint.clone()
+	}
+
 	/// Get the genesis hash.
 	pub fn genesis_hash(&self) -> H256 {
 		self.genesis_header.hash()
@@ -797,6 +896,39 @@ impl HeaderChain {
 		}
 	}
 
+	/// Get a verified epoch transition proof fetched before for the given block hash.
+	pub fn cached_epoch_proof(&self, hash: H256) -> Option<Vec<u8>> {
+		let key = epoch_proof_cache_key(hash);
+		match self.db.get(self.col, &*key) {
+			Ok(db_fetch) => db_fetch.map(|bytes| {
+				let rlp = Rlp::new(&bytes);
+				rlp.val_at(1).expect("decoding value from db failed")
+			}),
+			Err(e) => {
+				warn!(target: "chain", "Error reading from database: {}", e);
+				None
+			}
+		}
+	}
+
+	/// Cache a verified epoch transition proof of the given block.
+	/// Proofs of blocks which are already pruned from the candidates aren't cached.
+	pub fn cache_epoch_proof(&self, batch: &mut DBTransaction, hash: H256, number: u64, proof: &[u8]) {
+		let earliest_era = self.candidates.read().keys().next().cloned();
+		if earliest_era.map_or(false, |era| number < era) {
+			return;
+		}
+
+		let mut stream = RlpStream::new_list(2);
+		stream.append(&number).append(&proof);
+		batch.put_vec(self.col, &*epoch_proof_cache_key(hash), stream.out());
+	}
+
+	/// Remove the cached epoch transition proof of the given block, if any.
+	pub fn invalidate_epoch_proof(&self, batch: &mut DBTransaction, hash: H256) {
+		batch.delete(self.col, &*epoch_proof_cache_key(hash));
+	}
+
 	/// Get the transition to the epoch the given parent hash is part of
 	/// or transitions to.
 	/// This will give the epoch that any children of this parent belong to.
@@ -859,13 +991,15 @@ impl<'a> Iterator for AncestryIter<'a> {
 
 #[cfg(test)]
 mod tests {
-	use super::{HeaderChain, HardcodedSync};
+	use super::{HeaderChain, HardcodedSync, CHT_SIZE_KEY};
 	use std::sync::Arc;
 
 	use cache::Cache;
+	use cht;
+	use common_types::encoded;
 	use common_types::header::Header;
 	use common_types::ids::BlockId;
-	use ethcore::spec::Spec;
+	use ethcore::spec::{Spec, SpecHardcodedSync};
 	use ethereum_types::U256;
 	use kvdb::KeyValueDB;
 	use kvdb_memorydb;
@@ -1195,7 +1329,7 @@ mod tests {
 		let mut parent_hash = genesis_header.hash();
 		let mut rolling_timestamp = genesis_header.timestamp();
 		let mut total_difficulty = *genesis_header.difficulty();
-		let h_num = 3 * ::cht::SIZE + 1;
+		let h_num = 3 * cht::SIZE + 1;
 		for i in 1..10000 {
 			let mut header = Header::new();
 			header.set_parent_hash(parent_hash);
@@ -1222,4 +1356,139 @@ mod tests {
 		let decoded: Header = hardcoded_sync.header.decode().expect("decoding failed");
 		assert_eq!(decoded.number(), h_num);
 	}
+
+	fn spec_with_cht_size(size: u64) -> Result<Spec, String> {
+		let json = include_str!("../../../res/null_morden.json")
+			.replacen("\t\"params\": {\n", &format!("\t\"params\": {{\n\t\t\"chtSize\": \"{:#x}\",\n", size), 1);
+		Spec::load(&::std::env::temp_dir(), json.as_bytes())
+	}
+
+	#[test]
+	fn custom_cht_size() {
+		const CHT_SIZE: u64 = 64;
+
+		assert!(spec_with_cht_size(100).is_err());
+		let mut spec = spec_with_cht_size(CHT_SIZE).unwrap();
+		let genesis_header = spec.genesis_header();
+		let db = make_db();
+
+		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
+
+		let chain = HeaderChain::new(db.clone(), None, &spec, cache.clone(), HardcodedSync::Allow).unwrap();
+
+		let mut parent_hash = genesis_header.hash();
+		let mut rolling_timestamp = genesis_header.timestamp();
+		for i in 1..3000 {
+			let mut header = Header::new();
+			header.set_parent_hash(parent_hash);
+			header.set_number(i);
+			header.set_timestamp(rolling_timestamp);
+			header.set_difficulty(*genesis_header.difficulty() * i as u32);
+			parent_hash = header.hash();
+
+			let mut tx = db.transaction();
+			let pending = chain.insert(&mut tx, &header, None).unwrap();
+			db.write(tx).unwrap();
+			chain.apply_pending(pending);
+
+			rolling_timestamp += 10;
+		}
+
+		// blocks up to `3000 - HISTORY` are covered by CHTs.
+		assert_eq!(chain.cht_size(), CHT_SIZE);
+		assert!(chain.cht_root(13).is_some());
+		assert!(chain.cht_root(14).is_none());
+		assert!(chain.block_header(BlockId::Number(800)).is_none());
+		assert!(chain.block_header(BlockId::Number(900)).is_some());
+
+		let hardcoded_sync = chain.read_hardcoded_sync().unwrap().unwrap();
+		let hardcoded_num = hardcoded_sync.header.decode().unwrap().number();
+		assert_eq!(hardcoded_num, cht::start_number(hardcoded_sync.chts.len() as u64, CHT_SIZE));
+
+		// the database can't be used with a spec of a different size.
+		assert!(HeaderChain::new(db.clone(), None, &Spec::new_test(), cache.clone(), HardcodedSync::Allow).is_err());
+		assert!(HeaderChain::new(db.clone(), None, &spec, cache.clone(), HardcodedSync::Allow).is_ok());
+
+		spec.hardcoded_sync = Some(hardcoded_sync);
+		let chain = HeaderChain::new(make_db(), None, &spec, cache.clone(), HardcodedSync::Allow).unwrap();
+		assert_eq!(chain.best_block().number, hardcoded_num);
+		assert_eq!(chain.checkpoint().unwrap().number, hardcoded_num);
+
+		// databases synchronized before keep going without the checkpoint.
+		let chain = HeaderChain::new(db, None, &spec, cache, HardcodedSync::Allow).unwrap();
+		assert!(chain.checkpoint().is_none());
+	}
+
+	#[test]
+	fn cht_size_is_not_stored_if_database_is_refused() {
+		let db = make_db();
+		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
+		let mut spec = spec_with_cht_size(64).unwrap();
+		spec.hardcoded_sync = Some(SpecHardcodedSync {
+			header: encoded::Header::new(vec![0xc0]),
+			total_difficulty: 0.into(),
+			chts: vec![],
+		});
+
+		assert!(HeaderChain::new(db.clone(), None, &spec, cache.clone(), HardcodedSync::Allow).is_err());
+		assert!(db.get(None, CHT_SIZE_KEY).unwrap().is_none());
+		assert!(HeaderChain::new(db.clone(), None, &Spec::new_test(), cache, HardcodedSync::Allow).is_ok());
+		assert_eq!(db.get(None, CHT_SIZE_KEY).unwrap().map(|size| ::rlp::decode::<u64>(&size).unwrap()), Some(cht::SIZE));
+	}
+
+	#[test]
+	fn epoch_proof_cache() {
+		const CHT_SIZE: u64 = 64;
+
+		let spec = Spec::new_test();
+		let genesis_header = spec.genesis_header();
+		let db = make_db();
+		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
+
+		let chain = HeaderChain::new(db.clone(), None, &spec, cache, HardcodedSync::Allow, CHT_SIZE).unwrap();
+
+		let import = |parent_hash, number: u64, difficulty: U256| {
+			let mut header = Header::new();
+			header.set_parent_hash(parent_hash);
+			header.set_number(number);
+			header.set_timestamp(genesis_header.timestamp() + number * 10);
+			header.set_difficulty(difficulty);
+
+			let mut tx = db.transaction();
+			let pending = chain.insert(&mut tx, &header, None).unwrap();
+			db.write(tx).unwrap();
+			chain.apply_pending(pending);
+			header.hash()
+		};
+
+		let block_1 = import(genesis_header.hash(), 1, *genesis_header.difficulty());
+		let block_2 = import(block_1, 2, *genesis_header.difficulty());
+
+		let mut tx = db.transaction();
+		chain.cache_epoch_proof(&mut tx, block_2, 2, b"proof");
+		db.write(tx).unwrap();
+		assert_eq!(chain.cached_epoch_proof(block_2), Some(b"proof".to_vec()));
+
+		// reorg across the transition.
+		let fork_2 = import(block_1, 2, *genesis_header.difficulty() * 2);
+		assert_eq!(chain.cached_epoch_proof(block_2), None);
+
+		let mut tx = db.transaction();
+		chain.cache_epoch_proof(&mut tx, fork_2, 2, b"other proof");
+		db.write(tx).unwrap();
+		assert_eq!(chain.cached_epoch_proof(fork_2), Some(b"other proof".to_vec()));
+
+		// proofs are pruned along with the blocks.
+		let mut parent_hash = fork_2;
+		for i in 3..3000 {
+			parent_hash = import(parent_hash, i, *genesis_header.difficulty());
+		}
+
+		assert!(chain.cht_root(0).is_some());
+		let mut tx = db.transaction();
+		chain.cache_epoch_proof(&mut tx, block_2, 2, b"proof");
+		db.write(tx).unwrap();
+		assert_eq!(chain.cached_epoch_proof(block_2), None);
+		assert_eq!(chain.cached_epoch_proof(fork_2), None);
+	}
 }
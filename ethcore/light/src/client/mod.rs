@@ -43,6 +43,7 @@ use self::header_chain::{AncestryIter, HeaderChain, HardcodedSync};
 
 use cache::Cache;
 
+pub use self::header_chain::Checkpoint;
 pub use self::service::Service;
 
 mod header_chain;
@@ -132,6 +133,9 @@ pub trait LightChainClient: Send + Sync {
 	/// Get the `i`th CHT root.
 	fn cht_root(&self, i: usize) -> Option<H256>;
 
+	/// Get the number of blocks in each CHT.
+	fn cht_size(&self) -> u64;
+
 	/// Get a report of import activity since the last call.
 	fn report(&self) -> ClientReport;
 }
@@ -293,6 +297,16 @@ impl<T: ChainDataFetcher> Client<T> {
 		self.chain.cht_root(i)
 	}
 
+	/// Get the number of blocks in each CHT.
+	pub fn cht_size(&self) -> u64 {
+		self.chain.cht_size()
+	}
+
+	/// Get the checkpoint the database started synchronizing from, if any.
+	pub fn checkpoint(&self) -> Option<Checkpoint> {
+		self.chain.checkpoint()
+	}
+
 	/// Import a set of pre-verified headers from the queue.
 	pub fn import_verified(&self) {
 		const MAX: usize = 256;
@@ -336,6 +350,9 @@ impl<T: ChainDataFetcher> Client<T> {
 				}
 				Err(e) => {
 					debug!(target: "client", "Error importing header {:?}: {:?}", (num, hash), e);
+					let mut tx = self.db.transaction();
+					self.chain.invalidate_epoch_proof(&mut tx, hash);
+					self.db.write_buffered(tx);
 					bad.push(hash);
 					continue;
 				}
@@ -514,19 +531,29 @@ impl<T: ChainDataFetcher> Client<T> {
 	}
 
 	// attempts to fetch the epoch proof from the network until successful.
+	// proofs fetched before are taken from the cache instead.
 	fn write_pending_proof(&self, header: &Header, proof: Proof<EthereumMachine>) -> Result<(), T::Error> {
+		let mut batch = self.db.transaction();
 		let proof = match proof {
 			Proof::Known(known) => known,
-			Proof::WithState(state_dependent) => {
-				self.fetcher.epoch_transition(
-					header.hash(),
-					self.engine.clone(),
-					state_dependent
-				).into_future().wait()?
-			}
+			Proof::WithState(state_dependent) => match self.chain.cached_epoch_proof(header.hash()) {
+				Some(cached) => {
+					trace!(target: "client", "Using cached epoch transition proof for block #{}", header.number());
+					cached
+				}
+				None => {
+					let proof = self.fetcher.epoch_transition(
+						header.hash(),
+						self.engine.clone(),
+						state_dependent
+					).into_future().wait()?;
+
+					self.chain.cache_epoch_proof(&mut batch, header.hash(), header.number(), &proof);
+					proof
+				}
+			},
 		};
 
-		let mut batch = self.db.transaction();
 		self.chain.insert_pending_transition(&mut batch, header.hash(), &epoch::PendingTransition {
 			proof,
 		});
@@ -610,6 +637,10 @@ impl<T: ChainDataFetcher> LightChainClient for Client<T> {
 		Client::cht_root(self, i)
 	}
 
+	fn cht_size(&self) -> u64 {
+		Client::cht_size(self)
+	}
+
 	fn report(&self) -> ClientReport {
 		Client::report(self)
 	}
@@ -646,3 +677,145 @@ impl<T: ChainDataFetcher> ::ethcore::client::EngineClient for Client<T> {
 		Client::block_header(self, id)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use std::time::Duration;
+
+	use common_types::encoded;
+	use common_types::header::Header;
+	use common_types::receipt::Receipt;
+	use ethcore::engines::{EthEngine, StateDependentProof};
+	use ethcore::machine::EthereumMachine;
+	use ethcore::spec::Spec;
+	use ethereum_types::H256;
+	use io::IoChannel;
+	use kvdb::KeyValueDB;
+	use kvdb_memorydb;
+	use parking_lot::Mutex;
+
+	use cache::Cache;
+	use super::{Client, Config, ChainDataFetcher};
+
+	// counts the epoch transition proofs requested from the network.
+	#[derive(Clone, Default)]
+	struct CountingFetcher {
+		transitions: Arc<AtomicUsize>,
+	}
+
+	impl ChainDataFetcher for CountingFetcher {
+		type Error = &'static str;
+
+		type Body = Result<encoded::Block, &'static str>;
+		type Receipts = Result<Vec<Receipt>, &'static str>;
+		type Transition = Result<Vec<u8>, &'static str>;
+
+		fn block_body(&self, _header: &Header) -> Self::Body {
+			Err("fetching block bodies unavailable")
+		}
+
+		fn block_receipts(&self, _header: &Header) -> Self::Receipts {
+			Err("fetching block receipts unavailable")
+		}
+
+		fn epoch_transition(
+			&self,
+			hash: H256,
+			_engine: Arc<EthEngine>,
+			_checker: Arc<StateDependentProof<EthereumMachine>>
+		) -> Self::Transition {
+			self.transitions.fetch_add(1, Ordering::SeqCst);
+			Ok(hash.to_vec())
+		}
+	}
+
+	// authority round chain handing over to a fresh validator contract every 10 blocks.
+	// every handover is signalled with a proof depending on state.
+	fn rotating_validators_spec() -> Spec {
+		let json = r#"{
+			"name": "RotatingValidators",
+			"engine": {
+				"authorityRound": {
+					"params": {
+						"stepDuration": 1,
+						"validators": {
+							"multi": {
+								"0": { "list": ["0x7d577a597b2742b498cb5cf0c26cdcd726d39e6e"] },
+								"10": { "safeContract": "0x0000000000000000000000000000000000000010" },
+								"20": { "safeContract": "0x0000000000000000000000000000000000000020" },
+								"30": { "safeContract": "0x0000000000000000000000000000000000000030" }
+							}
+						}
+					}
+				}
+			},
+			"params": {
+				"gasLimitBoundDivisor": "0x0400",
+				"accountStartNonce": "0x0",
+				"maximumExtraDataSize": "0x20",
+				"minGasLimit": "0x1388",
+				"networkID": "0x69"
+			},
+			"genesis": {
+				"seal": {
+					"authorityRound": {
+						"step": "0x0",
+						"signature": "0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+					}
+				},
+				"difficulty": "0x20000",
+				"author": "0x0000000000000000000000000000000000000000",
+				"timestamp": "0x00",
+				"parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+				"extraData": "0x",
+				"gasLimit": "0x222222"
+			},
+			"accounts": {}
+		}"#;
+
+		Spec::load(&::std::env::temp_dir(), json.as_bytes()).expect("test spec is valid; qed")
+	}
+
+	#[test]
+	fn epoch_proofs_are_fetched_once() {
+		let spec = rotating_validators_spec();
+		let db: Arc<KeyValueDB> = Arc::new(kvdb_memorydb::create(0));
+
+		let genesis = spec.genesis_header();
+		let headers: Vec<_> = (1..35).scan(genesis.clone(), |parent, i| {
+			let mut header = Header::new();
+			header.set_parent_hash(parent.hash());
+			header.set_number(i);
+			header.set_timestamp(i);
+			header.set_difficulty(*genesis.difficulty());
+			header.set_gas_limit(*genesis.gas_limit());
+			*parent = header.clone();
+			Some(header)
+		}).collect();
+
+		// the second pass over the same headers is served from the proof cache.
+		for &expected_requests in &[3, 0] {
+			let fetcher = CountingFetcher::default();
+			let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
+			let config = Config {
+				verify_full: false,
+				check_seal: false,
+				..Default::default()
+			};
+			let client = Client::new(config, db.clone(), None, &spec, fetcher.clone(), IoChannel::disconnected(), cache).unwrap();
+
+			for header in &headers {
+				client.import_header(header.clone()).unwrap();
+			}
+			client.flush_queue();
+			client.import_verified();
+
+			for header in headers.iter().filter(|h| h.number() % 10 == 0) {
+				assert_eq!(client.chain.pending_transition(header.hash()).unwrap().proof, header.hash().to_vec());
+			}
+			assert_eq!(fetcher.transitions.load(Ordering::SeqCst), expected_requests);
+		}
+	}
+}
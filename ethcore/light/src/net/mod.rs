@@ -34,18 +34,20 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 
-use self::request_credits::{Credits, FlowParams};
+use self::request_credits::{Credits, CreditsRequest, FlowParams};
 use self::context::{Ctx, TickCtx};
 use self::error::Punishment;
 use self::load_timer::{LoadDistribution, NullStore, MOVING_SAMPLE_SIZE};
 use self::request_set::RequestSet;
 use self::id_guard::IdGuard;
+use self::serve_limits::{PeerBudget, ServeWindow};
 
 mod context;
 mod error;
 mod load_timer;
 mod status;
 mod request_set;
+mod serve_limits;
 
 #[cfg(test)]
 mod tests;
@@ -56,6 +58,7 @@ pub use self::context::{BasicContext, EventContext, IoContext};
 pub use self::error::Error;
 pub use self::load_timer::{SampleStore, FileStore};
 pub use self::status::{Status, Capabilities, Announcement};
+pub use self::serve_limits::{ServeLimits, ServedRequests};
 
 const TIMEOUT: TimerToken = 0;
 const TIMEOUT_INTERVAL: Duration = Duration::from_secs(1);
@@ -72,13 +75,19 @@ const RECALCULATE_COSTS_INTERVAL: Duration = Duration::from_secs(60 * 60);
 const STATISTICS_TIMEOUT: TimerToken = 4;
 const STATISTICS_INTERVAL: Duration = Duration::from_secs(15);
 
-/// Maximum load share for the light server
+const DEFERRED_TIMEOUT: TimerToken = 5;
+const DEFERRED_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Default maximum load share for the light server
 pub const MAX_LIGHTSERV_LOAD: f64 = 0.5;
 
 /// Factor to multiply leecher count to cater for
 /// extra sudden connections (should be >= 1.0)
 pub const LEECHER_COUNT_FACTOR: f64 = 1.25;
 
+// the maximum amount of requests we'll fill in a single packet.
+const MAX_REQUESTS: usize = 256;
+
 // minimum interval between updates.
 const UPDATE_INTERVAL: Duration = Duration::from_millis(5000);
 
@@ -173,6 +182,8 @@ pub struct Peer {
 	skip_update: bool,
 	local_flow: Arc<FlowParams>,
 	awaiting_acknowledge: Option<(Instant, Arc<FlowParams>)>,
+	credits_request: Option<CreditsRequest>, // credits they asked us for.
+	budget: PeerBudget,
 }
 
 /// Whether or not a peer was kept by a handler
@@ -267,6 +278,13 @@ pub struct Config {
 	pub max_stored_seconds: u64,
 	/// The network config median peers (used as default peer count)
 	pub median_peers: f64,
+	/// Maximum share of the server's time spent on serving light peers.
+	pub max_load: f64,
+	/// Limits on the rate of requests served to light peers.
+	pub serve_limits: ServeLimits,
+	/// Request credits to ask serving peers for in the handshake.
+	/// `None` accepts whatever they offer.
+	pub credits_request: Option<CreditsRequest>,
 }
 
 impl Default for Config {
@@ -277,6 +295,9 @@ impl Default for Config {
 		Config {
 			max_stored_seconds: MAX_ACCUMULATED,
 			median_peers: MEDIAN_PEERS,
+			max_load: MAX_LIGHTSERV_LOAD,
+			serve_limits: ServeLimits::default(),
+			credits_request: None,
 		}
 	}
 }
@@ -293,6 +314,21 @@ pub struct Params {
 	pub sample_store: Option<Box<SampleStore>>,
 }
 
+/// Request credits of a peer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerCredits {
+	/// Credits the peer has left for requests to us.
+	pub local: U256,
+	/// Maximum credits of the peer for requests to us.
+	pub local_limit: U256,
+	/// Credits of the peer recharged per second.
+	pub local_recharge_rate: U256,
+	/// Our credits left for requests to the peer, if it serves us.
+	pub remote: Option<U256>,
+	/// Maximum of our credits for requests to the peer, if it serves us.
+	pub remote_limit: Option<U256>,
+}
+
 /// Type alias for convenience.
 pub type PeerMap = HashMap<PeerId, Mutex<Peer>>;
 
@@ -405,6 +441,7 @@ pub struct LightProtocol {
 	sample_store: Box<SampleStore>,
 	load_distribution: LoadDistribution,
 	statistics: RwLock<Statistics>,
+	serve_window: Mutex<ServeWindow>,
 }
 
 impl LightProtocol {
@@ -416,13 +453,15 @@ impl LightProtocol {
 		let sample_store = params.sample_store.unwrap_or_else(|| Box::new(NullStore));
 		let load_distribution = LoadDistribution::load(&*sample_store);
 		// Default load share relative to median peers
-		let load_share = MAX_LIGHTSERV_LOAD / params.config.median_peers;
+		let load_share = params.config.max_load / params.config.median_peers;
 		let flow_params = FlowParams::from_request_times(
 			|kind| load_distribution.expected_time(kind),
 			load_share,
 			Duration::from_secs(params.config.max_stored_seconds),
 		);
 
+		let serve_window = ServeWindow::new(params.config.serve_limits.clone(), Instant::now());
+
 		LightProtocol {
 			provider,
 			config: params.config,
@@ -438,6 +477,7 @@ impl LightProtocol {
 			sample_store,
 			load_distribution,
 			statistics: RwLock::new(Statistics::new()),
+			serve_window: Mutex::new(serve_window),
 		}
 	}
 
@@ -447,6 +487,37 @@ impl LightProtocol {
 			.map(|peer| peer.lock().status.clone())
 	}
 
+	/// Get the request credits of a peer, as currently estimated.
+	pub fn peer_credits(&self, peer: PeerId) -> Option<PeerCredits> {
+		self.peers.read().get(&peer).map(|peer| {
+			let peer = peer.lock();
+
+			let mut local = peer.local_credits.clone();
+			peer.local_flow.recharge(&mut local);
+
+			let remote = peer.remote_flow.as_ref().map(|&(ref credits, ref params)| {
+				let mut credits = credits.clone();
+				if peer.pending_requests.is_empty() {
+					params.recharge(&mut credits);
+				}
+				(credits.current(), *params.limit())
+			});
+
+			PeerCredits {
+				local: local.current(),
+				local_limit: *peer.local_flow.limit(),
+				local_recharge_rate: *peer.local_flow.recharge_rate(),
+				remote: remote.map(|(current, _)| current),
+				remote_limit: remote.map(|(_, limit)| limit),
+			}
+		})
+	}
+
+	/// Get the counters of requests served to a peer.
+	pub fn served_requests(&self, peer: PeerId) -> Option<ServedRequests> {
+		self.peers.read().get(&peer).map(|peer| peer.lock().budget.counters())
+	}
+
 	/// Get number of (connected, active) peers.
 	pub fn peer_count(&self) -> (usize, usize) {
 		let num_pending = self.pending_peers.read().len();
@@ -776,7 +847,7 @@ impl LightProtocol {
 		} else {
 			&**cost_local_flow
 		};
-		let status_packet = status::write_handshake(&status, &capabilities, Some(local_flow));
+		let status_packet = status::write_handshake(&status, &capabilities, Some(local_flow), self.config.credits_request.as_ref());
 
 		self.pending_peers.write().insert(peer, PendingPeer {
 			sent_head: chain_info.best_block_hash,
@@ -836,7 +907,7 @@ impl LightProtocol {
 
 		let avg_peer_count = self.statistics.read().avg_peer_count();
 		// Load share relative to average peer count +LEECHER_COUNT_FACTOR%
-		let load_share = MAX_LIGHTSERV_LOAD / (avg_peer_count * LEECHER_COUNT_FACTOR);
+		let load_share = self.config.max_load / (avg_peer_count * LEECHER_COUNT_FACTOR);
 		let new_params = Arc::new(FlowParams::from_request_times(
 			|kind| self.load_distribution.expected_time(kind),
 			load_share,
@@ -848,19 +919,16 @@ impl LightProtocol {
 		let peers = self.peers.read();
 		let now = Instant::now();
 
-		let packet_body = {
-			let mut stream = RlpStream::new_list(3);
-			stream.append(new_params.limit())
-				.append(new_params.recharge_rate())
-				.append(new_params.cost_table());
-			stream.out()
-		};
-
 		for (peer_id, peer_info) in peers.iter() {
 			let mut peer_info = peer_info.lock();
 
-			io.send(*peer_id, packet::UPDATE_CREDITS, packet_body.clone());
-			peer_info.awaiting_acknowledge = Some((now, new_params.clone()));
+			let peer_params = match peer_info.credits_request {
+				Some(ref credits_request) => Arc::new(new_params.negotiate(credits_request)),
+				None => new_params.clone(),
+			};
+
+			io.send(*peer_id, packet::UPDATE_CREDITS, update_credits_packet(&peer_params));
+			peer_info.awaiting_acknowledge = Some((now, peer_params));
 		}
 	}
 
@@ -880,7 +948,7 @@ impl LightProtocol {
 			}
 		};
 
-		let (status, capabilities, flow_params) = status::parse_handshake(data)?;
+		let (status, capabilities, flow_params, credits_request) = status::parse_handshake(data)?;
 
 		trace!(target: "pip", "Connected peer with chain head {:?}", (status.head_hash, status.head_num));
 
@@ -902,6 +970,16 @@ impl LightProtocol {
 			self.flow_params.read().clone()
 		};
 
+		// our status offered the peer our default parameters. if it asked for
+		// others, the negotiated ones apply once the peer acknowledges them.
+		let negotiated_flow = match credits_request {
+			Some(ref credits_request) if !io.is_reserved_peer(peer) => {
+				let negotiated = local_flow.negotiate(credits_request);
+				if negotiated != *local_flow { Some(Arc::new(negotiated)) } else { None }
+			}
+			_ => None,
+		};
+
 		self.peers.write().insert(peer, Mutex::new(Peer {
 			local_credits: local_flow.create_credits(),
 			status: status.clone(),
@@ -914,9 +992,16 @@ impl LightProtocol {
 			propagated_transactions: HashSet::new(),
 			skip_update: false,
 			local_flow,
-			awaiting_acknowledge: None,
+			awaiting_acknowledge: negotiated_flow.clone().map(|params| (Instant::now(), params)),
+			credits_request,
+			budget: PeerBudget::new(io.is_reserved_peer(peer)),
 		}));
 
+		if let Some(params) = negotiated_flow {
+			trace!(target: "pip", "Negotiated request credits with peer {}: {:?}", peer, params);
+			io.send(peer, packet::UPDATE_CREDITS, update_credits_packet(&params));
+		}
+
 		let any_kept = self.handlers.iter().map(
 			|handler| handler.on_connect(
 				&Ctx {
@@ -983,12 +1068,6 @@ impl LightProtocol {
 
 	// Receive requests from a peer.
 	fn request(&self, peer_id: PeerId, io: &IoContext, raw: &Rlp) -> Result<(), Error> {
-		// the maximum amount of requests we'll fill in a single packet.
-		const MAX_REQUESTS: usize = 256;
-
-		use ::request::Builder;
-		use ::request::CompleteRequest;
-
 		let peers = self.peers.read();
 		let peer = match peers.get(&peer_id) {
 			Some(peer) => peer,
@@ -1000,6 +1079,29 @@ impl LightProtocol {
 		let mut peer = peer.lock();
 		let peer: &mut Peer = &mut *peer;
 
+		// packets beyond the peer's budget are served later, in order of arrival.
+		let count = ::std::cmp::min(raw.at(1)?.item_count()?, MAX_REQUESTS) as u64;
+		let admitted = !peer.budget.has_deferred()
+			&& self.serve_window.lock().admit(&mut peer.budget, count, Instant::now());
+
+		if !admitted {
+			trace!(target: "pip", "Deferring {} requests from peer {}", count, peer_id);
+			return match peer.budget.defer(count, raw.as_raw().to_vec()) {
+				true => Ok(()),
+				false => Err(Error::Overburdened),
+			};
+		}
+
+		let response = self.serve_requests(peer_id, peer, raw)?;
+		io.respond(packet::RESPONSE, response);
+		Ok(())
+	}
+
+	// Serve a packet of requests, producing the response packet.
+	fn serve_requests(&self, peer_id: PeerId, peer: &mut Peer, raw: &Rlp) -> Result<Vec<u8>, Error> {
+		use ::request::Builder;
+		use ::request::CompleteRequest;
+
 		let req_id: u64 = raw.val_at(0)?;
 		let mut request_builder = Builder::default();
 
@@ -1040,13 +1142,33 @@ impl LightProtocol {
 		trace!(target: "pip", "Responded to {}/{} requests in packet {}", responses.len(), num_requests, req_id);
 		trace!(target: "pip", "Peer {} has {} credits remaining.", peer_id, peer.local_credits.current());
 
-		io.respond(packet::RESPONSE, {
-			let mut stream = RlpStream::new_list(3);
-			let cur_credits = peer.local_credits.current();
-			stream.append(&req_id).append(&cur_credits).append_list(&responses);
-			stream.out()
-		});
-		Ok(())
+		let mut stream = RlpStream::new_list(3);
+		let cur_credits = peer.local_credits.current();
+		stream.append(&req_id).append(&cur_credits).append_list(&responses);
+		Ok(stream.out())
+	}
+
+	// serve packets deferred due to peers exceeding their budget.
+	fn serve_deferred(&self, io: &IoContext, now: Instant) {
+		for (&peer_id, peer) in self.peers.read().iter() {
+			let mut peer = peer.lock();
+			let peer: &mut Peer = &mut *peer;
+
+			loop {
+				let packet = match peer.budget.next_deferred(&mut *self.serve_window.lock(), now) {
+					Some(packet) => packet,
+					None => break,
+				};
+
+				match self.serve_requests(peer_id, peer, &Rlp::new(&packet)) {
+					Ok(response) => io.send(peer_id, packet::RESPONSE, response),
+					Err(e) => {
+						punish(peer_id, io, &e);
+						break;
+					}
+				}
+			}
+		}
 	}
 
 	// handle a packet with responses.
@@ -1145,6 +1267,15 @@ impl LightProtocol {
 	}
 }
 
+// encode an update of request credit parameters.
+fn update_credits_packet(params: &FlowParams) -> Vec<u8> {
+	let mut stream = RlpStream::new_list(3);
+	stream.append(params.limit())
+		.append(params.recharge_rate())
+		.append(params.cost_table());
+	stream.out()
+}
+
 // if something went wrong, figure out how much to punish the peer.
 fn punish(peer: PeerId, io: &IoContext, e: &Error) {
 	match e.punishment() {
@@ -1172,6 +1303,8 @@ impl NetworkProtocolHandler for LightProtocol {
 			.expect("Error registering request timer interval token.");
 		io.register_timer(STATISTICS_TIMEOUT, STATISTICS_INTERVAL)
 			.expect("Error registering statistics timer.");
+		io.register_timer(DEFERRED_TIMEOUT, DEFERRED_INTERVAL)
+			.expect("Error registering deferred requests timer.");
 	}
 
 	fn read(&self, io: &NetworkContext, peer: &PeerId, packet_id: u8, data: &[u8]) {
@@ -1193,6 +1326,7 @@ impl NetworkProtocolHandler for LightProtocol {
 			PROPAGATE_TIMEOUT => self.propagate_transactions(&io),
 			RECALCULATE_COSTS_TIMEOUT => self.begin_new_cost_period(&io),
 			STATISTICS_TIMEOUT => self.tick_statistics(),
+			DEFERRED_TIMEOUT => self.serve_deferred(&io, Instant::now()),
 			_ => warn!(target: "pip", "received timeout on unknown token {}", timer),
 		}
 	}
@@ -364,7 +364,12 @@ impl FlowParams {
 	/// Recharge the given credits based on time passed since last
 	/// update.
 	pub fn recharge(&self, credits: &mut Credits) {
-		let now = Instant::now();
+		self.recharge_at(credits, Instant::now())
+	}
+
+	/// Recharge the given credits based on time passed between last update and `now`.
+	pub fn recharge_at(&self, credits: &mut Credits, now: Instant) {
+		if now <= credits.recharge_point { return }
 
 		// recompute and update only in terms of full seconds elapsed
 		// in order to keep the estimate as an underestimate.
@@ -385,6 +390,25 @@ impl FlowParams {
 			credits.estimate = self.limit
 		}
 	}
+
+	/// Negotiate the flow parameters of a peer asking for the given credits.
+	/// Neither the limit nor the recharge rate of `self` are ever exceeded.
+	pub fn negotiate(&self, request: &CreditsRequest) -> FlowParams {
+		FlowParams {
+			limit: ::std::cmp::min(self.limit, request.limit),
+			costs: self.costs.clone(),
+			recharge: ::std::cmp::min(self.recharge, request.recharge_rate),
+		}
+	}
+}
+
+/// Request credits a peer asks for in its handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreditsRequest {
+	/// Maximum amount of credits the peer wants to accumulate.
+	pub limit: U256,
+	/// Amount of credits the peer wants to be recharged per second.
+	pub recharge_rate: U256,
 }
 
 impl Default for FlowParams {
@@ -452,4 +476,15 @@ mod tests {
 		assert_eq!(flow_params2.costs, flow_params3.costs);
 		assert_eq!(flow_params.costs.headers.unwrap(), flow_params2.costs.headers.unwrap() * 2u32);
 	}
+
+	#[test]
+	fn negotiation_never_exceeds_offer() {
+		let offer = FlowParams::new(100.into(), Default::default(), 20.into());
+
+		let smaller = offer.negotiate(&CreditsRequest { limit: 50.into(), recharge_rate: 10.into() });
+		assert_eq!(smaller, FlowParams::new(50.into(), Default::default(), 10.into()));
+
+		let larger = offer.negotiate(&CreditsRequest { limit: 500.into(), recharge_rate: 100.into() });
+		assert_eq!(larger, offer);
+	}
 }
@@ -0,0 +1,333 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Limits on the rate of requests served to light peers.
+//!
+//! Served requests are counted in windows of one second. Reserved peers may use
+//! the whole budget of a window, while other peers are capped at a per-peer budget
+//! and only share the part of the budget which isn't reserved.
+//!
+//! Packets of peers exceeding their budget are deferred to a later window instead
+//! of being rejected, so greedy peers see higher latency rather than disconnects.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Length of a window in which served requests are counted.
+pub const WINDOW: Duration = Duration::from_secs(1);
+
+/// Maximum number of request packets deferred per peer.
+pub const MAX_DEFERRED_PACKETS: usize = 64;
+
+/// Limits on the number of requests served per second.
+/// A limit of zero means no limit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServeLimits {
+	/// Requests served per second to all peers.
+	pub total: u64,
+	/// Requests served per second to each non-reserved peer.
+	pub per_peer: u64,
+	/// Share of the total budget guaranteed to reserved peers.
+	pub reserved_share: f64,
+}
+
+impl Default for ServeLimits {
+	fn default() -> Self {
+		ServeLimits {
+			total: 0,
+			per_peer: 0,
+			reserved_share: 0.25,
+		}
+	}
+}
+
+/// Counters of requests served to a peer.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ServedRequests {
+	/// Number of requests served to the peer.
+	pub served: u64,
+	/// Number of requests delayed due to the peer exceeding its budget.
+	pub delayed: u64,
+	/// Number of request packets waiting to be served.
+	pub queued: usize,
+}
+
+/// Serving state of a single peer.
+#[derive(Debug)]
+pub struct PeerBudget {
+	reserved: bool,
+	window: u64,
+	window_served: u64,
+	served: u64,
+	delayed: u64,
+	deferred: VecDeque<(u64, Vec<u8>)>,
+}
+
+impl PeerBudget {
+	/// Create a budget for a newly connected peer.
+	pub fn new(reserved: bool) -> Self {
+		PeerBudget {
+			reserved,
+			window: 0,
+			window_served: 0,
+			served: 0,
+			delayed: 0,
+			deferred: VecDeque::new(),
+		}
+	}
+
+	/// Get the counters of requests served to the peer.
+	pub fn counters(&self) -> ServedRequests {
+		ServedRequests {
+			served: self.served,
+			delayed: self.delayed,
+			queued: self.deferred.len(),
+		}
+	}
+
+	/// Whether any packets of the peer are waiting to be served.
+	/// Newer packets have to wait until these are served.
+	pub fn has_deferred(&self) -> bool {
+		!self.deferred.is_empty()
+	}
+
+	/// Defer a packet with `count` requests to a later window.
+	/// Returns `false` if too many packets of the peer are waiting already.
+	pub fn defer(&mut self, count: u64, packet: Vec<u8>) -> bool {
+		if self.deferred.len() >= MAX_DEFERRED_PACKETS {
+			return false;
+		}
+
+		self.delayed += count;
+		self.deferred.push_back((count, packet));
+		true
+	}
+
+	/// Take the oldest deferred packet if it fits into the budget of the current window.
+	pub fn next_deferred(&mut self, window: &mut ServeWindow, now: Instant) -> Option<Vec<u8>> {
+		let count = match self.deferred.front() {
+			Some(&(count, _)) => count,
+			None => return None,
+		};
+
+		match window.admit(self, count, now) {
+			true => self.deferred.pop_front().map(|(_, packet)| packet),
+			false => None,
+		}
+	}
+}
+
+/// Budget of the current window, shared by all peers.
+#[derive(Debug)]
+pub struct ServeWindow {
+	limits: ServeLimits,
+	start: Instant,
+	index: u64,
+	normal: u64,
+	reserved: u64,
+}
+
+impl ServeWindow {
+	/// Create a new window starting at `now`.
+	pub fn new(limits: ServeLimits, now: Instant) -> Self {
+		ServeWindow {
+			limits,
+			start: now,
+			index: 0,
+			normal: 0,
+			reserved: 0,
+		}
+	}
+
+	/// Attempt to take budget for serving `count` requests to the peer.
+	/// A single packet may overdraw the budget as long as some of it is left,
+	/// so packets larger than the budget are served eventually.
+	pub fn admit(&mut self, peer: &mut PeerBudget, count: u64, now: Instant) -> bool {
+		self.roll(now);
+		if peer.window != self.index {
+			peer.window = self.index;
+			peer.window_served = 0;
+		}
+
+		let total = match self.limits.total {
+			0 => u64::max_value(),
+			total => total,
+		};
+		let admitted = if peer.reserved {
+			self.normal.saturating_add(self.reserved) < total
+		} else {
+			let normal_budget = match self.limits.total {
+				0 => u64::max_value(),
+				total => (total as f64 * (1.0 - self.limits.reserved_share)) as u64,
+			};
+			let peer_budget = match self.limits.per_peer {
+				0 => u64::max_value(),
+				per_peer => per_peer,
+			};
+
+			self.normal < normal_budget && peer.window_served < peer_budget
+		};
+
+		if admitted {
+			match peer.reserved {
+				true => self.reserved += count,
+				false => self.normal += count,
+			}
+			peer.window_served += count;
+			peer.served += count;
+		}
+
+		admitted
+	}
+
+	// begin a new window if the current one has passed.
+	fn roll(&mut self, now: Instant) {
+		if now < self.start + WINDOW {
+			return;
+		}
+
+		let passed = (now - self.start).as_secs();
+		self.start += Duration::from_secs(passed);
+		self.index += passed;
+		self.normal = 0;
+		self.reserved = 0;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn limits(total: u64, per_peer: u64, reserved_share: f64) -> ServeLimits {
+		ServeLimits { total, per_peer, reserved_share }
+	}
+
+	#[test]
+	fn unlimited_by_default() {
+		let now = Instant::now();
+		let mut window = ServeWindow::new(ServeLimits::default(), now);
+		let mut peer = PeerBudget::new(false);
+
+		for _ in 0..1000 {
+			assert!(window.admit(&mut peer, 256, now));
+		}
+		assert_eq!(peer.counters(), ServedRequests { served: 256_000, delayed: 0, queued: 0 });
+	}
+
+	#[test]
+	fn per_peer_budget_resets_each_window() {
+		let now = Instant::now();
+		let mut window = ServeWindow::new(limits(0, 10, 0.25), now);
+		let mut peer = PeerBudget::new(false);
+
+		assert!(window.admit(&mut peer, 6, now));
+		assert!(window.admit(&mut peer, 6, now));
+		assert!(!window.admit(&mut peer, 1, now + Duration::from_millis(999)));
+		assert!(window.admit(&mut peer, 1, now + WINDOW));
+		assert_eq!(peer.counters().served, 13);
+	}
+
+	#[test]
+	fn reserved_share_is_guaranteed() {
+		let now = Instant::now();
+		let mut window = ServeWindow::new(limits(100, 0, 0.25), now);
+		let mut normal = PeerBudget::new(false);
+		let mut reserved = PeerBudget::new(true);
+
+		assert!(window.admit(&mut normal, 75, now));
+		assert!(!window.admit(&mut normal, 1, now));
+
+		// reserved peers aren't capped per peer and may use the rest.
+		assert!(window.admit(&mut reserved, 25, now));
+		assert!(!window.admit(&mut reserved, 1, now));
+
+		// but also the whole budget if it's unused.
+		let later = now + WINDOW;
+		assert!(window.admit(&mut reserved, 99, later));
+		assert!(window.admit(&mut reserved, 1, later));
+		assert!(!window.admit(&mut normal, 1, later));
+	}
+
+	#[test]
+	fn deferred_packets_are_bounded() {
+		let mut peer = PeerBudget::new(false);
+		for i in 0..MAX_DEFERRED_PACKETS {
+			assert!(peer.defer(2, vec![i as u8]));
+		}
+		assert!(!peer.defer(2, vec![0]));
+		assert_eq!(peer.counters(), ServedRequests {
+			served: 0,
+			delayed: 2 * MAX_DEFERRED_PACKETS as u64,
+			queued: MAX_DEFERRED_PACKETS,
+		});
+	}
+
+	#[test]
+	fn greedy_peer_does_not_delay_modest_peer() {
+		const TICK: Duration = Duration::from_millis(100);
+
+		let start = Instant::now();
+		let mut window = ServeWindow::new(limits(40, 10, 0.25), start);
+		let mut greedy = PeerBudget::new(false);
+		let mut modest = PeerBudget::new(false);
+
+		let mut greedy_served = 0;
+		let mut modest_latency = Vec::new();
+		let mut modest_sent = Vec::new();
+
+		for tick in 0..100u32 {
+			let now = start + TICK * tick;
+
+			// serve deferred packets first, as the protocol does on its timer.
+			while greedy.next_deferred(&mut window, now).is_some() {
+				greedy_served += 1;
+			}
+			while let Some(packet) = modest.next_deferred(&mut window, now) {
+				modest_latency.push(now - modest_sent[packet[0] as usize]);
+			}
+
+			// the greedy peer sends 20 requests every tick.
+			for _ in 0..20 {
+				if greedy.has_deferred() || !window.admit(&mut greedy, 1, now) {
+					greedy.defer(1, Vec::new());
+				} else {
+					greedy_served += 1;
+				}
+			}
+
+			// the modest one sends 2 every second.
+			if tick % 10 == 0 {
+				modest_sent.push(now);
+				let packet = vec![(modest_sent.len() - 1) as u8];
+				if modest.has_deferred() || !window.admit(&mut modest, 2, now) {
+					assert!(modest.defer(2, packet));
+				} else {
+					modest_latency.push(Duration::from_secs(0));
+				}
+			}
+		}
+
+		// the greedy peer is capped at its own budget, but not disconnected.
+		assert_eq!(greedy_served, 100);
+		assert_eq!(greedy.counters().queued, MAX_DEFERRED_PACKETS);
+		assert!(greedy.counters().delayed > 0);
+
+		// while every request of the modest one is served right away.
+		assert_eq!(modest_latency.len(), 10);
+		assert!(modest_latency.iter().all(|latency| *latency < WINDOW));
+		assert_eq!(modest.counters(), ServedRequests { served: 20, delayed: 0, queued: 0 });
+	}
+}
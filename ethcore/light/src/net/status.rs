@@ -19,7 +19,7 @@
 use ethereum_types::{H256, U256};
 use rlp::{DecoderError, Encodable, Decodable, RlpStream, Rlp};
 
-use super::request_credits::FlowParams;
+use super::request_credits::{CreditsRequest, FlowParams};
 
 // recognized handshake/announcement keys.
 // unknown keys are to be skipped, known keys have a defined order.
@@ -39,6 +39,8 @@ enum Key {
 	BufferLimit,
 	BufferCostTable,
 	BufferRechargeRate,
+	RequestedBufferLimit,
+	RequestedRechargeRate,
 }
 
 impl Key {
@@ -58,6 +60,8 @@ impl Key {
 			Key::BufferLimit => "flowControl/BL",
 			Key::BufferCostTable => "flowControl/MRC",
 			Key::BufferRechargeRate => "flowControl/MRR",
+			Key::RequestedBufferLimit => "flowControl/requestBL",
+			Key::RequestedRechargeRate => "flowControl/requestMRR",
 		}
 	}
 
@@ -77,6 +81,8 @@ impl Key {
 			"flowControl/BL" => Some(Key::BufferLimit),
 			"flowControl/MRC" => Some(Key::BufferCostTable),
 			"flowControl/MRR" => Some(Key::BufferRechargeRate),
+			"flowControl/requestBL" => Some(Key::RequestedBufferLimit),
+			"flowControl/requestMRR" => Some(Key::RequestedRechargeRate),
 			_ => None
 		}
 	}
@@ -204,11 +210,12 @@ impl Capabilities {
 	}
 }
 
-/// Attempt to parse a handshake message into its three parts:
+/// Attempt to parse a handshake message into its four parts:
 ///   - chain status
 ///   - serving capabilities
 ///   - request credit parameters
-pub fn parse_handshake(rlp: &Rlp) -> Result<(Status, Capabilities, Option<FlowParams>), DecoderError> {
+///   - request credits asked for by the peer
+pub fn parse_handshake(rlp: &Rlp) -> Result<(Status, Capabilities, Option<FlowParams>, Option<CreditsRequest>), DecoderError> {
 	let mut parser = Parser {
 		pos: 0,
 		rlp,
@@ -240,11 +247,24 @@ pub fn parse_handshake(rlp: &Rlp) -> Result<(Status, Capabilities, Option<FlowPa
 		_ => None,
 	};
 
-	Ok((status, capabilities, flow_params))
+	let credits_request = match (
+		parser.expect(Key::RequestedBufferLimit),
+		parser.expect(Key::RequestedRechargeRate)
+	) {
+		(Ok(limit), Ok(recharge_rate)) => Some(CreditsRequest { limit, recharge_rate }),
+		_ => None,
+	};
+
+	Ok((status, capabilities, flow_params, credits_request))
 }
 
-/// Write a handshake, given status, capabilities, and flow parameters.
-pub fn write_handshake(status: &Status, capabilities: &Capabilities, flow_params: Option<&FlowParams>) -> Vec<u8> {
+/// Write a handshake, given status, capabilities, flow parameters, and requested credits.
+pub fn write_handshake(
+	status: &Status,
+	capabilities: &Capabilities,
+	flow_params: Option<&FlowParams>,
+	credits_request: Option<&CreditsRequest>,
+) -> Vec<u8> {
 	let mut pairs = Vec::new();
 	pairs.push(encode_pair(Key::ProtocolVersion, &status.protocol_version));
 	pairs.push(encode_pair(Key::NetworkId, &(status.network_id as u64)));
@@ -272,6 +292,11 @@ pub fn write_handshake(status: &Status, capabilities: &Capabilities, flow_params
 		pairs.push(encode_pair(Key::BufferRechargeRate, flow_params.recharge_rate()));
 	}
 
+	if let Some(credits_request) = credits_request {
+		pairs.push(encode_pair(Key::RequestedBufferLimit, &credits_request.limit));
+		pairs.push(encode_pair(Key::RequestedRechargeRate, &credits_request.recharge_rate));
+	}
+
 	let mut stream = RlpStream::new_list(pairs.len());
 
 	for pair in pairs {
@@ -372,7 +397,7 @@ pub fn write_announcement(announcement: &Announcement) -> Vec<u8> {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use super::super::request_credits::FlowParams;
+	use super::super::request_credits::{CreditsRequest, FlowParams};
 	use ethereum_types::{U256, H256};
 	use rlp::{RlpStream, Rlp};
 
@@ -401,9 +426,9 @@ mod tests {
 			1000.into(),
 		);
 
-		let handshake = write_handshake(&status, &capabilities, Some(&flow_params));
+		let handshake = write_handshake(&status, &capabilities, Some(&flow_params), None);
 
-		let (read_status, read_capabilities, read_flow)
+		let (read_status, read_capabilities, read_flow, _)
 			= parse_handshake(&Rlp::new(&handshake)).unwrap();
 
 		assert_eq!(read_status, status);
@@ -436,9 +461,9 @@ mod tests {
 			1000.into(),
 		);
 
-		let handshake = write_handshake(&status, &capabilities, Some(&flow_params));
+		let handshake = write_handshake(&status, &capabilities, Some(&flow_params), None);
 
-		let (read_status, read_capabilities, read_flow)
+		let (read_status, read_capabilities, read_flow, _)
 			= parse_handshake(&Rlp::new(&handshake)).unwrap();
 
 		assert_eq!(read_status, status);
@@ -471,7 +496,7 @@ mod tests {
 			1000.into(),
 		);
 
-		let handshake = write_handshake(&status, &capabilities, Some(&flow_params));
+		let handshake = write_handshake(&status, &capabilities, Some(&flow_params), None);
 		let interleaved = {
 			let handshake = Rlp::new(&handshake);
 			let mut stream = RlpStream::new_list(handshake.item_count().unwrap_or(0) * 3);
@@ -488,7 +513,7 @@ mod tests {
 			stream.out()
 		};
 
-		let (read_status, read_capabilities, read_flow)
+		let (read_status, read_capabilities, read_flow, _)
 			= parse_handshake(&Rlp::new(&interleaved)).unwrap();
 
 		assert_eq!(read_status, status);
@@ -544,6 +569,35 @@ mod tests {
 		assert!(parse_announcement(&Rlp::new(&out)).is_ok());
 	}
 
+	#[test]
+	fn credits_request_roundtrip() {
+		let status = Status {
+			protocol_version: 1,
+			network_id: 1,
+			head_td: U256::default(),
+			head_hash: H256::default(),
+			head_num: 10,
+			genesis_hash: H256::zero(),
+			last_head: None,
+		};
+
+		let capabilities = Capabilities::default();
+
+		let credits_request = CreditsRequest {
+			limit: 500_000.into(),
+			recharge_rate: 100.into(),
+		};
+
+		let handshake = write_handshake(&status, &capabilities, None, Some(&credits_request));
+
+		let (read_status, _, read_flow, read_request)
+			= parse_handshake(&Rlp::new(&handshake)).unwrap();
+
+		assert_eq!(read_status, status);
+		assert!(read_flow.is_none());
+		assert_eq!(read_request, Some(credits_request));
+	}
+
 	#[test]
 	fn optional_flow() {
 		let status = Status {
@@ -563,9 +617,9 @@ mod tests {
 			tx_relay: true,
 		};
 
-		let handshake = write_handshake(&status, &capabilities, None);
+		let handshake = write_handshake(&status, &capabilities, None, None);
 
-		let (read_status, read_capabilities, read_flow)
+		let (read_status, read_capabilities, read_flow, _)
 			= parse_handshake(&Rlp::new(&handshake)).unwrap();
 
 		assert_eq!(read_status, status);
@@ -25,8 +25,10 @@ use ethcore::client::{EachBlockWith, TestBlockChainClient};
 use ethereum_types::{H256, U256, Address};
 use net::context::IoContext;
 use net::load_timer::MOVING_SAMPLE_SIZE;
+use net::request_credits::CreditsRequest;
 use net::status::{Capabilities, Status};
 use net::{LightProtocol, Params, packet, Peer, Statistics};
+use net::serve_limits::{PeerBudget, ServeLimits, ServeWindow, ServedRequests, WINDOW};
 use network::{PeerId, NodeId};
 use provider::Provider;
 use request::*;
@@ -34,7 +36,7 @@ use request;
 use rlp::{Rlp, RlpStream};
 
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 // helper for encoding a single request into a packet.
 // panics on bad backreference.
@@ -192,11 +194,11 @@ fn capabilities() -> Capabilities {
 
 fn write_handshake(status: &Status, capabilities: &Capabilities, proto: &LightProtocol) -> Vec<u8> {
 	let flow_params = proto.flow_params.read().clone();
-	::net::status::write_handshake(status, capabilities, Some(&*flow_params))
+	::net::status::write_handshake(status, capabilities, Some(&*flow_params), None)
 }
 
 fn write_free_handshake(status: &Status, capabilities: &Capabilities, proto: &LightProtocol) -> Vec<u8> {
-	::net::status::write_handshake(status, capabilities, Some(&proto.free_flow_params))
+	::net::status::write_handshake(status, capabilities, Some(&proto.free_flow_params), None)
 }
 
 // helper for setting up the protocol handler and provider.
@@ -696,6 +698,8 @@ fn id_guard() {
 		skip_update: false,
 		local_flow: flow_params,
 		awaiting_acknowledge: None,
+		credits_request: None,
+		budget: PeerBudget::new(false),
 	}));
 
 	// first, malformed responses.
@@ -812,3 +816,179 @@ fn sync_statistics() {
 	// `MOVING_SAMPLE_SIZE` values
 	assert_eq!(stats.avg_peer_count(), 40.0);
 }
+
+#[test]
+fn credit_exhaustion_and_recharge() {
+	let capabilities = capabilities();
+
+	let (provider, proto) = setup(capabilities);
+	let flow_params = proto.flow_params.read().clone();
+
+	provider.client.add_blocks(10, EachBlockWith::Nothing);
+	let status = status(provider.client.chain_info());
+
+	for peer in 1..3 {
+		let packet_body = write_handshake(&status, &capabilities, &proto);
+		proto.on_connect(peer, &Expect::Send(peer, packet::STATUS, packet_body.clone()));
+		proto.handle_packet(&Expect::Nothing, peer, packet::STATUS, &packet_body);
+	}
+
+	let requests = encode_single(Request::Headers(IncompleteHeadersRequest {
+		start: HashOrNumber::Number(1).into(),
+		max: 1,
+		skip: 0,
+		reverse: false,
+	}));
+	let cost = flow_params.compute_cost_multi(requests.requests()).unwrap();
+
+	// leave both sides with slightly less credits than a single request costs.
+	for peer in 1..3 {
+		let peers = proto.peers.read();
+		let mut peer = peers.get(&peer).unwrap().lock();
+		let peer = &mut *peer;
+
+		let excess = peer.local_credits.current() - cost + 1;
+		peer.local_credits.deduct_cost(excess).unwrap();
+		let (ref mut credits, _) = *peer.remote_flow.as_mut().unwrap();
+		credits.deduct_cost(excess).unwrap();
+	}
+
+	assert_eq!(proto.peer_credits(1).unwrap().local, cost - 1);
+	assert_eq!(proto.peer_credits(1).unwrap().remote, Some(cost - 1));
+	assert_eq!(proto.peer_credits(1).unwrap().local_limit, *flow_params.limit());
+
+	// requests are held back by the client and refused by the server.
+	match proto.request_from(&Expect::Nothing, 1, requests.clone()) {
+		Err(::net::Error::NoCredits) => {},
+		other => panic!("unexpected result: {:?}", other),
+	}
+	proto.handle_packet(&Expect::Punish(1), 1, packet::REQUEST, &make_packet(111, &requests));
+
+	// credits recharge over time.
+	let later = Instant::now() + Duration::from_secs((cost / *flow_params.recharge_rate()).low_u64() + 1);
+	let recharged = {
+		let peers = proto.peers.read();
+		let mut peer = peers.get(&2).unwrap().lock();
+		let peer = &mut *peer;
+
+		flow_params.recharge_at(&mut peer.local_credits, later);
+		let (ref mut credits, ref params) = *peer.remote_flow.as_mut().unwrap();
+		params.recharge_at(credits, later);
+
+		peer.local_credits.current()
+	};
+	assert!(recharged >= cost);
+
+	let request_packet = make_packet(0, &requests);
+	assert_eq!(proto.request_from(&Expect::Send(2, packet::REQUEST, request_packet.clone()), 2, requests.clone()).unwrap().0, 0);
+
+	let response = {
+		let headers = vec![provider.client.block_header(BlockId::Number(1)).unwrap()];
+		let response = vec![Response::Headers(HeadersResponse { headers })];
+
+		let mut stream = RlpStream::new_list(3);
+		stream.append(&0usize).append(&(recharged - cost)).append_list(&response);
+		stream.out()
+	};
+	proto.handle_packet(&Expect::Respond(packet::RESPONSE, response), 2, packet::REQUEST, &request_packet);
+}
+
+#[test]
+fn credits_are_negotiated_per_peer() {
+	let capabilities = capabilities();
+
+	let (provider, proto) = setup(capabilities);
+	let flow_params = proto.flow_params.read().clone();
+	let status = status(provider.client.chain_info());
+
+	for peer in 1..3 {
+		let packet_body = write_handshake(&status, &capabilities, &proto);
+		proto.on_connect(peer, &Expect::Send(peer, packet::STATUS, packet_body));
+	}
+
+	// peer 1 asks for half of the credits we offer, peer 2 takes the offer.
+	let credits_request = CreditsRequest {
+		limit: *flow_params.limit() / 2u32,
+		recharge_rate: *flow_params.recharge_rate() / 2u32,
+	};
+	let negotiated = flow_params.negotiate(&credits_request);
+
+	{
+		let packet_body = ::net::status::write_handshake(&status, &capabilities, Some(&*flow_params), Some(&credits_request));
+		let update = ::net::update_credits_packet(&negotiated);
+		proto.handle_packet(&Expect::Send(1, packet::UPDATE_CREDITS, update), 1, packet::STATUS, &packet_body);
+	}
+
+	{
+		let packet_body = write_handshake(&status, &capabilities, &proto);
+		proto.handle_packet(&Expect::Nothing, 2, packet::STATUS, &packet_body);
+	}
+
+	// the negotiated parameters only apply once acknowledged.
+	assert_eq!(proto.peer_credits(1).unwrap().local_limit, *flow_params.limit());
+	proto.handle_packet(&Expect::Nothing, 1, packet::ACKNOWLEDGE_UPDATE, &[]);
+
+	let credits = proto.peer_credits(1).unwrap();
+	assert_eq!(credits.local_limit, *negotiated.limit());
+	assert_eq!(credits.local_recharge_rate, *negotiated.recharge_rate());
+
+	let credits = proto.peer_credits(2).unwrap();
+	assert_eq!(credits.local_limit, *flow_params.limit());
+	assert_eq!(credits.local_recharge_rate, *flow_params.recharge_rate());
+}
+
+#[test]
+fn requests_over_budget_are_deferred() {
+	let capabilities = capabilities();
+
+	let (provider, proto) = setup(capabilities);
+	let flow_params = proto.flow_params.read().clone();
+	*proto.serve_window.lock() = ServeWindow::new(ServeLimits {
+		total: 0,
+		per_peer: 2,
+		reserved_share: 0.25,
+	}, Instant::now());
+
+	provider.client.add_blocks(10, EachBlockWith::Nothing);
+	let status = status(provider.client.chain_info());
+
+	// peer 1 is greedy, peer 2 modest.
+	for peer in 1..3 {
+		let packet_body = write_handshake(&status, &capabilities, &proto);
+		proto.on_connect(peer, &Expect::Send(peer, packet::STATUS, packet_body.clone()));
+		proto.handle_packet(&Expect::Nothing, peer, packet::STATUS, &packet_body);
+	}
+
+	let requests = encode_single(Request::Headers(IncompleteHeadersRequest {
+		start: HashOrNumber::Number(1).into(),
+		max: 1,
+		skip: 0,
+		reverse: false,
+	}));
+	let cost = flow_params.compute_cost_multi(requests.requests()).unwrap();
+	let response = |req_id: usize, served: u64| {
+		let headers = vec![provider.client.block_header(BlockId::Number(1)).unwrap()];
+		let response = vec![Response::Headers(HeadersResponse { headers })];
+
+		let mut stream = RlpStream::new_list(3);
+		stream.append(&req_id).append(&(*flow_params.limit() - cost * served)).append_list(&response);
+		stream.out()
+	};
+
+	for req_id in 0..2 {
+		let expected = Expect::Respond(packet::RESPONSE, response(req_id, req_id as u64 + 1));
+		proto.handle_packet(&expected, 1, packet::REQUEST, &make_packet(req_id, &requests));
+	}
+
+	// the greedy peer isn't punished, but has to wait for the next window.
+	proto.handle_packet(&Expect::Nothing, 1, packet::REQUEST, &make_packet(2, &requests));
+	assert_eq!(proto.served_requests(1), Some(ServedRequests { served: 2, delayed: 1, queued: 1 }));
+
+	// while the modest one is served right away.
+	proto.handle_packet(&Expect::Respond(packet::RESPONSE, response(0, 1)), 2, packet::REQUEST, &make_packet(0, &requests));
+	assert_eq!(proto.served_requests(2), Some(ServedRequests { served: 1, delayed: 0, queued: 0 }));
+
+	proto.serve_deferred(&Expect::Nothing, Instant::now());
+	proto.serve_deferred(&Expect::Send(1, packet::RESPONSE, response(2, 3)), Instant::now() + WINDOW);
+	assert_eq!(proto.served_requests(1), Some(ServedRequests { served: 3, delayed: 1, queued: 0 }));
+}
@@ -0,0 +1,68 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas price estimation for light clients.
+//!
+//! The light client has no local record of recent transactions, so the gas prices
+//! are collected from block bodies fetched on demand.
+
+use std::sync::Arc;
+
+use common_types::encoded;
+use ethereum_types::{H256, U256};
+use futures::Future;
+use parking_lot::Mutex;
+use stats::Corpus;
+
+use cache::Cache;
+use net::BasicContext;
+
+use super::{request, error, OnDemandRequester};
+
+/// Number of recent blocks with transactions sampled for the gas price corpus.
+pub const GAS_PRICE_SAMPLE_SIZE: usize = 100;
+
+/// Fetch the bodies of the blocks with given headers and collect gas prices of all their
+/// transactions into a corpus.
+///
+/// The corpus is cached for `best_block`, so it's only fetched again after the chain head changes.
+/// Block bodies are cached as well, so only the bodies of new blocks are requested from the network then.
+pub fn fetch_corpus<OD: OnDemandRequester>(
+	ctx: &BasicContext,
+	on_demand: &OD,
+	cache: Arc<Mutex<Cache>>,
+	best_block: H256,
+	headers: Vec<encoded::Header>,
+) -> impl Future<Item = Corpus<U256>, Error = error::Error> + Send {
+	let block_requests = headers.into_iter()
+		.map(|hdr| request::Body(hdr.into()))
+		.collect::<Vec<_>>();
+
+	on_demand.request(ctx, block_requests)
+		.expect("no back-references; therefore all back-references are valid; qed")
+		.map(move |bodies| {
+			let prices = bodies.into_iter().fold(Vec::new(), |mut v, block| {
+				for t in block.transaction_views().iter() {
+					v.push(t.gas_price())
+				}
+				v
+			});
+
+			let corpus: Corpus<_> = prices.into();
+			cache.lock().set_gas_price_corpus(best_block, corpus.clone());
+			corpus
+		})
+}
@@ -0,0 +1,136 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Log queries for light clients.
+//!
+//! Header blooms are used to find blocks which may contain matching logs,
+//! and only receipts of those blocks are fetched on demand.
+
+use std::collections::BTreeMap;
+
+use common_types::BlockNumber;
+use common_types::encoded;
+use common_types::filter::Filter;
+use common_types::log_entry::{LogEntry, LocalizedLogEntry};
+use ethereum_types::H256;
+use futures::{future, Future, Stream};
+use futures::stream::futures_unordered;
+
+use net::BasicContext;
+
+use super::{request, error, OnDemandRequester};
+
+/// Maximum number of blocks whose receipts may be fetched to answer a single query.
+pub const MAX_CANDIDATE_BLOCKS: usize = 256;
+
+/// Number of blocks whose receipts are requested together.
+/// Together with `MAX_CANDIDATE_BLOCKS` it bounds the number of concurrent requests of a query.
+pub const RECEIPTS_BATCH_SIZE: usize = 32;
+
+/// A log matching a filter.
+/// Hash of the transaction is not known until the block body is fetched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedLog {
+	/// Plain log entry.
+	pub entry: LogEntry,
+	/// Block in which this log was created.
+	pub block_hash: H256,
+	/// Block number.
+	pub block_number: BlockNumber,
+	/// Index of transaction within block.
+	pub transaction_index: usize,
+	/// Log position in the block.
+	pub log_index: usize,
+	/// Log position in the transaction.
+	pub transaction_log_index: usize,
+}
+
+impl MatchedLog {
+	/// Complete the log with the hash of its transaction.
+	pub fn localized(self, transaction_hash: H256) -> LocalizedLogEntry {
+		LocalizedLogEntry {
+			entry: self.entry,
+			block_hash: self.block_hash,
+			block_number: self.block_number,
+			transaction_hash,
+			transaction_index: self.transaction_index,
+			log_index: self.log_index,
+			transaction_log_index: self.transaction_log_index,
+		}
+	}
+}
+
+/// Select headers of blocks which may contain logs matching the filter, according to their blooms.
+pub fn candidate_blocks(filter: &Filter, headers: Vec<encoded::Header>) -> Vec<encoded::Header> {
+	let bit_combos = filter.bloom_possibilities();
+	headers.into_iter()
+		.filter(|hdr| {
+			let hdr_bloom = hdr.log_bloom();
+			bit_combos.iter().any(|bloom| hdr_bloom.contains_bloom(bloom))
+		})
+		.collect()
+}
+
+/// Fetch receipts of the candidate blocks in batches and collect logs matching the filter,
+/// ordered by block number and log index.
+pub fn fetch_matching_logs<OD: OnDemandRequester>(
+	ctx: &BasicContext,
+	on_demand: &OD,
+	filter: Filter,
+	candidates: Vec<encoded::Header>,
+) -> impl Future<Item = Vec<MatchedLog>, Error = error::Error> + Send {
+	let batches: Vec<_> = candidates.chunks(RECEIPTS_BATCH_SIZE)
+		.map(|batch| {
+			let blocks: Vec<_> = batch.iter().map(|hdr| (hdr.number(), hdr.hash())).collect();
+			let reqs: Vec<_> = batch.iter().cloned().map(|hdr| request::BlockReceipts(hdr.into())).collect();
+
+			on_demand.request(ctx, reqs)
+				.expect("no back-references; therefore all back-references are valid; qed")
+				.map(move |receipts| blocks.into_iter().zip(receipts).collect::<Vec<_>>())
+		})
+		.collect();
+
+	// as the receipts come in, find logs within them which match the filter.
+	// insert them into a BTreeMap to maintain order by number and block index.
+	futures_unordered(batches)
+		.fold(BTreeMap::new(), move |mut matches, blocks| {
+			for ((block_number, block_hash), receipts) in blocks {
+				let logs = receipts.into_iter()
+					.enumerate()
+					.flat_map(|(transaction_index, receipt)| {
+						receipt.logs.into_iter()
+							.enumerate()
+							.map(move |(transaction_log_index, entry)| (transaction_index, transaction_log_index, entry))
+					})
+					.enumerate();
+
+				for (log_index, (transaction_index, transaction_log_index, entry)) in logs {
+					if filter.matches(&entry) {
+						matches.insert((block_number, log_index), MatchedLog {
+							entry,
+							block_hash,
+							block_number,
+							transaction_index,
+							log_index,
+							transaction_log_index,
+						});
+					}
+				}
+			}
+			future::ok::<_, error::Error>(matches)
+		})
+		.map(|matches| matches.into_iter().map(|(_, log)| log).collect())
+}
@@ -22,7 +22,7 @@ use std::cmp;
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures::{Poll, Future, Async};
 use futures::sync::oneshot::{self, Receiver};
@@ -49,6 +49,8 @@ pub use types::request::ResponseError;
 #[cfg(test)]
 mod tests;
 
+pub mod gas_price;
+pub mod logs;
 pub mod request;
 mod request_guard;
 mod response_guard;
@@ -63,6 +65,10 @@ pub const DEFAULT_RESPONSE_TIME_TO_LIVE: Duration = Duration::from_secs(10);
 pub const DEFAULT_MAX_REQUEST_BACKOFF_ROUNDS: usize = 10;
 /// The default number failed request to be regarded as failure
 pub const DEFAULT_NUM_CONSECUTIVE_FAILED_REQUESTS: usize = 1;
+/// The default time to wait for a peer to answer before the request is dispatched to another peer
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// The default number of peers a request may time out on before it fails
+pub const DEFAULT_MAX_REQUEST_ATTEMPTS: usize = 3;
 
 /// OnDemand related errors
 pub mod error {
@@ -90,6 +96,12 @@ pub mod error {
 				description("OnDemand request maximum backoff iterations exceeded")
 				display("OnDemand request maximum backoff iterations exceeded")
 			}
+
+			#[doc = "No peer answered the request in time"]
+			NoResponsivePeers(attempts: usize) {
+				description("No responsive peers")
+				display("No responsive peers: request timed out {} times", attempts)
+			}
 		}
 	}
 }
@@ -150,6 +162,10 @@ struct Pending {
 	sender: oneshot::Sender<PendingResponse>,
 	request_guard: RequestGuard,
 	response_guard: ResponseGuard,
+	// peer the request is currently dispatched to and when.
+	dispatched: Option<(PeerId, Instant)>,
+	// peers which didn't answer this request in time.
+	timed_out_peers: Vec<PeerId>,
 }
 
 impl Pending {
@@ -285,6 +301,14 @@ impl Pending {
 			debug!(target: "on_demand", "Dropped oneshot channel receiver on time out");
 		}
 	}
+
+	// returning an error after too many peers didn't answer in time
+	fn no_responsive_peers(self) {
+		let err = self::error::ErrorKind::NoResponsivePeers(self.timed_out_peers.len());
+		if self.sender.send(Err(err.into())).is_err() {
+			debug!(target: "on_demand", "Dropped oneshot channel receiver on no responsive peers");
+		}
+	}
 }
 
 // helper to guess capabilities required for a given batch of network requests.
@@ -363,10 +387,14 @@ impl<T: request::RequestAdapter> Future for OnResponses<T> {
 pub struct OnDemand {
 	pending: RwLock<Vec<Pending>>,
 	peers: RwLock<HashMap<PeerId, Peer>>,
+	// number of requests each peer didn't answer in time, used to deprioritize slow peers.
+	peer_timeouts: RwLock<HashMap<PeerId, usize>>,
 	in_transit: RwLock<HashMap<ReqId, Pending>>,
 	cache: Arc<Mutex<Cache>>,
 	no_immediate_dispatch: bool,
 	response_time_window: Duration,
+	request_timeout: Duration,
+	request_attempts_max: usize,
 	request_backoff_start: Duration,
 	request_backoff_max: Duration,
 	request_backoff_rounds_max: usize,
@@ -425,6 +453,8 @@ impl OnDemandRequester for OnDemand {
 				self.request_backoff_max,
 			),
 			response_guard: ResponseGuard::new(self.response_time_window),
+			dispatched: None,
+			timed_out_peers: Vec::new(),
 		});
 
 		Ok(receiver)
@@ -444,9 +474,14 @@ impl OnDemandRequester for OnDemand {
 impl OnDemand {
 
 	/// Create a new `OnDemand` service with the given cache.
+	///
+	/// Requests not answered within `request_timeout` are dispatched to another peer,
+	/// until they time out on `request_attempts_max` peers.
 	pub fn new(
 		cache: Arc<Mutex<Cache>>,
 		response_time_window: Duration,
+		request_timeout: Duration,
+		request_attempts_max: usize,
 		request_backoff_start: Duration,
 		request_backoff_max: Duration,
 		request_backoff_rounds_max: usize,
@@ -456,10 +491,13 @@ impl OnDemand {
 		Self {
 			pending: RwLock::new(Vec::new()),
 			peers: RwLock::new(HashMap::new()),
+			peer_timeouts: RwLock::new(HashMap::new()),
 			in_transit: RwLock::new(HashMap::new()),
 			cache,
 			no_immediate_dispatch: false,
 			response_time_window: Self::sanitize_circuit_breaker_input(response_time_window, "Response time window"),
+			request_timeout: Self::sanitize_circuit_breaker_input(request_timeout, "Request timeout"),
+			request_attempts_max: cmp::max(1, request_attempts_max),
 			request_backoff_start: Self::sanitize_circuit_breaker_input(request_backoff_start, "Request initial backoff time window"),
 			request_backoff_max: Self::sanitize_circuit_breaker_input(request_backoff_max, "Request maximum backoff time window"),
 			request_backoff_rounds_max,
@@ -483,6 +521,8 @@ impl OnDemand {
 	fn new_test(
 		cache: Arc<Mutex<Cache>>,
 		request_ttl: Duration,
+		request_timeout: Duration,
+		request_attempts_max: usize,
 		request_backoff_start: Duration,
 		request_backoff_max: Duration,
 		request_backoff_rounds_max: usize,
@@ -491,6 +531,8 @@ impl OnDemand {
 		let mut me = OnDemand::new(
 			cache,
 			request_ttl,
+			request_timeout,
+			request_attempts_max,
 			request_backoff_start,
 			request_backoff_max,
 			request_backoff_rounds_max,
@@ -522,6 +564,7 @@ impl OnDemand {
 		// iterate over all pending requests, and check them for hang-up.
 		// then, try and find a peer who can serve it.
 		let peers = self.peers.read();
+		let peer_timeouts = self.peer_timeouts.read();
 
 		*pending = ::std::mem::replace(&mut *pending, Vec::new())
 			.into_iter()
@@ -531,13 +574,20 @@ impl OnDemand {
 				let num_peers = peers.len();
 				// The first peer to dispatch the request is chosen at random
 				let rand = rand::thread_rng().gen_range(0, cmp::max(1, num_peers));
-
-				for (peer_id, peer) in peers
+				let mut candidates = peers
 					.iter()
 					.cycle()
 					.skip(rand)
 					.take(num_peers)
-				{
+					.collect::<Vec<_>>();
+
+				// prefer peers which haven't timed out on this request, then the ones timing out least often.
+				candidates.sort_by_key(|&(peer_id, _)| (
+					pending.timed_out_peers.contains(peer_id),
+					peer_timeouts.get(peer_id).cloned().unwrap_or(0),
+				));
+
+				for (peer_id, peer) in candidates {
 
 					if !peer.can_fulfill(&pending.required_capabilities) {
 						trace!(target: "on_demand", "Peer {} without required capabilities, skipping", peer_id);
@@ -546,6 +596,7 @@ impl OnDemand {
 
 					if pending.request_guard.is_call_permitted() {
 						if let Ok(req_id) = ctx.request_from(*peer_id, pending.net_requests.clone()) {
+							pending.dispatched = Some((*peer_id, Instant::now()));
 							self.in_transit.write().insert(req_id, pending);
 							return None;
 						}
@@ -565,6 +616,39 @@ impl OnDemand {
 		trace!(target: "on_demand", "Was unable to dispatch {} requests.", pending.len());
 	}
 
+	// move requests which weren't answered within `request_timeout` back to `pending`,
+	// so they can be dispatched to another peer.
+	fn check_timeouts(&self, now: Instant) {
+		let timed_out = {
+			let mut in_transit = self.in_transit.write();
+			let request_timeout = self.request_timeout;
+			let ids = in_transit.iter()
+				.filter(|&(_, pending)| pending.dispatched.map_or(false, |(_, at)| at + request_timeout <= now))
+				.map(|(id, _)| *id)
+				.collect::<Vec<_>>();
+			ids.into_iter().filter_map(|id| in_transit.remove(&id)).collect::<Vec<_>>()
+		};
+
+		if timed_out.is_empty() {
+			return;
+		}
+
+		let mut pending = self.pending.write();
+		let mut peer_timeouts = self.peer_timeouts.write();
+		for mut timed_out in timed_out {
+			let (peer, _) = timed_out.dispatched.take().expect("only dispatched requests time out; qed");
+			debug!(target: "on_demand", "Peer {} didn't answer in time, reassigning request", peer);
+			*peer_timeouts.entry(peer).or_insert(0) += 1;
+			timed_out.timed_out_peers.push(peer);
+
+			if timed_out.timed_out_peers.len() >= self.request_attempts_max {
+				timed_out.no_responsive_peers();
+			} else {
+				pending.push(timed_out);
+			}
+		}
+	}
+
 	// submit a pending request set. attempts to answer from cache before
 	// going to the network. if complete, sends response and consumes the struct.
 	fn submit_pending(&self, ctx: &BasicContext, mut pending: Pending) {
@@ -600,6 +684,7 @@ impl Handler for OnDemand {
 
 	fn on_disconnect(&self, ctx: &EventContext, unfulfilled: &[ReqId]) {
 		self.peers.write().remove(&ctx.peer());
+		self.peer_timeouts.write().remove(&ctx.peer());
 		let ctx = ctx.as_basic();
 
 		{
@@ -632,6 +717,7 @@ impl Handler for OnDemand {
 			Some(req) => req,
 			None => return,
 		};
+		pending.dispatched = None;
 
 		if responses.is_empty() {
 			// Max number of `bad` responses reached, drop the request
@@ -659,11 +745,17 @@ impl Handler for OnDemand {
 			}
 		}
 
+		// the peer is responsive again.
+		if let Some(timeouts) = self.peer_timeouts.write().get_mut(&ctx.peer()) {
+			*timeouts = timeouts.saturating_sub(1);
+		}
+
 		pending.fill_unanswered();
 		self.submit_pending(ctx.as_basic(), pending);
 	}
 
 	fn tick(&self, ctx: &BasicContext) {
+		self.check_timeouts(Instant::now());
 		self.attempt_dispatch(ctx)
 	}
 }
@@ -765,9 +765,9 @@ pub struct HeaderProof {
 
 impl HeaderProof {
 	/// Construct a new header-by-number request. Fails if the given number is 0.
-	/// Provide the expected CHT root to compare against.
-	pub fn new(num: u64, cht_root: H256) -> Option<Self> {
-		::cht::block_to_cht_number(num).map(|cht_num| HeaderProof {
+	/// Provide the expected CHT root to compare against and the size of CHTs.
+	pub fn new(num: u64, cht_root: H256, cht_size: u64) -> Option<Self> {
+		::cht::block_to_cht_number(num, cht_size).map(|cht_num| HeaderProof {
 			num,
 			cht_num,
 			cht_root,
@@ -1117,7 +1117,7 @@ mod tests {
 
 	#[test]
 	fn no_invalid_header_by_number() {
-		assert!(HeaderProof::new(0, Default::default()).is_none())
+		assert!(HeaderProof::new(0, Default::default(), ::cht::SIZE).is_none())
 	}
 
 	#[test]
@@ -1138,11 +1138,11 @@ mod tests {
 				})
 			};
 
-			cht::build(cht::block_to_cht_number(10_000).unwrap(), fetcher).unwrap()
+			cht::build(cht::block_to_cht_number(10_000, cht::SIZE).unwrap(), cht::SIZE, fetcher).unwrap()
 		};
 
 		let proof = cht.prove(10_000, 0).unwrap().unwrap();
-		let req = HeaderProof::new(10_000, cht.root()).unwrap();
+		let req = HeaderProof::new(10_000, cht.root(), cht::SIZE).unwrap();
 
 		let cache = Mutex::new(make_cache());
 		assert!(req.check_response(&cache, &proof[..]).is_ok());
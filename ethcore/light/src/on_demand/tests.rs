@@ -36,6 +36,7 @@ enum Context {
 	NoOp,
 	WithPeer(PeerId),
 	RequestFrom(PeerId, ReqId),
+	RequestFromAny(ReqId),
 	Punish(PeerId),
 	FaultyRequest,
 }
@@ -63,6 +64,7 @@ impl BasicContext for Context {
 	fn request_from(&self, peer_id: PeerId, _: ::request::NetworkRequests) -> Result<ReqId, Error> {
 		match *self {
 			Context::RequestFrom(id, req_id) => if peer_id == id { Ok(req_id) } else { Err(Error::NoCredits) },
+			Context::RequestFromAny(req_id) => Ok(req_id),
 			Context::FaultyRequest => Err(Error::NoCredits),
 			_ => panic!("didn't expect to have requests dispatched."),
 		}
@@ -97,6 +99,10 @@ impl Harness {
 				cache,
 				// Response `time_to_live`
 				Duration::from_secs(5),
+				// Request timeout
+				super::DEFAULT_REQUEST_TIMEOUT,
+				// Request max attempts
+				super::DEFAULT_MAX_REQUEST_ATTEMPTS,
 				// Request start backoff
 				Duration::from_secs(1),
 				// Request max backoff
@@ -596,3 +602,226 @@ fn empty_responses_exceeds_limit_should_be_dropped() {
 	assert!(harness.service.in_transit.read().is_empty());
 	assert!(harness.service.pending.read().is_empty());
 }
+
+#[test]
+fn reassigns_request_when_peer_does_not_answer() {
+	let harness = Harness::create();
+
+	let slow_peer = 1;
+	let responsive_peer = 2;
+	for peer_id in &[slow_peer, responsive_peer] {
+		harness.inject_peer(*peer_id, Peer {
+			status: dummy_status(),
+			capabilities: dummy_capabilities(),
+		});
+	}
+
+	let header = Header::default();
+	let encoded = header.encoded();
+
+	let recv = harness.service.request_raw(
+		&Context::NoOp,
+		vec![request::HeaderByHash(header.hash().into()).into()]
+	).unwrap();
+
+	// the first peer never answers.
+	harness.service.dispatch_pending(&Context::RequestFrom(slow_peer, ReqId(1)));
+	assert_eq!(harness.service.in_transit.read().len(), 1);
+
+	harness.service.check_timeouts(Instant::now() + harness.service.request_timeout);
+	assert!(harness.service.in_transit.read().is_empty());
+	assert_eq!(harness.service.pending.read().len(), 1);
+	assert_eq!(harness.service.peer_timeouts.read().get(&slow_peer), Some(&1));
+
+	// so the request goes to the other one.
+	harness.service.dispatch_pending(&Context::RequestFromAny(ReqId(2)));
+	assert_eq!(harness.service.in_transit.read()[&ReqId(2)].dispatched.map(|(peer, _)| peer), Some(responsive_peer));
+
+	// a late answer from the slow peer is ignored.
+	harness.service.on_responses(
+		&Context::WithPeer(slow_peer),
+		ReqId(1),
+		&[Response::Headers(basic_request::HeadersResponse { headers: vec![encoded.clone()] })]
+	);
+	assert_eq!(harness.service.in_transit.read().len(), 1);
+
+	harness.service.on_responses(
+		&Context::WithPeer(responsive_peer),
+		ReqId(2),
+		&[Response::Headers(basic_request::HeadersResponse { headers: vec![encoded] })]
+	);
+
+	assert!(recv.wait().unwrap().is_ok());
+}
+
+#[test]
+fn fails_request_when_no_peer_answers() {
+	use super::error::ErrorKind;
+
+	let harness = Harness::create();
+	let peer_id = 10101;
+
+	harness.inject_peer(peer_id, Peer {
+		status: dummy_status(),
+		capabilities: dummy_capabilities(),
+	});
+
+	let recv = harness.service.request_raw(
+		&Context::NoOp,
+		vec![request::HeaderByHash(Header::default().hash().into()).into()]
+	).unwrap();
+
+	for i in 0..super::DEFAULT_MAX_REQUEST_ATTEMPTS {
+		harness.service.dispatch_pending(&Context::RequestFrom(peer_id, ReqId(i)));
+		assert_eq!(harness.service.in_transit.read().len(), 1);
+		harness.service.check_timeouts(Instant::now() + harness.service.request_timeout);
+	}
+
+	assert!(harness.service.in_transit.read().is_empty());
+	assert!(harness.service.pending.read().is_empty());
+	match recv.wait().unwrap() {
+		Err(e) => match *e.kind() {
+			ErrorKind::NoResponsivePeers(attempts) => assert_eq!(attempts, super::DEFAULT_MAX_REQUEST_ATTEMPTS),
+			ref other => panic!("Unexpected error: {:?}", other),
+		},
+		Ok(_) => panic!("Request should fail"),
+	}
+}
+
+#[test]
+fn gas_price_corpus_from_block_bodies() {
+	use common_types::encoded;
+	use common_types::transaction::Transaction;
+	use rlp::RlpStream;
+	use super::gas_price;
+
+	let harness = Harness::create();
+	let cache = harness.service.cache.clone();
+
+	let peer_id = 10101;
+	let req_id = ReqId(14426);
+
+	harness.inject_peer(peer_id, Peer {
+		status: dummy_status(),
+		capabilities: dummy_capabilities(),
+	});
+
+	let blocks = vec![vec![30u64, 10], vec![20]].into_iter().enumerate().map(|(i, prices)| {
+		let transactions = prices.into_iter().map(|gas_price| Transaction {
+			gas_price: gas_price.into(),
+			..Default::default()
+		}.fake_sign(Default::default())).collect::<Vec<_>>();
+
+		let mut body = RlpStream::new_list(2);
+		body.append_list(&transactions).begin_list(0);
+
+		let mut header = Header::default();
+		header.set_number(i as u64 + 1);
+		header.set_transactions_root(::triehash::ordered_trie_root(transactions.iter().map(|t| ::rlp::encode(t))));
+		(header.encoded(), encoded::Body::new(body.out()))
+	}).collect::<Vec<_>>();
+	let best_block = blocks[1].0.hash();
+
+	let corpus = gas_price::fetch_corpus(
+		&Context::NoOp,
+		&harness.service,
+		cache.clone(),
+		best_block,
+		blocks.iter().map(|&(ref header, _)| header.clone()).collect(),
+	);
+
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_id, req_id));
+	harness.service.on_responses(
+		&Context::WithPeer(peer_id),
+		req_id,
+		&blocks.iter()
+			.map(|&(_, ref body)| Response::Body(basic_request::BodyResponse { body: body.clone() }))
+			.collect::<Vec<_>>()
+	);
+
+	let corpus = corpus.wait().unwrap();
+	assert_eq!(corpus, vec![10.into(), 20.into(), 30.into()].into());
+	assert_eq!(corpus.percentile(50), Some(&20.into()));
+	assert_eq!(cache.lock().gas_price_corpus(&best_block), Some(corpus));
+	assert!(cache.lock().gas_price_corpus(&blocks[0].0.hash()).is_none());
+}
+
+#[test]
+fn logs_from_receipts_of_candidate_blocks() {
+	use common_types::filter::Filter;
+	use common_types::ids::BlockId;
+	use common_types::log_entry::LogEntry;
+	use common_types::receipt::{Receipt, TransactionOutcome};
+	use ethereum_types::{Address, Bloom};
+	use super::logs;
+
+	let harness = Harness::create();
+
+	let peer_id = 10101;
+	let req_id = ReqId(14426);
+
+	harness.inject_peer(peer_id, Peer {
+		status: dummy_status(),
+		capabilities: dummy_capabilities(),
+	});
+
+	let (watched, other) = (Address::from(1), Address::from(2));
+	let log = |address| LogEntry { address, topics: vec![H256::from(3)], data: vec![4] };
+	let blocks = vec![
+		vec![vec![log(watched)], vec![log(other)]],
+		vec![],
+		vec![vec![log(other), log(watched)]],
+	].into_iter().enumerate().map(|(i, logs)| {
+		let receipts = logs.into_iter()
+			.map(|logs| Receipt::new(TransactionOutcome::Unknown, Default::default(), logs))
+			.collect::<Vec<_>>();
+
+		let mut header = Header::default();
+		header.set_number(i as u64 + 1);
+		header.set_log_bloom(receipts.iter().fold(Bloom::default(), |mut bloom, r| { bloom.accrue_bloom(&r.log_bloom); bloom }));
+		header.set_receipts_root(::triehash::ordered_trie_root(receipts.iter().map(|r| ::rlp::encode(r))));
+		(header.encoded(), receipts)
+	}).collect::<Vec<_>>();
+
+	let filter = Filter {
+		from_block: BlockId::Earliest,
+		to_block: BlockId::Latest,
+		address: Some(vec![watched]),
+		topics: vec![None, None, None, None],
+		limit: None,
+	};
+
+	// the block without logs is skipped.
+	let candidates = logs::candidate_blocks(&filter, blocks.iter().map(|&(ref header, _)| header.clone()).collect());
+	assert_eq!(candidates.iter().map(|hdr| hdr.number()).collect::<Vec<_>>(), vec![1, 3]);
+
+	let matches = logs::fetch_matching_logs(&Context::NoOp, &harness.service, filter, candidates);
+
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_id, req_id));
+	harness.service.on_responses(
+		&Context::WithPeer(peer_id),
+		req_id,
+		&[&blocks[0], &blocks[2]].iter()
+			.map(|&&(_, ref receipts)| Response::Receipts(basic_request::ReceiptsResponse { receipts: receipts.clone() }))
+			.collect::<Vec<_>>()
+	);
+
+	assert_eq!(matches.wait().unwrap(), vec![
+		logs::MatchedLog {
+			entry: log(watched),
+			block_hash: blocks[0].0.hash(),
+			block_number: 1,
+			transaction_index: 0,
+			log_index: 0,
+			transaction_log_index: 0,
+		},
+		logs::MatchedLog {
+			entry: log(watched),
+			block_hash: blocks[2].0.hash(),
+			block_number: 3,
+			transaction_index: 0,
+			log_index: 1,
+			transaction_log_index: 1,
+		},
+	]);
+}
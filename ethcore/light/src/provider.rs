@@ -23,7 +23,7 @@ use common_types::blockchain_info::BlockChainInfo;
 use common_types::encoded;
 use common_types::ids::BlockId;
 use common_types::transaction::PendingTransaction;
-use ethcore::client::{BlockChainClient, ProvingBlockChainClient, ChainInfo, BlockInfo as ClientBlockInfo};
+use ethcore::client::{BlockChainClient, ProvingBlockChainClient, ChainInfo, EngineInfo, BlockInfo as ClientBlockInfo};
 use ethereum_types::H256;
 use parking_lot::RwLock;
 
@@ -141,7 +141,7 @@ pub trait Provider: Send + Sync {
 }
 
 // Implementation of a light client data provider for a client.
-impl<T: ProvingBlockChainClient + ?Sized> Provider for T {
+impl<T: ProvingBlockChainClient + EngineInfo + ?Sized> Provider for T {
 	fn chain_info(&self) -> BlockChainInfo {
 		ChainInfo::chain_info(self)
 	}
@@ -207,7 +207,8 @@ impl<T: ProvingBlockChainClient + ?Sized> Provider for T {
 	}
 
 	fn header_proof(&self, req: request::CompleteHeaderProofRequest) -> Option<request::HeaderProofResponse> {
-		let cht_number = match cht::block_to_cht_number(req.num) {
+		let cht_size = self.engine().params().cht_size;
+		let cht_number = match cht::block_to_cht_number(req.num, cht_size) {
 			Some(cht_num) => cht_num,
 			None => {
 				debug!(target: "pip_provider", "Requested CHT proof with invalid block number");
@@ -241,7 +242,7 @@ impl<T: ProvingBlockChainClient + ?Sized> Provider for T {
 				}
 			};
 
-			match cht::build(cht_number, block_info) {
+			match cht::build(cht_number, cht_size, block_info) {
 				Some(cht) => cht,
 				None => return None, // incomplete CHT.
 			}
@@ -301,6 +301,44 @@ impl TransactionQueue {
 			.collect()
 	}
 
+	/// Get all queued transactions, ready to be propagated or not.
+	///
+	/// Returned transactions are batched by sender, in order of ascending nonce.
+	pub fn pending_transactions(&self) -> Vec<PendingTransaction> {
+		self.by_account.values()
+			.flat_map(|acct_txs| acct_txs.current.iter().chain(acct_txs.future.values()).map(|info| info.hash))
+			.filter_map(|hash| self.by_hash.get(&hash).cloned())
+			.collect()
+	}
+
+	/// Import transactions persisted by a previous run, given the current nonces of their senders.
+	/// Transactions invalidated by the current nonce of their sender are dropped.
+	///
+	/// Returns the number of imported transactions.
+	pub fn import_persisted(&mut self, transactions: Vec<PendingTransaction>, nonces: &HashMap<Address, U256>) -> usize {
+		let mut imported = 0;
+		for tx in transactions {
+			let hash = tx.hash();
+			if nonces.get(&tx.sender()).map_or(false, |nonce| tx.nonce < *nonce) {
+				debug!(target: "txqueue", "Dropping stale persisted transaction {}", hash);
+				continue;
+			}
+
+			match self.import(tx) {
+				Ok(_) => imported += 1,
+				Err(e) => debug!(target: "txqueue", "Unable to import persisted transaction {}: {}", hash, e),
+			}
+		}
+
+		for (address, nonce) in nonces {
+			if self.by_account.contains_key(address) {
+				self.cull(*address, *nonce);
+			}
+		}
+
+		imported
+	}
+
 	/// Addresses for which we store transactions.
 	pub fn queued_senders(&self) -> Vec<Address> {
 		self.by_account.keys().cloned().collect()
@@ -390,7 +428,8 @@ impl TransactionQueue {
 #[cfg(test)]
 mod tests {
 	use super::TransactionQueue;
-	use ethereum_types::Address;
+	use std::collections::HashMap;
+	use ethereum_types::{Address, U256};
 	use common_types::transaction::{Transaction, PendingTransaction, Condition};
 
 	#[test]
@@ -579,4 +618,26 @@ mod tests {
 		assert_eq!(txq.future_transactions(0, 0).len(), 7);
 		assert_eq!(txq.next_nonce(&sender).unwrap(), 1.into());
 	}
+
+	#[test]
+	fn import_persisted() {
+		let (sender, other) = (Address::from(1), Address::from(2));
+		let tx = |sender, nonce: u64| {
+			let mut tx = Transaction::default();
+			tx.nonce = nonce.into();
+			PendingTransaction::from(tx.fake_sign(sender))
+		};
+
+		let mut txq = TransactionQueue::default();
+		let persisted = vec![tx(sender, 5), tx(sender, 6), tx(sender, 8), tx(other, 0)];
+		let nonces: HashMap<Address, U256> = vec![(sender, 5.into()), (other, 1.into())].into_iter().collect();
+
+		assert_eq!(txq.import_persisted(persisted.clone(), &nonces), 3);
+		assert_eq!(txq.ready_transactions(0, 0), persisted[..2].to_vec());
+		assert_eq!(txq.pending_transactions(), persisted[..3].to_vec());
+		assert_eq!(txq.queued_senders(), vec![sender]);
+
+		// known nonce is used for later imports.
+		assert!(txq.import(tx(sender, 4)).is_err());
+	}
 }
@@ -177,6 +177,11 @@ pub trait ChainNotify : Send + Sync {
 		// does nothing by default
 	}
 
+	/// fires when the latest finalized block of the chain advances
+	fn finalized_head(&self, _hash: H256) {
+		// does nothing by default
+	}
+
 	/// fires when new block is about to be imported
 	/// implementations should be light
 	fn block_pre_import(&self, _bytes: &Bytes, _hash: &H256, _difficulty: &U256) {
@@ -60,6 +60,7 @@ use client::{
 	IoClient, BadBlocks,
 };
 use client::bad_blocks;
+use client::service_transactions::{self, ServiceTransactions};
 use engines::{MAX_UNCLE_AGE, EthEngine, EpochTransition, ForkChoice, EngineError};
 use engines::epoch::PendingTransition;
 use error::{
@@ -239,6 +240,9 @@ pub struct Client {
 	/// A closure to call when we want to restart the client
 	exit_handler: Mutex<Option<Box<Fn(String) + 'static + Send>>>,
 
+	/// Service transactions of the engine waiting to be included
+	service_transactions: ServiceTransactions,
+
 	importer: Importer,
 }
 
@@ -270,6 +274,7 @@ impl Importer {
 		}
 
 		let max_blocks_to_import = client.config.max_round_blocks_to_import;
+		let finalized = client.chain.read().finalized_block_hash();
 		let (imported_blocks, import_results, invalid_blocks, imported, proposed_blocks, duration, has_more_blocks_to_import) = {
 			let mut imported_blocks = Vec::with_capacity(max_blocks_to_import);
 			let mut invalid_blocks = HashSet::new();
@@ -326,6 +331,7 @@ impl Importer {
 
 				if !has_more_blocks_to_import {
 					self.miner.chain_new_blocks(client, &imported_blocks, &invalid_blocks, route.enacted(), route.retracted(), false);
+					client.resubmit_service_transactions();
 				}
 
 				client.notify(|notify| {
@@ -341,6 +347,7 @@ impl Importer {
 						)
 					);
 				});
+				client.notify_finalized_head(finalized);
 			}
 		}
 
@@ -370,6 +377,17 @@ impl Importer {
 		};
 
 		let chain = client.chain.read();
+
+		// Reorgs never revert finalized blocks
+		if let Some(finalized) = chain.finalized_block_hash() {
+			let extends_finalized = chain.tree_route(finalized, *header.parent_hash())
+				.map_or(false, |route| route.ancestor == finalized);
+			if !extends_finalized {
+				warn!(target: "client", "Block import failed for #{} ({}): reverts finalized block {}", header.number(), header.hash(), finalized);
+				return Err(BlockError::RevertsFinalized(finalized).into());
+			}
+		}
+
 		// Verify Block Family
 		let verify_family_result = self.verifier.verify_block_family(
 			&header,
@@ -728,6 +746,21 @@ impl Client {
 			db.key_value().write(batch)?;
 		}
 
+		// without auto rollback nothing may be discarded, an inconsistent best block is an error.
+		let rollback = BlockChain::rollback_inconsistent_head(&*db, config.auto_rollback.unwrap_or(0), |root| state_db.journal_db().contains(root))
+			.map_err(|e| match config.auto_rollback {
+				Some(_) => format!("Best block is inconsistent and could not be rolled back: {}. Use --auto-rollback-depth to roll back further.", e),
+				None => format!("Best block is inconsistent: {}. Remove --no-auto-rollback to roll back to a consistent block.", e),
+			})?;
+
+		if let Some(rollback) = rollback {
+			warn!(target: "client", "Best block was inconsistent, rolled back {} block(s) to #{} ({:x})",
+				rollback.discarded.len(), rollback.best.0, rollback.best.1);
+			for (number, hash) in rollback.discarded {
+				warn!(target: "client", "Discarded block #{} ({})", number, Colour::Red.bold().paint(format!("{:x}", hash)));
+			}
+		}
+
 		let gb = spec.genesis_block();
 		let chain = Arc::new(BlockChain::new(config.blockchain.clone(), &gb, db.clone()));
 		let tracedb = RwLock::new(TraceDB::new(config.tracing.clone(), db.clone(), chain.clone()));
@@ -783,6 +816,10 @@ impl Client {
 			on_user_defaults_change: Mutex::new(None),
 			registrar_address,
 			exit_handler: Mutex::new(None),
+			service_transactions: match config.journal_service_transactions {
+				true => ServiceTransactions::load(db.key_value().clone()),
+				false => ServiceTransactions::in_memory(),
+			},
 			importer,
 			config,
 		});
@@ -828,6 +865,8 @@ impl Client {
 
 		// ensure buffered changes are flushed.
 		client.db.read().key_value().flush()?;
+
+		client.resubmit_service_transactions();
 		Ok(client)
 	}
 
@@ -869,6 +908,16 @@ impl Client {
 		}
 	}
 
+	// notify about the latest finalized block, if it changed since `previous`.
+	fn notify_finalized_head(&self, previous: Option<H256>) {
+		let finalized = self.chain.read().finalized_block_hash();
+		if let Some(hash) = finalized {
+			if finalized != previous {
+				self.notify(|notify| notify.finalized_head(hash));
+			}
+		}
+	}
+
 	/// Register an action to be done if a mode/spec_name change happens.
 	pub fn on_user_defaults_change<F>(&self, f: F) where F: 'static + FnMut(Option<Mode>) + Send {
 		*self.on_user_defaults_change.lock() = Some(Box::new(f));
@@ -1461,6 +1510,16 @@ impl ImportBlock for Client {
 			return Err(EthcoreError::Block(BlockError::UnknownParent(unverified.parent_hash())));
 		}
 
+		// an unknown block at or below the finalized one can only be on a conflicting fork.
+		{
+			let chain = self.chain.read();
+			if let (Some(hash), Some(number)) = (chain.finalized_block_hash(), chain.finalized_block_number()) {
+				if unverified.header.number() <= number {
+					return Err(EthcoreError::Block(BlockError::RevertsFinalized(hash)));
+				}
+			}
+		}
+
 		let raw = if self.importer.block_queue.is_empty() {
 			Some((
 				unverified.bytes.clone(),
@@ -1740,6 +1799,18 @@ impl BlockChainClient for Client {
 		}
 	}
 
+	fn is_finalized(&self, id: BlockId) -> Option<bool> {
+		let chain = self.chain.read();
+		let hash = Self::block_hash(&chain, id)?;
+		let number = chain.block_number(&hash)?;
+		if !self.engine.tracks_finality(number) {
+			return None;
+		}
+
+		let is_canon = chain.block_hash(number) == Some(hash);
+		Some(is_canon && chain.finalized_block_number().map_or(false, |finalized| number <= finalized))
+	}
+
 	fn block_total_difficulty(&self, id: BlockId) -> Option<U256> {
 		let chain = self.chain.read();
 
@@ -2155,6 +2226,58 @@ impl BlockChainClient for Client {
 	}
 
 	fn transact_contract(&self, address: Address, data: Bytes) -> Result<(), transaction::Error> {
+		let signed = self.sign_contract_call(address, data)?;
+		self.importer.miner.import_own_transaction(self, signed.into())
+	}
+
+	fn transact_service_contract(
+		&self,
+		purpose: String,
+		address: Address,
+		data: Bytes,
+		valid_until: Option<BlockNumber>,
+	) -> Result<(), transaction::Error> {
+		// the same call is pending or included already.
+		if let Some(previous) = self.service_transactions.get(&purpose) {
+			let hash = previous.transaction.hash();
+			let same_call = previous.transaction.action == Action::Call(address) && previous.transaction.data == data;
+			if same_call && (self.importer.miner.transaction(&hash).is_some() || self.chain.read().transaction_address(&hash).is_some()) {
+				return Ok(());
+			}
+		}
+
+		let signed = self.sign_contract_call(address, data)?;
+		let hash = signed.hash();
+		self.service_transactions.insert(::local_store::ServiceTransaction {
+			purpose: purpose.clone(),
+			valid_until,
+			transaction: signed.clone(),
+		});
+
+		self.importer.miner.import_own_transaction(self, signed.into()).map_err(|e| {
+			if !service_transactions::is_transient(&e) {
+				self.service_transactions.update(&purpose, &hash, None);
+			}
+			e
+		})
+	}
+
+	fn cancel_service_transaction(&self, purpose: &str) {
+		self.service_transactions.remove(purpose);
+	}
+
+	fn is_service_transaction_journaled(&self, purpose: &str) -> bool {
+		self.service_transactions.get(purpose).is_some()
+	}
+
+	fn registrar_address(&self) -> Option<Address> {
+		self.registrar_address.clone()
+	}
+}
+
+impl Client {
+	// build a transaction calling the contract, signed by the engine.
+	fn sign_contract_call(&self, address: Address, data: Bytes) -> Result<SignedTransaction, transaction::Error> {
 		let authoring_params = self.importer.miner.authoring_params();
 		let service_transaction_checker = self.importer.miner.service_transaction_checker();
 		let gas_price = if let Some(checker) = service_transaction_checker {
@@ -2165,8 +2288,9 @@ impl BlockChainClient for Client {
 		} else {
 			self.importer.miner.sensible_gas_price()
 		};
+		// several service transactions may be pending at once.
 		let transaction = transaction::Transaction {
-			nonce: self.latest_nonce(&authoring_params.author),
+			nonce: self.importer.miner.next_nonce(self, &authoring_params.author),
 			action: Action::Call(address),
 			gas: self.importer.miner.sensible_gas_limit(),
 			gas_price,
@@ -2176,12 +2300,56 @@ impl BlockChainClient for Client {
 		let chain_id = self.engine.signing_chain_id(&self.latest_env_info());
 		let signature = self.engine.sign(transaction.hash(chain_id))
 			.map_err(|e| transaction::Error::InvalidSignature(e.to_string()))?;
-		let signed = SignedTransaction::new(transaction.with_signature(signature, chain_id))?;
-		self.importer.miner.import_own_transaction(self, signed.into())
+		Ok(SignedTransaction::new(transaction.with_signature(signature, chain_id))?)
 	}
 
-	fn registrar_address(&self) -> Option<Address> {
-		self.registrar_address.clone()
+	/// Resubmit journaled service transactions of the engine which aren't pending anymore,
+	/// dropping those which got included or aren't useful anymore.
+	pub fn resubmit_service_transactions(&self) {
+		let best_block = self.chain.read().best_block_number();
+
+		for entry in self.service_transactions.entries() {
+			let hash = entry.transaction.hash();
+			let expired = entry.valid_until.map_or(false, |number| number < best_block);
+			if expired || self.chain.read().transaction_address(&hash).is_some() {
+				trace!(target: "client", "Service transaction {} ({}) is done, expired: {}", entry.purpose, hash, expired);
+				self.service_transactions.update(&entry.purpose, &hash, None);
+				continue;
+			}
+
+			if self.importer.miner.transaction(&hash).is_some() {
+				continue;
+			}
+
+			let address = match entry.transaction.action {
+				Action::Call(address) => address,
+				Action::Create => {
+					self.service_transactions.update(&entry.purpose, &hash, None);
+					continue;
+				},
+			};
+
+			// the signer may not be available yet, in which case it's retried on the next block.
+			let signed = match self.sign_contract_call(address, entry.transaction.data.clone()) {
+				Ok(signed) => signed,
+				Err(e) => {
+					debug!(target: "client", "Error signing service transaction {}: {}", entry.purpose, e);
+					continue;
+				},
+			};
+
+			let resubmitted = signed.hash();
+			self.service_transactions.update(&entry.purpose, &hash, Some(signed.clone()));
+			match self.importer.miner.import_own_transaction(self, signed.into()) {
+				Ok(()) => debug!(target: "client", "Resubmitted service transaction {} ({})", entry.purpose, resubmitted),
+				Err(e) => {
+					debug!(target: "client", "Error resubmitting service transaction {}: {}", entry.purpose, e);
+					if !service_transactions::is_transient(&e) {
+						self.service_transactions.update(&entry.purpose, &resubmitted, None);
+					}
+				},
+			}
+		}
 	}
 }
 
@@ -2291,7 +2459,7 @@ impl ReopenBlock for Client {
 			let h = chain.best_block_hash();
 			// Add new uncles
 			let uncles = chain
-				.find_uncle_hashes(&h, MAX_UNCLE_AGE)
+				.find_uncle_hashes(&h, engine.maximum_uncle_age(block.header.number()))
 				.unwrap_or_else(Vec::new);
 
 			for h in uncles {
@@ -2335,7 +2503,7 @@ impl PrepareOpenBlock for Client {
 
 		// Add uncles
 		chain
-			.find_uncle_headers(&h, MAX_UNCLE_AGE)
+			.find_uncle_headers(&h, engine.maximum_uncle_age(open_block.header.number()))
 			.unwrap_or_else(Vec::new)
 			.into_iter()
 			.take(engine.maximum_uncle_count(open_block.header.number()))
@@ -2366,6 +2534,7 @@ impl ImportSealedBlock for Client {
 		let header = block.header.clone();
 		let hash = header.hash();
 		self.notify(|n| n.block_pre_import(&raw, &hash, header.difficulty()));
+		let finalized = self.chain.read().finalized_block_hash();
 
 		let route = {
 			// Do a super duper basic verification to detect potential bugs
@@ -2410,6 +2579,7 @@ impl ImportSealedBlock for Client {
 			route.retracted(),
 			self.engine.seals_internally().is_some(),
 		);
+		self.resubmit_service_transactions();
 		self.notify(|notify| {
 			notify.new_blocks(
 				NewBlocks::new(
@@ -2423,6 +2593,7 @@ impl ImportSealedBlock for Client {
 				)
 			);
 		});
+		self.notify_finalized_head(finalized);
 		self.db.read().key_value().flush().expect("DB flush failed.");
 		Ok(hash)
 	}
@@ -2524,11 +2695,7 @@ impl SnapshotClient for Client {}
 
 impl Drop for Client {
 	fn drop(&mut self) {
-		if let Some(c) = Arc::get_mut(&mut self.engine) {
-			c.stop()
-		} else {
-			warn!(target: "shutdown", "unable to get mut ref for engine for shutdown.");
-		}
+		self.engine.stop();
 	}
 }
 
@@ -125,6 +125,11 @@ pub struct ClientConfig {
 	pub max_round_blocks_to_import: usize,
 	/// Snapshot configuration
 	pub snapshot: SnapshotConfiguration,
+	/// Maximal number of blocks to roll back at startup if the best block is found
+	/// to be inconsistent. With `None` opening the client fails instead.
+	pub auto_rollback: Option<u64>,
+	/// Whether service transactions of the engine are journaled in the local store.
+	pub journal_service_transactions: bool,
 }
 
 impl Default for ClientConfig {
@@ -151,6 +156,8 @@ impl Default for ClientConfig {
 			transaction_verification_queue_size: 8192,
 			max_round_blocks_to_import: 12,
 			snapshot: Default::default(),
+			auto_rollback: Some(10),
+			journal_service_transactions: true,
 		}
 	}
 }
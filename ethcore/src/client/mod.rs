@@ -23,6 +23,7 @@ mod config;
 #[cfg(any(test, feature = "test-helpers"))]
 mod evm_test_client;
 mod io_message;
+mod service_transactions;
 #[cfg(any(test, feature = "test-helpers"))]
 mod test_client;
 mod trace;
@@ -32,6 +33,7 @@ pub use self::config::{Mode, ClientConfig, DatabaseCompactionProfile, BlockChain
 #[cfg(any(test, feature = "test-helpers"))]
 pub use self::evm_test_client::{EvmTestClient, EvmTestError, TransactErr, TransactSuccess};
 pub use self::io_message::ClientIoMessage;
+pub use self::service_transactions::is_transient as is_transient_transaction_error;
 #[cfg(any(test, feature = "test-helpers"))]
 pub use self::test_client::{TestBlockChainClient, EachBlockWith};
 pub use self::chain_notify::{ChainNotify, NewBlocks, ChainRoute, ChainRouteType, ChainMessageType};
@@ -0,0 +1,183 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Journal of service transactions generated by the consensus engine.
+//!
+//! Service transactions, such as misbehaviour reports, are written to the local store
+//! and resubmitted on startup and on new blocks, until they are included in a block
+//! or aren't useful anymore. Only the latest transaction of each purpose is kept.
+
+use std::sync::Arc;
+
+use ethereum_types::H256;
+use kvdb::KeyValueDB;
+use local_store::{self, ServiceTransaction};
+use parking_lot::Mutex;
+use types::transaction::{self, SignedTransaction};
+
+/// Whether a transaction which failed to be imported may still be included later,
+/// e.g. once our account is funded or the transaction queue has space.
+pub fn is_transient(err: &transaction::Error) -> bool {
+	match *err {
+		transaction::Error::AlreadyImported |
+		transaction::Error::InsufficientBalance { .. } |
+		transaction::Error::Old |
+		transaction::Error::TooCheapToReplace |
+		transaction::Error::LimitReached => true,
+		_ => false,
+	}
+}
+
+/// Service transactions waiting to be included, backed by the local store.
+pub struct ServiceTransactions {
+	db: Option<Arc<KeyValueDB>>,
+	entries: Mutex<Vec<ServiceTransaction>>,
+}
+
+impl ServiceTransactions {
+	/// Load the journal from the database.
+	pub fn load(db: Arc<KeyValueDB>) -> Self {
+		let entries = local_store::service_transactions(&*db, ::db::COL_NODE_INFO).unwrap_or_else(|e| {
+			warn!(target: "client", "Error loading journaled service transactions: {}", e);
+			Vec::new()
+		});
+
+		if !entries.is_empty() {
+			info!(target: "client", "Loaded {} journaled service transactions", entries.len());
+		}
+
+		ServiceTransactions {
+			db: Some(db),
+			entries: Mutex::new(entries),
+		}
+	}
+
+	/// Keep the transactions in memory only, so they are lost on restart.
+	pub fn in_memory() -> Self {
+		ServiceTransactions {
+			db: None,
+			entries: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Journal a new transaction, replacing the one with the same purpose.
+	pub fn insert(&self, tx: ServiceTransaction) {
+		let mut entries = self.entries.lock();
+		entries.retain(|entry| entry.purpose != tx.purpose);
+		entries.push(tx);
+		self.write(&entries);
+	}
+
+	/// Replace or remove a journaled transaction, unless a newer one with the same purpose
+	/// was journaled since `previous`.
+	pub fn update(&self, purpose: &str, previous: &H256, next: Option<SignedTransaction>) {
+		let mut entries = self.entries.lock();
+		let index = match entries.iter().position(|entry| entry.purpose == purpose && entry.transaction.hash() == *previous) {
+			Some(index) => index,
+			None => return,
+		};
+
+		match next {
+			Some(tx) => entries[index].transaction = tx,
+			None => { entries.remove(index); },
+		}
+		self.write(&entries);
+	}
+
+	/// Remove the journaled transaction with the given purpose.
+	pub fn remove(&self, purpose: &str) {
+		let mut entries = self.entries.lock();
+		let len = entries.len();
+		entries.retain(|entry| entry.purpose != purpose);
+		if entries.len() != len {
+			self.write(&entries);
+		}
+	}
+
+	/// Get the journaled transaction with the given purpose.
+	pub fn get(&self, purpose: &str) -> Option<ServiceTransaction> {
+		self.entries.lock().iter().find(|entry| entry.purpose == purpose).cloned()
+	}
+
+	/// Get all journaled transactions.
+	pub fn entries(&self) -> Vec<ServiceTransaction> {
+		self.entries.lock().clone()
+	}
+
+	fn write(&self, entries: &[ServiceTransaction]) {
+		let db = match self.db {
+			Some(ref db) => db,
+			None => return,
+		};
+
+		if let Err(e) = local_store::write_service_transactions(&**db, ::db::COL_NODE_INFO, entries) {
+			warn!(target: "client", "Error journaling service transactions: {}", e);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+
+	use ethereum_types::Address;
+	use ethkey::{Brain, Generator};
+	use local_store::ServiceTransaction;
+	use types::transaction::{Action, Transaction};
+
+	use super::ServiceTransactions;
+
+	fn service_transaction(purpose: &str, nonce: u64) -> ServiceTransaction {
+		let keypair = Brain::new("service".into()).generate().unwrap();
+		let tx = Transaction {
+			nonce: nonce.into(),
+			action: Action::Call(Address::from(0x1234)),
+			..Default::default()
+		};
+
+		ServiceTransaction {
+			purpose: purpose.into(),
+			valid_until: None,
+			transaction: tx.sign(keypair.secret(), None),
+		}
+	}
+
+	#[test]
+	fn deduplicates_by_purpose() {
+		let db = Arc::new(::kvdb_memorydb::create(::db::NUM_COLUMNS.unwrap()));
+		let first = service_transaction("report:1", 0);
+		let replaced = service_transaction("report:2", 1);
+		let latest = service_transaction("report:2", 2);
+
+		{
+			let journal = ServiceTransactions::load(db.clone());
+			journal.insert(first.clone());
+			journal.insert(replaced.clone());
+			journal.insert(latest.clone());
+
+			// the replaced transaction was dropped already.
+			journal.update("report:2", &replaced.transaction.hash(), None);
+		}
+
+		let journal = ServiceTransactions::load(db.clone());
+		assert_eq!(journal.entries(), vec![first.clone(), latest.clone()]);
+
+		let resubmitted = service_transaction("report:1", 3);
+		journal.update("report:1", &first.transaction.hash(), Some(resubmitted.transaction.clone()));
+		journal.update("report:2", &latest.transaction.hash(), None);
+		assert_eq!(ServiceTransactions::load(db).entries(), vec![resubmitted]);
+	}
+}
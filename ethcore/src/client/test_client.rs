@@ -761,6 +761,10 @@ impl BlockChainClient for TestBlockChainClient {
 			.map(|header| self.spec.engine.extra_info(&header))
 	}
 
+	fn is_finalized(&self, _id: BlockId) -> Option<bool> {
+		None
+	}
+
 	fn block_status(&self, id: BlockId) -> BlockStatus {
 		match id {
 			BlockId::Number(number) if (number as usize) < self.blocks.read().len() => BlockStatus::InChain,
@@ -890,6 +894,20 @@ impl BlockChainClient for TestBlockChainClient {
 		self.miner.import_own_transaction(self, signed.into())
 	}
 
+	fn transact_service_contract(
+		&self,
+		_purpose: String,
+		address: Address,
+		data: Bytes,
+		_valid_until: Option<BlockNumber>,
+	) -> Result<(), transaction::Error> {
+		self.transact_contract(address, data)
+	}
+
+	fn cancel_service_transaction(&self, _purpose: &str) {}
+
+	fn is_service_transaction_journaled(&self, _purpose: &str) -> bool { false }
+
 	fn registrar_address(&self) -> Option<Address> { None }
 }
 
@@ -218,6 +218,10 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// Get block status by block header hash.
 	fn block_status(&self, id: BlockId) -> BlockStatus;
 
+	/// Whether the block is part of the canonical chain up to the latest finalized block.
+	/// `None` if the block is unknown or the engine doesn't track finality.
+	fn is_finalized(&self, id: BlockId) -> Option<bool>;
+
 	/// Get block total difficulty.
 	fn block_total_difficulty(&self, id: BlockId) -> Option<U256>;
 
@@ -378,6 +382,24 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// Schedule state-altering transaction to be executed on the next pending block.
 	fn transact_contract(&self, address: Address, data: Bytes) -> Result<(), transaction::Error>;
 
+	/// Schedule a service transaction of the engine calling a contract.
+	///
+	/// The transaction is journaled and resubmitted, also after restarts, until it's included
+	/// or the block `valid_until` has passed. It replaces a previous transaction with the same `purpose`.
+	fn transact_service_contract(
+		&self,
+		purpose: String,
+		address: Address,
+		data: Bytes,
+		valid_until: Option<BlockNumber>,
+	) -> Result<(), transaction::Error>;
+
+	/// Stop resubmitting the journaled service transaction with the given `purpose`.
+	fn cancel_service_transaction(&self, purpose: &str);
+
+	/// Whether a service transaction with the given `purpose` is journaled and resubmitted.
+	fn is_service_transaction_journaled(&self, purpose: &str) -> bool;
+
 	/// Get the address of the registry itself.
 	fn registrar_address(&self) -> Option<Address>;
 }
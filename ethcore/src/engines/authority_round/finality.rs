@@ -101,6 +101,9 @@ impl RollingFinality {
 		self.headers.iter().map(|(h, _)| h)
 	}
 
+	/// Get the number of stored hashes, which follow the last finalized one.
+	pub fn unfinalized_count(&self) -> usize { self.headers.len() }
+
 	/// Get the validator set.
 	pub fn validators(&self) -> &SimpleList { &self.signers }
 
@@ -16,7 +16,7 @@
 
 //! A blockchain engine that supports a non-instant BFT proof-of-authority.
 
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::{cmp, fmt};
 use std::iter::FromIterator;
 use std::ops::Deref;
@@ -36,6 +36,7 @@ use hash::keccak;
 use super::signer::EngineSigner;
 use super::validator_set::{ValidatorSet, SimpleList, new_validator_set};
 use self::finality::RollingFinality;
+use self::randomness::{BoundContract, RandomnessPhase};
 use ethkey::{self, Signature};
 use io::{IoContext, IoHandler, TimerToken, IoService};
 use itertools::{self, Itertools};
@@ -51,15 +52,17 @@ use unexpected::{Mismatch, OutOfBounds};
 use time_utils::CheckedSystemTime;
 
 mod finality;
+mod randomness;
 
 /// `AuthorityRound` params.
 pub struct AuthorityRoundParams {
 	/// Time to wait before next block or authority switching,
-	/// in seconds.
+	/// in seconds, from the given timestamps on. The first duration
+	/// applies from the start of the chain.
 	///
 	/// Deliberately typed as u16 as too high of a value leads
 	/// to slow block issuance.
-	pub step_duration: u16,
+	pub step_durations: BTreeMap<u64, u16>,
 	/// Starting step,
 	pub start_step: Option<u64>,
 	/// Valid validators.
@@ -84,21 +87,38 @@ pub struct AuthorityRoundParams {
 	pub empty_steps_transition: u64,
 	/// Number of accepted empty steps.
 	pub maximum_empty_steps: usize,
+	/// Number of empty steps collected before sealing a block without transactions.
+	/// Blocks are sealed once `maximum_empty_steps` are collected either way.
+	pub minimum_empty_steps_to_seal: usize,
 	/// Transition block to strict empty steps validation.
 	pub strict_empty_steps_transition: u64,
+	/// Randomness contract transition block.
+	pub randomness_contract_transition: u64,
+	/// Address of the commit-reveal randomness contract.
+	pub randomness_contract_address: Option<Address>,
 }
 
 const U16_MAX: usize = ::std::u16::MAX as usize;
 
 impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
 	fn from(p: ethjson::spec::AuthorityRoundParams) -> Self {
-		let mut step_duration_usize: usize = p.step_duration.into();
-		if step_duration_usize > U16_MAX {
-			step_duration_usize = U16_MAX;
-			warn!(target: "engine", "step_duration is too high ({}), setting it to {}", step_duration_usize, U16_MAX);
-		}
+		let map_step_duration = |step_duration: ethjson::uint::Uint| {
+			let step_duration_usize: usize = step_duration.into();
+			if step_duration_usize > U16_MAX {
+				warn!(target: "engine", "step_duration is too high ({}), setting it to {}", step_duration_usize, U16_MAX);
+				return U16_MAX as u16;
+			}
+			step_duration_usize as u16
+		};
+		let step_durations = match p.step_duration {
+			ethjson::spec::StepDuration::Single(duration) => vec![(0, map_step_duration(duration))].into_iter().collect(),
+			ethjson::spec::StepDuration::Transitions(transitions) => transitions.into_iter()
+				.map(|(timestamp, duration)| (timestamp.into(), map_step_duration(duration)))
+				.collect(),
+		};
+		let maximum_empty_steps = p.maximum_empty_steps.map_or(0, Into::into);
 		AuthorityRoundParams {
-			step_duration: step_duration_usize as u16,
+			step_durations,
 			validators: new_validator_set(p.validators),
 			start_step: p.start_step.map(Into::into),
 			validate_score_transition: p.validate_score_transition.map_or(0, Into::into),
@@ -114,10 +134,52 @@ impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
 			maximum_uncle_count_transition: p.maximum_uncle_count_transition.map_or(0, Into::into),
 			maximum_uncle_count: p.maximum_uncle_count.map_or(0, Into::into),
 			empty_steps_transition: p.empty_steps_transition.map_or(u64::max_value(), |n| ::std::cmp::max(n.into(), 1)),
-			maximum_empty_steps: p.maximum_empty_steps.map_or(0, Into::into),
+			maximum_empty_steps: maximum_empty_steps,
+			minimum_empty_steps_to_seal: p.minimum_empty_steps_to_seal.map_or(maximum_empty_steps, Into::into),
 			strict_empty_steps_transition: p.strict_empty_steps_transition.map_or(0, Into::into),
+			randomness_contract_transition: p.randomness_contract_transition.map_or(0, Into::into),
+			randomness_contract_address: p.randomness_contract_address.map(Into::into),
+		}
+	}
+}
+
+// Duration of steps from a transition on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct StepDurationInfo {
+	transition_step: u64,
+	transition_timestamp: u64,
+	step_duration: u64,
+}
+
+// Durations of steps from their transitions on. Transitions take effect at the first step
+// boundary at or after their timestamp, so that all nodes agree on the step at any time.
+// The first transition has to be at timestamp 0.
+fn step_duration_infos(step_durations: &BTreeMap<u64, u16>) -> Vec<StepDurationInfo> {
+	let mut durations = step_durations.values().map(|duration| *duration as u64);
+	let mut infos = vec![StepDurationInfo {
+		transition_step: 0,
+		transition_timestamp: 0,
+		step_duration: durations.next().expect("checked when creating the engine; qed"),
+	}];
+
+	for (timestamp, duration) in step_durations.keys().skip(1).zip(durations) {
+		let prev = *infos.last().expect("infos are never empty; qed");
+		let elapsed = timestamp.saturating_sub(prev.transition_timestamp);
+		let steps = elapsed / prev.step_duration + if elapsed % prev.step_duration == 0 { 0 } else { 1 };
+
+		// a transition taking effect at the same step boundary as the previous one replaces it.
+		if steps == 0 {
+			infos.pop();
 		}
+
+		infos.push(StepDurationInfo {
+			transition_step: prev.transition_step.saturating_add(steps),
+			transition_timestamp: prev.transition_timestamp.saturating_add(steps.saturating_mul(prev.step_duration)),
+			step_duration: duration,
+		});
 	}
+
+	infos
 }
 
 // Helper for managing the step.
@@ -125,16 +187,41 @@ impl From<ethjson::spec::AuthorityRoundParams> for AuthorityRoundParams {
 struct Step {
 	calibrate: bool, // whether calibration is enabled.
 	inner: AtomicUsize,
-	duration: u16,
+	durations: Vec<StepDurationInfo>,
 }
 
 impl Step {
 	fn load(&self) -> u64 { self.inner.load(AtomicOrdering::SeqCst) as u64 }
+
+	// the step in progress at the given time, in seconds since the epoch.
+	fn step_at(&self, timestamp: u64) -> u64 {
+		let info = self.durations.iter()
+			.rev()
+			.find(|info| info.transition_timestamp <= timestamp)
+			.expect("the first transition is at timestamp 0; qed");
+
+		info.transition_step + (timestamp - info.transition_timestamp) / info.step_duration
+	}
+
+	// the time at which the given step starts, in seconds since the epoch.
+	fn step_start(&self, step: u64) -> Option<u64> {
+		let info = self.durations.iter()
+			.rev()
+			.find(|info| info.transition_step <= step)
+			.expect("the first transition is at step 0; qed");
+
+		(step - info.transition_step).checked_mul(info.step_duration)
+			.and_then(|offset| offset.checked_add(info.transition_timestamp))
+	}
+
 	fn duration_remaining(&self) -> Duration {
-		let now = unix_now();
+		self.duration_remaining_at(unix_now())
+	}
+
+	fn duration_remaining_at(&self, now: Duration) -> Duration {
 		let expected_seconds = self.load()
 			.checked_add(1)
-			.and_then(|ctr| ctr.checked_mul(self.duration as u64))
+			.and_then(|ctr| self.step_start(ctr))
 			.map(Duration::from_secs);
 
 		match expected_seconds {
@@ -163,7 +250,7 @@ impl Step {
 
 	fn calibrate(&self) {
 		if self.calibrate {
-			let new_step = unix_now().as_secs() / (self.duration as u64);
+			let new_step = self.step_at(unix_now().as_secs());
 			self.inner.store(new_step as usize, AtomicOrdering::SeqCst);
 		}
 	}
@@ -185,11 +272,10 @@ impl Step {
 			Err(None)
 		// wait a bit for blocks in near future
 		} else if given > current {
-			let d = self.duration as u64;
 			Err(Some(OutOfBounds {
 				min: None,
-				max: Some(d * current),
-				found: d * given,
+				max: self.step_start(current),
+				found: self.step_start(given).unwrap_or(u64::max_value()),
 			}))
 		} else {
 			Ok(())
@@ -429,6 +515,9 @@ pub struct AuthorityRound {
 	empty_steps_transition: u64,
 	strict_empty_steps_transition: u64,
 	maximum_empty_steps: usize,
+	minimum_empty_steps_to_seal: usize,
+	randomness_contract_transition: u64,
+	randomness_contract_address: Option<Address>,
 	machine: EthereumMachine,
 }
 
@@ -661,21 +750,31 @@ impl<'a, A: ?Sized, B> Deref for CowLike<'a, A, B> where B: AsRef<A> {
 impl AuthorityRound {
 	/// Create a new instance of AuthorityRound engine.
 	pub fn new(our_params: AuthorityRoundParams, machine: EthereumMachine) -> Result<Arc<Self>, Error> {
-		if our_params.step_duration == 0 {
+		if our_params.step_durations.is_empty() {
+			error!(target: "engine", "Authority Round step duration is missing, aborting");
+			panic!("authority_round: step duration is missing")
+		}
+		if our_params.step_durations.values().any(|duration| *duration == 0) {
 			error!(target: "engine", "Authority Round step duration can't be zero, aborting");
 			panic!("authority_round: step duration can't be zero")
 		}
+		if our_params.step_durations.keys().next() != Some(&0) {
+			error!(target: "engine", "Authority Round step duration transitions have to start at timestamp 0, aborting");
+			panic!("authority_round: step duration transitions have to start at timestamp 0")
+		}
 		let should_timeout = our_params.start_step.is_none();
-		let initial_step = our_params.start_step.unwrap_or_else(|| (unix_now().as_secs() / (our_params.step_duration as u64)));
+		let step = Step {
+			inner: AtomicUsize::new(0),
+			calibrate: our_params.start_step.is_none(),
+			durations: step_duration_infos(&our_params.step_durations),
+		};
+		let initial_step = our_params.start_step.unwrap_or_else(|| step.step_at(unix_now().as_secs()));
+		step.inner.store(initial_step as usize, AtomicOrdering::SeqCst);
 		let engine = Arc::new(
 			AuthorityRound {
 				transition_service: IoService::<()>::start()?,
 				step: Arc::new(PermissionedStep {
-					inner: Step {
-						inner: AtomicUsize::new(initial_step as usize),
-						calibrate: our_params.start_step.is_none(),
-						duration: our_params.step_duration,
-					},
+					inner: step,
 					can_propose: AtomicBool::new(true),
 				}),
 				client: Arc::new(RwLock::new(None)),
@@ -693,7 +792,10 @@ impl AuthorityRound {
 				maximum_uncle_count: our_params.maximum_uncle_count,
 				empty_steps_transition: our_params.empty_steps_transition,
 				maximum_empty_steps: our_params.maximum_empty_steps,
+				minimum_empty_steps_to_seal: our_params.minimum_empty_steps_to_seal,
 				strict_empty_steps_transition: our_params.strict_empty_steps_transition,
+				randomness_contract_transition: our_params.randomness_contract_transition,
+				randomness_contract_address: our_params.randomness_contract_address,
 				machine: machine,
 			});
 
@@ -819,20 +921,21 @@ impl AuthorityRound {
 	}
 
 	// Returns the hashes of all ancestor blocks that are finalized by the given `chain_head`.
-	fn build_finality(&self, chain_head: &Header, ancestry: &mut Iterator<Item=Header>) -> Vec<H256> {
-		if self.immediate_transitions { return Vec::new() }
+	// returns the newly finalized hashes and the number of the last one of them.
+	fn build_finality(&self, chain_head: &Header, ancestry: &mut Iterator<Item=Header>) -> (Vec<H256>, Option<BlockNumber>) {
+		if self.immediate_transitions { return (Vec::new(), None) }
 
 		let client = match self.client.read().as_ref().and_then(|weak| weak.upgrade()) {
 			Some(client) => client,
 			None => {
 				warn!(target: "engine", "Unable to apply ancestry actions: missing client ref.");
-				return Vec::new();
+				return (Vec::new(), None);
 			}
 		};
 
 		let mut epoch_manager = self.epoch_manager.lock();
 		if !epoch_manager.zoom_to(&*client, &self.machine, &*self.validators, chain_head) {
-			return Vec::new();
+			return (Vec::new(), None);
 		}
 
 		if epoch_manager.finality_checker.subchain_head() != Some(*chain_head.parent_hash()) {
@@ -846,7 +949,7 @@ impl AuthorityRound {
 				Ok(empty_step_signers) => empty_step_signers,
 				Err(_) => {
 					warn!(target: "finality", "Failed to get empty step signatures from block {}", chain_head.hash());
-					return Vec::new();
+					return (Vec::new(), None);
 				}
 			};
 
@@ -873,12 +976,20 @@ impl AuthorityRound {
 
 			if let Err(e) = epoch_manager.finality_checker.build_ancestry_subchain(ancestry_iter) {
 				debug!(target: "engine", "inconsistent validator set within epoch: {:?}", e);
-				return Vec::new();
+				return (Vec::new(), None);
 			}
 		}
 
-		let finalized = epoch_manager.finality_checker.push_hash(chain_head.hash(), vec![*chain_head.author()]);
-		finalized.unwrap_or_default()
+		let finalized = epoch_manager.finality_checker.push_hash(chain_head.hash(), vec![*chain_head.author()])
+			.unwrap_or_default();
+
+		// the unfinalized hashes are the ancestry following the last finalized block up to the chain head.
+		let last_finalized = match finalized.is_empty() {
+			true => None,
+			false => Some(chain_head.number() - epoch_manager.finality_checker.unfinalized_count() as BlockNumber),
+		};
+
+		(finalized, last_finalized)
 	}
 }
 
@@ -1084,11 +1195,13 @@ impl Engine<EthereumMachine> for AuthorityRound {
 			}
 
 			// if there are no transactions to include in the block, we don't seal and instead broadcast a signed
-			// `EmptyStep(step, parent_hash)` message. If we exceed the maximum amount of `empty_step` rounds we proceed
-			// with the seal.
+			// `EmptyStep(step, parent_hash)` message. Once enough `empty_step` messages are collected, or we reach
+			// the maximum amount of `empty_step` rounds, we proceed with the seal. This is a local sealing policy,
+			// so validators with different thresholds still accept each other's blocks.
+			let empty_steps_to_seal = ::std::cmp::min(self.minimum_empty_steps_to_seal, self.maximum_empty_steps);
 			if header.number() >= self.empty_steps_transition &&
 				block.transactions.is_empty() &&
-				empty_steps.len() < self.maximum_empty_steps {
+				empty_steps.len() < empty_steps_to_seal {
 
 				if self.step.can_propose.compare_and_swap(true, false, AtomicOrdering::SeqCst) {
 					self.generate_empty_step(header.parent_hash());
@@ -1174,6 +1287,44 @@ impl Engine<EthereumMachine> for AuthorityRound {
 		self.validators.on_epoch_begin(first, &header, &mut call)
 	}
 
+	/// Take part in randomness collection, if a randomness contract is used.
+	fn on_prepare_block(&self, block: &ExecutedBlock) -> Result<(), Error> {
+		let contract_address = match self.randomness_contract_address {
+			Some(address) if block.header.number() >= self.randomness_contract_transition => address,
+			_ => return Ok(()),
+		};
+
+		let client = match self.client.read().as_ref().and_then(|weak| weak.upgrade()) {
+			Some(client) => client,
+			None => {
+				debug!(target: "engine", "Unable to prepare block: missing client ref.");
+				return Err(EngineError::RequiresClient.into())
+			},
+		};
+		let full_client = client.as_full_client()
+			.ok_or_else(|| EngineError::FailedSystemCall("Failed to upgrade to BlockchainClient.".to_string()))?;
+
+		// the signer is used again to sign the transaction, so it's released before submitting it.
+		let call = {
+			let signer = self.signer.read();
+			let signer = match *signer {
+				Some(ref signer) => signer,
+				None => return Ok(()),
+			};
+
+			let contract = BoundContract::new(full_client, ::client::BlockId::Hash(*block.header.parent_hash()), contract_address);
+			RandomnessPhase::load(&contract, signer.address())?.advance(&contract, contract_address, &**signer)?
+		};
+
+		if let Some(call) = call {
+			trace!(target: "engine", "Submitting randomness transaction {}", call.purpose());
+			full_client.transact_service_contract(call.purpose(), contract_address, call.data(), None)
+				.map_err(|e| EngineError::FailedSystemCall(format!("Failed to submit randomness transaction: {}", e)))?;
+		}
+
+		Ok(())
+	}
+
 	/// Apply the block reward on finalisation of the block.
 	fn on_close_block(&self, block: &mut ExecutedBlock) -> Result<(), Error> {
 		let mut beneficiaries = Vec::new();
@@ -1355,6 +1506,8 @@ impl Engine<EthereumMachine> for AuthorityRound {
 		res
 	}
 
+	fn batch_verifies_transactions(&self) -> bool { true }
+
 	fn genesis_epoch_data(&self, header: &Header, call: &Call) -> Result<Vec<u8>, String> {
 		self.validators.genesis_epoch_data(header, call)
 			.map(|set_proof| combine_proofs(0, &set_proof, &[]))
@@ -1407,7 +1560,7 @@ impl Engine<EthereumMachine> for AuthorityRound {
 			.while_some()
 			.take_while(|header| header.hash() != epoch_transition_hash);
 
-		let finalized = self.build_finality(chain_head, &mut ancestry);
+		let (finalized, _) = self.build_finality(chain_head, &mut ancestry);
 
 		self.is_epoch_end(chain_head, &finalized, chain, transition_store)
 	}
@@ -1536,8 +1689,25 @@ impl Engine<EthereumMachine> for AuthorityRound {
 		super::total_difficulty_fork_choice(new, current)
 	}
 
+	fn additional_params(&self) -> HashMap<String, String> {
+		let mut params = self.machine.additional_params();
+		if let Some(counters) = self.validators.report_counters() {
+			params.insert("reportsSubmitted".into(), counters.submitted.to_string());
+			params.insert("reportsCoalesced".into(), counters.coalesced.to_string());
+			params.insert("reportsThrottled".into(), counters.throttled.to_string());
+			params.insert("reportsStale".into(), counters.stale.to_string());
+			params.insert("reportsFailed".into(), counters.failed.to_string());
+			params.insert("reportsQueued".into(), counters.queued.to_string());
+		}
+		params
+	}
+
+	fn tracks_finality(&self, _number: BlockNumber) -> bool {
+		!self.immediate_transitions
+	}
+
 	fn ancestry_actions(&self, header: &Header, ancestry: &mut Iterator<Item=ExtendedHeader>) -> Vec<AncestryAction> {
-		let finalized = self.build_finality(
+		let (finalized, last_finalized) = self.build_finality(
 			header,
 			&mut ancestry.take_while(|e| !e.is_finalized).map(|e| e.header),
 		);
@@ -1546,6 +1716,10 @@ impl Engine<EthereumMachine> for AuthorityRound {
 			debug!(target: "finality", "Finalizing blocks: {:?}", finalized);
 		}
 
+		if let Some(number) = last_finalized {
+			self.validators.on_finalized(number);
+		}
+
 		finalized.into_iter().map(AncestryAction::MarkFinalized).collect()
 	}
 }
@@ -1555,6 +1729,7 @@ mod tests {
 	use std::collections::BTreeMap;
 	use std::sync::Arc;
 	use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+	use std::time::Duration;
 	use hash::keccak;
 	use accounts::AccountProvider;
 	use ethereum_types::{Address, H520, H256, U256};
@@ -1577,7 +1752,7 @@ mod tests {
 		F: FnOnce(&mut AuthorityRoundParams),
 	{
 		let mut params = AuthorityRoundParams {
-			step_duration: 1,
+			step_durations: [(0, 1)].iter().cloned().collect(),
 			start_step: Some(1),
 			validators: Box::new(TestSet::default()),
 			validate_score_transition: 0,
@@ -1587,10 +1762,13 @@ mod tests {
 			maximum_uncle_count: 0,
 			empty_steps_transition: u64::max_value(),
 			maximum_empty_steps: 0,
+			minimum_empty_steps_to_seal: 0,
 			block_reward: Default::default(),
 			block_reward_contract_transition: 0,
 			block_reward_contract: Default::default(),
 			strict_empty_steps_transition: 0,
+			randomness_contract_transition: 0,
+			randomness_contract_address: None,
 		};
 
 		// mutate aura params
@@ -1819,11 +1997,11 @@ mod tests {
     #[test]
     #[should_panic(expected="counter is too high")]
     fn test_counter_increment_too_high() {
-        use super::Step;
+        use super::{Step, step_duration_infos};
         let step = Step {
             calibrate: false,
             inner: AtomicUsize::new(::std::usize::MAX),
-            duration: 1,
+            durations: step_duration_infos(&[(0, 1)].iter().cloned().collect()),
         };
         step.increment();
 	}
@@ -1831,11 +2009,11 @@ mod tests {
 	#[test]
 	#[should_panic(expected="counter is too high")]
 	fn test_counter_duration_remaining_too_high() {
-		use super::Step;
+		use super::{Step, step_duration_infos};
 		let step = Step {
 			calibrate: false,
 			inner: AtomicUsize::new(::std::usize::MAX),
-			duration: 1,
+			durations: step_duration_infos(&[(0, 1)].iter().cloned().collect()),
 		};
 		step.duration_remaining();
 	}
@@ -1844,10 +2022,119 @@ mod tests {
 	#[should_panic(expected="authority_round: step duration can't be zero")]
 	fn test_step_duration_zero() {
 		aura(|params| {
-			params.step_duration = 0;
+			params.step_durations = [(0, 0)].iter().cloned().collect();
 		});
 	}
 
+	#[test]
+	#[should_panic(expected="authority_round: step duration transitions have to start at timestamp 0")]
+	fn test_step_duration_transitions_not_starting_at_zero() {
+		aura(|params| {
+			params.step_durations = [(10, 5), (1000, 15)].iter().cloned().collect();
+		});
+	}
+
+	// step as seen by a node whose clock shows the given time.
+	fn step_at_time(durations: &BTreeMap<u64, u16>, timestamp: u64) -> super::Step {
+		let step = super::Step {
+			calibrate: false,
+			inner: AtomicUsize::new(0),
+			durations: super::step_duration_infos(durations),
+		};
+		step.inner.store(step.step_at(timestamp) as usize, AtomicOrdering::SeqCst);
+		step
+	}
+
+	#[test]
+	fn step_duration_transition_at_step_boundary() {
+		// slow down from 5s to 15s steps; the transition isn't aligned to the old steps.
+		let durations = [(0, 5), (1003, 15)].iter().cloned().collect();
+
+		// the transition is postponed to the end of the step in progress.
+		assert_eq!(step_at_time(&durations, 999).load(), 199);
+		assert_eq!(step_at_time(&durations, 1000).load(), 200);
+		assert_eq!(step_at_time(&durations, 1004).load(), 200);
+		assert_eq!(step_at_time(&durations, 1005).load(), 201);
+		assert_eq!(step_at_time(&durations, 1019).load(), 201);
+		assert_eq!(step_at_time(&durations, 1020).load(), 202);
+
+		let step = step_at_time(&durations, 1004);
+		assert_eq!(step.step_start(200), Some(1000));
+		assert_eq!(step.step_start(201), Some(1005));
+		assert_eq!(step.step_start(202), Some(1020));
+
+		// the step timer waits for the new duration after the transition.
+		assert_eq!(step.duration_remaining_at(Duration::from_millis(1_004_500)), Duration::from_millis(500));
+		let step = step_at_time(&durations, 1005);
+		assert_eq!(step.duration_remaining_at(Duration::from_secs(1005)), Duration::from_secs(15));
+
+		// steps and their start times agree around the transition.
+		for timestamp in 900..1100 {
+			let current = step.step_at(timestamp);
+			assert!(step.step_start(current).unwrap() <= timestamp);
+			assert!(step.step_start(current + 1).unwrap() > timestamp);
+		}
+		for s in 150..250 {
+			assert_eq!(step.step_at(step.step_start(s).unwrap()), s);
+		}
+	}
+
+	#[test]
+	fn step_duration_transitions_at_step_boundary_and_within_first_step() {
+		// an aligned transition takes effect right away.
+		let durations = [(0, 5), (1000, 15), (1010, 2)].iter().cloned().collect();
+		assert_eq!(step_at_time(&durations, 999).load(), 199);
+		assert_eq!(step_at_time(&durations, 1000).load(), 200);
+
+		// a transition within the first step of the previous one takes effect after that step.
+		let step = step_at_time(&durations, 1000);
+		assert_eq!(step.durations.len(), 3);
+		assert_eq!(step.step_at(1014), 200);
+		assert_eq!(step.step_at(1015), 201);
+		assert_eq!(step.step_at(1017), 202);
+
+		// while a transition taking effect at the same step boundary as the previous one replaces it.
+		let durations = [(0, 5), (998, 15), (1000, 2)].iter().cloned().collect();
+		let step = step_at_time(&durations, 1000);
+		assert_eq!(step.durations.len(), 2);
+		assert_eq!(step.step_at(999), 199);
+		assert_eq!(step.step_at(1000), 200);
+		assert_eq!(step.step_at(1002), 201);
+	}
+
+	#[test]
+	fn verifies_block_steps_across_step_duration_transition() {
+		let durations: BTreeMap<_, _> = [(0, 5), (1003, 15)].iter().cloned().collect();
+
+		// a block for the first step after the transition is only accepted once it started.
+		let before = step_at_time(&durations, 1004);
+		match before.check_future(201) {
+			Err(Some(oob)) => {
+				assert_eq!(oob.max, Some(1000));
+				assert_eq!(oob.found, 1005);
+			},
+			other => panic!("unexpected result: {:?}", other),
+		}
+		assert!(step_at_time(&durations, 1005).check_future(201).is_ok());
+
+		// blocks too far in the future are rejected using the new duration.
+		let after = step_at_time(&durations, 1005);
+		assert!(after.check_future(205).is_err());
+		assert_eq!(after.check_future(206), Err(None));
+		assert!(step_at_time(&durations, 1005 + 4 * 15).check_future(205).is_ok());
+
+		// the engine honors the schedule when verifying blocks.
+		let engine = aura(|p| {
+			p.step_durations = durations.clone();
+			p.start_step = Some(200);
+		});
+		let mut header = Header::default();
+		header.set_seal(vec![encode(&201usize), encode(&(&H520::default() as &[u8]))]);
+		assert!(engine.verify_block_basic(&header).is_err());
+		engine.step();
+		assert!(engine.verify_block_basic(&header).is_ok());
+	}
+
 	fn setup_empty_steps() -> (Spec, Arc<AccountProvider>, Vec<Address>) {
 		let spec = Spec::new_test_round_empty_steps();
 		let tap = Arc::new(AccountProvider::transient_provider());
@@ -2236,7 +2523,7 @@ mod tests {
 	#[test]
 	fn test_empty_steps() {
 		let engine = aura(|p| {
-			p.step_duration = 4;
+			p.step_durations = [(0, 4)].iter().cloned().collect();
 			p.empty_steps_transition = 0;
 			p.maximum_empty_steps = 0;
 		});
@@ -2270,7 +2557,7 @@ mod tests {
 		let (_spec, tap, accounts) = setup_empty_steps();
 		let engine = aura(|p| {
 			p.validators = Box::new(SimpleList::new(accounts.clone()));
-			p.step_duration = 4;
+			p.step_durations = [(0, 4)].iter().cloned().collect();
 			p.empty_steps_transition = 0;
 			p.maximum_empty_steps = 0;
 		});
@@ -2307,7 +2594,7 @@ mod tests {
 		let (_spec, tap, accounts) = setup_empty_steps();
 		let engine = aura(|p| {
 			p.validators = Box::new(SimpleList::new(accounts.clone()));
-			p.step_duration = 4;
+			p.step_durations = [(0, 4)].iter().cloned().collect();
 			p.empty_steps_transition = 0;
 			p.maximum_empty_steps = 0;
 		});
@@ -2344,4 +2631,77 @@ mod tests {
 		set_empty_steps_seal(&mut header, step, &signature, &empty_steps);
 		assert_eq!(engine.verify_block_family(&header, &parent).unwrap(), ());
 	}
+
+	#[test]
+	fn seal_empty_block_after_minimum_empty_steps() {
+		let (spec, tap, accounts) = setup_empty_steps();
+		let genesis_header = spec.genesis_header();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+
+		// a validator for each account, with the given thresholds.
+		let validators = |minimum_empty_steps_to_seal: &[usize]| -> Vec<Arc<AuthorityRound>> {
+			accounts.iter().zip(&["1", "0"]).zip(minimum_empty_steps_to_seal).map(|((&address, &password), &minimum)| {
+				let engine = aura(|p| {
+					p.validators = Box::new(SimpleList::new(accounts.clone()));
+					p.empty_steps_transition = 1;
+					p.maximum_empty_steps = 3;
+					p.minimum_empty_steps_to_seal = minimum;
+				});
+				engine.set_signer(Box::new((tap.clone(), address, password.into())));
+				engine
+			}).collect()
+		};
+
+		// step the clock of all validators until one of them seals an empty block on top of genesis,
+		// relaying empty step messages between them. returns the block and its author.
+		let seal_empty_block = |validators: &[Arc<AuthorityRound>]| -> (Header, usize) {
+			for _ in 0..10 {
+				for (index, validator) in validators.iter().enumerate() {
+					let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+					let block = OpenBlock::new(&**validator, Default::default(), false, db, &genesis_header, last_hashes.clone(), accounts[index], (3141562.into(), 31415620.into()), vec![], false, None).unwrap();
+					let block = block.close_and_lock().unwrap();
+
+					if let Seal::Regular(seal) = validator.generate_seal(&block, &genesis_header) {
+						return (block.seal(&**validator, seal).unwrap().header.clone(), index);
+					}
+				}
+
+				let empty_steps: Vec<_> = validators.iter().flat_map(|v| v.empty_steps.lock().clone()).collect();
+				for validator in validators {
+					for empty_step in &empty_steps {
+						validator.handle_empty_step_message(empty_step.clone());
+					}
+					validator.step();
+				}
+			}
+
+			panic!("no empty block sealed in 10 steps");
+		};
+
+		let sealed_step = |header: &Header| super::header_step(header, 1).unwrap();
+		let sealed_empty_steps = |header: &Header| super::header_empty_steps(header).unwrap().len();
+
+		// by default empty blocks are sealed once the maximum of empty steps is collected.
+		let (header, _) = seal_empty_block(&validators(&[3, 3]));
+		assert_eq!((sealed_step(&header), sealed_empty_steps(&header)), (4, 3));
+
+		// which also bounds the threshold.
+		let (header, _) = seal_empty_block(&validators(&[5, 5]));
+		assert_eq!((sealed_step(&header), sealed_empty_steps(&header)), (4, 3));
+
+		// while a lower threshold seals them sooner.
+		let (header, _) = seal_empty_block(&validators(&[1, 1]));
+		assert_eq!((sealed_step(&header), sealed_empty_steps(&header)), (2, 1));
+
+		// validators with different thresholds accept each other's blocks.
+		for thresholds in &[[1, 3], [3, 1]] {
+			let validators = validators(thresholds);
+			let (header, author) = seal_empty_block(&validators);
+			assert_eq!(thresholds[author], 1);
+
+			let other = &validators[1 - author];
+			assert_eq!(other.verify_block_family(&header, &genesis_header).unwrap(), ());
+			assert_eq!(other.verify_block_external(&header).unwrap(), ());
+		}
+	}
 }
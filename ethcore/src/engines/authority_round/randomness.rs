@@ -0,0 +1,340 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! On-chain randomness collection with a commit-reveal contract.
+//!
+//! Randomness is collected in rounds. During the commit phase of a round each validator
+//! commits to the hash of a secret number, which it reveals during the following reveal phase.
+//! The contract combines the revealed numbers into a random seed, leaving out validators
+//! who don't reveal.
+//!
+//! Our secret of a round is derived from our signature of the contract address and the round,
+//! so other validators can't predict it and it doesn't need to be stored to survive restarts.
+//! Commits and reveals are sent as journaled service transactions, so they're resubmitted
+//! until included, also after restarts.
+
+use bytes::Bytes;
+use call_contract::CallContract;
+use ethereum_types::{H256, U256, Address};
+use hash::keccak;
+use types::ids::BlockId;
+
+use engines::EngineError;
+use engines::signer::EngineSigner;
+use error::Error;
+
+use_contract!(authority_round_random, "res/contracts/authority_round_random.json");
+
+/// Calls to the randomness contract.
+pub trait RandomnessContract {
+	/// The current collection round.
+	fn current_round(&self) -> Result<U256, String>;
+
+	/// Whether the current round is in its commit phase, rather than in its reveal phase.
+	fn is_commit_phase(&self) -> Result<bool, String>;
+
+	/// Whether the validator committed in the given round.
+	fn is_committed(&self, round: U256, validator: Address) -> Result<bool, String>;
+
+	/// Whether the validator revealed its number in the given round.
+	fn sent_reveal(&self, round: U256, validator: Address) -> Result<bool, String>;
+
+	/// The commitment of the validator in the given round.
+	fn commitment(&self, round: U256, validator: Address) -> Result<H256, String>;
+}
+
+/// The randomness contract at an address, called in the state of a block.
+pub struct BoundContract<'a, C: 'a + ?Sized> {
+	client: &'a C,
+	block_id: BlockId,
+	address: Address,
+}
+
+impl<'a, C: CallContract + ?Sized> BoundContract<'a, C> {
+	/// Bind the contract at `address` to the state of block `block_id`.
+	pub fn new(client: &'a C, block_id: BlockId, address: Address) -> Self {
+		BoundContract { client, block_id, address }
+	}
+
+	fn call(&self, data: Bytes) -> Result<Bytes, String> {
+		self.client.call_contract(self.block_id, self.address, data)
+	}
+}
+
+impl<'a, C: CallContract + ?Sized> RandomnessContract for BoundContract<'a, C> {
+	fn current_round(&self) -> Result<U256, String> {
+		let (data, decoder) = authority_round_random::functions::current_collect_round::call();
+		self.call(data).and_then(|value| decoder.decode(&value).map_err(|e| e.to_string()))
+	}
+
+	fn is_commit_phase(&self) -> Result<bool, String> {
+		let (data, decoder) = authority_round_random::functions::is_commit_phase::call();
+		self.call(data).and_then(|value| decoder.decode(&value).map_err(|e| e.to_string()))
+	}
+
+	fn is_committed(&self, round: U256, validator: Address) -> Result<bool, String> {
+		let (data, decoder) = authority_round_random::functions::is_committed::call(round, validator);
+		self.call(data).and_then(|value| decoder.decode(&value).map_err(|e| e.to_string()))
+	}
+
+	fn sent_reveal(&self, round: U256, validator: Address) -> Result<bool, String> {
+		let (data, decoder) = authority_round_random::functions::sent_reveal::call(round, validator);
+		self.call(data).and_then(|value| decoder.decode(&value).map_err(|e| e.to_string()))
+	}
+
+	fn commitment(&self, round: U256, validator: Address) -> Result<H256, String> {
+		let (data, decoder) = authority_round_random::functions::get_commit::call(round, validator);
+		self.call(data).and_then(|value| decoder.decode(&value).map_err(|e| e.to_string()))
+	}
+}
+
+/// A transaction advancing our phase of randomness collection.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RandomnessCall {
+	/// Commit to the hash of our secret number.
+	Commit {
+		/// The collection round.
+		round: U256,
+		/// Hash of the secret number.
+		hash: H256,
+	},
+	/// Reveal our secret number.
+	Reveal {
+		/// The collection round.
+		round: U256,
+		/// The secret number.
+		number: U256,
+	},
+}
+
+impl RandomnessCall {
+	/// Purpose of the service transaction. Transactions are deduplicated by purpose.
+	pub fn purpose(&self) -> String {
+		match *self {
+			RandomnessCall::Commit { round, .. } => format!("randomness_commit:{}", round),
+			RandomnessCall::Reveal { round, .. } => format!("randomness_reveal:{}", round),
+		}
+	}
+
+	/// Encoded call of the contract.
+	pub fn data(&self) -> Bytes {
+		match *self {
+			RandomnessCall::Commit { hash, .. } => authority_round_random::functions::commit_hash::encode_input(hash),
+			RandomnessCall::Reveal { number, .. } => authority_round_random::functions::reveal_number::encode_input(number),
+		}
+	}
+}
+
+/// Our phase of randomness collection in the current round, as recorded by the contract.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RandomnessPhase {
+	/// Nothing to do in the current round.
+	Waiting,
+	/// Commit phase of a round in which we haven't committed yet.
+	BeforeCommit(U256),
+	/// Reveal phase of a round in which we committed, but haven't revealed yet.
+	Reveal(U256),
+}
+
+impl RandomnessPhase {
+	/// Determine our phase from the state of the contract.
+	pub fn load<C: RandomnessContract>(contract: &C, our_address: Address) -> Result<Self, Error> {
+		let round = contract.current_round().map_err(failed_call)?;
+		let committed = contract.is_committed(round, our_address).map_err(failed_call)?;
+
+		if contract.is_commit_phase().map_err(failed_call)? {
+			return Ok(match committed {
+				true => RandomnessPhase::Waiting,
+				false => RandomnessPhase::BeforeCommit(round),
+			});
+		}
+
+		if !committed {
+			// we missed the commit phase, e.g. because the node was offline.
+			trace!(target: "engine", "Not taking part in randomness collection round {}", round);
+			return Ok(RandomnessPhase::Waiting);
+		}
+
+		Ok(match contract.sent_reveal(round, our_address).map_err(failed_call)? {
+			true => RandomnessPhase::Waiting,
+			false => RandomnessPhase::Reveal(round),
+		})
+	}
+
+	/// Build the transaction advancing our phase, if any.
+	pub fn advance<C: RandomnessContract>(
+		self,
+		contract: &C,
+		contract_address: Address,
+		signer: &EngineSigner,
+	) -> Result<Option<RandomnessCall>, Error> {
+		match self {
+			RandomnessPhase::Waiting => Ok(None),
+			RandomnessPhase::BeforeCommit(round) => {
+				let secret = round_secret(signer, contract_address, round)?;
+				Ok(Some(RandomnessCall::Commit { round, hash: keccak(secret) }))
+			},
+			RandomnessPhase::Reveal(round) => {
+				let secret = round_secret(signer, contract_address, round)?;
+				let commitment = contract.commitment(round, signer.address()).map_err(failed_call)?;
+				if commitment != keccak(secret) {
+					// the contract would reject the reveal anyway.
+					warn!(target: "engine", "Not revealing randomness of round {}: the commitment doesn't match our secret", round);
+					return Ok(None);
+				}
+
+				Ok(Some(RandomnessCall::Reveal { round, number: U256::from(&secret[..]) }))
+			},
+		}
+	}
+}
+
+// our secret number of a round.
+fn round_secret(signer: &EngineSigner, contract_address: Address, round: U256) -> Result<H256, Error> {
+	let mut message = contract_address.to_vec();
+	message.extend_from_slice(&H256::from(round));
+	let signature = signer.sign(keccak(message))?;
+	Ok(keccak(&signature[..]))
+}
+
+fn failed_call(e: String) -> Error {
+	EngineError::FailedSystemCall(format!("Randomness contract call failed: {}", e)).into()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cell::RefCell;
+	use std::collections::{HashMap, HashSet};
+
+	use ethereum_types::{H256, U256, Address};
+	use ethkey::{KeyPair, Secret};
+	use hash::keccak;
+
+	use engines::signer;
+	use super::{RandomnessContract, RandomnessCall, RandomnessPhase};
+
+	// the randomness contract, with phases switched by the test.
+	#[derive(Default)]
+	struct MockContract {
+		round: U256,
+		commit_phase: bool,
+		commits: RefCell<HashMap<(U256, Address), H256>>,
+		reveals: RefCell<HashSet<(U256, Address)>>,
+	}
+
+	impl MockContract {
+		fn start_round(&mut self, round: u64) {
+			self.round = round.into();
+			self.commit_phase = true;
+		}
+
+		// include a transaction of the validator.
+		fn include(&self, validator: Address, call: RandomnessCall) {
+			match call {
+				RandomnessCall::Commit { round, hash } => {
+					assert!(self.commit_phase && round == self.round);
+					assert!(self.commits.borrow_mut().insert((round, validator), hash).is_none());
+				},
+				RandomnessCall::Reveal { round, number } => {
+					assert!(!self.commit_phase && round == self.round);
+					assert_eq!(self.commits.borrow()[&(round, validator)], keccak(H256::from(number)));
+					assert!(self.reveals.borrow_mut().insert((round, validator)));
+				},
+			}
+		}
+	}
+
+	impl RandomnessContract for MockContract {
+		fn current_round(&self) -> Result<U256, String> { Ok(self.round) }
+
+		fn is_commit_phase(&self) -> Result<bool, String> { Ok(self.commit_phase) }
+
+		fn is_committed(&self, round: U256, validator: Address) -> Result<bool, String> {
+			Ok(self.commits.borrow().contains_key(&(round, validator)))
+		}
+
+		fn sent_reveal(&self, round: U256, validator: Address) -> Result<bool, String> {
+			Ok(self.reveals.borrow().contains(&(round, validator)))
+		}
+
+		fn commitment(&self, round: U256, validator: Address) -> Result<H256, String> {
+			Ok(self.commits.borrow().get(&(round, validator)).cloned().unwrap_or_default())
+		}
+	}
+
+	#[test]
+	fn walks_through_commit_and_reveal_rounds() {
+		let contract_address = Address::from(0x42);
+		let keypair = KeyPair::from_secret(Secret::from(keccak("1"))).unwrap();
+		let our_address = keypair.address();
+		let signer = signer::from_keypair(keypair);
+		let other = Address::from(0x1234);
+
+		let mut contract = MockContract::default();
+		let next_call = |contract: &MockContract| {
+			RandomnessPhase::load(contract, our_address).unwrap()
+				.advance(contract, contract_address, &*signer).unwrap()
+		};
+
+		contract.start_round(1);
+		let commit = next_call(&contract).unwrap();
+		assert_eq!(commit.purpose(), "randomness_commit:1");
+
+		// the same commitment is built until it's included, e.g. after a restart.
+		assert_eq!(next_call(&contract), Some(commit.clone()));
+		contract.include(our_address, commit);
+		contract.include(other, RandomnessCall::Commit { round: 1.into(), hash: keccak("other") });
+		assert_eq!(next_call(&contract), None);
+
+		contract.commit_phase = false;
+		let reveal = next_call(&contract).unwrap();
+		assert_eq!(reveal.purpose(), "randomness_reveal:1");
+		contract.include(our_address, reveal);
+		assert_eq!(next_call(&contract), None);
+
+		// the other validator doesn't reveal, which doesn't affect the next round.
+		contract.start_round(2);
+		let commit = next_call(&contract).unwrap();
+		match commit {
+			RandomnessCall::Commit { hash, .. } => assert!(hash != contract.commitment(1.into(), our_address).unwrap()),
+			_ => panic!("expected a commitment"),
+		}
+		contract.include(our_address, commit);
+		contract.commit_phase = false;
+		contract.include(our_address, next_call(&contract).unwrap());
+		assert_eq!(contract.reveals.borrow().len(), 2);
+	}
+
+	#[test]
+	fn waits_after_missing_the_commit_phase() {
+		let keypair = KeyPair::from_secret(Secret::from(keccak("1"))).unwrap();
+		let our_address = keypair.address();
+		let signer = signer::from_keypair(keypair);
+
+		let mut contract = MockContract::default();
+		contract.start_round(1);
+		contract.commit_phase = false;
+		assert_eq!(RandomnessPhase::load(&contract, our_address).unwrap(), RandomnessPhase::Waiting);
+
+		// a commitment made with another key isn't revealed.
+		contract.commit_phase = true;
+		contract.include(our_address, RandomnessCall::Commit { round: 1.into(), hash: keccak("other") });
+		contract.commit_phase = false;
+		let phase = RandomnessPhase::load(&contract, our_address).unwrap();
+		assert_eq!(phase, RandomnessPhase::Reveal(1.into()));
+		assert_eq!(phase.advance(&contract, Address::from(0x42), &*signer).unwrap(), None);
+	}
+}
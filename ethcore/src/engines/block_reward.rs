@@ -20,7 +20,9 @@
 use ethabi;
 use ethabi::ParamType;
 use ethereum_types::{H160, Address, U256};
+use ethjson;
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use hash::keccak;
 use error::Error;
@@ -151,6 +153,46 @@ impl BlockRewardContract {
 	}
 }
 
+/// Additional beneficiary of block rewards, receiving a share of the base reward of every block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RewardBeneficiary {
+	/// Beneficiary address.
+	pub address: Address,
+	/// Share of the block reward in percent.
+	pub share: u64,
+}
+
+impl From<ethjson::spec::RewardBeneficiary> for RewardBeneficiary {
+	fn from(b: ethjson::spec::RewardBeneficiary) -> Self {
+		RewardBeneficiary {
+			address: b.address.into(),
+			share: b.share.into(),
+		}
+	}
+}
+
+/// Convert the block reward of a spec into a map of transition blocks to the rewards from that block on.
+pub fn reward_schedule(reward: Option<ethjson::spec::BlockReward>) -> BTreeMap<BlockNumber, U256> {
+	match reward {
+		Some(ethjson::spec::BlockReward::Multi(multi)) => multi.into_iter()
+			.map(|(block, reward)| (block.into(), reward.into()))
+			.collect(),
+		Some(ethjson::spec::BlockReward::Single(reward)) => vec![(0, reward.into())].into_iter().collect(),
+		None => vec![(0, U256::zero())].into_iter().collect(),
+	}
+}
+
+/// Split the base reward of a block between the author and the additional beneficiaries.
+/// Returns the reward of the author, and the rewards of the beneficiaries.
+pub fn split_block_reward(reward: U256, beneficiaries: &[RewardBeneficiary]) -> (U256, Vec<(Address, U256)>) {
+	let shares: Vec<_> = beneficiaries.iter()
+		.map(|b| (b.address, reward * U256::from(b.share) / U256::from(100)))
+		.collect();
+	let paid = shares.iter().fold(U256::zero(), |paid, &(_, share)| paid + share);
+
+	(reward - paid, shares)
+}
+
 /// Applies the given block rewards, i.e. adds the given balance to each beneficiary' address.
 /// If tracing is enabled the operations are recorded.
 pub fn apply_block_rewards<M: Machine>(
@@ -183,7 +225,29 @@ mod test {
 	use test_helpers::generate_dummy_client_with_spec;
 
 	use engines::SystemOrCodeCallKind;
-	use super::{BlockRewardContract, RewardKind};
+	use super::{BlockRewardContract, RewardKind, RewardBeneficiary, split_block_reward};
+
+	#[test]
+	fn splits_block_reward_between_author_and_beneficiaries() {
+		let foundation = "0000000000000000000000000000000000000f0d".into();
+		let fund = "0000000000000000000000000000000000000f0e".into();
+		let beneficiaries = vec![
+			RewardBeneficiary { address: foundation, share: 10 },
+			RewardBeneficiary { address: fund, share: 5 },
+		];
+
+		assert_eq!(split_block_reward(1000.into(), &[]), (1000.into(), vec![]));
+		assert_eq!(
+			split_block_reward(1000.into(), &beneficiaries),
+			(850.into(), vec![(foundation, 100.into()), (fund, 50.into())])
+		);
+
+		// shares are rounded down, the remainder is paid to the author.
+		assert_eq!(
+			split_block_reward(999.into(), &beneficiaries),
+			(851.into(), vec![(foundation, 99.into()), (fund, 49.into())])
+		);
+	}
 
 	#[test]
 	fn block_reward_contract() {
@@ -745,11 +745,9 @@ impl Engine<EthereumMachine> for Clique {
 		}
 	}
 
-	fn stop(&mut self) {
-		if let Some(mut s) = self.step_service.as_mut() {
-			Arc::get_mut(&mut s).map(|x| x.stop());
-		} else {
-			warn!(target: "engine", "Stopping `CliqueStepService` failed requires mutable access");
+	fn stop(&self) {
+		if let Some(ref s) = self.step_service {
+			s.stop();
 		}
 	}
 
@@ -759,8 +757,8 @@ impl Engine<EthereumMachine> for Clique {
 		cmp::max(now.as_secs() as u64, parent_timestamp.saturating_add(self.period))
 	}
 
-	fn is_timestamp_valid(&self, header_timestamp: u64, parent_timestamp: u64) -> bool {
-		header_timestamp >= parent_timestamp.saturating_add(self.period)
+	fn is_timestamp_valid(&self, header: &Header, parent: &Header) -> bool {
+		header.timestamp() >= parent.timestamp().saturating_add(self.period)
 	}
 
 	fn fork_choice(&self, new: &ExtendedHeader, current: &ExtendedHeader) -> super::ForkChoice {
@@ -23,11 +23,12 @@ use std::sync::Arc;
 
 use engines::Engine;
 use machine::Machine;
+use parking_lot::Mutex;
 
 /// Service that is managing the engine
 pub struct StepService {
 	shutdown: Arc<AtomicBool>,
-	thread: Option<thread::JoinHandle<()>>,
+	thread: Mutex<Option<thread::JoinHandle<()>>>,
 }
 
 impl StepService {
@@ -62,15 +63,15 @@ impl StepService {
 
 		Arc::new(StepService {
 			shutdown: s,
-			thread: Some(thread),
+			thread: Mutex::new(Some(thread)),
 		})
 	}
 
 	/// Stop the `StepService`
-	pub fn stop(&mut self) {
+	pub fn stop(&self) {
 		trace!(target: "miner", "CliqueStepService: shutting down.");
 		self.shutdown.store(true, Ordering::Release);
-		if let Some(t) = self.thread.take() {
+		if let Some(t) = self.thread.lock().take() {
 			t.join().expect("CliqueStepService thread panicked!");
 		}
 	}
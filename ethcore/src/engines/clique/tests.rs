@@ -802,3 +802,35 @@ fn bonus_consensus_should_keep_track_of_votes_before_latest_per_signer() {
 	let tags = tester.into_tags(tester.clique_signers(&vote.hash()));
 	assert_eq!(&tags, &['A', 'B', 'C', 'D', 'E']);
 }
+
+#[test]
+fn signers_take_turns_to_seal_inturn() {
+	let tags = ['A', 'B', 'C'];
+	let tester = CliqueTester::with(10, 1, tags.to_vec());
+
+	let mut last = tester.genesis.clone();
+	for _ in 0..6 {
+		let number = last.number() + 1;
+		let inturn: Vec<_> = tags.iter()
+			.filter(|tag| tester.get_difficulty(number, &last, &tester.signers[*tag].address()) == DIFF_INTURN)
+			.collect();
+		assert_eq!(inturn.len(), 1);
+
+		last = tester.new_block_and_import(CliqueBlockType::Empty, &last, None, *inturn[0]).unwrap();
+		assert_eq!(*last.difficulty(), DIFF_INTURN);
+	}
+}
+
+#[test]
+fn noturn_signers_wait_for_wiggle_delay() {
+	let tester = CliqueTester::with(10, 5, vec!['A', 'B', 'C', 'D']);
+	let block = tester.new_block_and_import(CliqueBlockType::Empty, &tester.genesis, None, 'A').unwrap();
+
+	let state = tester.get_state_at_block(&block.hash());
+	let inturn = state.next_timestamp_inturn.unwrap();
+	let noturn = state.next_timestamp_noturn.unwrap();
+
+	assert_eq!(inturn, UNIX_EPOCH + Duration::from_secs(block.timestamp() + 5));
+	assert!(noturn >= inturn);
+	assert!(noturn < inturn + Duration::from_millis((4 / 2 + 1) * SIGNING_DELAY_NOTURN_MS));
+}
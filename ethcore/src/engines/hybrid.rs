@@ -0,0 +1,452 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! An engine handing verification and sealing over to another engine at a given block,
+//! e.g. to bootstrap a proof-of-authority chain with instant sealing.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Weak};
+
+use ethereum_types::{H256, U256, Address};
+use ethkey::{self, Signature};
+use parking_lot::RwLock;
+use rlp;
+use block::ExecutedBlock;
+use client::{EngineClient, ChainInfo};
+use engines::{Engine, EthEngine, Seal, SealVerificationCost, EngineError, ConstructedVerifier, EpochChange, Headers, PendingTransitionStore, ForkChoice, Proof, StateDependentProof};
+use engines::signer::EngineSigner;
+use error::Error;
+use machine::{AuxiliaryData, Call, EthereumMachine};
+use types::BlockNumber;
+use types::ancestry_action::AncestryAction;
+use types::header::{Header, ExtendedHeader};
+use types::transaction::SignedTransaction;
+
+/// Signer shared by the engines before and after the transition.
+struct SharedSigner(Arc<EngineSigner>);
+
+impl EngineSigner for SharedSigner {
+	fn sign(&self, hash: H256) -> Result<Signature, ethkey::Error> {
+		self.0.sign(hash)
+	}
+
+	fn address(&self) -> Address {
+		self.0.address()
+	}
+}
+
+/// Epoch data of the engine after the transition, generated on the state of the transition block.
+struct TransitionProof {
+	engine: Arc<EthEngine>,
+	header: Header,
+}
+
+impl StateDependentProof<EthereumMachine> for TransitionProof {
+	fn generate_proof(&self, state: &Call) -> Result<Vec<u8>, String> {
+		self.engine.genesis_epoch_data(&self.header, state)
+	}
+
+	// the epoch data is checked by the epoch verifier of the engine after the transition.
+	fn check_proof(&self, _machine: &EthereumMachine, _proof: &[u8]) -> Result<(), String> {
+		Ok(())
+	}
+}
+
+/// Engine delegating to one engine before the transition block and to another one from it on.
+pub struct Hybrid {
+	transition: BlockNumber,
+	first: Arc<EthEngine>,
+	second: Arc<EthEngine>,
+	machine: EthereumMachine,
+	best_block: RwLock<BlockNumber>,
+}
+
+impl Hybrid {
+	/// Create a new instance switching from `first` to `second` at block `transition`.
+	pub fn new(transition: BlockNumber, first: Arc<EthEngine>, second: Arc<EthEngine>, machine: EthereumMachine) -> Self {
+		Hybrid {
+			transition,
+			first,
+			second,
+			machine,
+			best_block: RwLock::new(0),
+		}
+	}
+
+	fn engine_at(&self, number: BlockNumber) -> &EthEngine {
+		match number < self.transition {
+			true => &*self.first,
+			false => &*self.second,
+		}
+	}
+
+	// the engine sealing the next block.
+	fn active(&self) -> &EthEngine {
+		self.engine_at(*self.best_block.read() + 1)
+	}
+
+	// the best block may also become lower on reorganization.
+	fn note_best_block(&self, number: BlockNumber) {
+		*self.best_block.write() = number;
+	}
+
+	fn is_transition(&self, header: &Header) -> bool {
+		self.transition != 0 && header.number() == self.transition
+	}
+
+	// the parent of the first block after the transition is sealed by the previous engine,
+	// so it's presented to the next one with a seal of the expected arity, zeroed like genesis seals.
+	fn parent_for<'a>(&self, number: BlockNumber, parent: &'a Header) -> Cow<'a, Header> {
+		if number != self.transition {
+			return Cow::Borrowed(parent);
+		}
+
+		let mut parent = parent.clone();
+		let fields = self.second.seal_fields(&parent);
+		parent.set_seal(vec![rlp::NULL_RLP.to_vec(); fields]);
+		Cow::Owned(parent)
+	}
+}
+
+impl Engine<EthereumMachine> for Hybrid {
+	fn name(&self) -> &str {
+		self.active().name()
+	}
+
+	fn machine(&self) -> &EthereumMachine { &self.machine }
+
+	fn seal_fields(&self, header: &Header) -> usize {
+		self.engine_at(header.number()).seal_fields(header)
+	}
+
+	fn extra_info(&self, header: &Header) -> BTreeMap<String, String> {
+		self.engine_at(header.number()).extra_info(header)
+	}
+
+	fn maximum_uncle_count(&self, block: BlockNumber) -> usize {
+		self.engine_at(block).maximum_uncle_count(block)
+	}
+
+	fn maximum_uncle_age(&self, block: BlockNumber) -> usize {
+		self.engine_at(block).maximum_uncle_age(block)
+	}
+
+	fn maximum_gas_limit(&self) -> Option<U256> {
+		self.active().maximum_gas_limit()
+	}
+
+	fn on_new_block(
+		&self,
+		block: &mut ExecutedBlock,
+		epoch_begin: bool,
+		ancestry: &mut Iterator<Item = ExtendedHeader>,
+	) -> Result<(), Error> {
+		self.engine_at(block.header.number()).on_new_block(block, epoch_begin, ancestry)
+	}
+
+	fn on_close_block(&self, block: &mut ExecutedBlock) -> Result<(), Error> {
+		self.engine_at(block.header.number()).on_close_block(block)
+	}
+
+	fn on_prepare_block(&self, block: &ExecutedBlock) -> Result<(), Error> {
+		self.engine_at(block.header.number()).on_prepare_block(block)
+	}
+
+	fn on_seal_block(&self, block: &mut ExecutedBlock) -> Result<(), Error> {
+		self.engine_at(block.header.number()).on_seal_block(block)
+	}
+
+	fn block_author(&self, first_transaction: &SignedTransaction) -> Option<Address> {
+		self.active().block_author(first_transaction)
+	}
+
+	fn seals_internally(&self) -> Option<bool> {
+		self.active().seals_internally()
+	}
+
+	fn generate_seal(&self, block: &ExecutedBlock, parent: &Header) -> Seal {
+		let number = block.header.number();
+		self.engine_at(number).generate_seal(block, &self.parent_for(number, parent))
+	}
+
+	fn verify_local_seal(&self, header: &Header) -> Result<(), Error> {
+		self.engine_at(header.number()).verify_local_seal(header)
+	}
+
+	fn verify_block_basic(&self, header: &Header) -> Result<(), Error> {
+		self.engine_at(header.number()).verify_block_basic(header)
+	}
+
+	fn verify_block_unordered(&self, header: &Header) -> Result<(), Error> {
+		self.engine_at(header.number()).verify_block_unordered(header)
+	}
+
+	fn seal_verification_cost(&self, header: &Header) -> SealVerificationCost {
+		self.engine_at(header.number()).seal_verification_cost(header)
+	}
+
+	fn verify_block_family(&self, header: &Header, parent: &Header) -> Result<(), Error> {
+		let number = header.number();
+		self.engine_at(number).verify_block_family(header, &self.parent_for(number, parent))
+	}
+
+	fn verify_block_external(&self, header: &Header) -> Result<(), Error> {
+		self.engine_at(header.number()).verify_block_external(header)
+	}
+
+	fn genesis_epoch_data(&self, header: &Header, state: &Call) -> Result<Vec<u8>, String> {
+		self.engine_at(header.number()).genesis_epoch_data(header, state)
+	}
+
+	// the engine after the transition starts its first epoch at the transition block.
+	fn signals_epoch_end(&self, header: &Header, aux: AuxiliaryData) -> EpochChange<EthereumMachine> {
+		if self.is_transition(header) {
+			return EpochChange::Yes(Proof::WithState(Arc::new(TransitionProof {
+				engine: self.second.clone(),
+				header: header.clone(),
+			})));
+		}
+
+		self.engine_at(header.number()).signals_epoch_end(header, aux)
+	}
+
+	fn is_epoch_end(
+		&self,
+		chain_head: &Header,
+		finalized: &[H256],
+		chain: &Headers<Header>,
+		transition_store: &PendingTransitionStore,
+	) -> Option<Vec<u8>> {
+		// the transition doesn't require finality.
+		if self.is_transition(chain_head) {
+			return transition_store(chain_head.hash()).map(|pending| pending.proof);
+		}
+
+		self.engine_at(chain_head.number()).is_epoch_end(chain_head, finalized, chain, transition_store)
+	}
+
+	fn is_epoch_end_light(
+		&self,
+		chain_head: &Header,
+		chain: &Headers<Header>,
+		transition_store: &PendingTransitionStore,
+	) -> Option<Vec<u8>> {
+		if self.is_transition(chain_head) {
+			return transition_store(chain_head.hash()).map(|pending| pending.proof);
+		}
+
+		self.engine_at(chain_head.number()).is_epoch_end_light(chain_head, chain, transition_store)
+	}
+
+	// `header` is the first block of the epoch, so epochs starting at the transition
+	// or later are verified by the engine after it.
+	fn epoch_verifier<'a>(&self, header: &Header, proof: &'a [u8]) -> ConstructedVerifier<'a, EthereumMachine> {
+		self.engine_at(header.number()).epoch_verifier(header, proof)
+	}
+
+	fn populate_from_parent(&self, header: &mut Header, parent: &Header) {
+		let number = header.number();
+		self.engine_at(number).populate_from_parent(header, &self.parent_for(number, parent))
+	}
+
+	fn handle_message(&self, message: &[u8]) -> Result<(), EngineError> {
+		self.active().handle_message(message)
+	}
+
+	fn set_signer(&self, signer: Box<EngineSigner>) {
+		let signer: Arc<EngineSigner> = Arc::from(signer);
+		self.first.set_signer(Box::new(SharedSigner(signer.clone())));
+		self.second.set_signer(Box::new(SharedSigner(signer)));
+	}
+
+	fn sign(&self, hash: H256) -> Result<Signature, Error> {
+		self.active().sign(hash)
+	}
+
+	fn register_client(&self, client: Weak<EngineClient>) {
+		if let Some(client) = client.upgrade() {
+			self.note_best_block(client.chain_info().best_block_number);
+		}
+
+		self.first.register_client(client.clone());
+		self.second.register_client(client);
+	}
+
+	fn step(&self) {
+		self.active().step()
+	}
+
+	fn stop(&self) {
+		self.first.stop();
+		self.second.stop();
+	}
+
+	// snapshots of a single engine don't cover the switch.
+	fn snapshot_components(&self) -> Option<Box<::snapshot::SnapshotComponents>> {
+		None
+	}
+
+	fn open_block_header_timestamp(&self, parent_timestamp: u64) -> u64 {
+		self.active().open_block_header_timestamp(parent_timestamp)
+	}
+
+	fn is_timestamp_valid(&self, header: &Header, parent: &Header) -> bool {
+		self.engine_at(header.number()).is_timestamp_valid(header, parent)
+	}
+
+	fn tracks_finality(&self, number: BlockNumber) -> bool {
+		self.engine_at(number).tracks_finality(number)
+	}
+
+	fn ancestry_actions(&self, header: &Header, ancestry: &mut Iterator<Item = ExtendedHeader>) -> Vec<AncestryAction> {
+		self.engine_at(header.number()).ancestry_actions(header, ancestry)
+	}
+
+	fn fork_choice(&self, new: &ExtendedHeader, best: &ExtendedHeader) -> ForkChoice {
+		let fork_choice = self.engine_at(new.header.number()).fork_choice(new, best);
+		if fork_choice == ForkChoice::New {
+			self.note_best_block(new.header.number());
+		}
+
+		fork_choice
+	}
+
+	fn executive_author(&self, header: &Header) -> Result<Address, Error> {
+		self.engine_at(header.number()).executive_author(header)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use ethereum_types::H520;
+	use hash::keccak;
+	use rlp;
+	use accounts::AccountProvider;
+	use blockchain::BlockProvider;
+	use client::{BlockInfo, ChainInfo, PrepareOpenBlock, ImportBlock, ImportSealedBlock};
+	use engines::{ForkChoice, Seal};
+	use error::{Error, BlockError};
+	use spec::Spec;
+	use test_helpers::{generate_dummy_client_with_spec, create_test_block};
+	use types::header::{Header, ExtendedHeader};
+	use unexpected::Mismatch;
+	use verification::queue::kind::blocks::Unverified;
+
+	#[test]
+	fn delegates_by_block_number() {
+		let engine = Spec::new_test_hybrid().engine;
+		let mut header = Header::default();
+
+		header.set_number(1);
+		assert_eq!(engine.seal_fields(&header), 0);
+		header.set_number(2);
+		assert_eq!(engine.seal_fields(&header), 1);
+	}
+
+	#[test]
+	fn delegates_timestamp_check_by_block_number() {
+		let engine = Spec::new_test_hybrid().engine;
+		let mut parent = Header::default();
+		parent.set_timestamp(10);
+		let mut header = Header::default();
+		header.set_timestamp(10);
+
+		// instant sealing allows blocks with the timestamp of their parent, while basic authority doesn't.
+		header.set_number(1);
+		assert!(engine.is_timestamp_valid(&header, &parent));
+		header.set_number(2);
+		assert!(!engine.is_timestamp_valid(&header, &parent));
+	}
+
+	#[test]
+	fn follows_best_block_on_reorganization() {
+		let engine = Spec::new_test_hybrid().engine;
+		let extended = |number, difficulty: u64| {
+			let mut header = Header::default();
+			header.set_number(number);
+			header.set_difficulty(difficulty.into());
+			ExtendedHeader { header, is_finalized: false, parent_total_difficulty: 0.into() }
+		};
+
+		// a block after the transition becomes the best one, so the next block is sealed by the validator.
+		assert_eq!(engine.fork_choice(&extended(2, 10), &extended(1, 1)), ForkChoice::New);
+		assert_eq!(engine.seals_internally(), Some(false));
+
+		// until the chain is reorganized back to before the transition.
+		assert_eq!(engine.fork_choice(&extended(1, 5), &extended(2, 10)), ForkChoice::Old);
+		assert_eq!(engine.seals_internally(), Some(false));
+		assert_eq!(engine.fork_choice(&extended(0, 20), &extended(2, 10)), ForkChoice::New);
+		assert_eq!(engine.seals_internally(), Some(true));
+	}
+
+	#[test]
+	fn imports_chain_across_transition() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let validator = tap.insert_account(keccak("").into(), &"".into()).unwrap();
+		let source = generate_dummy_client_with_spec(Spec::new_test_hybrid);
+		source.engine().set_signer(Box::new((tap, validator, "".into())));
+
+		// the first block is sealed instantly, the following ones by the validator.
+		let mut blocks = Vec::new();
+		for number in 1..4 {
+			let parent = source.best_block_header().decode().unwrap();
+			let b = source.prepare_open_block(validator, (3141562.into(), 31415620.into()), vec![]).unwrap();
+			let b = b.close_and_lock().unwrap();
+			let seal = match source.engine().generate_seal(&b, &parent) {
+				Seal::Regular(seal) => seal,
+				_ => {
+					assert_eq!(number, 1);
+					vec![]
+				},
+			};
+			let b = b.seal(source.engine(), seal).unwrap();
+			blocks.push(b.rlp_bytes());
+			source.import_sealed_block(b).unwrap();
+		}
+
+		let client = generate_dummy_client_with_spec(Spec::new_test_hybrid);
+		for block in &blocks {
+			client.import_block(Unverified::from_rlp(block.clone()).unwrap()).unwrap();
+		}
+		client.flush_queue();
+		client.import_verified_blocks();
+		assert_eq!(client.chain_info().best_block_number, 3);
+
+		// the engine after the transition starts its epoch at the transition block.
+		let chain = client.chain();
+		assert!(chain.epoch_transition(2, chain.block_hash(2).unwrap()).is_some());
+		assert!(chain.epoch_transition(3, chain.block_hash(3).unwrap()).is_none());
+
+		// a block sealed instantly after the switch.
+		let b = source.prepare_open_block(validator, (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let header = b.close_and_lock().unwrap().header.clone();
+		match client.import_block(Unverified::from_rlp(create_test_block(&header)).unwrap()) {
+			Err(Error::Block(BlockError::InvalidSealArity(Mismatch { expected: 1, found: 0 }))) => {},
+			other => panic!("unexpected import result: {:?}", other),
+		}
+
+		// and one sealed by the validator before it.
+		let mut header = Unverified::from_rlp(blocks[0].clone()).unwrap().header;
+		header.set_seal(vec![rlp::encode(&H520::default())]);
+		match client.import_block(Unverified::from_rlp(create_test_block(&header)).unwrap()) {
+			Err(Error::Block(BlockError::InvalidSealArity(Mismatch { expected: 0, found: 1 }))) => {},
+			other => panic!("unexpected import result: {:?}", other),
+		}
+		assert_eq!(client.chain_info().best_block_number, 3);
+	}
+}
@@ -14,22 +14,28 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use ethereum_types::Address;
 use engines::{Engine, Seal};
 use machine::Machine;
 use types::header::{Header, ExtendedHeader};
+use types::transaction::SignedTransaction;
 use block::ExecutedBlock;
 
 /// `InstantSeal` params.
 #[derive(Default, Debug, PartialEq)]
 pub struct InstantSealParams {
-	/// Whether to use millisecond timestamp
+	/// Whether to use millisecond timestamp.
+	/// Timestamps are strictly increasing then, even for blocks sealed within the same millisecond.
 	pub millisecond_timestamp: bool,
+	/// Whether blocks are authored by the sender of their first transaction.
+	pub author_from_first_sender: bool,
 }
 
 impl From<::ethjson::spec::InstantSealParams> for InstantSealParams {
 	fn from(p: ::ethjson::spec::InstantSealParams) -> Self {
 		InstantSealParams {
 			millisecond_timestamp: p.millisecond_timestamp,
+			author_from_first_sender: p.author_from_first_sender,
 		}
 	}
 }
@@ -71,19 +77,27 @@ impl<M: Machine> Engine<M> for InstantSeal<M> {
 		Ok(())
 	}
 
+	fn block_author(&self, first_transaction: &SignedTransaction) -> Option<Address> {
+		match self.params.author_from_first_sender {
+			true => Some(first_transaction.sender()),
+			false => None,
+		}
+	}
+
 	fn open_block_header_timestamp(&self, parent_timestamp: u64) -> u64 {
 		use std::{time, cmp};
 
 		let dur = time::SystemTime::now().duration_since(time::UNIX_EPOCH).unwrap_or_default();
-		let mut now = dur.as_secs();
 		if self.params.millisecond_timestamp {
-			now = now * 1000 + dur.subsec_millis() as u64;
+			let now = dur.as_secs() * 1000 + dur.subsec_millis() as u64;
+			cmp::max(now, parent_timestamp.saturating_add(1))
+		} else {
+			cmp::max(dur.as_secs(), parent_timestamp)
 		}
-		cmp::max(now, parent_timestamp)
 	}
 
-	fn is_timestamp_valid(&self, header_timestamp: u64, parent_timestamp: u64) -> bool {
-		header_timestamp >= parent_timestamp
+	fn is_timestamp_valid(&self, header: &Header, parent: &Header) -> bool {
+		header.timestamp() >= parent.timestamp()
 	}
 
 	fn fork_choice(&self, new: &ExtendedHeader, current: &ExtendedHeader) -> super::ForkChoice {
@@ -95,9 +109,11 @@ impl<M: Machine> Engine<M> for InstantSeal<M> {
 mod tests {
 	use std::sync::Arc;
 	use ethereum_types::{H520, Address};
+	use ethkey::{Generator, Random};
 	use test_helpers::get_temp_state_db;
 	use spec::Spec;
 	use types::header::Header;
+	use types::transaction::Transaction;
 	use block::*;
 	use engines::Seal;
 
@@ -126,4 +142,40 @@ mod tests {
 
 		assert!(engine.verify_block_unordered(&header).is_ok());
 	}
+	#[test]
+	fn millisecond_timestamps_are_strictly_increasing() {
+		let spec = Spec::new_instant_with_options();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+
+		// open blocks in quick succession, each on top of the previous one.
+		let mut parent = genesis_header;
+		for _ in 0..10 {
+			let header = {
+				let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+				let b = OpenBlock::new(engine, Default::default(), false, db, &parent, last_hashes.clone(), Address::default(), (3141562.into(), 31415620.into()), vec![], false, None).unwrap();
+				b.header.clone()
+			};
+
+			assert!(header.timestamp() > parent.timestamp());
+			parent = header;
+		}
+	}
+
+	#[test]
+	fn timestamps_without_options_may_repeat() {
+		let parent_timestamp = u64::max_value() / 2;
+
+		assert_eq!(Spec::new_instant().engine.open_block_header_timestamp(parent_timestamp), parent_timestamp);
+		assert_eq!(Spec::new_instant_with_options().engine.open_block_header_timestamp(parent_timestamp), parent_timestamp + 1);
+	}
+
+	#[test]
+	fn authors_blocks_by_first_sender_if_enabled() {
+		let transaction = Transaction::default().sign(Random.generate().unwrap().secret(), None);
+
+		assert_eq!(Spec::new_instant().engine.block_author(&transaction), None);
+		assert_eq!(Spec::new_instant_with_options().engine.block_author(&transaction), Some(transaction.sender()));
+	}
 }
@@ -19,6 +19,7 @@
 mod authority_round;
 mod basic_authority;
 mod clique;
+mod hybrid;
 mod instant_seal;
 mod null_engine;
 mod validator_set;
@@ -32,6 +33,7 @@ pub use self::instant_seal::{InstantSeal, InstantSealParams};
 pub use self::null_engine::NullEngine;
 pub use self::signer::EngineSigner;
 pub use self::clique::Clique;
+pub use self::hybrid::Hybrid;
 
 // TODO [ToDr] Remove re-export (#10130)
 pub use types::engines::ForkChoice;
@@ -149,6 +151,16 @@ impl error::Error for EngineError {
 	}
 }
 
+/// Relative cost of verifying the seal of a block in phase 2.
+/// Used by the verification queue to decide how to share work between its threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SealVerificationCost {
+	/// Cheap to verify, e.g. a single signature check. Such blocks are verified in batches.
+	Cheap,
+	/// Expensive to verify, e.g. a proof of work. Such blocks are verified one by one.
+	Expensive,
+}
+
 /// Seal type.
 #[derive(Debug, PartialEq, Eq)]
 pub enum Seal {
@@ -280,6 +292,9 @@ pub trait Engine<M: Machine>: Sync + Send {
 	/// Maximum number of uncles a block is allowed to declare.
 	fn maximum_uncle_count(&self, _block: BlockNumber) -> usize { 0 }
 
+	/// Maximum number of generations an uncle may be behind the block including it.
+	fn maximum_uncle_age(&self, _block: BlockNumber) -> usize { MAX_UNCLE_AGE }
+
 	/// Optional maximum gas limit.
 	fn maximum_gas_limit(&self) -> Option<U256> { None }
 
@@ -299,9 +314,19 @@ pub trait Engine<M: Machine>: Sync + Send {
 		Ok(())
 	}
 
+	/// Called when our node prepares a block for sealing, before transactions are pushed to it.
+	/// Service transactions submitted here may be included in the block.
+	fn on_prepare_block(&self, _block: &ExecutedBlock) -> Result<(), M::Error> {
+		Ok(())
+	}
+
 	/// Allow mutating the header during seal generation. Currently only used by Clique.
 	fn on_seal_block(&self, _block: &mut ExecutedBlock) -> Result<(), Error> { Ok(()) }
 
+	/// Author of a block sealed internally, given the first transaction to be included.
+	/// `None` means the configured author is used.
+	fn block_author(&self, _first_transaction: &SignedTransaction) -> Option<Address> { None }
+
 	/// None means that it requires external input (e.g. PoW) to seal a block.
 	/// Some(true) means the engine is currently prime for seal generation (i.e. node is the current validator).
 	/// Some(false) means that the node might seal internally but is not qualified now.
@@ -338,6 +363,9 @@ pub trait Engine<M: Machine>: Sync + Send {
 	/// The verification module can optionally avoid checking the seal (`check_seal`), if seal verification is disabled this method won't be called.
 	fn verify_block_unordered(&self, _header: &Header) -> Result<(), M::Error> { Ok(()) }
 
+	/// Relative cost of `verify_block_unordered` for the given header.
+	fn seal_verification_cost(&self, _header: &Header) -> SealVerificationCost { SealVerificationCost::Cheap }
+
 	/// Phase 3 verification. Check block information against parent. Returns either a null `Ok` or a general error detailing the problem with import.
 	fn verify_block_family(&self, _header: &Header, _parent: &Header) -> Result<(), M::Error> { Ok(()) }
 
@@ -426,7 +454,7 @@ pub trait Engine<M: Machine>: Sync + Send {
 	fn step(&self) {}
 
 	/// Stops any services that the may hold the Engine and makes it safe to drop.
-	fn stop(&mut self) {}
+	fn stop(&self) {}
 
 	/// Create a factory for building snapshot chunks and restoring from them.
 	/// Returning `None` indicates that this engine doesn't support snapshot creation.
@@ -447,11 +475,18 @@ pub trait Engine<M: Machine>: Sync + Send {
 		cmp::max(now.as_secs() as u64, parent_timestamp + 1)
 	}
 
-	/// Check whether the parent timestamp is valid.
-	fn is_timestamp_valid(&self, header_timestamp: u64, parent_timestamp: u64) -> bool {
-		header_timestamp > parent_timestamp
+	/// Check whether the timestamp of the header is valid given its parent.
+	fn is_timestamp_valid(&self, header: &Header, parent: &Header) -> bool {
+		header.timestamp() > parent.timestamp()
 	}
 
+	/// Whether the engine marks the block with the given number as finalized through `ancestry_actions`.
+	fn tracks_finality(&self, _number: BlockNumber) -> bool { false }
+
+	/// Whether the signatures of block transactions may be recovered in a batch by the machine,
+	/// instead of verifying each transaction through `verify_transaction_unordered`.
+	fn batch_verifies_transactions(&self) -> bool { false }
+
 	/// Gather all ancestry actions. Called at the last stage when a block is committed. The Engine must guarantee that
 	/// the ancestry exists.
 	fn ancestry_actions(&self, _header: &Header, _ancestry: &mut Iterator<Item = ExtendedHeader>) -> Vec<AncestryAction> {
@@ -534,6 +569,18 @@ pub trait EthEngine: Engine<::machine::EthereumMachine> {
 		self.machine().verify_transaction_unordered(t, header)
 	}
 
+	/// Verify all transactions of a block, regardless of order.
+	///
+	/// Same as `verify_transaction_unordered`, but checks the signatures in parallel
+	/// if the engine `batch_verifies_transactions`.
+	/// Results are returned in the same order as `txs`.
+	fn verify_transactions_unordered(&self, txs: Vec<UnverifiedTransaction>, header: &Header) -> Vec<Result<SignedTransaction, transaction::Error>> {
+		match self.batch_verifies_transactions() {
+			true => self.machine().verify_transactions_unordered(txs, header),
+			false => txs.into_iter().map(|t| self.verify_transaction_unordered(t, header)).collect(),
+		}
+	}
+
 	/// Perform basic/cheap transaction verification.
 	///
 	/// This should include all cheap checks that can be done before
@@ -14,8 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
+
 use engines::Engine;
-use engines::block_reward::{self, RewardKind};
+use engines::block_reward::{self, RewardBeneficiary, RewardKind};
 use ethereum_types::U256;
 use machine::Machine;
 use types::BlockNumber;
@@ -25,14 +27,19 @@ use block::ExecutedBlock;
 /// Params for a null engine.
 #[derive(Clone, Default)]
 pub struct NullEngineParams {
-	/// base reward for a block.
-	pub block_reward: U256,
+	/// base reward for a block, changing at the given blocks.
+	pub block_reward: BTreeMap<BlockNumber, U256>,
+	/// Additional beneficiaries of the block reward, paid from the reward of the author.
+	pub block_reward_beneficiaries: Vec<RewardBeneficiary>,
 }
 
 impl From<::ethjson::spec::NullEngineParams> for NullEngineParams {
 	fn from(p: ::ethjson::spec::NullEngineParams) -> Self {
 		NullEngineParams {
-			block_reward: p.block_reward.map_or_else(Default::default, Into::into),
+			block_reward: block_reward::reward_schedule(p.block_reward),
+			block_reward_beneficiaries: p.block_reward_beneficiaries.unwrap_or_default().into_iter()
+				.map(Into::into)
+				.collect(),
 		}
 	}
 }
@@ -72,16 +79,25 @@ impl<M: Machine> Engine<M> for NullEngine<M> {
 		let author = *block.header.author();
 		let number = block.header.number();
 
-		let reward = self.params.block_reward;
+		let reward = self.params.block_reward.iter()
+			.rev()
+			.find(|&(block, _)| *block <= number)
+			.map_or_else(U256::zero, |(_, reward)| *reward);
 		if reward == U256::zero() { return Ok(()) }
 
 		let n_uncles = block.uncles.len();
 
 		let mut rewards = Vec::new();
 
+		// Pay the shares of additional beneficiaries out of the base reward.
+		let (author_reward, shares) = block_reward::split_block_reward(reward, &self.params.block_reward_beneficiaries);
+
 		// Bestow block reward
-		let result_block_reward = reward + reward.shr(5) * U256::from(n_uncles);
+		let result_block_reward = author_reward + reward.shr(5) * U256::from(n_uncles);
 		rewards.push((author, RewardKind::Author, result_block_reward));
+		for (beneficiary, share) in shares {
+			rewards.push((beneficiary, RewardKind::External, share));
+		}
 
 		// bestow uncle rewards.
 		for u in &block.uncles {
@@ -57,7 +57,7 @@ mod test_signer {
 	impl EngineSigner for (Arc<AccountProvider>, Address, Password) {
 		fn sign(&self, hash: H256) -> Result<Signature, ethkey::Error> {
 			match self.0.sign(self.1, Some(self.2.clone()), hash) {
-				Err(SignError::NotUnlocked) => unreachable!(),
+				Err(SignError::NotUnlocked) | Err(SignError::PolicyViolation(_)) => unreachable!(),
 				Err(SignError::NotFound) => Err(ethkey::Error::InvalidAddress),
 				Err(SignError::Hardware(err)) => {
 					warn!("Error using hardware wallet for engine: {:?}", err);
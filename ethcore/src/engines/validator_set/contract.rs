@@ -18,6 +18,7 @@
 /// It can also report validators for misbehaviour with two levels: `reportMalicious` and `reportBenign`.
 
 use std::sync::Weak;
+use std::time::Instant;
 
 use bytes::Bytes;
 use ethereum_types::{H256, Address};
@@ -26,9 +27,10 @@ use parking_lot::RwLock;
 use types::BlockNumber;
 use types::header::Header;
 
-use client::EngineClient;
+use client::{EngineClient, ChainInfo, is_transient_transaction_error};
 
 use super::{ValidatorSet, SimpleList, SystemCall};
+use super::reporting::{Report, ReportCounters, ReportQueue, SubmitError, REPORT_VALIDITY_ROUNDS};
 use super::safe_contract::ValidatorSafeContract;
 
 use_contract!(validator_report, "res/contracts/validator_report.json");
@@ -37,6 +39,7 @@ use_contract!(validator_report, "res/contracts/validator_report.json");
 pub struct ValidatorContract {
 	contract_address: Address,
 	validators: ValidatorSafeContract,
+	reports: ReportQueue,
 	client: RwLock<Option<Weak<EngineClient>>>, // TODO [keorn]: remove
 }
 
@@ -45,26 +48,49 @@ impl ValidatorContract {
 		ValidatorContract {
 			contract_address,
 			validators: ValidatorSafeContract::new(contract_address),
+			reports: ReportQueue::default(),
 			client: RwLock::new(None),
 		}
 	}
 }
 
 impl ValidatorContract {
-	fn transact(&self, data: Bytes) -> Result<(), String> {
+	fn transact(&self, report: &Report) -> Result<(), SubmitError> {
 		let client = self.client.read().as_ref()
 			.and_then(Weak::upgrade)
-			.ok_or_else(|| "No client!")?;
+			.ok_or_else(|| SubmitError::Fail("No client!".into()))?;
+
+		// the block is expected to be final after a few rounds of the current validator set.
+		let validators = ::std::cmp::max(self.validators.count(&client.chain_info().best_block_hash), 1) as BlockNumber;
+		let valid_until = report.block.saturating_add(REPORT_VALIDITY_ROUNDS * validators);
 
 		match client.as_full_client() {
-			Some(c) => {
-				c.transact_contract(self.contract_address, data)
-					.map_err(|e| format!("Transaction import error: {}", e))?;
-				Ok(())
-			},
-			None => Err("No full client!".into()),
+			Some(c) => c.transact_service_contract(
+				report_purpose(report),
+				self.contract_address,
+				report.data.clone(),
+				Some(valid_until),
+			).map_err(|e| {
+				let msg = format!("Transaction import error: {}", e);
+				// the client keeps transactions which may be imported later, e.g. once our account is funded.
+				match is_transient_transaction_error(&e) {
+					true => SubmitError::Retry(msg),
+					false => SubmitError::Fail(msg),
+				}
+			}),
+			None => Err(SubmitError::Fail("No full client!".into())),
 		}
 	}
+
+	fn report(&self, report: Report) {
+		self.reports.report(report, Instant::now(), |report| self.transact(report));
+	}
+}
+
+// reports are journaled by the client, and resubmitted until they're included.
+fn report_purpose(report: &Report) -> String {
+	let kind = if report.malicious { "report_malicious" } else { "report_benign" };
+	format!("{}:{:x}:{}", kind, report.validator, report.block)
 }
 
 impl ValidatorSet for ValidatorContract {
@@ -109,22 +135,48 @@ impl ValidatorSet for ValidatorContract {
 		self.validators.count_with_caller(bh, caller)
 	}
 
-	fn report_malicious(&self, address: &Address, _set_block: BlockNumber, block: BlockNumber, proof: Bytes) {
-		let data = validator_report::functions::report_malicious::encode_input(*address, block, proof);
-		match self.transact(data) {
-			Ok(_) => warn!(target: "engine", "Reported malicious validator {}", address),
-			Err(s) => warn!(target: "engine", "Validator {} could not be reported {}", address, s),
-		}
+	fn report_malicious(&self, address: &Address, set_block: BlockNumber, block: BlockNumber, proof: Bytes) {
+		self.report(Report {
+			validator: *address,
+			set_block,
+			block,
+			malicious: true,
+			data: validator_report::functions::report_malicious::encode_input(*address, block, proof),
+		});
+	}
+
+	fn report_benign(&self, address: &Address, set_block: BlockNumber, block: BlockNumber) {
+		self.report(Report {
+			validator: *address,
+			set_block,
+			block,
+			malicious: false,
+			data: validator_report::functions::report_benign::encode_input(*address, block),
+		});
 	}
 
-	fn report_benign(&self, address: &Address, _set_block: BlockNumber, block: BlockNumber) {
-		let data = validator_report::functions::report_benign::encode_input(*address, block);
-		match self.transact(data) {
-			Ok(_) => warn!(target: "engine", "Reported benign validator misbehaviour {}", address),
-			Err(s) => warn!(target: "engine", "Validator {} could not be reported {}", address, s),
+	fn on_finalized(&self, number: BlockNumber) {
+		let stale = self.reports.on_finalized(number);
+
+		let client = match self.client.read().as_ref().and_then(Weak::upgrade) {
+			Some(client) => client,
+			None => return,
+		};
+
+		// stale reports don't need to be resubmitted, and those which aren't journaled anymore
+		// were either included or expired.
+		if let Some(client) = client.as_full_client() {
+			for report in &stale {
+				client.cancel_service_transaction(&report_purpose(report));
+			}
+			self.reports.retain_queued(|report| client.is_service_transaction_journaled(&report_purpose(report)));
 		}
 	}
 
+	fn report_counters(&self) -> Option<ReportCounters> {
+		Some(self.reports.counters())
+	}
+
 	fn register_client(&self, client: Weak<EngineClient>) {
 		self.validators.register_client(client.clone());
 		*self.client.write() = Some(client);
@@ -208,6 +260,7 @@ mod tests {
 		client.engine().step();
 		client.engine().step();
 		assert_eq!(client.chain_info().best_block_number, 2);
+		assert_eq!(client.engine().additional_params()["reportsSubmitted"], "2");
 
 		// Check if misbehaving validator was removed.
 		client.transact_contract(Default::default(), Default::default()).unwrap();
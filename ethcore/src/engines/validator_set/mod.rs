@@ -22,6 +22,7 @@ mod simple_list;
 mod safe_contract;
 mod contract;
 mod multi;
+mod reporting;
 
 use std::sync::Weak;
 
@@ -38,6 +39,7 @@ use client::EngineClient;
 #[cfg(test)]
 pub use self::test::TestSet;
 pub use self::simple_list::SimpleList;
+pub use self::reporting::ReportCounters;
 use self::contract::ValidatorContract;
 use self::safe_contract::ValidatorSafeContract;
 use self::multi::Multi;
@@ -140,6 +142,10 @@ pub trait ValidatorSet: Send + Sync + 'static {
 	fn report_malicious(&self, _validator: &Address, _set_block: BlockNumber, _block: BlockNumber, _proof: Bytes) {}
 	/// Notifies about benign misbehaviour.
 	fn report_benign(&self, _validator: &Address, _set_block: BlockNumber, _block: BlockNumber) {}
+	/// Notifies that blocks up to the given one are final.
+	fn on_finalized(&self, _number: BlockNumber) {}
+	/// Counters of misbehaviour reports, if the set submits them.
+	fn report_counters(&self) -> Option<ReportCounters> { None }
 	/// Allows blockchain state access.
 	fn register_client(&self, _client: Weak<EngineClient>) {}
 }
@@ -28,7 +28,7 @@ use types::ids::BlockId;
 
 use client::EngineClient;
 use machine::{AuxiliaryData, Call, EthereumMachine};
-use super::{SystemCall, ValidatorSet};
+use super::{ReportCounters, SystemCall, ValidatorSet};
 
 type BlockNumberLookup = Box<Fn(BlockId) -> Result<BlockNumber, String> + Send + Sync + 'static>;
 
@@ -134,6 +134,18 @@ impl ValidatorSet for Multi {
 		self.correct_set_by_number(set_block).1.report_benign(validator, set_block, block);
 	}
 
+	fn on_finalized(&self, number: BlockNumber) {
+		for set in self.sets.values() {
+			set.on_finalized(number);
+		}
+	}
+
+	fn report_counters(&self) -> Option<ReportCounters> {
+		self.sets.values()
+			.filter_map(|set| set.report_counters())
+			.fold(None, |total, counters| Some(total.unwrap_or_default() + counters))
+	}
+
 	fn register_client(&self, client: Weak<EngineClient>) {
 		for set in self.sets.values() {
 			set.register_client(client.clone());
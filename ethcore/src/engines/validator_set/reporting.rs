@@ -0,0 +1,406 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Deduplication and throttling of validator misbehaviour reports.
+//!
+//! Every node observing a misbehaving validator submits its own report transaction,
+//! so reports are coalesced and capped per validator, era and kind of misbehaviour. Report transactions which
+//! can't be imported yet, e.g. because our account has no balance, are journaled by the
+//! client and resubmitted until they're included, expire or their block is finalized.
+
+use std::collections::HashMap;
+use std::ops::Add;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use ethereum_types::Address;
+use parking_lot::Mutex;
+use types::BlockNumber;
+
+/// Time in which repeated reports about the same validator and block are coalesced.
+pub const COALESCE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Maximum number of benign reports submitted about a single validator per era.
+pub const MAX_BENIGN_REPORTS_PER_ERA: usize = 4;
+
+/// Maximum number of malicious reports submitted about a single validator per era.
+/// Counted apart from benign reports, so a flaky validator can't use up the reports of its attacks.
+pub const MAX_MALICIOUS_REPORTS_PER_ERA: usize = 4;
+
+/// Number of rounds of the validator set after the misbehaviour until which a report transaction
+/// is resubmitted, unless its block is finalized earlier. The block is expected to be final by then.
+pub const REPORT_VALIDITY_ROUNDS: BlockNumber = 4;
+
+/// A report of validator misbehaviour.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+	/// The reported validator.
+	pub validator: Address,
+	/// Block at which the validator set was activated, identifying the era.
+	pub set_block: BlockNumber,
+	/// Block at which the validator misbehaved.
+	pub block: BlockNumber,
+	/// Whether the misbehaviour is malicious or benign.
+	pub malicious: bool,
+	/// Call data of the report transaction.
+	pub data: Bytes,
+}
+
+/// Error submitting a report.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubmitError {
+	/// The report was journaled and is resubmitted later, e.g. once our account is funded.
+	Retry(String),
+	/// The report can't be submitted.
+	Fail(String),
+}
+
+/// Counters of reports handled by the queue.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ReportCounters {
+	/// Number of report transactions submitted.
+	pub submitted: u64,
+	/// Number of reports coalesced with a recent report about the same validator and block.
+	pub coalesced: u64,
+	/// Number of reports dropped since the validator was reported too often in its era.
+	pub throttled: u64,
+	/// Number of reports dropped since their block was finalized.
+	pub stale: u64,
+	/// Number of reports which failed to be submitted.
+	pub failed: u64,
+	/// Number of reports which couldn't be imported yet and are currently resubmitted by the client.
+	pub queued: u64,
+}
+
+impl Add for ReportCounters {
+	type Output = ReportCounters;
+
+	fn add(self, other: ReportCounters) -> ReportCounters {
+		ReportCounters {
+			submitted: self.submitted + other.submitted,
+			coalesced: self.coalesced + other.coalesced,
+			throttled: self.throttled + other.throttled,
+			stale: self.stale + other.stale,
+			failed: self.failed + other.failed,
+			queued: self.queued + other.queued,
+		}
+	}
+}
+
+#[derive(Default)]
+struct State {
+	recent: HashMap<(Address, BlockNumber, bool), Instant>,
+	era: BlockNumber,
+	era_reports: HashMap<(Address, bool), usize>,
+	finalized: BlockNumber,
+	queued: Vec<Report>,
+	counters: ReportCounters,
+}
+
+impl State {
+	// whether the report should be submitted, updating the counters otherwise.
+	fn admit(&mut self, report: &Report, now: Instant) -> bool {
+		if report.block <= self.finalized {
+			self.counters.stale += 1;
+			return false;
+		}
+
+		self.recent.retain(|_, reported| now < *reported + COALESCE_WINDOW);
+		if self.recent.contains_key(&(report.validator, report.block, report.malicious)) {
+			self.counters.coalesced += 1;
+			return false;
+		}
+
+		if report.set_block > self.era {
+			self.era = report.set_block;
+			self.era_reports.clear();
+		}
+
+		let max_reports = match report.malicious {
+			true => MAX_MALICIOUS_REPORTS_PER_ERA,
+			false => MAX_BENIGN_REPORTS_PER_ERA,
+		};
+		let era_reports = self.era_reports.entry((report.validator, report.malicious)).or_insert(0);
+		if *era_reports >= max_reports {
+			self.counters.throttled += 1;
+			return false;
+		}
+
+		*era_reports += 1;
+		self.recent.insert((report.validator, report.block, report.malicious), now);
+		true
+	}
+
+	fn set_queued<F>(&mut self, retain: F) where F: FnMut(&Report) -> bool {
+		self.queued.retain(retain);
+		self.counters.queued = self.queued.len() as u64;
+	}
+}
+
+fn is_same_offense(a: &Report, b: &Report) -> bool {
+	a.validator == b.validator && a.block == b.block && a.malicious == b.malicious
+}
+
+/// Filter of reports to submit, keeping their counters.
+#[derive(Default)]
+pub struct ReportQueue {
+	state: Mutex<State>,
+}
+
+impl ReportQueue {
+	/// Submit a report unless it's stale, a duplicate or the validator was reported too often.
+	///
+	/// The lock isn't held while submitting, as importing the report transaction
+	/// may cause further reports.
+	pub fn report<F>(&self, report: Report, now: Instant, submit: F) where
+		F: Fn(&Report) -> Result<(), SubmitError>,
+	{
+		if !self.state.lock().admit(&report, now) {
+			return;
+		}
+
+		let result = submit(&report);
+
+		let mut state = self.state.lock();
+		match result {
+			Ok(()) => {
+				warn!(target: "engine", "Reported {} validator misbehaviour {} at block {}",
+					if report.malicious { "malicious" } else { "benign" }, report.validator, report.block);
+				state.counters.submitted += 1;
+				state.set_queued(|queued| !is_same_offense(queued, &report));
+			},
+			Err(SubmitError::Retry(e)) => {
+				debug!(target: "engine", "Report of validator {} queued: {}", report.validator, e);
+				state.set_queued(|queued| !is_same_offense(queued, &report));
+				state.queued.push(report);
+				state.counters.queued += 1;
+			},
+			Err(SubmitError::Fail(e)) => {
+				warn!(target: "engine", "Validator {} could not be reported {}", report.validator, e);
+				state.counters.failed += 1;
+			},
+		}
+	}
+
+	/// Drop further reports about blocks which are already final. Returns the queued reports
+	/// which became stale, so that their resubmission can be cancelled.
+	pub fn on_finalized(&self, number: BlockNumber) -> Vec<Report> {
+		let mut state = self.state.lock();
+		state.finalized = ::std::cmp::max(state.finalized, number);
+
+		let (stale, queued): (Vec<_>, Vec<_>) = state.queued.drain(..).partition(|report| report.block <= number);
+		state.queued = queued;
+		state.counters.queued = state.queued.len() as u64;
+		state.counters.stale += stale.len() as u64;
+		stale
+	}
+
+	/// Forget queued reports which aren't resubmitted anymore, since they were included or expired.
+	pub fn retain_queued<F>(&self, is_resubmitted: F) where F: Fn(&Report) -> bool {
+		self.state.lock().set_queued(|report| is_resubmitted(report));
+	}
+
+	/// Get the counters of handled reports.
+	pub fn counters(&self) -> ReportCounters {
+		self.state.lock().counters
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+	use super::*;
+
+	// reporting contract which only accepts transactions while our account is funded.
+	#[derive(Default)]
+	struct MockContract {
+		funded: AtomicBool,
+		transactions: AtomicUsize,
+	}
+
+	impl MockContract {
+		fn funded() -> Self {
+			let contract = MockContract::default();
+			contract.funded.store(true, Ordering::SeqCst);
+			contract
+		}
+
+		fn submit(&self, _report: &Report) -> Result<(), SubmitError> {
+			match self.funded.load(Ordering::SeqCst) {
+				true => {
+					self.transactions.fetch_add(1, Ordering::SeqCst);
+					Ok(())
+				},
+				false => Err(SubmitError::Retry("Insufficient balance".into())),
+			}
+		}
+
+		fn transactions(&self) -> usize {
+			self.transactions.load(Ordering::SeqCst)
+		}
+	}
+
+	fn report(validator: u64, set_block: BlockNumber, block: BlockNumber) -> Report {
+		Report {
+			validator: Address::from(validator),
+			set_block,
+			block,
+			malicious: false,
+			data: Vec::new(),
+		}
+	}
+
+	#[test]
+	fn coalesces_and_throttles_burst_of_offenses() {
+		let contract = MockContract::funded();
+		let queue = ReportQueue::default();
+		let now = Instant::now();
+
+		// a flaky validator misses 10 blocks, each seen 5 times, e.g. while verifying and sealing.
+		for block in 1..11 {
+			for _ in 0..5 {
+				queue.report(report(1, 0, block), now, |r| contract.submit(r));
+			}
+		}
+
+		assert_eq!(contract.transactions(), MAX_BENIGN_REPORTS_PER_ERA);
+		assert_eq!(queue.counters(), ReportCounters {
+			submitted: MAX_BENIGN_REPORTS_PER_ERA as u64,
+			coalesced: 4 * MAX_BENIGN_REPORTS_PER_ERA as u64,
+			throttled: 50 - 5 * MAX_BENIGN_REPORTS_PER_ERA as u64,
+			stale: 0,
+			failed: 0,
+			queued: 0,
+		});
+
+		// other validators are reported independently.
+		queue.report(report(2, 0, 10), now, |r| contract.submit(r));
+		assert_eq!(contract.transactions(), MAX_BENIGN_REPORTS_PER_ERA + 1);
+
+		// a report in a new era is submitted.
+		queue.report(report(1, 20, 21), now, |r| contract.submit(r));
+		assert_eq!(contract.transactions(), MAX_BENIGN_REPORTS_PER_ERA + 2);
+	}
+
+	#[test]
+	fn throttles_malicious_reports_apart_from_benign_ones() {
+		let contract = MockContract::funded();
+		let queue = ReportQueue::default();
+		let now = Instant::now();
+
+		// the validator misses enough blocks to use up its benign reports.
+		for block in 1..11 {
+			queue.report(report(1, 0, block), now, |r| contract.submit(r));
+		}
+		assert_eq!(contract.transactions(), MAX_BENIGN_REPORTS_PER_ERA);
+
+		// and then double-signs.
+		for block in 11..21 {
+			queue.report(Report { malicious: true, ..report(1, 0, block) }, now, |r| contract.submit(r));
+		}
+		assert_eq!(contract.transactions(), MAX_BENIGN_REPORTS_PER_ERA + MAX_MALICIOUS_REPORTS_PER_ERA);
+		assert_eq!(queue.counters().throttled, (10 - MAX_BENIGN_REPORTS_PER_ERA + 10 - MAX_MALICIOUS_REPORTS_PER_ERA) as u64);
+	}
+
+	#[test]
+	fn reports_again_after_coalesce_window() {
+		let contract = MockContract::funded();
+		let queue = ReportQueue::default();
+		let now = Instant::now();
+
+		queue.report(report(1, 0, 1), now, |r| contract.submit(r));
+		queue.report(report(1, 0, 1), now + COALESCE_WINDOW / 2, |r| contract.submit(r));
+		assert_eq!(contract.transactions(), 1);
+
+		queue.report(report(1, 0, 1), now + COALESCE_WINDOW, |r| contract.submit(r));
+		assert_eq!(contract.transactions(), 2);
+	}
+
+	#[test]
+	fn counts_reports_queued_until_funded() {
+		let contract = MockContract::default();
+		let queue = ReportQueue::default();
+		let now = Instant::now();
+
+		// reports which can't be imported yet are left to the client to resubmit.
+		for validator in 1..4 {
+			queue.report(report(validator, 0, 5), now, |r| contract.submit(r));
+		}
+		assert_eq!(contract.transactions(), 0);
+		assert_eq!(queue.counters().queued, 3);
+
+		// and still count towards the coalescing window and the per era cap.
+		contract.funded.store(true, Ordering::SeqCst);
+		queue.report(report(1, 0, 5), now, |r| contract.submit(r));
+		assert_eq!(contract.transactions(), 0);
+		assert_eq!(queue.counters().coalesced, 1);
+
+		// once the client stops resubmitting a report, it isn't queued anymore.
+		queue.retain_queued(|r| r.validator != Address::from(1));
+		assert_eq!(queue.counters().queued, 2);
+
+		// a report submitted after the coalescing window replaces the queued one.
+		queue.report(report(2, 0, 5), now + COALESCE_WINDOW, |r| contract.submit(r));
+		assert_eq!(contract.transactions(), 1);
+		assert_eq!(queue.counters().queued, 1);
+	}
+
+	#[test]
+	fn drops_queued_reports_after_finality() {
+		let contract = MockContract::default();
+		let queue = ReportQueue::default();
+		let now = Instant::now();
+
+		for block in 1..4 {
+			queue.report(report(1, 0, block), now, |r| contract.submit(r));
+		}
+		assert_eq!(queue.counters().queued, 3);
+
+		let stale: Vec<_> = queue.on_finalized(2).into_iter().map(|r| r.block).collect();
+		assert_eq!(stale, vec![1, 2]);
+		assert_eq!(queue.counters().queued, 1);
+		assert_eq!(queue.counters().stale, 2);
+	}
+
+	#[test]
+	fn drops_stale_reports_after_finality() {
+		let contract = MockContract::funded();
+		let queue = ReportQueue::default();
+		let now = Instant::now();
+
+		queue.on_finalized(3);
+		for block in 1..6 {
+			queue.report(report(block, 0, block), now, |r| contract.submit(r));
+		}
+
+		assert_eq!(contract.transactions(), 2);
+		assert_eq!(queue.counters(), ReportCounters {
+			submitted: 2,
+			stale: 3,
+			..Default::default()
+		});
+	}
+
+	#[test]
+	fn drops_failed_reports() {
+		let queue = ReportQueue::default();
+		let now = Instant::now();
+
+		queue.report(report(1, 0, 1), now, |_| Err(SubmitError::Fail("No signer".into())));
+		assert_eq!(queue.counters(), ReportCounters { failed: 1, ..Default::default() });
+	}
+}
@@ -130,6 +130,9 @@ pub enum BlockError {
 	/// No transition to epoch number.
 	#[display(fmt = "Unknown transition to epoch number: {}", _0)]
 	UnknownEpochTransition(u64),
+	/// Block is on a fork which doesn't include the latest finalized block.
+	#[display(fmt = "Block is on a fork reverting finalized block {}", _0)]
+	RevertsFinalized(H256),
 }
 
 /// Newtype for Display impl to show seconds
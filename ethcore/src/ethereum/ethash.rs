@@ -28,8 +28,8 @@ use types::BlockNumber;
 use unexpected::{OutOfBounds, Mismatch};
 
 use block::ExecutedBlock;
-use engines::block_reward::{self, BlockRewardContract, RewardKind};
-use engines::{self, Engine};
+use engines::block_reward::{self, BlockRewardContract, RewardBeneficiary, RewardKind};
+use engines::{self, Engine, SealVerificationCost};
 use error::{BlockError, Error};
 use ethash::{self, quick_get_difficulty, slow_hash_block_number, EthashManager, OptimizeFor};
 use machine::EthereumMachine;
@@ -40,6 +40,9 @@ const SNAPSHOT_BLOCKS: u64 = 5000;
 /// Maximum number of blocks allowed in an ethash snapshot.
 const MAX_SNAPSHOT_BLOCKS: u64 = 30000;
 
+/// Number of uncles allowed per block by the protocol.
+const MAX_UNCLE_COUNT: usize = 2;
+
 /// Ethash specific seal
 #[derive(Debug, PartialEq)]
 pub struct Seal {
@@ -103,6 +106,8 @@ pub struct EthashParams {
 	pub ecip1017_era_rounds: u64,
 	/// Block reward in base units.
 	pub block_reward: BTreeMap<BlockNumber, U256>,
+	/// Additional beneficiaries of the block reward, paid from the reward of the author.
+	pub block_reward_beneficiaries: Vec<RewardBeneficiary>,
 	/// EXPIP-2 block height
 	pub expip2_transition: u64,
 	/// EXPIP-2 duration limit
@@ -115,6 +120,18 @@ pub struct EthashParams {
 	pub difficulty_bomb_delays: BTreeMap<BlockNumber, BlockNumber>,
 	/// Block to transition to progpow
 	pub progpow_transition: u64,
+	/// Maximum number of uncles per block, from the given blocks on.
+	pub max_uncle_count: BTreeMap<BlockNumber, usize>,
+	/// Maximum generation depth of uncles, from the given blocks on.
+	pub max_uncle_age: BTreeMap<BlockNumber, usize>,
+}
+
+// limits from the given blocks on, and the protocol default before the first one.
+fn uncle_limits(default: usize, limits: Option<BTreeMap<ethjson::uint::Uint, ethjson::uint::Uint>>) -> BTreeMap<BlockNumber, usize> {
+	let mut result = BTreeMap::new();
+	result.insert(0, default);
+	result.extend(limits.unwrap_or_default().into_iter().map(|(block, limit)| (block.into(), limit.into())));
+	result
 }
 
 impl From<ethjson::spec::EthashParams> for EthashParams {
@@ -133,26 +150,10 @@ impl From<ethjson::spec::EthashParams> for EthashParams {
 			ecip1010_pause_transition: p.ecip1010_pause_transition.map_or(u64::max_value(), Into::into),
 			ecip1010_continue_transition: p.ecip1010_continue_transition.map_or(u64::max_value(), Into::into),
 			ecip1017_era_rounds: p.ecip1017_era_rounds.map_or(u64::max_value(), Into::into),
-			block_reward: p.block_reward.map_or_else(
-				|| {
-					let mut ret = BTreeMap::new();
-					ret.insert(0, U256::zero());
-					ret
-				},
-				|reward| {
-					match reward {
-						ethjson::spec::BlockReward::Single(reward) => {
-							let mut ret = BTreeMap::new();
-							ret.insert(0, reward.into());
-							ret
-						},
-						ethjson::spec::BlockReward::Multi(multi) => {
-							multi.into_iter()
-								.map(|(block, reward)| (block.into(), reward.into()))
-								.collect()
-						},
-					}
-				}),
+			block_reward: block_reward::reward_schedule(p.block_reward),
+			block_reward_beneficiaries: p.block_reward_beneficiaries.unwrap_or_default().into_iter()
+				.map(Into::into)
+				.collect(),
 			expip2_transition: p.expip2_transition.map_or(u64::max_value(), Into::into),
 			expip2_duration_limit: p.expip2_duration_limit.map_or(30, Into::into),
 			progpow_transition: p.progpow_transition.map_or(u64::max_value(), Into::into),
@@ -164,7 +165,9 @@ impl From<ethjson::spec::EthashParams> for EthashParams {
 			},
 			difficulty_bomb_delays: p.difficulty_bomb_delays.unwrap_or_default().into_iter()
 				.map(|(block, delay)| (block.into(), delay.into()))
-				.collect()
+				.collect(),
+			max_uncle_count: uncle_limits(MAX_UNCLE_COUNT, p.max_uncle_count),
+			max_uncle_age: uncle_limits(engines::MAX_UNCLE_AGE, p.max_uncle_age),
 		}
 	}
 }
@@ -228,7 +231,13 @@ impl Engine<EthereumMachine> for Arc<Ethash> {
 		}
 	}
 
-	fn maximum_uncle_count(&self, _block: BlockNumber) -> usize { 2 }
+	fn maximum_uncle_count(&self, block: BlockNumber) -> usize {
+		self.ethash_params.max_uncle_count.range(..=block).last().map_or(MAX_UNCLE_COUNT, |(_, count)| *count)
+	}
+
+	fn maximum_uncle_age(&self, block: BlockNumber) -> usize {
+		self.ethash_params.max_uncle_age.range(..=block).last().map_or(engines::MAX_UNCLE_AGE, |(_, age)| *age)
+	}
 
 	fn maximum_gas_limit(&self) -> Option<U256> { Some(0x7fff_ffff_ffff_ffffu64.into()) }
 
@@ -276,10 +285,16 @@ impl Engine<EthereumMachine> for Arc<Ethash> {
 				//let n_uncles = LiveBlock::uncles(&*block).len();
 				let n_uncles = block.uncles.len();
 
+				// Pay the shares of additional beneficiaries out of the base reward.
+				let (author_reward, shares) = block_reward::split_block_reward(reward, &self.ethash_params.block_reward_beneficiaries);
+
 				// Bestow block rewards.
-				let mut result_block_reward = reward + reward.shr(5) * U256::from(n_uncles);
+				let mut result_block_reward = author_reward + reward.shr(5) * U256::from(n_uncles);
 
 				rewards.push((author, RewardKind::Author, result_block_reward));
+				for (beneficiary, share) in shares {
+					rewards.push((beneficiary, RewardKind::External, share));
+				}
 
 				// Bestow uncle rewards.
 				for u in &block.uncles {
@@ -336,6 +351,10 @@ impl Engine<EthereumMachine> for Arc<Ethash> {
 		Ok(())
 	}
 
+	fn seal_verification_cost(&self, _header: &Header) -> SealVerificationCost {
+		SealVerificationCost::Expensive
+	}
+
 	fn verify_block_unordered(&self, header: &Header) -> Result<(), Error> {
 		let seal = Seal::parse_seal(header.seal())?;
 
@@ -373,6 +392,8 @@ impl Engine<EthereumMachine> for Arc<Ethash> {
 		Ok(())
 	}
 
+	fn batch_verifies_transactions(&self) -> bool { true }
+
 	fn epoch_verifier<'a>(&self, _header: &Header, _proof: &'a [u8]) -> engines::ConstructedVerifier<'a, EthereumMachine> {
 		engines::ConstructedVerifier::Trusted(Box::new(self.clone()))
 	}
@@ -494,7 +515,7 @@ mod tests {
 	use types::header::Header;
 	use spec::Spec;
 	use engines::Engine;
-	use super::super::{new_morden, new_mcip3_test, new_homestead_test_machine};
+	use super::super::{new_morden, new_mcip3_test, new_reward_schedule_test, new_homestead_test_machine};
 	use super::{Ethash, EthashParams, ecip1017_eras_block_reward};
 	use rlp;
 	use tempdir::TempDir;
@@ -517,6 +538,7 @@ mod tests {
 				ret.insert(0, 0.into());
 				ret
 			},
+			block_reward_beneficiaries: Vec::new(),
 			difficulty_hardfork_transition: u64::max_value(),
 			difficulty_hardfork_bound_divisor: U256::from(0),
 			bomb_defuse_transition: u64::max_value(),
@@ -530,6 +552,8 @@ mod tests {
 			block_reward_contract_transition: 0,
 			difficulty_bomb_delays: BTreeMap::new(),
 			progpow_transition: u64::max_value(),
+			max_uncle_count: BTreeMap::new(),
+			max_uncle_age: BTreeMap::new(),
 		}
 	}
 
@@ -617,6 +641,46 @@ mod tests {
 		assert_eq!(b.state.balance(&dev_contract).unwrap(), U256::from_str("c249fdd327780000").unwrap());
 	}
 
+	#[test]
+	fn applies_reward_schedule_and_beneficiary_shares() {
+		let spec = new_reward_schedule_test();
+		let engine = &*spec.engine;
+		let author: Address = "000000000000000000000000000000000000aaaa".into();
+		let uncle_author: Address = "000000000000000000000000000000000000bbbb".into();
+		let foundation: Address = "0000000000000000000000000000000000001234".into();
+
+		let close_block = |number: u64, uncle: Option<Header>| {
+			let mut parent_header = spec.genesis_header();
+			parent_header.set_number(number - 1);
+			let db = spec.ensure_db_good(get_temp_state_db(), &Default::default()).unwrap();
+			let last_hashes = Arc::new(vec![parent_header.hash()]);
+			let mut b = OpenBlock::new(engine, Default::default(), false, db, &parent_header, last_hashes, author, (3141562.into(), 31415620.into()), vec![], false, None).unwrap();
+			if let Some(uncle) = uncle {
+				b.push_uncle(uncle).unwrap();
+			}
+			b.close().unwrap()
+		};
+
+		// 5 ether before the first transition, 10% of which go to the foundation.
+		let b = close_block(9, None);
+		assert_eq!(b.state.balance(&author).unwrap(), "3e73362871420000".into());
+		assert_eq!(b.state.balance(&foundation).unwrap(), "6f05b59d3b20000".into());
+
+		// 3 ether from block 10.
+		let b = close_block(10, None);
+		assert_eq!(b.state.balance(&author).unwrap(), "257853b1dd8e0000".into());
+		assert_eq!(b.state.balance(&foundation).unwrap(), "429d069189e0000".into());
+
+		// 2 ether from block 20, uncle rewards are based on the whole block reward.
+		let mut uncle = Header::new();
+		uncle.set_author(uncle_author);
+		uncle.set_number(19);
+		let b = close_block(20, Some(uncle));
+		assert_eq!(b.state.balance(&author).unwrap(), "19d8ede1ce2a4000".into());
+		assert_eq!(b.state.balance(&uncle_author).unwrap(), "18493fba64ef0000".into());
+		assert_eq!(b.state.balance(&foundation).unwrap(), "2c68af0bb140000".into());
+	}
+
 	#[test]
 	fn has_valid_metadata() {
 		let engine = test_spec().engine;
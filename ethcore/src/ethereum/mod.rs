@@ -159,6 +159,12 @@ pub fn new_constantinople_fix_test() -> Spec { load(None, include_bytes!("../../
 /// Create a new Musicoin-MCIP3-era spec.
 pub fn new_mcip3_test() -> Spec { load(None, include_bytes!("../../res/ethereum/mcip3_test.json")) }
 
+/// Create a new spec with a block reward schedule and an additional reward beneficiary.
+pub fn new_reward_schedule_test() -> Spec { load(None, include_bytes!("../../res/ethereum/reward_schedule_test.json")) }
+
+/// Create a new spec allowing fewer and younger uncles from block 10 on, and no uncles from block 20 on.
+pub fn new_uncle_policy_test() -> Spec { load(None, include_bytes!("../../res/ethereum/uncle_policy_test.json")) }
+
 // For tests
 
 /// Create a new Foundation Frontier-era chain spec as though it never changes to Homestead.
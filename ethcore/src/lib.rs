@@ -91,6 +91,7 @@ extern crate num;
 extern crate num_cpus;
 extern crate parity_bytes as bytes;
 extern crate parity_crypto;
+extern crate parity_local_store as local_store;
 extern crate parity_snappy as snappy;
 extern crate parking_lot;
 extern crate trie_db as trie;
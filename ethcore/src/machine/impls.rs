@@ -353,6 +353,12 @@ impl EthereumMachine {
 		Ok(SignedTransaction::new(t)?)
 	}
 
+	/// Verify many transactions at once, regardless of order.
+	/// Results are returned in the same order as `txs`.
+	pub fn verify_transactions_unordered(&self, txs: Vec<UnverifiedTransaction>, _header: &Header) -> Vec<Result<SignedTransaction, transaction::Error>> {
+		SignedTransaction::new_batch(txs).into_iter().map(|t| Ok(t?)).collect()
+	}
+
 	/// Does basic verification of the transaction.
 	pub fn verify_transaction_basic(&self, t: &UnverifiedTransaction, header: &Header) -> Result<(), transaction::Error> {
 		let check_low_s = match self.ethash_extensions {
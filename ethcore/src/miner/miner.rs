@@ -48,7 +48,7 @@ use types::header::Header;
 use types::receipt::RichReceipt;
 use using_queue::{UsingQueue, GetAction};
 
-use block::{ClosedBlock, SealedBlock};
+use block::{ClosedBlock, OpenBlock, SealedBlock};
 use client::{
 	BlockChain, ChainInfo, BlockProducer, SealedBlockImporter, Nonce, TransactionInfo, TransactionId
 };
@@ -430,19 +430,8 @@ impl Miner {
 				None => {
 					// block not found - create it.
 					trace!(target: "miner", "prepare_block: No existing work - making new block");
-					let params = self.params.read().clone();
-
-					match chain.prepare_open_block(
-						params.author,
-						params.gas_range_target,
-						params.extra_data,
-					) {
-						Ok(block) => block,
-						Err(err) => {
-							warn!(target: "miner", "Open new block failed with error {:?}. This is likely an error in chain specificiations or on-chain consensus smart contracts.", err);
-							return None;
-						}
-					}
+					let author = self.params.read().author;
+					self.open_new_block(chain, author)?
 				}
 			};
 
@@ -453,6 +442,10 @@ impl Miner {
 			(open_block, last_work_hash)
 		};
 
+		if let Err(e) = self.engine.on_prepare_block(&open_block) {
+			warn!(target: "miner", "prepare_block: Error preparing block for the engine: {}", e);
+		}
+
 		let mut invalid_transactions = HashSet::new();
 		let mut not_allowed_transactions = HashSet::new();
 		let mut senders_to_penalize = HashSet::new();
@@ -487,6 +480,18 @@ impl Miner {
 			}
 		);
 
+		// some engines author blocks by the sender of their first transaction.
+		if open_block.transactions.is_empty() {
+			let author = pending.first().and_then(|tx| self.engine.block_author(tx.signed()));
+			if let Some(author) = author.filter(|author| author != open_block.header.author()) {
+				trace!(target: "miner", "prepare_block: Opening block authored by {}", author);
+				open_block = self.open_new_block(chain, author)?;
+				if self.options.infinite_pending_block {
+					open_block.remove_gas_limit();
+				}
+			}
+		}
+
 		let took_ms = |elapsed: &Duration| {
 			elapsed.as_secs() * 1000 + elapsed.subsec_nanos() as u64 / 1_000_000
 		};
@@ -586,6 +591,23 @@ impl Miner {
 		Some((block, original_work_hash))
 	}
 
+	/// Opens a new block on top of the best block with the given author.
+	fn open_new_block<'a, C: BlockProducer>(&self, chain: &'a C, author: Address) -> Option<OpenBlock<'a>> {
+		let params = self.params.read().clone();
+
+		match chain.prepare_open_block(
+			author,
+			params.gas_range_target,
+			params.extra_data,
+		) {
+			Ok(block) => Some(block),
+			Err(err) => {
+				warn!(target: "miner", "Open new block failed with error {:?}. This is likely an error in chain specificiations or on-chain consensus smart contracts.", err);
+				None
+			}
+		}
+	}
+
 	/// Returns `true` if we should create pending block even if some other conditions are not met.
 	///
 	/// In general we always seal iff:
@@ -1371,7 +1393,7 @@ mod tests {
 	use rustc_hex::FromHex;
 	use types::BlockNumber;
 
-	use client::{TestBlockChainClient, EachBlockWith, ChainInfo, ImportSealedBlock};
+	use client::{TestBlockChainClient, EachBlockWith, ChainInfo, BlockInfo, ImportSealedBlock};
 	use miner::{MinerService, PendingOrdering};
 	use test_helpers::{generate_dummy_client, generate_dummy_client_with_spec};
 	use types::transaction::{Transaction};
@@ -1653,6 +1675,24 @@ mod tests {
 		assert_eq!(client.chain_info().best_block_number, 4 as BlockNumber);
 	}
 
+	#[test]
+	fn internal_seals_with_author_from_first_sender() {
+		let spec = Spec::new_instant_with_options();
+		let miner = Miner::new_for_tests(&spec, None);
+
+		let client = generate_dummy_client(2);
+
+		let transaction = transaction_with_chain_id(spec.chain_id());
+		let sender = transaction.sender();
+		let import = miner.import_external_transactions(&*client, vec![transaction.into()]).pop().unwrap();
+		assert_eq!(import.unwrap(), ());
+
+		miner.update_sealing(&*client);
+		client.flush_queue();
+		assert_eq!(client.chain_info().best_block_number, 3 as BlockNumber);
+		assert_eq!(client.block_header(BlockId::Latest).unwrap().author(), sender);
+	}
+
 	#[test]
 	fn should_not_fail_setting_engine_signer_without_account_provider() {
 		let spec = Spec::new_test_round;
@@ -36,7 +36,7 @@ use vm::{EnvInfo, CallType, ActionValue, ActionParams, ParamsType};
 use builtin::Builtin;
 use engines::{
 	EthEngine, NullEngine, InstantSeal, InstantSealParams, BasicAuthority, Clique,
-	AuthorityRound, DEFAULT_BLOCKHASH_CONTRACT
+	AuthorityRound, Hybrid, DEFAULT_BLOCKHASH_CONTRACT
 };
 use error::Error;
 use executive::Executive;
@@ -53,6 +53,9 @@ pub use ethash::OptimizeFor;
 
 const MAX_TRANSACTION_SIZE: usize = 300 * 1024;
 
+/// Number of blocks in each canonical hash trie, unless the spec says otherwise.
+pub const DEFAULT_CHT_SIZE: u64 = 2048;
+
 // helper for formatting errors.
 fn fmt_err<F: ::std::fmt::Display>(f: F) -> String {
 	format!("Spec json is invalid: {}", f)
@@ -64,8 +67,7 @@ fn fmt_err<F: ::std::fmt::Display>(f: F) -> String {
 ///
 /// we define a "bugfix" hard fork as any hard fork which
 /// you would put on-by-default in a new chain.
-#[derive(Debug, PartialEq, Default)]
-#[cfg_attr(test, derive(Clone))]
+#[derive(Debug, PartialEq, Default, Clone)]
 pub struct CommonParams {
 	/// Account start nonce.
 	pub account_start_nonce: U256,
@@ -151,6 +153,8 @@ pub struct CommonParams {
 	pub transaction_permission_contract_transition: BlockNumber,
 	/// Maximum size of transaction's RLP payload
 	pub max_transaction_size: usize,
+	/// Number of blocks in each canonical hash trie (CHT) served to light clients.
+	pub cht_size: u64,
 }
 
 impl CommonParams {
@@ -319,6 +323,7 @@ impl From<ethjson::spec::Params> for CommonParams {
 			node_permission_contract: p.node_permission_contract.map(Into::into),
 			max_code_size: p.max_code_size.map_or(u64::max_value(), Into::into),
 			max_transaction_size: p.max_transaction_size.map_or(MAX_TRANSACTION_SIZE, Into::into),
+			cht_size: p.cht_size.map_or(DEFAULT_CHT_SIZE, Into::into),
 			max_code_size_transition: p.max_code_size_transition.map_or(0, Into::into),
 			transaction_permission_contract: p.transaction_permission_contract.map(Into::into),
 			transaction_permission_contract_transition:
@@ -461,6 +466,19 @@ pub struct SpecHardcodedSync {
 }
 
 impl SpecHardcodedSync {
+	/// Loads hardcoded sync data from a JSON file with the structure of the spec's `hardcodedSync` section.
+	pub fn load<R: Read>(reader: R) -> Result<Self, String> {
+		let hs = ethjson::spec::HardcodedSync::load(reader).map_err(|e| format!("Hardcoded sync json is invalid: {}", e))?;
+		let header = encoded::Header::new(hs.header.from_hex().map_err(|e| format!("Invalid header: {}", e))?);
+		check_hardcoded_sync_hash(&header, hs.hash.map(Into::into))?;
+
+		Ok(SpecHardcodedSync {
+			header,
+			total_difficulty: hs.total_difficulty.into(),
+			chts: hs.chts.into_iter().map(Into::into).collect(),
+		})
+	}
+
 	/// Turns this specifications back into JSON. Useful for pretty printing.
 	pub fn to_json(self) -> ethjson::spec::HardcodedSync {
 		self.into()
@@ -481,6 +499,7 @@ impl Clone for SpecHardcodedSync {
 impl From<SpecHardcodedSync> for ethjson::spec::HardcodedSync {
 	fn from(sync: SpecHardcodedSync) -> ethjson::spec::HardcodedSync {
 		ethjson::spec::HardcodedSync {
+			hash: Some(sync.header.hash().into()),
 			header: sync.header.into_inner().to_hex(),
 			total_difficulty: ethjson::uint::Uint(sync.total_difficulty),
 			chts: sync.chts.into_iter().map(Into::into).collect(),
@@ -495,21 +514,32 @@ fn load_machine_from(s: ethjson::spec::Spec) -> EthereumMachine {
 	Spec::machine(&s.engine, params, builtins)
 }
 
+// hardcoded sync data may name the hash of its header to guard against corruption.
+fn check_hardcoded_sync_hash(header: &encoded::Header, expected: Option<H256>) -> Result<(), String> {
+	match expected {
+		Some(expected) if header.hash() != expected =>
+			Err(format!("Hardcoded sync header hash {} doesn't match the expected {}", header.hash(), expected)),
+		_ => Ok(()),
+	}
+}
+
 /// Load from JSON object.
 fn load_from(spec_params: SpecParams, s: ethjson::spec::Spec) -> Result<Spec, Error> {
-	let builtins = s.accounts
-		.builtins()
-		.into_iter()
-		.map(|p| (p.0.into(), From::from(p.1)))
-		.collect();
+	let builtins = s.accounts.builtins();
 	let g = Genesis::from(s.genesis);
 	let GenericSeal(seal_rlp) = g.seal.into();
 	let params = CommonParams::from(s.params);
+	if !params.cht_size.is_power_of_two() {
+		return Err(format!("Invalid CHT size {}: must be a power of two", params.cht_size).into());
+	}
 
 	let hardcoded_sync = if let Some(ref hs) = s.hardcoded_sync {
 		if let Ok(header) = hs.header.from_hex() {
+			let header = encoded::Header::new(header);
+			check_hardcoded_sync_hash(&header, hs.hash.clone().map(Into::into))?;
+
 			Some(SpecHardcodedSync {
-				header: encoded::Header::new(header),
+				header,
 				total_difficulty: hs.total_difficulty.into(),
 				chts: s.hardcoded_sync
 					.as_ref()
@@ -587,10 +617,23 @@ impl Spec {
 		params: CommonParams,
 		builtins: BTreeMap<Address, Builtin>,
 	) -> EthereumMachine {
-		if let ethjson::spec::Engine::Ethash(ref ethash) = *engine_spec {
-			EthereumMachine::with_ethash_extensions(params, builtins, ethash.params.clone().into())
-		} else {
-			EthereumMachine::regular(params, builtins)
+		match *engine_spec {
+			ethjson::spec::Engine::Ethash(ref ethash) =>
+				EthereumMachine::with_ethash_extensions(params, builtins, ethash.params.clone().into()),
+			// the machine of a hybrid chain keeps the Ethash extensions of an Ethash sub-engine.
+			ethjson::spec::Engine::Hybrid(ref hybrid) => {
+				let ethash = hybrid.params.engines.values()
+					.filter_map(|engine| match *engine {
+						ethjson::spec::Engine::Ethash(ref ethash) => Some(ethash),
+						_ => None,
+					})
+					.next();
+				match ethash {
+					Some(ethash) => EthereumMachine::with_ethash_extensions(params, builtins, ethash.params.clone().into()),
+					None => EthereumMachine::regular(params, builtins),
+				}
+			},
+			_ => EthereumMachine::regular(params, builtins),
 		}
 	}
 
@@ -600,9 +643,13 @@ impl Spec {
 		spec_params: SpecParams,
 		engine_spec: ethjson::spec::Engine,
 		params: CommonParams,
-		builtins: BTreeMap<Address, Builtin>,
+		builtins: BTreeMap<ethjson::hash::Address, ethjson::spec::Builtin>,
 	) -> Arc<EthEngine> {
-		let machine = Self::machine(&engine_spec, params, builtins);
+		let machine = Self::machine(
+			&engine_spec,
+			params.clone(),
+			builtins.clone().into_iter().map(|p| (p.0.into(), From::from(p.1))).collect(),
+		);
 
 		match engine_spec {
 			ethjson::spec::Engine::Null(null) => Arc::new(NullEngine::new(null.params.into(), machine)),
@@ -614,6 +661,14 @@ impl Spec {
 								.expect("Failed to start Clique consensus engine."),
 			ethjson::spec::Engine::AuthorityRound(authority_round) => AuthorityRound::new(authority_round.params.into(), machine)
 				.expect("Failed to start AuthorityRound consensus engine."),
+			ethjson::spec::Engine::Hybrid(hybrid) => {
+				// each sub-engine gets its own machine, built from the same parameters.
+				let mut engines = hybrid.params.engines.into_iter()
+					.map(|(number, engine)| (number, Self::engine(spec_params, engine, params.clone(), builtins.clone())));
+				let (_, first) = engines.next().expect("validated to define exactly two engines; qed");
+				let (transition, second) = engines.next().expect("validated to define exactly two engines; qed");
+				Arc::new(Hybrid::new(transition.into(), first, second, machine))
+			},
 		}
 	}
 
@@ -907,6 +962,13 @@ impl Spec {
 		load_bundled!("instant_seal")
 	}
 
+	/// Create a new Spec with InstantSeal consensus using millisecond timestamps,
+	/// which authors blocks by the sender of their first transaction.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn new_instant_with_options() -> Spec {
+		load_bundled!("instant_seal_options")
+	}
+
 	/// Create a new Spec which conforms to the Frontier-era Morden chain except that it's a
 	/// NullEngine consensus.
 	#[cfg(any(test, feature = "test-helpers"))]
@@ -943,6 +1005,21 @@ impl Spec {
 		load_bundled!("authority_round")
 	}
 
+	/// Create a new Spec with AuthorityRound consensus which does internal sealing (not
+	/// requiring work) and tracks finality of blocks.
+	/// Accounts with secrets keccak("0") and keccak("1") are the validators.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn new_test_round_finality() -> Self {
+		load_bundled!("authority_round_finality")
+	}
+
+	/// Create a new Spec which seals blocks instantly up to block 2 and switches to
+	/// BasicAuthority with the account of secret keccak("") as validator afterwards.
+	#[cfg(any(test, feature = "test-helpers"))]
+	pub fn new_test_hybrid() -> Self {
+		load_bundled!("hybrid")
+	}
+
 	/// Create a new Spec with AuthorityRound consensus which does internal sealing (not
 	/// requiring work) with empty step messages enabled.
 	/// Accounts with secrets keccak("0") and keccak("1") are the validators.
@@ -15,28 +15,41 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 
-use ethereum_types::{U256, Address};
-use ethkey::KeyPair;
+use accounts::AccountProvider;
+use blockchain::BlockChainDB;
+use db::Readable;
+use db::keys::BlockDetails;
+
+use ethereum_types::{H256, U256, Address};
+use ethkey::{KeyPair, Signature};
 use hash::keccak;
 use io::IoChannel;
+use parking_lot::RwLock;
 use tempdir::TempDir;
-use types::transaction::{PendingTransaction, Transaction, Action, Condition};
+use types::BlockNumber;
+use types::header::{Header, ExtendedHeader};
+use types::transaction::{self, PendingTransaction, Transaction, Action, Condition};
 use types::filter::Filter;
 use types::view;
 use types::views::BlockView;
 
-use client::{BlockChainClient, Client, ClientConfig, BlockId, ChainInfo, BlockInfo, PrepareOpenBlock, ImportSealedBlock, ImportBlock};
+use client::{BlockChainClient, EngineClient, Client, ClientConfig, BlockId, ChainInfo, BlockInfo, PrepareOpenBlock, ImportSealedBlock, ImportBlock};
+use engines::{self, Engine, EngineSigner, EthEngine, ForkChoice};
+use error::{Error as EthcoreError, BlockError};
+use ethcore_miner::gas_pricer::GasPricer;
 use ethereum;
 use executive::{Executive, TransactOptions};
-use miner::{Miner, PendingOrdering, MinerService};
+use machine::EthereumMachine;
+use miner::{Miner, MinerOptions, PendingOrdering, MinerService, Author};
 use spec::Spec;
 use state::{self, State, CleanupMode};
 use test_helpers::{
 	self,
 	generate_dummy_client, push_blocks_to_client, get_test_client_with_blocks, get_good_dummy_block_seq,
-	generate_dummy_client_with_data, get_good_dummy_block, get_bad_state_dummy_block
+	generate_dummy_client_with_data, get_good_dummy_block, get_bad_state_dummy_block, create_test_block
 };
 use verification::queue::kind::blocks::Unverified;
 
@@ -293,6 +306,66 @@ fn change_history_size() {
 	assert_eq!(client.state().balance(&address).unwrap(), 100.into());
 }
 
+fn client_with_corrupted_head(config: ClientConfig, db: Arc<BlockChainDB>) -> Result<Arc<Client>, ::error::Error> {
+	let test_spec = Spec::new_null();
+	{
+		let client = Client::new(
+			ClientConfig::default(),
+			&test_spec,
+			db.clone(),
+			Arc::new(Miner::new_for_tests(&test_spec, None)),
+			IoChannel::disconnected()
+		).unwrap();
+
+		for _ in 0..5 {
+			let b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+			let b = b.close_and_lock().unwrap().seal(&*test_spec.engine, vec![]).unwrap();
+			client.import_sealed_block(b).unwrap();
+		}
+		assert_eq!(client.chain_info().best_block_number, 5);
+
+		let mut batch = db.key_value().transaction();
+		batch.put(::db::COL_BODIES, &client.chain_info().best_block_hash, &[0xde, 0xad]);
+		db.key_value().write(batch).unwrap();
+	}
+
+	Client::new(
+		config,
+		&test_spec,
+		db,
+		Arc::new(Miner::new_for_tests(&test_spec, None)),
+		IoChannel::disconnected(),
+	)
+}
+
+#[test]
+fn rolls_back_inconsistent_best_block_on_startup() {
+	let client = client_with_corrupted_head(ClientConfig::default(), test_helpers::new_db()).unwrap();
+	assert_eq!(client.chain_info().best_block_number, 4);
+	assert!(client.block(BlockId::Latest).is_some());
+	assert!(client.state_at(BlockId::Latest).is_some());
+}
+
+#[test]
+fn fails_on_rollback_deeper_than_configured() {
+	let mut config = ClientConfig::default();
+	config.auto_rollback = Some(0);
+	assert!(client_with_corrupted_head(config, test_helpers::new_db()).is_err());
+}
+
+#[test]
+fn fails_fast_on_inconsistent_best_block_without_auto_rollback() {
+	let mut config = ClientConfig::default();
+	config.auto_rollback = None;
+	let db = test_helpers::new_db();
+	assert!(client_with_corrupted_head(config, db.clone()).is_err());
+
+	// nothing was rolled back, the inconsistent block is still the best one.
+	let best = db.key_value().get(::db::COL_EXTRA, b"best").unwrap().map(|best| H256::from_slice(&best)).unwrap();
+	let details = db.key_value().read::<BlockDetails, _>(::db::COL_EXTRA, &best).unwrap();
+	assert_eq!(details.number, 5);
+}
+
 #[test]
 fn does_not_propagate_delayed_transactions() {
 	let key = KeyPair::from_secret(keccak("test").into()).unwrap();
@@ -366,3 +439,151 @@ fn transaction_proof() {
 	assert_eq!(state.balance(&Address::default()).unwrap(), 5.into());
 	assert_eq!(state.balance(&address).unwrap(), 95.into());
 }
+
+/// Engine which emits service transactions on demand.
+struct ServiceTransactionEngine {
+	inner: Arc<EthEngine>,
+	signer: RwLock<Option<Box<EngineSigner>>>,
+	client: RwLock<Option<Weak<EngineClient>>>,
+}
+
+impl ServiceTransactionEngine {
+	fn new() -> Arc<Self> {
+		Arc::new(ServiceTransactionEngine {
+			inner: Spec::new_null().engine,
+			signer: RwLock::new(None),
+			client: RwLock::new(None),
+		})
+	}
+
+	fn emit(&self, purpose: &str, valid_until: Option<BlockNumber>) -> Result<(), transaction::Error> {
+		let client = self.client.read().as_ref().and_then(Weak::upgrade).expect("client is registered; qed");
+		let data = purpose.as_bytes().to_vec();
+		client.as_full_client().expect("full client; qed")
+			.transact_service_contract(purpose.into(), Address::from(0x1234), data, valid_until)
+	}
+}
+
+impl Engine<EthereumMachine> for ServiceTransactionEngine {
+	fn name(&self) -> &str { "ServiceTransactionEngine" }
+
+	fn machine(&self) -> &EthereumMachine { self.inner.machine() }
+
+	fn seals_internally(&self) -> Option<bool> { Some(false) }
+
+	fn verify_local_seal(&self, header: &Header) -> Result<(), ::error::Error> {
+		self.inner.verify_local_seal(header)
+	}
+
+	fn fork_choice(&self, new: &ExtendedHeader, best: &ExtendedHeader) -> ForkChoice {
+		self.inner.fork_choice(new, best)
+	}
+
+	fn set_signer(&self, signer: Box<EngineSigner>) {
+		*self.signer.write() = Some(signer);
+	}
+
+	fn sign(&self, hash: H256) -> Result<Signature, ::error::Error> {
+		match *self.signer.read() {
+			Some(ref signer) => Ok(signer.sign(hash)?),
+			None => Err(::ethkey::Error::InvalidSecret.into()),
+		}
+	}
+
+	fn register_client(&self, client: Weak<EngineClient>) {
+		*self.client.write() = Some(client);
+	}
+}
+
+#[test]
+fn resubmits_service_transactions_after_restart() {
+	let db = test_helpers::new_db();
+	let engine = ServiceTransactionEngine::new();
+	let keypair = KeyPair::from_secret(keccak("service").into()).unwrap();
+	let start = || {
+		let mut spec = Spec::new_null();
+		spec.engine = engine.clone() as Arc<EthEngine>;
+
+		let miner = Arc::new(Miner::new_for_tests(&spec, None));
+		miner.set_gas_range_target((1_000_000.into(), 1_000_000.into()));
+		miner.set_author(Author::Sealer(engines::signer::from_keypair(keypair.clone())));
+
+		let client = Client::new(ClientConfig::default(), &spec, db.clone(), miner, IoChannel::disconnected()).unwrap();
+		engine.register_client(Arc::downgrade(&client) as _);
+		client
+	};
+	let journaled = || ::local_store::service_transactions(&**db.key_value(), ::db::COL_NODE_INFO).unwrap();
+
+	{
+		let client = start();
+		engine.emit("report", None).unwrap();
+		engine.emit("reveal:1", Some(0)).unwrap();
+		engine.emit("reveal:1", Some(0)).unwrap();
+		assert_eq!(client.miner().ready_transactions(&*client, 10, PendingOrdering::Priority).len(), 2);
+	}
+
+	// the transactions are lost from the queue on restart, but not from the journal.
+	let purposes: Vec<_> = journaled().into_iter().map(|tx| tx.purpose).collect();
+	assert_eq!(purposes, vec!["report".to_owned(), "reveal:1".to_owned()]);
+
+	let client = start();
+	let ready = client.miner().ready_transactions(&*client, 10, PendingOrdering::Priority);
+	assert_eq!(ready.len(), 2);
+
+	// only the report gets included, while the round of the reveal passes.
+	let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+	b.push_transaction(ready[0].signed().clone(), None).unwrap();
+	let b = b.close_and_lock().unwrap().seal(&*engine, vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+
+	assert!(journaled().is_empty());
+}
+
+#[test]
+fn tracks_finality_and_refuses_to_revert_finalized_blocks() {
+	let tap = Arc::new(AccountProvider::transient_provider());
+	let validators = [
+		tap.insert_account(keccak("0").into(), &"".into()).unwrap(),
+		tap.insert_account(keccak("1").into(), &"".into()).unwrap(),
+	];
+
+	let spec = Spec::new_test_round_finality();
+	let miner = Miner::new(MinerOptions {
+		force_sealing: true,
+		reseal_min_period: Duration::from_secs(0),
+		..Default::default()
+	}, GasPricer::new_fixed(0.into()), &spec, Default::default());
+	miner.set_gas_range_target((1_000_000.into(), 1_000_000.into()));
+	let client = Client::new(ClientConfig::default(), &spec, test_helpers::new_db(), Arc::new(miner), IoChannel::disconnected()).unwrap();
+	client.engine().register_client(Arc::downgrade(&client) as _);
+	assert_eq!(client.is_finalized(BlockId::Number(0)), Some(false));
+
+	// seal blocks alternately with both validators, waiting a step whenever the other one is the proposer.
+	let mut next = 0;
+	for _ in 0..8 {
+		let best = client.chain_info().best_block_number;
+		if best == 3 { break }
+
+		client.miner().set_author(Author::Sealer(Box::new((tap.clone(), validators[next], "".into()))));
+		client.engine().step();
+		if client.chain_info().best_block_number > best {
+			next = 1 - next;
+		}
+	}
+	assert_eq!(client.chain_info().best_block_number, 3);
+
+	// a block is finalized once both validators built on top of it.
+	assert_eq!(client.is_finalized(BlockId::Number(1)), Some(true));
+	assert_eq!(client.is_finalized(BlockId::Number(2)), Some(true));
+	assert_eq!(client.is_finalized(BlockId::Number(3)), Some(false));
+
+	// a sibling of the finalized block is refused.
+	let finalized = client.block_hash(BlockId::Number(2)).unwrap();
+	let mut header = Header::new();
+	header.set_parent_hash(client.block_hash(BlockId::Number(1)).unwrap());
+	header.set_number(2);
+	match client.import_block(Unverified::from_rlp(create_test_block(&header)).unwrap()) {
+		Err(EthcoreError::Block(BlockError::RevertsFinalized(hash))) => assert_eq!(hash, finalized),
+		other => panic!("unexpected import result: {:?}", other),
+	}
+}
@@ -16,7 +16,7 @@
 
 //! Definition of valid items for the verification queue.
 
-use engines::EthEngine;
+use engines::{EthEngine, SealVerificationCost};
 use error::Error;
 
 use heapsize::HeapSizeOf;
@@ -62,13 +62,16 @@ pub trait Kind: 'static + Sized + Send + Sync {
 
 	/// Attempt to verify the `Unverified` item using the given engine.
 	fn verify(unverified: Self::Unverified, engine: &EthEngine, check_seal: bool) -> Result<Self::Verified, Error>;
+
+	/// Relative cost of verifying the `Unverified` item.
+	fn verification_cost(unverified: &Self::Unverified, engine: &EthEngine, check_seal: bool) -> SealVerificationCost;
 }
 
 /// The blocks verification module.
 pub mod blocks {
 	use super::{Kind, BlockLike};
 
-	use engines::EthEngine;
+	use engines::{EthEngine, SealVerificationCost};
 	use error::{Error, BlockError};
 	use types::header::Header;
 	use verification::{PreverifiedBlock, verify_block_basic, verify_block_unordered};
@@ -110,6 +113,13 @@ pub mod blocks {
 				}
 			}
 		}
+
+		fn verification_cost(un: &Self::Unverified, engine: &EthEngine, check_seal: bool) -> SealVerificationCost {
+			match check_seal {
+				true => engine.seal_verification_cost(&un.header),
+				false => SealVerificationCost::Cheap,
+			}
+		}
 	}
 
 	/// An unverified block.
@@ -188,7 +198,7 @@ pub mod blocks {
 pub mod headers {
 	use super::{Kind, BlockLike};
 
-	use engines::EthEngine;
+	use engines::{EthEngine, SealVerificationCost};
 	use error::Error;
 	use types::header::Header;
 	use verification::verify_header_params;
@@ -222,5 +232,12 @@ pub mod headers {
 				false => Ok(unverified),
 			}
 		}
+
+		fn verification_cost(unverified: &Self::Unverified, engine: &EthEngine, check_seal: bool) -> SealVerificationCost {
+			match check_seal {
+				true => engine.seal_verification_cost(unverified),
+				false => SealVerificationCost::Cheap,
+			}
+		}
 	}
 }
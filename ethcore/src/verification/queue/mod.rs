@@ -27,7 +27,7 @@ use ethereum_types::{H256, U256};
 use parking_lot::{Condvar, Mutex, RwLock};
 use io::*;
 use error::{BlockError, ImportError, Error};
-use engines::EthEngine;
+use engines::{EthEngine, SealVerificationCost};
 use client::ClientIoMessage;
 use len_caching_lock::LenCachingMutex;
 
@@ -39,6 +39,8 @@ pub mod kind;
 
 const MIN_MEM_LIMIT: usize = 16384;
 const MIN_QUEUE_LIMIT: usize = 512;
+// maximum number of cheap items verified by a thread at once.
+const MAX_CHEAP_BATCH: usize = 64;
 
 /// Type alias for block queue convenience.
 pub type BlockQueue = VerificationQueue<self::kind::Blocks>;
@@ -302,7 +304,7 @@ impl<K: Kind> VerificationQueue<K> {
 	) {
 		loop {
 			// check current state.
-			{
+			let verifiers = {
 				let mut cur_state = state.0.lock();
 				while let State::Work(x) = *cur_state {
 					// sleep until this thread is required.
@@ -313,11 +315,14 @@ impl<K: Kind> VerificationQueue<K> {
 					debug!(target: "verification", "verifier {} waking up", id);
 				}
 
-				if let State::Exit = *cur_state {
-					debug!(target: "verification", "verifier {} exiting", id);
-					break;
+				match *cur_state {
+					State::Work(x) => x,
+					State::Exit => {
+						debug!(target: "verification", "verifier {} exiting", id);
+						break;
+					}
 				}
-			}
+			};
 
 			// wait for work if empty.
 			{
@@ -343,61 +348,57 @@ impl<K: Kind> VerificationQueue<K> {
 			}
 
 			// do work.
-			let item = {
-				// acquire these locks before getting the item to verify.
+			let items = {
+				// acquire these locks before getting the items to verify.
 				let mut unverified = verification.unverified.lock();
 				let mut verifying = verification.verifying.lock();
 
-				let item = match unverified.pop_front() {
-					Some(item) => item,
-					None => continue,
-				};
+				let items = VerificationQueue::next_batch(&mut unverified, &*engine, verification.check_seal, verifiers);
+				if items.is_empty() {
+					continue;
+				}
 
-				verification.sizes.unverified.fetch_sub(item.heap_size_of_children(), AtomicOrdering::SeqCst);
-				verifying.push_back(Verifying { hash: item.hash(), output: None });
-				item
+				for item in &items {
+					verification.sizes.unverified.fetch_sub(item.heap_size_of_children(), AtomicOrdering::SeqCst);
+					verifying.push_back(Verifying { hash: item.hash(), output: None });
+				}
+				items
 			};
 
-			let hash = item.hash();
-			let is_ready = match K::verify(item, &*engine, verification.check_seal) {
-				Ok(verified) => {
-					let mut verifying = verification.verifying.lock();
-					let mut idx = None;
-					for (i, e) in verifying.iter_mut().enumerate() {
-						if e.hash == hash {
-							idx = Some(i);
-
-							verification.sizes.verifying.fetch_add(verified.heap_size_of_children(), AtomicOrdering::SeqCst);
-							e.output = Some(verified);
-							break;
+			let results: Vec<_> = items.into_iter()
+				.map(|item| (item.hash(), K::verify(item, &*engine, verification.check_seal)))
+				.collect();
+
+			let is_ready = {
+				let mut verifying = verification.verifying.lock();
+				let mut failed = Vec::new();
+				for (hash, result) in results {
+					match result {
+						Ok(verified) => {
+							if let Some(e) = verifying.iter_mut().find(|e| e.hash == hash) {
+								verification.sizes.verifying.fetch_add(verified.heap_size_of_children(), AtomicOrdering::SeqCst);
+								e.output = Some(verified);
+							}
+						},
+						Err(_) => {
+							verifying.retain(|e| e.hash != hash);
+							failed.push(hash);
 						}
 					}
+				}
 
-					if idx == Some(0) {
-						// we're next!
-						let mut verified = verification.verified.lock();
-						let mut bad = verification.bad.lock();
-						VerificationQueue::drain_verifying(&mut verifying, &mut verified, &mut bad, &verification.sizes);
-						true
-					} else {
-						false
-					}
-				},
-				Err(_) => {
-					let mut verifying = verification.verifying.lock();
+				// the front may have been verified by us or uncovered by a failure.
+				let is_next = verifying.front().map_or(false, |x| x.output.is_some());
+				if is_next || !failed.is_empty() {
 					let mut verified = verification.verified.lock();
 					let mut bad = verification.bad.lock();
+					bad.extend(failed);
 
-					bad.insert(hash.clone());
-					verifying.retain(|e| e.hash != hash);
-
-					if verifying.front().map_or(false, |x| x.output.is_some()) {
+					if is_next {
 						VerificationQueue::drain_verifying(&mut verifying, &mut verified, &mut bad, &verification.sizes);
-						true
-					} else {
-						false
 					}
 				}
+				is_next
 			};
 			if is_ready {
 				// Import the block immediately
@@ -406,6 +407,37 @@ impl<K: Kind> VerificationQueue<K> {
 		}
 	}
 
+	// take the next items to verify off the unverified queue.
+	// an expensive item is verified alone, while consecutive cheap items are
+	// verified in a batch, sized so the queue is still shared between all verifiers.
+	// items are taken in order, so `verifying` keeps the import order.
+	fn next_batch(
+		unverified: &mut VecDeque<K::Unverified>,
+		engine: &EthEngine,
+		check_seal: bool,
+		verifiers: usize,
+	) -> Vec<K::Unverified> {
+		let max_batch = cmp::max(1, cmp::min(MAX_CHEAP_BATCH, unverified.len() / cmp::max(1, verifiers)));
+		let mut batch = Vec::new();
+
+		while let Some(cost) = unverified.front().map(|item| K::verification_cost(item, engine, check_seal)) {
+			match cost {
+				SealVerificationCost::Expensive if batch.is_empty() => {
+					batch.extend(unverified.pop_front());
+					break;
+				},
+				SealVerificationCost::Expensive => break,
+				SealVerificationCost::Cheap => batch.extend(unverified.pop_front()),
+			}
+
+			if batch.len() >= max_batch {
+				break;
+			}
+		}
+
+		batch
+	}
+
 	fn drain_verifying(
 		verifying: &mut VecDeque<Verifying<K>>,
 		verified: &mut VecDeque<K::Verified>,
@@ -745,8 +777,16 @@ mod tests {
 	use test_helpers::{get_good_dummy_block_seq, get_good_dummy_block};
 	use error::*;
 	use bytes::Bytes;
+	use ethereum_types::{H256, H520};
+	use rlp::{self, RlpStream};
+	use std::collections::HashSet;
+	use triehash::ordered_trie_root;
+	use types::header::Header;
+	use types::transaction::UnverifiedTransaction;
 	use types::view;
 	use types::views::BlockView;
+	use verification::verify_block_unordered;
+	use super::kind::BlockLike;
 
 	// create a test block queue.
 	// auto_scaling enables verifier adjustment.
@@ -770,6 +810,22 @@ mod tests {
 		Unverified::from_rlp(bytes).expect("Should be valid rlp")
 	}
 
+	// create an Aura block at a past step. The seal signature isn't checked in the queue.
+	fn aura_block(number: u64, parent_hash: H256, transactions: &[UnverifiedTransaction]) -> Bytes {
+		let mut header = Header::new();
+		header.set_number(number);
+		header.set_parent_hash(parent_hash);
+		header.set_gas_limit(0x222222.into());
+		header.set_transactions_root(ordered_trie_root(transactions.iter().map(rlp::encode)));
+		header.set_seal(vec![rlp::encode(&1u64), rlp::encode(&H520::default())]);
+
+		let mut rlp = RlpStream::new_list(3);
+		rlp.append(&header);
+		rlp.append_list(transactions);
+		rlp.append_raw(&rlp::EMPTY_LIST_RLP, 1);
+		rlp.out()
+	}
+
 	#[test]
 	fn can_be_created() {
 		// TODO better test
@@ -900,6 +956,63 @@ mod tests {
 		assert_eq!(queue.num_verifiers(), 1);
 	}
 
+	#[test]
+	fn batched_verification_matches_sequential() {
+		// a chain of cheap to verify blocks. every 500 blocks there is also a sibling
+		// failing phase 2 verification due to an invalid signature, and a child of it.
+		let invalid_tx: UnverifiedTransaction = {
+			// signature with `r` of zero, passing phase 1 checks but failing recovery.
+			let mut tx = RlpStream::new_list(9);
+			tx.append(&0u8).append(&0u8).append(&0u8).append_empty_data().append(&0u8).append_empty_data();
+			tx.append(&27u8).append(&0u8).append(&1u8);
+			rlp::decode(&tx.out()).expect("valid transaction rlp")
+		};
+		let invalid = vec![invalid_tx];
+		let mut blocks = Vec::new();
+		let mut parent_hash = H256::zero();
+		for number in 1..3001 {
+			let block = aura_block(number, parent_hash, &[]);
+			if number % 500 == 0 {
+				let bad = aura_block(number, parent_hash, &invalid);
+				let bad_child = aura_block(number + 1, view!(BlockView, &bad).hash(), &[]);
+				blocks.push(bad);
+				blocks.push(bad_child);
+			}
+			parent_hash = view!(BlockView, &block).hash();
+			blocks.push(block);
+		}
+
+		let spec = Spec::new_test_round();
+		let engine = spec.engine;
+
+		let mut expected = Vec::new();
+		let mut bad = HashSet::new();
+		for block in &blocks {
+			let block = new_unverified(block.clone());
+			let (hash, parent_hash) = (block.hash(), block.parent_hash());
+			match verify_block_unordered(block, &*engine, true) {
+				Ok(_) if !bad.contains(&parent_hash) => expected.push(hash),
+				_ => { bad.insert(hash); },
+			}
+		}
+		assert_eq!(expected.len(), 3000);
+		assert_eq!(bad.len(), 12);
+
+		let queue = BlockQueue::new(get_test_config(4, false), engine, IoChannel::disconnected(), true);
+		for block in blocks {
+			// children of known bad blocks are rejected right away.
+			match queue.import(new_unverified(block)) {
+				Ok(_) | Err((_, Error::Import(ImportError::KnownBad))) => {},
+				Err((_, e)) => panic!("unexpected import error: {:?}", e),
+			}
+		}
+		queue.flush();
+
+		let verified: Vec<_> = queue.drain(usize::max_value()).into_iter().map(|block| block.header.hash()).collect();
+		assert_eq!(verified, expected);
+		assert!(queue.is_empty());
+	}
+
 		#[test]
 		fn worker_threads_honor_specified_number_without_scaling() {
 			let spec = Spec::new_test();
@@ -104,10 +104,10 @@ pub fn verify_block_unordered(block: Unverified, engine: &EthEngine, check_seal:
 		None
 	};
 
-	let transactions = block.transactions
+	let transactions = engine.verify_transactions_unordered(block.transactions, &header)
 		.into_iter()
 		.map(|t| {
-			let t = engine.verify_transaction_unordered(t, &header)?;
+			let t = t?;
 			if let Some(max_nonce) = nonce_cap {
 				if t.nonce >= max_nonce {
 					return Err(BlockError::TooManyTransactions(t.sender()).into());
@@ -163,6 +163,7 @@ fn verify_uncles(block: &PreverifiedBlock, bc: &BlockProvider, engine: &EthEngin
 	let header = &block.header;
 	let num_uncles = block.uncles.len();
 	let max_uncles = engine.maximum_uncle_count(header.number());
+	let max_uncle_age = engine.maximum_uncle_age(header.number());
 	if num_uncles != 0 {
 		if num_uncles > max_uncles {
 			return Err(From::from(BlockError::TooManyUncles(OutOfBounds {
@@ -209,7 +210,7 @@ fn verify_uncles(block: &PreverifiedBlock, bc: &BlockProvider, engine: &EthEngin
 			//												(8 Invalid)
 
 			let depth = if header.number() > uncle.number() { header.number() - uncle.number() } else { 0 };
-			if depth > MAX_UNCLE_AGE as u64 {
+			if depth > max_uncle_age as u64 {
 				return Err(From::from(BlockError::UncleTooOld(OutOfBounds { min: Some(header.number() - depth), max: Some(header.number() - 1), found: uncle.number() })));
 			}
 			else if depth < 1 {
@@ -332,7 +333,7 @@ fn verify_parent(header: &Header, parent: &Header, engine: &EthEngine) -> Result
 
 	let gas_limit_divisor = engine.params().gas_limit_bound_divisor;
 
-	if !engine.is_timestamp_valid(header.timestamp(), parent.timestamp()) {
+	if !engine.is_timestamp_valid(header, parent) {
 		let now = SystemTime::now();
 		let min = now.checked_add(Duration::from_secs(parent.timestamp().saturating_add(1)))
 			.ok_or(BlockError::TimestampOverflow)?;
@@ -559,6 +560,41 @@ mod tests {
 		Ok(())
 	}
 
+	fn child_header(engine: &EthEngine, parent: &Header, extra: u8) -> Header {
+		let mut header = Header::new();
+		header.set_number(parent.number() + 1);
+		header.set_parent_hash(parent.hash());
+		header.set_gas_limit(*parent.gas_limit());
+		header.set_timestamp(parent.timestamp() + 10);
+		header.set_extra_data(vec![extra]);
+		engine.populate_from_parent(&mut header, parent);
+		header
+	}
+
+	// verify block `number` including uncles of the given depths.
+	fn uncles_test(engine: &EthEngine, number: BlockNumber, uncle_depths: &[usize]) -> Result<(), Error> {
+		let mut first = Header::new();
+		first.set_number(number - 8);
+		first.set_gas_limit(engine.params().min_gas_limit);
+
+		let mut ancestors = vec![first];
+		for _ in 0..7 {
+			let header = child_header(engine, ancestors.last().unwrap(), 0);
+			ancestors.push(header);
+		}
+
+		let mut bc = TestBlockChain::new();
+		for header in &ancestors {
+			bc.insert(create_test_block(header));
+		}
+
+		let uncles: Vec<_> = uncle_depths.iter()
+			.map(|depth| child_header(engine, &ancestors[ancestors.len() - 1 - depth], 1))
+			.collect();
+		let header = child_header(engine, ancestors.last().unwrap(), 0);
+		family_test(&create_test_block_with_data(&header, &[], &uncles), engine, &bc)
+	}
+
 	#[test]
 	fn test_verify_block_basic_with_invalid_transactions() {
 		let spec = Spec::new_test();
@@ -792,6 +828,29 @@ mod tests {
 		// TODO: some additional uncle checks
 	}
 
+	#[test]
+	fn uncle_policy_transitions() {
+		let spec = ::ethereum::new_uncle_policy_test();
+		let engine = &*spec.engine;
+
+		// two uncles of up to six generations are allowed before block 10.
+		check_ok(uncles_test(engine, 8, &[1, 6]));
+		check_fail(uncles_test(engine, 8, &[1, 7]),
+			UncleTooOld(OutOfBounds { min: Some(1), max: Some(7), found: 1 }));
+
+		// one uncle of up to two generations from block 10 on.
+		check_ok(uncles_test(engine, 18, &[2]));
+		check_fail(uncles_test(engine, 18, &[1, 2]),
+			TooManyUncles(OutOfBounds { min: None, max: Some(1), found: 2 }));
+		check_fail(uncles_test(engine, 18, &[3]),
+			UncleTooOld(OutOfBounds { min: Some(15), max: Some(17), found: 15 }));
+
+		// and none from block 20 on.
+		check_ok(uncles_test(engine, 28, &[]));
+		check_fail(uncles_test(engine, 28, &[1]),
+			TooManyUncles(OutOfBounds { min: None, max: Some(0), found: 1 }));
+	}
+
 	#[test]
 	fn dust_protection() {
 		use ethkey::{Generator, Random};
@@ -46,7 +46,7 @@ use chain::sync_packet::SyncPacket::{PrivateTransactionPacket, SignedPrivateTran
 use light::client::AsLightClient;
 use light::Provider;
 use light::net::{
-	self as light_net, LightProtocol, Params as LightParams,
+	self as light_net, LightProtocol, Params as LightParams, Config as LightConfig,
 	Capabilities, Handler as LightHandler, EventContext, SampleStore,
 };
 use parity_runtime::Executor;
@@ -118,6 +118,14 @@ pub struct SyncConfig {
 	pub warp_sync: WarpSync,
 	/// Enable light client server.
 	pub serve_light: bool,
+	/// Maximum share of time spent on serving light clients.
+	pub light_serve_load: f64,
+	/// How many seconds worth of request credits light clients may accumulate.
+	pub light_serve_credit_seconds: u64,
+	/// Limits on the rate of requests served to light clients.
+	pub light_serve_limits: light_net::ServeLimits,
+	/// Request credits asked of light servers, their own parameters are accepted if `None`.
+	pub light_credits_request: Option<light_net::request_credits::CreditsRequest>,
 }
 
 impl Default for SyncConfig {
@@ -131,6 +139,10 @@ impl Default for SyncConfig {
 			fork_block: None,
 			warp_sync: WarpSync::Disabled,
 			serve_light: false,
+			light_serve_load: light_net::MAX_LIGHTSERV_LOAD,
+			light_serve_credit_seconds: LightConfig::default().max_stored_seconds,
+			light_serve_limits: Default::default(),
+			light_credits_request: None,
 		}
 	}
 }
@@ -207,15 +219,21 @@ pub struct PipProtocolInfo {
 	pub head: H256,
 	/// Peer total difficulty if known
 	pub difficulty: U256,
+	/// Request credits of the peer.
+	pub credits: Option<light_net::PeerCredits>,
+	/// Requests served to the peer.
+	pub served: Option<light_net::ServedRequests>,
 }
 
-impl From<light_net::Status> for PipProtocolInfo {
-	fn from(status: light_net::Status) -> Self {
-		PipProtocolInfo {
+impl PipProtocolInfo {
+	fn from_peer(proto: &LightProtocol, peer_id: PeerId) -> Option<Self> {
+		proto.peer_status(peer_id).map(|status| PipProtocolInfo {
 			version: status.protocol_version,
 			head: status.head_hash,
 			difficulty: status.head_td,
-		}
+			credits: proto.peer_credits(peer_id),
+			served: proto.served_requests(peer_id),
+		})
 	}
 }
 
@@ -316,14 +334,20 @@ pub struct EthSync {
 }
 
 fn light_params(
-	network_id: u64,
+	config: &SyncConfig,
 	median_peers: f64,
 	pruning_info: PruningInfo,
 	sample_store: Option<Box<SampleStore>>,
 ) -> LightParams {
 	let mut light_params = LightParams {
-		network_id: network_id,
-		config: Default::default(),
+		network_id: config.network_id,
+		config: LightConfig {
+			max_stored_seconds: config.light_serve_credit_seconds,
+			max_load: config.light_serve_load,
+			serve_limits: config.light_serve_limits.clone(),
+			credits_request: config.light_credits_request.clone(),
+			..Default::default()
+		},
 		capabilities: Capabilities {
 			serve_headers: true,
 			serve_chain_since: Some(pruning_info.earliest_chain),
@@ -352,7 +376,7 @@ impl EthSync {
 
 				let median_peers = (params.network_config.min_peers + params.network_config.max_peers) as f64 / 2.0;
 				let light_params = light_params(
-					params.config.network_id,
+					&params.config,
 					median_peers,
 					pruning_info,
 					sample_store,
@@ -449,7 +473,7 @@ impl SyncProvider for EthSync {
 					remote_address: session_info.remote_address,
 					local_address: session_info.local_address,
 					eth_info: peer_info,
-					pip_info: light_proto.as_ref().and_then(|lp| lp.peer_status(peer_id)).map(Into::into),
+					pip_info: light_proto.as_ref().and_then(|lp| PipProtocolInfo::from_peer(lp, peer_id)),
 				})
 			}).collect()
 		}).unwrap_or_else(Vec::new)
@@ -888,6 +912,8 @@ pub struct LightSyncParams<L> {
 	pub handlers: Vec<Arc<LightHandler>>,
 	/// Other subprotocols to run.
 	pub attached_protos: Vec<AttachedProtocol>,
+	/// Request credits asked of light servers, their own parameters are accepted if `None`.
+	pub credits_request: Option<light_net::request_credits::CreditsRequest>,
 }
 
 /// Service for light synchronization.
@@ -911,7 +937,10 @@ impl LightSync {
 		let (sync, light_proto) = {
 			let light_params = LightParams {
 				network_id: params.network_id,
-				config: Default::default(),
+				config: LightConfig {
+					credits_request: params.credits_request.clone(),
+					..Default::default()
+				},
 				capabilities: Capabilities {
 					serve_headers: false,
 					serve_chain_since: None,
@@ -1044,7 +1073,7 @@ impl LightSyncProvider for LightSync {
 					remote_address: session_info.remote_address,
 					local_address: session_info.local_address,
 					eth_info: None,
-					pip_info: self.proto.peer_status(peer_id).map(Into::into),
+					pip_info: PipProtocolInfo::from_peer(&self.proto, peer_id),
 				})
 			}).collect()
 		}).unwrap_or_else(Vec::new)
@@ -20,7 +20,7 @@ use std::ops::Deref;
 
 use ethereum_types::{H256, H160, Address, U256};
 use ethjson;
-use ethkey::{self, Signature, Secret, Public, recover, public_to_address};
+use ethkey::{self, Signature, Secret, Public, recover, recover_batch, public_to_address};
 use hash::keccak;
 use heapsize::HeapSizeOf;
 use rlp::{self, RlpStream, Rlp, DecoderError, Encodable};
@@ -455,6 +455,33 @@ impl SignedTransaction {
 		}
 	}
 
+	/// Try to verify many transactions at once, recovering the senders in parallel.
+	/// Results are returned in the same order as `transactions`.
+	pub fn new_batch(transactions: Vec<UnverifiedTransaction>) -> Vec<Result<Self, ethkey::Error>> {
+		let signatures: Vec<_> = transactions.iter()
+			.filter(|t| !t.is_unsigned())
+			.map(|t| (t.signature(), t.unsigned.hash(t.chain_id())))
+			.collect();
+		let mut publics = recover_batch(&signatures).into_iter();
+
+		transactions.into_iter().map(|transaction| {
+			if transaction.is_unsigned() {
+				return Ok(SignedTransaction {
+					transaction: transaction,
+					sender: UNSIGNED_SENDER,
+					public: None,
+				});
+			}
+
+			let public = publics.next().expect("a public is recovered for every signed transaction; qed")?;
+			Ok(SignedTransaction {
+				transaction: transaction,
+				sender: public_to_address(&public),
+				public: Some(public),
+			})
+		}).collect()
+	}
+
 	/// Returns transaction sender.
 	pub fn sender(&self) -> Address {
 		self.sender
@@ -644,6 +671,45 @@ mod tests {
 		assert_eq!(t.chain_id(), Some(69));
 	}
 
+	#[test]
+	fn should_verify_batch_in_order() {
+		use ethkey::{Random, Generator};
+
+		let key = Random.generate().unwrap();
+		let tx = |nonce: u64| Transaction {
+			action: Action::Create,
+			nonce: nonce.into(),
+			gas_price: U256::from(3000),
+			gas: U256::from(50_000),
+			value: U256::from(1),
+			data: b"Hello!".to_vec()
+		};
+		let mut corrupted = tx(1).sign(&key.secret(), None).transaction;
+		corrupted.r = U256::zero();
+
+		let batch = vec![
+			tx(0).sign(&key.secret(), Some(69)).transaction,
+			corrupted,
+			tx(2).null_sign(69).transaction,
+			tx(3).sign(&key.secret(), None).transaction,
+		];
+		let results = SignedTransaction::new_batch(batch.clone());
+
+		assert_eq!(results.len(), 4);
+		for (result, unverified) in results.iter().zip(batch) {
+			let expected = if unverified.is_unsigned() {
+				Some(UNSIGNED_SENDER)
+			} else {
+				SignedTransaction::new(unverified).ok().map(|t| t.sender())
+			};
+			assert_eq!(result.as_ref().ok().map(|t| t.sender()), expected);
+		}
+		assert_eq!(results[0].as_ref().unwrap().sender(), Address::from(keccak(key.public())));
+		assert!(results[1].is_err());
+		assert_eq!(results[2].as_ref().unwrap().sender(), UNSIGNED_SENDER);
+		assert_eq!(results[3].as_ref().unwrap().nonce, 3.into());
+	}
+
 	#[test]
 	fn should_agree_with_vitalik() {
 		use rustc_hex::FromHex;
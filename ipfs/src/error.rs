@@ -71,9 +71,11 @@ pub enum Error {
 	UnsupportedHash,
 	UnsupportedCid,
 	BlockNotFound,
+	ReceiptsNotFound,
 	TransactionNotFound,
 	StateRootNotFound,
 	ContractNotFound,
+	ContentMismatch,
 }
 
 /// Convert Error into Out, handy when switching from Rust's Result-based
@@ -87,9 +89,11 @@ impl From<Error> for Out {
 			UnsupportedCid => Out::Bad("CID codec not supported"),
 			CidParsingFailed => Out::Bad("CID parsing failed"),
 			BlockNotFound => Out::NotFound("Block not found"),
+			ReceiptsNotFound => Out::NotFound("Receipts not found, block might be pruned"),
 			TransactionNotFound => Out::NotFound("Transaction not found"),
 			StateRootNotFound => Out::NotFound("State root not found"),
 			ContractNotFound => Out::NotFound("Contract not found"),
+			ContentMismatch => Out::NotFound("Stored content doesn't match the CID"),
 		}
 	}
 }
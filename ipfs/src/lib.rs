@@ -19,6 +19,7 @@ extern crate cid;
 extern crate unicase;
 
 extern crate rlp;
+extern crate triehash_ethereum as triehash;
 extern crate ethcore;
 extern crate parity_bytes as bytes;
 extern crate ethereum_types;
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use {rlp, multihash, IpfsHandler};
+use {rlp, multihash, triehash, IpfsHandler};
 use error::{Error, Result};
 use cid::{ToCid, Codec};
 
@@ -62,6 +62,7 @@ impl IpfsHandler {
 			Codec::EthereumBlock => self.block(hash),
 			Codec::EthereumBlockList => self.block_list(hash),
 			Codec::EthereumTx => self.transaction(hash),
+			Codec::EthereumTxReceipt => self.block_receipts(hash),
 			Codec::EthereumStateTrie => self.state_trie(hash),
 			Codec::Raw => self.contract_code(hash),
 			_ => return Err(Error::UnsupportedCid),
@@ -73,9 +74,25 @@ impl IpfsHandler {
 		let block_id = BlockId::Hash(hash);
 		let block = self.client().block_header(block_id).ok_or(Error::BlockNotFound)?;
 
+		if block.hash() != hash { return Err(Error::ContentMismatch); }
+
 		Ok(Out::OctetStream(block.into_inner()))
 	}
 
+	/// Get receipts of a block by block hash as raw binary.
+	/// Receipts are checked against the receipts root of the block header.
+	fn block_receipts(&self, hash: H256) -> Result<Out> {
+		let header = self.client().block_header(BlockId::Hash(hash)).ok_or(Error::BlockNotFound)?;
+		let receipts = self.client().block_receipts(&hash).ok_or(Error::ReceiptsNotFound)?.receipts;
+
+		let receipts_root = triehash::ordered_trie_root(receipts.iter().map(|r| rlp::encode(r)));
+		if header.hash() != hash || header.receipts_root() != receipts_root {
+			return Err(Error::ContentMismatch);
+		}
+
+		Ok(Out::OctetStream(rlp::encode_list(&receipts)))
+	}
+
 	/// Get list of block ommers by hash as raw binary.
 	fn block_list(&self, hash: H256) -> Result<Out> {
 		let uncles = self.client().find_uncles(&hash).ok_or(Error::BlockNotFound)?;
@@ -117,12 +134,21 @@ fn get_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
 mod tests {
 	use std::sync::Arc;
 	use super::*;
-	use ethcore::client::TestBlockChainClient;
+	use cid::{Cid, Version};
+	use ethcore::client::{TestBlockChainClient, EachBlockWith, BlockInfo, BlockChainClient};
+	use ethcore::test_helpers::generate_dummy_client_with_data;
 
 	fn get_mocked_handler() -> IpfsHandler {
 		IpfsHandler::new(None.into(), None.into(), Arc::new(TestBlockChainClient::new()))
 	}
 
+	/// Content ID of a Keccak-256 hash with given codec.
+	fn cid(codec: Codec, hash: H256) -> String {
+		let mut mh = vec![0x1b, 0x20];
+		mh.extend_from_slice(&*hash);
+		Cid::new(codec, Version::V1, &mh).to_string()
+	}
+
 	#[test]
 	fn test_get_param() {
 		let query = "foo=100&bar=200&qux=300";
@@ -148,6 +174,14 @@ mod tests {
 		assert_eq!(Err(Error::BlockNotFound), handler.route_cid(cid));
 	}
 
+	#[test]
+	fn cid_of_hash() {
+		let hash = H256::from("c3b5cb6e409f13267b2a15fdf3a78b891b4e7bf876a3574383cdd0cb98d31a54");
+
+		assert_eq!(cid(Codec::EthereumBlock, hash), "z43AaGF5tmkT9SEX6urrhwpEW5ZSaACY73Vw357ZXTsur2fR8BM");
+		assert_eq!(cid(Codec::EthereumTxReceipt, hash), "z45NHvZwmopPYvaSEjeCBzDZxVUhfCanh1jNzBB4QfGaM8oegrw");
+	}
+
 	#[test]
 	fn cid_route_block_list() {
 		let handler = get_mocked_handler();
@@ -168,6 +202,16 @@ mod tests {
 		assert_eq!(Err(Error::TransactionNotFound), handler.route_cid(cid));
 	}
 
+	#[test]
+	fn cid_route_receipts() {
+		let handler = get_mocked_handler();
+
+		// `eth-tx-receipt` with Keccak-256
+		let cid = "z45NHvZwmopPYvaSEjeCBzDZxVUhfCanh1jNzBB4QfGaM8oegrw";
+
+		assert_eq!(Err(Error::BlockNotFound), handler.route_cid(cid));
+	}
+
 	#[test]
 	fn cid_route_state_trie() {
 		let handler = get_mocked_handler();
@@ -217,6 +261,40 @@ mod tests {
 		assert_eq!(out, Out::NotFound("Block not found"));
 	}
 
+	#[test]
+	fn route_header_and_receipts() {
+		let client = generate_dummy_client_with_data(2, 2, &[1.into(), 1.into()]);
+		let handler = IpfsHandler::new(None.into(), None.into(), client.clone());
+
+		let header = client.block_header(BlockId::Number(2)).unwrap();
+		let hash = header.hash();
+		let receipts = client.block_receipts(&hash).unwrap().receipts;
+		assert_eq!(receipts.len(), 2);
+
+		let out = handler.route("/api/v0/block/get", Some(&format!("arg={}", cid(Codec::EthereumBlock, hash))));
+		assert_eq!(out, Out::OctetStream(header.into_inner()));
+
+		let out = handler.route("/api/v0/block/get", Some(&format!("arg={}", cid(Codec::EthereumTxReceipt, hash))));
+		assert_eq!(out, Out::OctetStream(rlp::encode_list(&receipts)));
+	}
+
+	#[test]
+	fn route_pruned_receipts() {
+		let client = TestBlockChainClient::new();
+		client.add_blocks(4, EachBlockWith::Nothing);
+
+		// the test client only has receipts of blocks with hashes starting with 'f'.
+		let hash = (0..5)
+			.filter_map(|n| client.block_hash(BlockId::Number(n)))
+			.find(|hash| *hash < H256::from("f000000000000000000000000000000000000000000000000000000000000000"))
+			.unwrap();
+		let handler = IpfsHandler::new(None.into(), None.into(), Arc::new(client));
+
+		let out = handler.route("/api/v0/block/get", Some(&format!("arg={}", cid(Codec::EthereumTxReceipt, hash))));
+
+		assert_eq!(out, Out::NotFound("Receipts not found, block might be pruned"));
+	}
+
 	#[test]
 	fn route_block_missing_query() {
 		let handler = get_mocked_handler();
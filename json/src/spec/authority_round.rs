@@ -16,18 +16,30 @@
 
 //! Authority params deserialization.
 
+use std::collections::BTreeMap;
 use hash::Address;
 use uint::Uint;
 use bytes::Bytes;
 use super::ValidatorSet;
 
+/// Step duration, either constant or changing at the given timestamps.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(untagged)]
+pub enum StepDuration {
+	/// Duration of all steps, in seconds.
+	Single(Uint),
+	/// Durations of steps, in seconds, from the given timestamps on.
+	Transitions(BTreeMap<Uint, Uint>),
+}
+
 /// Authority params deserialization.
 #[derive(Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "camelCase")]
 pub struct AuthorityRoundParams {
 	/// Block duration, in seconds.
-	pub step_duration: Uint,
+	pub step_duration: StepDuration,
 	/// Valid authorities
 	pub validators: ValidatorSet,
 	/// Starting step. Determined automatically if not specified.
@@ -56,8 +68,14 @@ pub struct AuthorityRoundParams {
 	pub empty_steps_transition: Option<Uint>,
 	/// Maximum number of accepted empty steps.
 	pub maximum_empty_steps: Option<Uint>,
+	/// Number of empty steps collected before sealing a block without transactions.
+	pub minimum_empty_steps_to_seal: Option<Uint>,
 	/// Strict validation of empty steps transition block.
 	pub strict_empty_steps_transition: Option<Uint>,
+	/// Block at which the randomness contract should start being used.
+	pub randomness_contract_transition: Option<Uint>,
+	/// Address of the commit-reveal randomness contract.
+	pub randomness_contract_address: Option<Address>,
 }
 
 /// Authority engine deserialization.
@@ -75,7 +93,7 @@ mod tests {
 	use serde_json;
 	use hash::Address;
 	use spec::validator_set::ValidatorSet;
-	use spec::authority_round::AuthorityRound;
+	use spec::authority_round::{AuthorityRound, StepDuration};
 
 	#[test]
 	fn authority_round_deserialization() {
@@ -89,17 +107,46 @@ mod tests {
 				"validateStepTransition": 150,
 				"blockReward": 5000000,
 				"maximumUncleCountTransition": 10000000,
-				"maximumUncleCount": 5
+				"maximumUncleCount": 5,
+				"maximumEmptySteps": 10,
+				"minimumEmptyStepsToSeal": 4,
+				"randomnessContractTransition": 50,
+				"randomnessContractAddress": "0x2000000000000000000000000000000000000001"
 			}
 		}"#;
 
 		let deserialized: AuthorityRound = serde_json::from_str(s).unwrap();
-		assert_eq!(deserialized.params.step_duration, Uint(U256::from(0x02)));
+		assert_eq!(deserialized.params.step_duration, StepDuration::Single(Uint(U256::from(0x02))));
 		assert_eq!(deserialized.params.validators, ValidatorSet::List(vec![Address(H160::from("0xc6d9d2cd449a754c494264e1809c50e34d64562b"))]));
 		assert_eq!(deserialized.params.start_step, Some(Uint(U256::from(24))));
 		assert_eq!(deserialized.params.immediate_transitions, None);
 		assert_eq!(deserialized.params.maximum_uncle_count_transition, Some(Uint(10_000_000.into())));
 		assert_eq!(deserialized.params.maximum_uncle_count, Some(Uint(5.into())));
+		assert_eq!(deserialized.params.maximum_empty_steps, Some(Uint(10.into())));
+		assert_eq!(deserialized.params.minimum_empty_steps_to_seal, Some(Uint(4.into())));
+		assert_eq!(deserialized.params.randomness_contract_transition, Some(Uint(50.into())));
+		assert_eq!(deserialized.params.randomness_contract_address, Some(Address(H160::from("0x2000000000000000000000000000000000000001"))));
+
+	}
+
+	#[test]
+	fn authority_round_step_duration_transitions_deserialization() {
+		let s = r#"{
+			"params": {
+				"stepDuration": {
+					"0": 5,
+					"1560000000": 15
+				},
+				"validators": {
+					"list" : ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+				}
+			}
+		}"#;
 
+		let deserialized: AuthorityRound = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.step_duration, StepDuration::Transitions(vec![
+			(Uint(0.into()), Uint(5.into())),
+			(Uint(1_560_000_000.into()), Uint(15.into())),
+		].into_iter().collect()));
 	}
 }
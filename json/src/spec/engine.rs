@@ -16,7 +16,7 @@
 
 //! Engine deserialization.
 
-use super::{Ethash, BasicAuthority, AuthorityRound, NullEngine, InstantSeal, Clique};
+use super::{Ethash, BasicAuthority, AuthorityRound, NullEngine, InstantSeal, Clique, Hybrid};
 
 /// Engine deserialization.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -35,7 +35,9 @@ pub enum Engine {
 	/// AuthorityRound engine.
 	AuthorityRound(AuthorityRound),
 	/// Clique engine.
-	Clique(Clique)
+	Clique(Clique),
+	/// Engine switching to another one at a block number.
+	Hybrid(Hybrid),
 }
 
 #[cfg(test)]
@@ -146,5 +148,21 @@ mod tests {
 			Engine::Clique(_) => {}, // Clique is unit tested in its own file.
 			_ => panic!(),
 		};
+
+		let s = r#"{
+			"hybrid": {
+				"params": {
+					"engines": {
+						"0": { "instantSeal": null },
+						"10": { "null": { "params": {} } }
+					}
+				}
+			}
+		}"#;
+		let deserialized: Engine = serde_json::from_str(s).unwrap();
+		match deserialized {
+			Engine::Hybrid(_) => {}, // Hybrid is unit tested in its own file.
+			_ => panic!(),
+		};
 	}
 }
@@ -17,6 +17,9 @@
 //! Ethash params deserialization.
 
 use std::collections::BTreeMap;
+use ethereum_types::U256;
+use serde::{Deserialize, Deserializer};
+use serde::de::{Error, Unexpected};
 use uint::{self, Uint};
 use bytes::Bytes;
 use hash::Address;
@@ -30,6 +33,43 @@ pub enum BlockReward {
 	Multi(BTreeMap<Uint, Uint>),
 }
 
+/// Additional beneficiary of block rewards, receiving a share of the reward of every block.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RewardBeneficiary {
+	/// Beneficiary address.
+	pub address: Address,
+	/// Share of the block reward in percent.
+	pub share: Uint,
+}
+
+/// Validate that the beneficiaries don't receive more than the whole block reward.
+pub fn validate_reward_shares<'de, D>(d: D) -> Result<Option<Vec<RewardBeneficiary>>, D::Error> where D: Deserializer<'de> {
+	let value: Option<Vec<RewardBeneficiary>> = Option::deserialize(d)?;
+
+	if let Some(ref beneficiaries) = value {
+		let total = beneficiaries.iter().fold(U256::zero(), |total, b| total.saturating_add(b.share.0));
+		if total > U256::from(100) {
+			return Err(Error::invalid_value(Unexpected::Other("shares exceeding 100%"), &"shares of at most 100% in total"))
+		}
+	}
+
+	Ok(value)
+}
+
+/// Validate that uncles aren't allowed to be older than the protocol allows, i.e. six generations.
+pub fn validate_max_uncle_age<'de, D>(d: D) -> Result<Option<BTreeMap<Uint, Uint>>, D::Error> where D: Deserializer<'de> {
+	let value: Option<BTreeMap<Uint, Uint>> = Option::deserialize(d)?;
+
+	if let Some(ref ages) = value {
+		if ages.values().any(|age| age.0 > U256::from(6)) {
+			return Err(Error::invalid_value(Unexpected::Other("uncle age exceeding 6"), &"uncle age of at most 6 generations"))
+		}
+	}
+
+	Ok(value)
+}
+
 /// Deserializable doppelganger of EthashParams.
 #[derive(Clone, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -61,6 +101,9 @@ pub struct EthashParams {
 	pub block_reward_contract_address: Option<Address>,
 	/// Block reward code. This overrides the block reward contract address.
 	pub block_reward_contract_code: Option<Bytes>,
+	/// Additional beneficiaries of the block reward, paid from the reward of the author.
+	#[serde(default, deserialize_with="validate_reward_shares")]
+	pub block_reward_beneficiaries: Option<Vec<RewardBeneficiary>>,
 
 	/// See main EthashParams docs.
 	pub dao_hardfork_transition: Option<Uint>,
@@ -91,6 +134,13 @@ pub struct EthashParams {
 	/// Delays of difficulty bombs.
 	pub difficulty_bomb_delays: Option<BTreeMap<Uint, Uint>>,
 
+	/// Maximum number of uncles per block, from the given blocks on.
+	pub max_uncle_count: Option<BTreeMap<Uint, Uint>>,
+	/// Maximum number of generations an uncle may be behind the block including it,
+	/// from the given blocks on.
+	#[serde(default, deserialize_with="validate_max_uncle_age")]
+	pub max_uncle_age: Option<BTreeMap<Uint, Uint>>,
+
 	/// EXPIP-2 block height
 	pub expip2_transition: Option<Uint>,
 	/// EXPIP-2 duration limit
@@ -114,7 +164,7 @@ mod tests {
 	use uint::Uint;
 	use ethereum_types::{H160, U256};
 	use hash::Address;
-	use spec::ethash::{Ethash, EthashParams, BlockReward};
+	use spec::ethash::{Ethash, EthashParams, BlockReward, RewardBeneficiary};
 
 	#[test]
 	fn ethash_deserialization() {
@@ -170,6 +220,7 @@ mod tests {
 				block_reward_contract_address: None,
 				block_reward_contract_code: None,
 				block_reward_contract_transition: None,
+				block_reward_beneficiaries: None,
 				dao_hardfork_transition: Some(Uint(U256::from(0x08))),
 				dao_hardfork_beneficiary: Some(Address(H160::from("0xabcabcabcabcabcabcabcabcabcabcabcabcabca"))),
 				dao_hardfork_accounts: Some(vec![
@@ -205,6 +256,8 @@ mod tests {
 				expip2_duration_limit: None,
 				progpow_transition: None,
 				difficulty_bomb_delays: None,
+				max_uncle_count: None,
+				max_uncle_age: None,
 			}
 		});
 	}
@@ -231,6 +284,7 @@ mod tests {
 				block_reward_contract_address: None,
 				block_reward_contract_code: None,
 				block_reward_contract_transition: None,
+				block_reward_beneficiaries: None,
 				dao_hardfork_transition: None,
 				dao_hardfork_beneficiary: None,
 				dao_hardfork_accounts: None,
@@ -245,6 +299,8 @@ mod tests {
 				expip2_duration_limit: None,
 				progpow_transition: None,
 				difficulty_bomb_delays: None,
+				max_uncle_count: None,
+				max_uncle_age: None,
 			}
 		});
 	}
@@ -261,4 +317,100 @@ mod tests {
 
 		let _deserialized: Ethash = serde_json::from_str(s).unwrap();
 	}
+
+	#[test]
+	fn ethash_deserialization_reward_schedule() {
+		let s = r#"{
+			"params": {
+				"difficultyBoundDivisor": "0x0800",
+				"minimumDifficulty": "0x020000",
+				"blockReward": {
+					"0": "0x4563918244f40000",
+					"10": "0x29a2241af62c0000",
+					"20": "0x1bc16d674ec80000"
+				},
+				"blockRewardBeneficiaries": [
+					{ "address": "0xabcabcabcabcabcabcabcabcabcabcabcabcabca", "share": 10 },
+					{ "address": "0xdefdefdefdefdefdefdefdefdefdefdefdefdefd", "share": 5 }
+				]
+			}
+		}"#;
+
+		let deserialized: Ethash = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.block_reward, Some(BlockReward::Multi(vec![
+			(Uint(U256::from(0)), Uint(U256::from(0x4563918244f40000u64))),
+			(Uint(U256::from(10)), Uint(U256::from(0x29a2241af62c0000u64))),
+			(Uint(U256::from(20)), Uint(U256::from(0x1bc16d674ec80000u64))),
+		].into_iter().collect())));
+		assert_eq!(deserialized.params.block_reward_beneficiaries, Some(vec![
+			RewardBeneficiary {
+				address: Address(H160::from("0xabcabcabcabcabcabcabcabcabcabcabcabcabca")),
+				share: Uint(U256::from(10)),
+			},
+			RewardBeneficiary {
+				address: Address(H160::from("0xdefdefdefdefdefdefdefdefdefdefdefdefdefd")),
+				share: Uint(U256::from(5)),
+			},
+		]));
+	}
+
+	#[test]
+	#[should_panic(expected = "shares of at most 100% in total")]
+	fn test_reward_shares_exceeding_block_reward() {
+		let s = r#"{
+			"params": {
+				"difficultyBoundDivisor": "0x0800",
+				"minimumDifficulty": "0x020000",
+				"blockRewardBeneficiaries": [
+					{ "address": "0xabcabcabcabcabcabcabcabcabcabcabcabcabca", "share": 60 },
+					{ "address": "0xdefdefdefdefdefdefdefdefdefdefdefdefdefd", "share": 41 }
+				]
+			}
+		}"#;
+
+		let _deserialized: Ethash = serde_json::from_str(s).unwrap();
+	}
+
+	#[test]
+	fn ethash_deserialization_uncle_policy() {
+		let s = r#"{
+			"params": {
+				"difficultyBoundDivisor": "0x0800",
+				"minimumDifficulty": "0x020000",
+				"maxUncleCount": {
+					"0": 2,
+					"100": 0
+				},
+				"maxUncleAge": {
+					"50": 3
+				}
+			}
+		}"#;
+
+		let deserialized: Ethash = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.max_uncle_count, Some(vec![
+			(Uint(U256::from(0)), Uint(U256::from(2))),
+			(Uint(U256::from(100)), Uint(U256::from(0))),
+		].into_iter().collect()));
+		assert_eq!(deserialized.params.max_uncle_age, Some(vec![
+			(Uint(U256::from(50)), Uint(U256::from(3))),
+		].into_iter().collect()));
+	}
+
+	#[test]
+	#[should_panic(expected = "uncle age of at most 6 generations")]
+	fn test_uncle_age_exceeding_protocol_limit() {
+		let s = r#"{
+			"params": {
+				"difficultyBoundDivisor": "0x0800",
+				"minimumDifficulty": "0x020000",
+				"maxUncleAge": {
+					"0": 6,
+					"10": 7
+				}
+			}
+		}"#;
+
+		let _deserialized: Ethash = serde_json::from_str(s).unwrap();
+	}
 }
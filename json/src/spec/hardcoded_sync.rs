@@ -16,6 +16,9 @@
 
 //! Spec hardcoded synchronization deserialization for the light client.
 
+use std::io::Read;
+use serde_json;
+use serde_json::Error;
 use hash::H256;
 use uint::Uint;
 
@@ -26,6 +29,8 @@ use uint::Uint;
 pub struct HardcodedSync {
 	/// Hexadecimal of the RLP encoding of the header of the block to start synchronization from.
 	pub header: String,
+	/// Hash of `header`, checked against it if given.
+	pub hash: Option<H256>,
 	/// Total difficulty including the block of `header`.
 	pub total_difficulty: Uint,
 	/// Ordered trie roots of blocks before and including `header`.
@@ -33,6 +38,13 @@ pub struct HardcodedSync {
 	pub chts: Vec<H256>,
 }
 
+impl HardcodedSync {
+	/// Loads hardcoded sync data from json, e.g. a checkpoint newer than the one in the spec.
+	pub fn load<R>(reader: R) -> Result<Self, Error> where R: Read {
+		serde_json::from_reader(reader)
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
@@ -54,6 +66,7 @@ mod tests {
 		let deserialized: HardcodedSync = serde_json::from_str(s).unwrap();
 		assert_eq!(deserialized, HardcodedSync {
 			header: String::from("f901f9a0d405da4e66f1445d455195229624e133f5baafe72b5cf7b3c36c12c8146e98b7a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347948888f1f195afa192cfee860698584c030f4c9db1a05fb2b4bfdef7b314451cb138a534d225c922fc0e5fbe25e451142732c3e25c25a088d2ec6b9860aae1a2c3b299f72b6a5d70d7f7ba4722c78f2c49ba96273c2158a007c6fdfa8eea7e86b81f5b0fc0f78f90cc19f4aa60d323151e0cac660199e9a1b90100000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000008302008003832fefba82524d84568e932a80a0a0349d8c3df71f1a48a9df7d03fd5f14aeee7d91332c009ecaff0a71ead405bd88ab4e252a7e8c2a23"),
+			hash: None,
 			total_difficulty: Uint(U256::from(0x400000000u64)),
 			chts: vec![
 				H256(Eth256::from("0x11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa")),
@@ -0,0 +1,123 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Hybrid engine params deserialization.
+
+use std::collections::BTreeMap;
+use serde::{Deserialize, Deserializer};
+use serde::de::{Error, Unexpected};
+use uint::Uint;
+use super::Engine;
+
+/// Validate that the engines define exactly one transition, from the engine used since genesis
+/// to another one which isn't hybrid itself.
+pub fn validate_transition<'de, D>(d: D) -> Result<BTreeMap<Uint, Engine>, D::Error> where D: Deserializer<'de> {
+	let value: BTreeMap<Uint, Engine> = BTreeMap::deserialize(d)?;
+
+	if value.len() != 2 {
+		return Err(Error::invalid_length(value.len(), &"an engine from genesis and one from the transition block"))
+	}
+	if value.keys().next().map_or(true, |number| !number.0.is_zero()) {
+		return Err(Error::invalid_value(Unexpected::Other("no engine from genesis"), &"an engine from block 0"))
+	}
+	if value.values().any(|engine| match *engine { Engine::Hybrid(_) => true, _ => false }) {
+		return Err(Error::invalid_value(Unexpected::Other("nested hybrid engine"), &"a non-hybrid engine"))
+	}
+
+	Ok(value)
+}
+
+/// Hybrid engine params deserialization.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct HybridParams {
+	/// Engines by the block number from which they are used.
+	#[serde(deserialize_with="validate_transition")]
+	pub engines: BTreeMap<Uint, Engine>,
+}
+
+/// Hybrid engine descriptor.
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hybrid {
+	/// Hybrid engine params.
+	pub params: HybridParams,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use uint::Uint;
+	use ethereum_types::U256;
+	use spec::Engine;
+	use super::Hybrid;
+
+	#[test]
+	fn hybrid_deserialization() {
+		let s = r#"{
+			"params": {
+				"engines": {
+					"0": { "instantSeal": null },
+					"0x0a": {
+						"basicAuthority": {
+							"params": {
+								"durationLimit": "0x0d",
+								"validators": {
+									"list": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+								}
+							}
+						}
+					}
+				}
+			}
+		}"#;
+
+		let deserialized: Hybrid = serde_json::from_str(s).unwrap();
+		let engines: Vec<_> = deserialized.params.engines.iter().collect();
+		assert_eq!(engines[0], (&Uint(U256::from(0)), &Engine::InstantSeal(None)));
+		assert_eq!(*engines[1].0, Uint(U256::from(10)));
+		match *engines[1].1 {
+			Engine::BasicAuthority(_) => {},
+			_ => panic!(),
+		}
+	}
+
+	#[test]
+	fn hybrid_requires_exactly_one_transition() {
+		let none = r#"{ "params": { "engines": { "0": { "instantSeal": null } } } }"#;
+		let two = r#"{ "params": { "engines": { "0": { "instantSeal": null }, "5": { "instantSeal": null }, "10": { "instantSeal": null } } } }"#;
+		let late = r#"{ "params": { "engines": { "5": { "instantSeal": null }, "10": { "instantSeal": null } } } }"#;
+
+		assert!(serde_json::from_str::<Hybrid>(none).is_err());
+		assert!(serde_json::from_str::<Hybrid>(two).is_err());
+		assert!(serde_json::from_str::<Hybrid>(late).is_err());
+	}
+
+	#[test]
+	fn hybrid_cannot_be_nested() {
+		let s = r#"{
+			"params": {
+				"engines": {
+					"0": { "instantSeal": null },
+					"10": { "hybrid": { "params": { "engines": { "0": { "instantSeal": null }, "20": { "instantSeal": null } } } } }
+				}
+			}
+		}"#;
+
+		assert!(serde_json::from_str::<Hybrid>(s).is_err());
+	}
+}
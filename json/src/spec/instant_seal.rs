@@ -24,6 +24,9 @@ pub struct InstantSealParams {
 	/// Whether to enable millisecond timestamp.
 	#[serde(default)]
 	pub millisecond_timestamp: bool,
+	/// Whether blocks are authored by the sender of their first transaction.
+	#[serde(default)]
+	pub author_from_first_sender: bool,
 }
 
 /// Instant seal engine descriptor.
@@ -32,6 +32,7 @@ pub mod null_engine;
 pub mod instant_seal;
 pub mod hardcoded_sync;
 pub mod clique;
+pub mod hybrid;
 
 pub use self::account::Account;
 pub use self::builtin::{Builtin, Pricing, Linear};
@@ -41,11 +42,12 @@ pub use self::spec::{Spec, ForkSpec};
 pub use self::seal::{Seal, Ethereum, AuthorityRoundSeal, TendermintSeal};
 pub use self::engine::Engine;
 pub use self::state::State;
-pub use self::ethash::{Ethash, EthashParams, BlockReward};
+pub use self::ethash::{Ethash, EthashParams, BlockReward, RewardBeneficiary};
 pub use self::validator_set::ValidatorSet;
 pub use self::basic_authority::{BasicAuthority, BasicAuthorityParams};
-pub use self::authority_round::{AuthorityRound, AuthorityRoundParams};
+pub use self::authority_round::{AuthorityRound, AuthorityRoundParams, StepDuration};
 pub use self::clique::{Clique, CliqueParams};
+pub use self::hybrid::{Hybrid, HybridParams};
 pub use self::null_engine::{NullEngine, NullEngineParams};
 pub use self::instant_seal::{InstantSeal, InstantSealParams};
 pub use self::hardcoded_sync::HardcodedSync;
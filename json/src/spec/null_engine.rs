@@ -16,7 +16,7 @@
 
 //! Null engine params deserialization.
 
-use uint::Uint;
+use spec::ethash::{self, BlockReward, RewardBeneficiary};
 
 /// Authority params deserialization.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -24,7 +24,10 @@ use uint::Uint;
 #[serde(rename_all = "camelCase")]
 pub struct NullEngineParams {
 	/// Block reward.
-	pub block_reward: Option<Uint>,
+	pub block_reward: Option<BlockReward>,
+	/// Additional beneficiaries of the block reward, paid from the reward of the author.
+	#[serde(default, deserialize_with="ethash::validate_reward_shares")]
+	pub block_reward_beneficiaries: Option<Vec<RewardBeneficiary>>,
 }
 
 /// Null engine descriptor
@@ -51,6 +54,7 @@ mod tests {
 		}"#;
 
 		let deserialized: NullEngine = serde_json::from_str(s).unwrap();
-		assert_eq!(deserialized.params.block_reward, Some(Uint(U256::from(0x0d))));
+		assert_eq!(deserialized.params.block_reward, Some(BlockReward::Single(Uint(U256::from(0x0d)))));
+		assert_eq!(deserialized.params.block_reward_beneficiaries, None);
 	}
 }
@@ -124,6 +124,8 @@ pub struct Params {
 	pub kip4_transition: Option<Uint>,
 	/// KIP6 activiation block height.
 	pub kip6_transition: Option<Uint>,
+	/// Number of blocks in each canonical hash trie served to light clients. Must be a power of two.
+	pub cht_size: Option<Uint>,
 }
 
 #[cfg(test)]
@@ -144,7 +146,8 @@ mod tests {
 			"accountStartNonce": "0x01",
 			"gasLimitBoundDivisor": "0x20",
 			"maxCodeSize": "0x1000",
-			"wasmActivationTransition": "0x1010"
+			"wasmActivationTransition": "0x1010",
+			"chtSize": "0x40"
 		}"#;
 
 		let deserialized: Params = serde_json::from_str(s).unwrap();
@@ -157,6 +160,7 @@ mod tests {
 		assert_eq!(deserialized.gas_limit_bound_divisor, Uint(U256::from(0x20)));
 		assert_eq!(deserialized.max_code_size, Some(Uint(U256::from(0x1000))));
 		assert_eq!(deserialized.wasm_activation_transition, Some(Uint(U256::from(0x1010))));
+		assert_eq!(deserialized.cht_size, Some(Uint(U256::from(0x40))));
 	}
 
 	#[test]
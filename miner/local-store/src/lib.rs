@@ -14,11 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Manages local node data: pending local transactions, sync security level
+//! Manages local node data: pending local transactions, journaled pool and service transactions, sync security level
 
 use std::sync::Arc;
 use std::fmt;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use types::transaction::{
 	SignedTransaction, PendingTransaction, UnverifiedTransaction,
@@ -27,9 +27,11 @@ use types::transaction::{
 use io::IoHandler;
 use rlp::Rlp;
 use kvdb::KeyValueDB;
+use parking_lot::Mutex;
 
 extern crate common_types as types;
 extern crate ethcore_io as io;
+extern crate parking_lot;
 extern crate rlp;
 extern crate serde_json;
 extern crate serde;
@@ -47,9 +49,54 @@ extern crate ethkey;
 extern crate kvdb_memorydb;
 
 const LOCAL_TRANSACTIONS_KEY: &'static [u8] = &*b"LOCAL_TXS";
+const POOL_JOURNAL_KEY: &'static [u8] = &*b"POOL_JOURNAL";
+const SERVICE_TRANSACTIONS_KEY: &'static [u8] = &*b"SERVICE_TXS";
 
 const UPDATE_TIMER: ::io::TimerToken = 0;
-const UPDATE_TIMEOUT: Duration = Duration::from_secs(15 * 60); // once every 15 minutes.
+const UPDATE_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default interval between updates of the store.
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(15 * 60); // once every 15 minutes.
+
+/// Categories of node data which are persisted.
+/// Each category is written under its own key, so a corrupted entry only loses that category.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Categories {
+	/// Pending transactions of local origin.
+	pub local_transactions: bool,
+	/// Other transactions in the pool.
+	pub pool_journal: bool,
+	/// Service transactions of the consensus engine. These are journaled by the client.
+	pub service_transactions: bool,
+}
+
+impl Default for Categories {
+	fn default() -> Self {
+		Categories {
+			local_transactions: true,
+			pool_journal: false,
+			service_transactions: true,
+		}
+	}
+}
+
+/// Local data store configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreConfig {
+	/// Interval between periodic updates of the store.
+	pub interval: Duration,
+	/// Categories of data to persist and restore.
+	pub categories: Categories,
+}
+
+impl Default for StoreConfig {
+	fn default() -> Self {
+		StoreConfig {
+			interval: DEFAULT_UPDATE_INTERVAL,
+			categories: Categories::default(),
+		}
+	}
+}
 
 /// Errors which can occur while using the local data store.
 #[derive(Debug)]
@@ -129,19 +176,88 @@ impl From<PendingTransaction> for TransactionEntry {
 	}
 }
 
+/// A service transaction generated by the consensus engine, such as a misbehaviour report.
+/// It's kept in the store until it's included in a block or becomes useless.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceTransaction {
+	/// What the transaction is for. Only the latest transaction of each purpose is kept.
+	pub purpose: String,
+	/// Last block at which the transaction is still useful.
+	pub valid_until: Option<types::BlockNumber>,
+	/// The latest submitted transaction.
+	pub transaction: SignedTransaction,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ServiceTransactionEntry {
+	purpose: String,
+	valid_until: Option<types::BlockNumber>,
+	rlp_bytes: Vec<u8>,
+}
+
+impl ServiceTransactionEntry {
+	fn into_service_transaction(self) -> Option<ServiceTransaction> {
+		let ServiceTransactionEntry { purpose, valid_until, rlp_bytes } = self;
+		TransactionEntry { rlp_bytes, condition: None }.into_pending().map(|pending| ServiceTransaction {
+			purpose,
+			valid_until,
+			transaction: pending.transaction,
+		})
+	}
+}
+
+impl<'a> From<&'a ServiceTransaction> for ServiceTransactionEntry {
+	fn from(tx: &'a ServiceTransaction) -> Self {
+		ServiceTransactionEntry {
+			purpose: tx.purpose.clone(),
+			valid_until: tx.valid_until,
+			rlp_bytes: ::rlp::encode(&tx.transaction),
+		}
+	}
+}
+
+/// Read the journaled service transactions out of the given database column.
+pub fn service_transactions(db: &KeyValueDB, col: Option<u32>) -> Result<Vec<ServiceTransaction>, Error> {
+	match db.get(col, SERVICE_TRANSACTIONS_KEY).map_err(Error::Io)? {
+		Some(val) => Ok(::serde_json::from_slice::<Vec<ServiceTransactionEntry>>(&val)
+			.map_err(Error::Json)?
+			.into_iter()
+			.filter_map(ServiceTransactionEntry::into_service_transaction)
+			.collect()),
+		None => Ok(Vec::new()),
+	}
+}
+
+/// Replace the journaled service transactions in the given database column.
+pub fn write_service_transactions(db: &KeyValueDB, col: Option<u32>, txs: &[ServiceTransaction]) -> Result<(), Error> {
+	let entries: Vec<ServiceTransactionEntry> = txs.iter().map(Into::into).collect();
+	let json = ::serde_json::to_vec(&entries).map_err(Error::Json)?;
+
+	let mut batch = db.transaction();
+	batch.put_vec(col, SERVICE_TRANSACTIONS_KEY, json);
+	db.write(batch).map_err(Error::Io)
+}
+
 /// Something which can provide information about the local node.
 pub trait NodeInfo: Send + Sync {
 	/// Get all pending transactions of local origin.
 	fn pending_transactions(&self) -> Vec<PendingTransaction>;
+
+	/// Get transactions in the pool which aren't of local origin.
+	fn pool_transactions(&self) -> Vec<PendingTransaction> {
+		Vec::new()
+	}
 }
 
-/// Create a new local data store, given a database, a column to write to, and a node.
+/// Create a new local data store, given a database, a column to write to, a node and the configuration.
 /// Attempts to read data out of the store, and move it into the node.
-pub fn create<T: NodeInfo>(db: Arc<KeyValueDB>, col: Option<u32>, node: T) -> LocalDataStore<T> {
+pub fn create<T: NodeInfo>(db: Arc<KeyValueDB>, col: Option<u32>, node: T, config: StoreConfig) -> LocalDataStore<T> {
 	LocalDataStore {
 		db: db,
 		col: col,
 		node: node,
+		config: config,
+		next_update: Mutex::new(None),
 	}
 }
 
@@ -153,65 +269,107 @@ pub struct LocalDataStore<T: NodeInfo> {
 	db: Arc<KeyValueDB>,
 	col: Option<u32>,
 	node: T,
+	config: StoreConfig,
+	next_update: Mutex<Option<Instant>>,
 }
 
 impl<T: NodeInfo> LocalDataStore<T> {
 	/// Attempt to read pending transactions out of the local store.
+	/// Returns nothing if local transactions aren't persisted.
 	pub fn pending_transactions(&self) -> Result<Vec<PendingTransaction>, Error> {
-		if let Some(val) = self.db.get(self.col, LOCAL_TRANSACTIONS_KEY).map_err(Error::Io)? {
-			let local_txs: Vec<_> = ::serde_json::from_slice::<Vec<TransactionEntry>>(&val)
-				.map_err(Error::Json)?
-				.into_iter()
-				.filter_map(TransactionEntry::into_pending)
-				.collect();
+		match self.config.categories.local_transactions {
+			true => self.read_txs(LOCAL_TRANSACTIONS_KEY),
+			false => Ok(Vec::new()),
+		}
+	}
 
-			Ok(local_txs)
-		} else {
-			Ok(Vec::new())
+	/// Attempt to read the journaled pool transactions out of the local store.
+	/// Returns nothing if the pool journal isn't persisted.
+	pub fn pool_transactions(&self) -> Result<Vec<PendingTransaction>, Error> {
+		match self.config.categories.pool_journal {
+			true => self.read_txs(POOL_JOURNAL_KEY),
+			false => Ok(Vec::new()),
 		}
 	}
 
-	/// Update the entries in the database.
+	/// Update the entries of persisted categories in the database.
 	pub fn update(&self) -> Result<(), Error> {
 		trace!(target: "local_store", "Updating local store entries.");
 
-		let local_entries: Vec<TransactionEntry> = self.node.pending_transactions()
-			.into_iter()
-			.map(Into::into)
-			.collect();
+		let mut batch = self.db.transaction();
+		if self.config.categories.local_transactions {
+			self.write_txs(&mut batch, LOCAL_TRANSACTIONS_KEY, self.node.pending_transactions())?;
+		}
+		if self.config.categories.pool_journal {
+			self.write_txs(&mut batch, POOL_JOURNAL_KEY, self.node.pool_transactions())?;
+		}
+		self.db.write(batch).map_err(Error::Io)
+	}
 
-		self.write_txs(&local_entries)
+	/// Update the store if the update interval has passed since the last periodic update.
+	/// The first call only schedules the next update.
+	pub fn tick(&self, now: Instant) -> Result<(), Error> {
+		{
+			let mut next_update = self.next_update.lock();
+			let due = next_update.map_or(false, |next| now >= next);
+			if next_update.is_none() || due {
+				*next_update = Some(now + self.config.interval);
+			}
+			if !due {
+				return Ok(());
+			}
+		}
+
+		self.update()
 	}
 
-	/// Clear data in this column.
+	/// Clear local transactions written by the store in this column, if they are persisted.
+	/// Other categories, like the pool journal, are left intact.
 	pub fn clear(&self) -> Result<(), Error> {
-		trace!(target: "local_store", "Clearing local store entries.");
+		if !self.config.categories.local_transactions {
+			return Ok(());
+		}
 
-		self.write_txs(&[])
-	}
+		trace!(target: "local_store", "Clearing local transactions.");
 
-	// helper for writing a vector of transaction entries to disk.
-	fn write_txs(&self, txs: &[TransactionEntry]) -> Result<(), Error> {
 		let mut batch = self.db.transaction();
+		self.write_txs(&mut batch, LOCAL_TRANSACTIONS_KEY, Vec::new())?;
+		self.db.write(batch).map_err(Error::Io)
+	}
+
+	// helper for reading transaction entries stored under the given key.
+	fn read_txs(&self, key: &[u8]) -> Result<Vec<PendingTransaction>, Error> {
+		match self.db.get(self.col, key).map_err(Error::Io)? {
+			Some(val) => Ok(::serde_json::from_slice::<Vec<TransactionEntry>>(&val)
+				.map_err(Error::Json)?
+				.into_iter()
+				.filter_map(TransactionEntry::into_pending)
+				.collect()),
+			None => Ok(Vec::new()),
+		}
+	}
 
-		let local_json = ::serde_json::to_value(txs).map_err(Error::Json)?;
-		let json_str = format!("{}", local_json);
+	// helper for writing transactions under the given key.
+	fn write_txs(&self, batch: &mut ::kvdb::DBTransaction, key: &[u8], txs: Vec<PendingTransaction>) -> Result<(), Error> {
+		let entries: Vec<TransactionEntry> = txs.into_iter().map(Into::into).collect();
+		let json = ::serde_json::to_vec(&entries).map_err(Error::Json)?;
 
-		batch.put_vec(self.col, LOCAL_TRANSACTIONS_KEY, json_str.into_bytes());
-		self.db.write(batch).map_err(Error::Io)
+		batch.put_vec(self.col, key, json);
+		Ok(())
 	}
 }
 
 impl<T: NodeInfo, M: Send + Sync + 'static> IoHandler<M> for LocalDataStore<T> {
 	fn initialize(&self, io: &::io::IoContext<M>) {
-		if let Err(e) = io.register_timer(UPDATE_TIMER, UPDATE_TIMEOUT) {
+		let timeout = ::std::cmp::min(self.config.interval, UPDATE_CHECK_TIMEOUT);
+		if let Err(e) = io.register_timer(UPDATE_TIMER, timeout) {
 			warn!(target: "local_store", "Error registering local store update timer: {}", e);
 		}
 	}
 
 	fn timeout(&self, _io: &::io::IoContext<M>, timer: ::io::TimerToken) {
 		if let UPDATE_TIMER = timer {
-			if let Err(e) = self.update() {
+			if let Err(e) = self.tick(Instant::now()) {
 				debug!(target: "local_store", "Error updating local store: {}", e);
 			}
 		}
@@ -228,9 +386,11 @@ impl<T: NodeInfo> Drop for LocalDataStore<T> {
 
 #[cfg(test)]
 mod tests {
-	use super::NodeInfo;
+	use super::{NodeInfo, StoreConfig, Categories, DEFAULT_UPDATE_INTERVAL, LOCAL_TRANSACTIONS_KEY};
 
 	use std::sync::Arc;
+	use std::time::{Duration, Instant};
+	use parking_lot::Mutex;
 	use types::transaction::{Transaction, Condition, PendingTransaction};
 	use ethkey::{Brain, Generator};
 
@@ -242,17 +402,48 @@ mod tests {
 		fn pending_transactions(&self) -> Vec<PendingTransaction> { self.0.clone() }
 	}
 
+	#[derive(Default)]
+	struct Node {
+		local: Mutex<Vec<PendingTransaction>>,
+		pool: Vec<PendingTransaction>,
+	}
+	impl NodeInfo for Node {
+		fn pending_transactions(&self) -> Vec<PendingTransaction> { self.local.lock().clone() }
+		fn pool_transactions(&self) -> Vec<PendingTransaction> { self.pool.clone() }
+	}
+
+	fn transactions(seed: &str, count: u64) -> Vec<PendingTransaction> {
+		let keypair = Brain::new(seed.into()).generate().unwrap();
+		(0..count).map(|nonce| {
+			let mut tx = Transaction::default();
+			tx.nonce = nonce.into();
+
+			PendingTransaction::new(tx.sign(keypair.secret(), None), None)
+		}).collect()
+	}
+
+	fn all_categories() -> StoreConfig {
+		StoreConfig {
+			interval: DEFAULT_UPDATE_INTERVAL,
+			categories: Categories {
+				local_transactions: true,
+				pool_journal: true,
+				service_transactions: true,
+			},
+		}
+	}
+
 	#[test]
 	fn twice_empty() {
 		let db = Arc::new(::kvdb_memorydb::create(0));
 
 		{
-			let store = super::create(db.clone(), None, Dummy(vec![]));
+			let store = super::create(db.clone(), None, Dummy(vec![]), Default::default());
 			assert_eq!(store.pending_transactions().unwrap(), vec![])
 		}
 
 		{
-			let store = super::create(db.clone(), None, Dummy(vec![]));
+			let store = super::create(db.clone(), None, Dummy(vec![]), Default::default());
 			assert_eq!(store.pending_transactions().unwrap(), vec![])
 		}
 	}
@@ -277,17 +468,17 @@ mod tests {
 
 		{
 			// nothing written yet, will write pending.
-			let store = super::create(db.clone(), None, Dummy(transactions.clone()));
+			let store = super::create(db.clone(), None, Dummy(transactions.clone()), Default::default());
 			assert_eq!(store.pending_transactions().unwrap(), vec![])
 		}
 		{
 			// pending written, will write nothing.
-			let store = super::create(db.clone(), None, Dummy(vec![]));
+			let store = super::create(db.clone(), None, Dummy(vec![]), Default::default());
 			assert_eq!(store.pending_transactions().unwrap(), transactions)
 		}
 		{
 			// pending removed, will write nothing.
-			let store = super::create(db.clone(), None, Dummy(vec![]));
+			let store = super::create(db.clone(), None, Dummy(vec![]), Default::default());
 			assert_eq!(store.pending_transactions().unwrap(), vec![])
 		}
 	}
@@ -315,15 +506,137 @@ mod tests {
 		let db = Arc::new(::kvdb_memorydb::create(0));
 		{
 			// nothing written, will write bad.
-			let store = super::create(db.clone(), None, Dummy(transactions.clone()));
+			let store = super::create(db.clone(), None, Dummy(transactions.clone()), Default::default());
 			assert_eq!(store.pending_transactions().unwrap(), vec![])
 		}
 		{
 			// try to load transactions. The last transaction, which is invalid, will be skipped.
-			let store = super::create(db.clone(), None, Dummy(vec![]));
+			let store = super::create(db.clone(), None, Dummy(vec![]), Default::default());
 			let loaded = store.pending_transactions().unwrap();
 			transactions.pop();
 			assert_eq!(loaded, transactions);
 		}
 	}
+
+	#[test]
+	fn service_transactions_are_kept_apart() {
+		let keypair = Brain::new("abcd".into()).generate().unwrap();
+		let service: Vec<_> = (0..2u64).map(|nonce| {
+			let mut tx = Transaction::default();
+			tx.nonce = nonce.into();
+
+			super::ServiceTransaction {
+				purpose: format!("report:{}", nonce),
+				valid_until: Some(nonce * 10),
+				transaction: tx.sign(keypair.secret(), None),
+			}
+		}).collect();
+		let pending = PendingTransaction::new(service[0].transaction.clone(), None);
+
+		let db = Arc::new(::kvdb_memorydb::create(0));
+		assert_eq!(super::service_transactions(&*db, None).unwrap(), vec![]);
+		super::write_service_transactions(&*db, None, &service).unwrap();
+
+		{
+			// local transactions don't overwrite the journal.
+			let store = super::create(db.clone(), None, Dummy(vec![pending.clone()]), Default::default());
+			store.update().unwrap();
+			assert_eq!(store.pending_transactions().unwrap(), vec![pending]);
+			store.clear().unwrap();
+		}
+
+		assert_eq!(super::service_transactions(&*db, None).unwrap(), service);
+		super::write_service_transactions(&*db, None, &service[1..]).unwrap();
+		assert_eq!(super::service_transactions(&*db, None).unwrap(), &service[1..]);
+	}
+
+	#[test]
+	fn periodic_updates() {
+		let txs = transactions("abcd", 2);
+		let db = Arc::new(::kvdb_memorydb::create(0));
+		let config = StoreConfig { interval: Duration::from_secs(60), ..Default::default() };
+		let node = Node { local: Mutex::new(vec![txs[0].clone()]), pool: vec![] };
+		let store = super::create(db.clone(), None, node, config);
+		let start = Instant::now();
+
+		// the first tick only schedules an update.
+		store.tick(start).unwrap();
+		assert_eq!(store.pending_transactions().unwrap(), vec![]);
+		store.tick(start + Duration::from_secs(59)).unwrap();
+		assert_eq!(store.pending_transactions().unwrap(), vec![]);
+
+		store.tick(start + Duration::from_secs(60)).unwrap();
+		assert_eq!(store.pending_transactions().unwrap(), vec![txs[0].clone()]);
+
+		// the next one is due an interval later.
+		store.node.local.lock().push(txs[1].clone());
+		store.tick(start + Duration::from_secs(119)).unwrap();
+		assert_eq!(store.pending_transactions().unwrap(), vec![txs[0].clone()]);
+		store.tick(start + Duration::from_secs(120)).unwrap();
+		assert_eq!(store.pending_transactions().unwrap(), txs);
+	}
+
+	#[test]
+	fn restores_selected_categories() {
+		let local = transactions("abcd", 2);
+		let pool = transactions("efgh", 3);
+		let db = Arc::new(::kvdb_memorydb::create(0));
+
+		{
+			let node = Node { local: Mutex::new(local.clone()), pool: pool.clone() };
+			super::create(db.clone(), None, node, all_categories()).update().unwrap();
+		}
+		{
+			// local transactions are neither restored nor overwritten, when cleared or updated on drop.
+			let mut config = all_categories();
+			config.categories.local_transactions = false;
+			let store = super::create(db.clone(), None, Node::default(), config);
+			assert_eq!(store.pending_transactions().unwrap(), vec![]);
+			assert_eq!(store.pool_transactions().unwrap(), pool);
+			store.clear().unwrap();
+		}
+		{
+			let store = super::create(db.clone(), None, Node::default(), all_categories());
+			assert_eq!(store.pending_transactions().unwrap(), local);
+			assert_eq!(store.pool_transactions().unwrap(), vec![]);
+		}
+	}
+
+	#[test]
+	fn clear_keeps_pool_journal() {
+		let local = transactions("abcd", 2);
+		let pool = transactions("efgh", 3);
+		let db = Arc::new(::kvdb_memorydb::create(0));
+
+		{
+			let node = Node { local: Mutex::new(local.clone()), pool: pool.clone() };
+			super::create(db.clone(), None, node, all_categories()).update().unwrap();
+		}
+
+		// running without a persistent transaction queue only drops local transactions.
+		let store = super::create(db.clone(), None, Node::default(), all_categories());
+		store.clear().unwrap();
+		assert_eq!(store.pending_transactions().unwrap(), vec![]);
+		assert_eq!(store.pool_transactions().unwrap(), pool);
+	}
+
+	#[test]
+	fn corrupted_category_is_lost_alone() {
+		let pool = transactions("efgh", 3);
+		let db = Arc::new(::kvdb_memorydb::create(0));
+		let node = Node { local: Mutex::new(vec![]), pool: pool.clone() };
+		let store = super::create(db.clone(), None, node, all_categories());
+
+		// missing categories are empty.
+		assert_eq!(store.pending_transactions().unwrap(), vec![]);
+		assert_eq!(store.pool_transactions().unwrap(), vec![]);
+
+		store.update().unwrap();
+		let mut batch = db.transaction();
+		batch.put(None, LOCAL_TRANSACTIONS_KEY, b"[{\"rlp_bytes\":");
+		db.write(batch).unwrap();
+
+		assert!(store.pending_transactions().is_err());
+		assert_eq!(store.pool_transactions().unwrap(), pool);
+	}
 }
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::num::NonZeroU32;
+use ethstore::KdfConfig;
 use params::SpecType;
 
 #[derive(Debug, PartialEq)]
@@ -22,7 +22,9 @@ pub enum AccountCmd {
 	New(NewAccount),
 	List(ListAccounts),
 	Import(ImportAccounts),
-	ImportFromGeth(ImportFromGethAccounts)
+	ImportFromGeth(ImportFromGethAccounts),
+	Backup(BackupAccounts),
+	Restore(RestoreAccounts),
 }
 
 #[derive(Debug, PartialEq)]
@@ -33,10 +35,11 @@ pub struct ListAccounts {
 
 #[derive(Debug, PartialEq)]
 pub struct NewAccount {
-	pub iterations: NonZeroU32,
+	pub kdf: KdfConfig,
 	pub path: String,
 	pub spec: SpecType,
 	pub password_file: Option<String>,
+	pub mnemonic: bool,
 }
 
 #[derive(Debug, PartialEq)]
@@ -56,6 +59,28 @@ pub struct ImportFromGethAccounts {
 	pub spec: SpecType,
 }
 
+/// Parameters for the encrypted keystore backup
+#[derive(Debug, PartialEq)]
+pub struct BackupAccounts {
+	pub kdf: KdfConfig,
+	pub path: String,
+	pub spec: SpecType,
+	/// file to write the archive to
+	pub file: String,
+	pub password_file: Option<String>,
+}
+
+/// Parameters for restoring the keystore from a backup
+#[derive(Debug, PartialEq)]
+pub struct RestoreAccounts {
+	pub path: String,
+	pub spec: SpecType,
+	/// archive to restore from
+	pub file: String,
+	pub password_file: Option<String>,
+	/// overwrite existing accounts
+	pub force: bool,
+}
 
 #[cfg(not(feature = "accounts"))]
 pub fn execute(_cmd: AccountCmd) -> Result<String, String> {
@@ -65,31 +90,43 @@ pub fn execute(_cmd: AccountCmd) -> Result<String, String> {
 #[cfg(feature = "accounts")]
 mod command {
 	use super::*;
-	use std::path::PathBuf;
+	use std::fs;
+	use std::path::{Path, PathBuf};
 	use accounts::{AccountProvider, AccountProviderSettings};
-	use ethstore::{EthStore, SecretStore, SecretVaultRef, import_account, import_accounts, read_geth_accounts};
+	use ethstore::{self, EthStore, SecretStore, SecretVaultRef, import_account, import_accounts, read_geth_accounts};
 	use ethstore::accounts_dir::RootDiskDirectory;
+	use ethkey::Mnemonic;
 	use helpers::{password_prompt, password_from_file};
+	use path::restrict_permissions_owner;
+
+	/// Number of words in the recovery phrase of new accounts.
+	const MNEMONIC_WORDS: usize = 12;
 
 	pub fn execute(cmd: AccountCmd) -> Result<String, String> {
 		match cmd {
 			AccountCmd::New(new_cmd) => new(new_cmd),
 			AccountCmd::List(list_cmd) => list(list_cmd),
 			AccountCmd::Import(import_cmd) => import(import_cmd),
-			AccountCmd::ImportFromGeth(import_geth_cmd) => import_geth(import_geth_cmd)
+			AccountCmd::ImportFromGeth(import_geth_cmd) => import_geth(import_geth_cmd),
+			AccountCmd::Backup(backup_cmd) => backup(backup_cmd),
+			AccountCmd::Restore(restore_cmd) => restore(restore_cmd),
 		}
 	}
 
-	fn keys_dir(path: String, spec: SpecType) -> Result<RootDiskDirectory, String> {
+	fn keys_path(path: String, spec: SpecType) -> Result<PathBuf, String> {
 		let spec = spec.spec(&::std::env::temp_dir())?;
 		let mut path = PathBuf::from(&path);
 		path.push(spec.data_dir);
-		RootDiskDirectory::create(path).map_err(|e| format!("Could not open keys directory: {}", e))
+		Ok(path)
+	}
+
+	fn keys_dir(path: String, spec: SpecType) -> Result<RootDiskDirectory, String> {
+		RootDiskDirectory::create(keys_path(path, spec)?).map_err(|e| format!("Could not open keys directory: {}", e))
 	}
 
-	fn secret_store(dir: Box<RootDiskDirectory>, iterations: Option<NonZeroU32>) -> Result<EthStore, String> {
-		match iterations {
-			Some(i) => EthStore::open_with_iterations(dir, i),
+	fn secret_store(dir: Box<RootDiskDirectory>, kdf: Option<KdfConfig>) -> Result<EthStore, String> {
+		match kdf {
+			Some(kdf) => EthStore::open_with_kdf(dir, kdf),
 			_ => EthStore::open(dir)
 		}.map_err(|e| format!("Could not open keys store: {}", e))
 	}
@@ -101,8 +138,14 @@ mod command {
 		};
 
 		let dir = Box::new(keys_dir(n.path, n.spec)?);
-		let secret_store = Box::new(secret_store(dir, Some(n.iterations))?);
+		let secret_store = Box::new(secret_store(dir, Some(n.kdf))?);
 		let acc_provider = AccountProvider::new(secret_store, AccountProviderSettings::default());
+		if n.mnemonic {
+			let mnemonic = Mnemonic::random(MNEMONIC_WORDS).map_err(|e| format!("Could not generate recovery phrase: {}", e))?;
+			let secret = mnemonic.derive_secret("", 0).map_err(|e| format!("Could not derive account: {}", e))?;
+			let new_account = acc_provider.insert_account(secret, &password).map_err(|e| format!("Could not create new account: {}", e))?;
+			return Ok(format!("0x{:x}\n\nRecovery phrase (write it down, it will not be shown again):\n{}", new_account, mnemonic.phrase()));
+		}
 		let new_account = acc_provider.new_account(&password).map_err(|e| format!("Could not create new account: {}", e))?;
 		Ok(format!("0x{:x}", new_account))
 	}
@@ -138,6 +181,33 @@ mod command {
 		Ok(format!("{} account(s) imported", imported))
 	}
 
+	fn backup(b: BackupAccounts) -> Result<String, String> {
+		let password = match b.password_file {
+			Some(file) => password_from_file(file)?,
+			None => password_prompt()?,
+		};
+
+		let path = keys_path(b.path, b.spec)?;
+		let archive = ethstore::backup(&path, &password, &b.kdf).map_err(|e| format!("Could not create backup: {}", e))?;
+		fs::write(&b.file, archive).map_err(|e| format!("Could not write {}: {}", b.file, e))?;
+		let _ = restrict_permissions_owner(Path::new(&b.file), true, false);
+
+		Ok(format!("Accounts backed up to {}", b.file))
+	}
+
+	fn restore(r: RestoreAccounts) -> Result<String, String> {
+		let password = match r.password_file {
+			Some(file) => password_from_file(file)?,
+			None => password_prompt()?,
+		};
+
+		let archive = fs::read(&r.file).map_err(|e| format!("Could not read {}: {}", r.file, e))?;
+		let path = keys_path(r.path, r.spec)?;
+		let restored = ethstore::restore(&path, &archive, &password, r.force).map_err(|e| format!("Could not restore backup: {}", e))?;
+
+		Ok(format!("{} account(s) restored", restored.len()))
+	}
+
 	fn import_geth(i: ImportFromGethAccounts) -> Result<String, String> {
 		use std::io::ErrorKind;
 		use ethstore::Error;
@@ -17,10 +17,13 @@
 use std::sync::Arc;
 
 use dir::Directories;
+use ethcore::client::ClientIoMessage;
 use ethereum_types::Address;
 use ethkey::Password;
+use io::IoHandler;
 
 use params::{SpecType, AccountsConfig};
+use rpc_apis::SignerService;
 
 #[cfg(not(feature = "accounts"))]
 mod accounts {
@@ -55,6 +58,10 @@ mod accounts {
 	pub fn accounts_list(_account_provider: Arc<AccountProvider>) -> Arc<Fn() -> Vec<Address> + Send + Sync> {
 		Arc::new(|| vec![])
 	}
+
+	pub fn accounts_handler(_account_provider: Arc<AccountProvider>, _signer_service: Arc<SignerService>) -> Option<Arc<IoHandler<ClientIoMessage> + Send>> {
+		None
+	}
 }
 
 #[cfg(feature = "accounts")]
@@ -70,12 +77,21 @@ mod accounts {
 	/// Initialize account provider
 	pub fn prepare_account_provider(spec: &SpecType, dirs: &Directories, data_dir: &str, cfg: AccountsConfig, passwords: &[Password]) -> Result<AccountProvider, String> {
 		use ethstore::EthStore;
-		use ethstore::accounts_dir::RootDiskDirectory;
+		use ethstore::accounts_dir::{KeyDirectory, MultiDirectory, RootDiskDirectory};
 		use accounts::AccountProviderSettings;
 
 		let path = dirs.keys_path(data_dir);
 		upgrade_key_location(&dirs.legacy_keys_path(cfg.testnet), &path);
-		let dir = Box::new(RootDiskDirectory::create(&path).map_err(|e| format!("Could not open keys directory: {}", e))?);
+		let mut dir: Box<KeyDirectory> = Box::new(RootDiskDirectory::create(&path).map_err(|e| format!("Could not open keys directory: {}", e))?);
+		if !cfg.extra_keys_paths.is_empty() {
+			let extra_dirs = cfg.extra_keys_paths.iter().map(|extra_path| {
+				if !::std::path::Path::new(extra_path).is_dir() {
+					return Err(format!("Could not open extra keys directory: {} does not exist", extra_path));
+				}
+				Ok(Box::new(RootDiskDirectory::at(extra_path)) as Box<KeyDirectory>)
+			}).collect::<Result<Vec<_>, String>>()?;
+			dir = Box::new(MultiDirectory::new(dir, extra_dirs));
+		}
 		let account_settings = AccountProviderSettings {
 			enable_hardware_wallets: cfg.enable_hardware_wallets,
 			hardware_wallet_classic_key: spec == &SpecType::Classic,
@@ -86,9 +102,11 @@ mod accounts {
 					"00a329c0648769a73afac7f9381e08fb43dbea72".into()
 				],
 			},
+			autolock: cfg.autolock,
+			allow_permanent_unlock: cfg.allow_permanent_unlock,
 		};
 
-		let ethstore = EthStore::open_with_iterations(dir, cfg.iterations).map_err(|e| format!("Could not open keys directory: {}", e))?;
+		let ethstore = EthStore::open_with_kdf(dir, cfg.kdf).map_err(|e| format!("Could not open keys directory: {}", e))?;
 		if cfg.refresh_time > 0 {
 			ethstore.set_refresh_time(::std::time::Duration::from_secs(cfg.refresh_time));
 		}
@@ -210,6 +228,49 @@ mod accounts {
 		Arc::new(move || account_provider.accounts().unwrap_or_default())
 	}
 
+	const ACCOUNTS_TIMER: ::io::TimerToken = 0;
+
+	/// Periodically locks accounts that have been idle for longer than `--accounts-autolock`
+	/// and picks up key files changed on disk (every `--accounts-refresh` seconds).
+	struct AccountsHandler {
+		accounts: Arc<AccountProvider>,
+		signer_service: Arc<SignerService>,
+	}
+
+	impl IoHandler<ClientIoMessage> for AccountsHandler {
+		fn initialize(&self, io: &::io::IoContext<ClientIoMessage>) {
+			io.register_timer(ACCOUNTS_TIMER, ::std::time::Duration::from_secs(5)).expect("Error registering accounts timer");
+		}
+
+		fn timeout(&self, _io: &::io::IoContext<ClientIoMessage>, timer: ::io::TimerToken) {
+			if timer != ACCOUNTS_TIMER {
+				return;
+			}
+
+			match self.accounts.refresh_accounts() {
+				Ok(true) => {
+					debug!("Accounts reloaded from disk");
+					self.signer_service.queue().accounts_changed();
+				},
+				Ok(false) => {},
+				Err(e) => warn!("Unable to reload accounts: {}", e),
+			}
+
+			let locked = self.accounts.lock_idle_accounts();
+			if !locked.is_empty() {
+				info!("Locked idle accounts: {:?}", locked);
+				self.signer_service.queue().accounts_locked(locked);
+			}
+		}
+	}
+
+	pub fn accounts_handler(account_provider: Arc<AccountProvider>, signer_service: Arc<SignerService>) -> Option<Arc<IoHandler<ClientIoMessage> + Send>> {
+		Some(Arc::new(AccountsHandler {
+			accounts: account_provider,
+			signer_service,
+		}))
+	}
+
 	fn insert_dev_account(account_provider: &AccountProvider) {
 		let secret: ethkey::Secret = "4d5db4107d237df6a3d58ee5f70ae63d73d7658d4026f2eefd2f204c81682cb7".into();
 		let dev_account = ethkey::KeyPair::from_secret(secret.clone()).expect("Valid secret produces valid key;qed");
@@ -240,5 +301,6 @@ pub use self::accounts::{
 	miner_author,
 	private_tx_signer,
 	accounts_list,
+	accounts_handler,
 };
 
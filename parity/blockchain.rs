@@ -35,6 +35,7 @@ use ethcore::verification::queue::kind::blocks::Unverified;
 use ethcore_service::ClientService;
 use cache::CacheConfig;
 use informant::{Informant, FullNodeInformantData, MillisecondDuration};
+use output::{self, OutputFormat, ImportSummary, ExportBlocksSummary, ExportStateSummary, DbSummary};
 use params::{SpecType, Pruning, Switch, tracing_switch_to_bool, fatdb_switch_to_bool};
 use helpers::{to_client_config, execute_upgrades};
 use dir::Directories;
@@ -88,6 +89,7 @@ pub struct ResetBlockchain {
 	pub compaction: DatabaseCompactionProfile,
 	pub cache_config: CacheConfig,
 	pub num: u32,
+	pub output_format: OutputFormat,
 }
 
 #[derive(Debug, PartialEq)]
@@ -95,6 +97,7 @@ pub struct KillBlockchain {
 	pub spec: SpecType,
 	pub dirs: Directories,
 	pub pruning: Pruning,
+	pub output_format: OutputFormat,
 }
 
 #[derive(Debug, PartialEq)]
@@ -116,6 +119,7 @@ pub struct ImportBlockchain {
 	pub verifier_settings: VerifierSettings,
 	pub light: bool,
 	pub max_round_blocks_to_import: usize,
+	pub output_format: OutputFormat,
 }
 
 #[derive(Debug, PartialEq)]
@@ -135,6 +139,7 @@ pub struct ExportBlockchain {
 	pub to_block: BlockId,
 	pub check_seal: bool,
 	pub max_round_blocks_to_import: usize,
+	pub output_format: OutputFormat,
 }
 
 #[derive(Debug, PartialEq)]
@@ -156,6 +161,7 @@ pub struct ExportState {
 	pub min_balance: Option<U256>,
 	pub max_balance: Option<U256>,
 	pub max_round_blocks_to_import: usize,
+	pub output_format: OutputFormat,
 }
 
 pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
@@ -315,11 +321,19 @@ fn execute_import_light(cmd: ImportBlockchain) -> Result<(), String> {
 	let ms = timer.elapsed().as_milliseconds();
 	let report = client.report();
 
-	info!("Import completed in {} seconds, {} headers, {} hdr/s",
-		ms / 1000,
-		report.blocks_imported,
-		(report.blocks_imported * 1000) as u64 / ms,
-	);
+	match cmd.output_format {
+		OutputFormat::Json => output::print_json(&ImportSummary {
+			duration_ms: ms,
+			blocks: report.blocks_imported as u64,
+			transactions: None,
+			gas: None,
+		}, false),
+		OutputFormat::Human => info!("Import completed in {} seconds, {} headers, {} hdr/s",
+			ms / 1000,
+			report.blocks_imported,
+			(report.blocks_imported * 1000) as u64 / ms,
+		),
+	}
 
 	Ok(())
 }
@@ -432,10 +446,12 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 			client: client.clone(),
 			sync: None,
 			net: None,
+			miner: None,
 		},
 		None,
 		None,
 		cmd.with_color,
+		cmd.output_format,
 	));
 
 	service.register_io_handler(informant).map_err(|_| "Unable to register informant handler".to_owned())?;
@@ -493,15 +509,23 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 	let report = client.report();
 
 	let ms = timer.elapsed().as_milliseconds();
-	info!("Import completed in {} seconds, {} blocks, {} blk/s, {} transactions, {} tx/s, {} Mgas, {} Mgas/s",
-		ms / 1000,
-		report.blocks_imported,
-		(report.blocks_imported * 1000) as u64 / ms,
-		report.transactions_applied,
-		(report.transactions_applied * 1000) as u64 / ms,
-		report.gas_processed / 1_000_000,
-		(report.gas_processed / (ms * 1000)).low_u64(),
-	);
+	match cmd.output_format {
+		OutputFormat::Json => output::print_json(&ImportSummary {
+			duration_ms: ms,
+			blocks: report.blocks_imported as u64,
+			transactions: Some(report.transactions_applied as u64),
+			gas: Some(report.gas_processed.low_u64()),
+		}, false),
+		OutputFormat::Human => info!("Import completed in {} seconds, {} blocks, {} blk/s, {} transactions, {} tx/s, {} Mgas, {} Mgas/s",
+			ms / 1000,
+			report.blocks_imported,
+			(report.blocks_imported * 1000) as u64 / ms,
+			report.transactions_applied,
+			(report.transactions_applied * 1000) as u64 / ms,
+			report.gas_processed / 1_000_000,
+			(report.gas_processed / (ms * 1000)).low_u64(),
+		),
+	}
 	Ok(())
 }
 
@@ -598,6 +622,7 @@ fn start_client(
 }
 
 fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
+	let timer = Instant::now();
 	let service = start_client(
 		cmd.dirs,
 		cmd.spec,
@@ -615,6 +640,7 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
 
 	let client = service.client();
 
+	let to_stdout = cmd.file_path.is_none();
 	let mut out: Box<io::Write> = match cmd.file_path {
 		Some(f) => Box::new(fs::File::create(&f).map_err(|_| format!("Cannot write to file given: {}", f))?),
 		None => Box::new(io::stdout()),
@@ -638,11 +664,19 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
 		}
 	}
 
-	info!("Export completed.");
+	match cmd.output_format {
+		OutputFormat::Json => output::print_json(&ExportBlocksSummary {
+			duration_ms: timer.elapsed().as_milliseconds(),
+			from_block: from,
+			to_block: to,
+		}, to_stdout),
+		OutputFormat::Human => info!("Export completed."),
+	}
 	Ok(())
 }
 
 fn execute_export_state(cmd: ExportState) -> Result<(), String> {
+	let timer = Instant::now();
 	let service = start_client(
 		cmd.dirs,
 		cmd.spec,
@@ -659,6 +693,7 @@ fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 
 	let client = service.client();
 
+	let to_stdout = cmd.file_path.is_none();
 	let mut out: Box<io::Write> = match cmd.file_path {
 		Some(f) => Box::new(fs::File::create(&f).map_err(|_| format!("Cannot write to file given: {}", f))?),
 		None => Box::new(io::stdout()),
@@ -725,7 +760,13 @@ fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 		}
 	}
 	out.write_fmt(format_args!("\n}}}}")).expect("Write error");
-	info!("Export completed.");
+	match cmd.output_format {
+		OutputFormat::Json => output::print_json(&ExportStateSummary {
+			duration_ms: timer.elapsed().as_milliseconds(),
+			accounts: i as u64,
+		}, to_stdout),
+		OutputFormat::Human => info!("Export completed."),
+	}
 	Ok(())
 }
 
@@ -746,7 +787,14 @@ fn execute_reset(cmd: ResetBlockchain) -> Result<(), String> {
 
 	let client = service.client();
 	client.reset(cmd.num)?;
-	info!("{}", Colour::Green.bold().paint("Successfully reset db!"));
+	match cmd.output_format {
+		OutputFormat::Json => output::print_json(&DbSummary {
+			command: "reset".into(),
+			deleted: None,
+			reset_blocks: Some(cmd.num),
+		}, false),
+		OutputFormat::Human => info!("{}", Colour::Green.bold().paint("Successfully reset db!")),
+	}
 
 	Ok(())
 }
@@ -762,7 +810,14 @@ pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
 	fs::remove_dir_all(&dir).map_err(|e| format!("Error removing database: {:?}", e))?;
 	user_defaults.is_first_launch = true;
 	user_defaults.save(&user_defaults_path)?;
-	info!("Database deleted.");
+	match cmd.output_format {
+		OutputFormat::Json => output::print_json(&DbSummary {
+			command: "kill".into(),
+			deleted: Some(dir.to_string_lossy().into_owned()),
+			reset_blocks: None,
+		}, false),
+		OutputFormat::Human => info!("Database deleted."),
+	}
 	Ok(())
 }
 
@@ -44,6 +44,10 @@ usage! {
 
 			CMD cmd_account_new {
 				"Create a new account (and its associated key) for the given --chain (default: mainnet)",
+
+				FLAG flag_account_new_mnemonic: (bool) = false,
+				"--mnemonic",
+				"Derive the account from a newly generated BIP-39 recovery phrase and print the phrase",
 			}
 
 			CMD cmd_account_list {
@@ -58,6 +62,28 @@ usage! {
 				"<PATH>...",
 				"Path to the accounts",
 			}
+
+			CMD cmd_account_backup
+			{
+				"Write an encrypted archive of all accounts, vaults and the address book of the given --chain (default: mainnet). The archive is encrypted with the first password from --password, or the password typed in.",
+
+				ARG arg_account_backup_file: (Option<String>) = None,
+				"--file=[FILE]",
+				"Path to the backup file",
+			}
+
+			CMD cmd_account_restore
+			{
+				"Restore accounts from an encrypted archive into the given --chain (default: mainnet)",
+
+				FLAG flag_account_restore_force: (bool) = false,
+				"--force",
+				"Overwrite accounts, vaults and metadata that already exist",
+
+				ARG arg_account_restore_file: (Option<String>) = None,
+				"--file=[FILE]",
+				"Path to the backup file",
+			}
 		}
 
 		CMD cmd_wallet
@@ -231,6 +257,38 @@ usage! {
 
 		}
 
+		CMD cmd_secretstore
+		{
+			"Manage the key shares database of the Secret Store",
+
+			CMD cmd_secretstore_backup
+			{
+				"Write an archive of all key shares, encrypted with the node key from --secretstore-secret",
+
+				ARG arg_secretstore_backup_file: (Option<String>) = None,
+				"--file=[FILE]",
+				"Path to the backup file",
+			}
+
+			CMD cmd_secretstore_restore
+			{
+				"Restore key shares from an archive, written by the backup command of this node",
+
+				FLAG flag_secretstore_restore_force: (bool) = false,
+				"--force",
+				"Overwrite key shares which have versions missing in the archive",
+
+				ARG arg_secretstore_restore_file: (Option<String>) = None,
+				"--file=[FILE]",
+				"Path to the backup file",
+			}
+
+			CMD cmd_secretstore_verify
+			{
+				"List stored key shares and check their integrity",
+			}
+		}
+
 		CMD cmd_export_hardcoded_sync
 		{
 			"Print the hashed light clients headers of the given --chain (default: mainnet) in a JSON format. To be used as hardcoded headers in a genesis file.",
@@ -318,6 +376,10 @@ usage! {
 			"--db-path=[PATH]",
 			"Specify the database directory path",
 
+			ARG arg_shutdown_timeout: (u64) = 300u64, or |c: &Config| c.parity.as_ref()?.shutdown_timeout.clone(),
+			"--shutdown-timeout=[SECS]",
+			"Number of seconds to wait for a graceful shutdown. The node exits uncleanly if it hasn't stopped by then.",
+
 		["Convenience Options"]
 			FLAG flag_unsafe_expose: (bool) = false, or |c: &Config| c.misc.as_ref()?.unsafe_expose,
 			"--unsafe-expose",
@@ -340,10 +402,34 @@ usage! {
 			"--fast-unlock",
 			"Use drastically faster unlocking mode. This setting causes raw secrets to be stored unprotected in memory, so use with care.",
 
+			ARG arg_accounts_autolock: (Option<u64>) = None, or |c: &Config| c.account.as_ref()?.autolock.clone(),
+			"--accounts-autolock=[SECS]",
+			"Lock unlocked accounts again after they haven't been used for SECS seconds.",
+
+			FLAG flag_allow_permanent_unlock: (bool) = false, or |c: &Config| c.account.as_ref()?.allow_permanent_unlock.clone(),
+			"--allow-permanent-unlock",
+			"Exempt permanently unlocked accounts from --accounts-autolock.",
+
 			ARG arg_keys_iterations: (u32) = 10240u32, or |c: &Config| c.account.as_ref()?.keys_iterations.clone(),
 			"--keys-iterations=[NUM]",
 			"Specify the number of iterations to use when deriving key from the password (bigger is more secure)",
 
+			ARG arg_keys_kdf: (String) = "pbkdf2", or |c: &Config| c.account.as_ref()?.keys_kdf.clone(),
+			"--keys-kdf=[KDF]",
+			"Specify the key derivation function to use when encrypting new keys. KDF may be one of: pbkdf2 - use --keys-iterations iterations; scrypt - use --keys-scrypt-n, --keys-scrypt-r and --keys-scrypt-p parameters. Existing keys can always be read regardless of this setting.",
+
+			ARG arg_keys_scrypt_n: (u32) = 262144u32, or |c: &Config| c.account.as_ref()?.keys_scrypt_n.clone(),
+			"--keys-scrypt-n=[NUM]",
+			"Specify the scrypt CPU/memory cost parameter used with --keys-kdf=scrypt. Must be a power of two.",
+
+			ARG arg_keys_scrypt_r: (u32) = 8u32, or |c: &Config| c.account.as_ref()?.keys_scrypt_r.clone(),
+			"--keys-scrypt-r=[NUM]",
+			"Specify the scrypt block size parameter used with --keys-kdf=scrypt.",
+
+			ARG arg_keys_scrypt_p: (u32) = 1u32, or |c: &Config| c.account.as_ref()?.keys_scrypt_p.clone(),
+			"--keys-scrypt-p=[NUM]",
+			"Specify the scrypt parallelization parameter used with --keys-kdf=scrypt.",
+
 			ARG arg_accounts_refresh: (u64) = 5u64, or |c: &Config| c.account.as_ref()?.refresh_time.clone(),
 			"--accounts-refresh=[TIME]",
 			"Specify the cache time of accounts read from disk. If you manage thousands of accounts set this to 0 to disable refresh.",
@@ -356,6 +442,10 @@ usage! {
 			"--password=[FILE]...",
 			"Provide a file containing a password for unlocking an account. Leading and trailing whitespace is trimmed.",
 
+			ARG arg_extra_keys_path: (Vec<String>) = Vec::new(), or |c: &Config| c.account.as_ref()?.extra_keys_path.clone(),
+			"--extra-keys-path=[PATH]...",
+			"Load additional accounts from the key files in PATH. These accounts can be used for signing, but are never modified; new accounts are always created in --keys-path.",
+
 		["Private Transactions Options"]
 			FLAG flag_private_enabled: (bool) = false, or |c: &Config| c.private_tx.as_ref()?.enabled,
 			"--private-tx-enabled",
@@ -390,6 +480,10 @@ usage! {
 			"--ui-path=[PATH]",
 			"Specify directory where Trusted UIs tokens should be stored.",
 
+			ARG arg_ui_confirmation_timeout: (Option<u64>) = None, or |c: &Config| c.ui.as_ref()?.confirmation_timeout.clone(),
+			"--ui-confirmation-timeout=[SECS]",
+			"Reject signing requests that haven't been confirmed in a Trusted UI within SECS seconds. Requests displayed in the UI get their deadline extended once.",
+
 		["Networking Options"]
 			FLAG flag_no_warp: (bool) = false, or |c: &Config| c.network.as_ref()?.warp.clone().map(|w| !w),
 			"--no-warp",
@@ -411,6 +505,34 @@ usage! {
 			"--no-serve-light",
 			"Disable serving of light peers.",
 
+			ARG arg_light_serve_load: (u8) = 50u8, or |c: &Config| c.network.as_ref()?.light_serve_load.clone(),
+			"--light-serve-load=[PERCENT]",
+			"Maximum share of time spent on serving light peers, in percent. Request costs offered to light peers are derived from it.",
+
+			ARG arg_light_serve_credit_seconds: (u64) = 300u64, or |c: &Config| c.network.as_ref()?.light_serve_credit_seconds.clone(),
+			"--light-serve-credit-seconds=[SECS]",
+			"How many seconds worth of request credits light peers may accumulate.",
+
+			ARG arg_light_serve_rate: (u64) = 0u64, or |c: &Config| c.network.as_ref()?.light_serve_rate.clone(),
+			"--light-serve-rate=[REQS]",
+			"Maximum number of requests served to light peers per second. 0 for no limit.",
+
+			ARG arg_light_serve_peer_rate: (u64) = 0u64, or |c: &Config| c.network.as_ref()?.light_serve_peer_rate.clone(),
+			"--light-serve-peer-rate=[REQS]",
+			"Maximum number of requests served to each light peer per second. Responses to peers exceeding it are delayed. Reserved peers are not limited. 0 for no limit.",
+
+			ARG arg_light_serve_reserved_share: (u8) = 25u8, or |c: &Config| c.network.as_ref()?.light_serve_reserved_share.clone(),
+			"--light-serve-reserved-share=[PERCENT]",
+			"Share of --light-serve-rate guaranteed to reserved light peers, in percent.",
+
+			ARG arg_light_request_credits: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.light_request_credits.clone(),
+			"--light-request-credits=[CREDITS]",
+			"Maximum amount of request credits to ask light servers for. Must be given together with --light-request-recharge. Servers' own parameters are used if not given.",
+
+			ARG arg_light_request_recharge: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.light_request_recharge.clone(),
+			"--light-request-recharge=[CREDITS]",
+			"Amount of request credits per second to ask light servers to recharge. Must be given together with --light-request-credits.",
+
 			ARG arg_warp_barrier: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.warp_barrier.clone(),
 			"--warp-barrier=[NUM]",
 			"When warp enabled never attempt regular sync before warping to block NUM.",
@@ -500,7 +622,7 @@ usage! {
 
 			ARG arg_jsonrpc_apis: (String) = "web3,eth,pubsub,net,parity,private,parity_pubsub,traces,rpc,shh,shh_pubsub,parity_transactions_pool", or |c: &Config| c.rpc.as_ref()?.apis.as_ref().map(|vec| vec.join(",")),
 			"--jsonrpc-apis=[APIS]",
-			"Specify the APIs available through the HTTP JSON-RPC interface using a comma-delimited list of API names. Possible names are: all, safe, debug, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore, shh, shh_pubsub. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc, shh, shh_pubsub",
+			"Specify the APIs available through the HTTP JSON-RPC interface using a comma-delimited list of API names. Possible names are: all, safe, debug, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore, secretstore_admin, shh, shh_pubsub. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc, shh, shh_pubsub",
 
 			ARG arg_jsonrpc_hosts: (String) = "none", or |c: &Config| c.rpc.as_ref()?.hosts.as_ref().map(|vec| vec.join(",")),
 			"--jsonrpc-hosts=[HOSTS]",
@@ -541,7 +663,7 @@ usage! {
 
 			ARG arg_ws_apis: (String) = "web3,eth,pubsub,net,parity,parity_pubsub,private,traces,rpc,shh,shh_pubsub,parity_transactions_pool", or |c: &Config| c.websockets.as_ref()?.apis.as_ref().map(|vec| vec.join(",")),
 			"--ws-apis=[APIS]",
-			"Specify the JSON-RPC APIs available through the WebSockets interface using a comma-delimited list of API names. Possible names are: all, safe, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore, shh, shh_pubsub. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc, shh, shh_pubsub",
+			"Specify the JSON-RPC APIs available through the WebSockets interface using a comma-delimited list of API names. Possible names are: all, safe, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore, secretstore_admin, shh, shh_pubsub. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc, shh, shh_pubsub",
 
 			ARG arg_ws_origins: (String) = "parity://*,chrome-extension://*,moz-extension://*", or |c: &Config| c.websockets.as_ref()?.origins.as_ref().map(|vec| vec.join(",")),
 			"--ws-origins=[URL]",
@@ -566,7 +688,7 @@ usage! {
 
 			ARG arg_ipc_apis: (String) = "web3,eth,pubsub,net,parity,parity_pubsub,parity_accounts,private,traces,rpc,shh,shh_pubsub,parity_transactions_pool", or |c: &Config| c.ipc.as_ref()?.apis.as_ref().map(|vec| vec.join(",")),
 			"--ipc-apis=[APIS]",
-			"Specify custom API set available via JSON-RPC over IPC using a comma-delimited list of API names. Possible names are: all, safe, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore, shh, shh_pubsub. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc, shh, shh_pubsub",
+			"Specify custom API set available via JSON-RPC over IPC using a comma-delimited list of API names. Possible names are: all, safe, web3, net, eth, pubsub, personal, signer, parity, parity_pubsub, parity_accounts, parity_set, traces, rpc, secretstore, secretstore_admin, shh, shh_pubsub. You can also disable a specific API by putting '-' in the front, example: all,-personal. 'safe' enables the following APIs: web3, net, eth, pubsub, parity, parity_pubsub, traces, rpc, shh, shh_pubsub",
 
 		["API and Console Options – IPFS"]
 			FLAG flag_ipfs_api: (bool) = false, or |c: &Config| c.ipfs.as_ref()?.enable.clone(),
@@ -594,6 +716,14 @@ usage! {
 			"--on-demand-time-window=[S]",
 			"Specify the maximum time to wait for a successful response",
 
+			ARG arg_on_demand_request_timeout: (Option<u64>) = None, or |c: &Config| c.light.as_ref()?.on_demand_request_timeout,
+			"--on-demand-request-timeout=[S]",
+			"Specify the time to wait for a peer to answer a request before the request is sent to another peer",
+
+			ARG arg_on_demand_request_attempts_max: (Option<usize>) = None, or |c: &Config| c.light.as_ref()?.on_demand_request_attempts_max,
+			"--on-demand-max-attempts=[TIMES]",
+			"Specify the number of peers a request may time out on before it fails",
+
 			ARG arg_on_demand_request_backoff_start: (Option<u64>) = None, or |c: &Config| c.light.as_ref()?.on_demand_request_backoff_start,
 			"--on-demand-start-backoff=[S]",
 			"Specify light client initial backoff time for a request",
@@ -610,6 +740,14 @@ usage! {
 			"--on-demand-consecutive-failures=[TIMES]",
 			"Specify light client the number of failures for a request until it gets exponentially backed off",
 
+			ARG arg_light_checkpoint: (Option<String>) = None, or |c: &Config| c.light.as_ref()?.checkpoint.clone(),
+			"--light-checkpoint=[FILE]",
+			"Start the light client from the checkpoint in FILE instead of the one in the chain specification. FILE has the structure of the specification's hardcodedSync section.",
+
+			ARG arg_light_call_cache_size: (usize) = 128usize, or |c: &Config| c.light.as_ref()?.call_cache_size.clone(),
+			"--light-call-cache-size=[ENTRIES]",
+			"Number of verified eth_call results the light client keeps cached.",
+
 		["Secret Store Options"]
 			FLAG flag_no_secretstore: (bool) = false, or |c: &Config| c.secretstore.as_ref()?.disable.clone(),
 			"--no-secretstore",
@@ -623,6 +761,10 @@ usage! {
 			"--no-secretstore-auto-migrate",
 			"Do not run servers set change session automatically when servers set changes. This option has no effect when servers set is read from configuration file.",
 
+			ARG arg_secretstore_auto_migrate: (String) = "none", or |c: &Config| c.secretstore.as_ref()?.auto_migrate.clone(),
+			"--secretstore-auto-migrate=[MODE]",
+			"Start servers set change session from this node when servers set changes and this node holds the Secret Store administrator key. MODE may be one of: none, admin (start session once all nodes of the new set are connected), dry-run (only log the migration plan). Replaces contract-driven auto migration when enabled.",
+
 			ARG arg_secretstore_http_cors: (String) = "none", or |c: &Config| c.secretstore.as_ref()?.cors.as_ref().map(|vec| vec.join(",")),
 			"--secretstore-http-cors=[URL]",
 			"Specify CORS header for Secret Store HTTP API responses. Special options: \"all\", \"none\".",
@@ -631,25 +773,17 @@ usage! {
 			"--secretstore-acl-contract=[SOURCE]",
 			"Secret Store permissioning contract address source: none, registry (contract address is read from 'secretstore_acl_checker' entry in registry) or address.",
 
-			ARG arg_secretstore_contract: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.service_contract.clone(),
-			"--secretstore-contract=[SOURCE]",
-			"Secret Store Service contract address source: none, registry (contract address is read from 'secretstore_service' entry in registry) or address.",
-
-			ARG arg_secretstore_srv_gen_contract: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.service_contract_srv_gen.clone(),
-			"--secretstore-srv-gen-contract=[SOURCE]",
-			"Secret Store Service server key generation contract address source: none, registry (contract address is read from 'secretstore_service_srv_gen' entry in registry) or address.",
-
-			ARG arg_secretstore_srv_retr_contract: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.service_contract_srv_retr.clone(),
-			"--secretstore-srv-retr-contract=[SOURCE]",
-			"Secret Store Service server key retrieval contract address source: none, registry (contract address is read from 'secretstore_service_srv_retr' entry in registry) or address.",
+			ARG arg_secretstore_acl_cache_ttl: (u64) = 600u64, or |c: &Config| c.secretstore.as_ref()?.acl_cache_ttl.clone(),
+			"--secretstore-acl-cache-ttl=[SECS]",
+			"Specify for how long results of Secret Store permissioning contract checks are cached, unless changed permissions are announced by the contract. Denied checks are cached for a tenth of this time. 0 disables the cache.",
 
-			ARG arg_secretstore_doc_store_contract: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.service_contract_doc_store.clone(),
-			"--secretstore-doc-store-contract=[SOURCE]",
-			"Secret Store Service document key store contract address source: none, registry (contract address is read from 'secretstore_service_doc_store' entry in registry) or address.",
+			ARG arg_secretstore_batch_parallelism: (usize) = 8usize, or |c: &Config| c.secretstore.as_ref()?.batch_parallelism.clone(),
+			"--secretstore-batch-parallelism=[NUM]",
+			"Specify how many Secret Store sessions are running concurrently to serve a single batch request.",
 
-			ARG arg_secretstore_doc_sretr_contract: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.service_contract_doc_sretr.clone(),
-			"--secretstore-doc-sretr-contract=[SOURCE]",
-			"Secret Store Service document key shadow retrieval contract address source: none, registry (contract address is read from 'secretstore_service_doc_sretr' entry in registry) or address.",
+			ARG arg_secretstore_service_contracts: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.service_contracts.as_ref().map(|vec| vec.join(",")),
+			"--secretstore-service-contracts=[CONTRACTS]",
+			"Comma-separated list of Secret Store Service contracts in form KIND:SOURCE, where KIND is one of: all, srv_gen, srv_retr, doc_store, doc_sretr and SOURCE is registry (contract address is read from the registry entry of this kind) or address. A contract, listed earlier, is disabled by putting '-' in the front of the same KIND:SOURCE, example: srv_gen:registry,doc_sretr:registry,-srv_gen:registry.",
 
 			ARG arg_secretstore_nodes: (String) = "", or |c: &Config| c.secretstore.as_ref()?.nodes.as_ref().map(|vec| vec.join(",")),
 			"--secretstore-nodes=[NODES]",
@@ -675,6 +809,18 @@ usage! {
 			"--secretstore-http-port=[PORT]",
 			"Specify the port portion for listening to Secret Store Key Server HTTP requests.",
 
+			ARG arg_secretstore_http_cert: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.http_cert.clone(),
+			"--secretstore-http-cert=[FILE]",
+			"Specify PEM-encoded certificate chain for Secret Store Key Server HTTP requests. HTTP API is served over TLS when the certificate and --secretstore-http-key are given.",
+
+			ARG arg_secretstore_http_key: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.http_key.clone(),
+			"--secretstore-http-key=[FILE]",
+			"Specify PEM-encoded private key of the --secretstore-http-cert certificate.",
+
+			ARG arg_secretstore_http_client_ca: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.http_client_ca.clone(),
+			"--secretstore-http-client-ca=[FILE]",
+			"Specify PEM-encoded CA certificates. When given, Secret Store HTTP API clients must present a certificate signed by one of them.",
+
 			ARG arg_secretstore_path: (String) = "$BASE/secretstore", or |c: &Config| c.secretstore.as_ref()?.path.clone(),
 			"--secretstore-path=[PATH]",
 			"Specify directory where Secret Store should save its data.",
@@ -720,6 +866,14 @@ usage! {
 			"--no-persistent-txqueue",
 			"Don't save pending local transactions to disk to be restored whenever the node restarts.",
 
+			ARG arg_local_store_interval: (u64) = 900u64, or |c: &Config| c.parity.as_ref()?.local_store_interval.clone(),
+			"--local-store-interval=[SECS]",
+			"Interval in seconds between saves of node-local data to disk.",
+
+			ARG arg_local_store: (String) = "local,service", or |c: &Config| c.parity.as_ref()?.local_store.clone(),
+			"--local-store=[CATEGORIES]",
+			"Node-local data saved to disk and restored whenever the node restarts. CATEGORIES is a comma-separated list of: local - pending local transactions; pool - other transactions in the pool; service - service transactions of the consensus engine; none - nothing.",
+
 			// For backward compatibility; Stratum should be enabled if the config file
 			// contains a `[stratum]` section and it is not explicitly disabled (disable = true)
 			FLAG flag_stratum: (bool) = false, or |c: &Config| Some(c.stratum.as_ref().map(|s| s.disable != Some(true)).unwrap_or(false)),
@@ -860,6 +1014,26 @@ usage! {
 			"--log-file=[FILENAME]",
 			"Specify a filename into which logging should be appended.",
 
+			ARG arg_log_file_size: (Option<u64>) = None, or |c: &Config| c.misc.as_ref()?.log_file_size.clone(),
+			"--log-file-size=[MB]",
+			"Rotate the log file before it grows beyond MB megabytes.",
+
+			FLAG flag_log_file_daily: (bool) = false, or |c: &Config| c.misc.as_ref()?.log_file_daily.clone(),
+			"--log-file-daily",
+			"Rotate the log file daily.",
+
+			ARG arg_log_file_keep: (usize) = 5usize, or |c: &Config| c.misc.as_ref()?.log_file_keep.clone(),
+			"--log-file-keep=[NUM]",
+			"Number of rotated log files to keep.",
+
+			FLAG flag_log_file_compress: (bool) = false, or |c: &Config| c.misc.as_ref()?.log_file_compress.clone(),
+			"--log-file-compress",
+			"Compress rotated log files with gzip.",
+
+			FLAG flag_json_logs: (bool) = false, or |c: &Config| c.misc.as_ref()?.json_logs.clone(),
+			"--json-logs",
+			"Print informant reports, block imports and summaries of the import, export and db commands as JSON objects, one per line, also written to --log-file.",
+
 		["Footprint Options"]
 			FLAG flag_scale_verifiers: (bool) = false, or |c: &Config| c.footprint.as_ref()?.scale_verifiers.clone(),
 			"--scale-verifiers",
@@ -913,6 +1087,14 @@ usage! {
 			"--num-verifiers=[INT]",
 			"Amount of verifier threads to use or to begin with, if verifier auto-scaling is enabled.",
 
+			FLAG flag_no_auto_rollback: (bool) = false, or |c: &Config| c.footprint.as_ref()?.no_auto_rollback.clone(),
+			"--no-auto-rollback",
+			"Don't roll back to the most recent consistent block if the best block is found to be inconsistent at startup. Startup will fail instead.",
+
+			ARG arg_auto_rollback_depth: (u64) = 10u64, or |c: &Config| c.footprint.as_ref()?.auto_rollback_depth.clone(),
+			"--auto-rollback-depth=[NUM]",
+			"Maximal number of blocks to roll back at startup when looking for a consistent best block.",
+
 		["Import/export Options"]
 			FLAG flag_no_seal_check: (bool) = false, or |_| None,
 			"--no-seal-check",
@@ -936,6 +1118,42 @@ usage! {
 			"--whisper-pool-size=[MB]",
 			"Target size of the whisper message pool in megabytes.",
 
+			ARG arg_whisper_min_pow: (f64) = 0f64, or |c: &Config| c.whisper.as_ref()?.min_pow.clone(),
+			"--whisper-min-pow=[POW]",
+			"Minimum proof-of-work of whisper envelopes accepted for relaying. Advertised to peers.",
+
+			ARG arg_whisper_max_message_size: (usize) = 8192usize, or |c: &Config| c.whisper.as_ref()?.max_message_size.clone(),
+			"--whisper-max-message-size=[KB]",
+			"Maximum size of a single whisper envelope accepted for relaying in kilobytes.",
+
+			ARG arg_whisper_peer_envelopes: (u64) = 0u64, or |c: &Config| c.whisper.as_ref()?.peer_envelopes.clone(),
+			"--whisper-peer-envelopes=[NUM]",
+			"Maximum number of whisper envelopes accepted from a single peer per second. Peers exceeding it are ignored for a while and disconnected when they keep doing so. 0 means no limit.",
+
+			ARG arg_whisper_peer_bandwidth: (u64) = 0u64, or |c: &Config| c.whisper.as_ref()?.peer_bandwidth.clone(),
+			"--whisper-peer-bandwidth=[KB]",
+			"Maximum size of whisper envelopes accepted from a single peer per second in kilobytes. 0 means no limit.",
+
+			FLAG flag_whisper_light: (bool) = false, or |c: &Config| c.whisper.as_ref()?.light,
+			"--whisper-light",
+			"Run whisper as a light node, which only receives envelopes matching its filters and doesn't relay envelopes of other peers.",
+
+			FLAG flag_whisper_mail_server: (bool) = false, or |c: &Config| c.whisper.as_ref()?.mail_server,
+			"--whisper-mail-server",
+			"Run whisper as a mail server, which stores relayed envelopes and serves them to peers requesting historical envelopes. Requires --whisper-mail-server-key.",
+
+			ARG arg_whisper_mail_server_key: (Option<String>) = None, or |c: &Config| c.whisper.as_ref()?.mail_server_key.clone(),
+			"--whisper-mail-server-key=[KEY]",
+			"Hex-encoded 32-byte symmetric key authenticating requests for historical envelopes.",
+
+			ARG arg_whisper_mail_server_size: (usize) = 100usize, or |c: &Config| c.whisper.as_ref()?.mail_server_size.clone(),
+			"--whisper-mail-server-size=[MB]",
+			"Target size of envelopes stored by the whisper mail server in megabytes.",
+
+			ARG arg_whisper_mail_server_retention: (u64) = 30u64, or |c: &Config| c.whisper.as_ref()?.mail_server_retention.clone(),
+			"--whisper-mail-server-retention=[DAYS]",
+			"Number of days the whisper mail server keeps envelopes after they expire.",
+
 		["Legacy Options"]
 			// Options that are hidden from config, but are still unique for its functionality.
 
@@ -1139,6 +1357,28 @@ usage! {
 			ARG arg_ntp_servers: (Option<String>) = None, or |_| None,
 			"--ntp-servers=[HOSTS]",
 			"Does nothing; checking if clock is sync with NTP servers is now done on the UI.",
+
+			// ARG deprecated in 2.6.
+
+			ARG arg_secretstore_contract: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.service_contract.clone(),
+			"--secretstore-contract=[SOURCE]",
+			"Deprecated. Use --secretstore-service-contracts=all:SOURCE instead.",
+
+			ARG arg_secretstore_srv_gen_contract: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.service_contract_srv_gen.clone(),
+			"--secretstore-srv-gen-contract=[SOURCE]",
+			"Deprecated. Use --secretstore-service-contracts=srv_gen:SOURCE instead.",
+
+			ARG arg_secretstore_srv_retr_contract: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.service_contract_srv_retr.clone(),
+			"--secretstore-srv-retr-contract=[SOURCE]",
+			"Deprecated. Use --secretstore-service-contracts=srv_retr:SOURCE instead.",
+
+			ARG arg_secretstore_doc_store_contract: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.service_contract_doc_store.clone(),
+			"--secretstore-doc-store-contract=[SOURCE]",
+			"Deprecated. Use --secretstore-service-contracts=doc_store:SOURCE instead.",
+
+			ARG arg_secretstore_doc_sretr_contract: (Option<String>) = None, or |c: &Config| c.secretstore.as_ref()?.service_contract_doc_sretr.clone(),
+			"--secretstore-doc-sretr-contract=[SOURCE]",
+			"Deprecated. Use --secretstore-service-contracts=doc_sretr:SOURCE instead.",
 	}
 }
 
@@ -1184,6 +1424,9 @@ struct Operating {
 	identity: Option<String>,
 	light: Option<bool>,
 	no_persistent_txqueue: Option<bool>,
+	local_store_interval: Option<u64>,
+	local_store: Option<String>,
+	shutdown_timeout: Option<u64>,
 	no_hardcoded_sync: Option<bool>,
 
 	#[serde(rename = "public_node")]
@@ -1196,9 +1439,16 @@ struct Account {
 	unlock: Option<Vec<String>>,
 	password: Option<Vec<String>>,
 	keys_iterations: Option<u32>,
+	keys_kdf: Option<String>,
+	keys_scrypt_n: Option<u32>,
+	keys_scrypt_r: Option<u32>,
+	keys_scrypt_p: Option<u32>,
 	refresh_time: Option<u64>,
 	disable_hardware: Option<bool>,
 	fast_unlock: Option<bool>,
+	autolock: Option<u64>,
+	allow_permanent_unlock: Option<bool>,
+	extra_keys_path: Option<Vec<String>>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1217,6 +1467,7 @@ struct PrivateTransactions {
 #[serde(deny_unknown_fields)]
 struct Ui {
 	path: Option<String>,
+	confirmation_timeout: Option<u64>,
 
 	#[serde(rename = "force")]
 	_legacy_force: Option<bool>,
@@ -1250,6 +1501,13 @@ struct Network {
 	reserved_peers: Option<String>,
 	reserved_only: Option<bool>,
 	no_serve_light: Option<bool>,
+	light_serve_load: Option<u8>,
+	light_serve_credit_seconds: Option<u64>,
+	light_serve_rate: Option<u64>,
+	light_serve_peer_rate: Option<u64>,
+	light_serve_reserved_share: Option<u8>,
+	light_request_credits: Option<u64>,
+	light_request_recharge: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1317,12 +1575,16 @@ struct SecretStore {
 	disable: Option<bool>,
 	disable_http: Option<bool>,
 	disable_auto_migrate: Option<bool>,
+	auto_migrate: Option<String>,
 	acl_contract: Option<String>,
+	acl_cache_ttl: Option<u64>,
+	batch_parallelism: Option<usize>,
 	service_contract: Option<String>,
 	service_contract_srv_gen: Option<String>,
 	service_contract_srv_retr: Option<String>,
 	service_contract_doc_store: Option<String>,
 	service_contract_doc_sretr: Option<String>,
+	service_contracts: Option<Vec<String>>,
 	self_secret: Option<String>,
 	admin_public: Option<String>,
 	nodes: Option<Vec<String>>,
@@ -1331,6 +1593,9 @@ struct SecretStore {
 	port: Option<u16>,
 	http_interface: Option<String>,
 	http_port: Option<u16>,
+	http_cert: Option<String>,
+	http_key: Option<String>,
+	http_client_ca: Option<String>,
 	path: Option<String>,
 	cors: Option<Vec<String>>
 }
@@ -1409,6 +1674,8 @@ struct Footprint {
 	fat_db: Option<String>,
 	scale_verifiers: Option<bool>,
 	num_verifiers: Option<usize>,
+	no_auto_rollback: Option<bool>,
+	auto_rollback_depth: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1423,6 +1690,11 @@ struct Snapshots {
 struct Misc {
 	logging: Option<String>,
 	log_file: Option<String>,
+	log_file_size: Option<u64>,
+	log_file_daily: Option<bool>,
+	log_file_keep: Option<usize>,
+	log_file_compress: Option<bool>,
+	json_logs: Option<bool>,
 	color: Option<bool>,
 	ports_shift: Option<u16>,
 	unsafe_expose: Option<bool>,
@@ -1433,16 +1705,29 @@ struct Misc {
 struct Whisper {
 	enabled: Option<bool>,
 	pool_size: Option<usize>,
+	min_pow: Option<f64>,
+	max_message_size: Option<usize>,
+	peer_envelopes: Option<u64>,
+	peer_bandwidth: Option<u64>,
+	light: Option<bool>,
+	mail_server: Option<bool>,
+	mail_server_key: Option<String>,
+	mail_server_size: Option<usize>,
+	mail_server_retention: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct Light {
 	on_demand_response_time_window: Option<u64>,
+	on_demand_request_timeout: Option<u64>,
+	on_demand_request_attempts_max: Option<usize>,
 	on_demand_request_backoff_start: Option<u64>,
 	on_demand_request_backoff_max: Option<u64>,
 	on_demand_request_backoff_rounds_max: Option<usize>,
 	on_demand_request_consecutive_failures: Option<usize>,
+	checkpoint: Option<String>,
+	call_cache_size: Option<usize>,
 }
 
 #[cfg(test)]
@@ -1551,6 +1836,9 @@ mod tests {
 		let args = Args::parse(&["parity", "--password", "~/.safe/1,~/.safe/2", "--ui-port", "8123"]).unwrap();
 		assert_eq!(args.arg_password, vec!["~/.safe/1".to_owned(), "~/.safe/2".to_owned()]);
 		assert_eq!(args.arg_ui_port, Some(8123));
+
+		let args = Args::parse(&["parity", "--extra-keys-path", "/mnt/keys", "--extra-keys-path", "/mnt/shared"]).unwrap();
+		assert_eq!(args.arg_extra_keys_path, vec!["/mnt/keys".to_owned(), "/mnt/shared".to_owned()]);
 	}
 
 	#[test]
@@ -1697,6 +1985,8 @@ mod tests {
 			cmd_account_new: false,
 			cmd_account_list: false,
 			cmd_account_import: false,
+			cmd_account_backup: false,
+			cmd_account_restore: false,
 			cmd_wallet: false,
 			cmd_wallet_import: false,
 			cmd_import: false,
@@ -1715,6 +2005,10 @@ mod tests {
 			cmd_db: false,
 			cmd_db_kill: false,
 			cmd_db_reset: false,
+			cmd_secretstore: false,
+			cmd_secretstore_backup: false,
+			cmd_secretstore_restore: false,
+			cmd_secretstore_verify: false,
 			cmd_export_hardcoded_sync: false,
 
 			// Arguments
@@ -1732,9 +2026,16 @@ mod tests {
 			arg_signer_sign_id: None,
 			arg_signer_reject_id: None,
 			arg_dapp_path: None,
+			flag_account_new_mnemonic: false,
 			arg_account_import_path: None,
+			arg_account_backup_file: None,
+			flag_account_restore_force: false,
+			arg_account_restore_file: None,
 			arg_wallet_import_path: None,
 			arg_db_reset_num: 10,
+			arg_secretstore_backup_file: None,
+			flag_secretstore_restore_force: false,
+			arg_secretstore_restore_file: None,
 
 			// -- Operating Options
 			arg_mode: "last".into(),
@@ -1750,11 +2051,14 @@ mod tests {
 			arg_chain: "xyz".into(),
 			arg_base_path: Some("$HOME/.parity".into()),
 			arg_db_path: Some("$HOME/.parity/chains".into()),
+			arg_shutdown_timeout: 300u64,
 			arg_keys_path: "$HOME/.parity/keys".into(),
 			arg_identity: "".into(),
 			flag_light: false,
 			flag_no_hardcoded_sync: false,
 			flag_no_persistent_txqueue: false,
+			arg_local_store_interval: 900u64,
+			arg_local_store: "local,service".into(),
 			flag_force_direct: false,
 
 			// -- Convenience Options
@@ -1765,10 +2069,17 @@ mod tests {
 			// -- Account Options
 			arg_unlock: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
 			arg_password: vec!["~/.safe/password.file".into()],
+			arg_extra_keys_path: Vec::new(),
 			arg_keys_iterations: 10240u32,
+			arg_keys_kdf: "pbkdf2".into(),
+			arg_keys_scrypt_n: 262144u32,
+			arg_keys_scrypt_r: 8u32,
+			arg_keys_scrypt_p: 1u32,
 			arg_accounts_refresh: 5u64,
 			flag_no_hardware_wallets: false,
 			flag_fast_unlock: false,
+			arg_accounts_autolock: None,
+			flag_allow_permanent_unlock: false,
 
 			// -- Private Transactions Options
 			flag_private_enabled: true,
@@ -1785,6 +2096,7 @@ mod tests {
 			arg_ui_interface: None,
 			arg_ui_hosts: None,
 			arg_ui_path: "$HOME/.parity/signer".into(),
+			arg_ui_confirmation_timeout: None,
 			flag_ui_no_validation: false,
 
 			// -- Networking Options
@@ -1805,6 +2117,13 @@ mod tests {
 			flag_reserved_only: false,
 			flag_no_ancient_blocks: false,
 			flag_no_serve_light: false,
+			arg_light_serve_load: 40u8,
+			arg_light_serve_credit_seconds: 600u64,
+			arg_light_serve_rate: 1000u64,
+			arg_light_serve_peer_rate: 100u64,
+			arg_light_serve_reserved_share: 30u8,
+			arg_light_request_credits: Some(1000000u64),
+			arg_light_request_recharge: Some(1000u64),
 
 			// -- API and Console Options
 			// RPC
@@ -1844,12 +2163,11 @@ mod tests {
 			flag_no_secretstore: false,
 			flag_no_secretstore_http: false,
 			flag_no_secretstore_auto_migrate: false,
+			arg_secretstore_auto_migrate: "none".into(),
 			arg_secretstore_acl_contract: Some("registry".into()),
-			arg_secretstore_contract: Some("none".into()),
-			arg_secretstore_srv_gen_contract: Some("none".into()),
-			arg_secretstore_srv_retr_contract: Some("none".into()),
-			arg_secretstore_doc_store_contract: Some("none".into()),
-			arg_secretstore_doc_sretr_contract: Some("none".into()),
+			arg_secretstore_acl_cache_ttl: 300u64,
+			arg_secretstore_batch_parallelism: 8usize,
+			arg_secretstore_service_contracts: Some("srv_gen:registry,-doc_sretr:0x0000000000000000000000000000000000000001".into()),
 			arg_secretstore_secret: None,
 			arg_secretstore_admin_public: None,
 			arg_secretstore_nodes: "".into(),
@@ -1858,6 +2176,9 @@ mod tests {
 			arg_secretstore_port: 8083u16,
 			arg_secretstore_http_interface: "local".into(),
 			arg_secretstore_http_port: 8082u16,
+			arg_secretstore_http_cert: Some("$HOME/.parity/secretstore/http.pem".into()),
+			arg_secretstore_http_key: Some("$HOME/.parity/secretstore/http.key".into()),
+			arg_secretstore_http_client_ca: None,
 			arg_secretstore_path: "$HOME/.parity/secretstore".into(),
 			arg_secretstore_http_cors: "null".into(),
 
@@ -1923,6 +2244,8 @@ mod tests {
 			arg_fat_db: "auto".into(),
 			flag_scale_verifiers: true,
 			arg_num_verifiers: Some(6),
+			flag_no_auto_rollback: false,
+			arg_auto_rollback_depth: 10u64,
 
 			// -- Import/Export Options
 			arg_export_blocks_from: "1".into(),
@@ -1941,14 +2264,27 @@ mod tests {
 
 			// -- Light options.
 			arg_on_demand_response_time_window: Some(2),
+			arg_on_demand_request_timeout: Some(5),
+			arg_on_demand_request_attempts_max: Some(4),
 			arg_on_demand_request_backoff_start: Some(9),
 			arg_on_demand_request_backoff_max: Some(15),
 			arg_on_demand_request_backoff_rounds_max: Some(100),
 			arg_on_demand_request_consecutive_failures: Some(1),
+			arg_light_checkpoint: Some("$HOME/checkpoint.json".into()),
+			arg_light_call_cache_size: 256usize,
 
 			// -- Whisper options.
 			flag_whisper: false,
 			arg_whisper_pool_size: 20,
+			arg_whisper_min_pow: 0.5,
+			arg_whisper_max_message_size: 1024,
+			arg_whisper_peer_envelopes: 100,
+			arg_whisper_peer_bandwidth: 512,
+			flag_whisper_light: false,
+			flag_whisper_mail_server: false,
+			arg_whisper_mail_server_key: None,
+			arg_whisper_mail_server_size: 200,
+			arg_whisper_mail_server_retention: 7,
 
 			// -- Legacy Options
 			flag_warp: false,
@@ -1992,9 +2328,19 @@ mod tests {
 
 			// -- Miscellaneous Options
 			arg_ntp_servers: None,
+			arg_secretstore_contract: Some("none".into()),
+			arg_secretstore_srv_gen_contract: Some("none".into()),
+			arg_secretstore_srv_retr_contract: Some("none".into()),
+			arg_secretstore_doc_store_contract: Some("none".into()),
+			arg_secretstore_doc_sretr_contract: Some("none".into()),
 			flag_version: false,
 			arg_logging: Some("own_tx=trace".into()),
 			arg_log_file: Some("/var/log/parity.log".into()),
+			arg_log_file_size: None,
+			flag_log_file_daily: false,
+			arg_log_file_keep: 5usize,
+			flag_log_file_compress: false,
+			flag_json_logs: false,
 			flag_no_color: false,
 			flag_no_config: false,
 		});
@@ -2043,18 +2389,29 @@ mod tests {
 				light: None,
 				no_hardcoded_sync: None,
 				no_persistent_txqueue: None,
+				local_store_interval: None,
+				local_store: None,
+				shutdown_timeout: None,
 				_legacy_public_node: None,
 			}),
 			account: Some(Account {
 				unlock: Some(vec!["0x1".into(), "0x2".into(), "0x3".into()]),
 				password: Some(vec!["passwdfile path".into()]),
 				keys_iterations: None,
+				keys_kdf: None,
+				keys_scrypt_n: None,
+				keys_scrypt_r: None,
+				keys_scrypt_p: None,
 				refresh_time: None,
 				disable_hardware: None,
 				fast_unlock: None,
+				autolock: None,
+				allow_permanent_unlock: None,
+				extra_keys_path: None,
 			}),
 			ui: Some(Ui {
 				path: None,
+				confirmation_timeout: None,
 				_legacy_force: None,
 				_legacy_disable: Some(true),
 				_legacy_port: None,
@@ -2079,6 +2436,13 @@ mod tests {
 				reserved_peers: Some("./path/to/reserved_peers".into()),
 				reserved_only: Some(true),
 				no_serve_light: None,
+				light_serve_load: None,
+				light_serve_credit_seconds: None,
+				light_serve_rate: None,
+				light_serve_peer_rate: None,
+				light_serve_reserved_share: None,
+				light_request_credits: None,
+				light_request_recharge: None,
 			}),
 			websockets: Some(Ws {
 				disable: Some(true),
@@ -2123,12 +2487,16 @@ mod tests {
 				disable: None,
 				disable_http: None,
 				disable_auto_migrate: None,
+				auto_migrate: None,
 				acl_contract: None,
+				acl_cache_ttl: None,
+				batch_parallelism: None,
 				service_contract: None,
 				service_contract_srv_gen: None,
 				service_contract_srv_retr: None,
 				service_contract_doc_store: None,
 				service_contract_doc_sretr: None,
+				service_contracts: None,
 				self_secret: None,
 				admin_public: None,
 				nodes: None,
@@ -2137,6 +2505,9 @@ mod tests {
 				port: Some(8083),
 				http_interface: None,
 				http_port: Some(8082),
+				http_cert: None,
+				http_key: None,
+				http_client_ca: None,
 				path: None,
 				cors: None,
 			}),
@@ -2198,13 +2569,19 @@ mod tests {
 				fat_db: Some("off".into()),
 				scale_verifiers: Some(false),
 				num_verifiers: None,
+				no_auto_rollback: None,
+				auto_rollback_depth: None,
 			}),
 			light: Some(Light {
 				on_demand_response_time_window: Some(2),
+				on_demand_request_timeout: None,
+				on_demand_request_attempts_max: None,
 				on_demand_request_backoff_start: Some(9),
 				on_demand_request_backoff_max: Some(15),
 				on_demand_request_backoff_rounds_max: Some(10),
 				on_demand_request_consecutive_failures: Some(1),
+				checkpoint: None,
+				call_cache_size: None,
 			}),
 			snapshots: Some(Snapshots {
 				disable_periodic: Some(true),
@@ -2213,6 +2590,11 @@ mod tests {
 			misc: Some(Misc {
 				logging: Some("own_tx=trace".into()),
 				log_file: Some("/var/log/parity.log".into()),
+				log_file_size: Some(100),
+				log_file_daily: None,
+				log_file_keep: Some(10),
+				log_file_compress: Some(true),
+				json_logs: None,
 				color: Some(true),
 				ports_shift: Some(0),
 				unsafe_expose: Some(false),
@@ -2220,6 +2602,15 @@ mod tests {
 			whisper: Some(Whisper {
 				enabled: Some(true),
 				pool_size: Some(50),
+				min_pow: None,
+				max_message_size: None,
+				peer_envelopes: None,
+				peer_bandwidth: None,
+				light: None,
+				mail_server: None,
+				mail_server_key: None,
+				mail_server_size: None,
+				mail_server_retention: None,
 			}),
 			stratum: None,
 		});
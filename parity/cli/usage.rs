@@ -135,7 +135,7 @@ macro_rules! usage {
 		}
 	) => {
 		use toml;
-		use std::{fs, io, process, cmp};
+		use std::{fmt, fs, io, process, cmp};
 		use std::io::Read;
 		use parity_version::version;
 		use clap::{Arg, App, SubCommand, AppSettings, ArgSettings, Error as ClapError, ErrorKind as ClapErrorKind};
@@ -182,6 +182,17 @@ macro_rules! usage {
 			}
 		}
 
+		impl fmt::Display for ArgsError {
+			fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				match *self {
+					ArgsError::Clap(ref e) => write!(f, "{}", e),
+					ArgsError::Decode(ref e) => write!(f, "Invalid parameters in config file: {}", e),
+					ArgsError::Config(ref path, ref e) => write!(f, "Error reading config file at {}: {}", path, e),
+					ArgsError::PeerConfiguration => write!(f, "You have supplied `min_peers` > `max_peers`"),
+				}
+			}
+		}
+
 		impl From<ClapError> for ArgsError {
 			fn from(e: ClapError) -> Self {
 				ArgsError::Clap(e)
@@ -195,7 +206,7 @@ macro_rules! usage {
 		}
 
 		/// Parsed command line arguments.
-		#[derive(Debug, PartialEq)]
+		#[derive(Debug, PartialEq, Clone)]
 		pub struct Args {
 			$(
 				pub $subc: bool,
@@ -364,6 +375,25 @@ macro_rules! usage {
 				Ok(toml::from_str(config)?)
 			}
 
+			/// Names of the options, as on the command line, whose values differ from `other`.
+			/// Subcommands and their arguments are not compared.
+			pub fn changed_options(&self, other: &Args) -> Vec<String> {
+				let mut changed = Vec::new();
+				$(
+					$(
+						if self.$flag != other.$flag {
+							changed.push(underscore_to_hyphen!(&stringify!($flag)[5..]));
+						}
+					)*
+					$(
+						if self.$arg != other.$arg {
+							changed.push(underscore_to_hyphen!(&stringify!($arg)[4..]));
+						}
+					)*
+				)*
+				changed
+			}
+
 			pub fn print_version() -> String {
 				format!(include_str!("./version.txt"), version())
 			}
@@ -24,12 +24,13 @@ use std::iter::FromIterator;
 use std::cmp;
 use cli::{Args, ArgsError};
 use hash::keccak;
-use ethereum_types::{U256, H256, Address};
+use ethereum_types::{U256, H256, H512, Address};
 use parity_version::{version_data, version};
 use bytes::Bytes;
 use ansi_term::Colour;
 use sync::{NetworkConfiguration, validate_node_url, self};
 use ethkey::{Secret, Public};
+use ethstore::KdfConfig;
 use ethcore::client::{VMType};
 use ethcore::miner::{stratum, MinerOptions};
 use ethcore::snapshot::SnapshotConfiguration;
@@ -43,19 +44,26 @@ use cache::CacheConfig;
 use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_queue_strategy, to_queue_penalization};
 use dir::helpers::{replace_home, replace_home_and_local};
 use params::{ResealPolicy, AccountsConfig, GasPricerConfig, MinerExtras, SpecType};
-use ethcore_logger::Config as LogConfig;
+use ethcore_logger::{Config as LogConfig, FileRotation};
 use dir::{self, Directories, default_hypervisor_path, default_local_path, default_data_path};
 use ipfs::Configuration as IpfsConfiguration;
+use local_store::{StoreConfig as LocalStoreConfig, Categories as LocalStoreCategories};
 use ethcore_private_tx::{ProviderConfig, EncryptorConfig};
-use secretstore::{NodeSecretKey, Configuration as SecretStoreConfiguration, ContractAddress as SecretStoreContractAddress};
+use secretstore::{NodeSecretKey, Configuration as SecretStoreConfiguration, ContractAddress as SecretStoreContractAddress,
+	ServiceContract as SecretStoreServiceContract, ServiceContractKind as SecretStoreServiceContractKind,
+	HttpTlsConfiguration as SecretStoreHttpTlsConfiguration, AdminMigrationMode as SecretStoreAdminMigrationMode,
+	SecretStoreCmd, SecretStoreCommand};
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 use run::RunCmd;
+use output::OutputFormat;
 use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, DataFormat, ResetBlockchain};
 use export_hardcoded_sync::ExportHsyncCmd;
 use presale::ImportWallet;
-use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts};
+use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts, BackupAccounts, RestoreAccounts};
 use snapshot::{self, SnapshotCommand};
 use network::{IpFilter};
+use light::net::ServeLimits;
+use light::net::request_credits::CreditsRequest;
 
 const DEFAULT_MAX_PEERS: u16 = 50;
 const DEFAULT_MIN_PEERS: u16 = 25;
@@ -86,6 +94,7 @@ pub enum Cmd {
 	Snapshot(SnapshotCommand),
 	Hash(Option<String>),
 	ExportHardcodedSync(ExportHsyncCmd),
+	SecretStore(SecretStoreCommand),
 }
 
 pub struct Execute {
@@ -94,10 +103,12 @@ pub struct Execute {
 }
 
 /// Configuration for the Parity client.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Configuration {
 	/// Arguments to be interpreted.
 	pub args: Args,
+	/// Command line the arguments were parsed from.
+	command: Vec<String>,
 }
 
 impl Configuration {
@@ -111,11 +122,18 @@ impl Configuration {
 	pub fn parse_cli<S: AsRef<str>>(command: &[S]) -> Result<Self, ArgsError> {
 		let config = Configuration {
 			args: Args::parse(command)?,
+			command: command.iter().map(|arg| arg.as_ref().to_owned()).collect(),
 		};
 
 		Ok(config)
 	}
 
+	/// Parses the configuration again from the same command line,
+	/// picking up changes of the config file.
+	pub fn reload(&self) -> Result<Self, ArgsError> {
+		Self::parse_cli(&self.command[..])
+	}
+
 	pub(crate) fn into_command(self) -> Result<Execute, String> {
 		let dirs = self.directories();
 		let pruning = self.args.arg_pruning.parse()?;
@@ -128,6 +146,7 @@ impl Configuration {
 		};
 		let update_policy = self.update_policy()?;
 		let logger_config = self.logger_config();
+		let output_format = OutputFormat::json_if(self.args.flag_json_logs);
 		let ws_conf = self.ws_config()?;
 		let snapshot_conf = self.snapshot_config()?;
 		let http_conf = self.http_config()?;
@@ -144,8 +163,7 @@ impl Configuration {
 		let ipfs_conf = self.ipfs_config();
 		let secretstore_conf = self.secretstore_config()?;
 		let format = self.format()?;
-		let keys_iterations = NonZeroU32::new(self.args.arg_keys_iterations)
-			.ok_or_else(|| "--keys-iterations must be non-zero")?;
+		let keys_kdf = self.keys_kdf()?;
 
 		let cmd = if self.args.flag_version {
 			Cmd::Version
@@ -192,20 +210,23 @@ impl Configuration {
 				compaction,
 				cache_config,
 				num: self.args.arg_db_reset_num,
+				output_format,
 			}))
 		} else if self.args.cmd_db && self.args.cmd_db_kill {
 			Cmd::Blockchain(BlockchainCmd::Kill(KillBlockchain {
 				spec: spec,
 				dirs: dirs,
 				pruning: pruning,
+				output_format: output_format,
 			}))
 		} else if self.args.cmd_account {
 			let account_cmd = if self.args.cmd_account_new {
 				let new_acc = NewAccount {
-					iterations: keys_iterations,
+					kdf: keys_kdf,
 					path: dirs.keys,
 					spec: spec,
 					password_file: self.accounts_config()?.password_files.first().map(|x| x.to_owned()),
+					mnemonic: self.args.flag_account_new_mnemonic,
 				};
 				AccountCmd::New(new_acc)
 			} else if self.args.cmd_account_list {
@@ -221,6 +242,24 @@ impl Configuration {
 					spec: spec,
 				};
 				AccountCmd::Import(import_acc)
+			} else if self.args.cmd_account_backup {
+				let backup = BackupAccounts {
+					kdf: keys_kdf,
+					path: dirs.keys,
+					spec: spec,
+					file: self.args.arg_account_backup_file.clone().ok_or("Please specify the backup file with --file")?,
+					password_file: self.accounts_config()?.password_files.first().map(|x| x.to_owned()),
+				};
+				AccountCmd::Backup(backup)
+			} else if self.args.cmd_account_restore {
+				let restore = RestoreAccounts {
+					path: dirs.keys,
+					spec: spec,
+					file: self.args.arg_account_restore_file.clone().ok_or("Please specify the backup file with --file")?,
+					password_file: self.accounts_config()?.password_files.first().map(|x| x.to_owned()),
+					force: self.args.flag_account_restore_force,
+				};
+				AccountCmd::Restore(restore)
 			} else {
 				unreachable!();
 			};
@@ -236,7 +275,7 @@ impl Configuration {
 			Cmd::Account(account_cmd)
 		} else if self.args.cmd_wallet {
 			let presale_cmd = ImportWallet {
-				iterations: keys_iterations,
+				kdf: keys_kdf,
 				path: dirs.keys,
 				spec: spec,
 				wallet_path: self.args.arg_wallet_import_path.clone().unwrap(),
@@ -262,6 +301,7 @@ impl Configuration {
 				verifier_settings: self.verifier_settings(),
 				light: self.args.flag_light,
 				max_round_blocks_to_import: self.args.arg_max_round_blocks_to_import,
+				output_format: output_format,
 			};
 			Cmd::Blockchain(BlockchainCmd::Import(import_cmd))
 		} else if self.args.cmd_export {
@@ -282,6 +322,7 @@ impl Configuration {
 					to_block: to_block_id(&self.args.arg_export_blocks_to)?,
 					check_seal: !self.args.flag_no_seal_check,
 					max_round_blocks_to_import: self.args.arg_max_round_blocks_to_import,
+					output_format: output_format,
 				};
 				Cmd::Blockchain(BlockchainCmd::Export(export_cmd))
 			} else if self.args.cmd_export_state {
@@ -303,6 +344,7 @@ impl Configuration {
 					min_balance: self.args.arg_export_state_min_balance.and_then(|s| to_u256(&s).ok()),
 					max_balance: self.args.arg_export_state_max_balance.and_then(|s| to_u256(&s).ok()),
 					max_round_blocks_to_import: self.args.arg_max_round_blocks_to_import,
+					output_format: output_format,
 				};
 				Cmd::Blockchain(BlockchainCmd::ExportState(export_cmd))
 			} else {
@@ -353,6 +395,28 @@ impl Configuration {
 				compaction: compaction,
 			};
 			Cmd::ExportHardcodedSync(export_hs_cmd)
+		} else if self.args.cmd_secretstore {
+			let secretstore_cmd = if self.args.cmd_secretstore_backup {
+				SecretStoreCmd::Backup {
+					file: self.args.arg_secretstore_backup_file.clone().ok_or("Please specify the backup file with --file")?,
+				}
+			} else if self.args.cmd_secretstore_restore {
+				SecretStoreCmd::Restore {
+					file: self.args.arg_secretstore_restore_file.clone().ok_or("Please specify the backup file with --file")?,
+					force: self.args.flag_secretstore_restore_force,
+				}
+			} else if self.args.cmd_secretstore_verify {
+				SecretStoreCmd::Verify
+			} else {
+				unreachable!();
+			};
+			Cmd::SecretStore(SecretStoreCommand {
+				cmd: secretstore_cmd,
+				conf: secretstore_conf,
+				spec: spec,
+				dirs: dirs,
+				acc_conf: self.accounts_config()?,
+			})
 		} else {
 			let daemon = if self.args.cmd_daemon {
 				Some(self.args.arg_daemon_pid_file.clone().expect("CLI argument is required; qed"))
@@ -361,7 +425,7 @@ impl Configuration {
 			};
 
 			let verifier_settings = self.verifier_settings();
-			let whisper_config = self.whisper_config();
+			let whisper_config = self.whisper_config()?;
 			let (private_provider_conf, private_enc_conf, private_tx_enabled) = self.private_provider_config()?;
 
 			let run_cmd = RunCmd {
@@ -409,16 +473,29 @@ impl Configuration {
 				download_old_blocks: !self.args.flag_no_ancient_blocks,
 				verifier_settings: verifier_settings,
 				serve_light: !self.args.flag_no_serve_light,
+				light_serve_load: self.light_serve_load()?,
+				light_serve_credit_seconds: self.args.arg_light_serve_credit_seconds,
+				light_serve_limits: self.light_serve_limits()?,
+				light_credits_request: self.light_credits_request()?,
 				light: self.args.flag_light,
 				no_persistent_txqueue: self.args.flag_no_persistent_txqueue,
+				local_store: self.local_store_config()?,
 				whisper: whisper_config,
 				no_hardcoded_sync: self.args.flag_no_hardcoded_sync,
 				max_round_blocks_to_import: self.args.arg_max_round_blocks_to_import,
+				auto_rollback: if self.args.flag_no_auto_rollback { None } else { Some(self.args.arg_auto_rollback_depth) },
 				on_demand_response_time_window: self.args.arg_on_demand_response_time_window,
+				on_demand_request_timeout: self.args.arg_on_demand_request_timeout,
+				on_demand_request_attempts_max: self.args.arg_on_demand_request_attempts_max,
 				on_demand_request_backoff_start: self.args.arg_on_demand_request_backoff_start,
 				on_demand_request_backoff_max: self.args.arg_on_demand_request_backoff_max,
 				on_demand_request_backoff_rounds_max: self.args.arg_on_demand_request_backoff_rounds_max,
 				on_demand_request_consecutive_failures: self.args.arg_on_demand_request_consecutive_failures,
+				light_checkpoint: self.args.arg_light_checkpoint.as_ref().map(|path| replace_home(&self.directories().base, path)),
+				light_call_cache_size: self.args.arg_light_call_cache_size,
+				shutdown_timeout: Duration::from_secs(self.args.arg_shutdown_timeout),
+				configuration: self.clone(),
+				output_format: output_format,
 			};
 			Cmd::Run(run_cmd)
 		};
@@ -483,6 +560,12 @@ impl Configuration {
 			mode: self.args.arg_logging.clone(),
 			color: !self.args.flag_no_color && !cfg!(windows),
 			file: self.args.arg_log_file.as_ref().map(|log_file| replace_home(&self.directories().base, log_file)),
+			file_rotation: FileRotation {
+				max_size: self.args.arg_log_file_size.map(|mb| mb * 1024 * 1024),
+				daily: self.args.flag_log_file_daily,
+				keep: self.args.arg_log_file_keep,
+				compress: self.args.flag_log_file_compress,
+			},
 		}
 	}
 
@@ -531,17 +614,60 @@ impl Configuration {
 		self.args.arg_notify_work.as_ref().map_or_else(Vec::new, |s| s.split(',').map(|s| s.to_owned()).collect())
 	}
 
+	fn keys_kdf(&self) -> Result<KdfConfig, String> {
+		match self.args.arg_keys_kdf.as_str() {
+			"pbkdf2" => NonZeroU32::new(self.args.arg_keys_iterations)
+				.map(KdfConfig::Pbkdf2)
+				.ok_or_else(|| "--keys-iterations must be non-zero".into()),
+			"scrypt" => KdfConfig::scrypt(self.args.arg_keys_scrypt_n, self.args.arg_keys_scrypt_r, self.args.arg_keys_scrypt_p)
+				.map_err(|e| format!("Invalid scrypt parameters: {}", e)),
+			other => Err(format!("Invalid key derivation function: {}. Expected one of: pbkdf2, scrypt", other)),
+		}
+	}
+
+	fn light_serve_load(&self) -> Result<f64, String> {
+		match self.args.arg_light_serve_load {
+			percent @ 1..=100 => Ok(percent as f64 / 100.0),
+			percent => Err(format!("Invalid light serving load: {}. Must be between 1 and 100", percent)),
+		}
+	}
+
+	fn light_serve_limits(&self) -> Result<ServeLimits, String> {
+		let reserved_share = match self.args.arg_light_serve_reserved_share {
+			percent @ 0..=100 => percent as f64 / 100.0,
+			percent => return Err(format!("Invalid reserved light serving share: {}. Must be between 0 and 100", percent)),
+		};
+
+		Ok(ServeLimits {
+			total: self.args.arg_light_serve_rate,
+			per_peer: self.args.arg_light_serve_peer_rate,
+			reserved_share,
+		})
+	}
+
+	fn light_credits_request(&self) -> Result<Option<CreditsRequest>, String> {
+		match (self.args.arg_light_request_credits, self.args.arg_light_request_recharge) {
+			(Some(limit), Some(recharge_rate)) => Ok(Some(CreditsRequest {
+				limit: limit.into(),
+				recharge_rate: recharge_rate.into(),
+			})),
+			(None, None) => Ok(None),
+			_ => Err("--light-request-credits and --light-request-recharge must be given together".into()),
+		}
+	}
+
 	fn accounts_config(&self) -> Result<AccountsConfig, String> {
-		let keys_iterations = NonZeroU32::new(self.args.arg_keys_iterations)
-			.ok_or_else(|| "--keys-iterations must be non-zero")?;
 		let cfg = AccountsConfig {
-			iterations: keys_iterations,
+			kdf: self.keys_kdf()?,
 			refresh_time: self.args.arg_accounts_refresh,
 			testnet: self.args.flag_testnet,
 			password_files: self.args.arg_password.iter().map(|s| replace_home(&self.directories().base, s)).collect(),
+			extra_keys_paths: self.args.arg_extra_keys_path.iter().map(|s| replace_home(&self.directories().base, s)).collect(),
 			unlocked_accounts: to_addresses(&self.args.arg_unlock)?,
 			enable_hardware_wallets: !self.args.flag_no_hardware_wallets,
 			enable_fast_unlock: self.args.flag_fast_unlock,
+			autolock: self.args.arg_accounts_autolock.map(Duration::from_secs),
+			allow_permanent_unlock: self.args.flag_allow_permanent_unlock,
 		};
 
 		Ok(cfg)
@@ -622,13 +748,12 @@ impl Configuration {
 		Ok(SecretStoreConfiguration {
 			enabled: self.secretstore_enabled(),
 			http_enabled: self.secretstore_http_enabled(),
-			auto_migrate_enabled: self.secretstore_auto_migrate_enabled(),
+			auto_migrate_enabled: self.secretstore_auto_migrate_enabled()?,
+			admin_migration_mode: self.secretstore_admin_migration_mode()?,
 			acl_check_contract_address: self.secretstore_acl_check_contract_address()?,
-			service_contract_address: self.secretstore_service_contract_address()?,
-			service_contract_srv_gen_address: self.secretstore_service_contract_srv_gen_address()?,
-			service_contract_srv_retr_address: self.secretstore_service_contract_srv_retr_address()?,
-			service_contract_doc_store_address: self.secretstore_service_contract_doc_store_address()?,
-			service_contract_doc_sretr_address: self.secretstore_service_contract_doc_sretr_address()?,
+			acl_check_cache_ttl: self.args.arg_secretstore_acl_cache_ttl,
+			batch_sessions_parallelism: self.args.arg_secretstore_batch_parallelism,
+			service_contracts: self.secretstore_service_contracts()?,
 			self_secret: self.secretstore_self_secret()?,
 			nodes: self.secretstore_nodes()?,
 			key_server_set_contract_address: self.secretstore_key_server_set_contract_address()?,
@@ -638,7 +763,37 @@ impl Configuration {
 			http_port: self.args.arg_ports_shift + self.args.arg_secretstore_http_port,
 			data_path: self.directories().secretstore,
 			admin_public: self.secretstore_admin_public()?,
-			cors: self.secretstore_cors()
+			cors: self.secretstore_cors(),
+			http_tls: self.secretstore_http_tls()?,
+		})
+	}
+
+	fn local_store_config(&self) -> Result<LocalStoreConfig, String> {
+		if self.args.arg_local_store_interval == 0 {
+			return Err("Local store interval must be greater than zero".into());
+		}
+
+		let mut categories = LocalStoreCategories {
+			local_transactions: false,
+			pool_journal: false,
+			service_transactions: false,
+		};
+		for category in self.args.arg_local_store.split(',') {
+			match category.trim() {
+				"local" => categories.local_transactions = true,
+				"pool" => categories.pool_journal = true,
+				"service" => categories.service_transactions = true,
+				"none" => {},
+				other => return Err(format!("Invalid local store category: {}", other)),
+			}
+		}
+		if self.args.flag_no_persistent_txqueue {
+			categories.local_transactions = false;
+		}
+
+		Ok(LocalStoreConfig {
+			interval: Duration::from_secs(self.args.arg_local_store_interval),
+			categories,
 		})
 	}
 
@@ -652,7 +807,7 @@ impl Configuration {
 		}
 	}
 
-	fn gas_pricer_config(&self) -> Result<GasPricerConfig, String> {
+	pub(crate) fn gas_pricer_config(&self) -> Result<GasPricerConfig, String> {
 		fn wei_per_gas(usd_per_tx: f32, usd_per_eth: f32) -> U256 {
 			let wei_per_usd: f32 = 1.0e18 / usd_per_eth;
 			let gas_per_tx: f32 = 21000.0;
@@ -696,7 +851,7 @@ impl Configuration {
 		}
 	}
 
-	fn init_reserved_nodes(&self) -> Result<Vec<String>, String> {
+	pub(crate) fn init_reserved_nodes(&self) -> Result<Vec<String>, String> {
 		use std::fs::File;
 
 		match self.args.arg_reserved_peers {
@@ -870,7 +1025,7 @@ impl Configuration {
 		Ok(conf)
 	}
 
-	fn http_config(&self) -> Result<HttpConfiguration, String> {
+	pub(crate) fn http_config(&self) -> Result<HttpConfiguration, String> {
 		let conf = HttpConfiguration {
 			enabled: self.rpc_enabled(),
 			interface: self.rpc_interface(),
@@ -893,7 +1048,7 @@ impl Configuration {
 		Ok(conf)
 	}
 
-	fn ws_config(&self) -> Result<WsConfiguration, String> {
+	pub(crate) fn ws_config(&self) -> Result<WsConfiguration, String> {
 		let support_token_api =
 			// enabled when not unlocking
 			self.args.arg_unlock.is_none();
@@ -907,6 +1062,7 @@ impl Configuration {
 			origins: self.ws_origins(),
 			signer_path: self.directories().signer.into(),
 			support_token_api,
+			confirmation_timeout: self.args.arg_ui_confirmation_timeout.map(Duration::from_secs),
 			max_connections: self.args.arg_ws_max_connections,
 		};
 
@@ -1063,6 +1219,21 @@ impl Configuration {
 		Self::cors(self.args.arg_secretstore_http_cors.as_ref())
 	}
 
+	fn secretstore_http_tls(&self) -> Result<Option<SecretStoreHttpTlsConfiguration>, String> {
+		let path = |path: &String| replace_home(&self.directories().base, path);
+		match (&self.args.arg_secretstore_http_cert, &self.args.arg_secretstore_http_key) {
+			(Some(cert), Some(key)) => Ok(Some(SecretStoreHttpTlsConfiguration {
+				certificate_path: path(cert),
+				private_key_path: path(key),
+				client_ca_path: self.args.arg_secretstore_http_client_ca.as_ref().map(path),
+			})),
+			(None, None) if self.args.arg_secretstore_http_client_ca.is_some() =>
+				Err("--secretstore-http-client-ca requires --secretstore-http-cert and --secretstore-http-key".into()),
+			(None, None) => Ok(None),
+			_ => Err("--secretstore-http-cert and --secretstore-http-key must be given together".into()),
+		}
+	}
+
 	fn secretstore_self_secret(&self) -> Result<Option<NodeSecretKey>, String> {
 		match self.args.arg_secretstore_secret {
 			Some(ref s) if s.len() == 64 => Ok(Some(NodeSecretKey::Plain(s.parse()
@@ -1126,32 +1297,60 @@ impl Configuration {
 		!self.args.flag_no_secretstore_http && cfg!(feature = "secretstore")
 	}
 
-	fn secretstore_auto_migrate_enabled(&self) -> bool {
-		!self.args.flag_no_secretstore_auto_migrate
-	}
-
-	fn secretstore_acl_check_contract_address(&self) -> Result<Option<SecretStoreContractAddress>, String> {
-		into_secretstore_service_contract_address(self.args.arg_secretstore_acl_contract.as_ref())
+	fn secretstore_auto_migrate_enabled(&self) -> Result<bool, String> {
+		// contract-driven migration is replaced with administrator-initiated migration
+		Ok(!self.args.flag_no_secretstore_auto_migrate
+			&& self.secretstore_admin_migration_mode()? == SecretStoreAdminMigrationMode::Disabled)
 	}
 
-	fn secretstore_service_contract_address(&self) -> Result<Option<SecretStoreContractAddress>, String> {
-		into_secretstore_service_contract_address(self.args.arg_secretstore_contract.as_ref())
+	fn secretstore_admin_migration_mode(&self) -> Result<SecretStoreAdminMigrationMode, String> {
+		match self.args.arg_secretstore_auto_migrate.as_str() {
+			"none" => Ok(SecretStoreAdminMigrationMode::Disabled),
+			"admin" => Ok(SecretStoreAdminMigrationMode::Enabled),
+			"dry-run" => Ok(SecretStoreAdminMigrationMode::DryRun),
+			mode => Err(format!("Invalid secretstore auto-migrate mode: {}. Expected one of: none, admin, dry-run.", mode)),
+		}
 	}
 
-	fn secretstore_service_contract_srv_gen_address(&self) -> Result<Option<SecretStoreContractAddress>, String> {
-		into_secretstore_service_contract_address(self.args.arg_secretstore_srv_gen_contract.as_ref())
+	fn secretstore_acl_check_contract_address(&self) -> Result<Option<SecretStoreContractAddress>, String> {
+		into_secretstore_service_contract_address(self.args.arg_secretstore_acl_contract.as_ref())
 	}
 
-	fn secretstore_service_contract_srv_retr_address(&self) -> Result<Option<SecretStoreContractAddress>, String> {
-		into_secretstore_service_contract_address(self.args.arg_secretstore_srv_retr_contract.as_ref())
-	}
+	fn secretstore_service_contracts(&self) -> Result<Vec<SecretStoreServiceContract>, String> {
+		let single_kind_contracts = vec![
+			(SecretStoreServiceContractKind::General, &self.args.arg_secretstore_contract),
+			(SecretStoreServiceContractKind::ServerKeyGeneration, &self.args.arg_secretstore_srv_gen_contract),
+			(SecretStoreServiceContractKind::ServerKeyRetrieval, &self.args.arg_secretstore_srv_retr_contract),
+			(SecretStoreServiceContractKind::DocumentKeyStore, &self.args.arg_secretstore_doc_store_contract),
+			(SecretStoreServiceContractKind::DocumentKeyShadowRetrieval, &self.args.arg_secretstore_doc_sretr_contract),
+		];
+
+		// deprecated single-kind options are listed before --secretstore-service-contracts entries
+		let mut contracts = Vec::new();
+		for (kind, source) in single_kind_contracts {
+			if let Some(address) = into_secretstore_service_contract_address(source.as_ref())? {
+				contracts.push(SecretStoreServiceContract { kind, address });
+			}
+		}
 
-	fn secretstore_service_contract_doc_store_address(&self) -> Result<Option<SecretStoreContractAddress>, String> {
-		into_secretstore_service_contract_address(self.args.arg_secretstore_doc_store_contract.as_ref())
-	}
+		if let Some(ref list) = self.args.arg_secretstore_service_contracts {
+			for contract in list.split(',').filter(|contract| !contract.is_empty()) {
+				match contract.starts_with('-') {
+					true => {
+						let disabled = into_secretstore_service_contract(&contract[1..])?;
+						contracts.retain(|configured| *configured != disabled);
+					},
+					false => {
+						let enabled = into_secretstore_service_contract(contract)?;
+						if !contracts.contains(&enabled) {
+							contracts.push(enabled);
+						}
+					},
+				}
+			}
+		}
 
-	fn secretstore_service_contract_doc_sretr_address(&self) -> Result<Option<SecretStoreContractAddress>, String> {
-		into_secretstore_service_contract_address(self.args.arg_secretstore_doc_sretr_contract.as_ref())
+		Ok(contracts)
 	}
 
 	fn secretstore_key_server_set_contract_address(&self) -> Result<Option<SecretStoreContractAddress>, String> {
@@ -1168,11 +1367,36 @@ impl Configuration {
 		settings
 	}
 
-	fn whisper_config(&self) -> ::whisper::Config {
-		::whisper::Config {
+	fn whisper_config(&self) -> Result<::whisper::Config, String> {
+		let min_pow = self.args.arg_whisper_min_pow;
+		if !min_pow.is_finite() || min_pow < 0.0 {
+			return Err(format!("Invalid whisper minimal PoW: {}. Must be a non-negative number", min_pow));
+		}
+
+		let mail_server = match (self.args.flag_whisper_mail_server, self.args.arg_whisper_mail_server_key.as_ref()) {
+			(false, _) => None,
+			(true, None) => return Err("Whisper mail server requires --whisper-mail-server-key".into()),
+			(true, Some(key)) => {
+				let key: H256 = key.parse().map_err(|e| format!("Invalid whisper mail server key: {}. Error: {:?}", key, e))?;
+				Some(::parity_whisper::mail_server::Config {
+					bloom: H512::from([0xff; 64]),
+					key: key.0,
+					max_size: self.args.arg_whisper_mail_server_size * 1024 * 1024,
+					retention: Duration::from_secs(self.args.arg_whisper_mail_server_retention * 24 * 60 * 60),
+				})
+			}
+		};
+
+		Ok(::whisper::Config {
 			enabled: self.args.flag_whisper,
 			target_message_pool_size: self.args.arg_whisper_pool_size * 1024 * 1024,
-		}
+			min_pow,
+			max_message_size: self.args.arg_whisper_max_message_size * 1024,
+			peer_envelopes_per_second: self.args.arg_whisper_peer_envelopes,
+			peer_bytes_per_second: self.args.arg_whisper_peer_bandwidth * 1024,
+			light: self.args.flag_whisper_light,
+			mail_server,
+		})
 	}
 }
 
@@ -1184,6 +1408,25 @@ fn into_secretstore_service_contract_address(s: Option<&String>) -> Result<Optio
 	}
 }
 
+fn into_secretstore_service_contract(s: &str) -> Result<SecretStoreServiceContract, String> {
+	let mut parts = s.splitn(2, ':');
+	let kind = match parts.next() {
+		Some("all") => SecretStoreServiceContractKind::General,
+		Some("srv_gen") => SecretStoreServiceContractKind::ServerKeyGeneration,
+		Some("srv_retr") => SecretStoreServiceContractKind::ServerKeyRetrieval,
+		Some("doc_store") => SecretStoreServiceContractKind::DocumentKeyStore,
+		Some("doc_sretr") => SecretStoreServiceContractKind::DocumentKeyShadowRetrieval,
+		_ => return Err(format!("Invalid Secret Store service contract kind: {}", s)),
+	};
+	let address = match parts.next() {
+		Some("registry") => SecretStoreContractAddress::Registry,
+		Some(address) => SecretStoreContractAddress::Address(to_address(Some(address.into()))?),
+		None => return Err(format!("Secret Store service contract source is missing: {}", s)),
+	};
+
+	Ok(SecretStoreServiceContract { kind, address })
+}
+
 #[cfg(test)]
 mod tests {
 	use std::io::Write;
@@ -1197,7 +1440,7 @@ mod tests {
 	use parity_rpc::NetworkSettings;
 	use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 
-	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts};
+	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts, BackupAccounts, RestoreAccounts};
 	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat, ExportState};
 	use cli::Args;
 	use dir::{Directories, default_hypervisor_path};
@@ -1207,6 +1450,8 @@ mod tests {
 	use rpc::WsConfiguration;
 	use rpc_apis::ApiSet;
 	use run::RunCmd;
+	use output::OutputFormat;
+	use secretstore::SecretStoreCmd;
 
 	use network::{AllowIP, IpFilter};
 
@@ -1225,6 +1470,7 @@ mod tests {
 	fn parse(args: &[&str]) -> Configuration {
 		Configuration {
 			args: Args::parse_without_config(args).unwrap(),
+			command: args.iter().map(|arg| arg.to_string()).collect(),
 		}
 	}
 
@@ -1240,13 +1486,52 @@ mod tests {
 		let args = vec!["parity", "account", "new"];
 		let conf = parse(&args);
 		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::New(NewAccount {
-			iterations: *ITERATIONS,
+			kdf: KdfConfig::Pbkdf2(*ITERATIONS),
+			path: Directories::default().keys,
+			password_file: None,
+			spec: SpecType::default(),
+			mnemonic: false,
+		})));
+	}
+
+	#[test]
+	fn test_command_account_new_with_mnemonic() {
+		let args = vec!["parity", "account", "new", "--mnemonic"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::New(NewAccount {
+			kdf: KdfConfig::Pbkdf2(*ITERATIONS),
 			path: Directories::default().keys,
 			password_file: None,
 			spec: SpecType::default(),
+			mnemonic: true,
 		})));
 	}
 
+	#[test]
+	fn test_command_account_new_with_scrypt() {
+		let args = vec!["parity", "account", "new", "--keys-kdf", "scrypt", "--keys-scrypt-n", "1024", "--keys-scrypt-p", "2"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::New(NewAccount {
+			kdf: KdfConfig::Scrypt { n: 1024, r: 8, p: 2 },
+			path: Directories::default().keys,
+			password_file: None,
+			spec: SpecType::default(),
+			mnemonic: false,
+		})));
+	}
+
+	#[test]
+	fn test_invalid_keys_kdf() {
+		let args = vec!["parity", "account", "new", "--keys-kdf", "scrypt", "--keys-scrypt-n", "1000"];
+		assert!(parse(&args).into_command().is_err());
+
+		let args = vec!["parity", "account", "new", "--keys-kdf", "bcrypt"];
+		assert!(parse(&args).into_command().is_err());
+
+		let args = vec!["parity", "account", "new", "--keys-iterations", "0"];
+		assert!(parse(&args).into_command().is_err());
+	}
+
 	#[test]
 	fn test_command_account_list() {
 		let args = vec!["parity", "account", "list"];
@@ -1270,12 +1555,69 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_account_backup() {
+		let args = vec!["parity", "account", "backup", "--file", "backup.parity"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::Backup(BackupAccounts {
+			kdf: KdfConfig::Pbkdf2(*ITERATIONS),
+			path: Directories::default().keys,
+			spec: SpecType::default(),
+			file: "backup.parity".into(),
+			password_file: None,
+		})));
+	}
+
+	#[test]
+	fn test_command_account_restore() {
+		let args = vec!["parity", "account", "restore", "--file", "backup.parity", "--force"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::Restore(RestoreAccounts {
+			path: Directories::default().keys,
+			spec: SpecType::default(),
+			file: "backup.parity".into(),
+			password_file: None,
+			force: true,
+		})));
+
+		let args = vec!["parity", "account", "restore"];
+		assert!(parse(&args).into_command().is_err());
+	}
+
+	#[test]
+	fn test_command_secretstore_backup_and_restore() {
+		let args = vec!["parity", "secretstore", "restore", "--file", "keys.backup", "--force"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::SecretStore(cmd) => {
+				assert_eq!(cmd.cmd, SecretStoreCmd::Restore { file: "keys.backup".into(), force: true });
+				assert_eq!(cmd.conf.data_path, Directories::default().secretstore);
+			},
+			_ => panic!("Should be secretstore command"),
+		}
+
+		let args = vec!["parity", "secretstore", "backup", "--file", "keys.backup"];
+		match parse(&args).into_command().unwrap().cmd {
+			Cmd::SecretStore(cmd) => assert_eq!(cmd.cmd, SecretStoreCmd::Backup { file: "keys.backup".into() }),
+			_ => panic!("Should be secretstore command"),
+		}
+
+		let args = vec!["parity", "secretstore", "verify"];
+		match parse(&args).into_command().unwrap().cmd {
+			Cmd::SecretStore(cmd) => assert_eq!(cmd.cmd, SecretStoreCmd::Verify),
+			_ => panic!("Should be secretstore command"),
+		}
+
+		let args = vec!["parity", "secretstore", "backup"];
+		assert!(parse(&args).into_command().is_err());
+	}
+
 	#[test]
 	fn test_command_wallet_import() {
 		let args = vec!["parity", "wallet", "import", "my_wallet.json", "--password", "pwd"];
 		let conf = parse(&args);
 		assert_eq!(conf.into_command().unwrap().cmd, Cmd::ImportPresaleWallet(ImportWallet {
-			iterations: *ITERATIONS,
+			kdf: KdfConfig::Pbkdf2(*ITERATIONS),
 			path: Directories::default().keys,
 			wallet_path: "my_wallet.json".into(),
 			password_file: Some("pwd".into()),
@@ -1305,6 +1647,7 @@ mod tests {
 			verifier_settings: Default::default(),
 			light: false,
 			max_round_blocks_to_import: 12,
+			output_format: Default::default(),
 		})));
 	}
 
@@ -1328,6 +1671,7 @@ mod tests {
 			to_block: BlockId::Latest,
 			check_seal: true,
 			max_round_blocks_to_import: 12,
+			output_format: Default::default(),
 		})));
 	}
 
@@ -1353,6 +1697,7 @@ mod tests {
 			min_balance: None,
 			max_balance: None,
 			max_round_blocks_to_import: 12,
+			output_format: Default::default(),
 		})));
 	}
 
@@ -1376,9 +1721,26 @@ mod tests {
 			to_block: BlockId::Latest,
 			check_seal: true,
 			max_round_blocks_to_import: 12,
+			output_format: Default::default(),
 		})));
 	}
 
+	#[test]
+	fn test_json_logs() {
+		let args = vec!["parity", "export", "blocks", "--json-logs", "blockchain.json"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Blockchain(BlockchainCmd::Export(cmd)) => assert_eq!(cmd.output_format, OutputFormat::Json),
+			_ => panic!("Should be export command."),
+		}
+
+		let conf = parse(&["parity", "--json-logs"]);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Run(cmd) => assert_eq!(cmd.output_format, OutputFormat::Json),
+			_ => panic!("Should be Cmd::Run"),
+		}
+	}
+
 	#[test]
 	fn test_command_signer_new_token() {
 		let args = vec!["parity", "signer", "new-token"];
@@ -1393,14 +1755,90 @@ mod tests {
 			hosts: Some(vec![]),
 			signer_path: expected.into(),
 			support_token_api: true,
+			confirmation_timeout: None,
 			max_connections: 100,
 		}, LogConfig {
 			color: !cfg!(windows),
 			mode: None,
 			file: None,
+			file_rotation: Default::default(),
 		} ));
 	}
 
+	#[test]
+	fn test_log_file_rotation() {
+		let conf = parse(&["parity", "--log-file", "parity.log", "--log-file-size", "10", "--log-file-keep", "3", "--log-file-compress"]);
+		assert_eq!(conf.logger_config().file_rotation, FileRotation {
+			max_size: Some(10 * 1024 * 1024),
+			daily: false,
+			keep: 3,
+			compress: true,
+		});
+
+		let conf = parse(&["parity", "--log-file-daily"]);
+		assert_eq!(conf.logger_config().file_rotation, FileRotation { daily: true, ..Default::default() });
+	}
+
+	#[test]
+	fn test_ui_confirmation_timeout() {
+		let args = vec!["parity", "--ui-confirmation-timeout", "120"];
+		let conf = parse(&args);
+
+		assert_eq!(conf.ws_config().unwrap(), WsConfiguration {
+			confirmation_timeout: Some(Duration::from_secs(120)),
+			..Default::default()
+		});
+	}
+
+	#[test]
+	fn test_light_serve_load() {
+		assert_eq!(parse(&["parity"]).light_serve_load(), Ok(0.5));
+		assert_eq!(parse(&["parity", "--light-serve-load", "20"]).light_serve_load(), Ok(0.2));
+		assert!(parse(&["parity", "--light-serve-load", "0"]).light_serve_load().is_err());
+		assert!(parse(&["parity", "--light-serve-load", "101"]).light_serve_load().is_err());
+	}
+
+	#[test]
+	fn test_light_serve_limits() {
+		assert_eq!(parse(&["parity"]).light_serve_limits(), Ok(ServeLimits::default()));
+		assert_eq!(parse(&["parity", "--light-serve-rate", "500", "--light-serve-peer-rate", "50", "--light-serve-reserved-share", "10"]).light_serve_limits(), Ok(ServeLimits {
+			total: 500,
+			per_peer: 50,
+			reserved_share: 0.1,
+		}));
+		assert!(parse(&["parity", "--light-serve-reserved-share", "101"]).light_serve_limits().is_err());
+	}
+
+	#[test]
+	fn test_light_credits_request() {
+		assert_eq!(parse(&["parity"]).light_credits_request(), Ok(None));
+		assert_eq!(parse(&["parity", "--light-request-credits", "5000", "--light-request-recharge", "50"]).light_credits_request(), Ok(Some(CreditsRequest {
+			limit: 5000u64.into(),
+			recharge_rate: 50u64.into(),
+		})));
+		assert!(parse(&["parity", "--light-request-credits", "5000"]).light_credits_request().is_err());
+	}
+
+	#[test]
+	fn test_local_store_config() {
+		assert_eq!(parse(&["parity"]).local_store_config(), Ok(LocalStoreConfig::default()));
+		assert_eq!(parse(&["parity", "--local-store-interval", "60", "--local-store", "pool,service", "--no-persistent-txqueue"]).local_store_config(), Ok(LocalStoreConfig {
+			interval: Duration::from_secs(60),
+			categories: LocalStoreCategories {
+				local_transactions: false,
+				pool_journal: true,
+				service_transactions: true,
+			},
+		}));
+		assert_eq!(parse(&["parity", "--local-store", "none"]).local_store_config().unwrap().categories, LocalStoreCategories {
+			local_transactions: false,
+			pool_journal: false,
+			service_transactions: false,
+		});
+		assert!(parse(&["parity", "--local-store", "local,blocks"]).local_store_config().is_err());
+		assert!(parse(&["parity", "--local-store-interval", "0"]).local_store_config().is_err());
+	}
+
 	#[test]
 	fn test_ws_max_connections() {
 		let args = vec!["parity", "--ws-max-connections", "1"];
@@ -1470,16 +1908,29 @@ mod tests {
 			download_old_blocks: true,
 			verifier_settings: Default::default(),
 			serve_light: true,
+			light_serve_load: 0.5,
+			light_serve_credit_seconds: 300,
+			light_serve_limits: Default::default(),
+			light_credits_request: None,
 			light: false,
 			no_hardcoded_sync: false,
 			no_persistent_txqueue: false,
+			local_store: Default::default(),
 			whisper: Default::default(),
 			max_round_blocks_to_import: 12,
+			auto_rollback: Some(10),
 			on_demand_response_time_window: None,
+			on_demand_request_timeout: None,
+			on_demand_request_attempts_max: None,
 			on_demand_request_backoff_start: None,
 			on_demand_request_backoff_max: None,
 			on_demand_request_backoff_rounds_max: None,
 			on_demand_request_consecutive_failures: None,
+			light_checkpoint: None,
+			light_call_cache_size: 128,
+			shutdown_timeout: Duration::from_secs(300),
+			configuration: conf.clone(),
+			output_format: OutputFormat::Human,
 		};
 		expected.secretstore_conf.enabled = cfg!(feature = "secretstore");
 		expected.secretstore_conf.http_enabled = cfg!(feature = "secretstore");
@@ -1989,4 +2440,128 @@ mod tests {
 		assert_eq!(conf1.secretstore_cors(), None);
 		assert_eq!(conf2.secretstore_cors(), Some(vec!["http://parity.io".into(),"http://something.io".into()]));
 	}
+
+	#[test]
+	fn should_parse_secretstore_http_tls() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--secretstore-http-cert", "/tls/http.pem", "--secretstore-http-key", "/tls/http.key",
+			"--secretstore-http-client-ca", "/tls/ca.pem"]);
+		let conf2 = parse(&["parity", "--secretstore-http-cert", "/tls/http.pem"]);
+		let conf3 = parse(&["parity", "--secretstore-http-client-ca", "/tls/ca.pem"]);
+
+		// then
+		assert_eq!(conf0.secretstore_http_tls(), Ok(None));
+		assert_eq!(conf1.secretstore_http_tls(), Ok(Some(SecretStoreHttpTlsConfiguration {
+			certificate_path: "/tls/http.pem".into(),
+			private_key_path: "/tls/http.key".into(),
+			client_ca_path: Some("/tls/ca.pem".into()),
+		})));
+		assert!(conf2.secretstore_http_tls().is_err());
+		assert!(conf3.secretstore_http_tls().is_err());
+	}
+
+	#[test]
+	fn should_parse_secretstore_service_contracts() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--secretstore-srv-gen-contract", "registry",
+			"--secretstore-service-contracts", "doc_sretr:registry,doc_sretr:0x0000000000000000000000000000000000000001,-all:registry"]);
+		let conf2 = parse(&["parity", "--secretstore-service-contracts", "srv_gen"]);
+		let conf3 = parse(&["parity", "--secretstore-service-contracts", "acl:registry"]);
+		let conf4 = parse(&["parity", "--secretstore-contract", "registry", "--secretstore-doc-store-contract", "none",
+			"--secretstore-service-contracts", "srv_retr:registry,-all:registry,-srv_retr:registry,doc_store:registry,doc_store:registry"]);
+		let conf5 = parse(&["parity", "--secretstore-service-contracts", "-doc_store"]);
+
+		// then
+		assert_eq!(conf0.secretstore_service_contracts(), Ok(vec![]));
+		assert_eq!(conf1.secretstore_service_contracts(), Ok(vec![
+			SecretStoreServiceContract {
+				kind: SecretStoreServiceContractKind::ServerKeyGeneration,
+				address: SecretStoreContractAddress::Registry,
+			},
+			SecretStoreServiceContract {
+				kind: SecretStoreServiceContractKind::DocumentKeyShadowRetrieval,
+				address: SecretStoreContractAddress::Registry,
+			},
+			SecretStoreServiceContract {
+				kind: SecretStoreServiceContractKind::DocumentKeyShadowRetrieval,
+				address: SecretStoreContractAddress::Address(1.into()),
+			},
+		]));
+		assert!(conf2.secretstore_service_contracts().is_err());
+		assert!(conf3.secretstore_service_contracts().is_err());
+		assert_eq!(conf4.secretstore_service_contracts(), Ok(vec![
+			SecretStoreServiceContract {
+				kind: SecretStoreServiceContractKind::DocumentKeyStore,
+				address: SecretStoreContractAddress::Registry,
+			},
+		]));
+		assert!(conf5.secretstore_service_contracts().is_err());
+	}
+
+	#[test]
+	fn should_reject_invalid_whisper_min_pow() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity", "--whisper-min-pow", "0.2"]);
+		let conf1 = parse(&["parity", "--whisper-min-pow=-1"]);
+		let conf2 = parse(&["parity", "--whisper-min-pow", "NaN"]);
+		let conf3 = parse(&["parity", "--whisper-min-pow", "inf"]);
+
+		// then
+		assert_eq!(conf0.whisper_config().unwrap().min_pow, 0.2);
+		assert!(conf1.whisper_config().is_err());
+		assert!(conf2.whisper_config().is_err());
+		assert!(conf3.whisper_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_whisper_mail_server() {
+		// given
+		let key = "0101010101010101010101010101010101010101010101010101010101010101";
+
+		// when
+		let conf0 = parse(&["parity", "--whisper"]);
+		let conf1 = parse(&["parity", "--whisper-mail-server"]);
+		let conf2 = parse(&["parity", "--whisper-mail-server", "--whisper-mail-server-key", key,
+			"--whisper-mail-server-size", "50", "--whisper-mail-server-retention", "2"]);
+		let conf3 = parse(&["parity", "--whisper-mail-server", "--whisper-mail-server-key", "0x01"]);
+
+		// then
+		assert_eq!(conf0.whisper_config().unwrap().mail_server, None);
+		assert!(conf1.whisper_config().is_err());
+		assert_eq!(conf2.whisper_config().unwrap().mail_server, Some(::parity_whisper::mail_server::Config {
+			bloom: H512::from([0xff; 64]),
+			key: [1; 32],
+			max_size: 50 * 1024 * 1024,
+			retention: Duration::from_secs(2 * 24 * 60 * 60),
+		}));
+		assert!(conf3.whisper_config().is_err());
+	}
+
+	#[test]
+	fn should_parse_secretstore_auto_migrate() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--secretstore-auto-migrate", "dry-run"]);
+		let conf2 = parse(&["parity", "--secretstore-auto-migrate", "admin"]);
+		let conf3 = parse(&["parity", "--secretstore-auto-migrate", "always"]);
+
+		// then
+		assert_eq!(conf0.secretstore_admin_migration_mode(), Ok(SecretStoreAdminMigrationMode::Disabled));
+		assert_eq!(conf0.secretstore_auto_migrate_enabled(), Ok(true));
+		assert_eq!(conf1.secretstore_admin_migration_mode(), Ok(SecretStoreAdminMigrationMode::DryRun));
+		assert_eq!(conf1.secretstore_auto_migrate_enabled(), Ok(false));
+		assert_eq!(conf2.secretstore_admin_migration_mode(), Ok(SecretStoreAdminMigrationMode::Enabled));
+		assert_eq!(conf2.secretstore_auto_migrate_enabled(), Ok(false));
+		assert!(conf3.secretstore_admin_migration_mode().is_err());
+	}
 }
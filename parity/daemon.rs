@@ -0,0 +1,391 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! PID file of a daemonized node and the deadline of a graceful shutdown.
+//!
+//! The PID file is written and kept locked by `parity_daemonize` while the node runs,
+//! so a file which isn't locked and doesn't name a running parity process is left over
+//! from a crashed node.
+
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+#[cfg(unix)]
+pub use self::pid_file::{PidFile, PidFileState, pid_file_state, clean_stale_pid_file, process_alive, is_parity_process};
+
+#[cfg(unix)]
+mod pid_file {
+	use std::fs::{self, File};
+	use std::io::{self, Read};
+	use std::os::unix::io::AsRawFd;
+	use std::path::{Path, PathBuf};
+	use std::process::{self, Command};
+
+	use libc;
+
+	/// State of a PID file found at startup.
+	#[derive(Debug, PartialEq)]
+	pub enum PidFileState {
+		/// There's no PID file.
+		Missing,
+		/// The PID file belongs to a running node.
+		Running(Option<u32>),
+		/// The PID file was left over by a node which isn't running anymore.
+		Stale,
+	}
+
+	/// PID file of the current process, removed when it's dropped.
+	#[derive(Debug)]
+	pub struct PidFile {
+		path: PathBuf,
+	}
+
+	impl PidFile {
+		/// Take over the PID file written and locked by `parity_daemonize::daemonize`,
+		/// so that it's removed when the daemon exits. The lock stays with the daemon.
+		pub fn adopt<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+			let path = path.as_ref().to_owned();
+			let content = fs::read_to_string(&path).map_err(|e| format!("Unable to read PID file {}: {}", path.display(), e))?;
+			match content.trim().parse::<u32>() {
+				Ok(pid) if pid == process::id() => Ok(PidFile { path }),
+				_ => Err(format!("PID file {} doesn't name the daemon process {}", path.display(), process::id())),
+			}
+		}
+
+		/// Path of the PID file.
+		pub fn path(&self) -> &Path {
+			&self.path
+		}
+	}
+
+	impl Drop for PidFile {
+		fn drop(&mut self) {
+			if let Err(e) = fs::remove_file(&self.path) {
+				warn!("Unable to remove PID file {}: {}", self.path.display(), e);
+			}
+		}
+	}
+
+	/// Check the PID file at `path`. A PID file which isn't locked is stale
+	/// unless `is_parity` confirms the recorded PID belongs to a running node.
+	pub fn pid_file_state(path: &Path, is_parity: &Fn(u32) -> bool) -> io::Result<PidFileState> {
+		let mut file = match File::open(path) {
+			Ok(file) => file,
+			Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(PidFileState::Missing),
+			Err(e) => return Err(e),
+		};
+
+		let mut content = String::new();
+		file.read_to_string(&mut content)?;
+		let pid = content.trim().parse::<u32>().ok().filter(|pid| *pid > 0);
+
+		if !try_lock(&file)? {
+			return Ok(PidFileState::Running(pid));
+		}
+
+		match pid {
+			Some(pid) if is_parity(pid) => Ok(PidFileState::Running(Some(pid))),
+			_ => Ok(PidFileState::Stale),
+		}
+	}
+
+	/// Remove the PID file at `path` if it's stale. Fails if it belongs to a running node.
+	pub fn clean_stale_pid_file(path: &Path) -> Result<(), String> {
+		let is_parity = |pid| process_alive(pid) && is_parity_process(pid);
+		match pid_file_state(path, &is_parity).map_err(|e| format!("Unable to read PID file {}: {}", path.display(), e))? {
+			PidFileState::Missing => Ok(()),
+			PidFileState::Running(Some(pid)) => Err(format!("Parity is already running with PID {} ({})", pid, path.display())),
+			PidFileState::Running(None) => Err(format!("Parity is already running ({})", path.display())),
+			PidFileState::Stale => {
+				warn!("Removing stale PID file {}", path.display());
+				fs::remove_file(path).map_err(|e| format!("Unable to remove stale PID file {}: {}", path.display(), e))
+			},
+		}
+	}
+
+	/// Whether a process with the given PID exists.
+	pub fn process_alive(pid: u32) -> bool {
+		if pid == 0 || pid > libc::pid_t::max_value() as u32 {
+			return false;
+		}
+
+		match unsafe { libc::kill(pid as libc::pid_t, 0) } {
+			0 => true,
+			_ => io::Error::last_os_error().raw_os_error() == Some(libc::EPERM),
+		}
+	}
+
+	/// Whether the process with the given PID is a parity node.
+	/// Reads its name from `/proc`, or from `ps` where there's no `/proc`. A process whose name
+	/// can't be read is assumed not to be one, since a running node keeps its PID file locked.
+	pub fn is_parity_process(pid: u32) -> bool {
+		let name = match fs::read_to_string(format!("/proc/{}/comm", pid)) {
+			Ok(name) => name,
+			Err(_) => match Command::new("ps").args(&["-p", &pid.to_string(), "-o", "comm="]).output() {
+				Ok(ref output) if output.status.success() => String::from_utf8_lossy(&output.stdout).into_owned(),
+				_ => return false,
+			},
+		};
+
+		// `ps` prints the path of the executable on some platforms.
+		Path::new(name.trim()).file_name().map_or(false, |name| name == "parity")
+	}
+
+	// take an exclusive lock of the file; returns `false` if it's locked already.
+	fn try_lock(file: &File) -> io::Result<bool> {
+		match unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } {
+			0 => Ok(true),
+			_ => match io::Error::last_os_error() {
+				ref e if e.raw_os_error() == Some(libc::EWOULDBLOCK) => Ok(false),
+				e => Err(e),
+			},
+		}
+	}
+}
+
+/// Steps of a graceful shutdown, run in order until they finish or the deadline passes.
+#[derive(Default)]
+pub struct ShutdownSequence<'a> {
+	steps: Vec<(&'static str, Box<FnMut() + 'a>)>,
+}
+
+impl<'a> ShutdownSequence<'a> {
+	/// Create an empty sequence.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add a named step to the end of the sequence.
+	pub fn step<F: FnOnce() + 'a>(mut self, name: &'static str, step: F) -> Self {
+		let mut step = Some(step);
+		self.steps.push((name, Box::new(move || if let Some(step) = step.take() { step() })));
+		self
+	}
+
+	/// Run the steps in order. If they haven't finished within `deadline`, `on_timeout` is called
+	/// from a watchdog thread with the names of the steps which are still running or pending,
+	/// while the steps keep running.
+	/// Returns whether the sequence finished in time.
+	pub fn run<T>(self, deadline: Duration, on_timeout: T) -> bool
+		where T: FnOnce(Vec<&'static str>) + Send + 'static
+	{
+		let pending = Arc::new(Mutex::new(self.steps.iter().map(|&(name, _)| name).collect::<Vec<_>>()));
+		let (done, finished) = mpsc::channel::<()>();
+
+		let watchdog = {
+			let pending = pending.clone();
+			thread::Builder::new().name("shutdown".into()).spawn(move || {
+				match finished.recv_timeout(deadline) {
+					Err(mpsc::RecvTimeoutError::Timeout) => {
+						on_timeout(pending.lock().clone());
+						false
+					},
+					_ => true,
+				}
+			})
+		};
+		if let Err(ref e) = watchdog {
+			warn!("Unable to enforce the shutdown deadline: {}", e);
+		}
+
+		for (name, mut step) in self.steps {
+			trace!("Shutting down: {}", name);
+			step();
+			pending.lock().remove(0);
+		}
+
+		let _ = done.send(());
+		watchdog.ok().map_or(true, |watchdog| watchdog.join().unwrap_or(false))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::thread;
+	use std::time::Duration;
+
+	use parking_lot::Mutex;
+
+	use super::ShutdownSequence;
+
+	#[test]
+	fn runs_steps_in_order() {
+		let log = Arc::new(Mutex::new(Vec::new()));
+		let timed_out = Arc::new(Mutex::new(None));
+
+		let finished = {
+			let step = |name: &'static str| {
+				let log = log.clone();
+				move || log.lock().push(name)
+			};
+			let timed_out = timed_out.clone();
+
+			ShutdownSequence::new()
+				.step("client service", step("client service"))
+				.step("local store", step("local store"))
+				.step("client", step("client"))
+				.run(Duration::from_secs(10), move |pending| *timed_out.lock() = Some(pending))
+		};
+
+		assert!(finished);
+		assert_eq!(*log.lock(), vec!["client service", "local store", "client"]);
+		assert_eq!(*timed_out.lock(), None);
+	}
+
+	#[test]
+	fn reports_steps_running_at_deadline() {
+		let log = Arc::new(Mutex::new(Vec::new()));
+		let timed_out = Arc::new(Mutex::new(None));
+
+		let finished = {
+			let timed_out = timed_out.clone();
+			let (log1, log2, log3) = (log.clone(), log.clone(), log.clone());
+
+			ShutdownSequence::new()
+				.step("local store", move || log1.lock().push("local store"))
+				.step("import queue", move || {
+					thread::sleep(Duration::from_millis(500));
+					log2.lock().push("import queue");
+				})
+				.step("client", move || log3.lock().push("client"))
+				.run(Duration::from_millis(100), move |pending| *timed_out.lock() = Some(pending))
+		};
+
+		assert!(!finished);
+		assert_eq!(*timed_out.lock(), Some(vec!["import queue", "client"]));
+		// remaining steps aren't skipped, unless the process exits on timeout.
+		assert_eq!(*log.lock(), vec!["local store", "import queue", "client"]);
+	}
+
+	#[cfg(unix)]
+	mod pid_file {
+		use std::{env, fs, thread};
+		use std::path::PathBuf;
+		use std::process::{self, Command};
+		use std::time::{Duration, Instant};
+
+		use parity_daemonize;
+		use tempdir::TempDir;
+
+		use daemon::{PidFile, PidFileState, pid_file_state, clean_stale_pid_file, process_alive, is_parity_process};
+
+		// PID file of the daemon started by `daemon_process`.
+		const DAEMON_PID_FILE: &str = "PARITY_TEST_DAEMON_PID_FILE";
+
+		#[test]
+		fn detects_stale_pid_files() {
+			let tempdir = TempDir::new("").unwrap();
+			let path = tempdir.path().join("parity.pid");
+			assert_eq!(pid_file_state(&path, &process_alive).unwrap(), PidFileState::Missing);
+
+			fs::write(&path, "garbage").unwrap();
+			assert_eq!(pid_file_state(&path, &process_alive).unwrap(), PidFileState::Stale);
+
+			// a process which exited already.
+			let mut child = Command::new("true").spawn().unwrap();
+			let dead = child.id();
+			child.wait().unwrap();
+			fs::write(&path, format!("{}\n", dead)).unwrap();
+			assert_eq!(pid_file_state(&path, &process_alive).unwrap(), PidFileState::Stale);
+
+			// a running process which isn't parity.
+			fs::write(&path, format!("{}\n", process::id())).unwrap();
+			assert_eq!(pid_file_state(&path, &process_alive).unwrap(), PidFileState::Running(Some(process::id())));
+			assert_eq!(pid_file_state(&path, &|_| false).unwrap(), PidFileState::Stale);
+
+			fs::write(&path, format!("{}\n", dead)).unwrap();
+			clean_stale_pid_file(&path).unwrap();
+			assert!(!path.exists());
+		}
+
+		#[test]
+		fn recognizes_only_parity_processes() {
+			let mut child = Command::new("sleep").arg("10").spawn().unwrap();
+			assert!(!is_parity_process(child.id()));
+			child.kill().unwrap();
+			child.wait().unwrap();
+			assert!(!is_parity_process(child.id()));
+		}
+
+		#[test]
+		fn only_adopts_own_pid_file() {
+			let tempdir = TempDir::new("").unwrap();
+			let path = tempdir.path().join("parity.pid");
+			assert!(PidFile::adopt(&path).is_err());
+
+			fs::write(&path, format!("{}\n", process::id() + 1)).unwrap();
+			assert!(PidFile::adopt(&path).is_err());
+
+			fs::write(&path, format!("{}\n", process::id())).unwrap();
+			let pid_file = PidFile::adopt(&path).unwrap();
+			assert_eq!(pid_file.path(), path.as_path());
+			drop(pid_file);
+			assert!(!path.exists());
+		}
+
+		// not a test on its own: daemonizes the test process spawned by `daemon_keeps_pid_file_locked`,
+		// since forking the multithreaded test harness itself isn't safe.
+		#[test]
+		fn daemon_process() {
+			let path = match env::var_os(DAEMON_PID_FILE) {
+				Some(path) => PathBuf::from(path),
+				None => return,
+			};
+			let stop = path.with_extension("stop");
+
+			// the spawned process exits once the daemon detaches from it.
+			let mut handle = parity_daemonize::daemonize(path.clone()).unwrap();
+			let _pid_file = PidFile::adopt(&path).unwrap();
+			handle.detach();
+			while !stop.exists() {
+				thread::sleep(Duration::from_millis(10));
+			}
+		}
+
+		#[test]
+		fn daemon_keeps_pid_file_locked() {
+			let tempdir = TempDir::new("").unwrap();
+			let path = tempdir.path().join("parity.pid");
+			let stop = path.with_extension("stop");
+
+			let status = Command::new(env::current_exe().unwrap())
+				.args(&["--exact", "daemon::tests::pid_file::daemon_process", "--test-threads=1"])
+				.env(DAEMON_PID_FILE, &path)
+				.status()
+				.unwrap();
+			assert!(status.success());
+
+			let daemon = match pid_file_state(&path, &|_| false).unwrap() {
+				PidFileState::Running(Some(pid)) => pid,
+				state => panic!("the daemon doesn't hold the PID file: {:?}", state),
+			};
+			assert!(daemon != process::id() && process_alive(daemon));
+			assert!(clean_stale_pid_file(&path).is_err());
+
+			// removed by the daemon on exit.
+			fs::write(&stop, "").unwrap();
+			let deadline = Instant::now() + Duration::from_secs(10);
+			while path.exists() {
+				assert!(Instant::now() < deadline, "the daemon didn't remove its PID file");
+				thread::sleep(Duration::from_millis(10));
+			}
+		}
+	}
+}
@@ -67,6 +67,16 @@ pub fn open_secretstore_db(data_path: &str) -> Result<Arc<KeyValueDB>, String> {
 	Ok(Arc::new(Database::open_default(&db_path).map_err(|e| format!("Error opening database: {:?}", e))?))
 }
 
+/// Column of the whisper mail server DB storing envelopes.
+pub const COL_WHISPER_MAIL: Option<u32> = Some(0);
+
+/// Open a whisper mail server DB at the given path.
+pub fn open_whisper_mail_db(db_path: &Path) -> Result<Arc<KeyValueDB>, String> {
+	let db_path = db_path.to_str().ok_or_else(|| "Invalid whisper mail server path".to_string())?;
+	let db_config = DatabaseConfig::with_columns(Some(1));
+	Ok(Arc::new(Database::open(&db_config, &db_path).map_err(|e| format!("Error opening database: {:?}", e))?))
+}
+
 /// Create a restoration db handler using the config generated by `client_path` and `client_config`.
 pub fn restoration_db_handler(client_path: &Path, client_config: &ClientConfig) -> Box<BlockChainDBHandler> {
 	let client_db_config = helpers::client_db_config(client_path, client_config);
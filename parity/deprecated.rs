@@ -229,6 +229,28 @@ pub fn find_deprecated(args: &Args) -> Vec<Deprecated> {
 		result.push(Deprecated::Removed("--ntp-servers"));
 	}
 
+	// Deprecated in 2.6.
+
+	if args.arg_secretstore_contract.is_some() {
+		result.push(Deprecated::Replaced("--secretstore-contract", "--secretstore-service-contracts=all:SOURCE"));
+	}
+
+	if args.arg_secretstore_srv_gen_contract.is_some() {
+		result.push(Deprecated::Replaced("--secretstore-srv-gen-contract", "--secretstore-service-contracts=srv_gen:SOURCE"));
+	}
+
+	if args.arg_secretstore_srv_retr_contract.is_some() {
+		result.push(Deprecated::Replaced("--secretstore-srv-retr-contract", "--secretstore-service-contracts=srv_retr:SOURCE"));
+	}
+
+	if args.arg_secretstore_doc_store_contract.is_some() {
+		result.push(Deprecated::Replaced("--secretstore-doc-store-contract", "--secretstore-service-contracts=doc_store:SOURCE"));
+	}
+
+	if args.arg_secretstore_doc_sretr_contract.is_some() {
+		result.push(Deprecated::Replaced("--secretstore-doc-sretr-contract", "--secretstore-service-contracts=doc_sretr:SOURCE"));
+	}
+
 	result
 }
 
@@ -261,6 +283,8 @@ mod tests {
 			args.flag_dapps_apis_all = true;
 			args.flag_fast_and_loose = true;
 			args.arg_ntp_servers = Some(Default::default());
+			args.arg_secretstore_contract = Some(Default::default());
+			args.arg_secretstore_doc_sretr_contract = Some(Default::default());
 			args
 		}), vec![
 			Deprecated::DoesNothing("--warp"),
@@ -282,6 +306,8 @@ mod tests {
 			Deprecated::Replaced("--dapps-apis-all", "--jsonrpc-apis"),
 			Deprecated::Removed("--fast-and-loose"),
 			Deprecated::Removed("--ntp-servers"),
+			Deprecated::Replaced("--secretstore-contract", "--secretstore-service-contracts=all:SOURCE"),
+			Deprecated::Replaced("--secretstore-doc-sretr-contract", "--secretstore-service-contracts=doc_sretr:SOURCE"),
 		]);
 	}
 }
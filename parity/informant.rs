@@ -28,6 +28,7 @@ use ethcore::client::{
 	BlockQueueInfo, ChainNotify, NewBlocks, ClientReport, Client, ClientIoMessage
 };
 use types::BlockNumber;
+use ethcore::miner::{Miner, MinerService};
 use ethcore::snapshot::{RestorationStatus, SnapshotService as SS};
 use ethcore::snapshot::service::Service as SnapshotService;
 use sync::{LightSyncProvider, LightSync, SyncProvider, ManageNetwork};
@@ -39,6 +40,8 @@ use parity_rpc::is_major_importing_or_waiting;
 use parity_rpc::informant::RpcStats;
 use ethereum_types::H256;
 use parking_lot::{RwLock, Mutex};
+use output::{self, OutputFormat, NodeStatus, BlockStatus, PeerStatus, QueueStatus, SyncProgress,
+	SnapshotProgress, TxPoolStatus, ImportRate, RpcStatus, BlockImport};
 
 /// Format byte counts to standard denominations.
 pub fn format_bytes(b: usize) -> String {
@@ -101,6 +104,7 @@ pub struct Report {
 	queue_info: BlockQueueInfo,
 	cache_sizes: CacheSizes,
 	sync_info: Option<SyncInfo>,
+	txpool: Option<TxPoolStatus>,
 }
 
 /// Something which can provide data to the informant.
@@ -120,6 +124,7 @@ pub struct FullNodeInformantData {
 	pub client: Arc<Client>,
 	pub sync: Option<Arc<SyncProvider>>,
 	pub net: Option<Arc<ManageNetwork>>,
+	pub miner: Option<Arc<Miner>>,
 }
 
 impl InformantData for FullNodeInformantData {
@@ -161,6 +166,14 @@ impl InformantData for FullNodeInformantData {
 			_ => None
 		};
 
+		let txpool = self.miner.as_ref().map(|miner| {
+			let status = miner.queue_status().status;
+			TxPoolStatus {
+				transactions: status.transaction_count,
+				senders: status.senders,
+			}
+		});
+
 		Report {
 			importing,
 			chain_info,
@@ -168,6 +181,7 @@ impl InformantData for FullNodeInformantData {
 			queue_info,
 			cache_sizes,
 			sync_info,
+			txpool,
 		}
 	}
 }
@@ -210,6 +224,7 @@ impl InformantData for LightNodeInformantData {
 			queue_info,
 			cache_sizes,
 			sync_info,
+			txpool: None,
 		}
 	}
 }
@@ -217,6 +232,7 @@ impl InformantData for LightNodeInformantData {
 pub struct Informant<T> {
 	last_tick: RwLock<Instant>,
 	with_color: bool,
+	format: OutputFormat,
 	target: T,
 	snapshot: Option<Arc<SnapshotService>>,
 	rpc_stats: Option<Arc<RpcStats>>,
@@ -228,16 +244,18 @@ pub struct Informant<T> {
 }
 
 impl<T: InformantData> Informant<T> {
-	/// Make a new instance potentially `with_color` output.
+	/// Make a new instance potentially `with_color` output, or JSON output.
 	pub fn new(
 		target: T,
 		snapshot: Option<Arc<SnapshotService>>,
 		rpc_stats: Option<Arc<RpcStats>>,
 		with_color: bool,
+		format: OutputFormat,
 	) -> Self {
 		Informant {
 			last_tick: RwLock::new(Instant::now()),
 			with_color: with_color,
+			format: format,
 			target: target,
 			snapshot: snapshot,
 			rpc_stats: rpc_stats,
@@ -271,6 +289,7 @@ impl<T: InformantData> Informant<T> {
 			queue_info,
 			cache_sizes,
 			sync_info,
+			txpool,
 			..
 		} = full_report;
 
@@ -288,6 +307,62 @@ impl<T: InformantData> Informant<T> {
 		*self.last_tick.write() = now;
 		*self.last_report.lock() = full_report.client_report.clone();
 
+		if self.format == OutputFormat::Json {
+			let per_second = |count: f64| count * 1000.0 / elapsed.as_milliseconds() as f64;
+			let executes_transactions = self.target.executes_transactions();
+			let status = NodeStatus {
+				timestamp: output::timestamp(),
+				syncing: importing,
+				best_block: BlockStatus {
+					number: chain_info.best_block_number,
+					hash: format!("{:#x}", chain_info.best_block_hash),
+				},
+				peers: sync_info.as_ref().map(|sync_info| PeerStatus {
+					connected: sync_info.num_peers,
+					max: sync_info.max_peers,
+				}),
+				queue: QueueStatus {
+					unverified: queue_info.unverified_queue_size,
+					verified: queue_info.verified_queue_size,
+				},
+				sync: sync_info.as_ref().map(|sync_info| SyncProgress {
+					last_imported_block: sync_info.last_imported_block_number,
+					last_imported_old_block: sync_info.last_imported_old_block_number,
+					snapshot: match (snapshot_sync, self.snapshot.as_ref().map(|s| s.status())) {
+						(true, Some(RestorationStatus::Ongoing { state_chunks, block_chunks, state_chunks_done, block_chunks_done })) => Some(SnapshotProgress {
+							chunks_done: (state_chunks_done + block_chunks_done) as u64,
+							chunks: Some((state_chunks + block_chunks) as u64),
+						}),
+						(true, Some(RestorationStatus::Initializing { chunks_done })) => Some(SnapshotProgress {
+							chunks_done: chunks_done as u64,
+							chunks: None,
+						}),
+						_ => None,
+					},
+				}),
+				txpool,
+				import_rate: ImportRate {
+					blocks_per_second: per_second(client_report.blocks_imported as f64),
+					transactions_per_second: match executes_transactions {
+						true => Some(per_second(client_report.transactions_applied as f64)),
+						false => None,
+					},
+					mgas_per_second: match executes_transactions {
+						true => Some(per_second((client_report.gas_processed / 1000).low_u64() as f64) / 1000.0),
+						false => None,
+					},
+				},
+				caches: cache_sizes.sizes.iter().map(|(name, size)| (name.to_string(), *size)).collect(),
+				rpc: rpc_stats.map(|rpc_stats| RpcStatus {
+					sessions: rpc_stats.sessions(),
+					requests_per_second: rpc_stats.requests_rate(),
+					roundtrip_us: rpc_stats.approximated_roundtrip() as u64,
+				}),
+			};
+			output::print_json(&status, false);
+			return;
+		}
+
 		let paint = |c: Style, t: String| match self.with_color && atty::is(atty::Stream::Stdout) {
 			true => format!("{}", c.paint(t)),
 			false => t,
@@ -382,22 +457,34 @@ impl ChainNotify for Informant<FullNodeInformantData> {
 				let header_view = block.header_view();
 				let size = block.rlp().as_raw().len();
 				let (skipped, skipped_txs) = (self.skipped.load(AtomicOrdering::Relaxed) + new_blocks.imported.len() - 1, self.skipped_txs.load(AtomicOrdering::Relaxed) + txs_imported);
-				info!(target: "import", "Imported {} {} ({} txs, {} Mgas, {} ms, {} KiB){}",
-					Colour::White.bold().paint(format!("#{}", header_view.number())),
-					Colour::White.bold().paint(format!("{}", header_view.hash())),
-					Colour::Yellow.bold().paint(format!("{}", block.transactions_count())),
-					Colour::Yellow.bold().paint(format!("{:.2}", header_view.gas_used().low_u64() as f32 / 1000000f32)),
-					Colour::Purple.bold().paint(format!("{}", new_blocks.duration.as_milliseconds())),
-					Colour::Blue.bold().paint(format!("{:.2}", size as f32 / 1024f32)),
-					if skipped > 0 {
-						format!(" + another {} block(s) containing {} tx(s)",
-							Colour::Red.bold().paint(format!("{}", skipped)),
-							Colour::Red.bold().paint(format!("{}", skipped_txs))
-						)
-					} else {
-						String::new()
-					}
-				);
+				if self.format == OutputFormat::Json {
+					output::print_json(&BlockImport {
+						timestamp: output::timestamp(),
+						block: BlockStatus { number: header_view.number(), hash: format!("{:#x}", header_view.hash()) },
+						transactions: Some(block.transactions_count()),
+						gas_used: header_view.gas_used().low_u64(),
+						duration_ms: Some(new_blocks.duration.as_milliseconds()),
+						skipped_blocks: skipped,
+						skipped_transactions: Some(skipped_txs),
+					}, false);
+				} else {
+					info!(target: "import", "Imported {} {} ({} txs, {} Mgas, {} ms, {} KiB){}",
+						Colour::White.bold().paint(format!("#{}", header_view.number())),
+						Colour::White.bold().paint(format!("{}", header_view.hash())),
+						Colour::Yellow.bold().paint(format!("{}", block.transactions_count())),
+						Colour::Yellow.bold().paint(format!("{:.2}", header_view.gas_used().low_u64() as f32 / 1000000f32)),
+						Colour::Purple.bold().paint(format!("{}", new_blocks.duration.as_milliseconds())),
+						Colour::Blue.bold().paint(format!("{:.2}", size as f32 / 1024f32)),
+						if skipped > 0 {
+							format!(" + another {} block(s) containing {} tx(s)",
+								Colour::Red.bold().paint(format!("{}", skipped)),
+								Colour::Red.bold().paint(format!("{}", skipped_txs))
+							)
+						} else {
+							String::new()
+						}
+					);
+				}
 				self.skipped.store(0, AtomicOrdering::Relaxed);
 				self.skipped_txs.store(0, AtomicOrdering::Relaxed);
 				*last_import = Instant::now();
@@ -419,17 +506,29 @@ impl LightChainNotify for Informant<LightNodeInformantData> {
 
 		if ripe {
 			if let Some(header) = good.last().and_then(|h| client.block_header(BlockId::Hash(*h))) {
-				info!(target: "import", "Imported {} {} ({} Mgas){}",
-					Colour::White.bold().paint(format!("#{}", header.number())),
-					Colour::White.bold().paint(format!("{}", header.hash())),
-					Colour::Yellow.bold().paint(format!("{:.2}", header.gas_used().low_u64() as f32 / 1000000f32)),
-					if good.len() > 1 {
-						format!(" + another {} header(s)",
-								Colour::Red.bold().paint(format!("{}", good.len() - 1)))
-					} else {
-						String::new()
-					}
-				);
+				if self.format == OutputFormat::Json {
+					output::print_json(&BlockImport {
+						timestamp: output::timestamp(),
+						block: BlockStatus { number: header.number(), hash: format!("{:#x}", header.hash()) },
+						transactions: None,
+						gas_used: header.gas_used().low_u64(),
+						duration_ms: None,
+						skipped_blocks: good.len() - 1,
+						skipped_transactions: None,
+					}, false);
+				} else {
+					info!(target: "import", "Imported {} {} ({} Mgas){}",
+						Colour::White.bold().paint(format!("#{}", header.number())),
+						Colour::White.bold().paint(format!("{}", header.hash())),
+						Colour::Yellow.bold().paint(format!("{:.2}", header.gas_used().low_u64() as f32 / 1000000f32)),
+						if good.len() > 1 {
+							format!(" + another {} header(s)",
+									Colour::Red.bold().paint(format!("{}", good.len() - 1)))
+						} else {
+							String::new()
+						}
+					);
+				}
 				*last_import = Instant::now();
 			}
 		}
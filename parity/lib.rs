@@ -82,6 +82,9 @@ extern crate ethcore_accounts as accounts;
 #[cfg(feature = "secretstore")]
 extern crate ethcore_secretstore;
 
+#[cfg(unix)]
+extern crate libc;
+
 #[cfg(test)]
 #[macro_use]
 extern crate pretty_assertions;
@@ -89,6 +92,12 @@ extern crate pretty_assertions;
 #[cfg(test)]
 extern crate tempdir;
 
+#[cfg(all(test, unix))]
+extern crate parity_daemonize;
+
+#[cfg(test)]
+extern crate kvdb_memorydb;
+
 #[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
@@ -99,6 +108,7 @@ mod blockchain;
 mod cache;
 mod cli;
 mod configuration;
+mod daemon;
 mod export_hardcoded_sync;
 mod ipfs;
 mod deprecated;
@@ -106,8 +116,10 @@ mod helpers;
 mod informant;
 mod light_helpers;
 mod modules;
+mod output;
 mod params;
 mod presale;
+mod reload;
 mod rpc;
 mod rpc_apis;
 mod run;
@@ -132,6 +144,10 @@ use hash::keccak_buffer;
 use std::alloc::System;
 
 pub use self::configuration::Configuration;
+pub use self::daemon::ShutdownSequence;
+#[cfg(unix)]
+pub use self::daemon::{PidFile, clean_stale_pid_file};
+pub use self::reload::{ConfigReloader, ReloadReport};
 pub use self::run::RunningClient;
 pub use parity_rpc::PubSubSession;
 pub use ethcore_logger::{Config as LoggerConfig, setup_log, RotatingLogger};
@@ -217,6 +233,7 @@ fn execute<Cr, Rr>(
 		Cmd::SignerReject { id, port, authfile } => cli_signer::signer_reject(id, port, authfile).map(|s| ExecutionAction::Instant(Some(s))),
 		Cmd::Snapshot(snapshot_cmd) => snapshot::execute(snapshot_cmd).map(|s| ExecutionAction::Instant(Some(s))),
 		Cmd::ExportHardcodedSync(export_hs_cmd) => export_hardcoded_sync::execute(export_hs_cmd).map(|s| ExecutionAction::Instant(Some(s))),
+		Cmd::SecretStore(secretstore_cmd) => secretstore::execute(secretstore_cmd).map(|s| ExecutionAction::Instant(Some(s))),
 	}
 }
 
@@ -0,0 +1,88 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Hardcoded sync checkpoints loaded from a file.
+
+use std::fs::File;
+use std::path::Path;
+
+use ethcore::spec::SpecHardcodedSync;
+use light::cht;
+
+/// Load a checkpoint replacing the hardcoded sync data of the chain specification.
+/// The checkpoint is rejected unless its header has the hash given with it, if any,
+/// and its block is the first one after its CHTs of `cht_size` blocks.
+pub fn load_checkpoint(path: &Path, cht_size: u64) -> Result<SpecHardcodedSync, String> {
+	let file = File::open(path)
+		.map_err(|e| format!("Unable to open checkpoint file {}: {}", path.display(), e))?;
+	let checkpoint = SpecHardcodedSync::load(file)
+		.map_err(|e| format!("Invalid checkpoint file {}: {}", path.display(), e))?;
+
+	validate(&checkpoint, cht_size)
+		.map_err(|e| format!("Invalid checkpoint file {}: {}", path.display(), e))?;
+
+	Ok(checkpoint)
+}
+
+fn validate(checkpoint: &SpecHardcodedSync, cht_size: u64) -> Result<(), String> {
+	let header = checkpoint.header.decode()
+		.map_err(|e| format!("Undecodable header: {}", e))?;
+
+	let cht_count = checkpoint.chts.len() as u64;
+	if cht_count == 0 || header.number() != cht::start_number(cht_count, cht_size) {
+		return Err(format!("{} CHT roots don't cover the blocks before block #{}", cht_count, header.number()));
+	}
+
+	if checkpoint.total_difficulty < *header.difficulty() {
+		return Err(format!("Total difficulty {} is lower than the difficulty of block #{}", checkpoint.total_difficulty, header.number()));
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::PathBuf;
+
+	use light::cht;
+
+	use super::load_checkpoint;
+
+	fn fixture(name: &str) -> PathBuf {
+		PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("parity/light_helpers/tests").join(name)
+	}
+
+	#[test]
+	fn loads_checkpoint() {
+		let checkpoint = load_checkpoint(&fixture("checkpoint.json"), cht::SIZE).unwrap();
+
+		assert_eq!(checkpoint.header.number(), 4097);
+		assert_eq!(checkpoint.total_difficulty, 0x20080000.into());
+		assert_eq!(checkpoint.chts.len(), 2);
+	}
+
+	#[test]
+	fn rejects_checkpoint_with_wrong_cht_count() {
+		assert!(load_checkpoint(&fixture("checkpoint_wrong_chts.json"), cht::SIZE).is_err());
+		assert!(load_checkpoint(&fixture("checkpoint.json"), 64).is_err());
+		assert!(load_checkpoint(&fixture("missing.json"), cht::SIZE).is_err());
+	}
+
+	#[test]
+	fn rejects_checkpoint_with_wrong_hash() {
+		assert!(load_checkpoint(&fixture("checkpoint_wrong_hash.json"), cht::SIZE).is_err());
+	}
+}
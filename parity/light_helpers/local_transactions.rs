@@ -0,0 +1,269 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Persistence of the light client transaction queue in the local store.
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use ethereum_types::{Address, U256};
+use futures::Future;
+use io::{IoContext, IoHandler, TimerToken};
+use light::client::LightChainClient;
+use light::on_demand::{request, OnDemand, OnDemandRequester};
+use light::transaction_queue::TransactionQueue;
+use local_store::{LocalDataStore, NodeInfo};
+use parking_lot::{Mutex, RwLock};
+use sync::{LightSync, LightNetworkDispatcher};
+use types::transaction::PendingTransaction;
+
+const ALL_VALID_BACKREFS: &str = "no back-references, therefore all back-references valid; qed";
+
+const PERSIST_TIMER: TimerToken = 0;
+const PERSIST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Node info fetcher for the local store of a light client.
+pub struct LightNodeInfo {
+	/// The transaction queue to persist. `None` if it shouldn't be persisted.
+	pub txq: Option<Arc<RwLock<TransactionQueue>>>,
+	/// Transactions restored from the local store which aren't re-imported into the queue yet.
+	pub restoring: Arc<Mutex<Vec<PendingTransaction>>>,
+}
+
+impl NodeInfo for LightNodeInfo {
+	fn pending_transactions(&self) -> Vec<PendingTransaction> {
+		let txq = match self.txq {
+			Some(ref txq) => txq,
+			None => return Vec::new(),
+		};
+
+		// locked first, so transactions being re-imported are seen either here or in the queue.
+		let restoring = self.restoring.lock();
+		let mut pending = txq.read().pending_transactions();
+		pending.extend(restoring.iter().cloned());
+		pending
+	}
+}
+
+/// Updates the local store shortly after the transaction queue changes.
+pub struct PersistOnChange {
+	store: Arc<LocalDataStore<LightNodeInfo>>,
+	changed: Arc<AtomicBool>,
+}
+
+impl PersistOnChange {
+	/// Create a new handler listening to changes of the given queue.
+	pub fn new(store: Arc<LocalDataStore<LightNodeInfo>>, txq: &RwLock<TransactionQueue>) -> Self {
+		let changed = Arc::new(AtomicBool::new(false));
+
+		// listeners are called with the queue locked, so the store is only updated on timeout.
+		let flag = changed.clone();
+		txq.write().add_listener(Box::new(move |_| flag.store(true, Ordering::SeqCst)));
+
+		PersistOnChange { store, changed }
+	}
+}
+
+impl<M: Send + Sync + 'static> IoHandler<M> for PersistOnChange {
+	fn initialize(&self, io: &IoContext<M>) {
+		if let Err(e) = io.register_timer(PERSIST_TIMER, PERSIST_TIMEOUT) {
+			warn!(target: "local_store", "Error registering transaction queue persistence timer: {}", e);
+		}
+	}
+
+	fn timeout(&self, _io: &IoContext<M>, timer: TimerToken) {
+		if timer == PERSIST_TIMER && self.changed.swap(false, Ordering::SeqCst) {
+			if let Err(e) = self.store.update() {
+				debug!(target: "local_store", "Error updating local store: {}", e);
+			}
+		}
+	}
+}
+
+/// Re-import transactions restored from the local store into the queue.
+///
+/// The current nonces of their senders are fetched first, so transactions which became
+/// stale in the meantime are dropped instead of being propagated again. Until then the
+/// transactions are kept in `restoring`, so the local store doesn't lose them if it's
+/// written in the meantime or the nonces can't be fetched.
+/// Returns `None` if the nonces can't be fetched.
+pub fn restore_transactions<F, N>(
+	pending: Vec<PendingTransaction>,
+	restoring: Arc<Mutex<Vec<PendingTransaction>>>,
+	txq: Arc<RwLock<TransactionQueue>>,
+	fetch_nonces: F,
+) -> Option<impl Future<Item = (), Error = ()> + Send> where
+	F: FnOnce(Vec<Address>) -> Option<N>,
+	N: Future<Item = HashMap<Address, U256>, Error = String> + Send,
+{
+	let mut senders: Vec<Address> = pending.iter().map(|tx| tx.sender()).collect();
+	senders.sort();
+	senders.dedup();
+
+	*restoring.lock() = pending;
+
+	let restore = fetch_nonces(senders)?.then(move |nonces| {
+		match nonces {
+			Ok(nonces) => {
+				let mut restoring = restoring.lock();
+				let pending = mem::replace(&mut *restoring, Vec::new());
+				let total = pending.len();
+				let imported = txq.write().import_persisted(pending, &nonces);
+				info!("Restored {} of {} saved local transactions", imported, total);
+			},
+			Err(e) => warn!("Error fetching account nonces to restore saved transactions: {}", e),
+		}
+		Ok(())
+	});
+
+	Some(restore)
+}
+
+/// Fetch the current nonces of the given accounts on demand.
+/// Returns `None` if the network is unavailable.
+pub fn fetch_nonces(
+	client: &LightChainClient,
+	sync: &LightSync,
+	on_demand: &OnDemand,
+	senders: Vec<Address>,
+) -> Option<impl Future<Item = HashMap<Address, U256>, Error = String> + Send> {
+	let best_header = client.best_block_header();
+	let start_nonce = client.engine().account_start_nonce(best_header.number());
+
+	sync.with_context(move |ctx| {
+		let nonce_reqs = senders.iter()
+			.map(|&address| request::Account { header: best_header.clone().into(), address })
+			.collect::<Vec<_>>();
+
+		on_demand.request(ctx, nonce_reqs)
+			.expect(ALL_VALID_BACKREFS)
+			.map(move |accs| senders.into_iter()
+				.zip(accs.into_iter().map(|maybe_acc| maybe_acc.map_or(start_nonce, |acc| acc.nonce)))
+				.collect())
+			.map_err(|e| format!("{}", e))
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use std::sync::Arc;
+
+	use ethereum_types::{Address, U256};
+	use ethkey::{Brain, Generator};
+	use futures::Future;
+	use futures::sync::oneshot;
+	use light::transaction_queue::TransactionQueue;
+	use local_store::NodeInfo;
+	use parking_lot::{Mutex, RwLock};
+	use types::transaction::{Transaction, PendingTransaction, Condition};
+
+	use super::{LightNodeInfo, restore_transactions};
+
+	fn transaction(seed: &str, nonce: u64, condition: Option<Condition>) -> PendingTransaction {
+		let keypair = Brain::new(seed.into()).generate().unwrap();
+		let mut tx = Transaction::default();
+		tx.nonce = nonce.into();
+
+		PendingTransaction::new(tx.sign(keypair.secret(), None), condition)
+	}
+
+	#[test]
+	fn round_trips_queue_dropping_stale_transactions() {
+		let db = Arc::new(::kvdb_memorydb::create(0));
+		let pending = vec![
+			transaction("first", 3, None),
+			transaction("first", 4, Some(Condition::Number(100))),
+		];
+		let stale = transaction("second", 0, None);
+
+		{
+			let txq = Arc::new(RwLock::new(TransactionQueue::default()));
+			for tx in pending.iter().chain(Some(&stale)) {
+				txq.write().import(tx.clone()).unwrap();
+			}
+
+			// the queue is written on shutdown.
+			::local_store::create(db.clone(), None, LightNodeInfo { txq: Some(txq), restoring: Default::default() }, Default::default());
+		}
+
+		let store = ::local_store::create(db.clone(), None, LightNodeInfo { txq: None, restoring: Default::default() }, Default::default());
+		let mut restored = store.pending_transactions().unwrap();
+		restored.sort_by_key(|tx| tx.nonce);
+		assert_eq!(restored, vec![stale.clone(), pending[0].clone(), pending[1].clone()]);
+
+		// the transaction of the second sender got mined in the meantime.
+		let nonces: HashMap<Address, U256> = vec![
+			(pending[0].sender(), 3.into()),
+			(stale.sender(), 1.into()),
+		].into_iter().collect();
+
+		let mut txq = TransactionQueue::default();
+		assert_eq!(txq.import_persisted(restored, &nonces), 2);
+		assert_eq!(txq.pending_transactions(), pending);
+		assert_eq!(txq.ready_transactions(0, 0), vec![pending[0].clone()]);
+		assert_eq!(txq.queued_senders(), vec![pending[0].sender()]);
+	}
+
+	#[test]
+	fn keeps_transactions_stored_until_restored() {
+		let db = Arc::new(::kvdb_memorydb::create(0));
+		let mut pending = vec![transaction("first", 3, None), transaction("second", 0, None)];
+		pending.sort_by_key(|tx| tx.nonce);
+
+		let txq = Arc::new(RwLock::new(TransactionQueue::default()));
+		let restoring = Arc::new(Mutex::new(Vec::new()));
+		let store = ::local_store::create(db.clone(), None, LightNodeInfo {
+			txq: Some(txq.clone()),
+			restoring: restoring.clone(),
+		}, Default::default());
+
+		let (send_nonces, nonces) = oneshot::channel::<HashMap<Address, U256>>();
+		let restore = restore_transactions(pending.clone(), restoring.clone(), txq.clone(), |mut senders| {
+			senders.sort();
+			let mut expected = pending.iter().map(|tx| tx.sender()).collect::<Vec<_>>();
+			expected.sort();
+			assert_eq!(senders, expected);
+			Some(nonces.map_err(|_| "canceled".to_owned()))
+		}).unwrap();
+
+		// the store is written while the nonces are fetched.
+		store.update().unwrap();
+		let stored = || {
+			let mut stored = ::local_store::create(db.clone(), None, LightNodeInfo { txq: None, restoring: Default::default() }, Default::default())
+				.pending_transactions()
+				.unwrap();
+			stored.sort_by_key(|tx| tx.nonce);
+			stored
+		};
+		assert_eq!(stored(), pending);
+
+		// the transaction of the second sender got mined in the meantime.
+		send_nonces.send(vec![
+			(pending[0].sender(), 1.into()),
+			(pending[1].sender(), 3.into()),
+		].into_iter().collect()).unwrap();
+		restore.wait().unwrap();
+
+		assert!(restoring.lock().is_empty());
+		assert_eq!(txq.read().pending_transactions(), vec![pending[1].clone()]);
+		store.update().unwrap();
+		assert_eq!(stored(), vec![pending[1].clone()]);
+	}
+}
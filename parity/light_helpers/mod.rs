@@ -16,6 +16,10 @@
 
 //! Utilities and helpers for the light client.
 
+mod checkpoint;
 mod epoch_fetch;
+mod local_transactions;
 
+pub use self::checkpoint::load_checkpoint;
 pub use self::epoch_fetch::EpochFetch;
+pub use self::local_transactions::{LightNodeInfo, PersistOnChange, fetch_nonces, restore_transactions};
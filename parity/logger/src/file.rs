@@ -0,0 +1,426 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Log file with size-based and daily rotation.
+//!
+//! Rotated files are named after the log file with a `.1`, `.2`, ... suffix,
+//! the most recent one first, and a `.gz` suffix if they are compressed.
+//! Compressed rotation only renames the file to a staging name on the logging path,
+//! it is archived and compressed by a background thread so logging isn't held up by it.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use parking_lot::Mutex;
+use time;
+
+/// Rotation of the log file.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FileRotation {
+	/// Rotate the file before it grows beyond this number of bytes.
+	pub max_size: Option<u64>,
+	/// Rotate the file when the day changes.
+	pub daily: bool,
+	/// Number of rotated files to keep.
+	pub keep: usize,
+	/// Compress rotated files with gzip.
+	pub compress: bool,
+}
+
+impl Default for FileRotation {
+	fn default() -> Self {
+		FileRotation {
+			max_size: None,
+			daily: false,
+			keep: 5,
+			compress: false,
+		}
+	}
+}
+
+struct Current {
+	file: Option<File>,
+	size: u64,
+	day: i32,
+}
+
+/// Log file, appended to line by line and rotated according to `FileRotation`.
+pub struct LogFile {
+	path: PathBuf,
+	rotation: FileRotation,
+	current: Mutex<Current>,
+	// held while archives are shifted or compressed, never taken on the logging path
+	// when compressing.
+	archives: Arc<Mutex<()>>,
+	// sequence number of the next staged file.
+	next_staged: AtomicUsize,
+	compression: Mutex<Vec<thread::JoinHandle<()>>>,
+}
+
+impl LogFile {
+	/// Open the log file for appending, creating it if it doesn't exist.
+	pub fn open<P: Into<PathBuf>>(path: P, rotation: FileRotation) -> io::Result<Self> {
+		let path = path.into();
+		let (file, size) = open(&path)?;
+		// files staged, but not archived before the last shutdown are archived on the next rotation.
+		let next_staged = staged(&path)?.last().map_or(0, |&(seq, _)| seq + 1);
+
+		Ok(LogFile {
+			path,
+			rotation,
+			current: Mutex::new(Current {
+				file: Some(file),
+				size,
+				day: today(),
+			}),
+			archives: Default::default(),
+			next_staged: AtomicUsize::new(next_staged),
+			compression: Mutex::new(Vec::new()),
+		})
+	}
+
+	/// Append a line to the file, rotating it first if it's due.
+	pub fn write_line(&self, line: &str) -> io::Result<()> {
+		self.write_line_on(line, today())
+	}
+
+	/// Open the file again, e.g. after it has been moved by an external tool.
+	pub fn reopen(&self) -> io::Result<()> {
+		let mut current = self.current.lock();
+		current.file.take();
+
+		let (file, size) = open(&self.path)?;
+		current.file = Some(file);
+		current.size = size;
+		Ok(())
+	}
+
+	fn write_line_on(&self, line: &str, day: i32) -> io::Result<()> {
+		// the file is rotated and written to under the same lock,
+		// so no line gets lost or written out of order.
+		let mut current = self.current.lock();
+		let len = line.len() as u64 + 1;
+
+		let oversized = self.rotation.max_size.map_or(false, |max| current.size + len > max);
+		let next_day = self.rotation.daily && current.day != day;
+		current.day = day;
+
+		// the line is written even if the rotation failed.
+		let rotated = match current.size > 0 && (oversized || next_day) {
+			true => self.rotate(&mut current),
+			false => Ok(()),
+		};
+
+		if current.file.is_none() {
+			let (file, size) = open(&self.path)?;
+			current.file = Some(file);
+			current.size = size;
+		}
+		if let Some(ref mut file) = current.file {
+			file.write_all(line.as_bytes())?;
+			file.write_all(b"\n")?;
+		}
+		current.size += len;
+
+		rotated
+	}
+
+	fn rotate(&self, current: &mut Current) -> io::Result<()> {
+		// the file has to be closed first, it can't be renamed while open on some platforms.
+		current.file.take();
+
+		let archived = match self.rotation.keep {
+			0 => fs::remove_file(&self.path),
+			_ if self.rotation.compress => self.stage_current(),
+			keep => {
+				let _archives = self.archives.lock();
+				shift_archives(&self.path, keep).and_then(|_| fs::rename(&self.path, archive_path(&self.path, 1, false)))
+			},
+		};
+
+		let (file, size) = open(&self.path)?;
+		current.file = Some(file);
+		current.size = size;
+		archived
+	}
+
+	// move the current file out of the way and archive it in the background.
+	fn stage_current(&self) -> io::Result<()> {
+		let seq = self.next_staged.fetch_add(1, Ordering::SeqCst);
+		fs::rename(&self.path, staged_path(&self.path, seq))?;
+
+		let archives = self.archives.clone();
+		let path = self.path.clone();
+		let keep = self.rotation.keep;
+
+		let handle = thread::Builder::new().name("log-compression".into()).spawn(move || {
+			let _archives = archives.lock();
+			if let Err(e) = archive_staged(&path, keep) {
+				warn!(target: "logger", "Failed to archive log file {}: {}", path.display(), e);
+			}
+		})?;
+		self.compression.lock().push(handle);
+		Ok(())
+	}
+
+	#[cfg(test)]
+	fn wait_for_compression(&self) {
+		for handle in self.compression.lock().drain(..) {
+			handle.join().expect("log compression doesn't panic");
+		}
+	}
+}
+
+fn open(path: &Path) -> io::Result<(File, u64)> {
+	let file = OpenOptions::new().append(true).create(true).open(path)?;
+	let size = file.metadata()?.len();
+	Ok((file, size))
+}
+
+fn archive_path(path: &Path, index: usize, compressed: bool) -> PathBuf {
+	let mut name = path.to_owned().into_os_string();
+	name.push(format!(".{}", index));
+	if compressed {
+		name.push(".gz");
+	}
+	name.into()
+}
+
+fn staged_path(path: &Path, seq: usize) -> PathBuf {
+	let mut name = path.to_owned().into_os_string();
+	name.push(format!(".staged.{}", seq));
+	name.into()
+}
+
+// staged files with their sequence numbers, the oldest first.
+fn staged(path: &Path) -> io::Result<Vec<(usize, PathBuf)>> {
+	let dir = match path.parent() {
+		Some(dir) if dir != Path::new("") => dir,
+		_ => Path::new("."),
+	};
+	let prefix = match path.file_name() {
+		Some(name) => format!("{}.staged.", name.to_string_lossy()),
+		None => return Ok(Vec::new()),
+	};
+
+	let mut staged = Vec::new();
+	for entry in fs::read_dir(dir)? {
+		let entry = entry?;
+		let name = entry.file_name().to_string_lossy().into_owned();
+		if !name.starts_with(&prefix) {
+			continue;
+		}
+		if let Ok(seq) = name[prefix.len()..].parse() {
+			staged.push((seq, entry.path()));
+		}
+	}
+	staged.sort();
+	Ok(staged)
+}
+
+// shift the archives to make room for the first one, dropping the oldest.
+fn shift_archives(path: &Path, keep: usize) -> io::Result<()> {
+	for &compressed in &[false, true] {
+		let oldest = archive_path(path, keep, compressed);
+		if oldest.exists() {
+			fs::remove_file(&oldest)?;
+		}
+	}
+
+	for index in (1..keep).rev() {
+		for &compressed in &[false, true] {
+			let archive = archive_path(path, index, compressed);
+			if archive.exists() {
+				fs::rename(&archive, archive_path(path, index + 1, compressed))?;
+			}
+		}
+	}
+	Ok(())
+}
+
+// compress the staged files into the archives, in the order they were staged.
+fn archive_staged(path: &Path, keep: usize) -> io::Result<()> {
+	for (_, from) in staged(path)? {
+		shift_archives(path, keep)?;
+		let to = archive_path(path, 1, true);
+		if let Err(e) = compress(&from, &to) {
+			// keep the file uncompressed rather than losing it.
+			warn!(target: "logger", "Failed to compress {}: {}", from.display(), e);
+			let _ = fs::remove_file(&to);
+			fs::rename(&from, archive_path(path, 1, false))?;
+		}
+	}
+	Ok(())
+}
+
+fn compress(from: &Path, to: &Path) -> io::Result<()> {
+	let mut encoder = GzEncoder::new(File::create(to)?, Compression::default());
+	io::copy(&mut File::open(from)?, &mut encoder)?;
+	encoder.finish()?;
+	fs::remove_file(from)
+}
+
+// local day, changing at midnight.
+fn today() -> i32 {
+	let now = time::now();
+	now.tm_year * 1000 + now.tm_yday
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::{self, File};
+	use std::io::Read;
+	use std::path::Path;
+
+	use flate2::read::GzDecoder;
+	use tempdir::TempDir;
+
+	use super::{FileRotation, LogFile};
+
+	fn read(path: &Path) -> String {
+		let mut contents = String::new();
+		File::open(path).unwrap().read_to_string(&mut contents).unwrap();
+		contents
+	}
+
+	// lines of 32 bytes, including the newline.
+	fn line(index: usize) -> String {
+		format!("{:031}", index)
+	}
+
+	fn lines(range: ::std::ops::Range<usize>) -> String {
+		range.map(|index| line(index) + "\n").collect()
+	}
+
+	#[test]
+	fn rotates_past_size_threshold() {
+		let dir = TempDir::new("log-file").unwrap();
+		let path = dir.path().join("parity.log");
+		let rotation = FileRotation { max_size: Some(100), keep: 2, ..Default::default() };
+		let file = LogFile::open(&path, rotation).unwrap();
+
+		for index in 0..10 {
+			file.write_line(&line(index)).unwrap();
+		}
+
+		// three lines fit in a file, the oldest ones were dropped.
+		assert_eq!(read(&path), lines(9..10));
+		assert_eq!(read(&dir.path().join("parity.log.1")), lines(6..9));
+		assert_eq!(read(&dir.path().join("parity.log.2")), lines(3..6));
+		assert!(!dir.path().join("parity.log.3").exists());
+
+		// logging continues.
+		file.write_line(&line(10)).unwrap();
+		assert_eq!(read(&path), lines(9..11));
+	}
+
+	#[test]
+	fn appends_to_existing_file() {
+		let dir = TempDir::new("log-file").unwrap();
+		let path = dir.path().join("parity.log");
+		let rotation = FileRotation { max_size: Some(100), ..Default::default() };
+
+		LogFile::open(&path, rotation.clone()).unwrap().write_line(&line(0)).unwrap();
+		let file = LogFile::open(&path, rotation).unwrap();
+		for index in 1..4 {
+			file.write_line(&line(index)).unwrap();
+		}
+
+		assert_eq!(read(&path), lines(3..4));
+		assert_eq!(read(&dir.path().join("parity.log.1")), lines(0..3));
+	}
+
+	#[test]
+	fn compresses_rotated_files() {
+		let dir = TempDir::new("log-file").unwrap();
+		let path = dir.path().join("parity.log");
+		let rotation = FileRotation { max_size: Some(100), compress: true, ..Default::default() };
+		let file = LogFile::open(&path, rotation).unwrap();
+
+		for index in 0..7 {
+			file.write_line(&line(index)).unwrap();
+		}
+		file.wait_for_compression();
+
+		let mut decompressed = String::new();
+		GzDecoder::new(File::open(dir.path().join("parity.log.2.gz")).unwrap()).read_to_string(&mut decompressed).unwrap();
+		assert_eq!(decompressed, lines(0..3));
+		assert!(dir.path().join("parity.log.1.gz").exists());
+		assert!(!dir.path().join("parity.log.1").exists());
+		assert!(!dir.path().join("parity.log.staged.0").exists());
+		assert_eq!(read(&path), lines(6..7));
+	}
+
+	#[test]
+	fn logging_is_not_held_up_by_compression() {
+		let dir = TempDir::new("log-file").unwrap();
+		let path = dir.path().join("parity.log");
+		let rotation = FileRotation { max_size: Some(100), compress: true, ..Default::default() };
+		let file = LogFile::open(&path, rotation).unwrap();
+
+		{
+			// compression is stuck, rotation still goes on.
+			let _archives = file.archives.lock();
+			for index in 0..7 {
+				file.write_line(&line(index)).unwrap();
+			}
+			assert_eq!(read(&path), lines(6..7));
+		}
+		file.wait_for_compression();
+
+		let mut decompressed = String::new();
+		GzDecoder::new(File::open(dir.path().join("parity.log.1.gz")).unwrap()).read_to_string(&mut decompressed).unwrap();
+		assert_eq!(decompressed, lines(3..6));
+		assert!(dir.path().join("parity.log.2.gz").exists());
+	}
+
+	#[test]
+	fn rotates_daily() {
+		let dir = TempDir::new("log-file").unwrap();
+		let path = dir.path().join("parity.log");
+		let file = LogFile::open(&path, FileRotation { daily: true, ..Default::default() }).unwrap();
+
+		file.write_line_on("first", 1).unwrap();
+		file.write_line_on("second", 1).unwrap();
+		file.write_line_on("third", 2).unwrap();
+
+		assert_eq!(read(&dir.path().join("parity.log.1")), "first\nsecond\n");
+		assert_eq!(read(&path), "third\n");
+	}
+
+	#[test]
+	fn reopens_moved_file() {
+		let dir = TempDir::new("log-file").unwrap();
+		let path = dir.path().join("parity.log");
+		let moved = dir.path().join("parity.log.old");
+		let file = LogFile::open(&path, FileRotation::default()).unwrap();
+
+		file.write_line("before").unwrap();
+		fs::rename(&path, &moved).unwrap();
+		file.reopen().unwrap();
+		file.write_line("after").unwrap();
+
+		assert_eq!(read(&moved), "before\n");
+		assert_eq!(read(&path), "after\n");
+	}
+}
@@ -20,24 +20,32 @@ extern crate ansi_term;
 extern crate arrayvec;
 extern crate atty;
 extern crate env_logger;
-extern crate log as rlog;
+extern crate flate2;
 extern crate parking_lot;
 extern crate regex;
 extern crate time;
 
 #[macro_use]
 extern crate lazy_static;
+#[macro_use]
+extern crate log as rlog;
 
+#[cfg(test)]
+extern crate tempdir;
+
+mod file;
 mod rotating;
 
-use std::{env, thread, fs};
+use std::{env, thread, io};
 use std::sync::{Weak, Arc};
 use std::io::Write;
-use env_logger::{Builder as LogBuilder, Formatter};
+use env_logger::{Builder as LogBuilder, Formatter, Logger as EnvLogger};
+use rlog::{Log, Metadata, Record};
 use regex::Regex;
 use ansi_term::Colour;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 
+pub use file::{FileRotation, LogFile};
 pub use rotating::{RotatingLogger, init_log};
 
 #[derive(Debug, PartialEq, Clone)]
@@ -45,6 +53,7 @@ pub struct Config {
 	pub mode: Option<String>,
 	pub color: bool,
 	pub file: Option<String>,
+	pub file_rotation: FileRotation,
 }
 
 impl Default for Config {
@@ -53,17 +62,50 @@ impl Default for Config {
 			mode: None,
 			color: !cfg!(windows),
 			file: None,
+			file_rotation: FileRotation::default(),
 		}
 	}
 }
 
+type Format = Fn(&mut Formatter, &Record) -> io::Result<()> + Sync + Send;
+
+// logger set up by `setup_log`, kept together with its format to replace the filters.
+struct Filtered {
+	logger: EnvLogger,
+	format: Arc<Format>,
+}
+
 lazy_static! {
 	static ref ROTATING_LOGGER : Mutex<Weak<RotatingLogger>> = Mutex::new(Default::default());
+	static ref FILTERED_LOGGER: RwLock<Option<Filtered>> = RwLock::new(None);
+	static ref LOG_FILE: Mutex<Weak<LogFile>> = Mutex::new(Default::default());
 }
 
-/// Sets up the logger
-pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
-	use rlog::*;
+// the global logger, forwarding to the current filtered logger.
+struct Reloadable;
+
+impl Log for Reloadable {
+	fn enabled(&self, metadata: &Metadata) -> bool {
+		FILTERED_LOGGER.read().as_ref().map_or(false, |f| f.logger.enabled(metadata))
+	}
+
+	fn log(&self, record: &Record) {
+		if let Some(ref f) = *FILTERED_LOGGER.read() {
+			f.logger.log(record);
+		}
+	}
+
+	fn flush(&self) {
+		if let Some(ref f) = *FILTERED_LOGGER.read() {
+			f.logger.flush();
+		}
+	}
+}
+
+// create a builder with the default filters, `RUST_LOG` and `mode` applied.
+// returns the builder and the applied levels.
+fn filters(mode: Option<&str>) -> (LogBuilder, String) {
+	use rlog::LevelFilter;
 
 	let mut levels = String::new();
 	let mut builder = LogBuilder::new();
@@ -80,23 +122,71 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 		builder.parse(&lvl);
 	}
 
-	if let Some(ref s) = config.mode {
+	if let Some(s) = mode {
 		levels.push_str(s);
 		builder.parse(s);
 	}
 
+	(builder, levels)
+}
+
+fn build(mut builder: LogBuilder, format: &Arc<Format>) -> EnvLogger {
+	let format = format.clone();
+	builder.format(move |buf, record| format(buf, record));
+	builder.build()
+}
+
+/// Replaces the filters of the logger set up by `setup_log`.
+/// As on setup, `mode` is applied after the default filters and `RUST_LOG`.
+pub fn set_log_levels(mode: Option<&str>) -> Result<(), String> {
+	let mut filtered = FILTERED_LOGGER.write();
+	let filtered = filtered.as_mut().ok_or_else(|| "Logger is not set up".to_owned())?;
+
+	let (builder, levels) = filters(mode);
+	filtered.logger = build(builder, &filtered.format);
+	rlog::set_max_level(filtered.logger.filter());
+
+	if let Some(logs) = ROTATING_LOGGER.lock().upgrade() {
+		logs.set_levels(levels);
+	}
+	Ok(())
+}
+
+/// Reopens the log file of the logger set up by `setup_log`,
+/// e.g. after it has been moved by an external tool.
+pub fn reopen_log_file() -> Result<(), String> {
+	match LOG_FILE.lock().upgrade() {
+		Some(file) => file.reopen().map_err(|e| format!("Cannot reopen log file: {}", e)),
+		None => Ok(()),
+	}
+}
+
+/// Appends a line to the log file of the logger set up by `setup_log`, if there is one.
+/// For output printed outside of the logger, e.g. machine-readable reports.
+pub fn write_log_file_line(line: &str) {
+	if let Some(file) = LOG_FILE.lock().upgrade() {
+		// ignore errors - there's nothing we can do
+		let _ = file.write_line(line);
+	}
+}
+
+/// Sets up the logger
+pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
+	use rlog::*;
+
+	let (builder, levels) = filters(config.mode.as_ref().map(String::as_str));
+
 	let isatty = atty::is(atty::Stream::Stderr);
 	let enable_color = config.color && isatty;
 	let logs = Arc::new(RotatingLogger::new(levels));
 	let logger = logs.clone();
-	let mut open_options = fs::OpenOptions::new();
 
 	let maybe_file = match config.file.as_ref() {
-		Some(f) => Some(open_options
-			.append(true).create(true).open(f)
-			.map_err(|e| format!("Cannot write to log file given: {}, {}", f, e))?),
+		Some(f) => Some(Arc::new(LogFile::open(f, config.file_rotation.clone())
+			.map_err(|e| format!("Cannot write to log file given: {}, {}", f, e))?)),
 		None => None,
 	};
+	let log_file = maybe_file.as_ref().map_or_else(Weak::new, Arc::downgrade);
 
 	let format = move |buf: &mut Formatter, record: &Record| {
 		let timestamp = time::strftime("%Y-%m-%d %H:%M:%S %Z", &time::now()).unwrap();
@@ -115,10 +205,9 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 			false => removed_color.clone(),
 		};
 
-		if let Some(mut file) = maybe_file.as_ref() {
+		if let Some(ref file) = maybe_file {
 			// ignore errors - there's nothing we can do
-			let _ = file.write_all(removed_color.as_bytes());
-			let _ = file.write_all(b"\n");
+			let _ = file.write_line(&removed_color);
 		}
 		logger.append(removed_color);
 		if !isatty && record.level() <= Level::Info && atty::is(atty::Stream::Stdout) {
@@ -129,10 +218,17 @@ pub fn setup_log(config: &Config) -> Result<Arc<RotatingLogger>, String> {
 		writeln!(buf, "{}", ret)
     };
 
-	builder.format(format);
-	builder.try_init()
+	let format: Arc<Format> = Arc::new(format);
+	let filtered = Filtered {
+		logger: build(builder, &format),
+		format,
+	};
+	set_boxed_logger(Box::new(Reloadable))
 		.and_then(|_| {
+			set_max_level(filtered.logger.filter());
+			*FILTERED_LOGGER.write() = Some(filtered);
 			*ROTATING_LOGGER.lock() = Arc::downgrade(&logs);
+			*LOG_FILE.lock() = log_file;
 			Ok(logs)
 		})
 		// couldn't create new logger - try to fall back on previous logger.
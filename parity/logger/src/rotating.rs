@@ -48,7 +48,7 @@ const LOG_SIZE : usize = 128;
 /// Logger implementation that keeps up to `LOG_SIZE` log elements.
 pub struct RotatingLogger {
 	/// Defined logger levels
-	levels: String,
+	levels: RwLock<String>,
 	/// Logs array. Latest log is always at index 0
 	logs: RwLock<ArrayVec<[String; LOG_SIZE]>>,
 }
@@ -59,7 +59,7 @@ impl RotatingLogger {
 	/// It does not enforce levels - it's just read only.
 	pub fn new(levels: String) -> Self {
 		RotatingLogger {
-			levels: levels,
+			levels: RwLock::new(levels),
 			logs: RwLock::new(ArrayVec::<[_; LOG_SIZE]>::new()),
 		}
 	}
@@ -74,8 +74,13 @@ impl RotatingLogger {
 	}
 
 	/// Return levels
-	pub fn levels(&self) -> &str {
-		&self.levels
+	pub fn levels(&self) -> String {
+		self.levels.read().clone()
+	}
+
+	/// Replace levels after the filters of the logger changed.
+	pub fn set_levels(&self, levels: String) {
+		*self.levels.write() = levels;
 	}
 
 	/// Return logs
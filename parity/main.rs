@@ -30,6 +30,7 @@ extern crate parity_daemonize;
 extern crate ansi_term;
 
 #[cfg(windows)] extern crate winapi;
+#[cfg(unix)] extern crate libc;
 extern crate ethcore_logger;
 
 use std::ffi::OsString;
@@ -38,6 +39,9 @@ use std::io::{Read, Write};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(unix)]
+use std::sync::atomic::AtomicIsize;
+use std::time::Duration;
 use std::{process, env};
 
 use ansi_term::Colour;
@@ -46,10 +50,13 @@ use dir::default_hypervisor_path;
 use fdlimit::raise_fd_limit;
 use ethcore_logger::setup_log;
 use parity_ethereum::{start, ExecutionAction};
+#[cfg(unix)]
+use parity_ethereum::{ConfigReloader, PidFile, clean_stale_pid_file};
 use parity_daemonize::AsHandle;
 use parking_lot::{Condvar, Mutex};
 
 const PLEASE_RESTART_EXIT_CODE: i32 = 69;
+const SHUTDOWN_TIMEOUT_EXIT_CODE: i32 = 3;
 const PARITY_EXECUTABLE_NAME: &str = "parity";
 
 #[derive(Debug)]
@@ -138,6 +145,73 @@ fn global_init() {
 #[cfg(not(windows))]
 fn global_cleanup() {}
 
+// Write end of the pipe signal handlers forward received signals through, -1 until installed.
+#[cfg(unix)]
+static SIGNAL_PIPE: AtomicIsize = AtomicIsize::new(-1);
+
+#[cfg(unix)]
+extern "C" fn forward_signal(signal: libc::c_int) {
+	let fd = SIGNAL_PIPE.load(Ordering::SeqCst);
+	if fd >= 0 {
+		// only async-signal-safe calls are allowed here
+		let byte = signal as u8;
+		unsafe { libc::write(fd as libc::c_int, &byte as *const u8 as *const libc::c_void, 1); }
+	}
+}
+
+/// Reload the configuration of the running client on SIGHUP
+/// and reopen the log file on SIGUSR1, as expected by external log rotation.
+#[cfg(unix)]
+fn handle_signals(reloader: Arc<ConfigReloader>) {
+	use std::os::unix::io::FromRawFd;
+	use std::{io, mem, ptr};
+
+	let mut fds = [0 as libc::c_int; 2];
+	if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+		warn!("Unable to handle SIGHUP and SIGUSR1: {}", io::Error::last_os_error());
+		return;
+	}
+	let mut pipe = unsafe { File::from_raw_fd(fds[0]) };
+
+	let spawned = ::std::thread::Builder::new().name("signals".into()).spawn(move || {
+		let mut signal = [0u8; 1];
+		while pipe.read_exact(&mut signal).is_ok() {
+			match signal[0] as libc::c_int {
+				libc::SIGHUP => {
+					info!("Received SIGHUP, reloading configuration");
+					if let Err(e) = reloader.reload() {
+						warn!("Configuration not reloaded: {}", e);
+					}
+				},
+				libc::SIGUSR1 => {
+					if let Err(e) = ethcore_logger::reopen_log_file() {
+						warn!("{}", e);
+					}
+				},
+				_ => {},
+			}
+		}
+	});
+	if let Err(e) = spawned {
+		warn!("Unable to handle SIGHUP and SIGUSR1: {}", e);
+		return;
+	}
+
+	SIGNAL_PIPE.store(fds[1] as isize, Ordering::SeqCst);
+	for &signal in &[libc::SIGHUP, libc::SIGUSR1] {
+		let installed = unsafe {
+			let mut action: libc::sigaction = mem::zeroed();
+			action.sa_sigaction = forward_signal as usize;
+			action.sa_flags = libc::SA_RESTART;
+			libc::sigemptyset(&mut action.sa_mask);
+			libc::sigaction(signal, &action, ptr::null_mut())
+		};
+		if installed != 0 {
+			warn!("Unable to handle signal {}: {}", signal, io::Error::last_os_error());
+		}
+	}
+}
+
 // Starts parity binary installed via `parity-updater` and returns the code it exits with.
 fn run_parity() -> Result<(), Error> {
 	global_init();
@@ -206,6 +280,14 @@ fn main_direct(force_can_restart: bool) -> i32 {
 		info!("{}", Colour::Blue.paint("starting in daemon mode").to_string());
 		let _ = std::io::stdout().flush();
 
+		#[cfg(unix)]
+		{
+			if let Err(e) = clean_stale_pid_file(pid.as_ref()) {
+				error!("{}", Colour::Red.paint(e));
+				return 1;
+			}
+		}
+
 		match parity_daemonize::daemonize(pid) {
 			Ok(h) => Some(h),
 			Err(e) => {
@@ -220,7 +302,25 @@ fn main_direct(force_can_restart: bool) -> i32 {
 		None
 	};
 
+	// the PID file is locked by the daemon until it exits and removed afterwards.
+	#[cfg(unix)]
+	let _pid_file = match conf.args.arg_daemon_pid_file {
+		Some(ref path) => match PidFile::adopt(path) {
+			Ok(pid_file) => Some(pid_file),
+			Err(e) => {
+				if let Some(mut handle) = handle {
+					handle.detach_with_msg(format!("{}", Colour::Red.paint(&e)))
+				}
+				eprintln!("{}", e);
+				return 1;
+			}
+		},
+		None => None,
+	};
+
 	let can_restart = force_can_restart || conf.args.flag_can_restart;
+	let shutdown_timeout = conf.args.arg_shutdown_timeout;
+	let pid_file_path = conf.args.arg_daemon_pid_file.clone();
 
 	// increase max number of open files
 	raise_fd_limit();
@@ -314,6 +414,9 @@ fn main_direct(force_can_restart: bool) -> i32 {
 					}
 				});
 
+				#[cfg(unix)]
+				handle_signals(client.config_reloader());
+
 				// so the client has started successfully
 				// if this is a daemon, detach from the parent process
 				if let Some(mut handle) = handle {
@@ -326,7 +429,14 @@ fn main_direct(force_can_restart: bool) -> i32 {
 					let _ = exit.1.wait(&mut lock);
 				}
 
-				client.shutdown();
+				// exit uncleanly if the subsystems haven't stopped in time.
+				client.shutdown_sequence().run(Duration::from_secs(shutdown_timeout), move |pending| {
+					error!("Shutdown didn't finish within {} seconds, exiting. Still running: {}", shutdown_timeout, pending.join(", "));
+					if let Some(path) = pid_file_path {
+						let _ = remove_file(path);
+					}
+					process::exit(SHUTDOWN_TIMEOUT_EXIT_CODE);
+				});
 
 				if lock.should_restart {
 					if let Some(ref spec_name) = lock.spec_name_override {
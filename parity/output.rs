@@ -0,0 +1,331 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Machine-readable output of the informant and of the blockchain subcommands.
+//!
+//! With `--json-logs` every report is printed as a single line JSON object.
+//! Field names of the types below are part of the interface and must not change.
+
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ethcore_logger;
+use serde::Serialize;
+use serde_json;
+
+/// Format of the informant and subcommand summaries.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OutputFormat {
+	/// Human-readable log lines.
+	Human,
+	/// One JSON object per report.
+	Json,
+}
+
+impl Default for OutputFormat {
+	fn default() -> Self {
+		OutputFormat::Human
+	}
+}
+
+impl OutputFormat {
+	/// JSON if `json` is set, human-readable otherwise.
+	pub fn json_if(json: bool) -> Self {
+		match json {
+			true => OutputFormat::Json,
+			false => OutputFormat::Human,
+		}
+	}
+}
+
+/// Print a report on a single line to stdout, or to stderr if stdout carries other data.
+/// The line is also appended to the log file, if there is one.
+pub fn print_json<T: Serialize>(report: &T, stdout_taken: bool) {
+	let json = serde_json::to_string(report).expect("reports are plain structs; serialization can't fail; qed");
+	match stdout_taken {
+		true => eprintln!("{}", json),
+		false => println!("{}", json),
+	}
+	ethcore_logger::write_log_file_line(&json);
+}
+
+/// Seconds since the Unix epoch.
+pub fn timestamp() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Periodic status report of the informant.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStatus {
+	/// Time of the report, in seconds since the Unix epoch.
+	pub timestamp: u64,
+	/// Whether the node is catching up with the chain.
+	pub syncing: bool,
+	/// Best block of the local chain.
+	pub best_block: BlockStatus,
+	/// Connected peers, `null` if the node has no network.
+	pub peers: Option<PeerStatus>,
+	/// Block import queue.
+	pub queue: QueueStatus,
+	/// Progress of the sync, `null` if the node has no network.
+	pub sync: Option<SyncProgress>,
+	/// Transaction pool, `null` for a light client.
+	pub txpool: Option<TxPoolStatus>,
+	/// Import rates since the previous report.
+	pub import_rate: ImportRate,
+	/// Memory used by caches, in bytes, by cache name.
+	pub caches: BTreeMap<String, usize>,
+	/// RPC usage, `null` if no RPC server is running.
+	pub rpc: Option<RpcStatus>,
+}
+
+/// Number and hash of a block.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockStatus {
+	/// Block number.
+	pub number: u64,
+	/// Block hash, `0x`-prefixed.
+	pub hash: String,
+}
+
+/// Connected peers.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerStatus {
+	/// Number of connected peers.
+	pub connected: usize,
+	/// Maximal number of peers.
+	pub max: u32,
+}
+
+/// Sizes of the block import queue.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueStatus {
+	/// Blocks waiting for verification.
+	pub unverified: usize,
+	/// Verified blocks waiting to be imported.
+	pub verified: usize,
+}
+
+/// Progress of the sync.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProgress {
+	/// Last block imported by the sync.
+	pub last_imported_block: u64,
+	/// Last old block imported by the ancient block sync, if it's running.
+	pub last_imported_old_block: Option<u64>,
+	/// Snapshot restoration, if it's running.
+	pub snapshot: Option<SnapshotProgress>,
+}
+
+/// Progress of a snapshot restoration.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotProgress {
+	/// Restored chunks.
+	pub chunks_done: u64,
+	/// All chunks, `null` while the restoration is initializing.
+	pub chunks: Option<u64>,
+}
+
+/// Contents of the transaction pool.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxPoolStatus {
+	/// Number of transactions.
+	pub transactions: usize,
+	/// Number of distinct senders.
+	pub senders: usize,
+}
+
+/// Import rates.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportRate {
+	/// Blocks (headers for a light client) imported per second.
+	pub blocks_per_second: f64,
+	/// Transactions applied per second, `null` for a light client.
+	pub transactions_per_second: Option<f64>,
+	/// Millions of gas processed per second, `null` for a light client.
+	pub mgas_per_second: Option<f64>,
+}
+
+/// Usage of the RPC servers.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcStatus {
+	/// Open sessions.
+	pub sessions: usize,
+	/// Requests per second.
+	pub requests_per_second: usize,
+	/// Approximate roundtrip of a request, in microseconds.
+	pub roundtrip_us: u64,
+}
+
+/// Imported blocks, reported at most once a second like the human-readable import lines.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockImport {
+	/// Time of the report, in seconds since the Unix epoch.
+	pub timestamp: u64,
+	/// Last imported block (header for a light client).
+	pub block: BlockStatus,
+	/// Transactions in the last imported block, `null` for a light client.
+	pub transactions: Option<usize>,
+	/// Gas used by the last imported block.
+	pub gas_used: u64,
+	/// Import time of the last batch, in milliseconds, `null` for a light client.
+	pub duration_ms: Option<u64>,
+	/// Blocks (headers for a light client) imported since the previous report, besides the last one.
+	pub skipped_blocks: usize,
+	/// Transactions in the skipped blocks, `null` for a light client.
+	pub skipped_transactions: Option<usize>,
+}
+
+/// Summary of `parity import`.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+	/// Duration of the import, in milliseconds.
+	pub duration_ms: u64,
+	/// Imported blocks, or headers for a light client.
+	pub blocks: u64,
+	/// Applied transactions, `null` for a light client.
+	pub transactions: Option<u64>,
+	/// Gas processed, `null` for a light client.
+	pub gas: Option<u64>,
+}
+
+/// Summary of `parity export blocks`.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportBlocksSummary {
+	/// Duration of the export, in milliseconds.
+	pub duration_ms: u64,
+	/// First exported block.
+	pub from_block: u64,
+	/// Last exported block.
+	pub to_block: u64,
+}
+
+/// Summary of `parity export state`.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportStateSummary {
+	/// Duration of the export, in milliseconds.
+	pub duration_ms: u64,
+	/// Exported accounts.
+	pub accounts: u64,
+}
+
+/// Summary of `parity db kill` and `parity db reset`.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbSummary {
+	/// The subcommand, `kill` or `reset`.
+	pub command: String,
+	/// Path of the deleted database, `null` for `reset`.
+	pub deleted: Option<String>,
+	/// Number of blocks reset, `null` for `kill`.
+	pub reset_blocks: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeMap;
+	use serde_json::{self, Value};
+	use super::*;
+
+	fn keys(value: &Value) -> Vec<String> {
+		value.as_object().unwrap().keys().cloned().collect()
+	}
+
+	#[test]
+	fn node_status_field_names() {
+		let mut caches = BTreeMap::new();
+		caches.insert("db".to_owned(), 1024);
+		let status = NodeStatus {
+			timestamp: 1_500_000_000,
+			syncing: true,
+			best_block: BlockStatus { number: 10, hash: "0x01".into() },
+			peers: Some(PeerStatus { connected: 5, max: 50 }),
+			queue: QueueStatus { unverified: 1, verified: 2 },
+			sync: Some(SyncProgress {
+				last_imported_block: 12,
+				last_imported_old_block: None,
+				snapshot: Some(SnapshotProgress { chunks_done: 3, chunks: Some(4) }),
+			}),
+			txpool: Some(TxPoolStatus { transactions: 7, senders: 3 }),
+			import_rate: ImportRate { blocks_per_second: 1.5, transactions_per_second: Some(10.0), mgas_per_second: None },
+			caches,
+			rpc: Some(RpcStatus { sessions: 1, requests_per_second: 2, roundtrip_us: 3 }),
+		};
+
+		let value = serde_json::to_value(&status).unwrap();
+		let mut expected = vec![
+			"timestamp", "syncing", "bestBlock", "peers", "queue", "sync", "txpool", "importRate", "caches", "rpc",
+		];
+		expected.sort();
+		assert_eq!(keys(&value), expected);
+		assert_eq!(keys(&value["bestBlock"]), vec!["hash", "number"]);
+		assert_eq!(keys(&value["peers"]), vec!["connected", "max"]);
+		assert_eq!(keys(&value["queue"]), vec!["unverified", "verified"]);
+		assert_eq!(keys(&value["sync"]), vec!["lastImportedBlock", "lastImportedOldBlock", "snapshot"]);
+		assert_eq!(keys(&value["sync"]["snapshot"]), vec!["chunks", "chunksDone"]);
+		assert_eq!(keys(&value["txpool"]), vec!["senders", "transactions"]);
+		assert_eq!(keys(&value["importRate"]), vec!["blocksPerSecond", "mgasPerSecond", "transactionsPerSecond"]);
+		assert_eq!(keys(&value["rpc"]), vec!["requestsPerSecond", "roundtripUs", "sessions"]);
+		assert_eq!(value["caches"]["db"], 1024);
+		assert_eq!(value["importRate"]["mgasPerSecond"], Value::Null);
+	}
+
+	#[test]
+	fn summaries_field_names() {
+		let import = ImportSummary { duration_ms: 1000, blocks: 10, transactions: Some(20), gas: None };
+		assert_eq!(
+			serde_json::to_string(&import).unwrap(),
+			r#"{"durationMs":1000,"blocks":10,"transactions":20,"gas":null}"#
+		);
+
+		let export = ExportBlocksSummary { duration_ms: 5, from_block: 0, to_block: 100 };
+		assert_eq!(serde_json::to_string(&export).unwrap(), r#"{"durationMs":5,"fromBlock":0,"toBlock":100}"#);
+
+		let export = ExportStateSummary { duration_ms: 5, accounts: 3 };
+		assert_eq!(serde_json::to_string(&export).unwrap(), r#"{"durationMs":5,"accounts":3}"#);
+
+		let import = BlockImport {
+			timestamp: 1,
+			block: BlockStatus { number: 10, hash: "0x01".into() },
+			transactions: Some(2),
+			gas_used: 21000,
+			duration_ms: None,
+			skipped_blocks: 3,
+			skipped_transactions: None,
+		};
+		assert_eq!(
+			serde_json::to_string(&import).unwrap(),
+			r#"{"timestamp":1,"block":{"number":10,"hash":"0x01"},"transactions":2,"gasUsed":21000,"durationMs":null,"skippedBlocks":3,"skippedTransactions":null}"#
+		);
+
+		let db = DbSummary { command: "reset".into(), deleted: None, reset_blocks: Some(10) };
+		assert_eq!(serde_json::to_string(&db).unwrap(), r#"{"command":"reset","deleted":null,"resetBlocks":10}"#);
+	}
+}
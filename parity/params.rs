@@ -23,6 +23,7 @@ use ethcore::client::Mode;
 use ethcore::ethereum;
 use ethcore::spec::{Spec, SpecParams};
 use ethereum_types::{U256, Address};
+use ethstore::KdfConfig;
 use parity_runtime::Executor;
 use hash_fetch::fetch::Client as FetchClient;
 use journaldb::Algorithm;
@@ -220,25 +221,31 @@ impl str::FromStr for ResealPolicy {
 
 #[derive(Debug, PartialEq)]
 pub struct AccountsConfig {
-	pub iterations: NonZeroU32,
+	pub kdf: KdfConfig,
 	pub refresh_time: u64,
 	pub testnet: bool,
 	pub password_files: Vec<String>,
+	pub extra_keys_paths: Vec<String>,
 	pub unlocked_accounts: Vec<Address>,
 	pub enable_hardware_wallets: bool,
 	pub enable_fast_unlock: bool,
+	pub autolock: Option<Duration>,
+	pub allow_permanent_unlock: bool,
 }
 
 impl Default for AccountsConfig {
 	fn default() -> Self {
 		AccountsConfig {
-			iterations: NonZeroU32::new(10240).expect("10240 > 0; qed"),
+			kdf: KdfConfig::Pbkdf2(NonZeroU32::new(10240).expect("10240 > 0; qed")),
 			refresh_time: 5,
 			testnet: false,
 			password_files: Vec::new(),
+			extra_keys_paths: Vec::new(),
 			unlocked_accounts: Vec::new(),
 			enable_hardware_wallets: true,
 			enable_fast_unlock: false,
+			autolock: None,
+			allow_permanent_unlock: false,
 		}
 	}
 }
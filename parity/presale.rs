@@ -16,14 +16,13 @@
 
 
 use ethkey::Password;
-use ethstore::PresaleWallet;
+use ethstore::{PresaleWallet, KdfConfig};
 use helpers::{password_prompt, password_from_file};
 use params::SpecType;
-use std::num::NonZeroU32;
 
 #[derive(Debug, PartialEq)]
 pub struct ImportWallet {
-	pub iterations: NonZeroU32,
+	pub kdf: KdfConfig,
 	pub path: String,
 	pub spec: SpecType,
 	pub wallet_path: String,
@@ -50,7 +49,7 @@ pub fn import_account(cmd: &ImportWallet, kp: ethkey::KeyPair, password: Passwor
 	use ethstore::accounts_dir::RootDiskDirectory;
 
 	let dir = Box::new(RootDiskDirectory::create(cmd.path.clone()).unwrap());
-	let secret_store = Box::new(EthStore::open_with_iterations(dir, cmd.iterations).unwrap());
+	let secret_store = Box::new(EthStore::open_with_kdf(dir, cmd.kdf).unwrap());
 	let acc_provider = AccountProvider::new(secret_store, AccountProviderSettings::default());
 	acc_provider.insert_account(kp.secret().clone(), &password).unwrap();
 }
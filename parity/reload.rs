@@ -0,0 +1,538 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Reloading of the configuration of a running node.
+//!
+//! The config file is parsed again and compared with the configuration the node was started with.
+//! Logging filters, the minimal gas price, reserved peers and the hosts and origins accepted
+//! by the RPC servers are applied right away, changes of any other option are only reported,
+//! since they require a restart.
+
+use std::sync::Arc;
+
+use ethcore::miner::{Miner, MinerService};
+use ethcore_logger;
+use ethereum_types::U256;
+use parking_lot::Mutex;
+use sync::ManageNetwork;
+
+use configuration::Configuration;
+use params::GasPricerConfig;
+use rpc::{HttpConfiguration, ServerDomains, WsConfiguration};
+
+/// Options which are applied without a restart.
+const RELOADABLE_OPTIONS: &[&str] = &[
+	"logging", "reserved-peers",
+	"jsonrpc-hosts", "jsonrpc-cors", "rpccorsdomain",
+	"ws-hosts", "ws-origins",
+];
+
+/// Options determining the minimal gas price.
+/// They can only be applied without a restart as long as the gas price is fixed.
+const GAS_PRICE_OPTIONS: &[&str] = &["min-gas-price", "gasprice", "usd-per-tx", "usd-per-eth", "price-update-period"];
+
+/// Hosts and origins a RPC server accepts requests from, `None` if any are accepted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Domains {
+	/// Accepted hosts.
+	pub hosts: Option<Vec<String>>,
+	/// Accepted origins.
+	pub origins: Option<Vec<String>>,
+}
+
+/// Settings of a running node which can be changed without a restart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReloadableSettings {
+	/// Logging filters.
+	pub logging: Option<String>,
+	/// Minimal gas price, `None` if it's calibrated.
+	pub min_gas_price: Option<U256>,
+	/// Enode URLs of reserved peers.
+	pub reserved_peers: Vec<String>,
+	/// Domains accepted by the HTTP JSON-RPC server.
+	pub http_domains: Domains,
+	/// Domains accepted by the WebSockets server.
+	pub ws_domains: Domains,
+}
+
+impl ReloadableSettings {
+	/// Create settings from the parsed parameters of a node.
+	pub fn new(
+		logging: Option<String>,
+		gas_pricer: &GasPricerConfig,
+		reserved_peers: Vec<String>,
+		http: &HttpConfiguration,
+		ws: &WsConfiguration,
+	) -> Self {
+		ReloadableSettings {
+			logging,
+			min_gas_price: match *gas_pricer {
+				GasPricerConfig::Fixed(price) => Some(price),
+				GasPricerConfig::Calibrated { .. } => None,
+			},
+			reserved_peers,
+			http_domains: Domains {
+				hosts: http.hosts.clone(),
+				origins: http.cors.clone(),
+			},
+			ws_domains: Domains {
+				hosts: ws.hosts.clone(),
+				origins: ws.origins.clone(),
+			},
+		}
+	}
+
+	/// Read the settings from the configuration.
+	pub fn from_configuration(conf: &Configuration) -> Result<Self, String> {
+		Ok(ReloadableSettings::new(
+			conf.args.arg_logging.clone(),
+			&conf.gas_pricer_config()?,
+			conf.init_reserved_nodes()?,
+			&conf.http_config()?,
+			&conf.ws_config()?,
+		))
+	}
+}
+
+/// Difference between the running and the reloaded configuration.
+#[derive(Debug, Default, PartialEq)]
+pub struct ConfigDiff {
+	/// New logging filters, if they changed.
+	pub logging: Option<Option<String>>,
+	/// New minimal gas price, if it changed.
+	pub min_gas_price: Option<U256>,
+	/// Reserved peers to add.
+	pub add_reserved_peers: Vec<String>,
+	/// Reserved peers to remove.
+	pub remove_reserved_peers: Vec<String>,
+	/// New domains of the HTTP JSON-RPC server, if they changed.
+	pub http_domains: Option<Domains>,
+	/// New domains of the WebSockets server, if they changed.
+	pub ws_domains: Option<Domains>,
+	/// Changed options which are only applied on restart.
+	pub requires_restart: Vec<String>,
+}
+
+impl ConfigDiff {
+	/// Compare the reloaded configuration with the one the node was started with
+	/// and the settings currently in effect.
+	pub fn new(
+		started: &Configuration,
+		running: &ReloadableSettings,
+		reloaded: &Configuration,
+		settings: &ReloadableSettings,
+	) -> Self {
+		let fixed_gas_price = running.min_gas_price.is_some() && settings.min_gas_price.is_some();
+		let requires_restart = started.args.changed_options(&reloaded.args).into_iter()
+			.filter(|option| !RELOADABLE_OPTIONS.contains(&option.as_str()))
+			.filter(|option| !(fixed_gas_price && GAS_PRICE_OPTIONS.contains(&option.as_str())))
+			.collect();
+
+		ConfigDiff {
+			logging: match running.logging != settings.logging {
+				true => Some(settings.logging.clone()),
+				false => None,
+			},
+			min_gas_price: match (running.min_gas_price, settings.min_gas_price) {
+				(Some(old), Some(new)) if old != new => Some(new),
+				_ => None,
+			},
+			add_reserved_peers: settings.reserved_peers.iter()
+				.filter(|peer| !running.reserved_peers.contains(peer))
+				.cloned()
+				.collect(),
+			remove_reserved_peers: running.reserved_peers.iter()
+				.filter(|peer| !settings.reserved_peers.contains(peer))
+				.cloned()
+				.collect(),
+			http_domains: match running.http_domains != settings.http_domains {
+				true => Some(settings.http_domains.clone()),
+				false => None,
+			},
+			ws_domains: match running.ws_domains != settings.ws_domains {
+				true => Some(settings.ws_domains.clone()),
+				false => None,
+			},
+			requires_restart,
+		}
+	}
+
+	/// Apply the changes to the node, updating the running settings with those which succeeded.
+	pub fn apply(self, target: &ReloadTarget, running: &mut ReloadableSettings) -> ReloadReport {
+		let mut report = ReloadReport {
+			requires_restart: self.requires_restart,
+			..Default::default()
+		};
+
+		if let Some(logging) = self.logging {
+			match target.set_logging(logging.as_ref().map(String::as_str)) {
+				Ok(()) => {
+					running.logging = logging;
+					report.applied.push("logging".into());
+				},
+				Err(e) => report.failed.push(format!("logging: {}", e)),
+			}
+		}
+
+		if let Some(price) = self.min_gas_price {
+			match target.set_min_gas_price(price) {
+				Ok(()) => {
+					running.min_gas_price = Some(price);
+					report.applied.push("min-gas-price".into());
+				},
+				Err(e) => report.failed.push(format!("min-gas-price: {}", e)),
+			}
+		}
+
+		let mut peers_changed = false;
+		for peer in self.remove_reserved_peers {
+			match target.remove_reserved_peer(peer.clone()) {
+				Ok(()) => {
+					running.reserved_peers.retain(|p| *p != peer);
+					peers_changed = true;
+				},
+				Err(e) => report.failed.push(format!("reserved-peers: cannot remove {}: {}", peer, e)),
+			}
+		}
+		for peer in self.add_reserved_peers {
+			match target.add_reserved_peer(peer.clone()) {
+				Ok(()) => {
+					running.reserved_peers.push(peer);
+					peers_changed = true;
+				},
+				Err(e) => report.failed.push(format!("reserved-peers: cannot add {}: {}", peer, e)),
+			}
+		}
+		if peers_changed {
+			report.applied.push("reserved-peers".into());
+		}
+
+		if let Some(domains) = self.http_domains {
+			target.set_http_domains(&domains);
+			running.http_domains = domains;
+			report.applied.push("jsonrpc-domains".into());
+		}
+
+		if let Some(domains) = self.ws_domains {
+			target.set_ws_domains(&domains);
+			running.ws_domains = domains;
+			report.applied.push("ws-domains".into());
+		}
+
+		report
+	}
+}
+
+/// Outcome of reloading the configuration.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReloadReport {
+	/// Settings which were changed.
+	pub applied: Vec<String>,
+	/// Settings which couldn't be changed, with the reason.
+	pub failed: Vec<String>,
+	/// Changed options which are only applied on restart.
+	pub requires_restart: Vec<String>,
+}
+
+impl ReloadReport {
+	fn log(&self) {
+		match self.applied.is_empty() {
+			true => info!("Configuration reloaded, no settings changed"),
+			false => info!("Configuration reloaded, changed: {}", self.applied.join(", ")),
+		}
+		for failure in &self.failed {
+			warn!("Failed to apply reloaded setting {}", failure);
+		}
+		if !self.requires_restart.is_empty() {
+			warn!("Changes of the following options require a restart: {}", self.requires_restart.join(", "));
+		}
+	}
+}
+
+/// Parts of a running node affected by a configuration reload.
+pub trait ReloadTarget: Send + Sync {
+	/// Replace the logging filters.
+	fn set_logging(&self, mode: Option<&str>) -> Result<(), String>;
+	/// Set the fixed minimal gas price.
+	fn set_min_gas_price(&self, price: U256) -> Result<(), String>;
+	/// Add a reserved peer.
+	fn add_reserved_peer(&self, peer: String) -> Result<(), String>;
+	/// Remove a reserved peer.
+	fn remove_reserved_peer(&self, peer: String) -> Result<(), String>;
+	/// Replace the hosts and origins accepted by the HTTP JSON-RPC server.
+	fn set_http_domains(&self, domains: &Domains);
+	/// Replace the hosts and origins accepted by the WebSockets server.
+	fn set_ws_domains(&self, domains: &Domains);
+}
+
+/// Reload target of a full or light node.
+pub struct NodeReloadTarget {
+	/// Miner of a full node, `None` for a light client.
+	pub miner: Option<Arc<Miner>>,
+	/// Network of the node.
+	pub net: Arc<ManageNetwork>,
+	/// Domains of the HTTP JSON-RPC server.
+	pub http_domains: ServerDomains,
+	/// Domains of the WebSockets server.
+	pub ws_domains: ServerDomains,
+}
+
+impl ReloadTarget for NodeReloadTarget {
+	fn set_logging(&self, mode: Option<&str>) -> Result<(), String> {
+		ethcore_logger::set_log_levels(mode)
+	}
+
+	fn set_min_gas_price(&self, price: U256) -> Result<(), String> {
+		match self.miner {
+			Some(ref miner) => miner.set_minimal_gas_price(price).map(|_| ()).map_err(Into::into),
+			None => Err("light client has no minimal gas price".into()),
+		}
+	}
+
+	fn add_reserved_peer(&self, peer: String) -> Result<(), String> {
+		self.net.add_reserved_peer(peer)
+	}
+
+	fn remove_reserved_peer(&self, peer: String) -> Result<(), String> {
+		self.net.remove_reserved_peer(peer)
+	}
+
+	fn set_http_domains(&self, domains: &Domains) {
+		self.http_domains.set(domains.hosts.clone(), domains.origins.clone())
+	}
+
+	fn set_ws_domains(&self, domains: &Domains) {
+		self.ws_domains.set(domains.hosts.clone(), domains.origins.clone())
+	}
+}
+
+/// Reloads the configuration of a running node, e.g. on `SIGHUP`.
+pub struct ConfigReloader {
+	running: Mutex<(Configuration, ReloadableSettings)>,
+	target: Arc<ReloadTarget>,
+}
+
+impl ConfigReloader {
+	/// Create a reloader of a node started with the given configuration and settings.
+	pub fn new(started: Configuration, settings: ReloadableSettings, target: Arc<ReloadTarget>) -> Self {
+		ConfigReloader {
+			running: Mutex::new((started, settings)),
+			target,
+		}
+	}
+
+	/// Parse the configuration again and apply the changed settings.
+	/// Fails without changing anything if the configuration is invalid.
+	pub fn reload(&self) -> Result<ReloadReport, String> {
+		let mut running = self.running.lock();
+		let (ref started, ref mut settings) = *running;
+
+		let reloaded = started.reload().map_err(|e| format!("{}", e))?;
+		let reloaded_settings = ReloadableSettings::from_configuration(&reloaded)?;
+		let report = ConfigDiff::new(started, settings, &reloaded, &reloaded_settings).apply(&*self.target, settings);
+
+		report.log();
+		Ok(report)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::File;
+	use std::io::Write;
+	use std::path::Path;
+	use std::sync::Arc;
+
+	use ethereum_types::U256;
+	use parking_lot::Mutex;
+	use tempdir::TempDir;
+
+	use configuration::Configuration;
+	use super::*;
+
+	const PEER_1: &str = "enode://6f8a80d14311c39f35f516fa664deaaaa13e85b2f7493f37f6144d86991ec012937307647bd3b9a82abe2974e1407241d54947bbb39763a4cac9f77166ad92a0@172.0.0.1:30303";
+	const PEER_2: &str = "enode://e731347db0521f3476e6bbbb83375dcd7133a1601425ebd15fd10f3835fd4c304fba6282087ca5a0deeafadf0aa0d4fd56c3323331901c1f38bd181c283e3e35@128.199.55.137:30303";
+
+	#[derive(Default)]
+	struct TestTarget {
+		calls: Mutex<Vec<String>>,
+		fail_gas_price: bool,
+	}
+
+	impl ReloadTarget for TestTarget {
+		fn set_logging(&self, mode: Option<&str>) -> Result<(), String> {
+			self.calls.lock().push(format!("logging {:?}", mode));
+			Ok(())
+		}
+
+		fn set_min_gas_price(&self, price: U256) -> Result<(), String> {
+			match self.fail_gas_price {
+				true => Err("calibrated".into()),
+				false => Ok(self.calls.lock().push(format!("min-gas-price {}", price))),
+			}
+		}
+
+		fn add_reserved_peer(&self, peer: String) -> Result<(), String> {
+			Ok(self.calls.lock().push(format!("add {}", peer)))
+		}
+
+		fn remove_reserved_peer(&self, peer: String) -> Result<(), String> {
+			Ok(self.calls.lock().push(format!("remove {}", peer)))
+		}
+
+		fn set_http_domains(&self, domains: &Domains) {
+			self.calls.lock().push(format!("http {:?} {:?}", domains.hosts, domains.origins))
+		}
+
+		fn set_ws_domains(&self, domains: &Domains) {
+			self.calls.lock().push(format!("ws {:?} {:?}", domains.hosts, domains.origins))
+		}
+	}
+
+	fn write(path: &Path, contents: &str) {
+		File::create(path).unwrap().write_all(contents.as_bytes()).unwrap();
+	}
+
+	fn config_file(dir: &TempDir, contents: &str) -> Configuration {
+		let path = dir.path().join("config.toml");
+		write(&path, contents);
+		Configuration::parse_cli(&["parity", "--config", path.to_str().unwrap()]).unwrap()
+	}
+
+	fn settings(conf: &Configuration) -> ReloadableSettings {
+		ReloadableSettings::from_configuration(conf).unwrap()
+	}
+
+	#[test]
+	fn diffs_reloadable_settings() {
+		let dir = TempDir::new("reload").unwrap();
+		let peers_1 = dir.path().join("peers_1");
+		let peers_2 = dir.path().join("peers_2");
+		write(&peers_1, &format!("{}\n", PEER_1));
+		write(&peers_2, &format!("# moved\n{}\n", PEER_2));
+
+		let before = config_file(&dir, &format!(
+			"[network]\nreserved_peers = {:?}\n[mining]\nmin_gas_price = 1000\n[misc]\nlogging = \"info\"\n[rpc]\ncors = [\"http://a.io\"]\n",
+			peers_1.to_str().unwrap(),
+		));
+		let after = config_file(&dir, &format!(
+			"[network]\nreserved_peers = {:?}\n[mining]\nmin_gas_price = 2000\n[misc]\nlogging = \"sync=debug\"\n[rpc]\ncors = [\"http://b.io\"]\nhosts = [\"b.io\"]\n",
+			peers_2.to_str().unwrap(),
+		));
+
+		assert_eq!(ConfigDiff::new(&before, &settings(&before), &after, &settings(&after)), ConfigDiff {
+			logging: Some(Some("sync=debug".into())),
+			min_gas_price: Some(2000.into()),
+			add_reserved_peers: vec![PEER_2.into()],
+			remove_reserved_peers: vec![PEER_1.into()],
+			http_domains: Some(Domains {
+				hosts: Some(vec!["b.io".into()]),
+				origins: Some(vec!["http://b.io".into()]),
+			}),
+			ws_domains: None,
+			requires_restart: vec![],
+		});
+		assert_eq!(ConfigDiff::new(&after, &settings(&after), &after, &settings(&after)), ConfigDiff::default());
+	}
+
+	#[test]
+	fn reports_options_requiring_restart() {
+		let dir = TempDir::new("reload").unwrap();
+		// the gas price of the foundation chain is calibrated by default.
+		let before = config_file(&dir, "[network]\nport = 30303\n");
+		let after = config_file(&dir, "[network]\nport = 30304\n[mining]\nmin_gas_price = 5\n[websockets]\ndisable = true\n");
+
+		let mut diff = ConfigDiff::new(&before, &settings(&before), &after, &settings(&after));
+		diff.requires_restart.sort();
+		assert_eq!(diff, ConfigDiff {
+			requires_restart: vec!["min-gas-price".into(), "no-ws".into(), "port".into()],
+			..Default::default()
+		});
+	}
+
+	#[test]
+	fn applies_changes_and_reports_failures() {
+		let domains = Domains { hosts: Some(vec![]), origins: None };
+		let ws_domains = Domains { hosts: None, origins: Some(vec!["parity://*".into()]) };
+		let mut running = ReloadableSettings {
+			logging: None,
+			min_gas_price: Some(1.into()),
+			reserved_peers: vec![PEER_1.into()],
+			http_domains: domains.clone(),
+			ws_domains: domains.clone(),
+		};
+		let diff = ConfigDiff {
+			logging: Some(Some("rpc=trace".into())),
+			min_gas_price: Some(2.into()),
+			add_reserved_peers: vec![PEER_2.into()],
+			remove_reserved_peers: vec![],
+			http_domains: None,
+			ws_domains: Some(ws_domains.clone()),
+			requires_restart: vec!["port".into()],
+		};
+
+		let target = TestTarget { fail_gas_price: true, ..Default::default() };
+		let report = diff.apply(&target, &mut running);
+
+		assert_eq!(report, ReloadReport {
+			applied: vec!["logging".into(), "reserved-peers".into(), "ws-domains".into()],
+			failed: vec!["min-gas-price: calibrated".into()],
+			requires_restart: vec!["port".into()],
+		});
+		assert_eq!(*target.calls.lock(), vec![
+			"logging Some(\"rpc=trace\")".to_owned(),
+			format!("add {}", PEER_2),
+			"ws None Some([\"parity://*\"])".to_owned(),
+		]);
+		// the gas price remains changed on the next reload.
+		assert_eq!(running, ReloadableSettings {
+			logging: Some("rpc=trace".into()),
+			min_gas_price: Some(1.into()),
+			reserved_peers: vec![PEER_1.into(), PEER_2.into()],
+			http_domains: domains,
+			ws_domains,
+		});
+	}
+
+	#[test]
+	fn reloads_config_file() {
+		let dir = TempDir::new("reload").unwrap();
+		let started = config_file(&dir, "[mining]\nmin_gas_price = 1000\n");
+		let target = Arc::new(TestTarget::default());
+		let reloader = ConfigReloader::new(started.clone(), settings(&started), target.clone());
+
+		// unknown settings are rejected.
+		config_file(&dir, "[mining]\nmin_gas_price = 2000\nunknown_option = 1\n");
+		assert!(reloader.reload().unwrap_err().contains("unknown_option"));
+		assert!(target.calls.lock().is_empty());
+
+		config_file(&dir, "[mining]\nmin_gas_price = 2000\n[network]\nmax_peers = 10\n");
+		assert_eq!(reloader.reload(), Ok(ReloadReport {
+			applied: vec!["min-gas-price".into()],
+			failed: vec![],
+			requires_restart: vec!["max-peers".into()],
+		}));
+
+		// applied settings are not changed again.
+		assert_eq!(reloader.reload(), Ok(ReloadReport {
+			applied: vec![],
+			failed: vec![],
+			requires_restart: vec!["max-peers".into()],
+		}));
+		assert_eq!(*target.calls.lock(), vec!["min-gas-price 2000".to_owned()]);
+	}
+}
@@ -18,6 +18,7 @@ use std::io;
 use std::sync::Arc;
 use std::path::PathBuf;
 use std::collections::HashSet;
+use std::time::Duration;
 
 use dir::default_data_path;
 use dir::helpers::replace_home;
@@ -97,6 +98,7 @@ pub struct WsConfiguration {
 	pub hosts: Option<Vec<String>>,
 	pub signer_path: PathBuf,
 	pub support_token_api: bool,
+	pub confirmation_timeout: Option<Duration>,
 }
 
 impl Default for WsConfiguration {
@@ -112,14 +114,50 @@ impl Default for WsConfiguration {
 			hosts: Some(Vec::new()),
 			signer_path: replace_home(&data_dir, "$BASE/signer").into(),
 			support_token_api: true,
+			confirmation_timeout: None,
 		}
 	}
 }
 
+impl HttpConfiguration {
+	/// Hosts and origins the server accepts requests from.
+	pub fn domains(&self) -> ServerDomains {
+		ServerDomains::new(&self.interface, self.port, self.hosts.clone(), self.cors.clone())
+	}
+}
+
 impl WsConfiguration {
 	pub fn address(&self) -> Option<rpc::Host> {
 		address(self.enabled, &self.interface, self.port, &self.hosts)
 	}
+
+	/// Hosts and origins the server accepts requests from.
+	pub fn domains(&self) -> ServerDomains {
+		ServerDomains::new(&self.interface, self.port, self.hosts.clone(), self.origins.clone())
+	}
+}
+
+/// Hosts and origins a HTTP or WebSockets server accepts requests from,
+/// which can be replaced while the server is running.
+#[derive(Debug, Clone)]
+pub struct ServerDomains {
+	allowed: rpc::AllowedDomains,
+	address: Option<rpc::Host>,
+}
+
+impl ServerDomains {
+	fn new(interface: &str, port: u16, hosts: Option<Vec<String>>, origins: Option<Vec<String>>) -> Self {
+		let address = Some(format!("{}:{}", interface, port).into());
+		ServerDomains {
+			allowed: rpc::AllowedDomains::new(with_domain(hosts, DAPPS_DOMAIN, &address), origins),
+			address,
+		}
+	}
+
+	/// Replace the accepted hosts and origins. The address of the server is always accepted as a host.
+	pub fn set(&self, hosts: Option<Vec<String>>, origins: Option<Vec<String>>) {
+		self.allowed.set(with_domain(hosts, DAPPS_DOMAIN, &self.address), origins)
+	}
 }
 
 fn address(enabled: bool, bind_iface: &str, bind_port: u16, hosts: &Option<Vec<String>>) -> Option<rpc::Host> {
@@ -141,13 +179,13 @@ pub struct Dependencies<D: rpc_apis::Dependencies> {
 
 pub fn new_ws<D: rpc_apis::Dependencies>(
 	conf: WsConfiguration,
+	domains: ServerDomains,
 	deps: &Dependencies<D>,
 ) -> Result<Option<WsServer>, String> {
 	if !conf.enabled {
 		return Ok(None);
 	}
 
-	let domain = DAPPS_DOMAIN;
 	let url = format!("{}:{}", conf.interface, conf.port);
 	let addr = url.parse().map_err(|_| format!("Invalid WebSockets listen host/port given: {}", url))?;
 
@@ -163,9 +201,6 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
 		handler
 	};
 
-	let allowed_origins = into_domains(with_domain(conf.origins, domain, &None));
-	let allowed_hosts = into_domains(with_domain(conf.hosts, domain, &Some(url.clone().into())));
-
 	let signer_path;
 	let path = match conf.support_token_api {
 		true => {
@@ -177,11 +212,11 @@ pub fn new_ws<D: rpc_apis::Dependencies>(
 	let start_result = rpc::start_ws(
 		&addr,
 		handler,
-		allowed_origins,
-		allowed_hosts,
+		DomainsValidation::Disabled,
+		DomainsValidation::Disabled,
 		conf.max_connections,
 		rpc::WsExtractor::new(path.clone()),
-		rpc::WsExtractor::new(path.clone()),
+		domains.allowed.ws_middleware(rpc::WsExtractor::new(path.clone())),
 		rpc::WsStats::new(deps.stats.clone()),
 	);
 
@@ -198,26 +233,24 @@ pub fn new_http<D: rpc_apis::Dependencies>(
 	id: &str,
 	options: &str,
 	conf: HttpConfiguration,
+	domains: ServerDomains,
 	deps: &Dependencies<D>,
 ) -> Result<Option<HttpServer>, String> {
 	if !conf.enabled {
 		return Ok(None);
 	}
 
-	let domain = DAPPS_DOMAIN;
 	let url = format!("{}:{}", conf.interface, conf.port);
 	let addr = url.parse().map_err(|_| format!("Invalid {} listen host/port given: {}", id, url))?;
 	let handler = setup_apis(conf.apis, deps);
 
-	let cors_domains = into_domains(conf.cors);
-	let allowed_hosts = into_domains(with_domain(conf.hosts, domain, &Some(url.clone().into())));
-
-	let start_result = rpc::start_http(
+	let start_result = rpc::start_http_with_middleware(
 		&addr,
-		cors_domains,
-		allowed_hosts,
+		DomainsValidation::Disabled,
+		DomainsValidation::Disabled,
 		handler,
 		rpc::RpcExtractor,
+		domains.allowed,
 		conf.server_threads,
 		conf.max_payload,
 		conf.keep_alive,
@@ -257,10 +290,6 @@ pub fn new_ipc<D: rpc_apis::Dependencies>(
 	}
 }
 
-fn into_domains<T: From<String>>(items: Option<Vec<String>>) -> DomainsValidation<T> {
-	items.map(|vals| vals.into_iter().map(T::from).collect()).into()
-}
-
 fn with_domain(items: Option<Vec<String>>, domain: &str, dapps_address: &Option<rpc::Host>) -> Option<Vec<String>> {
 	fn extract_port(s: &str) -> Option<u16> {
 		s.split(':').nth(1).and_then(|s| s.parse().ok())
@@ -39,8 +39,11 @@ use parity_rpc::dispatch::{FullDispatcher, LightDispatcher};
 use parity_rpc::informant::{ActivityNotifier, ClientNotifier};
 use parity_rpc::{Host, Metadata, NetworkSettings};
 use parity_rpc::v1::traits::TransactionsPool;
+use parity_rpc::v1::secretstore_admin::KeyServerAdmin;
+use parity_rpc::v1::{ConfigReload, LightCheckpoint};
 use parity_runtime::Executor;
 use parking_lot::{Mutex, RwLock};
+use reload::ConfigReloader;
 use sync::{LightSync, ManageNetwork, SyncProvider};
 use updater::Updater;
 
@@ -80,6 +83,8 @@ pub enum Api {
 	ParitySet,
 	/// SecretStore (UNSAFE: arbitrary hash signing)
 	SecretStore,
+	/// SecretStore administrative sessions (UNSAFE: Side Effects affecting key server cluster)
+	SecretStoreAdmin,
 	/// Geth-compatible (best-effort) debug API (Potentially UNSAFE)
 	/// NOTE We don't aim to support all methods, only the ones that are useful.
 	Debug,
@@ -106,6 +111,7 @@ impl FromStr for Api {
 			"pubsub" => Ok(EthPubSub),
 			"rpc" => Ok(Rpc),
 			"secretstore" => Ok(SecretStore),
+			"secretstore_admin" => Ok(SecretStoreAdmin),
 			"shh" => Ok(Whisper),
 			"shh_pubsub" => Ok(WhisperPubSub),
 			"signer" => Ok(Signer),
@@ -174,6 +180,15 @@ impl FromStr for ApiSet {
 	}
 }
 
+fn reload_config(reloader: &Arc<ConfigReloader>) -> Arc<Fn() -> Result<ConfigReload, String> + Send + Sync> {
+	let reloader = reloader.clone();
+	Arc::new(move || reloader.reload().map(|report| ConfigReload {
+		applied: report.applied,
+		failed: report.failed,
+		requires_restart: report.requires_restart,
+	}))
+}
+
 fn to_modules(apis: &HashSet<Api>) -> BTreeMap<String, String> {
 	let mut modules = BTreeMap::new();
 	for api in apis {
@@ -190,6 +205,7 @@ fn to_modules(apis: &HashSet<Api>) -> BTreeMap<String, String> {
 			Api::Private => ("private", "1.0"),
 			Api::Rpc => ("rpc", "1.0"),
 			Api::SecretStore => ("secretstore", "1.0"),
+			Api::SecretStoreAdmin => ("secretstore_admin", "1.0"),
 			Api::Signer => ("signer", "1.0"),
 			Api::Traces => ("traces", "1.0"),
 			Api::Web3 => ("web3", "1.0"),
@@ -244,6 +260,7 @@ pub struct FullDependencies {
 	pub net: Arc<ManageNetwork>,
 	pub accounts: Arc<AccountProvider>,
 	pub private_tx_service: Option<Arc<PrivateTxService>>,
+	pub secretstore_admin: Option<Arc<KeyServerAdmin>>,
 	pub miner: Arc<Miner>,
 	pub external_miner: Arc<ExternalMiner>,
 	pub logger: Arc<RotatingLogger>,
@@ -260,6 +277,7 @@ pub struct FullDependencies {
 	pub poll_lifetime: u32,
 	pub allow_missing_blocks: bool,
 	pub no_ancient_blocks: bool,
+	pub config_reloader: Arc<ConfigReloader>,
 }
 
 impl FullDependencies {
@@ -437,6 +455,7 @@ impl FullDependencies {
 							&self.updater,
 							&self.net_service,
 							self.fetch.clone(),
+							&reload_config(&self.config_reloader),
 						).to_delegate(),
 					);
 					#[cfg(feature = "accounts")]
@@ -456,6 +475,9 @@ impl FullDependencies {
 					#[cfg(feature = "accounts")]
 					handler.extend_with(SecretStoreClient::new(&self.accounts).to_delegate());
 				}
+				Api::SecretStoreAdmin => {
+					handler.extend_with(SecretStoreAdminClient::new(self.secretstore_admin.clone()).to_delegate());
+				}
 				Api::Whisper => {
 					if let Some(ref whisper_rpc) = self.whisper_rpc {
 						let whisper = whisper_rpc.make_handler(self.net.clone());
@@ -528,6 +550,8 @@ pub struct LightDependencies<T> {
 	pub private_tx_service: Option<Arc<PrivateTransactionManager>>,
 	pub gas_price_percentile: usize,
 	pub poll_lifetime: u32,
+	pub light_checkpoint: Option<LightCheckpoint>,
+	pub config_reloader: Arc<ConfigReloader>,
 }
 
 impl<C: LightChainClient + 'static> LightDependencies<C> {
@@ -658,6 +682,7 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 							signer,
 							self.ws_address.clone(),
 							self.gas_price_percentile,
+							self.light_checkpoint.clone(),
 						).to_delegate(),
 					);
 					#[cfg(feature = "accounts")]
@@ -686,8 +711,12 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 					handler.extend_with(ParityAccounts::to_delegate(ParityAccountsClient::new(&self.accounts)));
 				}
 				Api::ParitySet => handler.extend_with(
-					light::ParitySetClient::new(self.client.clone(), self.sync.clone(), self.fetch.clone())
-						.to_delegate(),
+					light::ParitySetClient::new(
+						self.client.clone(),
+						self.sync.clone(),
+						self.fetch.clone(),
+						reload_config(&self.config_reloader),
+					).to_delegate(),
 				),
 				Api::Traces => handler.extend_with(light::TracesClient.to_delegate()),
 				Api::Rpc => {
@@ -698,6 +727,9 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 					#[cfg(feature = "accounts")]
 					handler.extend_with(SecretStoreClient::new(&self.accounts).to_delegate());
 				}
+				Api::SecretStoreAdmin => {
+					handler.extend_with(SecretStoreAdminClient::new(None).to_delegate());
+				}
 				Api::Whisper => {
 					if let Some(ref whisper_rpc) = self.whisper_rpc {
 						let whisper = whisper_rpc.make_handler(self.net.clone());
@@ -784,6 +816,7 @@ impl ApiSet {
 				public_list.insert(Api::Signer);
 				public_list.insert(Api::Personal);
 				public_list.insert(Api::SecretStore);
+				public_list.insert(Api::SecretStoreAdmin);
 				public_list.insert(Api::ParityTransactionsPool);
 				public_list
 			}
@@ -821,6 +854,7 @@ mod test {
 		assert_eq!(Api::Traces, "traces".parse().unwrap());
 		assert_eq!(Api::Rpc, "rpc".parse().unwrap());
 		assert_eq!(Api::SecretStore, "secretstore".parse().unwrap());
+		assert_eq!(Api::SecretStoreAdmin, "secretstore_admin".parse().unwrap());
 		assert_eq!(Api::Private, "private".parse().unwrap());
 		assert_eq!(Api::Whisper, "shh".parse().unwrap());
 		assert_eq!(Api::WhisperPubSub, "shh_pubsub".parse().unwrap());
@@ -900,6 +934,7 @@ mod test {
 					Api::Traces,
 					Api::Rpc,
 					Api::SecretStore,
+					Api::SecretStoreAdmin,
 					Api::Whisper,
 					Api::WhisperPubSub,
 					Api::ParityAccounts,
@@ -930,6 +965,7 @@ mod test {
 					Api::Traces,
 					Api::Rpc,
 					Api::SecretStore,
+					Api::SecretStoreAdmin,
 					Api::Whisper,
 					Api::WhisperPubSub,
 					Api::ParityAccounts,
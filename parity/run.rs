@@ -15,6 +15,7 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::any::Any;
+use std::path::Path;
 use std::sync::{Arc, Weak, atomic};
 use std::time::{Duration, Instant};
 use std::thread;
@@ -35,6 +36,7 @@ use hash_fetch::{self, fetch};
 use informant::{Informant, LightNodeInformantData, FullNodeInformantData};
 use journaldb::Algorithm;
 use light::Cache as LightDataCache;
+use light::cache::CacheSizes as LightCacheSizes;
 use miner::external::ExternalMiner;
 use miner::work_notify::WorkPoster;
 use node_filter::NodeFilter;
@@ -43,6 +45,7 @@ use sync::{self, SyncConfig, PrivateTxHandler};
 use parity_rpc::{
 	Origin, Metadata, NetworkSettings, informant, PubSubSession, FutureResult, FutureResponse, FutureOutput
 };
+use parity_rpc::v1::LightCheckpoint;
 use updater::{UpdatePolicy, Updater};
 use parity_version::version;
 use ethcore_private_tx::{ProviderConfig, EncryptorConfig, SecretStoreEncryptor};
@@ -54,10 +57,14 @@ use account_utils;
 use helpers::{to_client_config, execute_upgrades, passwords_from_files};
 use dir::{Directories, DatabaseDirectories};
 use cache::CacheConfig;
+use configuration::Configuration;
+use reload::{ConfigReloader, NodeReloadTarget, ReloadableSettings};
 use user_defaults::UserDefaults;
 use ipfs;
 use jsonrpc_core;
 use modules;
+use output::OutputFormat;
+use daemon::ShutdownSequence;
 use registrar::{RegistrarClient, Asynchronous};
 use rpc;
 use rpc_apis;
@@ -128,31 +135,39 @@ pub struct RunCmd {
 	pub download_old_blocks: bool,
 	pub verifier_settings: VerifierSettings,
 	pub serve_light: bool,
+	pub light_serve_load: f64,
+	pub light_serve_credit_seconds: u64,
+	pub light_serve_limits: ::light::net::ServeLimits,
+	pub light_credits_request: Option<::light::net::request_credits::CreditsRequest>,
 	pub light: bool,
 	pub no_persistent_txqueue: bool,
+	pub local_store: ::local_store::StoreConfig,
 	pub whisper: ::whisper::Config,
 	pub no_hardcoded_sync: bool,
 	pub max_round_blocks_to_import: usize,
+	pub auto_rollback: Option<u64>,
 	pub on_demand_response_time_window: Option<u64>,
+	pub on_demand_request_timeout: Option<u64>,
+	pub on_demand_request_attempts_max: Option<usize>,
 	pub on_demand_request_backoff_start: Option<u64>,
 	pub on_demand_request_backoff_max: Option<u64>,
 	pub on_demand_request_backoff_rounds_max: Option<usize>,
 	pub on_demand_request_consecutive_failures: Option<usize>,
+	pub light_checkpoint: Option<String>,
+	pub light_call_cache_size: usize,
+	pub shutdown_timeout: Duration,
+	pub configuration: Configuration,
+	pub output_format: OutputFormat,
 }
 
 // node info fetcher for the local store.
 struct FullNodeInfo {
-	miner: Option<Arc<Miner>>, // TODO: only TXQ needed, just use that after decoupling.
+	miner: Arc<Miner>, // TODO: only TXQ needed, just use that after decoupling.
 }
 
 impl ::local_store::NodeInfo for FullNodeInfo {
 	fn pending_transactions(&self) -> Vec<::types::transaction::PendingTransaction> {
-		let miner = match self.miner.as_ref() {
-			Some(m) => m,
-			None => return Vec::new(),
-		};
-
-		miner.local_transactions()
+		self.miner.local_transactions()
 			.values()
 			.filter_map(|status| match *status {
 				::miner::pool::local_transactions::Status::Pending(ref tx) => Some(tx.pending().clone()),
@@ -160,6 +175,15 @@ impl ::local_store::NodeInfo for FullNodeInfo {
 			})
 			.collect()
 	}
+
+	fn pool_transactions(&self) -> Vec<::types::transaction::PendingTransaction> {
+		let local = self.miner.local_transactions();
+		self.miner.queued_transactions()
+			.into_iter()
+			.filter(|tx| !local.contains_key(&tx.signed().hash()))
+			.map(|tx| tx.pending().clone())
+			.collect()
+	}
 }
 
 type LightClient = ::light::client::Client<::light_helpers::EpochFetch>;
@@ -173,7 +197,14 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 	use parking_lot::{Mutex, RwLock};
 
 	// load spec
-	let spec = cmd.spec.spec(SpecParams::new(cmd.dirs.cache.as_ref(), OptimizeFor::Memory))?;
+	let mut spec = cmd.spec.spec(SpecParams::new(cmd.dirs.cache.as_ref(), OptimizeFor::Memory))?;
+
+	// replace the hardcoded sync data of the spec with a newer checkpoint.
+	if let Some(ref path) = cmd.light_checkpoint {
+		let checkpoint = ::light_helpers::load_checkpoint(Path::new(path), spec.params().cht_size)?;
+		info!("Loaded light client checkpoint at block #{} from {}", checkpoint.header.number(), path);
+		spec.hardcoded_sync = Some(checkpoint);
+	}
 
 	// load genesis hash
 	let genesis_hash = spec.genesis_header().hash();
@@ -201,8 +232,11 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 
 	info!("Running in experimental {} mode.", Colour::Blue.bold().paint("Light Client"));
 
-	// TODO: configurable cache size.
-	let cache = LightDataCache::new(Default::default(), Duration::from_secs(60 * GAS_CORPUS_EXPIRATION_MINUTES));
+	let cache_sizes = LightCacheSizes {
+		executions: cmd.light_call_cache_size,
+		..Default::default()
+	};
+	let cache = LightDataCache::new(cache_sizes, Duration::from_secs(60 * GAS_CORPUS_EXPIRATION_MINUTES));
 	let cache = Arc::new(Mutex::new(cache));
 
 	// start client and create transaction queue.
@@ -224,6 +258,11 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 		|s| Duration::from_secs(s)
 	);
 
+	let request_timeout = cmd.on_demand_request_timeout.map_or(
+		::light::on_demand::DEFAULT_REQUEST_TIMEOUT,
+		|s| Duration::from_secs(s)
+	);
+
 	let request_backoff_start = cmd.on_demand_request_backoff_start.map_or(
 		::light::on_demand::DEFAULT_REQUEST_MIN_BACKOFF_DURATION,
 		|s| Duration::from_secs(s)
@@ -238,6 +277,8 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 		::light::on_demand::OnDemand::new(
 			cache.clone(),
 			response_time_window,
+			request_timeout,
+			cmd.on_demand_request_attempts_max.unwrap_or(::light::on_demand::DEFAULT_MAX_REQUEST_ATTEMPTS),
 			request_backoff_start,
 			request_backoff_max,
 			cmd.on_demand_request_backoff_rounds_max.unwrap_or(::light::on_demand::DEFAULT_MAX_REQUEST_BACKOFF_ROUNDS),
@@ -256,22 +297,42 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 						 &cmd.cache_config,
 						 &cmd.compaction).map_err(|e| format!("Failed to open database {:?}", e))?;
 
+	let node_db = db.key_value().clone();
 	let service = light_client::Service::start(config, &spec, fetch, db, cache.clone())
 		.map_err(|e| format!("Error starting light client: {}", e))?;
 	let client = service.client().clone();
+
+	// an existing database keeps synchronizing from the checkpoint it was started with.
+	let light_checkpoint = client.checkpoint().map(|checkpoint| {
+		let from_spec = spec.hardcoded_sync.as_ref().map_or(false, |hs| hs.header.hash() == checkpoint.hash);
+		LightCheckpoint::new(&checkpoint, cmd.light_checkpoint.clone().filter(|_| from_spec))
+	});
+	if let Some(ref path) = cmd.light_checkpoint {
+		if light_checkpoint.as_ref().map_or(true, |checkpoint| checkpoint.file.is_none()) {
+			warn!("The light client checkpoint from {} is not in use: the database was synchronized from elsewhere or hardcoded sync is disabled", path);
+		}
+	}
+
 	let txq = Arc::new(RwLock::new(::light::transaction_queue::TransactionQueue::default()));
 	let provider = ::light::provider::LightProvider::new(client.clone(), txq.clone());
 
 	// start network.
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
+	let reload_settings = ReloadableSettings::new(
+		cmd.logger_config.mode.clone(),
+		&cmd.gas_pricer_conf,
+		net_conf.reserved_nodes.clone(),
+		&cmd.http_conf,
+		&cmd.ws_conf,
+	);
 	if !cmd.custom_bootnodes {
 		net_conf.boot_nodes = spec.nodes.clone();
 	}
 
 	let mut attached_protos = Vec::new();
 	let whisper_factory = if cmd.whisper.enabled {
-		let whisper_factory = ::whisper::setup(cmd.whisper.target_message_pool_size, &mut attached_protos)
+		let whisper_factory = ::whisper::setup(&cmd.whisper, &db_dirs.whisper_mail_path(), &mut attached_protos)
 			.map_err(|e| format!("Failed to initialize whisper: {}", e))?;
 		whisper_factory
 	} else {
@@ -287,6 +348,7 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 		subprotocol_name: sync::LIGHT_PROTOCOL,
 		handlers: vec![on_demand.clone()],
 		attached_protos: attached_protos,
+		credits_request: cmd.light_credits_request.clone(),
 	};
 	let light_sync = LightSync::new(sync_params).map_err(|e| format!("Error starting network: {}", e))?;
 	let light_sync = Arc::new(light_sync);
@@ -298,6 +360,49 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 	// start the network.
 	light_sync.start_network();
 
+	// local store for pending transactions.
+	let restoring = Arc::new(Mutex::new(Vec::new()));
+	let store = {
+		let node_info = ::light_helpers::LightNodeInfo {
+			txq: match cmd.no_persistent_txqueue {
+				true => None,
+				false => Some(txq.clone()),
+			},
+			restoring: restoring.clone(),
+		};
+
+		let store = ::local_store::create(node_db, ::ethcore_db::COL_NODE_INFO, node_info, cmd.local_store.clone());
+
+		if cmd.no_persistent_txqueue {
+			info!("Running without a persistent transaction queue.");
+
+			if let Err(e) = store.clear() {
+				warn!("Error clearing persistent transaction queue: {}", e);
+			}
+		}
+
+		// re-queue pending transactions once current nonces of their senders are known.
+		match store.pending_transactions() {
+			Ok(ref pending) if pending.is_empty() => {},
+			Ok(pending) => match ::light_helpers::restore_transactions(pending, restoring, txq.clone(), |senders| {
+				::light_helpers::fetch_nonces(&*client, &light_sync, &on_demand, senders)
+			}) {
+				Some(restore) => runtime.executor().spawn(restore),
+				None => warn!("Unable to restore saved transactions: network is unavailable"),
+			},
+			Err(e) => warn!("Error loading cached pending transactions from disk: {}", e),
+		}
+
+		Arc::new(store)
+	};
+
+	// register it as an IO service to update periodically and after the queue changes.
+	service.register_handler(store.clone()).map_err(|_| "Unable to register local store handler".to_owned())?;
+	if !cmd.no_persistent_txqueue {
+		let persist = ::light_helpers::PersistOnChange::new(store, &txq);
+		service.register_handler(Arc::new(persist)).map_err(|_| "Unable to register local store handler".to_owned())?;
+	}
+
 	// fetch service
 	let fetch = fetch::Client::new(FETCH_LIGHT_NUM_DNS_THREADS).map_err(|e| format!("Error starting fetch client: {:?}", e))?;
 	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
@@ -308,6 +413,17 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 
 	// the dapps server
 	let signer_service = Arc::new(signer::new_service(&cmd.ws_conf, &cmd.logger_config));
+	let accounts_handler = account_utils::accounts_handler(account_provider.clone(), signer_service.clone());
+	let expiry_handler = signer::expiry_handler(signer_service.clone());
+
+	let http_domains = cmd.http_conf.domains();
+	let ws_domains = cmd.ws_conf.domains();
+	let config_reloader = Arc::new(ConfigReloader::new(cmd.configuration, reload_settings, Arc::new(NodeReloadTarget {
+		miner: None,
+		net: light_sync.clone(),
+		http_domains: http_domains.clone(),
+		ws_domains: ws_domains.clone(),
+	})));
 
 	// start RPCs
 	let deps_for_rpc_apis = Arc::new(rpc_apis::LightDependencies {
@@ -329,7 +445,9 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 		whisper_rpc: whisper_factory,
 		private_tx_service: None, //TODO: add this to client.
 		gas_price_percentile: cmd.gas_price_percentile,
-		poll_lifetime: cmd.poll_lifetime
+		poll_lifetime: cmd.poll_lifetime,
+		light_checkpoint,
+		config_reloader: config_reloader.clone(),
 	});
 
 	let dependencies = rpc::Dependencies {
@@ -340,8 +458,8 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 
 	// start rpc servers
 	let rpc_direct = rpc::setup_apis(rpc_apis::ApiSet::All, &dependencies);
-	let ws_server = rpc::new_ws(cmd.ws_conf, &dependencies)?;
-	let http_server = rpc::new_http("HTTP JSON-RPC", "jsonrpc", cmd.http_conf.clone(), &dependencies)?;
+	let ws_server = rpc::new_ws(cmd.ws_conf, ws_domains, &dependencies)?;
+	let http_server = rpc::new_http("HTTP JSON-RPC", "jsonrpc", cmd.http_conf.clone(), http_domains, &dependencies)?;
 	let ipc_server = rpc::new_ipc(cmd.ipc_conf, &dependencies)?;
 
 	// the informant
@@ -354,9 +472,16 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 		None,
 		Some(rpc_stats),
 		cmd.logger_config.color,
+		cmd.output_format,
 	));
 	service.add_notify(informant.clone());
 	service.register_handler(informant.clone()).map_err(|_| "Unable to register informant handler".to_owned())?;
+	if let Some(handler) = accounts_handler {
+		service.register_handler(handler).map_err(|_| "Unable to register accounts handler".to_owned())?;
+	}
+	if let Some(handler) = expiry_handler {
+		service.register_handler(handler).map_err(|_| "Unable to register signer expiry handler".to_owned())?;
+	}
 
 	client.set_exit_handler(on_client_rq);
 
@@ -366,7 +491,9 @@ fn execute_light_impl<Cr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq
 			informant,
 			client,
 			keep_alive: Box::new((service, ws_server, http_server, ipc_server, runtime)),
-		}
+		},
+		config_reloader,
+		shutdown_timeout: cmd.shutdown_timeout,
 	})
 }
 
@@ -473,6 +600,10 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	};
 	sync_config.download_old_blocks = cmd.download_old_blocks;
 	sync_config.serve_light = cmd.serve_light;
+	sync_config.light_serve_load = cmd.light_serve_load;
+	sync_config.light_serve_credit_seconds = cmd.light_serve_credit_seconds;
+	sync_config.light_serve_limits = cmd.light_serve_limits;
+	sync_config.light_credits_request = cmd.light_credits_request;
 
 	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
 
@@ -518,6 +649,11 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 		warn!("The --no-hardcoded-sync flag has no effect if you don't use --light");
 	}
 
+	// display warning if using --light-checkpoint
+	if cmd.light_checkpoint.is_some() {
+		warn!("The --light-checkpoint option has no effect if you don't use --light");
+	}
+
 	// create client config
 	let mut client_config = to_client_config(
 		&cmd.cache_config,
@@ -538,9 +674,18 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	client_config.queue.verifier_settings = cmd.verifier_settings;
 	client_config.transaction_verification_queue_size = ::std::cmp::max(2048, txpool_size / 4);
 	client_config.snapshot = cmd.snapshot_conf.clone();
+	client_config.auto_rollback = cmd.auto_rollback;
+	client_config.journal_service_transactions = cmd.local_store.categories.service_transactions;
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
+	let reload_settings = ReloadableSettings::new(
+		cmd.logger_config.mode.clone(),
+		&cmd.gas_pricer_conf,
+		net_conf.reserved_nodes.clone(),
+		&cmd.http_conf,
+		&cmd.ws_conf,
+	);
 	if !cmd.custom_bootnodes {
 		net_conf.boot_nodes = spec.nodes.clone();
 	}
@@ -590,13 +735,10 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	let store = {
 		let db = service.db();
 		let node_info = FullNodeInfo {
-			miner: match cmd.no_persistent_txqueue {
-				true => None,
-				false => Some(miner.clone()),
-			}
+			miner: miner.clone(),
 		};
 
-		let store = ::local_store::create(db.key_value().clone(), ::ethcore_db::COL_NODE_INFO, node_info);
+		let store = ::local_store::create(db.key_value().clone(), ::ethcore_db::COL_NODE_INFO, node_info, cmd.local_store.clone());
 
 		if cmd.no_persistent_txqueue {
 			info!("Running without a persistent transaction queue.");
@@ -618,6 +760,20 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 			Err(e) => warn!("Error loading cached pending transactions from disk: {}", e),
 		}
 
+		// re-queue journaled transactions of other origin.
+		match store.pool_transactions() {
+			Ok(ref pool) if pool.is_empty() => {},
+			Ok(pool) => {
+				let total = pool.len();
+				let imported = miner.import_external_transactions(&*client, pool.into_iter().map(|tx| tx.transaction.into()).collect())
+					.into_iter()
+					.filter(Result::is_ok)
+					.count();
+				info!("Restored {} of {} journaled pool transactions", imported, total);
+			},
+			Err(e) => warn!("Error loading journaled pool transactions from disk: {}", e),
+		}
+
 		Arc::new(store)
 	};
 
@@ -636,7 +792,7 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	let mut attached_protos = Vec::new();
 
 	let whisper_factory = if cmd.whisper.enabled {
-		let whisper_factory = ::whisper::setup(cmd.whisper.target_message_pool_size, &mut attached_protos)
+		let whisper_factory = ::whisper::setup(&cmd.whisper, &db_dirs.whisper_mail_path(), &mut attached_protos)
 			.map_err(|e| format!("Failed to initialize whisper: {}", e))?;
 
 		whisper_factory
@@ -717,10 +873,31 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	);
 	service.add_notify(updater.clone());
 
+	// secret store key server
+	let secretstore_deps = secretstore::Dependencies {
+		client: client.clone(),
+		sync: sync_provider.clone(),
+		miner: miner.clone(),
+		account_provider: account_provider.clone(),
+		accounts_passwords: &passwords,
+	};
+	let secretstore_key_server = secretstore::start(cmd.secretstore_conf.clone(), secretstore_deps, runtime.executor())?;
+
 	// set up dependencies for rpc servers
 	let rpc_stats = Arc::new(informant::RpcStats::default());
 	let secret_store = account_provider.clone();
 	let signer_service = Arc::new(signer::new_service(&cmd.ws_conf, &cmd.logger_config));
+	let accounts_handler = account_utils::accounts_handler(account_provider.clone(), signer_service.clone());
+	let expiry_handler = signer::expiry_handler(signer_service.clone());
+
+	let http_domains = cmd.http_conf.domains();
+	let ws_domains = cmd.ws_conf.domains();
+	let config_reloader = Arc::new(ConfigReloader::new(cmd.configuration, reload_settings, Arc::new(NodeReloadTarget {
+		miner: Some(miner.clone()),
+		net: manage_network.clone(),
+		http_domains: http_domains.clone(),
+		ws_domains: ws_domains.clone(),
+	})));
 
 	let deps_for_rpc_apis = Arc::new(rpc_apis::FullDependencies {
 		signer_service: signer_service,
@@ -746,6 +923,8 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 		poll_lifetime: cmd.poll_lifetime,
 		allow_missing_blocks: cmd.allow_missing_blocks,
 		no_ancient_blocks: !cmd.download_old_blocks,
+		secretstore_admin: secretstore_key_server.as_ref().and_then(|key_server| key_server.admin()),
+		config_reloader: config_reloader.clone(),
 	});
 
 	let dependencies = rpc::Dependencies {
@@ -756,19 +935,9 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 
 	// start rpc servers
 	let rpc_direct = rpc::setup_apis(rpc_apis::ApiSet::All, &dependencies);
-	let ws_server = rpc::new_ws(cmd.ws_conf.clone(), &dependencies)?;
+	let ws_server = rpc::new_ws(cmd.ws_conf.clone(), ws_domains, &dependencies)?;
 	let ipc_server = rpc::new_ipc(cmd.ipc_conf, &dependencies)?;
-	let http_server = rpc::new_http("HTTP JSON-RPC", "jsonrpc", cmd.http_conf.clone(), &dependencies)?;
-
-	// secret store key server
-	let secretstore_deps = secretstore::Dependencies {
-		client: client.clone(),
-		sync: sync_provider.clone(),
-		miner: miner.clone(),
-		account_provider,
-		accounts_passwords: &passwords,
-	};
-	let secretstore_key_server = secretstore::start(cmd.secretstore_conf.clone(), secretstore_deps, runtime.executor())?;
+	let http_server = rpc::new_http("HTTP JSON-RPC", "jsonrpc", cmd.http_conf.clone(), http_domains, &dependencies)?;
 
 	// the ipfs server
 	let ipfs_server = ipfs::start_server(cmd.ipfs_conf.clone(), client.clone())?;
@@ -779,13 +948,21 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 			client: service.client(),
 			sync: Some(sync_provider.clone()),
 			net: Some(manage_network.clone()),
+			miner: Some(miner.clone()),
 		},
 		Some(snapshot_service.clone()),
 		Some(rpc_stats.clone()),
 		cmd.logger_config.color,
+		cmd.output_format,
 	));
 	service.add_notify(informant.clone());
 	service.register_io_handler(informant.clone()).map_err(|_| "Unable to register informant handler".to_owned())?;
+	if let Some(handler) = accounts_handler {
+		service.register_io_handler(handler).map_err(|_| "Unable to register accounts handler".to_owned())?;
+	}
+	if let Some(handler) = expiry_handler {
+		service.register_io_handler(handler).map_err(|_| "Unable to register signer expiry handler".to_owned())?;
+	}
 
 	// save user defaults
 	user_defaults.is_first_launch = false;
@@ -831,7 +1008,9 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 			client,
 			client_service: Arc::new(service),
 			keep_alive: Box::new((watcher, updater, ws_server, http_server, ipc_server, secretstore_key_server, ipfs_server, runtime)),
-		}
+		},
+		config_reloader,
+		shutdown_timeout: cmd.shutdown_timeout,
 	})
 }
 
@@ -841,6 +1020,8 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 /// background.
 pub struct RunningClient {
 	inner: RunningClientInner,
+	config_reloader: Arc<ConfigReloader>,
+	shutdown_timeout: Duration,
 }
 
 enum RunningClientInner {
@@ -876,37 +1057,65 @@ impl RunningClient {
 		}
 	}
 
-	/// Shuts down the client.
+	/// Reloader of the configuration of the client.
+	pub fn config_reloader(&self) -> Arc<ConfigReloader> {
+		self.config_reloader.clone()
+	}
+
+	/// Shuts down the client, waiting for all of it to be dropped. Steps still running once
+	/// the configured shutdown timeout has passed are logged, but the shutdown isn't cut short;
+	/// use `shutdown_sequence` to act on the timeout instead.
 	pub fn shutdown(self) {
+		let timeout = self.shutdown_timeout;
+		self.shutdown_sequence().run(timeout, |pending| {
+			warn!("Shutdown timeout reached, still running: {}", pending.join(", "));
+		});
+	}
+
+	/// Steps to shut down the client. Dropping the services flushes the local store
+	/// and the private transaction logs, dropping the client flushes the database.
+	pub fn shutdown_sequence(self) -> ShutdownSequence<'static> {
 		match self.inner {
 			RunningClientInner::Light { rpc, informant, client, keep_alive } => {
 				// Create a weak reference to the client so that we can wait on shutdown
 				// until it is dropped
 				let weak_client = Arc::downgrade(&client);
-				drop(rpc);
-				drop(keep_alive);
-				informant.shutdown();
-				drop(informant);
-				drop(client);
-				wait_for_drop(weak_client);
+				ShutdownSequence::new()
+					.step("rpc", move || drop(rpc))
+					.step("services", move || drop(keep_alive))
+					.step("informant", move || {
+						informant.shutdown();
+						drop(informant);
+					})
+					.step("client", move || {
+						drop(client);
+						wait_for_drop(weak_client);
+					})
 			},
 			RunningClientInner::Full { rpc, informant, client, client_service, keep_alive } => {
 				info!("Finishing work, please wait...");
 				// Create a weak reference to the client so that we can wait on shutdown
 				// until it is dropped
 				let weak_client = Arc::downgrade(&client);
-				// Shutdown and drop the ServiceClient
-				client_service.shutdown();
-				drop(client_service);
-				// drop this stuff as soon as exit detected.
-				drop(rpc);
-				drop(keep_alive);
-				// to make sure timer does not spawn requests while shutdown is in progress
-				informant.shutdown();
-				// just Arc is dropping here, to allow other reference release in its default time
-				drop(informant);
-				drop(client);
-				wait_for_drop(weak_client);
+				ShutdownSequence::new()
+					// Shutdown and drop the ServiceClient
+					.step("client service", move || {
+						client_service.shutdown();
+						drop(client_service);
+					})
+					// drop this stuff as soon as exit detected.
+					.step("rpc", move || drop(rpc))
+					.step("services", move || drop(keep_alive))
+					// to make sure timer does not spawn requests while shutdown is in progress
+					.step("informant", move || {
+						informant.shutdown();
+						// just Arc is dropping here, to allow other reference release in its default time
+						drop(informant);
+					})
+					.step("client", move || {
+						drop(client);
+						wait_for_drop(weak_client);
+					})
 			}
 		}
 	}
@@ -17,13 +17,14 @@
 use std::collections::BTreeMap;
 use std::sync::Arc;
 use account_utils::AccountProvider;
-use dir::default_data_path;
+use dir::{Directories, default_data_path};
 use dir::helpers::replace_home;
 use ethcore::client::Client;
 use ethcore::miner::Miner;
 use ethkey::{Secret, Public, Password};
 use sync::SyncProvider;
 use ethereum_types::Address;
+use params::{SpecType, AccountsConfig};
 use parity_runtime::Executor;
 
 /// This node secret key.
@@ -45,6 +46,41 @@ pub enum ContractAddress {
 	Address(Address),
 }
 
+/// Kind of requests, served by the secret store service contract.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ServiceContractKind {
+	/// General contract, serving all kinds of requests.
+	General,
+	/// Server key generation requests.
+	ServerKeyGeneration,
+	/// Server key retrieval requests.
+	ServerKeyRetrieval,
+	/// Document key store requests.
+	DocumentKeyStore,
+	/// Document key shadow retrieval requests.
+	DocumentKeyShadowRetrieval,
+}
+
+/// Secret store service contract.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ServiceContract {
+	/// Kind of requests, served by the contract.
+	pub kind: ServiceContractKind,
+	/// Contract address source.
+	pub address: ContractAddress,
+}
+
+/// Servers set change sessions, started on behalf of administrator.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AdminMigrationMode {
+	/// Sessions are never started automatically.
+	Disabled,
+	/// Migration plan is logged, but session is not started.
+	DryRun,
+	/// Session is started when servers set changes.
+	Enabled,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 /// Secret store configuration
 pub struct Configuration {
@@ -54,18 +90,16 @@ pub struct Configuration {
 	pub http_enabled: bool,
 	/// Is auto migrate enabled.
 	pub auto_migrate_enabled: bool,
+	/// Servers set change sessions, started by this node on behalf of administrator.
+	pub admin_migration_mode: AdminMigrationMode,
 	/// ACL check contract address.
 	pub acl_check_contract_address: Option<ContractAddress>,
-	/// Service contract address.
-	pub service_contract_address: Option<ContractAddress>,
-	/// Server key generation service contract address.
-	pub service_contract_srv_gen_address: Option<ContractAddress>,
-	/// Server key retrieval service contract address.
-	pub service_contract_srv_retr_address: Option<ContractAddress>,
-	/// Document key store service contract address.
-	pub service_contract_doc_store_address: Option<ContractAddress>,
-	/// Document key shadow retrieval service contract address.
-	pub service_contract_doc_sretr_address: Option<ContractAddress>,
+	/// Lifetime of cached ACL checks results (in seconds).
+	pub acl_check_cache_ttl: u64,
+	/// Maximal number of sessions, which are running concurrently to serve single batch request.
+	pub batch_sessions_parallelism: usize,
+	/// Service contracts to listen to.
+	pub service_contracts: Vec<ServiceContract>,
 	/// This node secret.
 	pub self_secret: Option<NodeSecretKey>,
 	/// Other nodes IDs + addresses.
@@ -86,6 +120,46 @@ pub struct Configuration {
 	pub admin_public: Option<Public>,
 	// Allowed CORS domains
 	pub cors: Option<Vec<String>>,
+	/// TLS configuration of HTTP API. If None, HTTP API is served over plain HTTP.
+	pub http_tls: Option<HttpTlsConfiguration>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+/// Secret store HTTP API TLS configuration
+pub struct HttpTlsConfiguration {
+	/// Path to PEM-encoded certificate chain.
+	pub certificate_path: String,
+	/// Path to PEM-encoded private key.
+	pub private_key_path: String,
+	/// Path to PEM-encoded CA certificates used to verify client certificates.
+	pub client_ca_path: Option<String>,
+}
+
+/// Secret store key shares database command.
+#[derive(Debug, PartialEq)]
+pub enum SecretStoreCmd {
+	/// Write encrypted archive of all key shares to the file.
+	Backup {
+		file: String,
+	},
+	/// Restore key shares from the archive file.
+	Restore {
+		file: String,
+		/// Overwrite key shares, having versions which are missing in the archive.
+		force: bool,
+	},
+	/// List stored key shares and check their integrity.
+	Verify,
+}
+
+/// Secret store key shares database command with its configuration.
+#[derive(Debug, PartialEq)]
+pub struct SecretStoreCommand {
+	pub cmd: SecretStoreCmd,
+	pub conf: Configuration,
+	pub spec: SpecType,
+	pub dirs: Directories,
+	pub acc_conf: AccountsConfig,
 }
 
 /// Secret store dependencies
@@ -104,7 +178,9 @@ pub struct Dependencies<'a> {
 
 #[cfg(not(feature = "secretstore"))]
 mod server {
-	use super::{Configuration, Dependencies, Executor};
+	use std::sync::Arc;
+	use parity_rpc::v1::secretstore_admin::KeyServerAdmin;
+	use super::{Configuration, Dependencies, Executor, SecretStoreCommand};
 
 	/// Noop key server implementation
 	pub struct KeyServer;
@@ -114,17 +190,37 @@ mod server {
 		pub fn new(_conf: Configuration, _deps: Dependencies, _executor: Executor) -> Result<Self, String> {
 			Ok(KeyServer)
 		}
+
+		/// Administrative sessions are not available without the key server.
+		pub fn admin(&self) -> Option<Arc<KeyServerAdmin>> {
+			None
+		}
+	}
+
+	/// Key shares database commands are not supported without the key server.
+	pub fn execute(_cmd: SecretStoreCommand) -> Result<String, String> {
+		Err("Secret Store is not supported by this build".into())
 	}
 }
 
 #[cfg(feature = "secretstore")]
 mod server {
+	use std::collections::BTreeSet;
+	use std::fs;
+	use std::path::Path;
 	use std::sync::Arc;
-	use ethcore_secretstore;
-	use ethkey::KeyPair;
+	use std::time::Duration;
+	use account_utils::{self, AccountProvider};
+	use ethcore_secretstore::{self, AdminSessionsServer};
+	use ethereum_types::{H256, H512};
+	use ethkey::{KeyPair, Password, Signature};
+	use parity_rpc::v1::secretstore_admin::{KeyServerAdmin, KeyServerAdminSession};
 	use ansi_term::Colour::{Red, White};
 	use db;
-	use super::{Configuration, Dependencies, NodeSecretKey, ContractAddress, Executor};
+	use helpers::passwords_from_files;
+	use path::restrict_permissions_owner;
+	use super::{Configuration, Dependencies, NodeSecretKey, ContractAddress, ServiceContractKind, AdminMigrationMode, Executor,
+		SecretStoreCmd, SecretStoreCommand};
 
 	fn into_service_contract_address(address: ContractAddress) -> ethcore_secretstore::ContractAddress {
 		match address {
@@ -133,38 +229,136 @@ mod server {
 		}
 	}
 
+	fn into_admin_migration_mode(mode: AdminMigrationMode) -> ethcore_secretstore::AdminMigrationMode {
+		match mode {
+			AdminMigrationMode::Disabled => ethcore_secretstore::AdminMigrationMode::Disabled,
+			AdminMigrationMode::DryRun => ethcore_secretstore::AdminMigrationMode::DryRun,
+			AdminMigrationMode::Enabled => ethcore_secretstore::AdminMigrationMode::Enabled,
+		}
+	}
+
+	fn into_service_contract_kind(kind: ServiceContractKind) -> ethcore_secretstore::ServiceContractKind {
+		match kind {
+			ServiceContractKind::General => ethcore_secretstore::ServiceContractKind::General,
+			ServiceContractKind::ServerKeyGeneration => ethcore_secretstore::ServiceContractKind::ServerKeyGeneration,
+			ServiceContractKind::ServerKeyRetrieval => ethcore_secretstore::ServiceContractKind::ServerKeyRetrieval,
+			ServiceContractKind::DocumentKeyStore => ethcore_secretstore::ServiceContractKind::DocumentKeyStore,
+			ServiceContractKind::DocumentKeyShadowRetrieval => ethcore_secretstore::ServiceContractKind::DocumentKeyShadowRetrieval,
+		}
+	}
+
+	#[cfg_attr(not(feature = "accounts"), allow(unused_variables))]
+	fn node_key_pair(self_secret: Option<NodeSecretKey>, account_provider: Arc<AccountProvider>, accounts_passwords: &[Password])
+		-> Result<Arc<ethcore_secretstore::NodeKeyPair>, String>
+	{
+		match self_secret {
+			Some(NodeSecretKey::Plain(secret)) => Ok(Arc::new(ethcore_secretstore::PlainNodeKeyPair::new(
+				KeyPair::from_secret(secret).map_err(|e| format!("invalid secret: {}", e))?))),
+			#[cfg(feature = "accounts")]
+			Some(NodeSecretKey::KeyStore(account)) => {
+				// Check if account exists
+				if !account_provider.has_account(account.clone()) {
+					return Err(format!("Account {} passed as secret store node key is not found", account));
+				}
+
+				// Check if any passwords have been read from the password file(s)
+				if accounts_passwords.is_empty() {
+					return Err(format!("No password found for the secret store node account {}", account));
+				}
+
+				// Attempt to sign in the engine signer.
+				let password = accounts_passwords.iter()
+					.find(|p| account_provider.sign(account.clone(), Some((*p).clone()), Default::default()).is_ok())
+					.ok_or_else(|| format!("No valid password for the secret store node account {}", account))?;
+				Ok(Arc::new(ethcore_secretstore::KeyStoreNodeKeyPair::new(account_provider, account, password.clone())
+					.map_err(|e| format!("{}", e))?))
+			},
+			None => Err("self secret is required when using secretstore".into()),
+		}
+	}
+
+	/// Execute key shares database command.
+	pub fn execute(cmd: SecretStoreCommand) -> Result<String, String> {
+		let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
+		let spec = cmd.spec.spec(&cmd.dirs.cache)?;
+		let account_provider = Arc::new(account_utils::prepare_account_provider(&cmd.spec, &cmd.dirs, &spec.data_dir, cmd.acc_conf, &passwords)?);
+		let self_key_pair = node_key_pair(cmd.conf.self_secret, account_provider, &passwords)?;
+		let db = db::open_secretstore_db(&cmd.conf.data_path)?;
+
+		match cmd.cmd {
+			SecretStoreCmd::Backup { file } => {
+				let archive = ethcore_secretstore::backup_key_storage(db, &*self_key_pair)
+					.map_err(|e| format!("Could not create backup: {}", e))?;
+				fs::write(&file, archive).map_err(|e| format!("Could not write {}: {}", file, e))?;
+				let _ = restrict_permissions_owner(Path::new(&file), true, false);
+
+				Ok(format!("Key shares backed up to {}", file))
+			},
+			SecretStoreCmd::Restore { file, force } => {
+				let archive = fs::read(&file).map_err(|e| format!("Could not read {}: {}", file, e))?;
+				let restored = ethcore_secretstore::restore_key_storage(db, &*self_key_pair, &archive, force)
+					.map_err(|e| format!("Could not restore backup: {}", e))?;
+
+				Ok(format!("{} key share(s) restored", restored))
+			},
+			SecretStoreCmd::Verify => {
+				let statuses = ethcore_secretstore::verify_key_storage(db, self_key_pair.public())
+					.map_err(|e| format!("Could not read key shares: {}", e))?;
+				let corrupted = statuses.iter().filter(|s| s.error.is_some()).count();
+				let report = statuses.iter()
+					.map(|s| format!("{:x} threshold: {} versions: {} {}",
+						s.key_id,
+						s.threshold.map_or_else(|| "-".to_owned(), |t| t.to_string()),
+						s.versions,
+						s.error.as_ref().map_or_else(|| "OK".to_owned(), |e| format!("CORRUPTED: {}", e))))
+					.chain(::std::iter::once(format!("{} key share(s), {} corrupted", statuses.len(), corrupted)))
+					.collect::<Vec<_>>()
+					.join("\n");
+
+				match corrupted {
+					0 => Ok(report),
+					_ => Err(report),
+				}
+			},
+		}
+	}
+
 	/// Key server
 	pub struct KeyServer {
-		_key_server: Box<ethcore_secretstore::KeyServer>,
+		key_server: Arc<ethcore_secretstore::KeyServer>,
+	}
+
+	/// Administrative sessions of the key server, exposed over RPC.
+	struct KeyServerAdminSessions(Arc<ethcore_secretstore::KeyServer>);
+
+	impl KeyServerAdmin for KeyServerAdminSessions {
+		fn change_servers_set(&self, old_set_signature: Signature, new_set_signature: Signature, new_servers_set: BTreeSet<H512>) -> Result<H256, String> {
+			self.0.start_servers_set_change(old_set_signature, new_set_signature, new_servers_set)
+				.map_err(Into::into)
+		}
+
+		fn sessions(&self) -> Result<Vec<KeyServerAdminSession>, String> {
+			let sessions = self.0.admin_sessions().map_err(Into::<String>::into)?;
+			Ok(sessions.into_iter().map(|session| KeyServerAdminSession {
+				id: session.id,
+				session_type: session.session_type,
+				master: session.master,
+				is_finished: session.is_finished,
+				keys_total: session.keys_total,
+				keys_processed: session.keys_processed,
+			}).collect())
+		}
+
+		fn abort_session(&self, session_id: H256, signature: Signature) -> Result<(), String> {
+			self.0.abort_admin_session(&session_id, signature)
+				.map_err(Into::into)
+		}
 	}
 
 	impl KeyServer {
 		/// Create new key server
 		pub fn new(mut conf: Configuration, deps: Dependencies, executor: Executor) -> Result<Self, String> {
-			let self_secret: Arc<ethcore_secretstore::NodeKeyPair> = match conf.self_secret.take() {
-				Some(NodeSecretKey::Plain(secret)) => Arc::new(ethcore_secretstore::PlainNodeKeyPair::new(
-					KeyPair::from_secret(secret).map_err(|e| format!("invalid secret: {}", e))?)),
-				#[cfg(feature = "accounts")]
-				Some(NodeSecretKey::KeyStore(account)) => {
-					// Check if account exists
-					if !deps.account_provider.has_account(account.clone()) {
-						return Err(format!("Account {} passed as secret store node key is not found", account));
-					}
-
-					// Check if any passwords have been read from the password file(s)
-					if deps.accounts_passwords.is_empty() {
-						return Err(format!("No password found for the secret store node account {}", account));
-					}
-
-					// Attempt to sign in the engine signer.
-					let password = deps.accounts_passwords.iter()
-						.find(|p| deps.account_provider.sign(account.clone(), Some((*p).clone()), Default::default()).is_ok())
-						.ok_or_else(|| format!("No valid password for the secret store node account {}", account))?;
-					Arc::new(ethcore_secretstore::KeyStoreNodeKeyPair::new(deps.account_provider, account, password.clone())
-						.map_err(|e| format!("{}", e))?)
-				},
-				None => return Err("self secret is required when using secretstore".into()),
-			};
+			let self_secret = node_key_pair(conf.self_secret.take(), deps.account_provider, deps.accounts_passwords)?;
 
 			info!("Starting SecretStore node: {}", White.bold().paint(format!("{:?}", self_secret.public())));
 			if conf.acl_check_contract_address.is_none() {
@@ -177,12 +371,12 @@ mod server {
 					address: conf.http_interface.clone(),
 					port: conf.http_port,
 				}) } else { None },
-				service_contract_address: conf.service_contract_address.map(into_service_contract_address),
-				service_contract_srv_gen_address: conf.service_contract_srv_gen_address.map(into_service_contract_address),
-				service_contract_srv_retr_address: conf.service_contract_srv_retr_address.map(into_service_contract_address),
-				service_contract_doc_store_address: conf.service_contract_doc_store_address.map(into_service_contract_address),
-				service_contract_doc_sretr_address: conf.service_contract_doc_sretr_address.map(into_service_contract_address),
+				service_contracts: conf.service_contracts.into_iter().map(|contract| ethcore_secretstore::ServiceContractConfiguration {
+					kind: into_service_contract_kind(contract.kind),
+					address: into_service_contract_address(contract.address),
+				}).collect(),
 				acl_check_contract_address: conf.acl_check_contract_address.map(into_service_contract_address),
+				acl_check_cache_ttl: Duration::from_secs(conf.acl_check_cache_ttl),
 				cluster_config: ethcore_secretstore::ClusterConfiguration {
 					listener_address: ethcore_secretstore::NodeAddress {
 						address: conf.interface.clone(),
@@ -196,8 +390,15 @@ mod server {
 					allow_connecting_to_higher_nodes: true,
 					admin_public: conf.admin_public,
 					auto_migrate_enabled: conf.auto_migrate_enabled,
+					admin_migration_mode: into_admin_migration_mode(conf.admin_migration_mode),
+					batch_sessions_parallelism: conf.batch_sessions_parallelism,
 				},
-				cors: conf.cors
+				cors: conf.cors,
+				http_tls: conf.http_tls.map(|tls| ethcore_secretstore::HttpTlsConfiguration {
+					certificate_path: tls.certificate_path,
+					private_key_path: tls.private_key_path,
+					client_ca_path: tls.client_ca_path,
+				}),
 			};
 
 			cconf.cluster_config.nodes.insert(self_secret.public().clone(), cconf.cluster_config.listener_address.clone());
@@ -207,13 +408,154 @@ mod server {
 				.map_err(|e| format!("Error starting KeyServer {}: {}", key_server_name, e))?;
 
 			Ok(KeyServer {
-				_key_server: key_server,
+				key_server: key_server.into(),
 			})
 		}
+
+		/// Administrative sessions of the key server.
+		pub fn admin(&self) -> Option<Arc<KeyServerAdmin>> {
+			Some(Arc::new(KeyServerAdminSessions(self.key_server.clone())))
+		}
+	}
+
+	#[cfg(test)]
+	mod tests {
+		use std::collections::{BTreeMap, BTreeSet};
+		use std::sync::Arc;
+		use std::thread;
+		use std::time::{Duration, Instant};
+		use ethcore::miner::Miner;
+		use ethcore::spec::Spec;
+		use ethcore::test_helpers::generate_dummy_client;
+		use ethcore_secretstore::{self, AdminSessionsServer};
+		use ethereum_types::{H256, H520};
+		use ethkey::{Generator, KeyPair, Random, sign};
+		use jsonrpc_core::IoHandler;
+		use parity_rpc::v1::{SecretStoreAdmin, SecretStoreAdminClient};
+		use parity_rpc::v1::secretstore_admin::ordered_servers_keccak;
+		use parity_runtime::Runtime;
+		use serde_json;
+		use sync::{SyncProvider, SyncStatus, SyncState, PeerInfo, Notification, TransactionStats};
+		use tempdir::TempDir;
+		use db;
+		use super::KeyServerAdminSessions;
+
+		/// Sync provider of the fully synchronized node.
+		struct SyncedProvider;
+
+		impl SyncProvider for SyncedProvider {
+			fn status(&self) -> SyncStatus { unimplemented!() }
+			fn peers(&self) -> Vec<PeerInfo> { Vec::new() }
+			fn enode(&self) -> Option<String> { None }
+			fn sync_notification(&self) -> Notification<SyncState> { unimplemented!() }
+			fn transactions_stats(&self) -> BTreeMap<H256, TransactionStats> { BTreeMap::new() }
+			fn is_major_syncing(&self) -> bool { false }
+		}
+
+		fn start_key_servers(start_port: u16, key_pairs: &[KeyPair], admin: &KeyPair, runtime: &Runtime, db_dir: &TempDir)
+			-> Vec<Arc<ethcore_secretstore::KeyServer>>
+		{
+			let client = generate_dummy_client(0);
+			let sync: Arc<SyncProvider> = Arc::new(SyncedProvider);
+			let miner = Arc::new(Miner::new_for_tests(&Spec::new_test(), None));
+			let nodes: BTreeMap<_, _> = key_pairs.iter().enumerate()
+				.map(|(i, kp)| (kp.public().clone(), ethcore_secretstore::NodeAddress {
+					address: "127.0.0.1".into(),
+					port: start_port + i as u16,
+				}))
+				.collect();
+
+			key_pairs.iter().enumerate().map(|(i, kp)| {
+				let config = ethcore_secretstore::ServiceConfiguration {
+					listener_address: None,
+					service_contracts: Vec::new(),
+					acl_check_contract_address: None,
+					acl_check_cache_ttl: Duration::from_secs(600),
+					cluster_config: ethcore_secretstore::ClusterConfiguration {
+						listener_address: nodes[kp.public()].clone(),
+						nodes: nodes.clone(),
+						key_server_set_contract_address: None,
+						allow_connecting_to_higher_nodes: true,
+						admin_public: Some(admin.public().clone()),
+						auto_migrate_enabled: false,
+						admin_migration_mode: ethcore_secretstore::AdminMigrationMode::Disabled,
+						batch_sessions_parallelism: 1,
+					},
+					cors: None,
+					http_tls: None,
+				};
+				let db = db::open_secretstore_db(db_dir.path().join(i.to_string()).to_str().unwrap()).unwrap();
+				let self_key_pair = Arc::new(ethcore_secretstore::PlainNodeKeyPair::new(kp.clone()));
+				ethcore_secretstore::start(client.clone(), sync.clone(), miner.clone(), self_key_pair, config, db, runtime.executor())
+					.unwrap()
+					.into()
+			}).collect()
+		}
+
+		fn wait_until<F: Fn() -> bool>(what: &str, condition: F) {
+			let start = Instant::now();
+			while !condition() {
+				if start.elapsed() > Duration::from_secs(5) {
+					panic!("{} has not happened in 5s", what);
+				}
+				thread::sleep(Duration::from_millis(10));
+			}
+		}
+
+		fn request(method: &str, params: Vec<String>) -> String {
+			format!(r#"{{"jsonrpc": "2.0", "method": "{}", "params":[{}], "id": 1}}"#, method, params.join(", "))
+		}
+
+		#[test]
+		fn rpc_secretstore_admin_works_with_key_servers_cluster() {
+			let runtime = Runtime::with_thread_count(2);
+			let db_dir = TempDir::new("secretstore-admin").unwrap();
+			let admin = Random.generate().unwrap();
+			let key_pairs: Vec<_> = (0..2).map(|_| Random.generate().unwrap()).collect();
+			let key_servers = start_key_servers(6190, &key_pairs, &admin, &runtime, &db_dir);
+			wait_until("cluster connection", || key_servers.iter()
+				.all(|ks| ks.status().unwrap().connected_nodes.len() == key_pairs.len() - 1));
+
+			let mut io = IoHandler::default();
+			io.extend_with(SecretStoreAdminClient::new(Some(Arc::new(KeyServerAdminSessions(key_servers[0].clone())))).to_delegate());
+
+			// there are no active sessions initially
+			let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+			assert_eq!(io.handle_request_sync(&request("secretstore_adminSessions", vec![])), Some(response.to_owned()));
+
+			// servers set change session is started by the key server && completed on all nodes
+			let servers_set: BTreeSet<_> = key_pairs.iter().map(|kp| kp.public().clone()).collect();
+			let servers_set_signature: H520 = sign(admin.secret(), &ordered_servers_keccak(servers_set.clone())).unwrap().into();
+			let servers_set = serde_json::to_string(&servers_set).unwrap();
+			let servers_set_signature = serde_json::to_string(&servers_set_signature).unwrap();
+			let response = io.handle_request_sync(&request("secretstore_changeServersSet", vec![
+				servers_set.clone(), servers_set_signature.clone(), servers_set, servers_set_signature,
+			])).unwrap();
+			let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+			let session_id: H256 = serde_json::from_value(response["result"].clone()).unwrap();
+			wait_until("servers set change completion", || key_servers.iter()
+				.all(|ks| ks.admin_sessions().unwrap().is_empty()));
+
+			// session could only be aborted by administrator...
+			let signature: H520 = sign(Random.generate().unwrap().secret(), &session_id).unwrap().into();
+			let abort_request = request("secretstore_abortAdminSession", vec![
+				serde_json::to_string(&session_id).unwrap(), serde_json::to_string(&signature).unwrap(),
+			]);
+			let response = r#"{"jsonrpc":"2.0","error":{"code":-32025,"message":"Secret Store administrative call failed.","data":"Access denied"},"id":1}"#;
+			assert_eq!(io.handle_request_sync(&abort_request), Some(response.to_owned()));
+
+			// ...and only while it is active
+			let signature: H520 = sign(admin.secret(), &session_id).unwrap().into();
+			let abort_request = request("secretstore_abortAdminSession", vec![
+				serde_json::to_string(&session_id).unwrap(), serde_json::to_string(&signature).unwrap(),
+			]);
+			let response = r#"{"jsonrpc":"2.0","error":{"code":-32025,"message":"Secret Store administrative call failed.","data":"no active session with given id"},"id":1}"#;
+			assert_eq!(io.handle_request_sync(&abort_request), Some(response.to_owned()));
+		}
 	}
 }
 
-pub use self::server::KeyServer;
+pub use self::server::{KeyServer, execute};
 
 impl Default for Configuration {
 	fn default() -> Self {
@@ -222,12 +564,11 @@ impl Default for Configuration {
 			enabled: true,
 			http_enabled: true,
 			auto_migrate_enabled: true,
+			admin_migration_mode: AdminMigrationMode::Disabled,
 			acl_check_contract_address: Some(ContractAddress::Registry),
-			service_contract_address: None,
-			service_contract_srv_gen_address: None,
-			service_contract_srv_retr_address: None,
-			service_contract_doc_store_address: None,
-			service_contract_doc_sretr_address: None,
+			acl_check_cache_ttl: 600,
+			batch_sessions_parallelism: 8,
+			service_contracts: Vec::new(),
 			self_secret: None,
 			admin_public: None,
 			nodes: BTreeMap::new(),
@@ -238,6 +579,7 @@ impl Default for Configuration {
 			http_port: 8082,
 			data_path: replace_home(&data_dir, "$BASE/secretstore"),
 			cors: Some(vec![]),
+			http_tls: None,
 		}
 	}
 }
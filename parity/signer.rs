@@ -16,9 +16,13 @@
 
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
 use ansi_term::Colour::White;
+use ethcore::client::ClientIoMessage;
 use ethcore_logger::Config as LogConfig;
+use io::{IoContext, IoHandler, TimerToken};
 use rpc;
 use rpc_apis;
 use parity_rpc;
@@ -38,7 +42,36 @@ pub fn new_service(ws_conf: &rpc::WsConfiguration, logger_config: &LogConfig) ->
 
 	rpc_apis::SignerService::new(move || {
 		generate_new_token(&signer_path, logger_config_color).map_err(|e| format!("{:?}", e))
-	}, signer_enabled)
+	}, signer_enabled, ws_conf.confirmation_timeout)
+}
+
+const EXPIRY_TIMER: TimerToken = 0;
+
+/// Periodically rejects signing requests that weren't confirmed within `--ui-confirmation-timeout`.
+struct ExpiryHandler {
+	signer_service: Arc<rpc_apis::SignerService>,
+}
+
+impl IoHandler<ClientIoMessage> for ExpiryHandler {
+	fn initialize(&self, io: &IoContext<ClientIoMessage>) {
+		io.register_timer(EXPIRY_TIMER, Duration::from_secs(1)).expect("Error registering signer expiry timer");
+	}
+
+	fn timeout(&self, _io: &IoContext<ClientIoMessage>, timer: TimerToken) {
+		if timer != EXPIRY_TIMER {
+			return;
+		}
+
+		let expired = self.signer_service.queue().expire_requests();
+		if !expired.is_empty() {
+			info!("Rejected signing requests that weren't confirmed in time: {:?}", expired);
+		}
+	}
+}
+
+/// Returns a handler rejecting expired signing requests, if the confirmation timeout is set.
+pub fn expiry_handler(signer_service: Arc<rpc_apis::SignerService>) -> Option<Arc<IoHandler<ClientIoMessage> + Send>> {
+	signer_service.queue().timeout().map(|_| Arc::new(ExpiryHandler { signer_service }) as Arc<IoHandler<ClientIoMessage> + Send>)
 }
 
 pub fn codes_path(path: &Path) -> PathBuf {
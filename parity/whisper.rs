@@ -16,18 +16,26 @@
 
 use std::sync::Arc;
 use std::io;
+use std::path::Path;
 
 use sync::{AttachedProtocol, ManageNetwork};
 use parity_rpc::Metadata;
+use parity_whisper::mail_server::{self, MailServer};
 use parity_whisper::message::Message;
 use parity_whisper::net::{self as whisper_net, Network as WhisperNetwork};
 use parity_whisper::rpc::{WhisperClient, PoolHandle, FilterManager};
 
 /// Whisper config.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq)]
 pub struct Config {
 	pub enabled: bool,
 	pub target_message_pool_size: usize,
+	pub min_pow: f64,
+	pub max_message_size: usize,
+	pub peer_envelopes_per_second: u64,
+	pub peer_bytes_per_second: u64,
+	pub light: bool,
+	pub mail_server: Option<mail_server::Config>,
 }
 
 impl Default for Config {
@@ -35,6 +43,12 @@ impl Default for Config {
 		Config {
 			enabled: false,
 			target_message_pool_size: 10 * 1024 * 1024,
+			min_pow: whisper_net::DEFAULT_MIN_POW,
+			max_message_size: whisper_net::DEFAULT_MAX_ENVELOPE_SIZE,
+			peer_envelopes_per_second: 0,
+			peer_bytes_per_second: 0,
+			light: false,
+			mail_server: None,
 		}
 	}
 }
@@ -62,6 +76,10 @@ impl PoolHandle for NetPoolHandle {
 	fn pool_status(&self) -> whisper_net::PoolStatus {
 		self.handle.pool_status()
 	}
+
+	fn peer_stats(&self) -> Vec<whisper_net::PeerStats> {
+		self.handle.peer_stats()
+	}
 }
 
 /// Factory for standard whisper RPC.
@@ -79,13 +97,36 @@ impl RpcFactory {
 
 /// Sets up whisper protocol and RPC handler.
 ///
-/// Will target the given pool size.
+/// Will target the given pool size and relay only envelopes within the configured limits.
+/// When running as a mail server, envelopes are stored in a database at the given path.
 #[cfg(not(feature = "ipc"))]
-pub fn setup(target_pool_size: usize, protos: &mut Vec<AttachedProtocol>)
+pub fn setup(config: &Config, mail_server_path: &Path, protos: &mut Vec<AttachedProtocol>)
 	-> io::Result<Option<RpcFactory>>
 {
+	let limits = whisper_net::EnvelopeLimits {
+		min_pow: config.min_pow,
+		max_size: config.max_message_size,
+		per_peer: whisper_net::PeerBudgets {
+			envelopes_per_second: config.peer_envelopes_per_second,
+			bytes_per_second: config.peer_bytes_per_second,
+			..Default::default()
+		},
+	};
 	let manager = Arc::new(FilterManager::new()?);
-	let net = Arc::new(WhisperNetwork::new(target_pool_size, manager.clone()));
+	let net = WhisperNetwork::with_limits(config.target_message_pool_size, limits, manager.clone());
+	let net = match config.light {
+		true => net.with_light_mode(),
+		false => net,
+	};
+	let net = Arc::new(match config.mail_server {
+		Some(ref mail_server_config) => {
+			let db = ::db::open_whisper_mail_db(mail_server_path)
+				.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+			let mail_server = MailServer::new(db, ::db::COL_WHISPER_MAIL, mail_server_config.clone());
+			net.with_mail_server(Arc::new(mail_server))
+		}
+		None => net,
+	});
 
 	protos.push(AttachedProtocol {
 		handler: net.clone() as Arc<_>,
@@ -107,7 +148,7 @@ pub fn setup(target_pool_size: usize, protos: &mut Vec<AttachedProtocol>)
 
 // TODO: make it possible to attach generic protocols in IPC.
 #[cfg(feature = "ipc")]
-pub fn setup(_target_pool_size: usize, _protos: &mut Vec<AttachedProtocol>)
+pub fn setup(_config: &Config, _mail_server_path: &Path, _protos: &mut Vec<AttachedProtocol>)
 	-> io::Result<Option<RpcFactory>>
 {
 	Ok(None)
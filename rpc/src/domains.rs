@@ -0,0 +1,121 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Validation of the hosts and origins a server accepts requests from.
+
+use std::str;
+use std::sync::Arc;
+
+use http::{self, hyper};
+use parking_lot::RwLock;
+use server_utils::cors::{self, AccessControlAllowOrigin, AllowCors};
+use server_utils::hosts::{self, Host};
+use ws;
+
+/// Hosts and origins a HTTP or WebSockets server accepts requests from.
+///
+/// The lists are shared with the running server, so they can be replaced without restarting it.
+/// `None` disables the respective validation.
+#[derive(Debug, Clone)]
+pub struct AllowedDomains {
+	domains: Arc<RwLock<Domains>>,
+}
+
+#[derive(Debug)]
+struct Domains {
+	hosts: Option<Vec<Host>>,
+	origins: Option<Vec<AccessControlAllowOrigin>>,
+}
+
+impl Domains {
+	fn new(hosts: Option<Vec<String>>, origins: Option<Vec<String>>) -> Self {
+		Domains {
+			hosts: hosts.map(|hosts| hosts.into_iter().map(Into::into).collect()),
+			origins: origins.map(|origins| origins.into_iter().map(Into::into).collect()),
+		}
+	}
+}
+
+impl AllowedDomains {
+	/// Accept requests from given hosts and origins.
+	pub fn new(hosts: Option<Vec<String>>, origins: Option<Vec<String>>) -> Self {
+		AllowedDomains {
+			domains: Arc::new(RwLock::new(Domains::new(hosts, origins))),
+		}
+	}
+
+	/// Replace the accepted hosts and origins.
+	pub fn set(&self, hosts: Option<Vec<String>>, origins: Option<Vec<String>>) {
+		*self.domains.write() = Domains::new(hosts, origins);
+	}
+
+	/// Wrap a WebSockets request middleware, so that it's only called for requests from accepted domains.
+	pub fn ws_middleware<T: ws::RequestMiddleware>(&self, middleware: T) -> WsDomainsMiddleware<T> {
+		WsDomainsMiddleware {
+			domains: self.clone(),
+			middleware,
+		}
+	}
+
+	fn validate(&self, host: Option<&str>, origin: Option<&str>) -> Result<(), Rejection> {
+		let domains = self.domains.read();
+		if !hosts::is_host_valid(host, &domains.hosts) {
+			return Err(Rejection::Host);
+		}
+		match cors::get_cors_allow_origin(origin, host, &domains.origins) {
+			AllowCors::Invalid => Err(Rejection::Origin),
+			AllowCors::NotRequired | AllowCors::Ok(_) => Ok(()),
+		}
+	}
+}
+
+enum Rejection {
+	Host,
+	Origin,
+}
+
+impl http::RequestMiddleware for AllowedDomains {
+	fn on_request(&self, request: hyper::Request<hyper::Body>) -> http::RequestMiddlewareAction {
+		let validation = {
+			let header = |name: &str| request.headers().get(name).and_then(|val| val.to_str().ok());
+			self.validate(header("host"), header("origin"))
+		};
+
+		match validation {
+			Ok(()) => request.into(),
+			Err(Rejection::Host) => http::Response::host_not_allowed().into(),
+			Err(Rejection::Origin) => http::Response::invalid_allow_origin().into(),
+		}
+	}
+}
+
+/// WebSockets request middleware rejecting requests from hosts and origins which aren't accepted.
+pub struct WsDomainsMiddleware<T> {
+	domains: AllowedDomains,
+	middleware: T,
+}
+
+impl<T: ws::RequestMiddleware> ws::RequestMiddleware for WsDomainsMiddleware<T> {
+	fn process(&self, req: &ws::ws::Request) -> ws::MiddlewareAction {
+		let header = |name: &str| req.header(name).and_then(|val| str::from_utf8(val).ok());
+		let reason: &[u8] = match self.domains.validate(header("host"), header("origin")) {
+			Ok(()) => return self.middleware.process(req),
+			Err(Rejection::Host) => b"Provided Host header is not whitelisted.\n",
+			Err(Rejection::Origin) => b"Origin of the request is not whitelisted.\n",
+		};
+		Some(ws::ws::Response::new(403, "Forbidden", reason.to_vec())).into()
+	}
+}
@@ -59,6 +59,7 @@ extern crate jsonrpc_derive;
 extern crate jsonrpc_http_server as http;
 extern crate jsonrpc_ipc_server as ipc;
 extern crate jsonrpc_pubsub;
+extern crate jsonrpc_server_utils as server_utils;
 
 extern crate common_types as types;
 extern crate ethash;
@@ -89,7 +90,6 @@ extern crate vm;
 #[cfg(any(test, feature = "ethcore-accounts"))]
 extern crate ethcore_accounts as accounts;
 
-#[cfg(any(test, feature = "ethcore-accounts"))]
 extern crate tiny_keccak;
 
 #[macro_use]
@@ -116,9 +116,13 @@ extern crate fake_fetch;
 #[cfg(test)]
 extern crate ethcore_io as io;
 
+#[cfg(test)]
+extern crate kvdb_memorydb;
+
 pub extern crate jsonrpc_ws_server as ws;
 
 mod authcodes;
+mod domains;
 mod http_common;
 pub mod v1;
 
@@ -138,6 +142,7 @@ pub use v1::block_import::{is_major_importing_or_waiting};
 pub use v1::PubSubSyncStatus;
 pub use v1::extractors::{RpcExtractor, WsExtractor, WsStats, WsDispatcher};
 pub use authcodes::{AuthCodes, TimeProvider};
+pub use domains::{AllowedDomains, WsDomainsMiddleware};
 pub use http_common::HttpMetaExtractor;
 
 use std::net::SocketAddr;
@@ -198,6 +203,7 @@ pub fn start_http_with_middleware<M, S, H, T, R>(
 		.threads(threads)
 		.cors(cors_domains)
 		.allowed_hosts(allowed_hosts)
+		.health_api(("/api/health", "parity_nodeStatus"))
 		.cors_allow_headers(AccessControlAllowHeaders::Any)
 		.max_request_body_size(max_payload * 1024 * 1024)
 		.request_middleware(middleware)
@@ -17,7 +17,7 @@
 use jsonrpc_core::MetaIoHandler;
 use http::{self, hyper};
 
-use {HttpServer};
+use {AllowedDomains, HttpServer};
 use tests::helpers::Server;
 use tests::http_client;
 use v1::{extractors, Metadata};
@@ -44,6 +44,22 @@ fn serve(handler: Option<MetaIoHandler<Metadata>>) -> Server<HttpServer> {
 	).unwrap())
 }
 
+fn serve_with_domains(domains: AllowedDomains) -> Server<HttpServer> {
+	let address = "127.0.0.1:0".parse().unwrap();
+
+	Server::new(|_remote| ::start_http_with_middleware(
+		&address,
+		http::DomainsValidation::Disabled,
+		http::DomainsValidation::Disabled,
+		MetaIoHandler::<Metadata>::default(),
+		extractors::RpcExtractor,
+		domains,
+		1,
+		5,
+		false,
+	).unwrap())
+}
+
 /// Test a single request to running server
 fn request(server: Server<HttpServer>, request: &str) -> http_client::Response {
 	http_client::request(server.server.address(), request)
@@ -52,7 +68,9 @@ fn request(server: Server<HttpServer>, request: &str) -> http_client::Response {
 #[cfg(test)]
 mod tests {
 	use jsonrpc_core::{MetaIoHandler, Value};
+	use tests::http_client;
 	use v1::Metadata;
+	use AllowedDomains;
 	use super::{request, Server};
 
 	fn serve() -> (Server<::HttpServer>, ::std::net::SocketAddr) {
@@ -143,4 +161,37 @@ mod tests {
 		assert!(res.headers.contains(&expected), "Headers missing in {:?}", res.headers);
 	}
 
+	#[test]
+	fn should_validate_replaced_hosts_and_origins() {
+		// given
+		let domains = AllowedDomains::new(Some(vec!["parity.io".into()]), Some(vec![]));
+		let server = super::serve_with_domains(domains.clone());
+		let address = server.server.address().to_owned();
+		let post = |host: &str, origin: &str| {
+			let req = r#"{"method":"hello","params":[],"jsonrpc":"2.0","id":1}"#;
+			http_client::request(&address,
+				&format!("\
+					POST / HTTP/1.1\r\n\
+					Host: {}\r\n\
+					Origin: {}\r\n\
+					Content-Type: application/json\r\n\
+					Content-Length: {}\r\n\
+					Connection: close\r\n\
+					\r\n\
+					{}
+				", host, origin, req.len(), req)
+			)
+		};
+
+		// when
+		let before = (post("parity.io", "http://parity.io"), post("example.com", "http://example.com"));
+		domains.set(Some(vec!["example.com".into()]), Some(vec!["http://parity.io".into()]));
+		let after = (post("parity.io", "http://parity.io"), post("example.com", "http://parity.io"));
+
+		// then
+		before.0.assert_status("HTTP/1.1 200 OK");
+		before.1.assert_status("HTTP/1.1 403 Forbidden");
+		after.0.assert_status("HTTP/1.1 403 Forbidden");
+		after.1.assert_status("HTTP/1.1 200 OK");
+	}
 }
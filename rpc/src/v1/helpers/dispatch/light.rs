@@ -20,7 +20,7 @@ use ethereum_types::{H256, Address, U256};
 use light::TransactionQueue as LightTransactionQueue;
 use light::cache::Cache as LightDataCache;
 use light::client::LightChainClient;
-use light::on_demand::{request, OnDemandRequester};
+use light::on_demand::{request, gas_price, OnDemandRequester};
 use parking_lot::{Mutex, RwLock};
 use stats::Corpus;
 use sync::{LightSyncProvider, LightNetworkDispatcher, ManageNetwork};
@@ -173,18 +173,16 @@ where
 		};
 
 		// fast path for known gas price.
-		let gas_price_percentile = self.gas_price_percentile;
 		let gas_price = match request_gas_price {
 			Some(gas_price) => Either::A(future::ok(with_gas_price(gas_price))),
-			None => Either::B(fetch_gas_price_corpus(
+			None => Either::B(fetch_gas_price(
 				self.sync.clone(),
 				self.client.clone(),
 				self.on_demand.clone(),
-				self.cache.clone()
-			).and_then(move |corp| match corp.percentile(gas_price_percentile) {
-				Some(percentile) => Ok(*percentile),
-				None => Ok(DEFAULT_GAS_PRICE), // fall back to default on error.
-			}).map(with_gas_price))
+				self.cache.clone(),
+				self.gas_price_percentile,
+				DEFAULT_GAS_PRICE,
+			).map(with_gas_price))
 		};
 
 		let future_account = self.account(from);
@@ -256,40 +254,20 @@ where
 	S: LightSyncProvider + LightNetworkDispatcher + ManageNetwork + 'static,
 	OD: OnDemandRequester + 'static
 {
-	const GAS_PRICE_SAMPLE_SIZE: usize = 100;
-
-	if let Some(cached) = { cache.lock().gas_price_corpus() } {
+	let best_block = client.best_block_header().hash();
+	if let Some(cached) = { cache.lock().gas_price_corpus(&best_block) } {
 		return Box::new(future::ok(cached))
 	}
 
-	let cache = cache.clone();
 	let eventual_corpus = sync.with_context(|ctx| {
 		// get some recent headers with gas used,
 		// and request each of the blocks from the network.
-		let block_requests = client.ancestry_iter(BlockId::Latest)
+		let headers = client.ancestry_iter(BlockId::Latest)
 			.filter(|hdr| hdr.gas_used() != U256::default())
-			.take(GAS_PRICE_SAMPLE_SIZE)
-			.map(|hdr| request::Body(hdr.into()))
+			.take(gas_price::GAS_PRICE_SAMPLE_SIZE)
 			.collect::<Vec<_>>();
 
-		// when the blocks come in, collect gas prices into a vector
-		on_demand.request(ctx, block_requests)
-			.expect("no back-references; therefore all back-references are valid; qed")
-			.map(|bodies| {
-				bodies.into_iter().fold(Vec::new(), |mut v, block| {
-					for t in block.transaction_views().iter() {
-						v.push(t.gas_price())
-					}
-					v
-				})
-			})
-			.map(move |prices| {
-				// produce a corpus from the vector and cache it.
-				// It's later used to get a percentile for default gas price.
-				let corpus: ::stats::Corpus<_> = prices.into();
-				cache.lock().set_gas_price_corpus(corpus.clone());
-				corpus
-			})
+		gas_price::fetch_corpus(ctx, &*on_demand, cache, best_block, headers)
 	});
 
 	match eventual_corpus {
@@ -297,3 +275,26 @@ where
 		None => Box::new(future::err(errors::network_disabled())),
 	}
 }
+
+/// Get the `percentile` of recent gas prices.
+/// Falls back to `default` if the gas prices couldn't be fetched or there were no recent transactions.
+pub fn fetch_gas_price<S, OD>(
+	sync: Arc<S>,
+	client: Arc<LightChainClient>,
+	on_demand: Arc<OD>,
+	cache: Arc<Mutex<LightDataCache>>,
+	percentile: usize,
+	default: U256,
+) -> BoxFuture<U256>
+where
+	S: LightSyncProvider + LightNetworkDispatcher + ManageNetwork + 'static,
+	OD: OnDemandRequester + 'static
+{
+	Box::new(fetch_gas_price_corpus(sync, client, on_demand, cache).then(move |corpus| match corpus {
+		Ok(corpus) => Ok(corpus.percentile(percentile).cloned().unwrap_or(default)),
+		Err(e) => {
+			warn!(target: "light_fetch", "Unable to fetch recent gas prices, using the default: {:?}", e);
+			Ok(default)
+		},
+	}))
+}
@@ -16,7 +16,7 @@
 
 use std::sync::Arc;
 
-use accounts::AccountProvider;
+use accounts::{AccountProvider, SignError};
 use bytes::Bytes;
 use crypto::DEFAULT_MAC;
 use ethereum_types::{H256, U256, Address};
@@ -55,8 +55,7 @@ impl super::Accounts for Signer {
 			return hardware_signature(&*self.accounts, filled.from, t, chain_id).map(WithToken::No)
 		}
 
-		let hash = t.hash(chain_id);
-		let signature = signature(&*self.accounts, filled.from, hash, password)?;
+		let signature = transaction_signature(&*self.accounts, filled.from, &t, chain_id, password)?;
 
 		Ok(signature.map(|sig| {
 			SignedTransaction::new(t.with_signature(sig, chain_id))
@@ -134,6 +133,18 @@ fn signature(accounts: &AccountProvider, address: Address, hash: H256, password:
 	})
 }
 
+fn transaction_signature(accounts: &AccountProvider, address: Address, t: &Transaction, chain_id: Option<u64>, password: SignWith) -> Result<WithToken<Signature>> {
+	match password.clone() {
+		SignWith::Nothing => accounts.sign_transaction(address, None, t, chain_id).map(WithToken::No),
+		SignWith::Password(pass) => accounts.sign_transaction(address, Some(pass), t, chain_id).map(WithToken::No),
+		SignWith::Token(token) => accounts.sign_transaction_with_token(address, token, t, chain_id).map(Into::into),
+	}.map_err(|e| match (e, password) {
+		(e @ SignError::PolicyViolation(_), _) => errors::policy_violation(e),
+		(e, SignWith::Nothing) => errors::signing(e),
+		(e, _) => errors::password(e),
+	})
+}
+
 // obtain a hardware signature from the given account.
 fn hardware_signature(accounts: &AccountProvider, address: Address, t: Transaction, chain_id: Option<u64>)
 	-> Result<SignedTransaction>
@@ -143,9 +154,12 @@ fn hardware_signature(accounts: &AccountProvider, address: Address, t: Transacti
 	let mut stream = rlp::RlpStream::new();
 	t.rlp_append_unsigned_transaction(&mut stream, chain_id);
 	let signature = accounts.sign_transaction_with_hardware(&address, &t, chain_id, &stream.as_raw())
-		.map_err(|e| {
-			debug!(target: "miner", "Error signing transaction with hardware wallet: {}", e);
-			errors::account("Error signing transaction with hardware wallet", e)
+		.map_err(|e| match e {
+			SignError::PolicyViolation(_) => errors::policy_violation(e),
+			e => {
+				debug!(target: "miner", "Error signing transaction with hardware wallet: {}", e);
+				errors::account("Error signing transaction with hardware wallet", e)
+			},
 		})?;
 
 	SignedTransaction::new(t.with_signature(signature, chain_id))
@@ -49,11 +49,15 @@ mod codes {
 	pub const ACCOUNT_LOCKED: i64 = -32020;
 	#[cfg(any(test, feature = "accounts"))]
 	pub const PASSWORD_INVALID: i64 = -32021;
+	#[cfg(any(test, feature = "accounts"))]
+	pub const ACCOUNT_POLICY_VIOLATION: i64 = -32022;
 	pub const ACCOUNT_ERROR: i64 = -32023;
 	pub const PRIVATE_ERROR: i64 = -32024;
+	pub const SECRETSTORE_ERROR: i64 = -32025;
 	pub const REQUEST_REJECTED: i64 = -32040;
 	pub const REQUEST_REJECTED_LIMIT: i64 = -32041;
 	pub const REQUEST_NOT_FOUND: i64 = -32042;
+	pub const REQUEST_TIMED_OUT: i64 = -32043;
 	pub const ENCRYPTION_ERROR: i64 = -32055;
 	pub const ENCODING_ERROR: i64 = -32058;
 	pub const FETCH_ERROR: i64 = -32060;
@@ -62,6 +66,7 @@ mod codes {
 	pub const DEPRECATED: i64 = -32070;
 	pub const EXPERIMENTAL_RPC: i64 = -32071;
 	pub const CANNOT_RESTART: i64 = -32080;
+	pub const CANNOT_RELOAD_CONFIG: i64 = -32081;
 }
 
 pub fn unimplemented(details: Option<String>) -> Error {
@@ -104,6 +109,14 @@ pub fn request_rejected() -> Error {
 	}
 }
 
+pub fn request_timed_out() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_TIMED_OUT),
+		message: "Request has been rejected because confirmation timed out.".into(),
+		data: None,
+	}
+}
+
 pub fn request_rejected_limit() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
@@ -120,6 +133,15 @@ pub fn request_rejected_param_limit(limit: u64, items_desc: &str) -> Error {
 	}
 }
 
+pub fn too_many_candidate_blocks(limit: usize, candidates: usize) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
+		message: format!("Logs may be contained in {} blocks, which exceeds the limit of {} blocks per query. \
+			Narrow down the block range or the filter.", candidates, limit),
+		data: None,
+	}
+}
+
 pub fn account<T: fmt::Debug>(error: &str, details: T) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::ACCOUNT_ERROR),
@@ -136,6 +158,14 @@ pub fn cannot_restart() -> Error {
 	}
 }
 
+pub fn cannot_reload_config(error: String) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::CANNOT_RELOAD_CONFIG),
+		message: "Configuration could not be reloaded.".into(),
+		data: Some(Value::String(error)),
+	}
+}
+
 /// Internal error signifying a logic error in code.
 /// Should not be used when function can just fail
 /// because of invalid parameters or incomplete node state.
@@ -386,6 +416,15 @@ pub fn signing(error: ::accounts::SignError) -> Error {
 	}
 }
 
+#[cfg(any(test, feature = "accounts"))]
+pub fn policy_violation(error: ::accounts::SignError) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::ACCOUNT_POLICY_VIOLATION),
+		message: format!("Transaction refused by the account policy: {}", error),
+		data: Some(Value::String("Refused transactions are listed by parity_accountPolicyViolations".into())),
+	}
+}
+
 #[cfg(any(test, feature = "accounts"))]
 pub fn password(error: ::accounts::SignError) -> Error {
 	Error {
@@ -411,6 +450,22 @@ pub fn private_message_block_id_not_supported() -> Error {
 	}
 }
 
+pub fn secretstore_disabled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Secret Store key server is disabled. This API is not available.".into(),
+		data: None,
+	}
+}
+
+pub fn secretstore(error: String) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::SECRETSTORE_ERROR),
+		message: "Secret Store administrative call failed.".into(),
+		data: Some(Value::String(error)),
+	}
+}
+
 pub fn transaction_message(error: &TransactionError) -> String {
 	use self::TransactionError::*;
 
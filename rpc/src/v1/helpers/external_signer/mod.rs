@@ -18,6 +18,7 @@
 
 use std::sync::Arc;
 use std::ops::Deref;
+use std::time::Duration;
 
 mod oneshot;
 mod signing_queue;
@@ -35,10 +36,11 @@ pub struct SignerService {
 
 impl SignerService {
 	/// Creates new Signer Service given function to generate new tokens.
-	pub fn new<F>(new_token: F, is_enabled: bool) -> Self
+	/// Requests not confirmed within `confirmation_timeout` are rejected.
+	pub fn new<F>(new_token: F, is_enabled: bool, confirmation_timeout: Option<Duration>) -> Self
 		where F: Fn() -> Result<String, String> + Send + Sync + 'static {
 		SignerService {
-			queue: Arc::new(ConfirmationsQueue::default()),
+			queue: Arc::new(ConfirmationsQueue::with_timeout(confirmation_timeout)),
 			generate_new_token: Box::new(new_token),
 			is_enabled,
 		}
@@ -62,7 +64,13 @@ impl SignerService {
 	#[cfg(test)]
 	/// Creates new Signer Service for tests.
 	pub fn new_test(is_enabled: bool) -> Self {
-		SignerService::new(|| Ok("new_token".into()), is_enabled)
+		SignerService::new(|| Ok("new_token".into()), is_enabled, None)
+	}
+
+	#[cfg(test)]
+	/// Creates new Signer Service for tests with given confirmation timeout.
+	pub fn new_test_with_timeout(is_enabled: bool, confirmation_timeout: Duration) -> Self {
+		SignerService::new(|| Ok("new_token".into()), is_enabled, Some(confirmation_timeout))
 	}
 }
 
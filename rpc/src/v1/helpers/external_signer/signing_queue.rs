@@ -14,9 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp;
 use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
 
-use ethereum_types::U256;
+use ethereum_types::{Address, U256};
 use parking_lot::{Mutex, RwLock};
 use super::oneshot;
 use v1::helpers::errors;
@@ -39,6 +41,12 @@ pub enum QueueEvent {
 	RequestRejected(U256),
 	/// Request resolved.
 	RequestConfirmed(U256),
+	/// Request was not confirmed in time.
+	RequestExpired(U256),
+	/// Accounts got locked after being idle.
+	AccountsLocked(Vec<Address>),
+	/// Key files were added, removed or modified on disk.
+	AccountsChanged,
 }
 
 /// Defines possible errors when inserting to queue
@@ -67,11 +75,21 @@ pub trait SigningQueue: Send + Sync {
 	fn request_untouched(&self, sender: ConfirmationSender);
 
 	/// Returns and removes a request if it is contained in the queue.
+	/// The request is reported by `requests_in_progress` until it's confirmed, rejected
+	/// or put back with `request_untouched`.
 	fn take(&self, id: &U256) -> Option<ConfirmationSender>;
 
 	/// Return copy of all the requests in the queue.
 	fn requests(&self) -> Vec<ConfirmationRequest>;
 
+	/// Return copy of all the requests in the queue to be displayed to the user.
+	/// Displayed requests get their confirmation deadline extended once.
+	fn peek(&self) -> Vec<ConfirmationRequest>;
+
+	/// Return copy of the requests taken from the queue that are still being signed,
+	/// e.g. waiting for the user to confirm them on a hardware wallet.
+	fn requests_in_progress(&self) -> Vec<ConfirmationRequest>;
+
 	/// Returns number of requests awaiting confirmation.
 	fn len(&self) -> usize;
 
@@ -85,6 +103,8 @@ pub struct ConfirmationSender {
 	pub request: ConfirmationRequest,
 
 	sender: oneshot::Sender<ConfirmationResult>,
+	deadline: Option<Instant>,
+	extended: bool,
 }
 
 /// Receiving end of the Confirmation channel; can be used as a `Future` to await for `ConfirmationRequest`
@@ -96,10 +116,50 @@ pub type ConfirmationReceiver =  oneshot::Receiver<ConfirmationResult>;
 pub struct ConfirmationsQueue {
 	id: Mutex<U256>,
 	queue: RwLock<BTreeMap<U256, ConfirmationSender>>,
+	processing: RwLock<BTreeMap<U256, ConfirmationRequest>>,
 	on_event: RwLock<Vec<Box<Fn(QueueEvent) -> () + Send + Sync>>>,
+	timeout: Option<Duration>,
 }
 
 impl ConfirmationsQueue {
+	/// Creates a queue rejecting requests that are not confirmed within `timeout`.
+	pub fn with_timeout(timeout: Option<Duration>) -> Self {
+		ConfirmationsQueue {
+			timeout,
+			..Default::default()
+		}
+	}
+
+	/// Returns the confirmation timeout of this queue.
+	pub fn timeout(&self) -> Option<Duration> {
+		self.timeout
+	}
+
+	/// Rejects all requests that haven't been confirmed before their deadline.
+	/// Returns ids of the expired requests.
+	pub fn expire_requests(&self) -> Vec<U256> {
+		self.expire_requests_at(Instant::now())
+	}
+
+	fn expire_requests_at(&self, now: Instant) -> Vec<U256> {
+		let expired = {
+			let mut queue = self.queue.write();
+			let ids = queue.iter()
+				.filter(|&(_, sender)| sender.deadline.map_or(false, |deadline| deadline <= now))
+				.map(|(id, _)| *id)
+				.collect::<Vec<_>>();
+			ids.into_iter().filter_map(|id| queue.remove(&id)).collect::<Vec<_>>()
+		};
+
+		expired.into_iter().map(|sender| {
+			let id = sender.request.id;
+			debug!(target: "own_tx", "Signer: Request expired ({:?}).", id);
+			self.notify_message(QueueEvent::RequestExpired(id));
+			sender.sender.send(Err(errors::request_timed_out()));
+			id
+		}).collect()
+	}
+
 	/// Adds a queue listener. For each event, `listener` callback will be invoked.
 	pub fn on_event<F: Fn(QueueEvent) -> () + Send + Sync + 'static>(&self, listener: F) {
 		self.on_event.write().push(Box::new(listener));
@@ -112,8 +172,20 @@ impl ConfirmationsQueue {
 		self.on_event.write().clear();
 	}
 
+	/// Notifies listeners that given accounts were locked automatically.
+	pub fn accounts_locked(&self, accounts: Vec<Address>) {
+		self.notify_message(QueueEvent::AccountsLocked(accounts));
+	}
+
+	/// Notifies listeners that accounts were reloaded from disk.
+	pub fn accounts_changed(&self) {
+		self.notify_message(QueueEvent::AccountsChanged);
+	}
+
 	/// Notifies `ConfirmationReceiver` holder about the result given a request.
 	fn notify_result(&self, sender: ConfirmationSender, result: Option<ConfirmationResult>) -> Option<ConfirmationRequest> {
+		self.processing.write().remove(&sender.request.id);
+
 		// notify receiver about the event
 		self.notify_message(result.clone().map_or_else(
 			|| QueueEvent::RequestRejected(sender.request.id),
@@ -168,6 +240,8 @@ impl SigningQueue for ConfirmationsQueue {
 					payload: request,
 					origin,
 				},
+				deadline: self.timeout.map(|timeout| Instant::now() + timeout),
+				extended: false,
 			});
 			(id, receiver)
 		};
@@ -177,7 +251,11 @@ impl SigningQueue for ConfirmationsQueue {
 	}
 
 	fn take(&self, id: &U256) -> Option<ConfirmationSender> {
-		self.queue.write().remove(id)
+		let sender = self.queue.write().remove(id);
+		if let Some(ref sender) = sender {
+			self.processing.write().insert(*id, sender.request.clone());
+		}
+		sender
 	}
 
 	fn request_rejected(&self, sender: ConfirmationSender) -> Option<ConfirmationRequest> {
@@ -191,6 +269,7 @@ impl SigningQueue for ConfirmationsQueue {
 	}
 
 	fn request_untouched(&self, sender: ConfirmationSender) {
+		self.processing.write().remove(&sender.request.id);
 		self.queue.write().insert(sender.request.id, sender);
 	}
 
@@ -199,6 +278,22 @@ impl SigningQueue for ConfirmationsQueue {
 		queue.values().map(|sender| sender.request.clone()).collect()
 	}
 
+	fn peek(&self) -> Vec<ConfirmationRequest> {
+		let mut queue = self.queue.write();
+		if let Some(timeout) = self.timeout {
+			let extended_deadline = Instant::now() + timeout;
+			for sender in queue.values_mut().filter(|sender| !sender.extended) {
+				sender.deadline = sender.deadline.map(|deadline| cmp::max(deadline, extended_deadline));
+				sender.extended = true;
+			}
+		}
+		queue.values().map(|sender| sender.request.clone()).collect()
+	}
+
+	fn requests_in_progress(&self) -> Vec<ConfirmationRequest> {
+		self.processing.read().values().cloned().collect()
+	}
+
 	fn len(&self) -> usize {
 		let queue = self.queue.read();
 		queue.len()
@@ -213,11 +308,12 @@ impl SigningQueue for ConfirmationsQueue {
 #[cfg(test)]
 mod test {
 	use std::sync::Arc;
+	use std::time::{Duration, Instant};
 	use ethereum_types::{U256, Address};
 	use parking_lot::Mutex;
 	use jsonrpc_core::futures::Future;
 	use v1::helpers::external_signer::{SigningQueue, ConfirmationsQueue, QueueEvent};
-	use v1::helpers::{FilledTransactionRequest, ConfirmationPayload};
+	use v1::helpers::{errors, FilledTransactionRequest, ConfirmationPayload};
 	use v1::types::ConfirmationResponse;
 
 	fn request() -> ConfirmationPayload {
@@ -288,5 +384,89 @@ mod test {
 		assert_eq!(el.id, U256::from(1));
 		assert_eq!(el.payload, request);
 	}
+
+	#[test]
+	fn should_reject_expired_requests() {
+		// given
+		let received = Arc::new(Mutex::new(vec![]));
+		let queue = ConfirmationsQueue::with_timeout(Some(Duration::from_secs(10)));
+		let r = received.clone();
+		queue.on_event(move |notification| {
+			r.lock().push(notification);
+		});
+		let (id, future) = queue.add_request(request(), Default::default()).unwrap();
+
+		// when
+		assert!(queue.expire_requests_at(Instant::now() + Duration::from_secs(5)).is_empty());
+		let expired = queue.expire_requests_at(Instant::now() + Duration::from_secs(11));
+
+		// then
+		assert_eq!(expired, vec![id]);
+		assert!(queue.is_empty());
+		assert_eq!(future.wait(), Err(errors::request_timed_out()));
+		assert_eq!(*received.lock(), vec![QueueEvent::NewRequest(id), QueueEvent::RequestExpired(id)]);
+	}
+
+	#[test]
+	fn should_extend_deadline_of_displayed_requests_once() {
+		// given
+		let queue = ConfirmationsQueue::with_timeout(Some(Duration::from_secs(10)));
+		let (id, _future) = queue.add_request(request(), Default::default()).unwrap();
+
+		// when
+		assert_eq!(queue.peek().len(), 1);
+		let deadline = queue.queue.read()[&id].deadline.unwrap();
+		queue.peek();
+
+		// then
+		assert_eq!(queue.queue.read()[&id].deadline, Some(deadline));
+		assert!(queue.expire_requests_at(deadline - Duration::from_secs(1)).is_empty());
+		assert_eq!(queue.expire_requests_at(deadline), vec![id]);
+	}
+
+	#[test]
+	fn should_never_expire_requests_without_timeout() {
+		// given
+		let queue = ConfirmationsQueue::default();
+		let _future = queue.add_request(request(), Default::default()).unwrap();
+
+		// when
+		let expired = queue.expire_requests_at(Instant::now() + Duration::from_secs(3600 * 24 * 365));
+
+		// then
+		assert!(expired.is_empty());
+		assert_eq!(queue.len(), 1);
+	}
+
+	#[test]
+	fn should_report_requests_in_progress_until_resolved() {
+		// given
+		let queue = ConfirmationsQueue::with_timeout(Some(Duration::from_secs(10)));
+		let (id, _future) = queue.add_request(request(), Default::default()).unwrap();
+
+		// when
+		let sender = queue.take(&id).unwrap();
+
+		// then
+		assert!(queue.requests().is_empty());
+		assert_eq!(queue.requests_in_progress().len(), 1);
+		assert_eq!(queue.requests_in_progress()[0].payload, request());
+		assert!(queue.expire_requests_at(Instant::now() + Duration::from_secs(11)).is_empty());
+
+		// when
+		queue.request_untouched(sender);
+
+		// then
+		assert!(queue.requests_in_progress().is_empty());
+		assert_eq!(queue.requests().len(), 1);
+
+		// when
+		let sender = queue.take(&id).unwrap();
+		queue.request_rejected(sender);
+
+		// then
+		assert!(queue.requests_in_progress().is_empty());
+		assert!(queue.is_empty());
+	}
 }
 
@@ -32,14 +32,14 @@ use jsonrpc_core::{Result, Error};
 use jsonrpc_core::futures::{future, Future};
 use jsonrpc_core::futures::future::Either;
 
-use light::cache::Cache;
+use light::cache::{Cache, ExecutionKey};
 use light::client::LightChainClient;
 use light::{cht, MAX_HEADERS_PER_REQUEST};
 use light::on_demand::{
 	request, OnDemandRequester, HeaderRef, Request as OnDemandRequest,
 	Response as OnDemandResponse, ExecutionResult,
 };
-use light::on_demand::error::Error as OnDemandError;
+use light::on_demand::logs::{self, MatchedLog};
 use light::request::Field;
 use light::TransactionQueue;
 
@@ -141,6 +141,23 @@ fn extract_header(res: &[OnDemandResponse], header: HeaderRef) -> Option<encoded
 	}
 }
 
+// convert a log found in receipts. there is no way to easily retrieve transaction hash here.
+fn log_without_tx_hash(log: MatchedLog) -> Log {
+	Log {
+		address: log.entry.address,
+		topics: log.entry.topics.into_iter().map(Into::into).collect(),
+		data: log.entry.data.into(),
+		block_hash: Some(log.block_hash),
+		block_number: Some(log.block_number.into()),
+		transaction_hash: None,
+		transaction_index: Some(log.transaction_index.into()),
+		log_index: Some(log.log_index.into()),
+		transaction_log_index: Some(log.transaction_log_index.into()),
+		log_type: "mined".into(),
+		removed: false,
+	}
+}
+
 impl<S, OD> LightFetch<S, OD>
 where
 	S: LightSyncProvider + LightNetworkDispatcher + ManageNetwork + 'static,
@@ -155,11 +172,12 @@ where
 
 		match id {
 			BlockId::Number(n) => {
-				let cht_root = cht::block_to_cht_number(n).and_then(|cn| self.client.cht_root(cn as usize));
+				let cht_size = self.client.cht_size();
+				let cht_root = cht::block_to_cht_number(n, cht_size).and_then(|cn| self.client.cht_root(cn as usize));
 				match cht_root {
 					None => Err(errors::unknown_block()),
 					Some(root) => {
-						let req = request::HeaderProof::new(n, root)
+						let req = request::HeaderProof::new(n, root, cht_size)
 							.expect("only fails for 0; client always stores genesis; client already queried; qed");
 
 						let idx = reqs.len();
@@ -249,11 +267,6 @@ where
 		num: Option<BlockNumber>,
 		txq: Arc<RwLock<TransactionQueue>>
 	) -> impl Future<Item = ExecutionResult, Error = Error> + Send {
-
-		// (21000 G_transaction + 32000 G_create + some marginal to allow a few operations)
-		const START_GAS: u64 = 60_000;
-
-		let (sync, on_demand, client) = (self.sync.clone(), self.on_demand.clone(), self.client.clone());
 		let req: CallRequestHelper = req.into();
 
 		// Note: Here we treat `Pending` as `Latest`.
@@ -269,10 +282,43 @@ where
 			}
 		};
 
+		// results are cached by the hash of the block the call resolves to,
+		// so calls at `latest` miss once the head advances.
+		let key = ExecutionKey {
+			block_hash: H256::default(),
+			from: req.from.unwrap_or_default(),
+			to: req.to,
+			value: req.value.unwrap_or_default(),
+			data: req.data.clone().unwrap_or_default(),
+			gas: req.gas,
+			gas_price: req.gas_price,
+			nonce: req.nonce,
+		};
+
+		let (fetch, cache) = (self.clone(), self.cache.clone());
+		self.header(id).and_then(move |hdr| {
+			let key = ExecutionKey { block_hash: hdr.hash(), ..key };
+			cached_execution(&cache, key, move || fetch.read_only_execution_at(req, hdr, txq))
+		})
+	}
+
+	// execute a read-only transaction at the given block.
+	fn read_only_execution_at(
+		&self,
+		req: CallRequestHelper,
+		hdr: encoded::Header,
+		txq: Arc<RwLock<TransactionQueue>>
+	) -> impl Future<Item = ExecutionResult, Error = Error> + Send {
+
+		// (21000 G_transaction + 32000 G_create + some marginal to allow a few operations)
+		const START_GAS: u64 = 60_000;
+
+		let (sync, on_demand, client) = (self.sync.clone(), self.on_demand.clone(), self.client.clone());
+
 		let from = req.from.unwrap_or_default();
 		let nonce_fut = match req.nonce {
 			Some(nonce) => Either::A(future::ok(Some(nonce))),
-			None => Either::B(self.account(from, id, txq).map(|acc| acc.map(|a| a.nonce))),
+			None => Either::B(self.account(from, BlockId::Hash(hdr.hash()), txq).map(|acc| acc.map(|a| a.nonce))),
 		};
 
 		let gas_price_fut = match req.gas_price {
@@ -280,9 +326,6 @@ where
 			None => Either::B(self.gas_price()),
 		};
 
-		// if nonce resolves, this should too since it'll be in the LRU-cache.
-		let header_fut = self.header(id);
-
 		// fetch missing transaction fields from the network.
 		Box::new(nonce_fut.join(gas_price_fut).and_then(move |(nonce, gas_price)| {
 			future::done(
@@ -295,7 +338,7 @@ where
 					data: req.data.unwrap_or_default(),
 				}))
 			)
-		}).join(header_fut).and_then(move |((gas_known, tx), hdr)| {
+		}).and_then(move |(gas_known, tx)| {
 			// then request proved execution.
 			// TODO: get last-hashes from network.
 			let hash = hdr.hash();
@@ -317,20 +360,17 @@ where
 	}
 
 	/// Helper to fetch the corpus gas price from 1) the cache 2) the network then it tries to estimate the percentile
-	/// using `gas_price_percentile` if the estimated percentile is zero the `DEFAULT_GAS_PRICE` is returned
+	/// using `gas_price_percentile`. If there are no recent transactions or the corpus can't be fetched
+	/// the `DEFAULT_GAS_PRICE` is returned
 	pub fn gas_price(&self) -> impl Future<Item = U256, Error = Error> + Send {
-		let gas_price_percentile = self.gas_price_percentile;
-
-		dispatch::light::fetch_gas_price_corpus(
+		dispatch::light::fetch_gas_price(
 			self.sync.clone(),
 			self.client.clone(),
 			self.on_demand.clone(),
 			self.cache.clone(),
+			self.gas_price_percentile,
+			DEFAULT_GAS_PRICE.into(),
 		)
-		.map(move |corp| {
-			corp.percentile(gas_price_percentile)
-				.map_or_else(|| DEFAULT_GAS_PRICE.into(), |percentile| *percentile)
-		})
 	}
 
 	/// Get a block itself. Fails on unknown block ID.
@@ -365,63 +405,61 @@ where
 		}))
 	}
 
+	/// Get transaction logs, without hashes of the transactions.
 	pub fn logs_no_tx_hash(&self, filter: EthcoreFilter) -> impl Future<Item = Vec<Log>, Error = Error> + Send {
+		self.matching_logs(filter)
+			.map(|matches| matches.into_iter().map(log_without_tx_hash).collect())
+	}
+
+	/// Get transaction logs
+	pub fn logs(&self, filter: EthcoreFilter) -> impl Future<Item = Vec<Log>, Error = Error> + Send {
 		use jsonrpc_core::futures::stream::{self, Stream};
+		let fetcher_block = self.clone();
+		self.matching_logs(filter)
+			// retrieve transaction hash.
+			.and_then(move |matches| {
+				let mut blocks = BTreeMap::new();
+				for log in matches.iter() {
+					blocks.entry(log.block_hash).or_insert_with(|| {
+						fetcher_block.block(BlockId::Hash(log.block_hash))
+					});
+				}
+				// future get blocks (unordered it)
+				stream::futures_unordered(blocks.into_iter().map(|(_, v)| v)).collect().map(move |blocks| {
+					let transactions_per_block: BTreeMap<_, _> = blocks.iter()
+						.map(|block| (block.hash(), block.transactions())).collect();
+					matches.into_iter().map(|log| {
+						// transaction index is from an enumerate call over receipts so not need to check value
+						let tx_hash = transactions_per_block.get(&log.block_hash)
+							.and_then(|txs| txs.get(log.transaction_index))
+							.map(types::transaction::UnverifiedTransaction::hash);
+						let mut log = log_without_tx_hash(log);
+						log.transaction_hash = tx_hash;
+						log
+					}).collect()
+				})
+			})
+	}
 
+	// Find logs matching the filter in receipts of blocks which may contain them.
+	fn matching_logs(&self, filter: EthcoreFilter) -> impl Future<Item = Vec<MatchedLog>, Error = Error> + Send {
 		const MAX_BLOCK_RANGE: u64 = 1000;
 
 		let fetcher = self.clone();
 		self.headers_range_by_block_id(filter.from_block, filter.to_block, MAX_BLOCK_RANGE)
-			.and_then(move |mut headers| {
-				if headers.is_empty() {
+			.and_then(move |headers| {
+				let candidates = logs::candidate_blocks(&filter, headers);
+				if candidates.is_empty() {
 					return Either::A(future::ok(Vec::new()));
 				}
+				if candidates.len() > logs::MAX_CANDIDATE_BLOCKS {
+					return Either::A(future::err(errors::too_many_candidate_blocks(logs::MAX_CANDIDATE_BLOCKS, candidates.len())));
+				}
 
 				let on_demand = &fetcher.on_demand;
-
 				let maybe_future = fetcher.sync.with_context(move |ctx| {
-					// find all headers which match the filter, and fetch the receipts for each one.
-					// match them with their numbers for easy sorting later.
-					let bit_combos = filter.bloom_possibilities();
-					let receipts_futures: Vec<_> = headers.drain(..)
-						.filter(|ref hdr| {
-							let hdr_bloom = hdr.log_bloom();
-							bit_combos.iter().any(|bloom| hdr_bloom.contains_bloom(bloom))
-						})
-						.map(|hdr| (hdr.number(), hdr.hash(), request::BlockReceipts(hdr.into())))
-						.map(|(num, hash, req)| on_demand.request(ctx, req).expect(NO_INVALID_BACK_REFS_PROOF).map(move |x| (num, hash, x)))
-						.collect();
-
-					// as the receipts come in, find logs within them which match the filter.
-					// insert them into a BTreeMap to maintain order by number and block index.
-					stream::futures_unordered(receipts_futures)
-						.fold(BTreeMap::new(), move |mut matches, (num, hash, receipts)| {
-							let mut block_index: usize = 0;
-							for (transaction_index, receipt) in receipts.into_iter().enumerate() {
-								for (transaction_log_index, log) in receipt.logs.into_iter().enumerate() {
-									if filter.matches(&log) {
-										matches.insert((num, block_index), Log {
-											address: log.address,
-											topics: log.topics.into_iter().map(Into::into).collect(),
-											data: log.data.into(),
-											block_hash: Some(hash),
-											block_number: Some(num.into()),
-											// No way to easily retrieve transaction hash, so let's just skip it.
-											transaction_hash: None,
-											transaction_index: Some(transaction_index.into()),
-											log_index: Some(block_index.into()),
-											transaction_log_index: Some(transaction_log_index.into()),
-											log_type: "mined".into(),
-											removed: false,
-										});
-									}
-									block_index += 1;
-								}
-							}
-							future::ok::<_, OnDemandError>(matches)
-						})
+					logs::fetch_matching_logs(ctx, &**on_demand, filter, candidates)
 						.map_err(errors::on_demand_error)
-						.map(|matches| matches.into_iter().map(|(_, v)| v).collect())
 				});
 
 				match maybe_future {
@@ -431,38 +469,6 @@ where
 			})
 	}
 
-	/// Get transaction logs
-	pub fn logs(&self, filter: EthcoreFilter) -> impl Future<Item = Vec<Log>, Error = Error> + Send {
-		use jsonrpc_core::futures::stream::{self, Stream};
-		let fetcher_block = self.clone();
-		self.logs_no_tx_hash(filter)
-			// retrieve transaction hash.
-			.and_then(move |mut result| {
-				let mut blocks = BTreeMap::new();
-				for log in result.iter() {
-						let block_hash = log.block_hash.as_ref().expect("Previously initialized with value; qed");
-						blocks.entry(*block_hash).or_insert_with(|| {
-							fetcher_block.block(BlockId::Hash(*block_hash))
-						});
-				}
-				// future get blocks (unordered it)
-				stream::futures_unordered(blocks.into_iter().map(|(_, v)| v)).collect().map(move |blocks| {
-					let transactions_per_block: BTreeMap<_, _> = blocks.iter()
-						.map(|block| (block.hash(), block.transactions())).collect();
-					for log in result.iter_mut() {
-						let log_index = log.transaction_index.expect("Previously initialized with value; qed");
-						let block_hash = log.block_hash.expect("Previously initialized with value; qed");
-						let tx_hash = transactions_per_block.get(&block_hash)
-							// transaction index is from an enumerate call in log common so not need to check value
-							.and_then(|txs| txs.get(log_index.as_usize()))
-							.map(types::transaction::UnverifiedTransaction::hash);
-						log.transaction_hash = tx_hash;
-					}
-					result
-				})
-			})
-	}
-
 	// Get a transaction by hash. also returns the index in the block.
 	// Only returns transactions in the canonical chain.
 	pub fn transaction_by_hash(&self, tx_hash: H256)
@@ -691,7 +697,8 @@ where
 			let start_hash = if let Some(hdr) = headers.last() {
 				hdr.parent_hash().into()
 			} else {
-				let cht_root = cht::block_to_cht_number(to_number)
+				let cht_size = fetcher.client.cht_size();
+				let cht_root = cht::block_to_cht_number(to_number, cht_size)
 					.and_then(|cht_num| fetcher.client.cht_root(cht_num as usize));
 
 				let cht_root = match cht_root {
@@ -699,7 +706,7 @@ where
 					None => return Either::A(future::err(errors::unknown_block())),
 				};
 
-				let header_proof = request::HeaderProof::new(to_number, cht_root)
+				let header_proof = request::HeaderProof::new(to_number, cht_root, cht_size)
 					.expect("HeaderProof::new is Some(_) if cht::block_to_cht_number() is Some(_); \
 							this would return above if block_to_cht_number returned None; qed");
 
@@ -728,6 +735,24 @@ where
 	}
 }
 
+// answer a read-only execution from the cache, or execute it and cache the result.
+fn cached_execution<F, Fut>(cache: &Arc<Mutex<Cache>>, key: ExecutionKey, execute: F) -> impl Future<Item = ExecutionResult, Error = Error> + Send
+where
+	F: FnOnce() -> Fut,
+	Fut: Future<Item = ExecutionResult, Error = Error> + Send + 'static,
+{
+	if let Some(result) = cache.lock().execution(&key) {
+		trace!(target: "light_fetch", "Execution at block {} found in cache", key.block_hash);
+		return Either::A(future::ok(result));
+	}
+
+	let cache = cache.clone();
+	Either::B(execute().map(move |result| {
+		cache.lock().insert_execution(key, result.clone());
+		result
+	}))
+}
+
 struct ExecuteParams<S, OD>
 where
 	S: LightSyncProvider + LightNetworkDispatcher + ManageNetwork + 'static,
@@ -828,3 +853,75 @@ where
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::cell::Cell;
+	use std::sync::Arc;
+	use std::time::Duration;
+
+	use ethcore::executed::Executed;
+	use jsonrpc_core::futures::{future, Future};
+	use light::cache::{Cache, CacheSizes, ExecutionKey};
+	use parking_lot::Mutex;
+
+	use super::cached_execution;
+
+	#[test]
+	fn identical_calls_are_executed_once_per_block() {
+		let sizes = CacheSizes { executions: 2, ..Default::default() };
+		let cache = Arc::new(Mutex::new(Cache::new(sizes, Duration::from_secs(60))));
+		let requests = Cell::new(0);
+		let call = |block_hash: u64| {
+			let key = ExecutionKey {
+				block_hash: block_hash.into(),
+				from: Default::default(),
+				to: Some(1.into()),
+				value: 0.into(),
+				data: vec![0x70, 0xa0, 0x82, 0x31],
+				gas: None,
+				gas_price: None,
+				nonce: None,
+			};
+
+			// the balance returned differs from block to block.
+			let executed = Executed {
+				exception: None,
+				gas: 60_000.into(),
+				gas_used: 21_000.into(),
+				refunded: 0.into(),
+				cumulative_gas_used: 21_000.into(),
+				logs: Vec::new(),
+				contracts_created: Vec::new(),
+				output: vec![block_hash as u8],
+				trace: Vec::new(),
+				vm_trace: None,
+				state_diff: None,
+			};
+
+			cached_execution(&cache, key, || {
+				requests.set(requests.get() + 1);
+				future::ok(Ok(executed))
+			}).wait().unwrap().unwrap().output
+		};
+
+		assert_eq!(call(1), vec![1]);
+		assert_eq!(requests.get(), 1);
+
+		// the second identical call issues no requests.
+		assert_eq!(call(1), vec![1]);
+		assert_eq!(requests.get(), 1);
+
+		// but a call resolving to a new head does.
+		assert_eq!(call(2), vec![2]);
+		assert_eq!(requests.get(), 2);
+
+		// the least recently used result is evicted once the cache is full.
+		assert_eq!(call(3), vec![3]);
+		assert_eq!(requests.get(), 3);
+		assert_eq!(call(2), vec![2]);
+		assert_eq!(requests.get(), 3);
+		assert_eq!(call(1), vec![1]);
+		assert_eq!(requests.get(), 4);
+	}
+}
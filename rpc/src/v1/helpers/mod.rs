@@ -31,6 +31,7 @@ pub mod light_fetch;
 pub mod nonce;
 #[cfg(any(test, feature = "accounts"))]
 pub mod secretstore;
+pub mod secretstore_admin;
 
 mod network_settings;
 mod poll_filter;
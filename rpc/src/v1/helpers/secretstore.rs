@@ -14,16 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
 use rand::{Rng, OsRng};
-use ethereum_types::{H256, H512};
 use ethkey::{self, Public, Secret, Random, Generator, math};
 use crypto;
 use bytes::Bytes;
 use jsonrpc_core::Error;
 use v1::helpers::errors;
 use v1::types::EncryptedDocumentKey;
-use tiny_keccak::Keccak;
 
 /// Initialization vector length.
 const INIT_VEC_LEN: usize = 16;
@@ -90,19 +87,6 @@ pub fn decrypt_document_with_shadow(decrypted_secret: Public, common_point: Publ
 	decrypt_document(key.to_vec(), encrypted_document)
 }
 
-/// Calculate Keccak(ordered servers set)
-pub fn ordered_servers_keccak(servers_set: BTreeSet<H512>) -> H256 {
-	let mut servers_set_keccak = Keccak::new_keccak256();
-	for server in servers_set {
-		servers_set_keccak.update(&server.0);
-	}
-
-	let mut servers_set_keccak_value = [0u8; 32];
-	servers_set_keccak.finalize(&mut servers_set_keccak_value);
-
-	servers_set_keccak_value.into()
-}
-
 fn into_document_key(key: Bytes) -> Result<Bytes, Error> {
 	// key is a previously distributely generated Public
 	if key.len() != 64 {
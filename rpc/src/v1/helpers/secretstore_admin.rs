@@ -0,0 +1,64 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Secret Store key server administration.
+
+use std::collections::BTreeSet;
+use ethereum_types::{H256, H512};
+use ethkey::Signature;
+use tiny_keccak::Keccak;
+
+/// Administrative session, which is active on the key server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyServerAdminSession {
+	/// Session id.
+	pub id: H256,
+	/// Session type.
+	pub session_type: String,
+	/// Node which has started the session.
+	pub master: H512,
+	/// Is session finished.
+	pub is_finished: bool,
+	/// Number of keys to process (servers set change sessions only, known on master node).
+	pub keys_total: Option<usize>,
+	/// Number of processed keys (servers set change sessions only, known on master node).
+	pub keys_processed: Option<usize>,
+}
+
+/// Administrative sessions of the Secret Store key server, running on this node.
+pub trait KeyServerAdmin: Send + Sync {
+	/// Start servers set change session. Returns id of the started session.
+	fn change_servers_set(&self, old_set_signature: Signature, new_set_signature: Signature, new_servers_set: BTreeSet<H512>) -> Result<H256, String>;
+
+	/// Get administrative sessions, which are currently active on this node.
+	fn sessions(&self) -> Result<Vec<KeyServerAdminSession>, String>;
+
+	/// Abort active administrative session. Signature is the signature of session id, made with administrator key.
+	fn abort_session(&self, session_id: H256, signature: Signature) -> Result<(), String>;
+}
+
+/// Calculate Keccak(ordered servers set)
+pub fn ordered_servers_keccak(servers_set: BTreeSet<H512>) -> H256 {
+	let mut servers_set_keccak = Keccak::new_keccak256();
+	for server in servers_set {
+		servers_set_keccak.update(&server.0);
+	}
+
+	let mut servers_set_keccak_value = [0u8; 32];
+	servers_set_keccak.finalize(&mut servers_set_keccak_value);
+
+	servers_set_keccak_value.into()
+}
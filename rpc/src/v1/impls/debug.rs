@@ -81,6 +81,7 @@ impl<C: BlockChainClient + 'static> Debug for DebugClient<C> {
 					),
 					transactions_root: cast(block.header.transactions_root()),
 					extra_data: block.header.extra_data().clone().into(),
+					finalized: None,
 				},
 				extra_info: vec![
 					("reason".to_owned(), reason),
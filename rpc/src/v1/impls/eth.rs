@@ -290,6 +290,10 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 							false => BlockTransactions::Hashes(block.transaction_hashes()),
 						},
 						extra_data: Bytes::new(view.extra_data()),
+						finalized: match is_pending {
+							true => None,
+							false => self.client.is_finalized(BlockId::Hash(view.hash())),
+						},
 					},
 					extra_info: extra.expect(EXTRA_INFO_PROOF),
 				}))
@@ -425,6 +429,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM, T: StateInfo + 'static> EthClient<C, SN, S
 				seal_fields: uncle.seal().iter().cloned().map(Into::into).collect(),
 				uncles: vec![],
 				transactions: BlockTransactions::Hashes(vec![]),
+				finalized: None,
 			},
 			extra_info: extra,
 		};
@@ -53,6 +53,7 @@ pub struct EthPubSubClient<C> {
 	logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
 	transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
 	sync_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	finalized_heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
 }
 
 impl<C> EthPubSubClient<C>
@@ -85,6 +86,7 @@ impl<C> EthPubSubClient<C> {
 		let logs_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		let transactions_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 		let sync_subscribers = Arc::new(RwLock::new(Subscribers::default()));
+		let finalized_heads_subscribers = Arc::new(RwLock::new(Subscribers::default()));
 
 		EthPubSubClient {
 			handler: Arc::new(ChainNotificationHandler {
@@ -94,11 +96,13 @@ impl<C> EthPubSubClient<C> {
 				logs_subscribers: logs_subscribers.clone(),
 				transactions_subscribers: transactions_subscribers.clone(),
 				sync_subscribers: sync_subscribers.clone(),
+				finalized_heads_subscribers: finalized_heads_subscribers.clone(),
 			}),
 			sync_subscribers,
 			heads_subscribers,
 			logs_subscribers,
 			transactions_subscribers,
+			finalized_heads_subscribers,
 		}
 	}
 
@@ -110,6 +114,7 @@ impl<C> EthPubSubClient<C> {
 		*client.logs_subscribers.write() = Subscribers::new_test();
 		*client.transactions_subscribers.write() = Subscribers::new_test();
 		*client.sync_subscribers.write() = Subscribers::new_test();
+		*client.finalized_heads_subscribers.write() = Subscribers::new_test();
 		client
 	}
 
@@ -152,6 +157,7 @@ pub struct ChainNotificationHandler<C> {
 	logs_subscribers: Arc<RwLock<Subscribers<(Client, EthFilter)>>>,
 	transactions_subscribers: Arc<RwLock<Subscribers<Client>>>,
 	sync_subscribers: Arc<RwLock<Subscribers<Client>>>,
+	finalized_heads_subscribers: Arc<RwLock<Subscribers<Client>>>,
 }
 
 impl<C> ChainNotificationHandler<C> {
@@ -174,6 +180,15 @@ impl<C> ChainNotificationHandler<C> {
 		}
 	}
 
+	fn notify_finalized_head(&self, header: encoded::Header, extra_info: BTreeMap<String, String>) {
+		for subscriber in self.finalized_heads_subscribers.read().values() {
+			Self::notify(&self.executor, subscriber, pubsub::Result::Header(Box::new(RichHeader {
+				inner: (&header).into(),
+				extra_info: extra_info.clone(),
+			})));
+		}
+	}
+
 	fn notify_syncing(&self, sync_status: pubsub::PubSubSyncStatus) {
 		for subscriber in self.sync_subscribers.read().values() {
 			Self::notify(&self.executor, subscriber, pubsub::Result::SyncState(sync_status.clone()));
@@ -294,6 +309,16 @@ impl<C: BlockChainClient> ChainNotify for ChainNotificationHandler<C> {
 			}
 		});
 	}
+
+	fn finalized_head(&self, hash: H256) {
+		if self.finalized_heads_subscribers.read().is_empty() { return }
+
+		let header = self.client.block_header(BlockId::Hash(hash));
+		let extra_info = self.client.block_extra_info(BlockId::Hash(hash));
+		if let (Some(header), Some(extra_info)) = (header, extra_info) {
+			self.notify_finalized_head(header, extra_info);
+		}
+	}
 }
 
 impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
@@ -337,6 +362,13 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 			(pubsub::Kind::NewPendingTransactions, _) => {
 				errors::invalid_params("newPendingTransactions", "Expected no parameters.")
 			},
+			(pubsub::Kind::NewFinalizedHeads, None) => {
+				self.finalized_heads_subscribers.write().push(subscriber);
+				return;
+			},
+			(pubsub::Kind::NewFinalizedHeads, _) => {
+				errors::invalid_params("newFinalizedHeads", "Expected no parameters.")
+			},
 			_ => {
 				errors::unimplemented(None)
 			},
@@ -350,7 +382,8 @@ impl<C: Send + Sync + 'static> EthPubSub for EthPubSubClient<C> {
 		let res2 = self.logs_subscribers.write().remove(&id).is_some();
 		let res3 = self.transactions_subscribers.write().remove(&id).is_some();
 		let res4 = self.sync_subscribers.write().remove(&id).is_some();
+		let res5 = self.finalized_heads_subscribers.write().remove(&id).is_some();
 
-		Ok(res || res2 || res3 || res4)
+		Ok(res || res2 || res3 || res4 || res5)
 	}
 }
@@ -166,6 +166,7 @@ where
 						_ => BlockTransactions::Hashes(block.transaction_hashes().into_iter().map(Into::into).collect()),
 					},
 					extra_data: Bytes::new(header.extra_data().clone()),
+					finalized: None,
 				},
 				extra_info,
 			}
@@ -178,9 +179,10 @@ where
 				Some(score) => Either::A(future::ok(fill_rich(block, Some(score)))),
 				None => {
 					// make a CHT request to fetch the chain score.
-					let req = cht::block_to_cht_number(block.number())
+					let cht_size = client.cht_size();
+					let req = cht::block_to_cht_number(block.number(), cht_size)
 						.and_then(|num| client.cht_root(num as usize))
-						.and_then(|root| request::HeaderProof::new(block.number(), root));
+						.and_then(|root| request::HeaderProof::new(block.number(), root, cht_size));
 
 					let req = match req {
 						Some(req) => req,
@@ -601,6 +603,7 @@ fn extract_uncle_at_index<T: LightChainClient>(block: encoded::Block, index: Ind
 				seal_fields: uncle.seal().iter().cloned().map(Into::into).collect(),
 				uncles: vec![],
 				transactions: BlockTransactions::Hashes(vec![]),
+				finalized: None,
 			},
 			extra_info,
 		})
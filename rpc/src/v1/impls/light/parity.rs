@@ -44,7 +44,7 @@ use v1::types::{
 	LightBlockNumber, ChainStatus, Receipt,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, Header, RichHeader, RecoveredAccount,
-	Log, Filter,
+	Log, Filter, LightCheckpoint,
 };
 use Host;
 use v1::helpers::errors::light_unimplemented;
@@ -62,6 +62,7 @@ where
 	signer: Option<Arc<SignerService>>,
 	ws_address: Option<Host>,
 	gas_price_percentile: usize,
+	light_checkpoint: Option<LightCheckpoint>,
 }
 
 impl<S, OD> ParityClient<S, OD>
@@ -77,6 +78,7 @@ where
 		signer: Option<Arc<SignerService>>,
 		ws_address: Option<Host>,
 		gas_price_percentile: usize,
+		light_checkpoint: Option<LightCheckpoint>,
 	) -> Self {
 		ParityClient {
 			light_dispatch,
@@ -85,6 +87,7 @@ where
 			signer,
 			ws_address,
 			gas_price_percentile,
+			light_checkpoint,
 		}
 	}
 
@@ -134,7 +137,7 @@ where
 	}
 
 	fn dev_logs_levels(&self) -> Result<String> {
-		Ok(self.logger.levels().to_owned())
+		Ok(self.logger.levels())
 	}
 
 	fn net_chain(&self) -> Result<String> {
@@ -421,4 +424,8 @@ where
 	fn submit_raw_block(&self, _block: Bytes) -> Result<H256> {
 		Err(light_unimplemented(None))
 	}
+
+	fn light_checkpoint(&self) -> Result<Option<LightCheckpoint>> {
+		Ok(self.light_checkpoint.clone())
+	}
 }
@@ -30,22 +30,29 @@ use jsonrpc_core::{Result, BoxFuture};
 use jsonrpc_core::futures::Future;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, ReleaseInfo, Transaction};
+use v1::types::{Bytes, ConfigReload, ReleaseInfo, Transaction};
 
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<F> {
 	client: Arc<LightChainClient>,
 	net: Arc<ManageNetwork>,
 	fetch: F,
+	reload_config: Arc<Fn() -> ::std::result::Result<ConfigReload, String> + Send + Sync>,
 }
 
 impl<F: Fetch> ParitySetClient<F> {
 	/// Creates new `ParitySetClient` with given `Fetch`.
-	pub fn new(client: Arc<LightChainClient>, net: Arc<ManageNetwork>, fetch: F) -> Self {
+	pub fn new(
+		client: Arc<LightChainClient>,
+		net: Arc<ManageNetwork>,
+		fetch: F,
+		reload_config: Arc<Fn() -> ::std::result::Result<ConfigReload, String> + Send + Sync>,
+	) -> Self {
 		ParitySetClient {
 			client,
 			net,
 			fetch,
+			reload_config,
 		}
 	}
 }
@@ -149,4 +156,8 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 	fn remove_transaction(&self, _hash: H256) -> Result<Option<Transaction>> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn reload_config(&self) -> Result<ConfigReload> {
+		(self.reload_config)().map_err(errors::cannot_reload_config)
+	}
 }
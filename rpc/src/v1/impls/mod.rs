@@ -32,6 +32,7 @@ mod pubsub;
 mod rpc;
 #[cfg(any(test, feature = "accounts"))]
 mod secretstore;
+mod secretstore_admin;
 mod signer;
 mod signing;
 mod signing_unsafe;
@@ -60,6 +61,7 @@ pub use self::pubsub::PubSubClient;
 pub use self::rpc::RpcClient;
 #[cfg(any(test, feature = "accounts"))]
 pub use self::secretstore::SecretStoreClient;
+pub use self::secretstore_admin::SecretStoreAdminClient;
 pub use self::signer::SignerClient;
 pub use self::signing::SigningQueueClient;
 pub use self::signing_unsafe::SigningUnsafeClient;
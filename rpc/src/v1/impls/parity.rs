@@ -45,7 +45,7 @@ use v1::types::{
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, ChainStatus, Log, Filter,
-	RichHeader, Receipt, RecoveredAccount,
+	RichHeader, Receipt, RecoveredAccount, LightCheckpoint,
 	block_number_to_id
 };
 use Host;
@@ -131,7 +131,7 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 	}
 
 	fn dev_logs_levels(&self) -> Result<String> {
-		Ok(self.logger.levels().to_owned())
+		Ok(self.logger.levels())
 	}
 
 	fn net_chain(&self) -> Result<String> {
@@ -481,4 +481,8 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		);
 		Ok(result.map_err(errors::cannot_submit_block)?)
 	}
+
+	fn light_checkpoint(&self) -> Result<Option<LightCheckpoint>> {
+		Ok(None)
+	}
 }
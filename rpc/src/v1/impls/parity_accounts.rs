@@ -22,14 +22,14 @@ use std::collections::{
 };
 
 use ethereum_types::{Address, H160, H256, H520};
-use ethkey::{Brain, Generator, Secret};
-use ethstore::KeyFile;
-use accounts::AccountProvider;
+use ethkey::{Brain, Generator, Mnemonic, Secret};
+use ethstore::{Error as StoreError, KeyFile};
+use accounts::{self, AccountProvider, Derivation, DerivationPath};
 use jsonrpc_core::Result;
 use v1::helpers::deprecated::{self, DeprecationNotice};
 use v1::helpers::errors;
 use v1::traits::{ParityAccounts, ParityAccountsInfo};
-use v1::types::{Derive, DeriveHierarchical, DeriveHash,ExtAccountInfo, AccountInfo, HwAccountInfo};
+use v1::types::{Derive, DeriveHierarchical, DeriveHash,ExtAccountInfo, AccountInfo, AccountImportResult, AccountPolicy, Bytes, HwAccountInfo, RefusedTransaction};
 use ethkey::Password;
 
 /// Account management (personal) rpc implementation.
@@ -54,6 +54,23 @@ impl ParityAccountsClient {
 	}
 }
 
+fn zip_with_passwords<'a>(jsons: &'a [String], passwords: &'a [Password]) -> Result<Vec<(&'a [u8], &'a Password)>> {
+	if jsons.len() != passwords.len() {
+		return Err(errors::invalid_params("passwords", "Expected exactly one password per wallet."));
+	}
+
+	Ok(jsons.iter().map(|json| json.as_bytes()).zip(passwords).collect())
+}
+
+fn into_import_results(results: Vec<::std::result::Result<Address, StoreError>>) -> Vec<AccountImportResult> {
+	results.into_iter()
+		.map(|result| match result {
+			Ok(address) => AccountImportResult { address: Some(address.into()), error: None },
+			Err(e) => AccountImportResult { address: None, error: Some(e.to_string()) },
+		})
+		.collect()
+}
+
 impl ParityAccountsInfo for ParityAccountsClient {
 	fn accounts_info(&self) -> Result<BTreeMap<H160, AccountInfo>> {
 		self.deprecation_notice("parity_accountsInfo");
@@ -91,6 +108,12 @@ impl ParityAccountsInfo for ParityAccountsClient {
 		self.accounts.locked_hardware_accounts().map_err(|e| errors::account("Error communicating with hardware wallet.", e))
 	}
 
+	fn hardware_passphrase_requests(&self) -> Result<Vec<String>> {
+		self.deprecation_notice("parity_hardwarePassphraseRequests");
+
+		self.accounts.hardware_passphrase_requests().map_err(|e| errors::account("Error communicating with hardware wallet.", e))
+	}
+
 	fn default_account(&self) -> Result<H160> {
 		self.deprecation_notice("parity_defaultAccount");
 
@@ -149,6 +172,18 @@ impl ParityAccounts for ParityAccountsClient {
 			.map_err(|e| errors::account("Could not create account.", e))
 	}
 
+	fn import_presale_wallets(&self, wallets: Vec<String>, passwords: Vec<Password>) -> Result<Vec<AccountImportResult>> {
+		self.deprecation_notice("parity_importPresaleWallets");
+		let wallets = zip_with_passwords(&wallets, &passwords)?;
+		Ok(into_import_results(self.accounts.import_presales(&wallets)))
+	}
+
+	fn import_keystore_files(&self, keyfiles: Vec<String>, passwords: Vec<Password>) -> Result<Vec<AccountImportResult>> {
+		self.deprecation_notice("parity_importKeystoreFiles");
+		let keyfiles = zip_with_passwords(&keyfiles, &passwords)?;
+		Ok(into_import_results(self.accounts.import_wallets(&keyfiles, true)))
+	}
+
 	fn new_account_from_secret(&self, secret: H256, pass: Password) -> Result<H160> {
 		self.deprecation_notice("parity_newAccountFromSecret");
 		let secret = Secret::from_unsafe_slice(&secret.0)
@@ -158,6 +193,23 @@ impl ParityAccounts for ParityAccountsClient {
 			.map_err(|e| errors::account("Could not create account.", e))
 	}
 
+	fn new_account_from_seed(&self, seed: Bytes, pass: Password) -> Result<H160> {
+		self.deprecation_notice("parity_newAccountFromSeed");
+		self.accounts.insert_hd_seed(&seed.0, &pass)
+			.map(Into::into)
+			.map_err(|e| errors::account("Could not create account.", e))
+	}
+
+	fn new_account_from_mnemonic(&self, phrase: String, index: u32, pass: Password) -> Result<H160> {
+		self.deprecation_notice("parity_newAccountFromMnemonic");
+		let secret = Mnemonic::from_phrase(&phrase)
+			.and_then(|mnemonic| mnemonic.derive_secret("", index))
+			.map_err(|e| errors::invalid_params("mnemonic", e))?;
+		self.accounts.insert_account(secret, &pass)
+			.map(Into::into)
+			.map_err(|e| errors::account("Could not create account.", e))
+	}
+
 	fn test_password(&self, account: H160, password: Password) -> Result<bool> {
 		self.deprecation_notice("parity_testPassword");
 		let account: Address = account.into();
@@ -202,6 +254,55 @@ impl ParityAccounts for ParityAccountsClient {
 		Ok(true)
 	}
 
+	fn account_policy(&self, addr: H160) -> Result<Option<AccountPolicy>> {
+		self.deprecation_notice("parity_accountPolicy");
+
+		Ok(self.accounts.account_policy(&addr.into()).map(|policy| AccountPolicy {
+			recipients: policy.recipients,
+			max_value: policy.max_value,
+			max_gas_price: policy.max_gas_price,
+			methods: policy.methods.map(|methods| methods.iter().map(|m| m.to_vec().into()).collect()),
+		}))
+	}
+
+	fn account_policy_violations(&self, addr: H160) -> Result<Vec<RefusedTransaction>> {
+		self.deprecation_notice("parity_accountPolicyViolations");
+
+		Ok(self.accounts.policy_violations(&addr.into()).into_iter().map(|refused| RefusedTransaction {
+			hash: refused.hash,
+			nonce: refused.nonce,
+			reason: refused.reason,
+			timestamp: refused.timestamp,
+		}).collect())
+	}
+
+	fn set_account_policy(&self, addr: H160, policy: AccountPolicy) -> Result<bool> {
+		self.deprecation_notice("parity_setAccountPolicy");
+
+		let methods = match policy.methods {
+			Some(methods) => Some(methods.into_iter().map(|method| {
+				let method = method.into_vec();
+				if method.len() != 4 {
+					return Err(errors::invalid_params("methods", "Expected 4-byte method selectors"));
+				}
+				let mut selector = [0u8; 4];
+				selector.copy_from_slice(&method);
+				Ok(selector)
+			}).collect::<Result<Vec<_>>>()?),
+			None => None,
+		};
+
+		self.accounts
+			.set_account_policy(addr.into(), accounts::AccountPolicy {
+				recipients: policy.recipients,
+				max_value: policy.max_value,
+				max_gas_price: policy.max_gas_price,
+				methods,
+			})
+			.map_err(|e| errors::account("Could not set account policy.", e))
+			.map(|_| true)
+	}
+
 	fn set_account_meta(&self, addr: H160, meta: String) -> Result<bool> {
 		self.deprecation_notice("parity_setAccountMeta");
 		let addr: Address = addr.into();
@@ -276,6 +377,15 @@ impl ParityAccounts for ParityAccountsClient {
 			.map(|_| true)
 	}
 
+	fn rename_vault(&self, name: String, new_name: String) -> Result<bool> {
+		self.deprecation_notice("parity_renameVault");
+
+		self.accounts
+			.rename_vault(&name, &new_name)
+			.map_err(|e| errors::account("Could not rename vault.", e))
+			.map(|_| true)
+	}
+
 	fn change_vault(&self, address: H160, new_vault: String) -> Result<bool> {
 		self.deprecation_notice("parity_changeVault");
 		self.accounts
@@ -329,13 +439,25 @@ impl ParityAccounts for ParityAccountsClient {
 			.map_err(|e| errors::account("Could not derive account.", e))
 	}
 
-	fn export_account(&self, addr: H160, password: Password) -> Result<KeyFile> {
+	fn derive_account_hierarchical(&self, addr: H160, password: Password, path: String, save_as_account: bool) -> Result<H160> {
+		self.deprecation_notice("parity_deriveAccountHierarchical");
+		let addr: Address = addr.into();
+		let path: DerivationPath = path.parse()
+			.map_err(|e| errors::invalid_params("derivation path", e))?;
+		self.accounts
+			.derive_account(&addr, Some(password), Derivation::from(&path), save_as_account)
+			.map(Into::into)
+			.map_err(|e| errors::account("Could not derive account.", e))
+	}
+
+	fn export_account(&self, addr: H160, password: Password, new_password: Option<Password>) -> Result<KeyFile> {
 		self.deprecation_notice("parity_exportAccount");
 		let addr = addr.into();
 		self.accounts
 			.export_account(
 				&addr,
 				password,
+				new_password,
 			)
 			.map(Into::into)
 			.map_err(|e| errors::account("Could not export account.", e))
@@ -358,6 +480,12 @@ impl ParityAccounts for ParityAccountsClient {
 
 		self.accounts.hardware_pin_matrix_ack(&path, &pin).map_err(|e| errors::account("Error communicating with hardware wallet.", e))
 	}
+
+	fn hardware_passphrase_ack(&self, path: String, passphrase: String) -> Result<bool> {
+		self.deprecation_notice("parity_hardwarePassphraseAck");
+
+		self.accounts.hardware_passphrase_ack(&path, &passphrase).map_err(|e| errors::account("Error communicating with hardware wallet.", e))
+	}
 }
 
 fn into_vec<A, B>(a: Vec<A>) -> Vec<B> where
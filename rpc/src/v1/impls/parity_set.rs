@@ -32,7 +32,7 @@ use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_core::futures::Future;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, ReleaseInfo, Transaction};
+use v1::types::{Bytes, ConfigReload, ReleaseInfo, Transaction};
 
 #[cfg(any(test, feature = "accounts"))]
 pub mod accounts {
@@ -88,6 +88,7 @@ pub struct ParitySetClient<C, M, U, F = fetch::Client> {
 	updater: Arc<U>,
 	net: Arc<ManageNetwork>,
 	fetch: F,
+	reload_config: Arc<Fn() -> ::std::result::Result<ConfigReload, String> + Send + Sync>,
 }
 
 impl<C, M, U, F> ParitySetClient<C, M, U, F>
@@ -100,6 +101,7 @@ impl<C, M, U, F> ParitySetClient<C, M, U, F>
 		updater: &Arc<U>,
 		net: &Arc<ManageNetwork>,
 		fetch: F,
+		reload_config: &Arc<Fn() -> ::std::result::Result<ConfigReload, String> + Send + Sync>,
 	) -> Self {
 		ParitySetClient {
 			client: client.clone(),
@@ -107,6 +109,7 @@ impl<C, M, U, F> ParitySetClient<C, M, U, F>
 			updater: updater.clone(),
 			net: net.clone(),
 			fetch,
+			reload_config: reload_config.clone(),
 		}
 	}
 }
@@ -240,4 +243,8 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 			.map(|t| Transaction::from_pending(t.pending().clone()))
 		)
 	}
+
+	fn reload_config(&self) -> Result<ConfigReload> {
+		(self.reload_config)().map_err(errors::cannot_reload_config)
+	}
 }
@@ -27,7 +27,8 @@ use ethkey::Secret;
 use jsonrpc_core::Result;
 use v1::helpers::errors;
 use v1::helpers::secretstore::{generate_document_key, encrypt_document,
-	decrypt_document, decrypt_document_with_shadow, ordered_servers_keccak};
+	decrypt_document, decrypt_document_with_shadow};
+use v1::helpers::secretstore_admin::ordered_servers_keccak;
 use v1::traits::SecretStore;
 use v1::types::{Bytes, EncryptedDocumentKey};
 use ethkey::Password;
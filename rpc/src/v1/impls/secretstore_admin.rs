@@ -0,0 +1,83 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! SecretStore administrative sessions rpc implementation.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use ethereum_types::{H256, H512, H520};
+use ethkey::{recover, Signature};
+
+use jsonrpc_core::Result;
+use v1::helpers::errors;
+use v1::helpers::secretstore_admin::{KeyServerAdmin, ordered_servers_keccak};
+use v1::traits::SecretStoreAdmin;
+use v1::types::SecretStoreAdminSession;
+
+/// SecretStore administrative sessions rpc implementation.
+pub struct SecretStoreAdminClient {
+	key_server: Option<Arc<KeyServerAdmin>>,
+}
+
+impl SecretStoreAdminClient {
+	/// Creates new SecretStoreAdminClient. `None` if key server is not running on this node.
+	pub fn new(key_server: Option<Arc<KeyServerAdmin>>) -> Self {
+		SecretStoreAdminClient {
+			key_server,
+		}
+	}
+
+	fn key_server(&self) -> Result<&KeyServerAdmin> {
+		match self.key_server {
+			Some(ref key_server) => Ok(&**key_server),
+			None => Err(errors::secretstore_disabled()),
+		}
+	}
+}
+
+impl SecretStoreAdmin for SecretStoreAdminClient {
+	fn change_servers_set(&self, old_servers_set: BTreeSet<H512>, old_set_signature: H520, new_servers_set: BTreeSet<H512>, new_set_signature: H520) -> Result<H256> {
+		let key_server = self.key_server()?;
+		let old_set_signature: Signature = old_set_signature.into();
+		let new_set_signature: Signature = new_set_signature.into();
+
+		// both sets must be signed with the same (administrator) key. Key servers are checking that
+		// the key is the configured administrator key && the old set is the current servers set.
+		let old_set_signer = recover(&old_set_signature, &ordered_servers_keccak(old_servers_set))
+			.map_err(|e| errors::invalid_params("old_set_signature", e))?;
+		let new_set_signer = recover(&new_set_signature, &ordered_servers_keccak(new_servers_set.clone()))
+			.map_err(|e| errors::invalid_params("new_set_signature", e))?;
+		if old_set_signer != new_set_signer {
+			return Err(errors::invalid_params("new_set_signature", "servers sets are signed with different keys"));
+		}
+
+		key_server.change_servers_set(old_set_signature, new_set_signature, new_servers_set)
+			.map_err(errors::secretstore)
+	}
+
+	fn admin_sessions(&self) -> Result<Vec<SecretStoreAdminSession>> {
+		self.key_server()?.sessions()
+			.map(|sessions| sessions.into_iter().map(Into::into).collect())
+			.map_err(errors::secretstore)
+	}
+
+	fn abort_admin_session(&self, session_id: H256, signature: H520) -> Result<bool> {
+		self.key_server()?.abort_session(session_id, signature.into())
+			.map(|_| true)
+			.map_err(errors::secretstore)
+	}
+}
@@ -160,7 +160,17 @@ impl<D: Dispatcher + 'static> Signer for SignerClient<D> {
 	fn requests_to_confirm(&self) -> Result<Vec<ConfirmationRequest>> {
 		self.deprecation_notice.print("signer_requestsToConfirm", deprecated::msgs::ACCOUNTS);
 
-		Ok(self.signer.requests()
+		Ok(self.signer.peek()
+			.into_iter()
+			.map(Into::into)
+			.collect()
+		)
+	}
+
+	fn requests_in_progress(&self) -> Result<Vec<ConfirmationRequest>> {
+		self.deprecation_notice.print("signer_requestsInProgress", deprecated::msgs::ACCOUNTS);
+
+		Ok(self.signer.requests_in_progress()
 			.into_iter()
 			.map(Into::into)
 			.collect()
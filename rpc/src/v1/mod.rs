@@ -41,11 +41,11 @@ pub mod informant;
 pub mod metadata;
 pub mod traits;
 
-pub use self::traits::{Debug, Eth, EthFilter, EthPubSub, EthSigning, Net, Parity, ParityAccountsInfo, ParityAccounts, ParitySet, ParitySetAccounts, ParitySigning, Personal, PubSub, Private, Rpc, SecretStore, Signer, Traces, Web3};
+pub use self::traits::{Debug, Eth, EthFilter, EthPubSub, EthSigning, Net, Parity, ParityAccountsInfo, ParityAccounts, ParitySet, ParitySetAccounts, ParitySigning, Personal, PubSub, Private, Rpc, SecretStore, SecretStoreAdmin, Signer, Traces, Web3};
 pub use self::impls::*;
-pub use self::helpers::{NetworkSettings, block_import, dispatch};
+pub use self::helpers::{NetworkSettings, block_import, dispatch, secretstore_admin};
 pub use self::metadata::Metadata;
-pub use self::types::Origin;
+pub use self::types::{ConfigReload, Origin, LightCheckpoint};
 pub use self::types::pubsub::PubSubSyncStatus;
 pub use self::extractors::{RpcExtractor, WsExtractor, WsStats, WsDispatcher};
 
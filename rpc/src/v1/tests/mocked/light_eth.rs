@@ -0,0 +1,213 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Tests of the light client's `eth_` RPCs, counting the requests made to the network.
+
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use ethcore::client::Executed;
+use ethcore::spec::Spec;
+use ethereum_types::{H256, Address};
+use futures::sync::oneshot::{self, Receiver};
+use io::IoChannel;
+use jsonrpc_core::IoHandler;
+use kvdb_memorydb;
+use light::TransactionQueue;
+use light::cache::{Cache, ExecutionKey};
+use light::client::{self, Client};
+use light::client::fetch::{self, Unavailable};
+use light::net::{BasicContext, Announcement, Error as NetError, ReqId};
+use light::on_demand::{OnDemandRequester, OnResponses, Request, Response};
+use light::on_demand::error::Result as OnDemandResult;
+use light::on_demand::request::RequestAdapter;
+use light::request::{NetworkRequests, NoSuchOutput};
+use network::{NetworkContext, NodeId, PeerId, ProtocolId};
+use parking_lot::{Mutex, RwLock};
+use sync::{LightSyncProvider, LightSyncInfo, LightNetworkDispatcher, ManageNetwork, PeerNumbers, PeerInfo, TransactionStats};
+
+use v1::{Eth, Metadata};
+use v1::impls::light::EthClient;
+
+// network context without any peers.
+struct TestContext;
+
+impl BasicContext for TestContext {
+	fn persistent_peer_id(&self, _peer: PeerId) -> Option<NodeId> { None }
+	fn request_from(&self, _peer: PeerId, _request: NetworkRequests) -> Result<ReqId, NetError> { Err(NetError::NoCredits) }
+	fn make_announcement(&self, _announcement: Announcement) { }
+	fn disconnect_peer(&self, _peer: PeerId) { }
+	fn disable_peer(&self, _peer: PeerId) { }
+}
+
+struct TestLightSync;
+
+impl LightSyncProvider for TestLightSync {
+	fn peer_numbers(&self) -> PeerNumbers { PeerNumbers { connected: 0, active: 0, max: 25, min: 25 } }
+	fn peers(&self) -> Vec<PeerInfo> { Vec::new() }
+	fn network_id(&self) -> u64 { 1 }
+	fn enode(&self) -> Option<String> { None }
+	fn transactions_stats(&self) -> BTreeMap<H256, TransactionStats> { BTreeMap::new() }
+}
+
+impl LightSyncInfo for TestLightSync {
+	fn highest_block(&self) -> Option<u64> { None }
+	fn start_block(&self) -> u64 { 0 }
+	fn is_major_importing(&self) -> bool { false }
+}
+
+impl LightNetworkDispatcher for TestLightSync {
+	fn with_context<F, T>(&self, f: F) -> Option<T> where F: FnOnce(&BasicContext) -> T {
+		Some(f(&TestContext))
+	}
+}
+
+impl ManageNetwork for TestLightSync {
+	fn accept_unreserved_peers(&self) { }
+	fn deny_unreserved_peers(&self) { }
+	fn remove_reserved_peer(&self, _peer: String) -> Result<(), String> { Ok(()) }
+	fn add_reserved_peer(&self, _peer: String) -> Result<(), String> { Ok(()) }
+	fn start_network(&self) { }
+	fn stop_network(&self) { }
+	fn num_peers_range(&self) -> RangeInclusive<u32> { 25..=50 }
+	fn with_proto_context(&self, _: ProtocolId, _: &mut FnMut(&NetworkContext)) { }
+}
+
+// counts the requests sent to the network, which never answers them.
+#[derive(Default)]
+struct TestOnDemand {
+	requests: AtomicUsize,
+}
+
+impl OnDemandRequester for TestOnDemand {
+	fn request<T>(&self, _ctx: &BasicContext, _requests: T) -> Result<OnResponses<T>, NoSuchOutput> where
+		T: RequestAdapter
+	{
+		unimplemented!("the tested calls only submit raw requests")
+	}
+
+	fn request_raw(&self, _ctx: &BasicContext, requests: Vec<Request>) -> Result<Receiver<OnDemandResult<Vec<Response>>>, NoSuchOutput> {
+		self.requests.fetch_add(requests.len(), Ordering::SeqCst);
+		let (sender, receiver) = oneshot::channel();
+		if requests.is_empty() {
+			let _ = sender.send(Ok(Vec::new()));
+		}
+		Ok(receiver)
+	}
+}
+
+struct LightEthTester {
+	client: Arc<Client<Unavailable>>,
+	on_demand: Arc<TestOnDemand>,
+	cache: Arc<Mutex<Cache>>,
+	io: IoHandler<Metadata>,
+}
+
+impl LightEthTester {
+	fn new() -> Self {
+		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(60))));
+		let client = Arc::new(Client::new(
+			client::Config::default(),
+			Arc::new(kvdb_memorydb::create(0)),
+			None,
+			&Spec::new_test(),
+			fetch::unavailable(),
+			IoChannel::disconnected(),
+			cache.clone(),
+		).unwrap());
+		let on_demand = Arc::new(TestOnDemand::default());
+
+		let eth = EthClient::new(
+			Arc::new(TestLightSync),
+			client.clone(),
+			on_demand.clone(),
+			Arc::new(RwLock::new(TransactionQueue::default())),
+			Arc::new(|| Vec::<Address>::new()),
+			cache.clone(),
+			50,
+			60,
+		);
+		let mut io = IoHandler::default();
+		io.extend_with(eth.to_delegate());
+
+		LightEthTester { client, on_demand, cache, io }
+	}
+
+	fn requests(&self) -> usize {
+		self.on_demand.requests.load(Ordering::SeqCst)
+	}
+}
+
+#[test]
+fn rpc_eth_call_answers_cached_call_without_network_requests() {
+	let tester = LightEthTester::new();
+	let executed = Executed {
+		exception: None,
+		gas: 60_000.into(),
+		gas_used: 21_000.into(),
+		refunded: 0.into(),
+		cumulative_gas_used: 21_000.into(),
+		logs: Vec::new(),
+		contracts_created: Vec::new(),
+		output: vec![0x12, 0x34],
+		trace: Vec::new(),
+		vm_trace: None,
+		state_diff: None,
+	};
+	tester.cache.lock().insert_execution(ExecutionKey {
+		block_hash: tester.client.chain_info().best_block_hash,
+		from: Address::default(),
+		to: Some(Address::from(1)),
+		value: 0.into(),
+		data: vec![0x01],
+		gas: None,
+		gas_price: None,
+		nonce: None,
+	}, Ok(executed));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"to": "0x0000000000000000000000000000000000000001",
+			"data": "0x01"
+		}, "latest"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1234","id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(tester.requests(), 0);
+
+	// a call which isn't cached asks the network for the nonce of the sender.
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"to": "0x0000000000000000000000000000000000000001",
+			"data": "0x02",
+			"gasPrice": "0x1"
+		}, "latest"],
+		"id": 1
+	}"#;
+
+	assert!(tester.io.handle_request_sync(request).unwrap().contains("error"));
+	assert_eq!(tester.requests(), 1);
+}
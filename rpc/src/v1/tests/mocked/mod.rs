@@ -20,6 +20,7 @@
 mod debug;
 mod eth;
 mod eth_pubsub;
+mod light_eth;
 mod manage_network;
 mod net;
 mod parity;
@@ -32,6 +33,7 @@ mod pubsub;
 mod rpc;
 #[cfg(any(test, feature = "accounts"))]
 mod secretstore;
+mod secretstore_admin;
 mod signer;
 #[cfg(any(test, feature = "accounts"))]
 mod signing;
@@ -16,11 +16,13 @@
 
 use std::sync::Arc;
 
-use accounts::{AccountProvider, AccountProviderSettings};
+use accounts::{self, AccountProvider, AccountProviderSettings};
 use ethereum_types::Address;
 use ethstore::EthStore;
 use ethstore::accounts_dir::RootDiskDirectory;
+use serde_json;
 use tempdir::TempDir;
+use types::transaction::{Action, Transaction};
 
 use jsonrpc_core::IoHandler;
 use v1::{ParityAccounts, ParityAccountsInfo, ParityAccountsClient};
@@ -269,6 +271,67 @@ fn rpc_parity_change_vault_password() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_rename_vault() {
+	let tempdir = TempDir::new("").unwrap();
+	let tester = setup_with_vaults_support(tempdir.path().to_str().unwrap());
+
+	assert!(tester.accounts.create_vault("vault1", &"password1".into()).is_ok());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_renameVault", "params":["vault1", "vault2"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(tester.accounts.list_opened_vaults().unwrap(), vec!["vault2".to_owned()]);
+}
+
+#[test]
+fn rpc_parity_set_account_policy() {
+	let tester = setup();
+	let address = tester.accounts.new_account(&"password".into()).unwrap();
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_setAccountPolicy", "params":["0x{:x}", {{"recipients":["0xd46e8dd67c5d32be8058bb8eb970870f07244567"],"maxValue":"0x64","methods":["0xa9059cbb"]}}], "id": 1}}"#, address);
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_accountPolicy", "params":["0x{:x}"], "id": 1}}"#, address);
+	let response = r#"{"jsonrpc":"2.0","result":{"recipients":["0xd46e8dd67c5d32be8058bb8eb970870f07244567"],"maxValue":"0x64","maxGasPrice":null,"methods":["0xa9059cbb"]},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_setAccountPolicy", "params":["0x{:x}", {{"methods":["0xa9059c"]}}], "id": 1}}"#, address);
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: methods","data":"\"Expected 4-byte method selectors\""},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_account_policy_violations() {
+	let tester = setup();
+	let address = tester.accounts.new_account(&"password".into()).unwrap();
+	tester.accounts.set_account_policy(address, accounts::AccountPolicy {
+		recipients: Some(vec![1.into()]),
+		..Default::default()
+	}).unwrap();
+	let transaction = Transaction {
+		nonce: 5.into(),
+		gas_price: 0.into(),
+		gas: 21_000.into(),
+		action: Action::Create,
+		value: 0.into(),
+		data: vec![],
+	};
+	assert!(tester.accounts.sign_transaction(address, Some("password".into()), &transaction, None).is_err());
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_accountPolicyViolations", "params":["0x{:x}"], "id": 1}}"#, address);
+	let response = tester.io.handle_request_sync(&request).unwrap();
+	let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+	let violations = response["result"].as_array().unwrap();
+
+	assert_eq!(violations.len(), 1);
+	assert_eq!(violations[0]["hash"], format!("0x{:x}", transaction.hash(None)));
+	assert_eq!(violations[0]["nonce"], "0x5");
+	assert_eq!(violations[0]["reason"], "Contract creation is not allowed");
+}
+
 #[test]
 fn rpc_parity_change_vault() {
 	let tempdir = TempDir::new("").unwrap();
@@ -417,6 +480,41 @@ fn derive_key_index() {
 	assert_eq!(res, Some(response.into()));
 }
 
+#[test]
+fn derive_account_hierarchical_from_seed() {
+	let tester = setup();
+
+	let request = r#"{"jsonrpc":"2.0","method":"parity_newAccountFromSeed","params":["0x5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4", "test"],"id":1}"#;
+	let res = tester.io.handle_request_sync(&request).unwrap();
+	let root: serde_json::Value = serde_json::from_str(&res).unwrap();
+	let root = root["result"].as_str().unwrap().to_owned();
+
+	let request = r#"{"jsonrpc":"2.0","method":"parity_deriveAccountHierarchical","params":["<ROOT>", "test", "m/44'/60'/0'/0/0", true],"id":2}"#;
+	let request = request.replace("<ROOT>", &root);
+	let response = r#"{"jsonrpc":"2.0","result":"0x9858effd232b4033e47d90003d41ec34ecaeda94","id":2}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.into()));
+	assert!(tester.accounts.has_account("0x9858effd232b4033e47d90003d41ec34ecaeda94".into()));
+
+	let request = r#"{"jsonrpc":"2.0","method":"parity_deriveAccountHierarchical","params":["<ROOT>", "test", "44'/60'", false],"id":3}"#;
+	let request = request.replace("<ROOT>", &root);
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: derivation path","data":"InvalidPath"},"id":3}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.into()));
+}
+
+#[test]
+fn new_account_from_mnemonic() {
+	let tester = setup();
+
+	let request = r#"{"jsonrpc":"2.0","method":"parity_newAccountFromMnemonic","params":["abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about", 0, "test"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x9858effd232b4033e47d90003d41ec34ecaeda94","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.into()));
+	assert!(tester.accounts.has_account("0x9858effd232b4033e47d90003d41ec34ecaeda94".into()));
+
+	let request = r#"{"jsonrpc":"2.0","method":"parity_newAccountFromMnemonic","params":["abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon", 0, "test"],"id":2}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: mnemonic","data":"InvalidChecksum"},"id":2}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.into()));
+}
+
 #[test]
 fn should_export_account() {
 	// given
@@ -443,6 +541,28 @@ fn should_export_account() {
 	assert_eq!(result, Some(response.into()));
 }
 
+#[test]
+fn should_export_account_with_new_password() {
+	// given
+	let tempdir = TempDir::new("").unwrap();
+	let tester = setup_with_vaults_support(tempdir.path().to_str().unwrap());
+	tester.accounts.create_vault("vault1", &"vault_password".into()).unwrap();
+	let address = tester.accounts.new_account(&"password".into()).unwrap();
+	tester.accounts.change_vault(address, "vault1").unwrap();
+
+	// when
+	let request = format!(r#"{{"jsonrpc":"2.0","method":"parity_exportAccount","params":["0x{:x}","password","new_password"],"id":1}}"#, address);
+	let response = tester.io.handle_request_sync(&request).unwrap();
+	let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+	let exported = serde_json::to_vec(&response["result"]).unwrap();
+
+	// then
+	let other = AccountProvider::transient_provider();
+	other.import_wallet(&exported, &"password".into(), false).unwrap_err();
+	assert_eq!(other.import_wallet(&exported, &"new_password".into(), false).unwrap(), address);
+	assert!(other.test_password(&address, &"new_password".into()).unwrap());
+}
+
 #[test]
 fn should_import_wallet() {
 	let tester = setup();
@@ -463,6 +583,48 @@ fn should_import_wallet() {
 	assert!(account_uuid != id);
 }
 
+#[test]
+fn should_import_keystore_files_in_batch() {
+	let tester = setup();
+
+	let wallet = r#""{\"id\":\"6a186c80-7797-cff2-bc2e-7c1d6a6cc76e\",\"version\":3,\"crypto\":{\"cipher\":\"aes-128-ctr\",\"cipherparams\":{\"iv\":\"478736fb55872c1baf01b27b1998c90b\"},\"ciphertext\":\"fe5a63cc0055d7b0b3b57886f930ad9b63f48950d1348145d95996c41e05f4e0\",\"kdf\":\"pbkdf2\",\"kdfparams\":{\"c\":10240,\"dklen\":32,\"prf\":\"hmac-sha256\",\"salt\":\"658436d6738a19731149a98744e5cf02c8d5aa1f8e80c1a43cc9351c70a984e4\"},\"mac\":\"c7384b26ecf25539d942030230062af9b69de5766cbcc4690bffce1536644631\"},\"address\":\"00bac56a8a27232baa044c03f43bf3648c961735\",\"name\":\"hello world\",\"meta\":\"{}\"}""#;
+	let request = r#"{"jsonrpc":"2.0","method":"parity_importKeystoreFiles","params":[[<W>, <W>, "{}", <W>], ["himom2", "himom", "himom", "himom"]],"id":1}"#;
+	let request = request.replace("<W>", wallet);
+	let response = r#"{"jsonrpc":"2.0","result":[{"error":"Invalid password"},{"address":"0x00bac56a8a27232baa044c03f43bf3648c961735"},{"error":"Invalid key file: Invalid JSON format"},{"error":"Account 0x00bac56a8a27232baa044c03f43bf3648c961735 already exists"}],"id":1}"#;
+
+	let res = tester.io.handle_request_sync(&request).unwrap();
+
+	assert_eq!(res, response);
+	assert_eq!(tester.accounts.accounts().unwrap(), vec!["0x00bac56a8a27232baa044c03f43bf3648c961735".into()]);
+}
+
+#[test]
+fn should_import_presale_wallets_in_batch() {
+	let tester = setup();
+
+	let wallet = r#""{\"encseed\":\"137103c28caeebbcea5d7f95edb97a289ded151b72159137cb7b2671f394f54cff8c121589dcb373e267225547b3c71cbdb54f6e48ec85cd549f96cf0dedb3bc0a9ac6c79b9c426c5878ca2c9d06ff42a23cb648312fc32ba83649de0928e066\",\"ethaddr\":\"ede84640d1a1d3e06902048e67aa7db8d52c2ce1\",\"email\":\"123@gmail.com\",\"btcaddr\":\"1JvqEc6WLhg6GnyrLBe2ztPAU28KRfuseH\"}""#;
+	let request = r#"{"jsonrpc":"2.0","method":"parity_importPresaleWallets","params":[[<W>, <W>, <W>], ["124", "123", "123"]],"id":1}"#;
+	let request = request.replace("<W>", wallet);
+	let response = r#"{"jsonrpc":"2.0","result":[{"error":"Invalid password"},{"address":"0xede84640d1a1d3e06902048e67aa7db8d52c2ce1"},{"error":"Account 0xede84640d1a1d3e06902048e67aa7db8d52c2ce1 already exists"}],"id":1}"#;
+
+	let res = tester.io.handle_request_sync(&request).unwrap();
+
+	assert_eq!(res, response);
+	assert_eq!(tester.accounts.accounts().unwrap(), vec!["0xede84640d1a1d3e06902048e67aa7db8d52c2ce1".into()]);
+}
+
+#[test]
+fn should_reject_batch_import_with_mismatched_passwords() {
+	let tester = setup();
+
+	let request = r#"{"jsonrpc":"2.0","method":"parity_importKeystoreFiles","params":[["{}", "{}"], ["himom"]],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: passwords","data":"\"Expected exactly one password per wallet.\""},"id":1}"#;
+
+	let res = tester.io.handle_request_sync(&request).unwrap();
+
+	assert_eq!(res, response);
+}
+
 #[test]
 fn should_sign_message() {
 	let tester = setup();
@@ -25,6 +25,7 @@ use sync::ManageNetwork;
 
 use jsonrpc_core::IoHandler;
 use v1::{ParitySet, ParitySetClient};
+use v1::types::ConfigReload;
 use v1::tests::helpers::{TestMinerService, TestUpdater};
 use super::manage_network::TestManageNetwork;
 
@@ -60,6 +61,11 @@ fn parity_set_client(
 		updater,
 		&(net.clone() as Arc<ManageNetwork>),
 		FakeFetch::new(Some(1)),
+		&(Arc::new(|| Ok(ConfigReload {
+			applied: vec!["logging".into()],
+			failed: vec![],
+			requires_restart: vec!["port".into()],
+		})) as Arc<Fn() -> Result<ConfigReload, String> + Send + Sync>),
 	)
 }
 
@@ -269,3 +275,17 @@ fn rpc_parity_set_engine_signer() {
 	assert_eq!(&format!("{}", signature.pretty()), "6f46069ded2154af6e806706e4f7f6fd310ac45f3c6dccb85f11c0059ee20a09245df0a0008bb84a10882b1298284bc93058e7bc5938ea728e77620061687a6401");
 }
 
+
+#[test]
+fn rpc_parity_reload_config() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_reloadConfig", "params": [], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"applied":["logging"],"failed":[],"requiresRestart":["port"]},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
@@ -18,7 +18,7 @@ use std::sync::Arc;
 use std::str::FromStr;
 
 use bytes::ToPretty;
-use accounts::AccountProvider;
+use accounts::{AccountProvider, AccountPolicy};
 use ethereum_types::{Address, H520, U256};
 use ethcore::client::TestBlockChainClient;
 use jsonrpc_core::IoHandler;
@@ -199,6 +199,35 @@ fn send_transaction() {
 	sign_and_send_test("personal_sendTransaction");
 }
 
+#[test]
+fn send_transaction_violating_account_policy() {
+	let tester = setup();
+	let address = tester.accounts.new_account(&"password123".into()).unwrap();
+	tester.accounts.set_account_policy(address, AccountPolicy {
+		max_value: Some(100.into()),
+		..Default::default()
+	}).unwrap();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "personal_sendTransaction",
+		"params": [{
+			"from": ""#.to_owned() + format!("0x{:x}", address).as_ref() + r#"",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0",
+			"gasPrice": "0x9184e72a000",
+			"value": "0x9184e72a"
+		}, "password123"],
+		"id": 1
+	}"#;
+
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32022,"message":"Transaction refused by the account policy: Value 2441406250 exceeds the limit of 100","data":"Refused transactions are listed by parity_accountPolicyViolations"},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request.as_ref()), Some(response.into()));
+	assert_eq!(tester.miner.imported_transactions.lock().len(), 0);
+	assert_eq!(tester.accounts.policy_violations(&address).len(), 1);
+}
+
 #[test]
 fn sign_and_send_transaction() {
 	sign_and_send_test("personal_signAndSendTransaction");
@@ -26,7 +26,7 @@ use jsonrpc_core::{IoHandler, Success};
 use v1::metadata::Metadata;
 use v1::SecretStoreClient;
 use v1::traits::secretstore::SecretStore;
-use v1::helpers::secretstore::ordered_servers_keccak;
+use v1::helpers::secretstore_admin::ordered_servers_keccak;
 use v1::types::EncryptedDocumentKey;
 
 struct Dependencies {
@@ -0,0 +1,145 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use ethereum_types::{H256, H512, H520};
+use ethkey::{Random, Generator, KeyPair, Signature, sign};
+use parking_lot::Mutex;
+
+use serde_json;
+use jsonrpc_core::IoHandler;
+use v1::SecretStoreAdminClient;
+use v1::traits::SecretStoreAdmin;
+use v1::helpers::secretstore_admin::{KeyServerAdmin, KeyServerAdminSession, ordered_servers_keccak};
+
+#[derive(Default)]
+struct TestKeyServerAdmin {
+	sessions: Mutex<Vec<KeyServerAdminSession>>,
+	new_servers_sets: Mutex<Vec<BTreeSet<H512>>>,
+}
+
+impl KeyServerAdmin for TestKeyServerAdmin {
+	fn change_servers_set(&self, _old_set_signature: Signature, _new_set_signature: Signature, new_servers_set: BTreeSet<H512>) -> Result<H256, String> {
+		let mut sessions = self.sessions.lock();
+		if !sessions.is_empty() {
+			return Err("Exclusive session active".into());
+		}
+
+		self.new_servers_sets.lock().push(new_servers_set);
+		sessions.push(KeyServerAdminSession {
+			id: 1.into(),
+			session_type: "servers set change".into(),
+			master: 2.into(),
+			is_finished: false,
+			keys_total: Some(3),
+			keys_processed: Some(1),
+		});
+		Ok(1.into())
+	}
+
+	fn sessions(&self) -> Result<Vec<KeyServerAdminSession>, String> {
+		Ok(self.sessions.lock().clone())
+	}
+
+	fn abort_session(&self, session_id: H256, _signature: Signature) -> Result<(), String> {
+		let mut sessions = self.sessions.lock();
+		match sessions.iter().position(|s| s.id == session_id) {
+			Some(index) => {
+				sessions.remove(index);
+				Ok(())
+			},
+			None => Err("no active session with given id".into()),
+		}
+	}
+}
+
+fn io(key_server: Option<Arc<TestKeyServerAdmin>>) -> IoHandler {
+	let mut io = IoHandler::default();
+	io.extend_with(SecretStoreAdminClient::new(key_server.map(|ks| ks as Arc<KeyServerAdmin>)).to_delegate());
+	io
+}
+
+fn servers_set(num_servers: usize) -> BTreeSet<H512> {
+	(0..num_servers).map(|_| Random.generate().unwrap().public().clone()).collect()
+}
+
+fn change_servers_set_request(old_set_signer: &KeyPair, old_servers_set: &BTreeSet<H512>, new_set_signer: &KeyPair, new_servers_set: &BTreeSet<H512>) -> String {
+	let old_set_signature: H520 = sign(old_set_signer.secret(), &ordered_servers_keccak(old_servers_set.clone())).unwrap().into();
+	let new_set_signature: H520 = sign(new_set_signer.secret(), &ordered_servers_keccak(new_servers_set.clone())).unwrap().into();
+	format!(r#"{{"jsonrpc": "2.0", "method": "secretstore_changeServersSet", "params":[{}, {}, {}, {}], "id": 1}}"#,
+		serde_json::to_string(old_servers_set).unwrap(),
+		serde_json::to_string(&old_set_signature).unwrap(),
+		serde_json::to_string(new_servers_set).unwrap(),
+		serde_json::to_string(&new_set_signature).unwrap())
+}
+
+#[test]
+fn rpc_secretstore_admin_is_disabled_without_key_server() {
+	let io = io(None);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "secretstore_adminSessions", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Secret Store key server is disabled. This API is not available."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_secretstore_admin_changes_servers_set() {
+	let key_server = Arc::new(TestKeyServerAdmin::default());
+	let io = io(Some(key_server.clone()));
+	let admin = Random.generate().unwrap();
+	let old_servers_set = servers_set(3);
+	let new_servers_set: BTreeSet<_> = old_servers_set.iter().cloned().take(2).collect();
+
+	// start session
+	let request = change_servers_set_request(&admin, &old_servers_set, &admin, &new_servers_set);
+	let response = r#"{"jsonrpc":"2.0","result":"0x0000000000000000000000000000000000000000000000000000000000000001","id":1}"#;
+	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
+	assert_eq!(*key_server.new_servers_sets.lock(), vec![new_servers_set.clone()]);
+
+	// check its progress
+	let request = r#"{"jsonrpc": "2.0", "method": "secretstore_adminSessions", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"id":"0x0000000000000000000000000000000000000000000000000000000000000001","type":"servers set change","master":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000002","isFinished":false,"keysTotal":3,"keysProcessed":1}],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	// and abort it
+	let signature: H520 = sign(admin.secret(), &1.into()).unwrap().into();
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "secretstore_abortAdminSession", "params":["0x0000000000000000000000000000000000000000000000000000000000000001", {}], "id": 1}}"#,
+		serde_json::to_string(&signature).unwrap());
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
+	assert!(key_server.sessions.lock().is_empty());
+
+	// aborting unknown session fails
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32025,"message":"Secret Store administrative call failed.","data":"no active session with given id"},"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_secretstore_admin_rejects_sets_signed_with_different_keys() {
+	let key_server = Arc::new(TestKeyServerAdmin::default());
+	let io = io(Some(key_server.clone()));
+	let old_servers_set = servers_set(3);
+	let new_servers_set: BTreeSet<_> = old_servers_set.iter().cloned().take(2).collect();
+
+	let request = change_servers_set_request(&Random.generate().unwrap(), &old_servers_set,
+		&Random.generate().unwrap(), &new_servers_set);
+	let response = io.handle_request_sync(&request).unwrap();
+	assert!(response.contains(r#""code":-32602"#));
+	assert!(key_server.sessions.lock().is_empty());
+	assert!(key_server.new_servers_sets.lock().is_empty());
+}
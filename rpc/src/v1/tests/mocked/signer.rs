@@ -16,10 +16,12 @@
 
 use std::sync::Arc;
 use std::str::FromStr;
+use std::time::Duration;
 use ethereum_types::{H520, U256, Address};
+use ethkey::{self, Generator, KeyPair, Random, Signature};
 use bytes::ToPretty;
 
-use accounts::AccountProvider;
+use accounts::{AccountProvider, HardwareError, HardwareWallets, TransactionInfo, WalletInfo};
 use ethcore::client::TestBlockChainClient;
 use parity_runtime::Runtime;
 use parking_lot::Mutex;
@@ -28,11 +30,12 @@ use types::transaction::{Transaction, Action, SignedTransaction};
 
 use serde_json;
 use jsonrpc_core::IoHandler;
+use jsonrpc_core::futures::Future;
 use v1::{SignerClient, Signer, Origin};
 use v1::metadata::Metadata;
 use v1::tests::helpers::TestMinerService;
 use v1::types::Bytes as RpcBytes;
-use v1::helpers::{nonce, FilledTransactionRequest, ConfirmationPayload};
+use v1::helpers::{errors, nonce, FilledTransactionRequest, ConfirmationPayload, ConfirmationRequest};
 use v1::helpers::external_signer::{SigningQueue, SignerService};
 use v1::helpers::dispatch::{self, FullDispatcher, eth_data_hash};
 
@@ -58,9 +61,15 @@ fn miner_service() -> Arc<TestMinerService> {
 }
 
 fn signer_tester() -> SignerTester {
+	signer_tester_with(SignerService::new_test(false))
+}
+
+fn signer_tester_with(signer: SignerService) -> SignerTester {
+	signer_tester_with_accounts(Arc::new(signer), accounts_provider())
+}
+
+fn signer_tester_with_accounts(signer: Arc<SignerService>, accounts: Arc<AccountProvider>) -> SignerTester {
 	let runtime = Runtime::with_thread_count(1);
-	let signer = Arc::new(SignerService::new_test(false));
-	let accounts = accounts_provider();
 	let account_signer = Arc::new(dispatch::Signer::new(accounts.clone()));
 	let client = blockchain_client();
 	let miner = miner_service();
@@ -79,6 +88,75 @@ fn signer_tester() -> SignerTester {
 	}
 }
 
+/// Hardware wallet signing messages with a single key, recording the requests
+/// the signer shows as in progress while the device is asked to sign.
+struct TestHardwareWallet {
+	key: KeyPair,
+	signer: Arc<SignerService>,
+	confirm: bool,
+	in_progress: Arc<Mutex<Vec<ConfirmationRequest>>>,
+}
+
+impl HardwareWallets for TestHardwareWallet {
+	fn list_wallets(&self) -> Vec<WalletInfo> {
+		self.wallet_info(&self.key.address()).into_iter().collect()
+	}
+
+	fn list_locked_wallets(&self) -> Result<Vec<String>, HardwareError> {
+		Ok(vec![])
+	}
+
+	fn wallet_info(&self, address: &Address) -> Option<WalletInfo> {
+		if *address != self.key.address() {
+			return None;
+		}
+		Some(WalletInfo {
+			name: "Test".into(),
+			manufacturer: "Parity".into(),
+			serial: "1".into(),
+			address: *address,
+		})
+	}
+
+	fn sign_message(&self, _address: &Address, msg: &[u8]) -> Result<Signature, HardwareError> {
+		*self.in_progress.lock() = self.signer.requests_in_progress();
+		if !self.confirm {
+			return Err(HardwareError::KeyNotFound);
+		}
+		Ok(ethkey::sign(self.key.secret(), &eth_data_hash(msg.to_vec())).unwrap())
+	}
+
+	fn sign_transaction(&self, _address: &Address, _t_info: &TransactionInfo, _encoded_transaction: &[u8]) -> Result<Signature, HardwareError> {
+		Err(HardwareError::KeyNotFound)
+	}
+
+	fn pin_matrix_ack(&self, _path: &str, _pin: &str) -> Result<bool, HardwareError> {
+		Ok(false)
+	}
+
+	fn list_passphrase_requests(&self) -> Result<Vec<String>, HardwareError> {
+		Ok(vec![])
+	}
+
+	fn passphrase_ack(&self, _path: &str, _passphrase: &str) -> Result<bool, HardwareError> {
+		Ok(false)
+	}
+}
+
+fn hardware_signer_tester(confirm: bool) -> (SignerTester, KeyPair, Arc<Mutex<Vec<ConfirmationRequest>>>) {
+	let signer = Arc::new(SignerService::new_test(false));
+	let key = Random.generate().unwrap();
+	let in_progress = Arc::new(Mutex::new(vec![]));
+	let wallet = TestHardwareWallet {
+		key: key.clone(),
+		signer: signer.clone(),
+		confirm,
+		in_progress: in_progress.clone(),
+	};
+	let accounts = AccountProvider::transient_provider().with_hardware_wallets(Box::new(wallet));
+	(signer_tester_with_accounts(signer, Arc::new(accounts)), key, in_progress)
+}
+
 #[test]
 fn should_return_list_of_items_to_confirm() {
 	// given
@@ -136,6 +214,35 @@ fn should_reject_transaction_from_queue_without_dispatching() {
 	assert_eq!(tester.miner.imported_transactions.lock().len(), 0);
 }
 
+#[test]
+fn should_reject_transaction_when_confirmation_times_out() {
+	// given
+	let tester = signer_tester_with(SignerService::new_test_with_timeout(false, Duration::from_secs(0)));
+	let (_, confirmation_future) = tester.signer.add_request(ConfirmationPayload::SendTransaction(FilledTransactionRequest {
+		from: Address::from(1),
+		used_default_from: false,
+		to: Some(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap()),
+		gas_price: U256::from(10_000),
+		gas: U256::from(10_000_000),
+		value: U256::from(1),
+		data: vec![],
+		nonce: None,
+		condition: None,
+	}), Origin::Unknown).unwrap();
+	assert_eq!(tester.signer.requests().len(), 1);
+
+	// when
+	let expired = tester.signer.expire_requests();
+
+	// then
+	assert_eq!(expired, vec![1.into()]);
+	assert_eq!(confirmation_future.wait(), Err(errors::request_timed_out()));
+	let request = r#"{"jsonrpc":"2.0","method":"signer_requestsToConfirm","params":[],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+	assert_eq!(tester.miner.imported_transactions.lock().len(), 0);
+}
+
 #[test]
 fn should_not_remove_transaction_if_password_is_invalid() {
 	// given
@@ -557,3 +664,51 @@ fn should_generate_new_token() {
 	// then
 	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
 }
+
+#[test]
+fn should_confirm_data_sign_with_hardware_wallet_and_show_it_while_signing() {
+	// given
+	let (tester, key, in_progress) = hardware_signer_tester(true);
+	let _confirmation_future = tester.signer.add_request(ConfirmationPayload::EthSignMessage(
+		key.address(),
+		vec![1, 2, 3, 4].into(),
+	), Origin::Unknown).unwrap();
+
+	let data_hash = eth_data_hash(vec![1, 2, 3, 4].into());
+	let signature = H520(ethkey::sign(key.secret(), &data_hash).unwrap().into_electrum());
+	let signature = format!("{:?}", signature);
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"signer_confirmRequest","params":["0x1",{},""],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":""#.to_owned() + &signature + r#"","id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+	let in_progress = in_progress.lock();
+	assert_eq!(in_progress.len(), 1);
+	assert_eq!(in_progress[0].payload, ConfirmationPayload::EthSignMessage(key.address(), vec![1, 2, 3, 4].into()));
+	assert_eq!(tester.signer.requests().len(), 0);
+	assert_eq!(tester.signer.requests_in_progress().len(), 0);
+}
+
+#[test]
+fn should_return_request_to_queue_when_hardware_wallet_fails() {
+	// given
+	let (tester, key, in_progress) = hardware_signer_tester(false);
+	let _confirmation_future = tester.signer.add_request(ConfirmationPayload::EthSignMessage(
+		key.address(),
+		vec![1, 2, 3, 4].into(),
+	), Origin::Unknown).unwrap();
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"signer_confirmRequest","params":["0x1",{},""],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32023,"message":"Error signing message with hardware_wallet","data":"NotFound"},"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+	assert_eq!(in_progress.lock().len(), 1);
+	assert_eq!(tester.signer.requests().len(), 1);
+	let request = r#"{"jsonrpc":"2.0","method":"signer_requestsInProgress","params":[],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+}
@@ -30,6 +30,7 @@ pub mod private;
 pub mod pubsub;
 pub mod rpc;
 pub mod secretstore;
+pub mod secretstore_admin;
 pub mod signer;
 pub mod traces;
 pub mod transactions_pool;
@@ -49,6 +50,7 @@ pub use self::private::Private;
 pub use self::pubsub::PubSub;
 pub use self::rpc::Rpc;
 pub use self::secretstore::SecretStore;
+pub use self::secretstore_admin::SecretStoreAdmin;
 pub use self::signer::Signer;
 pub use self::traces::Traces;
 pub use self::transactions_pool::TransactionsPool;
@@ -27,7 +27,7 @@ use v1::types::{
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, ChainStatus, Log, Filter,
-	RichHeader, Receipt,
+	RichHeader, Receipt, LightCheckpoint,
 };
 
 /// Parity-specific rpc interface.
@@ -240,4 +240,9 @@ pub trait Parity {
 	/// Submit raw block to be published to the network
 	#[rpc(name = "parity_submitRawBlock")]
 	fn submit_raw_block(&self, Bytes) -> Result<H256>;
+
+	/// Returns the hardcoded sync checkpoint of the light client.
+	/// `None` for full nodes and light clients syncing from the genesis.
+	#[rpc(name = "parity_lightCheckpoint")]
+	fn light_checkpoint(&self) -> Result<Option<LightCheckpoint>>;
 }
@@ -23,7 +23,7 @@ use ethereum_types::{H160, H256, H520};
 use ethkey::Password;
 use ethstore::KeyFile;
 use v1::types::{DeriveHash, DeriveHierarchical, ExtAccountInfo};
-use v1::types::{AccountInfo, HwAccountInfo};
+use v1::types::{AccountInfo, AccountImportResult, AccountPolicy, Bytes, HwAccountInfo, RefusedTransaction};
 
 /// Parity-specific read-only accounts rpc interface.
 #[rpc]
@@ -40,6 +40,10 @@ pub trait ParityAccountsInfo {
 	#[rpc(name = "parity_lockedHardwareAccountsInfo")]
 	fn locked_hardware_accounts_info(&self) -> Result<Vec<String>>;
 
+	/// Get a list of paths to hardware wallets waiting for a passphrase
+	#[rpc(name = "parity_hardwarePassphraseRequests")]
+	fn hardware_passphrase_requests(&self) -> Result<Vec<String>>;
+
 	/// Returns default account for dapp.
 	#[rpc(name = "parity_defaultAccount")]
 	fn default_account(&self) -> Result<H160>;
@@ -62,11 +66,35 @@ pub trait ParityAccounts {
 	#[rpc(name = "parity_newAccountFromWallet")]
 	fn new_account_from_wallet(&self, String, Password) -> Result<H160>;
 
+	/// Imports many presale wallets at once.
+	/// Second parameter contains passwords for the wallets (and the new accounts), in the same order.
+	/// Every wallet is imported independently and a result is returned for each of them.
+	#[rpc(name = "parity_importPresaleWallets")]
+	fn import_presale_wallets(&self, Vec<String>, Vec<Password>) -> Result<Vec<AccountImportResult>>;
+
+	/// Imports many JSON keyfiles at once.
+	/// Second parameter contains passwords for the keyfiles, in the same order.
+	/// Every keyfile is imported independently and a result is returned for each of them.
+	#[rpc(name = "parity_importKeystoreFiles")]
+	fn import_keystore_files(&self, Vec<String>, Vec<Password>) -> Result<Vec<AccountImportResult>>;
+
 	/// Creates new account from the given raw secret.
 	/// Second parameter is password for the new account.
 	#[rpc(name = "parity_newAccountFromSecret")]
 	fn new_account_from_secret(&self, H256, Password) -> Result<H160>;
 
+	/// Creates new hierarchical deterministic account from the given seed (BIP-32 master key).
+	/// Second parameter is password for the new account.
+	/// Child accounts can be derived using `parity_deriveAccountHierarchical`.
+	#[rpc(name = "parity_newAccountFromSeed")]
+	fn new_account_from_seed(&self, Bytes, Password) -> Result<H160>;
+
+	/// Creates new account from the given BIP-39 mnemonic phrase.
+	/// The key is derived at `m/44'/60'/0'/0/index` path, where `index` is the second parameter.
+	/// Third parameter is password for the new account.
+	#[rpc(name = "parity_newAccountFromMnemonic")]
+	fn new_account_from_mnemonic(&self, String, u32, Password) -> Result<H160>;
+
 	/// Returns true if given `password` would unlock given `account`.
 	/// Arguments: `account`, `password`.
 	#[rpc(name = "parity_testPassword")]
@@ -95,6 +123,19 @@ pub trait ParityAccounts {
 	#[rpc(name = "parity_setAccountMeta")]
 	fn set_account_meta(&self, H160, String) -> Result<bool>;
 
+	/// Returns signing policy of given account.
+	#[rpc(name = "parity_accountPolicy")]
+	fn account_policy(&self, H160) -> Result<Option<AccountPolicy>>;
+
+	/// Returns the most recent transactions of given account refused by its signing policy, oldest first.
+	/// Refused transactions are never signed, so they don't show up in `parity_localTransactions`.
+	#[rpc(name = "parity_accountPolicyViolations")]
+	fn account_policy_violations(&self, H160) -> Result<Vec<RefusedTransaction>>;
+
+	/// Restricts transactions the account is allowed to sign. Empty policy removes all restrictions.
+	#[rpc(name = "parity_setAccountPolicy")]
+	fn set_account_policy(&self, H160, AccountPolicy) -> Result<bool>;
+
 	/// Imports a number of Geth accounts, with the list provided as the argument.
 	#[rpc(name = "parity_importGethAccounts")]
 	fn import_geth_accounts(&self, Vec<H160>) -> Result<Vec<H160>>;
@@ -127,6 +168,10 @@ pub trait ParityAccounts {
 	#[rpc(name = "parity_changeVaultPassword")]
 	fn change_vault_password(&self, String, Password) -> Result<bool>;
 
+	/// Rename opened vault.
+	#[rpc(name = "parity_renameVault")]
+	fn rename_vault(&self, String, String) -> Result<bool>;
+
 	/// Change vault of the given address.
 	#[rpc(name = "parity_changeVault")]
 	fn change_vault(&self, H160, String) -> Result<bool>;
@@ -150,9 +195,16 @@ pub trait ParityAccounts {
 	#[rpc(name = "parity_deriveAddressIndex")]
 	fn derive_key_index(&self, H160, Password, DeriveHierarchical, bool) -> Result<H160>;
 
+	/// Derive new address from given account address using
+	/// BIP-32 derivation path (i.e. `m/44'/60'/0'/0/0`).
+	/// Resulting address can be either saved as a new account (with the same password).
+	#[rpc(name = "parity_deriveAccountHierarchical")]
+	fn derive_account_hierarchical(&self, H160, Password, String, bool) -> Result<H160>;
+
 	/// Exports an account with given address if provided password matches.
+	/// The exported key file is re-encrypted with the new password, if given.
 	#[rpc(name = "parity_exportAccount")]
-	fn export_account(&self, H160, Password) -> Result<KeyFile>;
+	fn export_account(&self, H160, Password, Option<Password>) -> Result<KeyFile>;
 
 	/// Sign raw hash with the key corresponding to address and password.
 	#[rpc(name = "parity_signMessage")]
@@ -161,4 +213,8 @@ pub trait ParityAccounts {
 	/// Send a PinMatrixAck to a hardware wallet, unlocking it
 	#[rpc(name = "parity_hardwarePinMatrixAck")]
 	fn hardware_pin_matrix_ack(&self, String, String) -> Result<bool>;
+
+	/// Send a passphrase to a hardware wallet, opening the wallet it protects
+	#[rpc(name = "parity_hardwarePassphraseAck")]
+	fn hardware_passphrase_ack(&self, String, String) -> Result<bool>;
 }
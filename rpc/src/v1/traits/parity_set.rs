@@ -20,7 +20,7 @@ use ethereum_types::{H160, H256, U256};
 use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
 
-use v1::types::{Bytes, ReleaseInfo, Transaction};
+use v1::types::{Bytes, ConfigReload, ReleaseInfo, Transaction};
 
 /// Parity-specific rpc interface for operations altering the account-related settings.
 #[rpc]
@@ -121,4 +121,9 @@ pub trait ParitySet {
 	/// Returns `true` when transaction was removed, `false` if it was not found.
 	#[rpc(name = "parity_removeTransaction")]
 	fn remove_transaction(&self, H256) -> Result<Option<Transaction>>;
+
+	/// Reloads the config file, applying changed logging filters, minimal gas price
+	/// and reserved peers. Returns the changed settings and those requiring a restart.
+	#[rpc(name = "parity_reloadConfig")]
+	fn reload_config(&self) -> Result<ConfigReload>;
 }
@@ -0,0 +1,45 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! SecretStore administrative sessions rpc interface.
+
+use std::collections::BTreeSet;
+
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use ethereum_types::{H256, H512, H520};
+use v1::types::SecretStoreAdminSession;
+
+/// SecretStore administrative sessions rpc interface.
+#[rpc]
+pub trait SecretStoreAdmin {
+	/// Start servers set change session on the key server, running on this node.
+	/// Signatures are made with administrator key over hashes of ordered servers sets (see `secretstore_serversSetHash`).
+	/// Returns id of the started session.
+	/// Arguments: `old_servers_set`, `old_set_signature`, `new_servers_set`, `new_set_signature`.
+	#[rpc(name = "secretstore_changeServersSet")]
+	fn change_servers_set(&self, BTreeSet<H512>, H520, BTreeSet<H512>, H520) -> Result<H256>;
+
+	/// Returns administrative sessions, which are currently active on this node.
+	#[rpc(name = "secretstore_adminSessions")]
+	fn admin_sessions(&self) -> Result<Vec<SecretStoreAdminSession>>;
+
+	/// Abort active administrative session.
+	/// Signature is made with administrator key over the session id.
+	/// Arguments: `session_id`, `signature`.
+	#[rpc(name = "secretstore_abortAdminSession")]
+	fn abort_admin_session(&self, H256, H520) -> Result<bool>;
+}
@@ -33,6 +33,11 @@ pub trait Signer {
 	#[rpc(name = "signer_requestsToConfirm")]
 	fn requests_to_confirm(&self) -> Result<Vec<ConfirmationRequest>>;
 
+	/// Returns a list of confirmed items that are still being signed,
+	/// e.g. awaiting confirmation on a hardware wallet.
+	#[rpc(name = "signer_requestsInProgress")]
+	fn requests_in_progress(&self) -> Result<Vec<ConfirmationRequest>>;
+
 	/// Confirm specific request.
 	#[rpc(name = "signer_confirmRequest")]
 	fn confirm_request(&self, U256, TransactionModification, String) -> BoxFuture<ConfirmationResponse>;
@@ -60,6 +60,17 @@ pub struct ExtAccountInfo {
 	pub uuid: Option<String>,
 }
 
+/// Result of importing a single account as part of a batch.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct AccountImportResult {
+	/// Address of the imported account
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub address: Option<H160>,
+	/// Reason why the account couldn't be imported
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+}
+
 /// Hardware wallet information.
 #[derive(Debug, Default, Clone, PartialEq, Serialize)]
 pub struct HwAccountInfo {
@@ -69,6 +80,34 @@ pub struct HwAccountInfo {
 	pub manufacturer: String,
 }
 
+/// Signing policy of a local account.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct AccountPolicy {
+	/// Allowed recipients.
+	pub recipients: Option<Vec<H160>>,
+	/// Maximum value per transaction.
+	pub max_value: Option<U256>,
+	/// Maximum gas price.
+	pub max_gas_price: Option<U256>,
+	/// Allowed methods (4-byte selectors).
+	pub methods: Option<Vec<Bytes>>,
+}
+
+/// Transaction refused by an account signing policy.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefusedTransaction {
+	/// Hash of the unsigned transaction.
+	pub hash: H256,
+	/// Nonce of the transaction.
+	pub nonce: U256,
+	/// Violated constraint.
+	pub reason: String,
+	/// Time of the refusal (seconds since unix epoch).
+	pub timestamp: u64,
+}
+
 /// account derived from a signature
 /// as well as information that tells if it is valid for
 /// the current chain
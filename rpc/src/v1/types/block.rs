@@ -87,6 +87,9 @@ pub struct Block {
 	pub transactions: BlockTransactions,
 	/// Size in bytes
 	pub size: Option<U256>,
+	/// Whether the block is finalized. Only present on chains with finality.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub finalized: Option<bool>,
 }
 
 /// Block header representation.
@@ -244,6 +247,7 @@ mod tests {
 			uncles: vec![],
 			transactions: BlockTransactions::Hashes(vec![].into()),
 			size: Some(69.into()),
+			finalized: None,
 		};
 		let serialized_block = serde_json::to_string(&block).unwrap();
 		let rich_block = RichBlock {
@@ -282,6 +286,7 @@ mod tests {
 			uncles: vec![],
 			transactions: BlockTransactions::Hashes(vec![].into()),
 			size: None,
+			finalized: None,
 		};
 		let serialized_block = serde_json::to_string(&block).unwrap();
 		let rich_block = RichBlock {
@@ -297,6 +302,35 @@ mod tests {
 		assert_eq!(serialized_rich_block, r#"{"author":"0x0000000000000000000000000000000000000000","difficulty":"0x0","extraData":"0x","gasLimit":"0x0","gasUsed":"0x0","hash":"0x0000000000000000000000000000000000000000000000000000000000000000","logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","miner":"0x0000000000000000000000000000000000000000","mixHash":"0x0000000000000000000000000000000000000000000000000000000000000000","nonce":"0x0000000000000000","number":"0x0","parentHash":"0x0000000000000000000000000000000000000000000000000000000000000000","receiptsRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","sealFields":["0x","0x"],"sha3Uncles":"0x0000000000000000000000000000000000000000000000000000000000000000","size":null,"stateRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","timestamp":"0x0","totalDifficulty":"0x0","transactions":[],"transactionsRoot":"0x0000000000000000000000000000000000000000000000000000000000000000","uncles":[]}"#);
 	}
 
+	#[test]
+	fn serializes_finalized_when_known() {
+		let block = Block {
+			hash: Some(H256::default()),
+			parent_hash: H256::default(),
+			uncles_hash: H256::default(),
+			author: H160::default(),
+			miner: H160::default(),
+			state_root: H256::default(),
+			transactions_root: H256::default(),
+			receipts_root: H256::default(),
+			number: Some(U256::default()),
+			gas_used: U256::default(),
+			gas_limit: U256::default(),
+			extra_data: Bytes::default(),
+			logs_bloom: Some(H2048::default()),
+			timestamp: U256::default(),
+			difficulty: U256::default(),
+			total_difficulty: Some(U256::default()),
+			seal_fields: vec![],
+			uncles: vec![],
+			transactions: BlockTransactions::Hashes(vec![].into()),
+			size: None,
+			finalized: Some(true),
+		};
+
+		assert!(serde_json::to_string(&block).unwrap().ends_with(r#""size":null,"finalized":true}"#));
+	}
+
 	#[test]
 	fn test_serialize_header() {
 		let header = Header {
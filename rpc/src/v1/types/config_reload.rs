@@ -0,0 +1,48 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Outcome of reloading the configuration.
+
+/// Settings changed by reloading the configuration.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigReload {
+	/// Settings which were changed.
+	pub applied: Vec<String>,
+	/// Settings which couldn't be changed, with the reason.
+	pub failed: Vec<String>,
+	/// Changed options which are only applied on restart.
+	pub requires_restart: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::ConfigReload;
+
+	#[test]
+	fn config_reload_serialization() {
+		let reload = ConfigReload {
+			applied: vec!["logging".into()],
+			failed: vec![],
+			requires_restart: vec!["port".into()],
+		};
+
+		let serialized = serde_json::to_string(&reload).unwrap();
+		assert_eq!(serialized, r#"{"applied":["logging"],"failed":[],"requiresRestart":["port"]}"#);
+	}
+}
@@ -0,0 +1,72 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Hardcoded sync checkpoint of a light client.
+
+use ethereum_types::{H256, U256};
+use light::client::Checkpoint;
+
+/// Describes the checkpoint a light client synchronizes from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LightCheckpoint {
+	/// File the checkpoint was loaded from, `None` if it's taken from the chain specification.
+	pub file: Option<String>,
+	/// Number of the checkpoint block.
+	pub number: u64,
+	/// Hash of the checkpoint block.
+	pub hash: H256,
+	/// Total difficulty including the checkpoint block.
+	pub total_difficulty: U256,
+	/// Number of CHT roots covering the blocks before the checkpoint.
+	pub cht_count: usize,
+}
+
+impl LightCheckpoint {
+	/// Describe the checkpoint a light client database was synchronized from, loaded from `file` if any.
+	pub fn new(checkpoint: &Checkpoint, file: Option<String>) -> Self {
+		LightCheckpoint {
+			file,
+			number: checkpoint.number,
+			hash: checkpoint.hash,
+			total_difficulty: checkpoint.total_difficulty,
+			cht_count: checkpoint.cht_count,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::LightCheckpoint;
+	use ethereum_types::{H256, U256};
+	use serde_json;
+
+	#[test]
+	fn serialize_light_checkpoint() {
+		let checkpoint = LightCheckpoint {
+			file: Some("checkpoint.json".into()),
+			number: 4097,
+			hash: H256::from(U256::from(5)),
+			total_difficulty: 0x400000.into(),
+			cht_count: 2,
+		};
+
+		assert_eq!(
+			serde_json::to_string(&checkpoint).unwrap(),
+			r#"{"file":"checkpoint.json","number":4097,"hash":"0x0000000000000000000000000000000000000000000000000000000000000005","totalDifficulty":"0x400000","chtCount":2}"#
+		);
+	}
+}
@@ -24,12 +24,14 @@ mod block;
 mod block_number;
 mod bytes;
 mod call_request;
+mod config_reload;
 mod confirmations;
 mod consensus_status;
 mod derivation;
 mod filter;
 mod histogram;
 mod index;
+mod light_checkpoint;
 mod log;
 mod node_kind;
 mod provenance;
@@ -49,11 +51,12 @@ mod eip191;
 pub mod pubsub;
 
 pub use self::eip191::{EIP191Version, PresignedTransaction};
-pub use self::account_info::{AccountInfo, ExtAccountInfo, HwAccountInfo, EthAccount, StorageProof, RecoveredAccount};
+pub use self::account_info::{AccountInfo, AccountPolicy, AccountImportResult, ExtAccountInfo, HwAccountInfo, EthAccount, RefusedTransaction, StorageProof, RecoveredAccount};
 pub use self::bytes::Bytes;
 pub use self::block::{RichBlock, Block, BlockTransactions, Header, RichHeader, Rich};
 pub use self::block_number::{BlockNumber, LightBlockNumber, block_number_to_id};
 pub use self::call_request::CallRequest;
+pub use self::config_reload::ConfigReload;
 pub use self::confirmations::{
 	ConfirmationPayload, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken,
 	TransactionModification, EIP191SignRequest, EthSignRequest, DecryptRequest, Either
@@ -63,15 +66,16 @@ pub use self::derivation::{DeriveHash, DeriveHierarchical, Derive};
 pub use self::filter::{Filter, FilterChanges};
 pub use self::histogram::Histogram;
 pub use self::index::Index;
+pub use self::light_checkpoint::LightCheckpoint;
 pub use self::log::Log;
 pub use self::node_kind::{NodeKind, Availability, Capability};
 pub use self::provenance::Origin;
 pub use self::receipt::Receipt;
 pub use self::rpc_settings::RpcSettings;
-pub use self::secretstore::EncryptedDocumentKey;
+pub use self::secretstore::{EncryptedDocumentKey, AdminSession as SecretStoreAdminSession};
 pub use self::sync::{
 	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
-	TransactionStats, ChainStatus, EthProtocolInfo, PipProtocolInfo,
+	TransactionStats, ChainStatus, EthProtocolInfo, PipProtocolInfo, PipCreditsInfo, PipServedInfo,
 };
 pub use self::trace::{LocalizedTrace, TraceResults, TraceResultsWithTransactionHash};
 pub use self::trace_filter::TraceFilter;
@@ -69,6 +69,8 @@ pub enum Kind {
 	NewPendingTransactions,
 	/// Node syncing status subscription.
 	Syncing,
+	/// Finalized block headers subscription.
+	NewFinalizedHeads,
 }
 
 /// Subscription kind.
@@ -113,6 +115,7 @@ mod tests {
 		assert_eq!(serde_json::from_str::<Kind>(r#""logs""#).unwrap(), Kind::Logs);
 		assert_eq!(serde_json::from_str::<Kind>(r#""newPendingTransactions""#).unwrap(), Kind::NewPendingTransactions);
 		assert_eq!(serde_json::from_str::<Kind>(r#""syncing""#).unwrap(), Kind::Syncing);
+		assert_eq!(serde_json::from_str::<Kind>(r#""newFinalizedHeads""#).unwrap(), Kind::NewFinalizedHeads);
 	}
 
 	#[test]
@@ -14,7 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use ethereum_types::H512;
+use ethereum_types::{H256, H512};
+use v1::helpers::secretstore_admin::KeyServerAdminSession;
 use v1::types::Bytes;
 
 /// Encrypted document key.
@@ -29,10 +30,42 @@ pub struct EncryptedDocumentKey {
 	pub encrypted_key: Bytes,
 }
 
+/// Administrative session, which is active on the Secret Store key server.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminSession {
+	/// Session id.
+	pub id: H256,
+	/// Session type.
+	#[serde(rename = "type")]
+	pub session_type: String,
+	/// Public of the node, which has started the session.
+	pub master: H512,
+	/// Is session finished.
+	pub is_finished: bool,
+	/// Number of keys to process by servers set change session. Known on master node only.
+	pub keys_total: Option<usize>,
+	/// Number of keys processed by servers set change session. Known on master node only.
+	pub keys_processed: Option<usize>,
+}
+
+impl From<KeyServerAdminSession> for AdminSession {
+	fn from(session: KeyServerAdminSession) -> Self {
+		AdminSession {
+			id: session.id,
+			session_type: session.session_type,
+			master: session.master,
+			is_finished: session.is_finished,
+			keys_total: session.keys_total,
+			keys_processed: session.keys_processed,
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
-	use super::EncryptedDocumentKey;
+	use super::{EncryptedDocumentKey, AdminSession};
 
 	#[test]
 	fn test_serialize_encrypted_document_key() {
@@ -50,4 +83,19 @@ mod tests {
 		assert_eq!(deserialized.encrypted_point, 2.into());
 		assert_eq!(deserialized.encrypted_key, vec![3].into());
 	}
+
+	#[test]
+	fn test_serialize_admin_session() {
+		let session = AdminSession {
+			id: 1.into(),
+			session_type: "servers set change".into(),
+			master: 2.into(),
+			is_finished: false,
+			keys_total: Some(10),
+			keys_processed: None,
+		};
+
+		let serialized = serde_json::to_string(&session).unwrap();
+		assert_eq!(serialized, r#"{"id":"0x0000000000000000000000000000000000000000000000000000000000000001","type":"servers set change","master":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000002","isFinished":false,"keysTotal":10,"keysProcessed":null}"#);
+	}
 }
@@ -18,6 +18,7 @@ use network::client_version::ClientVersion;
 use std::collections::BTreeMap;
 
 use ethereum_types::{U256, H512};
+use light::net::{PeerCredits, ServedRequests};
 use sync::{self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats};
 use serde::{Serialize, Serializer};
 
@@ -114,6 +115,10 @@ pub struct PipProtocolInfo {
 	pub difficulty: U256,
 	/// SHA3 of peer best block hash
 	pub head: String,
+	/// Request credits of the peer
+	pub credits: Option<PipCreditsInfo>,
+	/// Requests served to the peer
+	pub served: Option<PipServedInfo>,
 }
 
 impl From<sync::PipProtocolInfo> for PipProtocolInfo {
@@ -122,6 +127,57 @@ impl From<sync::PipProtocolInfo> for PipProtocolInfo {
 			version: info.version,
 			difficulty: info.difficulty,
 			head: format!("{:x}", info.head),
+			credits: info.credits.map(Into::into),
+			served: info.served.map(Into::into),
+		}
+	}
+}
+
+/// Peer PIP request credits
+#[derive(Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipCreditsInfo {
+	/// Credits the peer has left for requests to this node
+	pub served: U256,
+	/// Maximum credits of the peer for requests to this node
+	pub served_limit: U256,
+	/// Credits of the peer recharged per second
+	pub served_recharge_rate: U256,
+	/// Credits left for requests to the peer, if it's a server
+	pub available: Option<U256>,
+	/// Maximum credits for requests to the peer, if it's a server
+	pub available_limit: Option<U256>,
+}
+
+impl From<PeerCredits> for PipCreditsInfo {
+	fn from(credits: PeerCredits) -> Self {
+		PipCreditsInfo {
+			served: credits.local,
+			served_limit: credits.local_limit,
+			served_recharge_rate: credits.local_recharge_rate,
+			available: credits.remote,
+			available_limit: credits.remote_limit,
+		}
+	}
+}
+
+/// Requests served to a PIP peer
+#[derive(Default, Debug, Serialize)]
+pub struct PipServedInfo {
+	/// Number of requests served to the peer
+	pub served: u64,
+	/// Number of requests delayed due to the peer exceeding its budget
+	pub delayed: u64,
+	/// Number of request packets waiting to be served
+	pub queued: usize,
+}
+
+impl From<ServedRequests> for PipServedInfo {
+	fn from(served: ServedRequests) -> Self {
+		PipServedInfo {
+			served: served.served,
+			delayed: served.delayed,
+			queued: served.queued,
 		}
 	}
 }
@@ -16,11 +16,15 @@
 
 use std::sync::Arc;
 use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 use parking_lot::{Mutex, RwLock};
 use call_contract::CallContract;
-use ethcore::client::{BlockId, ChainNotify, NewBlocks};
-use ethereum_types::Address;
-use ethabi::FunctionOutputDecoder;
+use common_types::filter::Filter;
+use common_types::log_entry::LocalizedLogEntry;
+use ethcore::client::{BlockChainClient, BlockId, ChainNotify, NewBlocks};
+use ethereum_types::{H256, Address};
+use ethabi::{FunctionOutputDecoder, RawLog};
+use hash::keccak;
 use trusted_client::TrustedClient;
 use types::{Error, ServerKeyId, ContractAddress};
 
@@ -28,6 +32,18 @@ use_contract!(acl_storage, "res/acl_storage.json");
 
 const ACL_CHECKER_CONTRACT_REGISTRY_NAME: &'static str = "secretstore_acl_checker";
 
+/// Permissions of requester to access the document have been changed.
+const PERMISSIONS_CHANGED_EVENT_NAME: &'static [u8] = &*b"PermissionsChanged(address,bytes32)";
+
+/// Denied checks are cached for this fraction of the grants lifetime.
+const DENIALS_TTL_DIVISOR: u32 = 10;
+/// Maximal number of cached checks.
+const MAX_CACHED_CHECKS: usize = 16 * 1024;
+
+lazy_static! {
+	static ref PERMISSIONS_CHANGED_EVENT_NAME_HASH: H256 = keccak(PERMISSIONS_CHANGED_EVENT_NAME);
+}
+
 /// ACL storage of Secret Store
 pub trait AclStorage: Send + Sync {
 	/// Check if requestor can access document with hash `document`
@@ -48,6 +64,30 @@ struct CachedContract {
 	address_source: ContractAddress,
 	/// Current contract address.
 	contract_address: Option<Address>,
+	/// Results of recent checks.
+	permissions: PermissionsCache,
+}
+
+/// Cache of ACL checks results.
+///
+/// Entries are invalidated by `PermissionsChanged` events of the ACL contract. Contracts
+/// which don't emit these events are covered by expiring entries after the configured TTL.
+struct PermissionsCache {
+	/// Lifetime of granted checks.
+	grants_ttl: Duration,
+	/// Lifetime of denied checks.
+	denials_ttl: Duration,
+	/// Cached checks results with their expiration time.
+	checks: HashMap<(ServerKeyId, Address), (bool, Instant)>,
+}
+
+/// Change of permissions read from the ACL contract logs.
+#[derive(Debug, PartialEq)]
+enum PermissionsChange {
+	/// Permissions of requester to access the document have been changed.
+	Document(ServerKeyId, Address),
+	/// Unknown change. All permissions are potentially changed.
+	All,
 }
 
 /// Dummy ACL storage implementation (check always passed).
@@ -57,10 +97,10 @@ pub struct DummyAclStorage {
 }
 
 impl OnChainAclStorage {
-	pub fn new(trusted_client: TrustedClient, address_source: ContractAddress) -> Result<Arc<Self>, Error> {
+	pub fn new(trusted_client: TrustedClient, address_source: ContractAddress, cache_ttl: Duration) -> Result<Arc<Self>, Error> {
 		let client = trusted_client.get_untrusted();
 		let acl_storage = Arc::new(OnChainAclStorage {
-			contract: Mutex::new(CachedContract::new(trusted_client, address_source)),
+			contract: Mutex::new(CachedContract::new(trusted_client, address_source, cache_ttl)),
 		});
 		client
 			.ok_or_else(|| Error::Internal("Constructing OnChainAclStorage without active Client".into()))?
@@ -77,19 +117,26 @@ impl AclStorage for OnChainAclStorage {
 
 impl ChainNotify for OnChainAclStorage {
 	fn new_blocks(&self, new_blocks: NewBlocks) {
-		if new_blocks.has_more_blocks_to_import { return }
+		let mut contract = self.contract.lock();
+		if new_blocks.has_more_blocks_to_import {
+			// logs of these blocks won't be read => forget everything
+			contract.permissions.clear();
+			return;
+		}
 		if !new_blocks.route.enacted().is_empty() || !new_blocks.route.retracted().is_empty() {
-			self.contract.lock().update_contract_address()
+			contract.update_contract_address();
+			contract.update_permissions(new_blocks.route.enacted(), new_blocks.route.retracted());
 		}
 	}
 }
 
 impl CachedContract {
-	pub fn new(client: TrustedClient, address_source: ContractAddress) -> Self {
+	pub fn new(client: TrustedClient, address_source: ContractAddress, cache_ttl: Duration) -> Self {
 		let mut contract = CachedContract {
 			client,
 			address_source,
 			contract_address: None,
+			permissions: PermissionsCache::new(cache_ttl),
 		};
 		contract.update_contract_address();
 		contract
@@ -102,13 +149,46 @@ impl CachedContract {
 				contract_address);
 
 			self.contract_address = contract_address;
+			self.permissions.clear();
+		}
+	}
+
+	pub fn update_permissions(&mut self, enacted: &[H256], retracted: &[H256]) {
+		// permissions could be changed in retracted blocks
+		if !retracted.is_empty() {
+			self.permissions.clear();
+		}
+
+		let (first_block, last_block) = match (enacted.first(), enacted.last()) {
+			(Some(first_block), Some(last_block)) => (*first_block, *last_block),
+			_ => return,
+		};
+		let contract_address = match self.contract_address {
+			Some(contract_address) => contract_address,
+			None => return,
+		};
+		let logs = self.client.get().and_then(|client| client.logs(Filter {
+			from_block: BlockId::Hash(first_block),
+			to_block: BlockId::Hash(last_block),
+			address: Some(vec![contract_address]),
+			topics: vec![],
+			limit: None,
+		}).ok());
+
+		match logs {
+			Some(logs) => for change in permissions_changes(logs) {
+				self.permissions.invalidate(&change);
+			},
+			None => self.permissions.clear(),
 		}
 	}
 
 	pub fn check(&mut self, requester: Address, document: &ServerKeyId) -> Result<bool, Error> {
-		if let Some(client) = self.client.get() {
+		let client = &self.client;
+		let contract_address = self.contract_address;
+		self.permissions.check(requester, document, Instant::now(), || if let Some(client) = client.get() {
 			// call contract to check accesss
-			match self.contract_address {
+			match contract_address {
 				Some(contract_address) => {
 					let (encoded, decoder) = acl_storage::functions::check_permissions::call(requester, document.clone());
 					let d = client.call_contract(BlockId::Latest, contract_address, encoded)
@@ -120,8 +200,75 @@ impl CachedContract {
 			}
 		} else {
 			Err(Error::Internal("Calling ACL contract without trusted blockchain client".into()))
+		})
+	}
+}
+
+impl PermissionsCache {
+	pub fn new(ttl: Duration) -> Self {
+		PermissionsCache {
+			grants_ttl: ttl,
+			denials_ttl: ttl / DENIALS_TTL_DIVISOR,
+			checks: HashMap::new(),
 		}
 	}
+
+	/// Return cached result of the check, or perform the check and cache its result.
+	pub fn check<F>(&mut self, requester: Address, document: &ServerKeyId, now: Instant, check: F) -> Result<bool, Error>
+		where F: FnOnce() -> Result<bool, Error>
+	{
+		let key = (document.clone(), requester);
+		match self.checks.get(&key) {
+			Some(&(is_allowed, expires)) if now < expires => return Ok(is_allowed),
+			_ => (),
+		}
+
+		let is_allowed = check()?;
+		let ttl = if is_allowed { self.grants_ttl } else { self.denials_ttl };
+		if ttl != Duration::from_secs(0) {
+			if self.checks.len() >= MAX_CACHED_CHECKS {
+				self.checks.retain(|_, &mut (_, expires)| now < expires);
+			}
+			if self.checks.len() < MAX_CACHED_CHECKS {
+				self.checks.insert(key, (is_allowed, now + ttl));
+			}
+		}
+
+		Ok(is_allowed)
+	}
+
+	/// Forget results of checks affected by the change.
+	pub fn invalidate(&mut self, change: &PermissionsChange) {
+		match *change {
+			PermissionsChange::Document(ref document, ref requester) => {
+				self.checks.remove(&(document.clone(), requester.clone()));
+			},
+			PermissionsChange::All => self.clear(),
+		}
+	}
+
+	/// Forget results of all checks.
+	pub fn clear(&mut self) {
+		self.checks.clear();
+	}
+}
+
+/// Read permissions changes from the ACL contract logs.
+fn permissions_changes(logs: Vec<LocalizedLogEntry>) -> Vec<PermissionsChange> {
+	logs.into_iter()
+		.map(|log| {
+			let raw_log: RawLog = (log.entry.topics.into_iter().map(|t| t.0.into()).collect(), log.entry.data).into();
+			if raw_log.topics.first() != Some(&*PERMISSIONS_CHANGED_EVENT_NAME_HASH) {
+				// the contract may change permissions in any other way
+				return PermissionsChange::All;
+			}
+
+			match acl_storage::events::permissions_changed::parse_log(raw_log) {
+				Ok(l) => PermissionsChange::Document(l.document, l.user),
+				Err(_) => PermissionsChange::All,
+			}
+		})
+		.collect()
 }
 
 impl DummyAclStorage {
@@ -143,3 +290,228 @@ impl AclStorage for DummyAclStorage {
 			.unwrap_or(true))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::collections::{BTreeMap, HashMap};
+	use std::sync::Arc;
+	use std::time::{Duration, Instant};
+	use common_types::ids::TransactionId;
+	use common_types::log_entry::{LogEntry, LocalizedLogEntry};
+	use common_types::transaction::{Transaction, Action};
+	use ethcore::client::{BlockChainClient, Client};
+	use ethcore::miner::Miner;
+	use ethcore::spec::Spec;
+	use ethcore::test_helpers::{generate_dummy_client, push_block_with_transactions};
+	use ethereum_types::{H256, Address};
+	use ethkey::{Generator, Random, KeyPair};
+	use parking_lot::Mutex;
+	use sync::{SyncProvider, SyncStatus, SyncState, PeerInfo, Notification, TransactionStats};
+	use trusted_client::TrustedClient;
+	use types::{Error, ContractAddress};
+	use PlainNodeKeyPair;
+	use super::{AclStorage, OnChainAclStorage, PermissionsCache, PermissionsChange, permissions_changes,
+		PERMISSIONS_CHANGED_EVENT_NAME_HASH};
+
+	/// Mocked ACL contract, counting calls.
+	#[derive(Default)]
+	struct DummyAclContract {
+		denied: Mutex<HashMap<(H256, Address), bool>>,
+		calls: Mutex<usize>,
+	}
+
+	impl DummyAclContract {
+		fn check(&self, requester: Address, document: &H256) -> Result<bool, Error> {
+			*self.calls.lock() += 1;
+			Ok(!self.denied.lock().get(&(document.clone(), requester)).cloned().unwrap_or(false))
+		}
+
+		fn set_denied(&self, requester: Address, document: H256, denied: bool) -> LocalizedLogEntry {
+			self.denied.lock().insert((document.clone(), requester), denied);
+			log_entry(vec![*PERMISSIONS_CHANGED_EVENT_NAME_HASH, H256::from(requester), document])
+		}
+
+		fn calls(&self) -> usize {
+			*self.calls.lock()
+		}
+	}
+
+	/// Sync provider of the fully synchronized node.
+	struct SyncedProvider;
+
+	impl SyncProvider for SyncedProvider {
+		fn status(&self) -> SyncStatus { unimplemented!() }
+		fn peers(&self) -> Vec<PeerInfo> { Vec::new() }
+		fn enode(&self) -> Option<String> { None }
+		fn sync_notification(&self) -> Notification<SyncState> { unimplemented!() }
+		fn transactions_stats(&self) -> BTreeMap<H256, TransactionStats> { BTreeMap::new() }
+		fn is_major_syncing(&self) -> bool { false }
+	}
+
+	/// Code of the ACL contract, denying access to the document once it is revoked.
+	///
+	/// `checkPermissions(address,bytes32)` returns `!revoked[document]`; any other call
+	/// with 64 bytes of `(user, document)` input revokes access to the document and emits
+	/// `PermissionsChanged(user, document)`.
+	fn acl_contract_code() -> Vec<u8> {
+		let mut runtime = vec![
+			0x36, 0x60, 0x44, 0x14, 0x60, 0x3a, 0x57,	// jump to check if input is 68 bytes long
+			0x60, 0x01, 0x60, 0x20, 0x35, 0x55,			// revoked[document] = 1
+			0x60, 0x20, 0x35, 0x60, 0x00, 0x35, 0x7f,	// push document, user and event hash
+		];
+		runtime.extend_from_slice(&*PERMISSIONS_CHANGED_EVENT_NAME_HASH);
+		runtime.extend_from_slice(&[
+			0x60, 0x00, 0x60, 0x00, 0xa3, 0x00,			// log3 and stop
+			0x5b, 0x60, 0x24, 0x35, 0x54, 0x15,			// check: !revoked[document]
+			0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,	// return it
+		]);
+
+		// constructor returns the runtime code
+		let mut code = vec![0x60, runtime.len() as u8, 0x80, 0x60, 0x0b, 0x60, 0x00, 0x39, 0x60, 0x00, 0xf3];
+		code.extend(runtime);
+		code
+	}
+
+	fn transact(client: &Arc<Client>, sender: &KeyPair, nonce: u64, action: Action, data: Vec<u8>) -> H256 {
+		let transaction = Transaction {
+			nonce: nonce.into(),
+			action,
+			gas: 200_000.into(),
+			gas_price: 0.into(),
+			value: 0.into(),
+			data,
+		}.sign(sender.secret(), client.signing_chain_id());
+		let hash = transaction.hash();
+		push_block_with_transactions(client, &[transaction]);
+		hash
+	}
+
+	fn log_entry(topics: Vec<H256>) -> LocalizedLogEntry {
+		LocalizedLogEntry {
+			entry: LogEntry { address: Default::default(), topics, data: Vec::new() },
+			block_hash: Default::default(),
+			block_number: 0,
+			transaction_hash: Default::default(),
+			transaction_index: 0,
+			log_index: 0,
+			transaction_log_index: 0,
+		}
+	}
+
+	#[test]
+	fn permissions_changes_are_read_from_logs() {
+		let requester = Address::from(1);
+		let document = H256::from(2);
+		assert_eq!(permissions_changes(vec![
+			log_entry(vec![*PERMISSIONS_CHANGED_EVENT_NAME_HASH, H256::from(requester), document]),
+			log_entry(vec![*PERMISSIONS_CHANGED_EVENT_NAME_HASH]),
+			log_entry(vec![H256::from(3)]),
+		]), vec![
+			PermissionsChange::Document(document, requester),
+			PermissionsChange::All,
+			PermissionsChange::All,
+		]);
+	}
+
+	#[test]
+	fn revoked_permission_is_denied_after_event() {
+		let contract = DummyAclContract::default();
+		let mut cache = PermissionsCache::new(Duration::from_secs(600));
+		let (requester, document) = (Address::from(1), H256::from(2));
+		let now = Instant::now();
+
+		// grant is cached
+		assert!(cache.check(requester, &document, now, || contract.check(requester, &document)).unwrap());
+		assert!(cache.check(requester, &document, now, || contract.check(requester, &document)).unwrap());
+		assert_eq!(contract.calls(), 1);
+
+		// permission is revoked in the new block
+		let log = contract.set_denied(requester, document, true);
+		for change in permissions_changes(vec![log]) {
+			cache.invalidate(&change);
+		}
+		assert!(!cache.check(requester, &document, now, || contract.check(requester, &document)).unwrap());
+		assert_eq!(contract.calls(), 2);
+
+		// and denial is cached as well
+		assert!(!cache.check(requester, &document, now, || contract.check(requester, &document)).unwrap());
+		assert_eq!(contract.calls(), 2);
+	}
+
+	#[test]
+	fn revoked_permission_is_denied_after_new_block() {
+		let client = generate_dummy_client(0);
+		let sync: Arc<SyncProvider> = Arc::new(SyncedProvider);
+		let miner = Arc::new(Miner::new_for_tests(&Spec::new_test(), None));
+		let sender = Random.generate().unwrap();
+		let trusted_client = TrustedClient::new(Arc::new(PlainNodeKeyPair::new(sender.clone())), client.clone(), sync.clone(), miner.clone());
+
+		let deployment = transact(&client, &sender, 0, Action::Create, acl_contract_code());
+		let contract_address = client.transaction_receipt(TransactionId::Hash(deployment))
+			.and_then(|receipt| receipt.contract_address)
+			.unwrap();
+
+		let acl_storage = OnChainAclStorage::new(trusted_client, ContractAddress::Address(contract_address),
+			Duration::from_secs(600)).unwrap();
+		let (requester, document) = (Address::from(1), H256::from(2));
+		assert!(acl_storage.check(requester, &document).unwrap());
+
+		// permission is revoked in the new block => cached grant is forgotten when the block is imported
+		let mut revoke_input = H256::from(requester).to_vec();
+		revoke_input.extend_from_slice(&*document);
+		transact(&client, &sender, 1, Action::Call(contract_address), revoke_input);
+		assert!(!acl_storage.check(requester, &document).unwrap());
+	}
+
+	#[test]
+	fn unrelated_events_keep_other_checks_cached() {
+		let contract = DummyAclContract::default();
+		let mut cache = PermissionsCache::new(Duration::from_secs(600));
+		let (requester, document, other_document) = (Address::from(1), H256::from(2), H256::from(3));
+		let now = Instant::now();
+
+		assert!(cache.check(requester, &document, now, || contract.check(requester, &document)).unwrap());
+		let log = contract.set_denied(requester, other_document, true);
+		for change in permissions_changes(vec![log]) {
+			cache.invalidate(&change);
+		}
+		assert!(cache.check(requester, &document, now, || contract.check(requester, &document)).unwrap());
+		assert_eq!(contract.calls(), 1);
+	}
+
+	#[test]
+	fn denials_expire_before_grants() {
+		let contract = DummyAclContract::default();
+		let mut cache = PermissionsCache::new(Duration::from_secs(600));
+		let (granted, denied, document) = (Address::from(1), Address::from(2), H256::from(3));
+		let now = Instant::now();
+
+		// permission changes without events are only noticed after the TTL
+		contract.set_denied(denied, document, true);
+		assert!(cache.check(granted, &document, now, || contract.check(granted, &document)).unwrap());
+		assert!(!cache.check(denied, &document, now, || contract.check(denied, &document)).unwrap());
+		contract.set_denied(granted, document, true);
+		contract.set_denied(denied, document, false);
+
+		let later = now + Duration::from_secs(60);
+		assert!(cache.check(granted, &document, later, || contract.check(granted, &document)).unwrap());
+		assert!(cache.check(denied, &document, later, || contract.check(denied, &document)).unwrap());
+		assert_eq!(contract.calls(), 3);
+
+		let much_later = now + Duration::from_secs(600);
+		assert!(!cache.check(granted, &document, much_later, || contract.check(granted, &document)).unwrap());
+		assert_eq!(contract.calls(), 4);
+	}
+
+	#[test]
+	fn failed_checks_are_not_cached() {
+		let contract = DummyAclContract::default();
+		let mut cache = PermissionsCache::new(Duration::from_secs(600));
+		let (requester, document) = (Address::from(1), H256::from(2));
+		let now = Instant::now();
+
+		assert!(cache.check(requester, &document, now, || Err(Error::Internal("offline".into()))).is_err());
+		assert!(cache.check(requester, &document, now, || contract.check(requester, &document)).unwrap());
+		assert_eq!(contract.calls(), 1);
+	}
+}
@@ -18,7 +18,9 @@ use std::collections::BTreeSet;
 use std::sync::Arc;
 use parking_lot::Mutex;
 use crypto::DEFAULT_MAC;
+use ethereum_types::H256;
 use ethkey::crypto;
+use tiny_keccak::Keccak;
 use parity_runtime::Executor;
 use super::acl_storage::AclStorage;
 use super::key_storage::KeyStorage;
@@ -26,8 +28,8 @@ use super::key_server_set::KeyServerSet;
 use key_server_cluster::{math, new_network_cluster};
 use traits::{AdminSessionsServer, ServerKeyGenerator, DocumentKeyServer, MessageSigner, KeyServer, NodeKeyPair};
 use types::{Error, Public, RequestSignature, Requester, ServerKeyId, EncryptedDocumentKey, EncryptedDocumentKeyShadow,
-	ClusterConfiguration, MessageHash, EncryptedMessageSignature, NodeId};
-use key_server_cluster::{ClusterClient, ClusterConfiguration as NetClusterConfiguration, NetConnectionsManagerConfig};
+	ClusterConfiguration, MessageHash, EncryptedMessageSignature, NodeId, AdminSessionStatus, KeyServerStatus};
+use key_server_cluster::{ClusterClient, ClusterSession, ClusterConfiguration as NetClusterConfiguration, NetConnectionsManagerConfig};
 
 /// Secret store key server implementation
 pub struct KeyServerImpl {
@@ -37,6 +39,7 @@ pub struct KeyServerImpl {
 /// Secret store key server data.
 pub struct KeyServerCore {
 	cluster: Arc<ClusterClient>,
+	batch_sessions_parallelism: usize,
 }
 
 impl KeyServerImpl {
@@ -65,6 +68,32 @@ impl AdminSessionsServer for KeyServerImpl {
 			.expect("new_servers_set_change_session creates servers_set_change_session; qed")
 			.wait().map_err(Into::into)
 	}
+
+	fn start_servers_set_change(&self, old_set_signature: RequestSignature, new_set_signature: RequestSignature, new_servers_set: BTreeSet<NodeId>) -> Result<H256, Error> {
+		let servers_set_change_session = self.data.lock().cluster
+			.new_servers_set_change_session(None, None, new_servers_set, old_set_signature, new_set_signature)?;
+		Ok(servers_set_change_session.id())
+	}
+
+	fn change_key_threshold(&self, key_id: &ServerKeyId, new_threshold: usize, signature: RequestSignature) -> Result<(), Error> {
+		let threshold_change_session = self.data.lock().cluster
+			.new_threshold_change_session(key_id.clone(), new_threshold, signature)?;
+		threshold_change_session.as_threshold_change()
+			.expect("new_threshold_change_session creates threshold_change_session; qed")
+			.wait().map_err(Into::into)
+	}
+
+	fn admin_sessions(&self) -> Result<Vec<AdminSessionStatus>, Error> {
+		Ok(self.data.lock().cluster.admin_sessions())
+	}
+
+	fn abort_admin_session(&self, session_id: &H256, signature: RequestSignature) -> Result<(), Error> {
+		self.data.lock().cluster.abort_admin_session(session_id, &signature)
+	}
+
+	fn status(&self) -> Result<KeyServerStatus, Error> {
+		Ok(self.data.lock().cluster.status())
+	}
 }
 
 impl ServerKeyGenerator for KeyServerImpl {
@@ -132,6 +161,30 @@ impl DocumentKeyServer for KeyServerImpl {
 			.expect("when wait is called without timeout it always returns Some; qed")
 			.map_err(Into::into)
 	}
+
+	fn restore_document_keys_shadows(&self, key_ids: &[ServerKeyId], requester: &Requester) -> Result<Vec<Result<EncryptedDocumentKeyShadow, Error>>, Error> {
+		// recover requestor' public key from batch signature
+		let public = requester.public(&document_keys_batch_hash(key_ids)).map_err(Error::InsufficientRequesterData)?;
+		let requester: Requester = public.into();
+
+		let (cluster, batch_sessions_parallelism) = {
+			let data = self.data.lock();
+			(data.cluster.clone(), data.batch_sessions_parallelism)
+		};
+
+		// start at most batch_sessions_parallelism sessions at once && wait for all of them to complete
+		let mut results = Vec::with_capacity(key_ids.len());
+		for key_ids in key_ids.chunks(::std::cmp::max(batch_sessions_parallelism, 1)) {
+			let decryption_sessions: Vec<_> = key_ids.iter()
+				.map(|key_id| cluster.new_decryption_session(key_id.clone(), None, requester.clone(), None, true, false))
+				.collect();
+			results.extend(decryption_sessions.into_iter().map(|decryption_session| decryption_session
+				.and_then(|decryption_session| decryption_session.wait(None)
+					.expect("when wait is called without timeout it always returns Some; qed"))));
+		}
+
+		Ok(results)
+	}
 }
 
 impl MessageSigner for KeyServerImpl {
@@ -187,6 +240,7 @@ impl KeyServerCore {
 			listen_address: (config.listener_address.address.clone(), config.listener_address.port),
 			allow_connecting_to_higher_nodes: config.allow_connecting_to_higher_nodes,
 			auto_migrate_enabled: config.auto_migrate_enabled,
+			admin_migration_mode: config.admin_migration_mode,
 		};
 
 		let core = new_network_cluster(executor, cconfig, net_config)?;
@@ -195,10 +249,24 @@ impl KeyServerCore {
 
 		Ok(KeyServerCore {
 			cluster,
+			batch_sessions_parallelism: config.batch_sessions_parallelism,
 		})
 	}
 }
 
+/// Compute hash of the document keys batch, which must be signed by the requester of batch shadows retrieval.
+pub fn document_keys_batch_hash(key_ids: &[ServerKeyId]) -> H256 {
+	let mut batch_keccak = Keccak::new_keccak256();
+	for key_id in key_ids {
+		batch_keccak.update(&*key_id);
+	}
+
+	let mut batch_keccak_value = [0u8; 32];
+	batch_keccak.finalize(&mut batch_keccak_value);
+
+	batch_keccak_value.into()
+}
+
 #[cfg(test)]
 pub mod tests {
 	use std::collections::BTreeSet;
@@ -207,20 +275,20 @@ pub mod tests {
 	use std::net::SocketAddr;
 	use std::collections::BTreeMap;
 	use crypto::DEFAULT_MAC;
-	use ethkey::{self, crypto, Secret, Random, Generator, verify_public};
+	use ethkey::{self, crypto, Secret, KeyPair, Random, Generator, verify_public};
 	use acl_storage::DummyAclStorage;
 	use key_storage::KeyStorage;
 	use key_storage::tests::DummyKeyStorage;
 	use node_key_pair::PlainNodeKeyPair;
 	use key_server_set::tests::MapKeyServerSet;
-	use key_server_cluster::math;
+	use key_server_cluster::{math, ordered_nodes_hash};
 	use ethereum_types::{H256, H520};
 	use parity_runtime::Runtime;
 	use types::{Error, Public, ClusterConfiguration, NodeAddress, RequestSignature, ServerKeyId,
 		EncryptedDocumentKey, EncryptedDocumentKeyShadow, MessageHash, EncryptedMessageSignature,
-		Requester, NodeId};
+		Requester, NodeId, AdminSessionStatus, KeyServerStatus, AdminMigrationMode};
 	use traits::{AdminSessionsServer, ServerKeyGenerator, DocumentKeyServer, MessageSigner, KeyServer};
-	use super::KeyServerImpl;
+	use super::{KeyServerImpl, document_keys_batch_hash};
 
 	#[derive(Default)]
 	pub struct DummyKeyServer;
@@ -231,6 +299,26 @@ pub mod tests {
 		fn change_servers_set(&self, _old_set_signature: RequestSignature, _new_set_signature: RequestSignature, _new_servers_set: BTreeSet<NodeId>) -> Result<(), Error> {
 			unimplemented!("test-only")
 		}
+
+		fn start_servers_set_change(&self, _old_set_signature: RequestSignature, _new_set_signature: RequestSignature, _new_servers_set: BTreeSet<NodeId>) -> Result<H256, Error> {
+			unimplemented!("test-only")
+		}
+
+		fn change_key_threshold(&self, _key_id: &ServerKeyId, _new_threshold: usize, _signature: RequestSignature) -> Result<(), Error> {
+			unimplemented!("test-only")
+		}
+
+		fn admin_sessions(&self) -> Result<Vec<AdminSessionStatus>, Error> {
+			unimplemented!("test-only")
+		}
+
+		fn abort_admin_session(&self, _session_id: &H256, _signature: RequestSignature) -> Result<(), Error> {
+			unimplemented!("test-only")
+		}
+
+		fn status(&self) -> Result<KeyServerStatus, Error> {
+			unimplemented!("test-only")
+		}
 	}
 
 	impl ServerKeyGenerator for DummyKeyServer {
@@ -255,6 +343,10 @@ pub mod tests {
 		fn restore_document_key_shadow(&self, _key_id: &ServerKeyId, _requester: &Requester) -> Result<EncryptedDocumentKeyShadow, Error> {
 			unimplemented!("test-only")
 		}
+
+		fn restore_document_keys_shadows(&self, _key_ids: &[ServerKeyId], _requester: &Requester) -> Result<Vec<Result<EncryptedDocumentKeyShadow, Error>>, Error> {
+			unimplemented!("test-only")
+		}
 	}
 
 	impl MessageSigner for DummyKeyServer {
@@ -267,8 +359,17 @@ pub mod tests {
 		}
 	}
 
-	fn make_key_servers(start_port: u16, num_nodes: usize) -> (Vec<KeyServerImpl>, Vec<Arc<DummyKeyStorage>>, Runtime) {
+	pub fn make_key_servers(start_port: u16, num_nodes: usize) -> (Vec<KeyServerImpl>, Vec<Arc<DummyKeyStorage>>, Runtime) {
 		let key_pairs: Vec<_> = (0..num_nodes).map(|_| Random.generate().unwrap()).collect();
+		make_key_servers_with_admin(start_port, key_pairs, None)
+	}
+
+	pub fn make_key_servers_with_admin(start_port: u16, key_pairs: Vec<KeyPair>, admin_public: Option<Public>) -> (Vec<KeyServerImpl>, Vec<Arc<DummyKeyStorage>>, Runtime) {
+		make_key_servers_with_acl_storage(start_port, key_pairs, admin_public, Arc::new(DummyAclStorage::default()))
+	}
+
+	pub fn make_key_servers_with_acl_storage(start_port: u16, key_pairs: Vec<KeyPair>, admin_public: Option<Public>, acl_storage: Arc<DummyAclStorage>) -> (Vec<KeyServerImpl>, Vec<Arc<DummyKeyStorage>>, Runtime) {
+		let num_nodes = key_pairs.len();
 		let configs: Vec<_> = (0..num_nodes).map(|i| ClusterConfiguration {
 				listener_address: NodeAddress {
 					address: "127.0.0.1".into(),
@@ -281,8 +382,10 @@ pub mod tests {
 					})).collect(),
 				key_server_set_contract_address: None,
 				allow_connecting_to_higher_nodes: false,
-				admin_public: None,
+				admin_public: admin_public.clone(),
 				auto_migrate_enabled: false,
+				admin_migration_mode: AdminMigrationMode::Disabled,
+				batch_sessions_parallelism: 2,
 			}).collect();
 		let key_servers_set: BTreeMap<Public, SocketAddr> = configs[0].nodes.iter()
 			.map(|(k, a)| (k.clone(), format!("{}:{}", a.address, a.port).parse().unwrap()))
@@ -292,7 +395,7 @@ pub mod tests {
 		let key_servers: Vec<_> = configs.into_iter().enumerate().map(|(i, cfg)|
 			KeyServerImpl::new(&cfg, Arc::new(MapKeyServerSet::new(false, key_servers_set.clone())),
 				Arc::new(PlainNodeKeyPair::new(key_pairs[i].clone())),
-				Arc::new(DummyAclStorage::default()),
+				acl_storage.clone(),
 				key_storages[i].clone(), runtime.executor()).unwrap()
 		).collect();
 
@@ -512,6 +615,113 @@ pub mod tests {
 
 	#[test]
 	fn servers_set_change_session_works_over_network() {
-		// TODO [Test]
+		let _ = ::env_logger::try_init();
+		let admin_key_pair = Random.generate().unwrap();
+		let key_pairs: Vec<_> = (0..3).map(|_| Random.generate().unwrap()).collect();
+		let (key_servers, key_storages, runtime) = make_key_servers_with_admin(6121, key_pairs.clone(),
+			Some(admin_key_pair.public().clone()));
+
+		// generate server key
+		let threshold = 1;
+		let server_key_id = ServerKeyId::random();
+		let requestor_secret = Random.generate().unwrap().secret().clone();
+		let signature = ethkey::sign(&requestor_secret, &server_key_id).unwrap();
+		let server_public = key_servers[0].generate_key(&server_key_id, &signature.clone().into(), threshold).unwrap();
+
+		// start session which removes node2 from the servers set
+		let old_servers_set: BTreeSet<NodeId> = key_pairs.iter().map(|kp| kp.public().clone()).collect();
+		let new_servers_set: BTreeSet<NodeId> = key_pairs[0..2].iter().map(|kp| kp.public().clone()).collect();
+		let old_set_signature = ethkey::sign(admin_key_pair.secret(), &ordered_nodes_hash(&old_servers_set)).unwrap();
+		let new_set_signature = ethkey::sign(admin_key_pair.secret(), &ordered_nodes_hash(&new_servers_set)).unwrap();
+		let session_id = key_servers[0].start_servers_set_change(old_set_signature, new_set_signature, new_servers_set).unwrap();
+
+		// wait until session is completed on all nodes
+		let start = time::Instant::now();
+		loop {
+			let statuses: Vec<AdminSessionStatus> = key_servers.iter()
+				.flat_map(|ks| ks.admin_sessions().unwrap())
+				.collect();
+			if statuses.is_empty() {
+				break;
+			}
+
+			for status in statuses {
+				assert_eq!(status.id, session_id);
+				assert_eq!(status.session_type, "servers set change");
+				assert_eq!(&status.master, key_pairs[0].public());
+				if let (Some(keys_processed), Some(keys_total)) = (status.keys_processed, status.keys_total) {
+					assert_eq!(keys_total, 1);
+					assert!(keys_processed <= keys_total);
+				}
+			}
+
+			if time::Instant::now() - start > time::Duration::from_millis(3000) {
+				panic!("servers set change session is not completed in 3000ms");
+			}
+			::std::thread::sleep(time::Duration::from_millis(10));
+		}
+
+		// node2 has cleared its database && the key is still available
+		assert!(key_storages[2].get(&server_key_id).unwrap().is_none());
+		for key_storage in &key_storages[0..2] {
+			let key_share = key_storage.get(&server_key_id).unwrap().unwrap();
+			assert_eq!(key_share.public, server_public);
+			assert!(!key_share.last_version().unwrap().id_numbers.contains_key(key_pairs[2].public()));
+		}
+		drop(runtime);
+	}
+
+	#[test]
+	fn admin_session_abort_requires_admin_signature() {
+		let _ = ::env_logger::try_init();
+		let admin_key_pair = Random.generate().unwrap();
+		let (key_servers, _, runtime) = make_key_servers_with_admin(6124, vec![Random.generate().unwrap()],
+			Some(admin_key_pair.public().clone()));
+
+		let session_id = H256::random();
+		let other_signature = ethkey::sign(Random.generate().unwrap().secret(), &session_id).unwrap();
+		assert_eq!(key_servers[0].abort_admin_session(&session_id, other_signature), Err(Error::AccessDenied));
+
+		let admin_signature = ethkey::sign(admin_key_pair.secret(), &session_id).unwrap();
+		assert_eq!(key_servers[0].abort_admin_session(&session_id, admin_signature), Err(Error::NoActiveSessionWithId));
+		assert_eq!(key_servers[0].admin_sessions(), Ok(Vec::new()));
+		drop(runtime);
+	}
+
+	#[test]
+	fn document_keys_shadows_are_restored_in_batch_when_access_to_some_keys_is_denied() {
+		let _ = ::env_logger::try_init();
+		let acl_storage = Arc::new(DummyAclStorage::default());
+		let key_pairs: Vec<_> = (0..3).map(|_| Random.generate().unwrap()).collect();
+		let (key_servers, _, runtime) = make_key_servers_with_acl_storage(6125, key_pairs, None, acl_storage.clone());
+
+		// generate document keys
+		let threshold = 1;
+		let requester_secret = Random.generate().unwrap().secret().clone();
+		let documents: Vec<ServerKeyId> = (0..5).map(|_| H256::random()).collect();
+		for document in &documents {
+			let signature = ethkey::sign(&requester_secret, document).unwrap();
+			key_servers[0].generate_document_key(document, &signature.into(), threshold).unwrap();
+		}
+
+		// deny access to one of documents
+		let requester_public = KeyPair::from_secret(requester_secret.clone()).unwrap().public().clone();
+		acl_storage.prohibit(ethkey::public_to_address(&requester_public), documents[2].clone());
+
+		// retrieve shadows in batch
+		let batch_signature = ethkey::sign(&requester_secret, &document_keys_batch_hash(&documents)).unwrap();
+		let results = key_servers[1].restore_document_keys_shadows(&documents, &batch_signature.into()).unwrap();
+		assert_eq!(results.len(), documents.len());
+		for (i, result) in results.into_iter().enumerate() {
+			match i {
+				2 => assert!(result.is_err()),
+				_ => {
+					let shadow = result.unwrap();
+					assert!(shadow.common_point.is_some());
+					assert!(shadow.decrypt_shadows.is_some());
+				},
+			}
+		}
+		drop(runtime);
 	}
 }
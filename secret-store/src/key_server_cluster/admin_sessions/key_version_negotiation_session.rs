@@ -450,6 +450,13 @@ impl<T> ClusterSession for SessionImpl<T> where T: SessionTransport {
 		self.data.lock().state == SessionState::Finished
 	}
 
+	fn is_failed(&self) -> bool {
+		match self.data.lock().result {
+			Some(Err(_)) => true,
+			_ => false,
+		}
+	}
+
 	fn on_session_timeout(&self) {
 		let mut data = self.data.lock();
 
@@ -18,6 +18,7 @@ pub mod key_version_negotiation_session;
 pub mod servers_set_change_session;
 pub mod share_add_session;
 pub mod share_change_session;
+pub mod threshold_change_session;
 
 mod sessions_queue;
 
@@ -110,6 +110,8 @@ struct SessionData {
 	pub new_nodes_set: Option<BTreeSet<NodeId>>,
 	/// Share change sessions queue (valid on master nodes only).
 	pub sessions_queue: Option<SessionsQueue>,
+	/// Total number of share change sessions (valid on master nodes only).
+	pub sessions_total: Option<usize>,
 	/// Share change sessions key version negotiation.
 	pub negotiation_sessions: BTreeMap<SessionId, KeyVersionNegotiationSessionImpl<ServersSetChangeKeyVersionNegotiationTransport>>,
 	/// Share change sessions initialization state (valid on master nodes only).
@@ -118,6 +120,8 @@ struct SessionData {
 	pub delegated_key_sessions: BTreeMap<SessionId, NodeId>,
 	/// Active share change sessions.
 	pub active_key_sessions: BTreeMap<SessionId, ShareChangeSession>,
+	/// Fail instead of skipping keys, which can't be restored by nodes of the new set (valid on master node only).
+	pub reject_unrecoverable_keys: bool,
 	/// Servers set change result.
 	pub result: Option<Result<(), Error>>,
 }
@@ -199,10 +203,12 @@ impl SessionImpl {
 				consensus_session: None,
 				new_nodes_set: None,
 				sessions_queue: None,
+				sessions_total: None,
 				negotiation_sessions: BTreeMap::new(),
 				sessions_initialization_state: BTreeMap::new(),
 				delegated_key_sessions: BTreeMap::new(),
 				active_key_sessions: BTreeMap::new(),
+				reject_unrecoverable_keys: false,
 				result: None,
 			}),
 		})
@@ -218,6 +224,25 @@ impl SessionImpl {
 		self.core.migration_id.as_ref()
 	}
 
+	/// Get session progress: number of processed keys && total number of keys.
+	/// Progress is only known on master node, after consensus is established.
+	pub fn progress(&self) -> Option<(usize, usize)> {
+		let data = self.data.lock();
+		data.sessions_total.map(|sessions_total| {
+			let sessions_left = data.sessions_queue.as_ref().map(|queue| queue.len()).unwrap_or(0)
+				+ data.negotiation_sessions.len()
+				+ data.active_key_sessions.len()
+				+ data.delegated_key_sessions.len();
+			(sessions_total.saturating_sub(sessions_left), sessions_total)
+		})
+	}
+
+	/// Fail the session when there are less than threshold + 1 owners of some key in the new set,
+	/// instead of leaving this key unrecoverable. Must be called on master node before initialization.
+	pub fn reject_unrecoverable_keys(&self) {
+		self.data.lock().reject_unrecoverable_keys = true;
+	}
+
 	/// Wait for session completion.
 	pub fn wait(&self) -> Result<(), Error> {
 		Self::wait_session(&self.core.completed, &self.data, None, |data| data.result.clone())
@@ -401,7 +426,9 @@ impl SessionImpl {
 
 		// initialize sessions queue
 		data.state = SessionState::RunningShareChangeSessions;
-		data.sessions_queue = Some(SessionsQueue::new(&self.core.key_storage, unknown_sessions.keys().cloned().collect()));
+		let sessions_queue = SessionsQueue::new(&self.core.key_storage, unknown_sessions.keys().cloned().collect());
+		data.sessions_total = Some(sessions_queue.len());
+		data.sessions_queue = Some(sessions_queue);
 
 		// and disseminate session initialization requests
 		Self::disseminate_session_initialization_requests(&self.core, &mut *data)
@@ -806,6 +833,13 @@ impl SessionImpl {
 		let old_nodes_set = selected_version_holders;
 		let new_nodes_set = data.new_nodes_set.as_ref()
 			.expect("this method is called after consensus estabished; new_nodes_set is a result of consensus session; qed");
+		// keys, unknown to this node, are only checked here, when their threshold is known
+		if data.reject_unrecoverable_keys && old_nodes_set.intersection(new_nodes_set).count() < selected_version_threshold + 1 {
+			warn!(target: "secretstore_net", "{}: key {} with threshold {} can't be restored by nodes of new set",
+				core.meta.self_node_id, key_id, selected_version_threshold);
+			return Err(Error::NotEnoughNodesForThreshold);
+		}
+
 		let session_plan = prepare_share_change_session_plan(&core.all_nodes_set,
 			selected_version_threshold,
 			&key_id,
@@ -928,6 +962,13 @@ impl ClusterSession for SessionImpl {
 		self.data.lock().state == SessionState::Finished
 	}
 
+	fn is_failed(&self) -> bool {
+		match self.data.lock().result {
+			Some(Err(_)) => true,
+			_ => false,
+		}
+	}
+
 	fn on_session_timeout(&self) {
 		self.on_session_error(&self.core.meta.self_node_id, Error::NodeDisconnected);
 	}
@@ -37,6 +37,11 @@ impl SessionsQueue {
 			unknown_sessions: unknown_sessions.into_iter().collect(),
 		}
 	}
+
+	/// Get number of sessions left in the queue.
+	pub fn len(&self) -> usize {
+		self.known_sessions.len() + self.unknown_sessions.len()
+	}
 }
 
 impl Iterator for SessionsQueue {
@@ -771,6 +771,13 @@ impl<T> ClusterSession for SessionImpl<T> where T: SessionTransport {
 		self.data.lock().state == SessionState::Finished
 	}
 
+	fn is_failed(&self) -> bool {
+		match self.data.lock().result {
+			Some(Err(_)) => true,
+			_ => false,
+		}
+	}
+
 	fn on_session_timeout(&self) {
 		self.on_session_error(&self.core.meta.self_node_id, Error::NodeDisconnected)
 	}
@@ -0,0 +1,1296 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use std::collections::{BTreeSet, BTreeMap};
+use ethereum_types::{H256, Address};
+use ethkey::{Public, Secret, Signature};
+use parking_lot::{Mutex, Condvar};
+use key_server_cluster::{Error, SessionId, NodeId, DocumentKeyShare, DocumentKeyShareVersion, KeyStorage};
+use key_server_cluster::cluster::Cluster;
+use key_server_cluster::cluster_sessions::ClusterSession;
+use key_server_cluster::math;
+use key_server_cluster::message::{Message, ThresholdChangeMessage, ThresholdChangeConsensusMessage,
+	ConsensusMessageOfThresholdChange, InitializeConsensusSessionOfThresholdChange, KeyShareCommon,
+	ReshareKeysDissemination, ConfirmNewKeyShare, CommitNewKeyShares, ThresholdChangeError,
+	NewKeyShareCommitted, ThresholdChangeCompleted, ConfirmConsensusInitialization};
+use key_server_cluster::jobs::job_session::JobTransport;
+use key_server_cluster::jobs::dummy_job::{DummyJob, DummyJobTransport};
+use key_server_cluster::jobs::threshold_change_access_job::{ThresholdChangeAccessJob, ThresholdChangeAccessRequest};
+use key_server_cluster::jobs::consensus_session::{ConsensusSessionParams, ConsensusSessionState, ConsensusSession};
+use key_server_cluster::admin_sessions::ShareChangeSessionMeta;
+
+/// Threshold change session transport.
+pub trait SessionTransport: Clone + JobTransport<PartialJobRequest=ThresholdChangeAccessRequest, PartialJobResponse=bool> {
+	/// Get all connected nodes. Since ThresholdChange session requires all cluster nodes to be connected, this set equals to all known cluster nodes set.
+	fn nodes(&self) -> BTreeSet<NodeId>;
+	/// Send message to given node.
+	fn send(&self, node: &NodeId, message: ThresholdChangeMessage) -> Result<(), Error>;
+	/// Set data for master node (sent to slave nodes in consensus session initialization message).
+	fn set_master_data(&mut self, version: H256, new_threshold: usize, consensus_group: BTreeSet<NodeId>, id_numbers: BTreeMap<NodeId, Secret>);
+}
+
+/// Threshold change session.
+/// Re-shares existing key among all cluster nodes, so that it could be recovered by any new_threshold + 1 of them.
+/// Based on "Verifiable Secret Redistribution for Threshold Sharing Schemes" paper:
+/// http://www.cs.cmu.edu/~wing/publications/CMU-CS-02-114.pdf
+/// Brief overview:
+/// 1) initialization: master node (which has received request for threshold change) asks all other nodes to support change
+/// 2) every node of consensus group (threshold + 1 owners of key share) generates random polynom of degree new_threshold,
+/// with its interpolated share as absolute term, && sends polynom value + commitments to polynom coefficients to every node
+/// 3) every node verifies received values against commitments && checks that commitments are matching the key public
+/// 4) every node computes its new key share && confirms this to the master node
+/// 5) when all nodes have confirmed, master node asks every node to replace old key share with the new one
+/// 6) master node only replaces its own key share when every other node has confirmed that it has saved the new one;
+/// until then, every node is able to restore its old key share if session fails
+pub struct SessionImpl<T: SessionTransport> {
+	/// Session core.
+	core: SessionCore<T>,
+	/// Session data.
+	data: Mutex<SessionData<T>>,
+}
+
+/// Immutable session data.
+struct SessionCore<T: SessionTransport> {
+	/// Session metadata.
+	pub meta: ShareChangeSessionMeta,
+	/// Session-level nonce.
+	pub nonce: u64,
+	/// Original key share (for nodes which have it).
+	pub key_share: Option<DocumentKeyShare>,
+	/// Session transport to communicate to other cluster nodes.
+	pub transport: T,
+	/// Key storage.
+	pub key_storage: Arc<KeyStorage>,
+	/// Administrator public key.
+	pub admin_public: Option<Public>,
+	/// SessionImpl completion condvar.
+	pub completed: Condvar,
+}
+
+/// Threshold change consensus session type.
+type ThresholdChangeConsensusSession<T> = ConsensusSession<ThresholdChangeAccessJob, T, DummyJob, DummyJobTransport>;
+
+/// Mutable session data.
+struct SessionData<T: SessionTransport> {
+	/// Session state.
+	pub state: SessionState,
+	/// Key version to re-share.
+	pub version: Option<H256>,
+	/// New key threshold.
+	pub new_threshold: Option<usize>,
+	/// Consensus session.
+	pub consensus_session: Option<ThresholdChangeConsensusSession<T>>,
+	/// Common key share data (for nodes which have no share of given version).
+	pub key_share_common: Option<KeyShareCommonData>,
+	/// Nodes id numbers.
+	pub id_numbers: Option<BTreeMap<NodeId, Secret>>,
+	/// Secret subshares && free commitments, received from consensus group nodes.
+	pub secret_subshares: Option<BTreeMap<NodeId, Option<(Secret, Public)>>>,
+	/// New key share, waiting for commit.
+	pub new_key_share: Option<DocumentKeyShare>,
+	/// Nodes, which are yet to confirm new key share (on master node only).
+	pub unconfirmed_nodes: Option<BTreeSet<NodeId>>,
+	/// Nodes, which are yet to confirm that new key share is saved (on master node only).
+	pub uncommitted_nodes: Option<BTreeSet<NodeId>>,
+	/// Threshold change result.
+	pub result: Option<Result<(), Error>>,
+}
+
+/// Common key share data.
+struct KeyShareCommonData {
+	/// Author of the key.
+	pub author: Address,
+	/// Joint public.
+	pub joint_public: Public,
+	/// Common (shared) encryption point.
+	pub common_point: Option<Public>,
+	/// Encrypted point.
+	pub encrypted_point: Option<Public>,
+}
+
+/// Session state.
+#[derive(Debug, PartialEq)]
+enum SessionState {
+	/// State when consensus is establishing.
+	ConsensusEstablishing,
+	/// Waiting for keys dissemination.
+	WaitingForKeysDissemination,
+	/// Waiting for all nodes to confirm new key shares.
+	WaitingForCommit,
+	/// Waiting for all nodes to save new key shares. Old key share is kept until then.
+	WaitingForCompletion,
+	/// Session is completed.
+	Finished,
+}
+
+/// SessionImpl creation parameters
+pub struct SessionParams<T: SessionTransport> {
+	/// Session metadata.
+	pub meta: ShareChangeSessionMeta,
+	/// Session transport.
+	pub transport: T,
+	/// Key storage.
+	pub key_storage: Arc<KeyStorage>,
+	/// Administrator public key.
+	pub admin_public: Option<Public>,
+	/// Session nonce.
+	pub nonce: u64,
+}
+
+/// Isolated ThresholdChange session transport.
+#[derive(Clone)]
+pub struct IsolatedSessionTransport {
+	/// Key id.
+	session: SessionId,
+	/// Session-level nonce.
+	nonce: u64,
+	/// Key version.
+	version: Option<H256>,
+	/// New key threshold.
+	new_threshold: Option<usize>,
+	/// Consensus group.
+	consensus_group: Option<BTreeSet<NodeId>>,
+	/// Id numbers of all nodes.
+	id_numbers: Option<BTreeMap<NodeId, Secret>>,
+	/// Cluster.
+	cluster: Arc<Cluster>,
+}
+
+impl<T> SessionImpl<T> where T: SessionTransport {
+	/// Create new threshold change session.
+	pub fn new(params: SessionParams<T>) -> Result<Self, Error> {
+		let key_share = params.key_storage.get(&params.meta.id)?;
+
+		Ok(SessionImpl {
+			core: SessionCore {
+				meta: params.meta,
+				nonce: params.nonce,
+				key_share: key_share,
+				transport: params.transport,
+				key_storage: params.key_storage,
+				admin_public: params.admin_public,
+				completed: Condvar::new(),
+			},
+			data: Mutex::new(SessionData {
+				state: SessionState::ConsensusEstablishing,
+				version: None,
+				new_threshold: None,
+				consensus_session: None,
+				key_share_common: None,
+				id_numbers: None,
+				secret_subshares: None,
+				new_key_share: None,
+				unconfirmed_nodes: None,
+				uncommitted_nodes: None,
+				result: None,
+			}),
+		})
+	}
+
+	/// Wait for session completion.
+	pub fn wait(&self) -> Result<(), Error> {
+		Self::wait_session(&self.core.completed, &self.data, None, |data| data.result.clone())
+			.expect("wait_session returns Some if called without timeout; qed")
+	}
+
+	/// Initialize threshold change session on master node.
+	pub fn initialize(&self, new_threshold: usize, signature: Signature) -> Result<(), Error> {
+		debug_assert_eq!(self.core.meta.self_node_id, self.core.meta.master_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::ConsensusEstablishing || data.consensus_session.is_some() {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		let admin_public = self.core.admin_public.as_ref().cloned().ok_or(Error::ConsensusUnreachable)?;
+
+		// key share is required on master node
+		let key_share = self.core.key_share.as_ref().ok_or_else(|| Error::ServerKeyIsNotFound)?;
+		let key_version = key_share.last_version()?;
+
+		// every owner of key version must be connected, so that it could replace its old share
+		let nodes = self.core.transport.nodes();
+		if key_version.id_numbers.keys().any(|n| !nodes.contains(n)) {
+			return Err(Error::NodeDisconnected);
+		}
+
+		// new nodes map contains previous id_numbers for owners && random number for other nodes
+		let mut new_nodes_map = BTreeMap::new();
+		for node in nodes {
+			let id_number = match key_version.id_numbers.get(&node) {
+				Some(id_number) => id_number.clone(),
+				None => math::generate_random_scalar()?,
+			};
+			new_nodes_map.insert(node, id_number);
+		}
+
+		// let's select consensus group
+		let consensus_group: BTreeSet<_> = ::std::iter::once(self.core.meta.self_node_id.clone())
+			.chain(key_version.id_numbers.keys()
+				.filter(|n| **n != self.core.meta.self_node_id)
+				.take(key_share.threshold)
+				.cloned())
+			.collect();
+
+		// now check nodes map
+		Self::check_nodes_map(&self.core, &key_version.hash, new_threshold, &consensus_group, &new_nodes_map)?;
+
+		// prepare consensus session transport
+		let mut consensus_transport = self.core.transport.clone();
+		consensus_transport.set_master_data(key_version.hash.clone(), new_threshold, consensus_group.clone(), new_nodes_map.clone());
+
+		// create && initialize consensus session
+		let new_nodes_set: BTreeSet<_> = new_nodes_map.keys().cloned().collect();
+		let mut consensus_session = ConsensusSession::new(ConsensusSessionParams {
+			meta: self.core.meta.clone().into_consensus_meta(new_nodes_map.len())?,
+			consensus_executor: ThresholdChangeAccessJob::new_on_master(admin_public,
+				self.core.meta.id.clone(),
+				new_nodes_set.clone(),
+				new_threshold,
+				signature),
+			consensus_transport: consensus_transport,
+		})?;
+
+		consensus_session.initialize(new_nodes_set)?;
+
+		// update data
+		data.version = Some(key_version.hash.clone());
+		data.new_threshold = Some(new_threshold);
+		data.consensus_session = Some(consensus_session);
+		data.id_numbers = Some(new_nodes_map);
+		data.secret_subshares = Some(consensus_group.into_iter().map(|n| (n, None)).collect());
+
+		// consensus is established immediately if there's single node in the cluster
+		if data.consensus_session.as_ref().map(|cs| cs.state()) == Some(ConsensusSessionState::ConsensusEstablished) {
+			return Self::on_consensus_established(&self.core, &mut *data);
+		}
+
+		Ok(())
+	}
+
+	/// Process single message.
+	pub fn process_message(&self, sender: &NodeId, message: &ThresholdChangeMessage) -> Result<(), Error> {
+		if self.core.nonce != message.session_nonce() {
+			return Err(Error::ReplayProtection);
+		}
+
+		match message {
+			&ThresholdChangeMessage::ThresholdChangeConsensusMessage(ref message) =>
+				self.on_consensus_message(sender, message),
+			&ThresholdChangeMessage::KeyShareCommon(ref message) =>
+				self.on_common_key_share_data(sender, message),
+			&ThresholdChangeMessage::ReshareKeysDissemination(ref message) =>
+				self.on_keys_dissemination(sender, message),
+			&ThresholdChangeMessage::ConfirmNewKeyShare(ref message) =>
+				self.on_new_key_share_confirmed(sender, message),
+			&ThresholdChangeMessage::CommitNewKeyShares(ref message) =>
+				self.on_commit_new_key_shares(sender, message),
+			&ThresholdChangeMessage::NewKeyShareCommitted(ref message) =>
+				self.on_new_key_share_committed(sender, message),
+			&ThresholdChangeMessage::ThresholdChangeCompleted(ref message) =>
+				self.on_threshold_change_completed(sender, message),
+			&ThresholdChangeMessage::ThresholdChangeError(ref message) => {
+				// master only reports errors before it has saved its own new key share
+				// => it is safe to restore the old key share
+				if *sender == self.core.meta.master_node_id {
+					self.restore_uncommitted_key_share(message.error.clone());
+				}
+
+				self.on_session_error(sender, message.error.clone());
+				Ok(())
+			},
+		}
+	}
+
+	/// When consensus-related message is received.
+	pub fn on_consensus_message(&self, sender: &NodeId, message: &ThresholdChangeConsensusMessage) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		// start slave consensus session if needed
+		let mut data = self.data.lock();
+		match &message.message {
+			&ConsensusMessageOfThresholdChange::InitializeConsensusSession(ref message)
+				if data.consensus_session.is_none() && sender == &self.core.meta.master_node_id => {
+					let admin_public = self.core.admin_public.as_ref().cloned().ok_or(Error::ConsensusUnreachable)?;
+					data.consensus_session = Some(ConsensusSession::new(ConsensusSessionParams {
+						meta: self.core.meta.clone().into_consensus_meta(message.new_nodes_map.len())?,
+						consensus_executor: ThresholdChangeAccessJob::new_on_slave(admin_public, self.core.meta.id.clone()),
+						consensus_transport: self.core.transport.clone(),
+					})?);
+				},
+			_ => (),
+		};
+
+		// process consensus message
+		let (is_establishing_consensus, is_consensus_established, initialization_data) = {
+			let consensus_session = data.consensus_session.as_mut().ok_or(Error::InvalidMessage)?;
+			let is_establishing_consensus = consensus_session.state() == ConsensusSessionState::EstablishingConsensus;
+
+			let initialization_data = match &message.message {
+				&ConsensusMessageOfThresholdChange::InitializeConsensusSession(ref message) => {
+					let version: H256 = message.version.clone().into();
+					let consensus_group: BTreeSet<NodeId> = message.consensus_group.iter().cloned().map(Into::into).collect();
+					let new_nodes_map: BTreeMap<NodeId, Secret> = message.new_nodes_map.iter()
+						.map(|(n, nn)| (n.clone().into(), nn.clone().into()))
+						.collect();
+
+					// check nodes map before confirming consensus
+					Self::check_nodes_map(&self.core, &version, message.new_threshold, &consensus_group, &new_nodes_map)?;
+
+					consensus_session.on_consensus_partial_request(sender, ThresholdChangeAccessRequest::from(message))?;
+
+					Some((version, message.new_threshold, consensus_group, new_nodes_map))
+				},
+				&ConsensusMessageOfThresholdChange::ConfirmConsensusInitialization(ref message) => {
+					consensus_session.on_consensus_partial_response(sender, message.is_confirmed)?;
+					None
+				},
+			};
+
+			(
+				is_establishing_consensus,
+				consensus_session.state() == ConsensusSessionState::ConsensusEstablished,
+				initialization_data,
+			)
+		};
+
+		// update data
+		if let Some((version, new_threshold, consensus_group, new_nodes_map)) = initialization_data {
+			data.version = Some(version);
+			data.new_threshold = Some(new_threshold);
+			data.id_numbers = Some(new_nodes_map);
+			data.secret_subshares = Some(consensus_group.into_iter().map(|n| (n, None)).collect());
+		}
+
+		// if consensus is established, proceed
+		if !is_establishing_consensus || !is_consensus_established || self.core.meta.self_node_id != self.core.meta.master_node_id {
+			return Ok(());
+		}
+
+		Self::on_consensus_established(&self.core, &mut *data)
+	}
+
+	/// When common key share data is received by node, which has no share of the key.
+	pub fn on_common_key_share_data(&self, sender: &NodeId, message: &KeyShareCommon) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		// only master can send this message
+		if sender != &self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state != SessionState::ConsensusEstablishing && data.state != SessionState::WaitingForKeysDissemination {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// we only expect this message once && only if we do not have given version of the key
+		if data.key_share_common.is_some() || Self::has_key_version(&self.core, &data) {
+			return Err(Error::InvalidMessage);
+		}
+
+		// check that threshold && id numbers are the same as in consensus initialization message
+		let id_numbers: BTreeMap<NodeId, Secret> = message.id_numbers.iter()
+			.map(|(n, nn)| (n.clone().into(), nn.clone().into()))
+			.collect();
+		if data.new_threshold != Some(message.threshold) || data.id_numbers.as_ref() != Some(&id_numbers) {
+			return Err(Error::InvalidMessage);
+		}
+
+		data.key_share_common = Some(KeyShareCommonData {
+			author: message.author.clone().into(),
+			joint_public: message.joint_public.clone().into(),
+			common_point: message.common_point.clone().map(Into::into),
+			encrypted_point: message.encrypted_point.clone().map(Into::into),
+		});
+
+		Self::try_compute_new_key_share(&self.core, &mut *data)
+	}
+
+	/// When keys dissemination message is received.
+	pub fn on_keys_dissemination(&self, sender: &NodeId, message: &ReshareKeysDissemination) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// check state
+		if data.state == SessionState::ConsensusEstablishing && data.secret_subshares.is_some() {
+			data.state = SessionState::WaitingForKeysDissemination;
+		} else if data.state != SessionState::WaitingForKeysDissemination {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// check that we're waiting for this subshare
+		let explanation = "secret_subshares is filled during initialization; keys are disseminated after initialization; qed";
+		match data.secret_subshares.as_ref().expect(explanation).get(sender) {
+			None => return Err(Error::InvalidMessage),
+			Some(&Some(_)) => return Err(Error::InvalidMessage),
+			Some(&None) => (),
+		};
+
+		// verify subshare against sender' commitments
+		let new_threshold = data.new_threshold.expect("new_threshold is filled during initialization; qed");
+		let commitments: Vec<Public> = message.commitments.iter().cloned().map(Into::into).collect();
+		if commitments.len() != new_threshold + 1 {
+			return Err(Error::InvalidMessage);
+		}
+
+		let secret_subshare: Secret = message.secret_subshare.clone().into();
+		let is_valid_subshare = {
+			let self_id_number = data.id_numbers.as_ref().expect(explanation).get(&self.core.meta.self_node_id)
+				.ok_or(Error::InvalidMessage)?;
+			math::verify_polynom_value(&commitments, self_id_number, &secret_subshare)?
+		};
+		if !is_valid_subshare {
+			return Err(Error::InvalidMessage);
+		}
+
+		*data.secret_subshares.as_mut().expect(explanation)
+			.get_mut(sender)
+			.expect("checked couple of lines above; qed") = Some((secret_subshare, commitments[0].clone()));
+
+		// if we have received subshare from master node, it means that we should start dissemination
+		if sender == &self.core.meta.master_node_id {
+			Self::on_consensus_established(&self.core, &mut *data)?;
+		}
+
+		Self::try_compute_new_key_share(&self.core, &mut *data)
+	}
+
+	/// When node confirms that it has computed new key share.
+	pub fn on_new_key_share_confirmed(&self, sender: &NodeId, message: &ConfirmNewKeyShare) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// only master node is waiting for confirmations
+		if self.core.meta.self_node_id != self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+		if data.state != SessionState::WaitingForKeysDissemination && data.state != SessionState::WaitingForCommit {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		let is_unconfirmed_node = data.unconfirmed_nodes.as_mut()
+			.expect("unconfirmed_nodes are filled on master when consensus is established; qed")
+			.remove(sender);
+		if !is_unconfirmed_node {
+			return Err(Error::InvalidMessage);
+		}
+
+		Self::try_commit_new_key_shares(&self.core, &mut *data)
+	}
+
+	/// When master node asks to replace old key share with the new one.
+	pub fn on_commit_new_key_shares(&self, sender: &NodeId, message: &CommitNewKeyShares) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		// only master can send this message
+		if sender != &self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// new key share must be computed before commit
+		if data.state != SessionState::WaitingForCommit {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		// save new key share, but remember that we have to restore the old one if session fails
+		Self::save_new_key_share(&self.core, &mut *data)?;
+		data.state = SessionState::WaitingForCompletion;
+
+		self.core.transport.send(&self.core.meta.master_node_id, ThresholdChangeMessage::NewKeyShareCommitted(NewKeyShareCommitted {
+			session: self.core.meta.id.clone().into(),
+			session_nonce: self.core.nonce,
+		}))
+	}
+
+	/// When node confirms that it has saved new key share.
+	pub fn on_new_key_share_committed(&self, sender: &NodeId, message: &NewKeyShareCommitted) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		let mut data = self.data.lock();
+
+		// only master node is waiting for commits
+		if self.core.meta.self_node_id != self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+		if data.state != SessionState::WaitingForCompletion {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		let is_uncommitted_node = data.uncommitted_nodes.as_mut()
+			.expect("uncommitted_nodes are filled on master when commit is started; qed")
+			.remove(sender);
+		if !is_uncommitted_node {
+			return Err(Error::InvalidMessage);
+		}
+
+		Self::try_complete_session(&self.core, &mut *data)
+	}
+
+	/// When master node reports that all nodes have saved new key shares.
+	pub fn on_threshold_change_completed(&self, sender: &NodeId, message: &ThresholdChangeCompleted) -> Result<(), Error> {
+		debug_assert!(self.core.meta.id == *message.session);
+		debug_assert!(sender != &self.core.meta.self_node_id);
+
+		// only master can send this message
+		if sender != &self.core.meta.master_node_id {
+			return Err(Error::InvalidMessage);
+		}
+
+		let mut data = self.data.lock();
+
+		// new key share must be saved before completion
+		if data.state != SessionState::WaitingForCompletion {
+			return Err(Error::InvalidStateForRequest);
+		}
+
+		Self::complete_session(&self.core, &mut *data)
+	}
+
+	/// Check if this node has given version of the key.
+	fn has_key_version(core: &SessionCore<T>, data: &SessionData<T>) -> bool {
+		match (core.key_share.as_ref(), data.version.as_ref()) {
+			(Some(key_share), Some(version)) => key_share.version(version).is_ok(),
+			_ => false,
+		}
+	}
+
+	/// Check nodes map.
+	fn check_nodes_map(core: &SessionCore<T>, version: &H256, new_threshold: usize, consensus_group: &BTreeSet<NodeId>, new_nodes_map: &BTreeMap<NodeId, Secret>) -> Result<(), Error> {
+		// there must be at least new_threshold + 1 nodes in new nodes set
+		if new_threshold >= new_nodes_map.len() {
+			return Err(Error::NotEnoughNodesForThreshold);
+		}
+
+		// master node must always be a part of consensus group
+		if !consensus_group.contains(&core.meta.master_node_id) {
+			return Err(Error::ConsensusUnreachable);
+		}
+
+		// every node of consensus group must be a part of new nodes set && this node must be a part of new nodes set
+		if consensus_group.iter().any(|n| !new_nodes_map.contains_key(n)) || !new_nodes_map.contains_key(&core.meta.self_node_id) {
+			return Err(Error::ConsensusUnreachable);
+		}
+
+		// check that we are operating on the same version of the key
+		let key_version = core.key_share.as_ref().and_then(|ks| ks.version(version).ok().map(|kv| (ks, kv)));
+		match key_version {
+			Some((key_share, key_version)) => {
+				// there must be exactly thresold + 1 nodes in consensus group
+				if consensus_group.len() != key_share.threshold + 1 {
+					return Err(Error::ConsensusUnreachable);
+				}
+
+				// every owner of key version must be in new nodes set && has the same id number
+				// => all old shares are replaced with new shares
+				if key_version.id_numbers.iter().any(|(n, id)| new_nodes_map.get(n) != Some(id)) {
+					return Err(Error::ConsensusUnreachable);
+				}
+
+				// every node of consensus group must be an owner of key version
+				if consensus_group.iter().any(|n| !key_version.id_numbers.contains_key(n)) {
+					return Err(Error::ConsensusUnreachable);
+				}
+			},
+			None => {
+				// if we do not have a share, we should not be a part of consenus group
+				if consensus_group.contains(&core.meta.self_node_id) {
+					return Err(Error::ConsensusUnreachable);
+				}
+			},
+		}
+
+		Ok(())
+	}
+
+	/// Start sending ThresholdChange-specific messages, when consensus is established.
+	fn on_consensus_established(core: &SessionCore<T>, data: &mut SessionData<T>) -> Result<(), Error> {
+		// update state
+		data.state = SessionState::WaitingForKeysDissemination;
+
+		// master node is waiting for confirmations from all nodes
+		if core.meta.self_node_id == core.meta.master_node_id {
+			data.unconfirmed_nodes = Some(data.id_numbers.as_ref()
+				.expect("id_numbers are filled during initialization; consensus is established after initialization; qed")
+				.keys().cloned().collect());
+		}
+
+		// if we're not a part of consensus group, wait for secret subshares
+		let explanation = "secret_subshares is a result of consensus job; consensus is established; qed";
+		let is_consensus_group_node = data.secret_subshares.as_ref().expect(explanation).contains_key(&core.meta.self_node_id);
+		if !is_consensus_group_node {
+			return Ok(());
+		}
+
+		// else if master => send shared data to every node without key share
+		if core.meta.self_node_id == core.meta.master_node_id {
+			Self::disseminate_common_share_data(core, data)?;
+		}
+
+		// ...and then disseminate keys
+		Self::disseminate_keys(core, data)?;
+
+		// ..and check if new key share could be computed
+		Self::try_compute_new_key_share(core, data)
+	}
+
+	/// Send common share data to every node, which has no share of the key.
+	fn disseminate_common_share_data(core: &SessionCore<T>, data: &SessionData<T>) -> Result<(), Error> {
+		let explanation = "disseminate_common_share_data is only called on master node; master node has the key share; qed";
+		let key_share = core.key_share.as_ref().expect(explanation);
+		let key_version = key_share.version(data.version.as_ref().expect(explanation)).expect(explanation);
+		let id_numbers = data.id_numbers.as_ref()
+			.expect("nodes are filled during consensus establishing; common share data sent after consensus is established; qed");
+		for node in id_numbers.keys().filter(|n| !key_version.id_numbers.contains_key(n)) {
+			core.transport.send(node, ThresholdChangeMessage::KeyShareCommon(KeyShareCommon {
+				session: core.meta.id.clone().into(),
+				session_nonce: core.nonce,
+				threshold: data.new_threshold.expect(explanation),
+				author: key_share.author.clone().into(),
+				joint_public: key_share.public.clone().into(),
+				common_point: key_share.common_point.clone().map(Into::into),
+				encrypted_point: key_share.encrypted_point.clone().map(Into::into),
+				id_numbers: id_numbers.iter().map(|(k, v)| (k.clone().into(), v.clone().into())).collect(),
+			}))?;
+		}
+
+		Ok(())
+	}
+
+	/// Disseminate re-shared keys.
+	fn disseminate_keys(core: &SessionCore<T>, data: &mut SessionData<T>) -> Result<(), Error> {
+		let explanation = "disseminate_keys is called after initialization has completed; this field is filled during initialization; qed";
+		let self_secret_subshare = {
+			// generate random polynom with interpolated secret share as absolute term
+			let key_share_explanation = "disseminate_keys is only called on consensus group nodes; consensus group nodes have specified version of the key; qed";
+			let key_share = core.key_share.as_ref().expect(key_share_explanation);
+			let key_version = key_share.version(data.version.as_ref().expect(explanation)).expect(key_share_explanation);
+			let id_numbers = data.id_numbers.as_ref().expect(explanation);
+			let consensus_group = data.secret_subshares.as_ref().expect(explanation);
+			let self_id_number = &id_numbers[&core.meta.self_node_id];
+			let other_id_numbers = consensus_group.keys()
+				.filter(|n| **n != core.meta.self_node_id)
+				.map(|n| &id_numbers[n]);
+			let mut secret_share_polynom = math::generate_random_polynom(data.new_threshold.expect(explanation))?;
+			secret_share_polynom[0] = math::compute_secret_subshare(key_share.threshold, &key_version.secret_share, self_id_number, other_id_numbers)?;
+			let commitments = math::compute_polynom_commitments(&secret_share_polynom)?;
+
+			// calculate secret subshare for every node (including this node)
+			for (node, node_number) in id_numbers.iter().filter(|&(n, _)| *n != core.meta.self_node_id) {
+				core.transport.send(node, ThresholdChangeMessage::ReshareKeysDissemination(ReshareKeysDissemination {
+					session: core.meta.id.clone().into(),
+					session_nonce: core.nonce,
+					secret_subshare: math::compute_polynom(&secret_share_polynom, node_number)?.into(),
+					commitments: commitments.iter().cloned().map(Into::into).collect(),
+				}))?;
+			}
+
+			(math::compute_polynom(&secret_share_polynom, self_id_number)?, commitments[0].clone())
+		};
+
+		*data.secret_subshares.as_mut().expect(explanation)
+			.get_mut(&core.meta.self_node_id)
+			.expect("disseminate_keys is only called on consensus group nodes; there's entry for every consensus node in secret_subshares; qed")
+				= Some(self_secret_subshare);
+
+		Ok(())
+	}
+
+	/// Compute new key share if all subshares are received.
+	fn try_compute_new_key_share(core: &SessionCore<T>, data: &mut SessionData<T>) -> Result<(), Error> {
+		// check if we're still waiting for some data
+		if data.state != SessionState::WaitingForKeysDissemination {
+			return Ok(());
+		}
+		let explanation = "secret_subshares is filled during initialization; new key share is computed after initialization; qed";
+		if data.secret_subshares.as_ref().expect(explanation).values().any(Option::is_none) {
+			return Ok(());
+		}
+		let has_key_version = Self::has_key_version(core, data);
+		if !has_key_version && data.key_share_common.is_none() {
+			return Ok(());
+		}
+
+		// check that re-shared key is the same key
+		let secret_subshares: Vec<_> = data.secret_subshares.as_ref().expect(explanation).values()
+			.map(|ss| ss.clone().expect("checked above; qed"))
+			.collect();
+		let joint_public = math::compute_public_sum(secret_subshares.iter().map(|ss| &ss.1))?;
+		let mut new_key_share = match has_key_version {
+			true => {
+				let mut key_share = core.key_share.clone().expect("has_key_version is true only when key share exists; qed");
+				key_share.versions.clear();
+				key_share
+			},
+			false => {
+				let key_share_common = data.key_share_common.as_ref().expect("checked above; qed");
+				DocumentKeyShare {
+					author: key_share_common.author.clone(),
+					threshold: 0,
+					public: key_share_common.joint_public.clone(),
+					common_point: key_share_common.common_point.clone(),
+					encrypted_point: key_share_common.encrypted_point.clone(),
+					versions: Vec::new(),
+				}
+			},
+		};
+		if joint_public != new_key_share.public {
+			return Err(Error::InvalidMessage);
+		}
+
+		// compose new key share
+		let secret_share = math::compute_secret_share(secret_subshares.iter().map(|ss| &ss.0))?;
+		new_key_share.threshold = data.new_threshold.expect(explanation);
+		new_key_share.versions.push(DocumentKeyShareVersion::new(data.id_numbers.clone().expect(explanation), secret_share));
+		data.new_key_share = Some(new_key_share);
+		data.state = SessionState::WaitingForCommit;
+
+		// confirm new key share
+		if core.meta.self_node_id != core.meta.master_node_id {
+			return core.transport.send(&core.meta.master_node_id, ThresholdChangeMessage::ConfirmNewKeyShare(ConfirmNewKeyShare {
+				session: core.meta.id.clone().into(),
+				session_nonce: core.nonce,
+			}));
+		}
+
+		data.unconfirmed_nodes.as_mut()
+			.expect("unconfirmed_nodes are filled on master when consensus is established; qed")
+			.remove(&core.meta.self_node_id);
+		Self::try_commit_new_key_shares(core, data)
+	}
+
+	/// Commit new key shares if all nodes have confirmed them.
+	fn try_commit_new_key_shares(core: &SessionCore<T>, data: &mut SessionData<T>) -> Result<(), Error> {
+		// check if we're still waiting for confirmations
+		let is_confirmed_by_all = data.unconfirmed_nodes.as_ref()
+			.expect("unconfirmed_nodes are filled on master when consensus is established; qed")
+			.is_empty();
+		if data.state != SessionState::WaitingForCommit || !is_confirmed_by_all {
+			return Ok(());
+		}
+
+		// ask other nodes to save new key shares, while keeping our old key share
+		let explanation = "id_numbers are filled during initialization; commit happens after initialization; qed";
+		let other_nodes: BTreeSet<_> = data.id_numbers.as_ref().expect(explanation).keys()
+			.filter(|n| **n != core.meta.self_node_id)
+			.cloned()
+			.collect();
+		for node in &other_nodes {
+			core.transport.send(node, ThresholdChangeMessage::CommitNewKeyShares(CommitNewKeyShares {
+				session: core.meta.id.clone().into(),
+				session_nonce: core.nonce,
+			}))?;
+		}
+
+		data.uncommitted_nodes = Some(other_nodes);
+		data.state = SessionState::WaitingForCompletion;
+		Self::try_complete_session(core, data)
+	}
+
+	/// Replace old key share with the new one on master node if all other nodes have saved their new key shares.
+	fn try_complete_session(core: &SessionCore<T>, data: &mut SessionData<T>) -> Result<(), Error> {
+		// check if we're still waiting for commits
+		let is_committed_by_all = data.uncommitted_nodes.as_ref()
+			.expect("uncommitted_nodes are filled on master when commit is started; qed")
+			.is_empty();
+		if !is_committed_by_all {
+			return Ok(());
+		}
+
+		// save new share first, so that error is broadcasted if we have failed to do that
+		Self::save_new_key_share(core, data)?;
+		Self::complete_session(core, data)?;
+
+		// ...and let other nodes forget their old key shares
+		// session is already committed => failing to notify one node must not prevent notifying the others
+		let explanation = "id_numbers are filled during initialization; completion happens after initialization; qed";
+		for node in data.id_numbers.as_ref().expect(explanation).keys().filter(|n| **n != core.meta.self_node_id) {
+			let send_result = core.transport.send(node, ThresholdChangeMessage::ThresholdChangeCompleted(ThresholdChangeCompleted {
+				session: core.meta.id.clone().into(),
+				session_nonce: core.nonce,
+			}));
+			if let Err(error) = send_result {
+				warn!(target: "secretstore_net", "{}: failed to notify {} about threshold change completion: {}",
+					core.meta.self_node_id, node, error);
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Replace old key share with the new one.
+	fn save_new_key_share(core: &SessionCore<T>, data: &mut SessionData<T>) -> Result<(), Error> {
+		let new_key_share = data.new_key_share.take()
+			.expect("new key share is saved in WaitingForCommit state; new_key_share is filled when entering this state; qed");
+		if core.key_share.is_some() {
+			core.key_storage.update(core.meta.id.clone(), new_key_share)
+		} else {
+			core.key_storage.insert(core.meta.id.clone(), new_key_share)
+		}
+	}
+
+	/// Replace new key share with the old one on slave node, if master has reported that session has failed.
+	fn restore_uncommitted_key_share(&self, error: Error) {
+		let mut data = self.data.lock();
+		let is_new_key_share_saved = data.state == SessionState::WaitingForCompletion
+			&& self.core.meta.self_node_id != self.core.meta.master_node_id;
+		if !is_new_key_share_saved {
+			return;
+		}
+
+		if let Err(error) = Self::restore_old_key_share(&self.core) {
+			warn!(target: "secretstore_net", "{}: failed to restore old key share after threshold change failure: {}",
+				self.core.meta.self_node_id, error);
+		}
+
+		warn!(target: "secretstore_net", "{}: threshold change session failed on master: {}",
+			self.core.meta.self_node_id, error);
+
+		data.state = SessionState::Finished;
+		data.result = Some(Err(error));
+		self.core.completed.notify_all();
+	}
+
+	/// Replace new key share with the old one.
+	fn restore_old_key_share(core: &SessionCore<T>) -> Result<(), Error> {
+		match core.key_share.clone() {
+			Some(key_share) => core.key_storage.update(core.meta.id.clone(), key_share),
+			None => core.key_storage.remove(&core.meta.id),
+		}
+	}
+
+	/// Complete session.
+	fn complete_session(core: &SessionCore<T>, data: &mut SessionData<T>) -> Result<(), Error> {
+		// signal session completion
+		data.state = SessionState::Finished;
+		data.result = Some(Ok(()));
+		core.completed.notify_all();
+
+		Ok(())
+	}
+}
+
+impl<T> ClusterSession for SessionImpl<T> where T: SessionTransport {
+	type Id = SessionId;
+
+	fn type_name() -> &'static str {
+		"threshold change"
+	}
+
+	fn id(&self) -> SessionId {
+		self.core.meta.id.clone()
+	}
+
+	fn is_finished(&self) -> bool {
+		self.data.lock().state == SessionState::Finished
+	}
+
+	fn is_failed(&self) -> bool {
+		match self.data.lock().result {
+			Some(Err(_)) => true,
+			_ => false,
+		}
+	}
+
+	fn on_session_timeout(&self) {
+		self.on_session_error(&self.core.meta.self_node_id, Error::NodeDisconnected)
+	}
+
+	fn on_node_timeout(&self, node: &NodeId) {
+		self.on_session_error(node, Error::NodeDisconnected)
+	}
+
+	fn on_session_error(&self, node: &NodeId, error: Error) {
+		let mut data = self.data.lock();
+
+		// old key share is only kept until session is completed
+		if data.state == SessionState::Finished {
+			return;
+		}
+
+		// slave nodes save new key share before all other nodes have saved theirs and only master knows
+		// whether session has been committed => slave never restores the old key share on its own and
+		// only fails the session if it has lost master (restoring happens when master reports an error)
+		let is_master = self.core.meta.self_node_id == self.core.meta.master_node_id;
+		let is_new_key_share_saved = data.state == SessionState::WaitingForCompletion;
+		if is_new_key_share_saved && !is_master {
+			if *node != self.core.meta.self_node_id && *node != self.core.meta.master_node_id {
+				return;
+			}
+
+			warn!(target: "secretstore_net", "{}: threshold change session failed after new key share is saved: {} on {}",
+				self.core.meta.self_node_id, error, node);
+
+			data.state = SessionState::Finished;
+			data.result = Some(Err(error));
+			self.core.completed.notify_all();
+			return;
+		}
+
+		// error in threshold change session is considered fatal
+		// => broadcast error if error occured on this node
+		// => broadcast error if master has not yet committed, so that slave nodes restore their old key shares
+		if *node == self.core.meta.self_node_id || (is_master && is_new_key_share_saved) {
+			for node in self.core.transport.nodes() {
+				// do not bother processing send error, as we already processing error
+				let _ = self.core.transport.send(&node, ThresholdChangeMessage::ThresholdChangeError(ThresholdChangeError {
+					session: self.core.meta.id.clone().into(),
+					session_nonce: self.core.nonce,
+					error: error.clone().into(),
+				}));
+			}
+		}
+
+		warn!(target: "secretstore_net", "{}: threshold change session failed: {} on {}",
+			self.core.meta.self_node_id, error, node);
+
+		data.state = SessionState::Finished;
+		data.new_key_share = None;
+		data.result = Some(Err(error));
+		self.core.completed.notify_all();
+	}
+
+	fn on_message(&self, sender: &NodeId, message: &Message) -> Result<(), Error> {
+		match *message {
+			Message::ThresholdChange(ref message) => self.process_message(sender, message),
+			_ => unreachable!("cluster checks message to be correct before passing; qed"),
+		}
+	}
+}
+
+impl IsolatedSessionTransport {
+	pub fn new(session_id: SessionId, nonce: u64, cluster: Arc<Cluster>) -> Self {
+		IsolatedSessionTransport {
+			session: session_id,
+			nonce: nonce,
+			cluster: cluster,
+			version: None,
+			new_threshold: None,
+			consensus_group: None,
+			id_numbers: None,
+		}
+	}
+}
+
+impl JobTransport for IsolatedSessionTransport {
+	type PartialJobRequest = ThresholdChangeAccessRequest;
+	type PartialJobResponse = bool;
+
+	fn send_partial_request(&self, node: &NodeId, request: ThresholdChangeAccessRequest) -> Result<(), Error> {
+		let explanation = "partial requests are sent from master node only; on master node this field is filled during creation; qed";
+		let id_numbers = self.id_numbers.as_ref().expect(explanation);
+
+		self.cluster.send(node, Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeConsensusMessage(ThresholdChangeConsensusMessage {
+			session: self.session.clone().into(),
+			session_nonce: self.nonce,
+			message: ConsensusMessageOfThresholdChange::InitializeConsensusSession(InitializeConsensusSessionOfThresholdChange {
+				version: self.version.clone().expect(explanation).into(),
+				new_threshold: request.new_threshold,
+				consensus_group: self.consensus_group.as_ref().expect(explanation).iter().cloned().map(Into::into).collect(),
+				new_nodes_map: request.new_servers_set.into_iter()
+					.filter_map(|n| id_numbers.get(&n).map(|id| (n.into(), id.clone().into())))
+					.collect(),
+				signature: request.signature.into(),
+			}),
+		})))
+	}
+
+	fn send_partial_response(&self, node: &NodeId, response: bool) -> Result<(), Error> {
+		self.cluster.send(node, Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeConsensusMessage(ThresholdChangeConsensusMessage {
+			session: self.session.clone().into(),
+			session_nonce: self.nonce,
+			message: ConsensusMessageOfThresholdChange::ConfirmConsensusInitialization(ConfirmConsensusInitialization {
+				is_confirmed: response,
+			}),
+		})))
+	}
+}
+
+impl SessionTransport for IsolatedSessionTransport {
+	fn nodes(&self) -> BTreeSet<NodeId> {
+		self.cluster.nodes()
+	}
+
+	fn set_master_data(&mut self, version: H256, new_threshold: usize, consensus_group: BTreeSet<NodeId>, id_numbers: BTreeMap<NodeId, Secret>) {
+		self.version = Some(version);
+		self.new_threshold = Some(new_threshold);
+		self.consensus_group = Some(consensus_group);
+		self.id_numbers = Some(id_numbers);
+	}
+
+	fn send(&self, node: &NodeId, message: ThresholdChangeMessage) -> Result<(), Error> {
+		self.cluster.send(node, Message::ThresholdChange(message))
+	}
+}
+
+#[cfg(test)]
+pub mod tests {
+	use std::collections::BTreeSet;
+	use ethkey::{Random, Generator, Public, sign};
+	use key_server_cluster::{NodeId, SessionId, Error, KeyStorage, NodeKeyPair};
+	use key_server_cluster::cluster::tests::MessageLoop as ClusterMessageLoop;
+	use key_server_cluster::cluster_sessions::ClusterSession;
+	use key_server_cluster::message::{Message, ThresholdChangeMessage};
+	use key_server_cluster::servers_set_change_session::tests::{MessageLoop, AdminSessionAdapter, generate_key};
+	use key_server_cluster::admin_sessions::ShareChangeSessionMeta;
+	use key_server_cluster::jobs::threshold_change_access_job::threshold_change_hash;
+	use key_server_cluster::math;
+	use super::{SessionImpl, SessionParams, IsolatedSessionTransport};
+
+	struct Adapter;
+
+	impl AdminSessionAdapter<SessionImpl<IsolatedSessionTransport>> for Adapter {
+		const SIGN_NEW_NODES: bool = false;
+
+		fn create(
+			mut meta: ShareChangeSessionMeta,
+			admin_public: Public,
+			_: BTreeSet<NodeId>,
+			ml: &ClusterMessageLoop,
+			idx: usize
+		) -> SessionImpl<IsolatedSessionTransport> {
+			meta.self_node_id = *ml.node_key_pair(idx).public();
+			SessionImpl::new(SessionParams {
+				meta: meta.clone(),
+				transport: IsolatedSessionTransport::new(meta.id, 1, ml.cluster(idx).view().unwrap()),
+				key_storage: ml.key_storage(idx).clone(),
+				admin_public: Some(admin_public),
+				nonce: 1,
+			}).unwrap()
+		}
+	}
+
+	impl MessageLoop<SessionImpl<IsolatedSessionTransport>> {
+		pub fn init_at(self, master: NodeId, new_threshold: usize) -> Result<Self, Error> {
+			let request_hash = threshold_change_hash(&SessionId::default(), new_threshold, &self.all_nodes_set);
+			let signature = sign(self.admin_key_pair.secret(), &request_hash).unwrap();
+			self.sessions[&master].initialize(new_threshold, signature)?;
+			Ok(self)
+		}
+
+		pub fn run_at(self, master: NodeId, new_threshold: usize) -> Result<Self, Error> {
+			let mut ml = self.init_at(master, new_threshold)?;
+			ml.run();
+			Ok(ml)
+		}
+
+		pub fn run_until_error(&mut self) -> Result<(), Error> {
+			while let Some(message) = self.take_message() {
+				self.process_message(message)?;
+			}
+			Ok(())
+		}
+
+		/// Check that every new_threshold + 1 nodes are able to decrypt data, encrypted with the original key.
+		pub fn check_key_is_reshared(&self, new_threshold: usize) {
+			let nodes: Vec<_> = self.sessions.keys().cloned().collect();
+			let key_shares: Vec<_> = nodes.iter()
+				.map(|n| self.ml.key_storage_of(n).get(&SessionId::default()).unwrap().unwrap())
+				.collect();
+			assert!(key_shares.iter().all(|ks| ks.threshold == new_threshold && ks.versions.len() == 1));
+
+			let document_secret_plain = math::generate_random_point().unwrap();
+			for first in 0..nodes.len() - new_threshold {
+				let subset: Vec<_> = (first..first + new_threshold + 1).collect();
+				let id_numbers: Vec<_> = subset.iter()
+					.map(|i| key_shares[*i].last_version().unwrap().id_numbers[&nodes[*i]].clone())
+					.collect();
+				let secret_shares: Vec<_> = subset.iter()
+					.map(|i| key_shares[*i].last_version().unwrap().secret_share.clone())
+					.collect();
+				let (document_secret_decrypted, document_secret_decrypted_test) =
+					math::tests::do_encryption_and_decryption(new_threshold,
+						self.original_key_pair.public(),
+						&id_numbers,
+						&secret_shares,
+						Some(self.original_key_pair.secret()),
+						document_secret_plain.clone());
+
+				assert_eq!(document_secret_plain, document_secret_decrypted_test);
+				assert_eq!(document_secret_plain, document_secret_decrypted);
+			}
+		}
+	}
+
+	#[test]
+	fn threshold_increased_on_all_nodes() {
+		// initial 2-of-3 session
+		let gml = generate_key(3, 1);
+
+		// add 2 nodes && change threshold so that it becames 3-of-5 session
+		let add = (0..2).map(|_| Random.generate().unwrap()).collect();
+		let master = gml.0.node(0);
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, Some(add), None, None)
+			.run_at(master, 2).unwrap();
+
+		ml.check_key_is_reshared(2);
+	}
+
+	#[test]
+	fn threshold_decreased_on_the_same_nodes() {
+		// initial 3-of-4 session
+		let gml = generate_key(4, 2);
+
+		// change threshold so that it becames 2-of-4 session
+		let master = gml.0.node(0);
+		let ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.run_at(master, 1).unwrap();
+
+		ml.check_key_is_reshared(1);
+	}
+
+	#[test]
+	fn threshold_change_fails_if_started_on_node_without_key_share() {
+		// initial 2-of-3 session
+		let gml = generate_key(3, 1);
+
+		// try to change threshold using new node as a master node
+		let add = vec![Random.generate().unwrap()];
+		let master = *add[0].public();
+		assert_eq!(MessageLoop::with_gml::<Adapter>(gml, master, Some(add), None, None)
+			.init_at(master, 2).unwrap_err(), Error::ServerKeyIsNotFound);
+	}
+
+	#[test]
+	fn threshold_change_fails_if_not_enough_nodes() {
+		// initial 2-of-3 session
+		let gml = generate_key(3, 1);
+
+		// try to change threshold so that it becames 4-of-3 session
+		let master = gml.0.node(0);
+		assert_eq!(MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.init_at(master, 3).unwrap_err(), Error::NotEnoughNodesForThreshold);
+	}
+
+	#[test]
+	fn threshold_change_fails_if_initialized_twice() {
+		// initial 2-of-3 session
+		let gml = generate_key(3, 1);
+
+		let master = gml.0.node(0);
+		assert_eq!(MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.init_at(master, 2).unwrap()
+			.init_at(master, 2).unwrap_err(), Error::InvalidStateForRequest);
+	}
+
+	#[test]
+	fn threshold_change_fails_if_share_owner_is_disconnected() {
+		// initial 2-of-3 session
+		let gml = generate_key(3, 1);
+
+		// isolate one of share owners
+		let master = gml.0.node(0);
+		let isolate = ::std::iter::once(gml.0.node(1)).collect();
+		let add = vec![Random.generate().unwrap()];
+		assert_eq!(MessageLoop::with_gml::<Adapter>(gml, master, Some(add), None, Some(isolate))
+			.init_at(master, 1).unwrap_err(), Error::NodeDisconnected);
+	}
+
+	#[test]
+	fn threshold_change_fails_if_not_signed_by_administrator() {
+		// initial 2-of-3 session
+		let gml = generate_key(3, 1);
+
+		// sign request with random key
+		let master = gml.0.node(0);
+		let mut ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None);
+		let request_hash = threshold_change_hash(&SessionId::default(), 2, &ml.all_nodes_set);
+		let signature = sign(Random.generate().unwrap().secret(), &request_hash).unwrap();
+		ml.sessions[&master].initialize(2, signature).unwrap();
+		assert_eq!(ml.run_until_error(), Err(Error::ConsensusUnreachable));
+
+		// key shares are unchanged
+		assert!(ml.sessions.keys().all(|n| ml.ml.key_storage_of(n).get(&SessionId::default()).unwrap().unwrap().threshold == 1));
+	}
+
+	#[test]
+	fn old_key_shares_are_restored_if_node_fails_to_commit() {
+		// initial 3-of-4 session
+		let gml = generate_key(4, 2);
+
+		// change threshold, but lose commit confirmation of one of nodes
+		let master = gml.0.node(0);
+		let mut ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.init_at(master, 1).unwrap();
+		let mut is_commit_lost = false;
+		while let Some(message) = ml.take_message() {
+			match message.2 {
+				Message::ThresholdChange(ThresholdChangeMessage::NewKeyShareCommitted(_)) if !is_commit_lost =>
+					is_commit_lost = true,
+				_ => ml.process_message(message).unwrap(),
+			}
+		}
+		assert!(is_commit_lost);
+
+		// master still has its old key share, while other nodes have saved new key shares
+		let threshold_of = |ml: &MessageLoop<SessionImpl<IsolatedSessionTransport>>, node: &NodeId|
+			ml.ml.key_storage_of(node).get(&SessionId::default()).unwrap().unwrap().threshold;
+		assert!(ml.sessions.keys().all(|n| threshold_of(&ml, n) == if *n == master { 2 } else { 1 }));
+
+		// when session fails, every node restores its old key share
+		ml.sessions[&master].on_session_timeout();
+		ml.run_until_error().unwrap();
+		assert!(ml.sessions.values().all(|s| s.is_finished() && s.is_failed()));
+		assert!(ml.sessions.keys().all(|n| threshold_of(&ml, n) == 2));
+	}
+
+	#[test]
+	fn new_key_shares_are_kept_if_completion_is_lost_after_commit() {
+		// initial 3-of-4 session
+		let gml = generate_key(4, 2);
+
+		// change threshold, but lose completion notification of one of nodes
+		let master = gml.0.node(0);
+		let mut ml = MessageLoop::with_gml::<Adapter>(gml, master, None, None, None)
+			.init_at(master, 1).unwrap();
+		let mut lost_completion_node = None;
+		while let Some(message) = ml.take_message() {
+			match message.2 {
+				Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeCompleted(_)) if lost_completion_node.is_none() =>
+					lost_completion_node = Some(message.1.clone()),
+				_ => ml.process_message(message).unwrap(),
+			}
+		}
+		let lost_completion_node = lost_completion_node.unwrap();
+
+		// when node loses master or other nodes, it keeps its new key share, because master has committed
+		let other_node = ml.sessions.keys().cloned().find(|n| *n != master && *n != lost_completion_node).unwrap();
+		ml.sessions[&lost_completion_node].on_node_timeout(&other_node);
+		assert!(!ml.sessions[&lost_completion_node].is_finished());
+		ml.sessions[&lost_completion_node].on_node_timeout(&master);
+		assert!(ml.sessions[&lost_completion_node].is_failed());
+		ml.run_until_error().unwrap();
+
+		let threshold_of = |ml: &MessageLoop<SessionImpl<IsolatedSessionTransport>>, node: &NodeId|
+			ml.ml.key_storage_of(node).get(&SessionId::default()).unwrap().unwrap().threshold;
+		assert!(ml.sessions.keys().all(|n| threshold_of(&ml, n) == 1));
+	}
+}
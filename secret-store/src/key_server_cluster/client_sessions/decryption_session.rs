@@ -690,6 +690,15 @@ impl ClusterSession for SessionImpl {
 			|| data.result.is_some()
 	}
 
+	fn is_failed(&self) -> bool {
+		let data = self.data.lock();
+		data.consensus_session.state() == ConsensusSessionState::Failed
+			|| match data.result {
+				Some(Err(_)) => true,
+				_ => false,
+			}
+	}
+
 	fn on_node_timeout(&self, node: &NodeId) {
 		// ignore error, only state matters
 		let _ = self.process_node_error(Some(node), Error::NodeDisconnected);
@@ -253,6 +253,10 @@ impl ClusterSession for SessionImpl {
 			|| data.state == SessionState::Finished
 	}
 
+	fn is_failed(&self) -> bool {
+		self.data.lock().state == SessionState::Failed
+	}
+
 	fn on_node_timeout(&self, node: &NodeId) {
 		let mut data = self.data.lock();
 
@@ -17,10 +17,11 @@
 use std::sync::Arc;
 use std::collections::{BTreeMap, BTreeSet};
 use parking_lot::RwLock;
-use ethkey::{Public, Signature, Random, Generator};
+use ethkey::{Public, Signature, Random, Generator, verify_public};
 use ethereum_types::{Address, H256};
 use parity_runtime::Executor;
-use key_server_cluster::{Error, NodeId, SessionId, Requester, AclStorage, KeyStorage, KeyServerSet, NodeKeyPair};
+use key_server_cluster::{Error, NodeId, SessionId, Requester, AclStorage, KeyStorage, KeyServerSet, NodeKeyPair,
+	AdminSessionStatus, KeyServerStatus, AdminMigrationMode};
 use key_server_cluster::cluster_sessions::{ClusterSession, AdminSession, ClusterSessions, SessionIdWithSubSession,
 	ClusterSessionsContainer, SERVERS_SET_CHANGE_SESSION_ID, create_cluster_view,
 	AdminSessionCreationData, ClusterSessionsListener};
@@ -40,6 +41,8 @@ use key_server_cluster::key_version_negotiation_session::{SessionImpl as KeyVers
 use key_server_cluster::connection_trigger::{ConnectionTrigger,
 	SimpleConnectionTrigger, ServersSetChangeSessionCreatorConnector};
 use key_server_cluster::connection_trigger_with_migration::ConnectionTriggerWithMigration;
+use key_server_cluster::connection_trigger_with_admin_migration::ConnectionTriggerWithAdminMigration;
+use key_server_cluster::jobs::servers_set_change_access_job::ordered_nodes_hash;
 
 #[cfg(test)]
 use key_server_cluster::cluster_connections::tests::{MessagesQueue, TestConnections, new_test_connections};
@@ -60,6 +63,14 @@ pub trait ClusterClient: Send + Sync {
 	fn new_key_version_negotiation_session(&self, session_id: SessionId) -> Result<Arc<KeyVersionNegotiationSession<KeyVersionNegotiationSessionTransport>>, Error>;
 	/// Start new servers set change session.
 	fn new_servers_set_change_session(&self, session_id: Option<SessionId>, migration_id: Option<H256>, new_nodes_set: BTreeSet<NodeId>, old_set_signature: Signature, new_set_signature: Signature) -> Result<Arc<AdminSession>, Error>;
+	/// Start new threshold change session.
+	fn new_threshold_change_session(&self, session_id: SessionId, new_threshold: usize, signature: Signature) -> Result<Arc<AdminSession>, Error>;
+	/// Get status of all active administrative sessions.
+	fn admin_sessions(&self) -> Vec<AdminSessionStatus>;
+	/// Abort active administrative session. Signature is the signature of session id, made with administrator key.
+	fn abort_admin_session(&self, session_id: &SessionId, signature: &Signature) -> Result<(), Error>;
+	/// Get status of this node: connections to other key servers && sessions statistics.
+	fn status(&self) -> KeyServerStatus;
 
 	/// Listen for new generation sessions.
 	fn add_generation_listener(&self, listener: Arc<ClusterSessionsListener<GenerationSession>>);
@@ -164,12 +175,19 @@ pub fn new_network_cluster(
 		connections: BTreeMap::new(),
 	}));
 
-	let connection_trigger: Box<ConnectionTrigger> = match net_config.auto_migrate_enabled {
-		false => Box::new(SimpleConnectionTrigger::with_config(&config)),
-		true if config.admin_public.is_none() => Box::new(ConnectionTriggerWithMigration::with_config(&config)),
-		true => return Err(Error::Internal(
+	let connection_trigger: Box<ConnectionTrigger> = match (net_config.auto_migrate_enabled, net_config.admin_migration_mode) {
+		(false, AdminMigrationMode::Disabled) => Box::new(SimpleConnectionTrigger::with_config(&config)),
+		(false, mode) if config.admin_public.is_some() => Box::new(ConnectionTriggerWithAdminMigration::with_config(&config, mode)),
+		(false, _) => return Err(Error::Internal(
+			"secret store administrator public key is required for administrator-initiated migration".into()
+		)),
+		(true, AdminMigrationMode::Disabled) if config.admin_public.is_none() => Box::new(ConnectionTriggerWithMigration::with_config(&config)),
+		(true, AdminMigrationMode::Disabled) => return Err(Error::Internal(
 			"secret store admininstrator public key is specified with auto-migration enabled".into()
 		)),
+		(true, _) => return Err(Error::Internal(
+			"secret store administrator-initiated migration is enabled with auto-migration enabled".into()
+		)),
 	};
 
 	let servers_set_change_creator_connector = connection_trigger.servers_set_change_creator_connector();
@@ -458,9 +476,51 @@ impl<C: ConnectionManager> ClusterClient for ClusterClientImpl<C> {
 				new_nodes_set,
 				old_set_signature,
 				new_set_signature,
+				reject_unrecoverable_keys: false,
 			})
 	}
 
+	fn new_threshold_change_session(&self, session_id: SessionId, new_threshold: usize, signature: Signature) -> Result<Arc<AdminSession>, Error> {
+		let cluster = create_cluster_view(self.data.self_key_pair.clone(), self.data.connections.provider(), true)?;
+		let session = self.data.sessions.admin_sessions.insert(cluster, self.data.self_key_pair.public().clone(),
+			session_id, None, false, Some(AdminSessionCreationData::ThresholdChange))?;
+		let initialization_result = session.as_threshold_change().expect("threshold change session is created; qed")
+			.initialize(new_threshold, signature);
+
+		process_initialization_result(
+			initialization_result,
+			session, &self.data.sessions.admin_sessions)
+	}
+
+	fn admin_sessions(&self) -> Vec<AdminSessionStatus> {
+		self.data.sessions.admin_sessions.sessions().into_iter()
+			.map(|(master, session)| session.status(master))
+			.collect()
+	}
+
+	fn status(&self) -> KeyServerStatus {
+		let configured_nodes: BTreeSet<NodeId> = self.data.config.key_server_set.snapshot().current_set.keys().cloned().collect();
+		KeyServerStatus {
+			connected_nodes: self.data.connections.provider().connected_nodes().unwrap_or_default(),
+			servers_set_hash: ordered_nodes_hash(&configured_nodes),
+			configured_nodes: configured_nodes,
+			sessions: self.data.sessions.status(),
+		}
+	}
+
+	fn abort_admin_session(&self, session_id: &SessionId, signature: &Signature) -> Result<(), Error> {
+		let admin_public = self.data.config.admin_public.as_ref().ok_or(Error::AccessDenied)?;
+		if !verify_public(admin_public, signature, session_id)? {
+			return Err(Error::AccessDenied);
+		}
+
+		let session = self.data.sessions.admin_sessions.get(session_id, false)
+			.ok_or(Error::NoActiveSessionWithId)?;
+		session.on_session_error(self.data.self_key_pair.public(), Error::SessionAborted);
+		self.data.sessions.admin_sessions.remove(session_id);
+		Ok(())
+	}
+
 	fn add_generation_listener(&self, listener: Arc<ClusterSessionsListener<GenerationSession>>) {
 		self.data.sessions.generation_sessions.add_listener(listener);
 	}
@@ -500,6 +560,7 @@ pub struct ServersSetChangeParams {
 	pub new_nodes_set: BTreeSet<NodeId>,
 	pub old_set_signature: Signature,
 	pub new_set_signature: Signature,
+	pub reject_unrecoverable_keys: bool,
 }
 
 pub fn new_servers_set_change_session(
@@ -519,7 +580,11 @@ pub fn new_servers_set_change_session(
 	let creation_data = AdminSessionCreationData::ServersSetChange(params.migration_id, params.new_nodes_set.clone());
 	let session = sessions.admin_sessions
 		.insert(cluster, *self_key_pair.public(), session_id, None, true, Some(creation_data))?;
-	let initialization_result = session.as_servers_set_change().expect("servers set change session is created; qed")
+	let servers_set_change_session = session.as_servers_set_change().expect("servers set change session is created; qed");
+	if params.reject_unrecoverable_keys {
+		servers_set_change_session.reject_unrecoverable_keys();
+	}
+	let initialization_result = servers_set_change_session
 		.initialize(params.new_nodes_set, params.old_set_signature, params.new_set_signature);
 
 	if initialization_result.is_ok() {
@@ -562,12 +627,17 @@ pub mod tests {
 	use ethereum_types::{Address, H256};
 	use ethkey::{Random, Generator, Public, Signature, sign};
 	use key_server_cluster::{NodeId, SessionId, Requester, Error, DummyAclStorage, DummyKeyStorage,
-		MapKeyServerSet, PlainNodeKeyPair, NodeKeyPair};
+		MapKeyServerSet, PlainNodeKeyPair, NodeKeyPair, AdminSessionStatus, KeyServerStatus, SessionsStatus,
+		KeyStorage, DocumentKeyShare, DocumentKeyShareVersion, AdminMigrationMode};
 	use key_server_cluster::message::Message;
-	use key_server_cluster::cluster::{new_test_cluster, Cluster, ClusterCore, ClusterConfiguration, ClusterClient};
+	use key_server_cluster::cluster::{new_test_cluster, new_servers_set_change_session, Cluster, ClusterCore,
+		ClusterConfiguration, ClusterClient};
+	use key_server_cluster::connection_trigger::{Maintain, ConnectionTrigger};
+	use key_server_cluster::connection_trigger_with_admin_migration::ConnectionTriggerWithAdminMigration;
 	use key_server_cluster::cluster_connections::ConnectionManager;
 	use key_server_cluster::cluster_connections::tests::{MessagesQueue, TestConnections};
 	use key_server_cluster::cluster_sessions::{ClusterSession, ClusterSessions, AdminSession, ClusterSessionsListener};
+	use key_server_cluster::jobs::servers_set_change_access_job::ordered_nodes_hash;
 	use key_server_cluster::generation_session::{SessionImpl as GenerationSession,
 		SessionState as GenerationSessionState};
 	use key_server_cluster::decryption_session::{SessionImpl as DecryptionSession};
@@ -606,6 +676,10 @@ pub mod tests {
 
 		fn new_key_version_negotiation_session(&self, _session_id: SessionId) -> Result<Arc<KeyVersionNegotiationSession<KeyVersionNegotiationSessionTransport>>, Error> { unimplemented!("test-only") }
 		fn new_servers_set_change_session(&self, _session_id: Option<SessionId>, _migration_id: Option<H256>, _new_nodes_set: BTreeSet<NodeId>, _old_set_signature: Signature, _new_set_signature: Signature) -> Result<Arc<AdminSession>, Error> { unimplemented!("test-only") }
+		fn new_threshold_change_session(&self, _session_id: SessionId, _new_threshold: usize, _signature: Signature) -> Result<Arc<AdminSession>, Error> { unimplemented!("test-only") }
+		fn admin_sessions(&self) -> Vec<AdminSessionStatus> { unimplemented!("test-only") }
+		fn abort_admin_session(&self, _session_id: &SessionId, _signature: &Signature) -> Result<(), Error> { unimplemented!("test-only") }
+		fn status(&self) -> KeyServerStatus { unimplemented!("test-only") }
 
 		fn add_generation_listener(&self, _listener: Arc<ClusterSessionsListener<GenerationSession>>) {}
 		fn add_decryption_listener(&self, _listener: Arc<ClusterSessionsListener<DecryptionSession>>) {}
@@ -684,6 +758,7 @@ pub mod tests {
 	pub struct MessageLoop {
 		messages: MessagesQueue,
 		preserve_sessions: bool,
+		admin_public: Option<Public>,
 		key_pairs_map: BTreeMap<NodeId, Arc<PlainNodeKeyPair>>,
 		acl_storages_map: BTreeMap<NodeId, Arc<DummyAclStorage>>,
 		key_storages_map: BTreeMap<NodeId, Arc<DummyKeyStorage>>,
@@ -717,6 +792,11 @@ pub mod tests {
 			self.clusters_map.values().nth(idx).unwrap()
 		}
 
+		/// Returns index of the node, holding administrator key.
+		pub fn admin(&self) -> usize {
+			self.clusters_map.keys().position(|node| Some(node) == self.admin_public.as_ref()).unwrap()
+		}
+
 		/// Get keys storage reference by its index.
 		pub fn key_storage(&self, idx: usize) -> &Arc<DummyKeyStorage> {
 			self.key_storages_map.values().nth(idx).unwrap()
@@ -759,6 +839,27 @@ pub mod tests {
 			}
 		}
 
+		/// Make node unreachable from other nodes.
+		pub fn disconnect(&self, idx: usize) {
+			let node = self.node(idx);
+			for (i, cluster) in self.clusters_map.values().enumerate() {
+				if i != idx {
+					cluster.data.connections.disconnect(node);
+				}
+			}
+		}
+
+		/// Make node, disconnected by `disconnect`, reachable again.
+		pub fn reconnect(&self, idx: usize) {
+			let node = self.node(idx);
+			for (i, cluster) in self.clusters_map.values().enumerate() {
+				if i != idx {
+					cluster.data.connections.exclude(node);
+					cluster.data.connections.include(node);
+				}
+			}
+		}
+
 		/// Exclude node from cluster.
 		pub fn exclude(&mut self, idx: usize) {
 			let node = self.node(idx);
@@ -785,7 +886,7 @@ pub mod tests {
 					.collect())),
 				key_storage: key_storage.clone(),
 				acl_storage: acl_storage.clone(),
-				admin_public: None,
+				admin_public: self.admin_public.clone(),
 				preserve_sessions: self.preserve_sessions,
 			};
 			let cluster = new_test_cluster(self.messages.clone(), cluster_params).unwrap();
@@ -839,18 +940,27 @@ pub mod tests {
 	}
 
 	pub fn make_clusters(num_nodes: usize) -> MessageLoop {
-		do_make_clusters(num_nodes, false)
+		do_make_clusters(num_nodes, false, false)
 	}
 
 	pub fn make_clusters_and_preserve_sessions(num_nodes: usize) -> MessageLoop {
-		do_make_clusters(num_nodes, true)
+		do_make_clusters(num_nodes, true, false)
+	}
+
+	pub fn make_clusters_with_admin(num_nodes: usize) -> MessageLoop {
+		do_make_clusters(num_nodes, false, true)
 	}
 
-	fn do_make_clusters(num_nodes: usize, preserve_sessions: bool) -> MessageLoop {
+	fn do_make_clusters(num_nodes: usize, preserve_sessions: bool, with_admin: bool) -> MessageLoop {
 		let ports_begin = 0;
 		let messages = Arc::new(Mutex::new(VecDeque::new()));
 		let key_pairs: Vec<_> = (0..num_nodes)
 			.map(|_| Arc::new(PlainNodeKeyPair::new(Random.generate().unwrap()))).collect();
+		// the first node holds administrator key
+		let admin_public = match with_admin {
+			true => Some(*key_pairs[0].public()),
+			false => None,
+		};
 		let key_storages: Vec<_> = (0..num_nodes).map(|_| Arc::new(DummyKeyStorage::default())).collect();
 		let acl_storages: Vec<_> = (0..num_nodes).map(|_| Arc::new(DummyAclStorage::default())).collect();
 		let cluster_params: Vec<_> = (0..num_nodes).map(|i| ClusterConfiguration {
@@ -860,7 +970,7 @@ pub mod tests {
 				.collect())),
 			key_storage: key_storages[i].clone(),
 			acl_storage: acl_storages[i].clone(),
-			admin_public: None,
+			admin_public: admin_public.clone(),
 			preserve_sessions,
 		}).collect();
 		let clusters: Vec<_> = cluster_params.into_iter()
@@ -873,7 +983,7 @@ pub mod tests {
 			.map(|(c, ks)| (*c.data.config.self_key_pair.public(), ks)).collect();
 		let acl_storages_map = clusters.iter().zip(acl_storages.into_iter())
 			.map(|(c, acls)| (*c.data.config.self_key_pair.public(), acls)).collect();
-		MessageLoop { preserve_sessions, messages, key_pairs_map, acl_storages_map, key_storages_map, clusters_map }
+		MessageLoop { preserve_sessions, admin_public, messages, key_pairs_map, acl_storages_map, key_storages_map, clusters_map }
 	}
 
 	#[test]
@@ -1107,4 +1217,164 @@ pub mod tests {
 		ml.loop_until(|| session.is_finished());
 		session1.wait().unwrap_err();
 	}
+
+	#[test]
+	fn status_reports_connected_nodes_and_servers_set_hash() {
+		let ml = make_clusters(3);
+		let status = ml.cluster(0).client().status();
+		assert_eq!(status.configured_nodes, ml.nodes());
+		assert_eq!(status.connected_nodes, vec![ml.node(1), ml.node(2)].into_iter().collect());
+		assert_eq!(status.servers_set_hash, ordered_nodes_hash(&ml.nodes()));
+
+		ml.disconnect(2);
+		let status = ml.cluster(0).client().status();
+		assert_eq!(status.configured_nodes, ml.nodes());
+		assert_eq!(status.connected_nodes, vec![ml.node(1)].into_iter().collect());
+	}
+
+	#[test]
+	fn sessions_statistics_are_updated_when_sessions_are_created_and_completed() {
+		let ml = make_clusters(3);
+		let generation_status = |idx: usize| ml.cluster(idx).client().status().sessions.into_iter()
+			.find(|status| status.session_type == "generation")
+			.unwrap();
+		let assert_counters = |status: SessionsStatus, active, completed, failed| {
+			assert_eq!((status.active, status.completed, status.failed), (active, completed, failed));
+			assert_eq!(status.average_duration.is_some(), completed + failed != 0);
+		};
+
+		// active session is counted on every node
+		let session = ml.cluster(0).client()
+			.new_generation_session(SessionId::default(), Default::default(), Default::default(), 1).unwrap();
+		assert_counters(generation_status(0), 1, 0, 0);
+		ml.loop_until(|| (0..3).all(|i| ml.cluster(i).client().generation_session(&SessionId::default()).is_some()));
+		assert_counters(generation_status(1), 1, 0, 0);
+
+		// completed session is counted once it is removed from the container
+		ml.loop_until(|| session.joint_public_and_secret().is_some()
+			&& (0..3).all(|i| ml.cluster(i).client().generation_session(&SessionId::default()).is_none()));
+		for i in 0..3 {
+			assert_counters(generation_status(i), 0, 1, 0);
+		}
+
+		// failed session is counted separately
+		ml.cluster(0).client().make_faulty_generation_sessions();
+		let session_id = SessionId::from(1);
+		let session = ml.cluster(0).client()
+			.new_generation_session(session_id, Default::default(), Default::default(), 1).unwrap();
+		ml.loop_until(|| session.joint_public_and_secret().is_some()
+			&& ml.cluster(0).client().generation_session(&session_id).is_none());
+		assert!(session.joint_public_and_secret().unwrap().is_err());
+		assert_counters(generation_status(0), 0, 1, 1);
+
+		// other sessions types are not affected
+		assert!(ml.cluster(0).client().status().sessions.into_iter()
+			.filter(|status| status.session_type != "generation")
+			.all(|status| status.active == 0 && status.completed == 0 && status.failed == 0));
+	}
+
+	// servers set, as it is read from the contract.
+	fn contract_set(nodes: &BTreeSet<NodeId>) -> BTreeMap<NodeId, ::std::net::SocketAddr> {
+		nodes.iter().map(|node| (*node, "127.0.0.1:0".parse().unwrap())).collect()
+	}
+
+	fn generate_key(ml: &MessageLoop, session_id: SessionId, threshold: usize) {
+		let session = ml.cluster(ml.admin()).client()
+			.new_generation_session(session_id, Default::default(), Default::default(), threshold).unwrap();
+		ml.loop_until(|| ml.is_empty()
+			&& (0..ml.nodes().len()).all(|i| ml.key_storage(i).get(&session_id).unwrap().is_some()));
+		assert!(session.joint_public_and_secret().unwrap().is_ok());
+	}
+
+	// start servers set change session, the way the connection trigger of administrator node does.
+	fn start_migration(ml: &MessageLoop, trigger: &mut ConnectionTriggerWithAdminMigration) -> Arc<AdminSession> {
+		let admin = ml.admin();
+		assert_eq!(trigger.on_maintain(), Some(Maintain::SessionAndConnections));
+		let params = trigger.maintain_session().unwrap();
+		new_servers_set_change_session(
+			ml.node_key_pair(admin).clone(),
+			ml.sessions(admin),
+			ml.cluster(admin).data.connections.provider(),
+			trigger.servers_set_change_creator_connector(),
+			params).unwrap()
+	}
+
+	#[test]
+	fn administrator_moves_keys_to_node_added_to_contract() {
+		let mut ml = make_clusters_with_admin(3);
+		let key_id = SessionId::from(1);
+		generate_key(&ml, key_id, 1);
+
+		// the contract is initially the same as the current set
+		let old_set = ml.nodes();
+		let contract = Arc::new(MapKeyServerSet::new(false, contract_set(&old_set)));
+		let admin = ml.admin();
+		let mut trigger = ConnectionTriggerWithAdminMigration::new(contract.clone(), ml.key_storage(admin).clone(),
+			ml.node_key_pair(admin).clone(), Some(ml.node(admin)), AdminMigrationMode::Enabled);
+		assert_eq!(trigger.on_maintain(), Some(Maintain::Connections));
+
+		// new node is added to the contract && connected
+		let new_node = Arc::new(PlainNodeKeyPair::new(Random.generate().unwrap()));
+		ml.include(new_node.clone());
+		let admin = ml.admin();
+		for node in ml.nodes().into_iter().filter(|node| *node != ml.node(admin)) {
+			trigger.on_connection_established(&node);
+		}
+		contract.set_nodes(contract_set(&ml.nodes()));
+
+		// session is started by administrator && moves key to the new node
+		let session = start_migration(&ml, &mut trigger);
+		ml.loop_until(|| session.is_finished());
+		assert_eq!(session.as_servers_set_change().unwrap().wait(), Ok(()));
+		let key_share = ml.key_storage_of(new_node.public()).get(&key_id).unwrap().unwrap();
+		assert_eq!(key_share.last_version().unwrap().id_numbers.keys().cloned().collect::<BTreeSet<_>>(), ml.nodes());
+
+		// nothing left to migrate
+		assert_eq!(trigger.on_maintain(), Some(Maintain::Connections));
+		assert!(trigger.maintain_session().is_none());
+	}
+
+	#[test]
+	fn administrator_refuses_to_lose_key_it_does_not_hold() {
+		let mut ml = make_clusters_with_admin(3);
+		generate_key(&ml, SessionId::from(1), 1);
+
+		// key, unknown to administrator, is shared among other nodes
+		let admin = ml.admin();
+		let owners: Vec<_> = ml.nodes().into_iter().filter(|node| *node != ml.node(admin)).collect();
+		let id_numbers: BTreeMap<_, _> = owners.iter()
+			.map(|node| (*node, Random.generate().unwrap().secret().clone()))
+			.collect();
+		let unknown_key_id = SessionId::from(2);
+		for owner in &owners {
+			ml.key_storage_of(owner).insert(unknown_key_id, DocumentKeyShare {
+				threshold: 1,
+				versions: vec![DocumentKeyShareVersion::new(id_numbers.clone(), Random.generate().unwrap().secret().clone())],
+				..Default::default()
+			}).unwrap();
+		}
+
+		let contract = Arc::new(MapKeyServerSet::new(false, contract_set(&ml.nodes())));
+		let mut trigger = ConnectionTriggerWithAdminMigration::new(contract.clone(), ml.key_storage(admin).clone(),
+			ml.node_key_pair(admin).clone(), Some(ml.node(admin)), AdminMigrationMode::Enabled);
+		assert_eq!(trigger.on_maintain(), Some(Maintain::Connections));
+
+		// one of owners is removed from the contract && disconnected
+		// => the administrator's own key could be migrated, but the unknown key couldn't be restored
+		let removed = ml.nodes().into_iter().position(|node| node == owners[1]).unwrap();
+		ml.exclude(removed);
+		let admin = ml.admin();
+		trigger.on_connection_established(&owners[0]);
+		contract.set_nodes(contract_set(&ml.nodes()));
+
+		let session = start_migration(&ml, &mut trigger);
+		ml.loop_until(|| session.is_finished());
+		assert_eq!(session.as_servers_set_change().unwrap().wait(), Err(Error::NotEnoughNodesForThreshold));
+		assert!(ml.key_storage_of(&owners[0]).get(&unknown_key_id).unwrap().is_some());
+
+		// session isn't restarted until the contract changes
+		assert_eq!(trigger.on_maintain(), Some(Maintain::Connections));
+		assert!(trigger.maintain_session().is_none());
+		assert!(ml.key_storage(admin).get(&unknown_key_id).unwrap().is_none());
+	}
 }
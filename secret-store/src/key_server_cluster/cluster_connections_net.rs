@@ -27,7 +27,7 @@ use tokio::timer::{Interval, timeout::Error as TimeoutError};
 use tokio_io::IoFuture;
 use ethkey::KeyPair;
 use parity_runtime::Executor;
-use key_server_cluster::{Error, NodeId, ClusterConfiguration, NodeKeyPair};
+use key_server_cluster::{Error, NodeId, ClusterConfiguration, NodeKeyPair, AdminMigrationMode};
 use key_server_cluster::cluster_connections::{ConnectionProvider, Connection, ConnectionManager};
 use key_server_cluster::connection_trigger::{Maintain, ConnectionTrigger};
 use key_server_cluster::cluster_message_processor::MessageProcessor;
@@ -62,6 +62,8 @@ pub struct NetConnectionsManagerConfig {
 	/// True if we should autostart key servers set change session when servers set changes?
 	/// This will only work when servers set is configured using KeyServerSet contract.
 	pub auto_migrate_enabled: bool,
+	/// Should we start servers set change session when servers set changes and this node holds administrator key?
+	pub admin_migration_mode: AdminMigrationMode,
 }
 
 /// Network connections manager.
@@ -165,6 +165,7 @@ impl SessionsMessageProcessor {
 				Message::Generation(_) => true,
 				Message::ShareAdd(_) => true,
 				Message::ServersSetChange(_) => true,
+				Message::ThresholdChange(_) => true,
 				_ => false,
 			}
 		}
@@ -260,6 +261,9 @@ impl MessageProcessor for SessionsMessageProcessor {
 			Message::ShareAdd(message) => self.process_message(
 				&self.sessions.admin_sessions, connection, Message::ShareAdd(message))
 				.map(|_| ()).unwrap_or_default(),
+			Message::ThresholdChange(message) => self.process_message(
+				&self.sessions.admin_sessions, connection, Message::ThresholdChange(message))
+				.map(|_| ()).unwrap_or_default(),
 			Message::Cluster(message) => self.process_cluster_message(connection, message),
 		}
 	}
@@ -21,7 +21,7 @@ use std::collections::{VecDeque, BTreeMap, BTreeSet};
 use parking_lot::{Mutex, RwLock, Condvar};
 use ethereum_types::H256;
 use ethkey::Secret;
-use key_server_cluster::{Error, NodeId, SessionId, Requester, NodeKeyPair};
+use key_server_cluster::{Error, NodeId, SessionId, Requester, NodeKeyPair, AdminSessionStatus, SessionsStatus};
 use key_server_cluster::cluster::{Cluster, ClusterConfiguration, ClusterView};
 use key_server_cluster::cluster_connections::ConnectionProvider;
 use key_server_cluster::connection_trigger::ServersSetChangeSessionCreatorConnector;
@@ -33,6 +33,8 @@ use key_server_cluster::signing_session_ecdsa::{SessionImpl as EcdsaSigningSessi
 use key_server_cluster::signing_session_schnorr::{SessionImpl as SchnorrSigningSessionImpl};
 use key_server_cluster::share_add_session::{SessionImpl as ShareAddSessionImpl, IsolatedSessionTransport as ShareAddTransport};
 use key_server_cluster::servers_set_change_session::{SessionImpl as ServersSetChangeSessionImpl};
+use key_server_cluster::threshold_change_session::{SessionImpl as ThresholdChangeSessionImpl,
+	IsolatedSessionTransport as ThresholdChangeTransport};
 use key_server_cluster::key_version_negotiation_session::{SessionImpl as KeyVersionNegotiationSessionImpl,
 	IsolatedSessionTransport as VersionNegotiationTransport};
 
@@ -47,6 +49,10 @@ use key_server_cluster::cluster_sessions_creator::{GenerationSessionCreator, Enc
 const SESSION_TIMEOUT_INTERVAL: Duration = Duration::from_secs(60);
 /// Interval to send session-level KeepAlive-messages.
 const SESSION_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+/// Statistics of finished sessions is collected for the last SESSIONS_STATS_INTERVAL.
+const SESSIONS_STATS_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Statistics of finished sessions is aggregated in buckets of SESSIONS_STATS_BUCKET_INTERVAL.
+const SESSIONS_STATS_BUCKET_INTERVAL: Duration = Duration::from_secs(60);
 
 lazy_static! {
 	/// Servers set change session id (there could be at most 1 session => hardcoded id).
@@ -75,6 +81,8 @@ pub trait ClusterSession {
 	fn id(&self) -> Self::Id;
 	/// If session is finished (either with succcess or not).
 	fn is_finished(&self) -> bool;
+	/// If session is finished with an error.
+	fn is_failed(&self) -> bool;
 	/// When it takes too much time to complete session.
 	fn on_session_timeout(&self);
 	/// When it takes too much time to receive response from the node.
@@ -109,6 +117,8 @@ pub enum AdminSession {
 	ShareAdd(ShareAddSessionImpl<ShareAddTransport>),
 	/// Servers set change session.
 	ServersSetChange(ServersSetChangeSessionImpl),
+	/// Threshold change session.
+	ThresholdChange(ThresholdChangeSessionImpl<ThresholdChangeTransport>),
 }
 
 /// Administrative session creation data.
@@ -117,6 +127,8 @@ pub enum AdminSessionCreationData {
 	ShareAdd(H256),
 	/// Servers set change session (block id, new_server_set).
 	ServersSetChange(Option<H256>, BTreeSet<NodeId>),
+	/// Threshold change session.
+	ThresholdChange,
 }
 
 /// Active sessions on this cluster.
@@ -157,6 +169,8 @@ pub struct ClusterSessionsContainer<S: ClusterSession, SC: ClusterSessionCreator
 	sessions: RwLock<BTreeMap<S::Id, QueuedSession<S>>>,
 	/// Listeners. Lock order: sessions -> listeners.
 	listeners: Mutex<Vec<Weak<ClusterSessionsListener<S>>>>,
+	/// Statistics of finished sessions. Lock order: sessions -> stats.
+	stats: Mutex<SessionsStats>,
 	/// Sessions container state.
 	container_state: Arc<Mutex<ClusterSessionsContainerState>>,
 	/// Do not actually remove sessions.
@@ -169,6 +183,8 @@ pub struct ClusterSessionsContainer<S: ClusterSession, SC: ClusterSessionCreator
 pub struct QueuedSession<S> {
 	/// Session master.
 	pub master: NodeId,
+	/// Session creation time.
+	pub creation_time: Instant,
 	/// Cluster view.
 	pub cluster_view: Arc<Cluster>,
 	/// Last keep alive time.
@@ -181,6 +197,25 @@ pub struct QueuedSession<S> {
 	pub queue: VecDeque<(NodeId, Message)>,
 }
 
+/// Statistics of sessions, finished within the last SESSIONS_STATS_INTERVAL.
+#[derive(Default)]
+struct SessionsStats {
+	/// Statistics buckets, ordered by start time.
+	buckets: VecDeque<SessionsStatsBucket>,
+}
+
+/// Statistics of sessions, finished within single bucket interval.
+struct SessionsStatsBucket {
+	/// Bucket start time.
+	start: Instant,
+	/// Number of successfully completed sessions.
+	completed: usize,
+	/// Number of failed sessions.
+	failed: usize,
+	/// Total duration of finished sessions.
+	total_duration: Duration,
+}
+
 /// Cluster sessions container state.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ClusterSessionsContainerState {
@@ -243,6 +278,19 @@ impl ClusterSessions {
 		self.admin_sessions.preserve_sessions = true;
 	}
 
+	/// Get statistics of all sessions.
+	pub fn status(&self) -> Vec<SessionsStatus> {
+		vec![
+			self.generation_sessions.status(),
+			self.encryption_sessions.status(),
+			self.decryption_sessions.status(),
+			self.schnorr_signing_sessions.status(),
+			self.ecdsa_signing_sessions.status(),
+			self.negotiation_sessions.status(),
+			self.admin_sessions.status(),
+		]
+	}
+
 	/// Send session-level keep-alive messages.
 	pub fn sessions_keep_alive(&self) {
 		self.admin_sessions.send_keep_alive(&*SERVERS_SET_CHANGE_SESSION_ID, &self.self_node_id);
@@ -285,6 +333,7 @@ impl<S, SC, D> ClusterSessionsContainer<S, SC, D> where S: ClusterSession, SC: C
 			creator: creator,
 			sessions: RwLock::new(BTreeMap::new()),
 			listeners: Mutex::new(Vec::new()),
+			stats: Mutex::new(SessionsStats::default()),
 			container_state: container_state,
 			preserve_sessions: false,
 			_pd: Default::default(),
@@ -316,6 +365,22 @@ impl<S, SC, D> ClusterSessionsContainer<S, SC, D> where S: ClusterSession, SC: C
 		self.sessions.read().values().nth(0).map(|s| s.session.clone())
 	}
 
+	pub fn sessions(&self) -> Vec<(NodeId, Arc<S>)> {
+		self.sessions.read().values().map(|s| (s.master.clone(), s.session.clone())).collect()
+	}
+
+	pub fn status(&self) -> SessionsStatus {
+		let active = self.sessions.read().len();
+		let (completed, failed, average_duration) = self.stats.lock().summary(Instant::now());
+		SessionsStatus {
+			session_type: S::type_name().into(),
+			active: active,
+			completed: completed,
+			failed: failed,
+			average_duration: average_duration,
+		}
+	}
+
 	pub fn insert(&self, cluster: Arc<Cluster>, master: NodeId, session_id: S::Id, session_nonce: Option<u64>, is_exclusive_session: bool, creation_data: Option<D>) -> Result<Arc<S>, Error> {
 		let mut sessions = self.sessions.write();
 		if sessions.contains_key(&session_id) {
@@ -332,6 +397,7 @@ impl<S, SC, D> ClusterSessionsContainer<S, SC, D> where S: ClusterSession, SC: C
 		// insert session
 		let queued_session = QueuedSession {
 			master: master,
+			creation_time: Instant::now(),
 			cluster_view: cluster,
 			last_keep_alive_time: Instant::now(),
 			last_message_time: Instant::now(),
@@ -397,6 +463,11 @@ impl<S, SC, D> ClusterSessionsContainer<S, SC, D> where S: ClusterSession, SC: C
 		if !self.preserve_sessions {
 			if let Some(session) = sessions.remove(session_id) {
 				self.container_state.lock().on_session_completed();
+				{
+					let now = Instant::now();
+					let duration = now.duration_since(session.creation_time);
+					self.stats.lock().on_session_finished(now, duration, session.session.is_failed());
+				}
 				self.notify_listeners(|l| l.on_session_removed(session.session.clone()));
 			}
 		}
@@ -446,6 +517,54 @@ impl<S, SC, D> ClusterSessionsContainer<S, SC, D> where S: ClusterSession, SC: C
 	}
 }
 
+impl SessionsStats {
+	/// When session is finished (either with success or not).
+	pub fn on_session_finished(&mut self, now: Instant, duration: Duration, is_failed: bool) {
+		self.prune(now);
+
+		let requires_new_bucket = self.buckets.back()
+			.map(|bucket| now >= bucket.start + SESSIONS_STATS_BUCKET_INTERVAL)
+			.unwrap_or(true);
+		if requires_new_bucket {
+			self.buckets.push_back(SessionsStatsBucket {
+				start: now,
+				completed: 0,
+				failed: 0,
+				total_duration: Duration::from_secs(0),
+			});
+		}
+
+		let bucket = self.buckets.back_mut().expect("new bucket is pushed above if there are no buckets; qed");
+		match is_failed {
+			true => bucket.failed += 1,
+			false => bucket.completed += 1,
+		}
+		bucket.total_duration += duration;
+	}
+
+	/// Get number of completed sessions, number of failed sessions and average session duration.
+	pub fn summary(&mut self, now: Instant) -> (usize, usize, Option<Duration>) {
+		self.prune(now);
+
+		let (completed, failed, total_duration) = self.buckets.iter()
+			.fold((0, 0, Duration::from_secs(0)), |(completed, failed, total_duration), bucket|
+				(completed + bucket.completed, failed + bucket.failed, total_duration + bucket.total_duration));
+		let average_duration = match completed + failed {
+			0 => None,
+			finished => Some(total_duration / finished as u32),
+		};
+
+		(completed, failed, average_duration)
+	}
+
+	/// Remove buckets, which are out of the statistics interval.
+	fn prune(&mut self, now: Instant) {
+		while self.buckets.front().map(|bucket| now >= bucket.start + SESSIONS_STATS_INTERVAL).unwrap_or(false) {
+			self.buckets.pop_front();
+		}
+	}
+}
+
 impl ClusterSessionsContainerState {
 	/// When session is starting.
 	pub fn on_session_starting(&mut self, is_exclusive_session: bool) -> Result<(), Error> {
@@ -517,6 +636,30 @@ impl AdminSession {
 			_ => None
 		}
 	}
+
+	pub fn as_threshold_change(&self) -> Option<&ThresholdChangeSessionImpl<ThresholdChangeTransport>> {
+		match *self {
+			AdminSession::ThresholdChange(ref session) => Some(session),
+			_ => None
+		}
+	}
+
+	pub fn status(&self, master: NodeId) -> AdminSessionStatus {
+		let (session_type, progress) = match *self {
+			AdminSession::ShareAdd(_) => (ShareAddSessionImpl::<ShareAddTransport>::type_name(), None),
+			AdminSession::ServersSetChange(ref session) => (ServersSetChangeSessionImpl::type_name(), session.progress()),
+			AdminSession::ThresholdChange(_) => (ThresholdChangeSessionImpl::<ThresholdChangeTransport>::type_name(), None),
+		};
+
+		AdminSessionStatus {
+			id: self.id(),
+			session_type: session_type.into(),
+			master: master,
+			is_finished: self.is_finished(),
+			keys_total: progress.map(|(_, total)| total),
+			keys_processed: progress.map(|(processed, _)| processed),
+		}
+	}
 }
 
 impl ClusterSession for AdminSession {
@@ -530,6 +673,7 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.id().clone(),
 			AdminSession::ServersSetChange(ref session) => session.id().clone(),
+			AdminSession::ThresholdChange(ref session) => session.id().clone(),
 		}
 	}
 
@@ -537,6 +681,15 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.is_finished(),
 			AdminSession::ServersSetChange(ref session) => session.is_finished(),
+			AdminSession::ThresholdChange(ref session) => session.is_finished(),
+		}
+	}
+
+	fn is_failed(&self) -> bool {
+		match *self {
+			AdminSession::ShareAdd(ref session) => session.is_failed(),
+			AdminSession::ServersSetChange(ref session) => session.is_failed(),
+			AdminSession::ThresholdChange(ref session) => session.is_failed(),
 		}
 	}
 
@@ -544,6 +697,7 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.on_session_timeout(),
 			AdminSession::ServersSetChange(ref session) => session.on_session_timeout(),
+			AdminSession::ThresholdChange(ref session) => session.on_session_timeout(),
 		}
 	}
 
@@ -551,6 +705,7 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.on_node_timeout(node_id),
 			AdminSession::ServersSetChange(ref session) => session.on_node_timeout(node_id),
+			AdminSession::ThresholdChange(ref session) => session.on_node_timeout(node_id),
 		}
 	}
 
@@ -558,6 +713,7 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.on_session_error(node, error),
 			AdminSession::ServersSetChange(ref session) => session.on_session_error(node, error),
+			AdminSession::ThresholdChange(ref session) => session.on_session_error(node, error),
 		}
 	}
 
@@ -565,6 +721,7 @@ impl ClusterSession for AdminSession {
 		match *self {
 			AdminSession::ShareAdd(ref session) => session.on_message(sender, message),
 			AdminSession::ServersSetChange(ref session) => session.on_message(sender, message),
+			AdminSession::ThresholdChange(ref session) => session.on_message(sender, message),
 		}
 	}
 }
@@ -590,14 +747,15 @@ pub fn create_cluster_view(self_key_pair: Arc<NodeKeyPair>, connections: Arc<Con
 mod tests {
 	use std::sync::Arc;
 	use std::sync::atomic::{AtomicUsize, Ordering};
+	use std::time::{Duration, Instant};
 	use ethkey::{Random, Generator};
 	use key_server_cluster::{Error, DummyAclStorage, DummyKeyStorage, MapKeyServerSet, PlainNodeKeyPair};
 	use key_server_cluster::cluster::ClusterConfiguration;
 	use key_server_cluster::connection_trigger::SimpleServersSetChangeSessionCreatorConnector;
 	use key_server_cluster::cluster::tests::DummyCluster;
 	use key_server_cluster::generation_session::{SessionImpl as GenerationSession};
-	use super::{ClusterSessions, AdminSessionCreationData, ClusterSessionsListener,
-		ClusterSessionsContainerState, SESSION_TIMEOUT_INTERVAL};
+	use super::{ClusterSessions, AdminSessionCreationData, ClusterSessionsListener, SessionsStats,
+		ClusterSessionsContainerState, SESSION_TIMEOUT_INTERVAL, SESSIONS_STATS_INTERVAL, SESSIONS_STATS_BUCKET_INTERVAL};
 
 	pub fn make_cluster_sessions() -> ClusterSessions {
 		let key_pair = Random.generate().unwrap();
@@ -704,4 +862,33 @@ mod tests {
 		assert_eq!(sessions.generation_sessions.sessions.read().len(), 0);
 		assert_eq!(*sessions.generation_sessions.container_state.lock(), ClusterSessionsContainerState::Idle);
 	}
+
+	#[test]
+	fn sessions_status_is_updated_when_session_is_inserted_and_removed() {
+		let sessions = make_cluster_sessions();
+		sessions.generation_sessions.insert(Arc::new(DummyCluster::new(Default::default())), Default::default(), Default::default(), None, false, None).unwrap();
+		let status = sessions.generation_sessions.status();
+		assert_eq!((status.session_type.as_str(), status.active, status.completed, status.failed), ("generation", 1, 0, 0));
+		assert_eq!(status.average_duration, None);
+
+		sessions.generation_sessions.remove(&Default::default());
+		let status = sessions.generation_sessions.status();
+		assert_eq!((status.active, status.completed, status.failed), (0, 1, 0));
+		assert!(status.average_duration.is_some());
+	}
+
+	#[test]
+	fn sessions_stats_are_collected_within_rolling_window() {
+		let start = Instant::now();
+		let mut stats = SessionsStats::default();
+		stats.on_session_finished(start, Duration::from_secs(2), false);
+		stats.on_session_finished(start + Duration::from_secs(30), Duration::from_secs(4), true);
+		stats.on_session_finished(start + SESSIONS_STATS_BUCKET_INTERVAL, Duration::from_secs(6), false);
+		assert_eq!(stats.summary(start + SESSIONS_STATS_BUCKET_INTERVAL), (2, 1, Some(Duration::from_secs(4))));
+
+		// first bucket is out of the window
+		assert_eq!(stats.summary(start + SESSIONS_STATS_INTERVAL), (1, 0, Some(Duration::from_secs(6))));
+		// and then the second one
+		assert_eq!(stats.summary(start + SESSIONS_STATS_INTERVAL + SESSIONS_STATS_BUCKET_INTERVAL), (0, 0, None));
+	}
 }
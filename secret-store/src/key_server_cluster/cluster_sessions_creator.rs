@@ -24,7 +24,8 @@ use key_server_cluster::cluster::{Cluster, ClusterConfiguration};
 use key_server_cluster::connection_trigger::ServersSetChangeSessionCreatorConnector;
 use key_server_cluster::cluster_sessions::{ClusterSession, SessionIdWithSubSession, AdminSession, AdminSessionCreationData};
 use key_server_cluster::message::{self, Message, DecryptionMessage, SchnorrSigningMessage, ConsensusMessageOfShareAdd,
-	ShareAddMessage, ServersSetChangeMessage, ConsensusMessage, ConsensusMessageWithServersSet, EcdsaSigningMessage};
+	ShareAddMessage, ServersSetChangeMessage, ConsensusMessage, ConsensusMessageWithServersSet, EcdsaSigningMessage,
+	ThresholdChangeMessage, ConsensusMessageOfThresholdChange};
 use key_server_cluster::generation_session::{SessionImpl as GenerationSessionImpl, SessionParams as GenerationSessionParams};
 use key_server_cluster::decryption_session::{SessionImpl as DecryptionSessionImpl,
 	SessionParams as DecryptionSessionParams};
@@ -37,6 +38,8 @@ use key_server_cluster::share_add_session::{SessionImpl as ShareAddSessionImpl,
 	SessionParams as ShareAddSessionParams, IsolatedSessionTransport as ShareAddTransport};
 use key_server_cluster::servers_set_change_session::{SessionImpl as ServersSetChangeSessionImpl,
 	SessionParams as ServersSetChangeSessionParams};
+use key_server_cluster::threshold_change_session::{SessionImpl as ThresholdChangeSessionImpl,
+	SessionParams as ThresholdChangeSessionParams, IsolatedSessionTransport as ThresholdChangeTransport};
 use key_server_cluster::key_version_negotiation_session::{SessionImpl as KeyVersionNegotiationSessionImpl,
 	SessionParams as KeyVersionNegotiationSessionParams, IsolatedSessionTransport as VersionNegotiationTransport,
 	FastestResultComputer as FastestResultKeyVersionsResultComputer};
@@ -412,6 +415,10 @@ impl ClusterSessionCreator<AdminSession, AdminSessionCreationData> for AdminSess
 				&ConsensusMessageOfShareAdd::InitializeConsensusSession(ref message) => Ok(Some(AdminSessionCreationData::ShareAdd(message.version.clone().into()))),
 				_ => Err(Error::InvalidMessage),
 			},
+			Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeConsensusMessage(ref message)) => match &message.message {
+				&ConsensusMessageOfThresholdChange::InitializeConsensusSession(_) => Ok(Some(AdminSessionCreationData::ThresholdChange)),
+				_ => Err(Error::InvalidMessage),
+			},
 			_ => Err(Error::InvalidMessage),
 		}
 	}
@@ -442,6 +449,21 @@ impl ClusterSessionCreator<AdminSession, AdminSessionCreationData> for AdminSess
 					admin_public: Some(self.admin_public.clone().ok_or(Error::AccessDenied)?),
 				})?)
 			},
+			Some(AdminSessionCreationData::ThresholdChange) => {
+				AdminSession::ThresholdChange(ThresholdChangeSessionImpl::new(ThresholdChangeSessionParams {
+					meta: ShareChangeSessionMeta {
+						id: id.clone(),
+						self_node_id: self.core.self_node_id.clone(),
+						master_node_id: master,
+						configured_nodes_count: cluster.configured_nodes_count(),
+						connected_nodes_count: cluster.connected_nodes_count(),
+					},
+					transport: ThresholdChangeTransport::new(id.clone(), nonce, cluster),
+					key_storage: self.core.key_storage.clone(),
+					nonce: nonce,
+					admin_public: Some(self.admin_public.clone().ok_or(Error::AccessDenied)?),
+				})?)
+			},
 			Some(AdminSessionCreationData::ServersSetChange(migration_id, new_nodes_set)) => {
 				let admin_public = self.servers_set_change_session_creator_connector.admin_public(migration_id.as_ref(), new_nodes_set)
 					.map_err(|_| Error::AccessDenied)?;
@@ -477,6 +499,7 @@ impl IntoSessionId<SessionId> for Message {
 			Message::EcdsaSigning(_) => Err(Error::InvalidMessage),
 			Message::ServersSetChange(ref message) => Ok(message.session_id().clone()),
 			Message::ShareAdd(ref message) => Ok(message.session_id().clone()),
+			Message::ThresholdChange(ref message) => Ok(message.session_id().clone()),
 			Message::KeyVersionNegotiation(_) => Err(Error::InvalidMessage),
 			Message::Cluster(_) => Err(Error::InvalidMessage),
 		}
@@ -493,6 +516,7 @@ impl IntoSessionId<SessionIdWithSubSession> for Message {
 			Message::EcdsaSigning(ref message) => Ok(SessionIdWithSubSession::new(message.session_id().clone(), message.sub_session_id().clone())),
 			Message::ServersSetChange(_) => Err(Error::InvalidMessage),
 			Message::ShareAdd(_) => Err(Error::InvalidMessage),
+			Message::ThresholdChange(_) => Err(Error::InvalidMessage),
 			Message::KeyVersionNegotiation(ref message) => Ok(SessionIdWithSubSession::new(message.session_id().clone(), message.sub_session_id().clone())),
 			Message::Cluster(_) => Err(Error::InvalidMessage),
 		}
@@ -0,0 +1,488 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use ethereum_types::H256;
+use ethkey::Public;
+use parking_lot::Mutex;
+use key_server_cluster::{KeyServerSet, KeyStorage, AdminMigrationMode};
+use key_server_cluster::cluster::{ClusterConfiguration, ServersSetChangeParams};
+use key_server_cluster::cluster_connections_net::NetConnectionsContainer;
+use key_server_cluster::cluster_sessions::{AdminSession, ClusterSession};
+use key_server_cluster::jobs::servers_set_change_access_job::ordered_nodes_hash;
+use key_server_cluster::connection_trigger::{Maintain, ConnectionsAction, ConnectionTrigger,
+	ServersSetChangeSessionCreatorConnector, TriggerConnections};
+use types::{Error, NodeId};
+use NodeKeyPair;
+
+/// Connection trigger, which keeps connections to current set and starts servers set change session
+/// on behalf of administrator, when servers set changes and this node holds administrator key.
+pub struct ConnectionTriggerWithAdminMigration {
+	/// This node key pair.
+	self_key_pair: Arc<NodeKeyPair>,
+	/// Key server set.
+	key_server_set: Arc<KeyServerSet>,
+	/// Key storage.
+	key_storage: Arc<KeyStorage>,
+	/// Migration mode.
+	mode: AdminMigrationMode,
+	/// Currently connected nodes.
+	connected: BTreeSet<NodeId>,
+	/// Servers set, which has been checked for required migration last time.
+	checked_set: Option<BTreeSet<NodeId>>,
+	/// Migration plan, which is waiting for all nodes of new set to be connected.
+	plan: Option<MigrationPlan>,
+	/// New servers set of the session, started by this node.
+	session_set: Option<BTreeSet<NodeId>>,
+	/// Trigger connections.
+	connections: TriggerConnections,
+	/// Servers set change session creator connector.
+	connector: Arc<ServersSetChangeSessionCreatorConnectorWithAdminMigration>,
+}
+
+/// Servers set change session creator connector, which returns pre-configured administrator public
+/// and tracks servers set change session, started by this node.
+pub struct ServersSetChangeSessionCreatorConnectorWithAdminMigration {
+	/// Secret store administrator public key.
+	admin_public: Option<Public>,
+	/// Active servers set change session.
+	session: Mutex<Option<Arc<AdminSession>>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Planned servers set change session.
+pub struct MigrationPlan {
+	/// New servers set.
+	pub new_set: BTreeSet<NodeId>,
+	/// Nodes, which will receive shares of some keys.
+	pub added_nodes: BTreeSet<NodeId>,
+	/// Nodes, which will lose shares of some keys.
+	pub removed_nodes: BTreeSet<NodeId>,
+	/// Number of keys, which shares will be moved.
+	pub keys_to_migrate: usize,
+}
+
+impl ConnectionTriggerWithAdminMigration {
+	/// Create new trigger from cluster configuration.
+	pub fn with_config(config: &ClusterConfiguration, mode: AdminMigrationMode) -> Self {
+		Self::new(config.key_server_set.clone(), config.key_storage.clone(), config.self_key_pair.clone(), config.admin_public, mode)
+	}
+
+	/// Create new trigger with administrator-initiated migration.
+	pub fn new(key_server_set: Arc<KeyServerSet>, key_storage: Arc<KeyStorage>, self_key_pair: Arc<NodeKeyPair>, admin_public: Option<Public>, mode: AdminMigrationMode) -> Self {
+		ConnectionTriggerWithAdminMigration {
+			self_key_pair: self_key_pair.clone(),
+			key_server_set: key_server_set,
+			key_storage: key_storage,
+			mode: mode,
+			connected: BTreeSet::new(),
+			checked_set: None,
+			plan: None,
+			session_set: None,
+			connections: TriggerConnections {
+				self_key_pair: self_key_pair,
+			},
+			connector: Arc::new(ServersSetChangeSessionCreatorConnectorWithAdminMigration {
+				admin_public: admin_public,
+				session: Mutex::new(None),
+			}),
+		}
+	}
+
+	/// Is this node holding administrator key?
+	fn is_admin(&self) -> bool {
+		self.connector.admin_public.as_ref() == Some(self.self_key_pair.public())
+	}
+
+	/// Is servers set change session, started by this node, still active?
+	fn is_session_active(&mut self) -> bool {
+		let mut session = self.connector.session.lock();
+		match session.as_ref().map(|session| session.is_finished()) {
+			Some(false) => true,
+			Some(true) => {
+				// either migration has completed, or session has failed => check again
+				// unless some keys can't be restored by nodes of this set => wait for the set to change
+				let unrecoverable = session.as_ref()
+					.and_then(|session| session.as_servers_set_change())
+					.map_or(false, |session| session.wait() == Err(Error::NotEnoughNodesForThreshold));
+				if unrecoverable {
+					warn!(target: "secretstore", "{}: servers set change session has failed: some keys can't be restored by nodes of new servers set",
+						self.self_key_pair.public());
+				}
+
+				*session = None;
+				self.checked_set = match unrecoverable {
+					true => self.session_set.take(),
+					false => None,
+				};
+				false
+			},
+			None => false,
+		}
+	}
+
+	/// Is planned session ready to be started? All nodes of the new set must be connected.
+	fn is_plan_ready(&self) -> bool {
+		if self.mode != AdminMigrationMode::Enabled {
+			return false;
+		}
+
+		let self_node_id = self.self_key_pair.public();
+		self.plan.as_ref()
+			.map(|plan| plan.new_set.iter().all(|node| node == self_node_id || self.connected.contains(node))
+				&& self.connected.iter().all(|node| plan.new_set.contains(node)))
+			.unwrap_or(false)
+	}
+
+	/// Check if migration to the new set is required and safe.
+	fn make_plan(&self, new_set: &BTreeSet<NodeId>) -> Option<MigrationPlan> {
+		let self_node_id = self.self_key_pair.public();
+		if !new_set.contains(self_node_id) {
+			warn!(target: "secretstore", "{}: not starting servers set change session: this node is not a part of new servers set",
+				self_node_id);
+			return None;
+		}
+
+		match plan_migration(&*self.key_storage, new_set) {
+			Ok(Some(plan)) => {
+				info!(target: "secretstore", "{}: {}servers set change is required: {} keys to migrate, nodes to add: {:?}, nodes to remove: {:?}",
+					self_node_id, if self.mode == AdminMigrationMode::DryRun { "(dry-run) " } else { "" },
+					plan.keys_to_migrate, plan.added_nodes, plan.removed_nodes);
+
+				match self.mode {
+					AdminMigrationMode::Enabled => Some(plan),
+					AdminMigrationMode::DryRun | AdminMigrationMode::Disabled => None,
+				}
+			},
+			Ok(None) => None,
+			Err(err) => {
+				warn!(target: "secretstore", "{}: refusing to start servers set change session: {}", self_node_id, err);
+				None
+			},
+		}
+	}
+}
+
+impl ConnectionTrigger for ConnectionTriggerWithAdminMigration {
+	fn on_maintain(&mut self) -> Option<Maintain> {
+		if !self.is_admin() || self.is_session_active() {
+			return Some(Maintain::Connections);
+		}
+
+		let current_set: BTreeSet<_> = self.key_server_set.snapshot().current_set.keys().cloned().collect();
+		if self.checked_set.as_ref() != Some(&current_set) {
+			self.plan = self.make_plan(&current_set);
+			self.checked_set = Some(current_set);
+		}
+
+		match self.is_plan_ready() {
+			true => Some(Maintain::SessionAndConnections),
+			false => {
+				if self.plan.is_some() {
+					trace!(target: "secretstore_net", "{}: waiting for all nodes of new servers set to be connected",
+						self.self_key_pair.public());
+				}
+
+				Some(Maintain::Connections)
+			},
+		}
+	}
+
+	fn on_connection_established(&mut self, node: &NodeId) -> Option<Maintain> {
+		self.connected.insert(node.clone());
+		match self.is_plan_ready() {
+			true => Some(Maintain::Session),
+			false => None,
+		}
+	}
+
+	fn on_connection_closed(&mut self, node: &NodeId) -> Option<Maintain> {
+		// we do not want to reconnect after every connection close
+		// because it could be a part of something bigger
+		self.connected.remove(node);
+		None
+	}
+
+	fn maintain_session(&mut self) -> Option<ServersSetChangeParams> {
+		if !self.is_plan_ready() {
+			return None;
+		}
+
+		// when session fails to start, the plan is prepared again on next maintain
+		let plan = self.plan.take().expect("plan is ready only when it is Some; qed");
+		self.checked_set = None;
+		self.session_set = Some(plan.new_set.clone());
+
+		// all nodes of the old set, that are not in the new set, are disconnected
+		// => they're not involved in ServersSetChangeSession
+		// => both sets are the same
+		let nodes_set_hash = ordered_nodes_hash(&plan.new_set);
+		let signatures = self.self_key_pair.sign(&nodes_set_hash)
+			.and_then(|old_set_signature| self.self_key_pair.sign(&nodes_set_hash)
+				.map(|new_set_signature| (old_set_signature, new_set_signature)));
+
+		match signatures {
+			Ok((old_set_signature, new_set_signature)) => Some(ServersSetChangeParams {
+				session_id: None,
+				migration_id: None,
+				new_nodes_set: plan.new_set,
+				old_set_signature,
+				new_set_signature,
+				reject_unrecoverable_keys: true,
+			}),
+			Err(err) => {
+				warn!(target: "secretstore", "{}: failed to sign servers set for servers set change session with: {}",
+					self.self_key_pair.public(), err);
+				None
+			},
+		}
+	}
+
+	fn maintain_connections(&mut self, connections: &mut NetConnectionsContainer) {
+		self.connections.maintain(ConnectionsAction::ConnectToCurrentSet, connections, &self.key_server_set.snapshot())
+	}
+
+	fn servers_set_change_creator_connector(&self) -> Arc<ServersSetChangeSessionCreatorConnector> {
+		self.connector.clone()
+	}
+}
+
+impl ServersSetChangeSessionCreatorConnector for ServersSetChangeSessionCreatorConnectorWithAdminMigration {
+	fn admin_public(&self, _migration_id: Option<&H256>, _new_server_set: BTreeSet<NodeId>) -> Result<Public, Error> {
+		self.admin_public.clone().ok_or(Error::AccessDenied)
+	}
+
+	fn set_key_servers_set_change_session(&self, session: Arc<AdminSession>) {
+		*self.session.lock() = Some(session);
+	}
+}
+
+/// Prepare migration of all keys, stored on this node, to the new servers set.
+/// Returns None if all keys are already shared among nodes of the new set. Returns error if
+/// less than threshold + 1 owners of any key share are left in the new set, i.e. the key
+/// could not be restored after migration. Keys, which aren't stored on this node, are checked
+/// by the servers set change session itself, before their shares are moved.
+pub fn plan_migration(key_storage: &KeyStorage, new_set: &BTreeSet<NodeId>) -> Result<Option<MigrationPlan>, String> {
+	let mut plan = MigrationPlan {
+		new_set: new_set.clone(),
+		added_nodes: BTreeSet::new(),
+		removed_nodes: BTreeSet::new(),
+		keys_to_migrate: 0,
+	};
+
+	for (key_id, key_share) in key_storage.iter() {
+		let key_version = key_share.last_version()
+			.map_err(|err| format!("failed to read key {}: {}", key_id, err))?;
+		let owners: BTreeSet<_> = key_version.id_numbers.keys().cloned().collect();
+		if owners == *new_set {
+			continue;
+		}
+
+		let remaining_owners = owners.intersection(new_set).count();
+		if remaining_owners <= key_share.threshold {
+			return Err(format!("key {} requires {} shares, but only {} of its owners are in new servers set",
+				key_id, key_share.threshold + 1, remaining_owners));
+		}
+
+		plan.keys_to_migrate += 1;
+		plan.added_nodes.extend(new_set.difference(&owners).cloned());
+		plan.removed_nodes.extend(owners.difference(new_set).cloned());
+	}
+
+	Ok(match plan.keys_to_migrate {
+		0 => None,
+		_ => Some(plan),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::{BTreeMap, BTreeSet};
+	use std::net::SocketAddr;
+	use std::sync::Arc;
+	use ethereum_types::H256;
+	use ethkey::{Random, Generator, KeyPair, Public, recover};
+	use key_server_cluster::{MapKeyServerSet, PlainNodeKeyPair, DummyKeyStorage, KeyStorage,
+		DocumentKeyShare, DocumentKeyShareVersion, AdminMigrationMode};
+	use key_server_cluster::connection_trigger::{Maintain, ConnectionTrigger};
+	use key_server_cluster::jobs::servers_set_change_access_job::ordered_nodes_hash;
+	use super::{ConnectionTriggerWithAdminMigration, plan_migration};
+
+	fn nodes_map(nodes: &[Public]) -> BTreeMap<Public, SocketAddr> {
+		nodes.iter().enumerate()
+			.map(|(i, node)| (node.clone(), format!("127.0.0.1:{}", 8080 + i).parse().unwrap()))
+			.collect()
+	}
+
+	fn insert_key(key_storage: &DummyKeyStorage, threshold: usize, owners: &[Public]) {
+		key_storage.insert(H256::random(), DocumentKeyShare {
+			threshold: threshold,
+			versions: vec![DocumentKeyShareVersion::new(
+				owners.iter().map(|owner| (owner.clone(), Random.generate().unwrap().secret().clone())).collect(),
+				Random.generate().unwrap().secret().clone(),
+			)],
+			..Default::default()
+		}).unwrap();
+	}
+
+	struct TestTrigger {
+		nodes: Vec<Public>,
+		key_server_set: Arc<MapKeyServerSet>,
+		key_storage: Arc<DummyKeyStorage>,
+		trigger: ConnectionTriggerWithAdminMigration,
+	}
+
+	fn make_trigger(num_nodes: usize, self_is_admin: bool, mode: AdminMigrationMode) -> TestTrigger {
+		let self_key_pair: KeyPair = Random.generate().unwrap();
+		let admin_public = match self_is_admin {
+			true => self_key_pair.public().clone(),
+			false => Random.generate().unwrap().public().clone(),
+		};
+		let nodes: Vec<_> = Some(self_key_pair.public().clone()).into_iter()
+			.chain((1..num_nodes).map(|_| Random.generate().unwrap().public().clone()))
+			.collect();
+		let key_server_set = Arc::new(MapKeyServerSet::new(false, nodes_map(&nodes)));
+		let key_storage = Arc::new(DummyKeyStorage::default());
+		let trigger = ConnectionTriggerWithAdminMigration::new(key_server_set.clone(), key_storage.clone(),
+			Arc::new(PlainNodeKeyPair::new(self_key_pair)), Some(admin_public), mode);
+
+		TestTrigger { nodes, key_server_set, key_storage, trigger }
+	}
+
+	#[test]
+	fn migration_is_not_planned_when_keys_are_owned_by_current_set() {
+		let mut test = make_trigger(3, true, AdminMigrationMode::Enabled);
+		insert_key(&test.key_storage, 1, &test.nodes);
+		for node in &test.nodes[1..] {
+			test.trigger.on_connection_established(node);
+		}
+
+		assert_eq!(test.trigger.on_maintain(), Some(Maintain::Connections));
+		assert!(test.trigger.maintain_session().is_none());
+	}
+
+	#[test]
+	fn migration_is_planned_when_node_is_added_to_servers_set() {
+		let mut test = make_trigger(3, true, AdminMigrationMode::Enabled);
+		insert_key(&test.key_storage, 1, &test.nodes);
+		for node in &test.nodes[1..] {
+			test.trigger.on_connection_established(node);
+		}
+		assert_eq!(test.trigger.on_maintain(), Some(Maintain::Connections));
+
+		// new node is added to the contract
+		let new_node = Random.generate().unwrap().public().clone();
+		let mut new_nodes = test.nodes.clone();
+		new_nodes.push(new_node.clone());
+		test.key_server_set.set_nodes(nodes_map(&new_nodes));
+
+		// session isn't started until new node is connected
+		assert_eq!(test.trigger.on_maintain(), Some(Maintain::Connections));
+		assert!(test.trigger.maintain_session().is_none());
+		assert_eq!(test.trigger.on_connection_established(&new_node), Some(Maintain::Session));
+
+		// session is started with signatures of the new set
+		let new_set: BTreeSet<_> = new_nodes.iter().cloned().collect();
+		let params = test.trigger.maintain_session().unwrap();
+		assert_eq!(params.new_nodes_set, new_set);
+		assert_eq!(params.migration_id, None);
+		assert_eq!(recover(&params.old_set_signature, &ordered_nodes_hash(&new_set)).unwrap(), test.nodes[0]);
+		assert_eq!(recover(&params.new_set_signature, &ordered_nodes_hash(&new_set)).unwrap(), test.nodes[0]);
+	}
+
+	#[test]
+	fn migration_is_not_started_in_dry_run_mode() {
+		let mut test = make_trigger(3, true, AdminMigrationMode::DryRun);
+		insert_key(&test.key_storage, 1, &test.nodes);
+		for node in &test.nodes[1..] {
+			test.trigger.on_connection_established(node);
+		}
+
+		let new_node = Random.generate().unwrap().public().clone();
+		let mut new_nodes = test.nodes.clone();
+		new_nodes.push(new_node.clone());
+		test.key_server_set.set_nodes(nodes_map(&new_nodes));
+
+		assert_eq!(test.trigger.on_maintain(), Some(Maintain::Connections));
+		assert_eq!(test.trigger.on_connection_established(&new_node), None);
+		assert!(test.trigger.maintain_session().is_none());
+	}
+
+	#[test]
+	fn migration_is_not_started_by_non_admin_node() {
+		let mut test = make_trigger(3, false, AdminMigrationMode::Enabled);
+		insert_key(&test.key_storage, 1, &test.nodes);
+
+		let new_node = Random.generate().unwrap().public().clone();
+		let mut new_nodes = test.nodes.clone();
+		new_nodes.push(new_node.clone());
+		test.key_server_set.set_nodes(nodes_map(&new_nodes));
+		for node in new_nodes.iter().skip(1) {
+			test.trigger.on_connection_established(node);
+		}
+
+		assert_eq!(test.trigger.on_maintain(), Some(Maintain::Connections));
+		assert!(test.trigger.maintain_session().is_none());
+	}
+
+	#[test]
+	fn migration_is_not_started_when_removed_node_is_still_connected() {
+		let mut test = make_trigger(4, true, AdminMigrationMode::Enabled);
+		insert_key(&test.key_storage, 1, &test.nodes);
+		for node in &test.nodes[1..] {
+			test.trigger.on_connection_established(node);
+		}
+
+		test.key_server_set.set_nodes(nodes_map(&test.nodes[0..3]));
+		assert_eq!(test.trigger.on_maintain(), Some(Maintain::Connections));
+		assert_eq!(test.trigger.on_connection_closed(&test.nodes[3]), None);
+		assert_eq!(test.trigger.on_maintain(), Some(Maintain::SessionAndConnections));
+		assert_eq!(test.trigger.maintain_session().unwrap().new_nodes_set, test.nodes[0..3].iter().cloned().collect::<BTreeSet<_>>());
+	}
+
+	#[test]
+	fn migration_is_refused_when_key_threshold_is_not_reachable_in_new_set() {
+		let mut test = make_trigger(4, true, AdminMigrationMode::Enabled);
+		insert_key(&test.key_storage, 1, &test.nodes);
+		insert_key(&test.key_storage, 2, &test.nodes);
+
+		// key with threshold 2 requires 3 owners
+		let new_set: BTreeSet<_> = test.nodes[0..2].iter().cloned().collect();
+		assert!(plan_migration(&*test.key_storage, &new_set).unwrap_err().contains("requires 3 shares"));
+
+		// => session is never started
+		test.key_server_set.set_nodes(nodes_map(&test.nodes[0..2]));
+		test.trigger.on_connection_established(&test.nodes[1]);
+		assert_eq!(test.trigger.on_maintain(), Some(Maintain::Connections));
+		assert!(test.trigger.maintain_session().is_none());
+	}
+
+	#[test]
+	fn migration_plan_contains_added_and_removed_nodes() {
+		let key_storage = DummyKeyStorage::default();
+		let nodes: Vec<_> = (0..4).map(|_| Random.generate().unwrap().public().clone()).collect();
+		insert_key(&key_storage, 1, &nodes[0..3]);
+		insert_key(&key_storage, 1, &nodes[1..4]);
+
+		let new_set: BTreeSet<_> = nodes[1..4].iter().cloned().collect();
+		let plan = plan_migration(&key_storage, &new_set).unwrap().unwrap();
+		assert_eq!(plan.keys_to_migrate, 1);
+		assert_eq!(plan.added_nodes, vec![nodes[3].clone()].into_iter().collect::<BTreeSet<_>>());
+		assert_eq!(plan.removed_nodes, vec![nodes[0].clone()].into_iter().collect::<BTreeSet<_>>());
+	}
+}
@@ -287,6 +287,7 @@ impl TriggerSession {
 				new_nodes_set: new_set,
 				old_set_signature,
 				new_set_signature,
+				reject_unrecoverable_keys: false,
 			}),
 			Err(err) => {
 				trace!(
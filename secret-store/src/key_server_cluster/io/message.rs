@@ -25,7 +25,8 @@ use ethkey::math::curve_order;
 use ethereum_types::{H256, U256};
 use key_server_cluster::Error;
 use key_server_cluster::message::{Message, ClusterMessage, GenerationMessage, EncryptionMessage, DecryptionMessage,
-	SchnorrSigningMessage, EcdsaSigningMessage, ServersSetChangeMessage, ShareAddMessage, KeyVersionNegotiationMessage};
+	SchnorrSigningMessage, EcdsaSigningMessage, ServersSetChangeMessage, ShareAddMessage, ThresholdChangeMessage,
+	KeyVersionNegotiationMessage};
 
 /// Size of serialized header.
 pub const MESSAGE_HEADER_SIZE: usize = 18;
@@ -130,6 +131,17 @@ pub fn serialize_message(message: Message) -> Result<SerializedMessage, Error> {
 		Message::ShareAdd(ShareAddMessage::NewKeysDissemination(payload))					=> (302, serde_json::to_vec(&payload)),
 		Message::ShareAdd(ShareAddMessage::ShareAddError(payload))							=> (303, serde_json::to_vec(&payload)),
 
+		Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeConsensusMessage(payload))
+																							=> (350, serde_json::to_vec(&payload)),
+		Message::ThresholdChange(ThresholdChangeMessage::KeyShareCommon(payload))			=> (351, serde_json::to_vec(&payload)),
+		Message::ThresholdChange(ThresholdChangeMessage::ReshareKeysDissemination(payload))
+																							=> (352, serde_json::to_vec(&payload)),
+		Message::ThresholdChange(ThresholdChangeMessage::ConfirmNewKeyShare(payload))		=> (353, serde_json::to_vec(&payload)),
+		Message::ThresholdChange(ThresholdChangeMessage::CommitNewKeyShares(payload))		=> (354, serde_json::to_vec(&payload)),
+		Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeError(payload))		=> (355, serde_json::to_vec(&payload)),
+		Message::ThresholdChange(ThresholdChangeMessage::NewKeyShareCommitted(payload))		=> (356, serde_json::to_vec(&payload)),
+		Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeCompleted(payload))	=> (357, serde_json::to_vec(&payload)),
+
 		Message::KeyVersionNegotiation(KeyVersionNegotiationMessage::RequestKeyVersions(payload))
 																							=> (450, serde_json::to_vec(&payload)),
 		Message::KeyVersionNegotiation(KeyVersionNegotiationMessage::KeyVersions(payload))
@@ -217,6 +229,15 @@ pub fn deserialize_message(header: &MessageHeader, payload: Vec<u8>) -> Result<M
 		302 => Message::ShareAdd(ShareAddMessage::NewKeysDissemination(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 		303 => Message::ShareAdd(ShareAddMessage::ShareAddError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 
+		350 => Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeConsensusMessage(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		351 => Message::ThresholdChange(ThresholdChangeMessage::KeyShareCommon(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		352 => Message::ThresholdChange(ThresholdChangeMessage::ReshareKeysDissemination(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		353 => Message::ThresholdChange(ThresholdChangeMessage::ConfirmNewKeyShare(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		354 => Message::ThresholdChange(ThresholdChangeMessage::CommitNewKeyShares(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		355 => Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		356 => Message::ThresholdChange(ThresholdChangeMessage::NewKeyShareCommitted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+		357 => Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeCompleted(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
+
 		450 => Message::KeyVersionNegotiation(KeyVersionNegotiationMessage::RequestKeyVersions(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 		451 => Message::KeyVersionNegotiation(KeyVersionNegotiationMessage::KeyVersions(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
 		452 => Message::KeyVersionNegotiation(KeyVersionNegotiationMessage::KeyVersionsError(serde_json::from_slice(&payload).map_err(|err| Error::Serde(err.to_string()))?)),
@@ -22,4 +22,5 @@ pub mod key_access_job;
 pub mod servers_set_change_access_job;
 pub mod signing_job_ecdsa;
 pub mod signing_job_schnorr;
+pub mod threshold_change_access_job;
 pub mod unknown_sessions_job;
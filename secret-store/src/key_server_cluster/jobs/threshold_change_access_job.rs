@@ -0,0 +1,132 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeSet, BTreeMap};
+use ethkey::{Public, Signature, recover};
+use tiny_keccak::Keccak;
+use key_server_cluster::{Error, NodeId, SessionId};
+use key_server_cluster::message::InitializeConsensusSessionOfThresholdChange;
+use key_server_cluster::jobs::job_session::{JobPartialResponseAction, JobPartialRequestAction, JobExecutor};
+
+/// Purpose of this job is to check if requestor is administrator of SecretStore (i.e. it have access to change key threshold).
+pub struct ThresholdChangeAccessJob {
+	/// Servers set administrator public key.
+	administrator: Public,
+	/// Key id.
+	key_id: SessionId,
+	/// New servers set.
+	new_servers_set: Option<BTreeSet<NodeId>>,
+	/// New key threshold.
+	new_threshold: Option<usize>,
+	/// Hash(key id, new threshold, new servers set), signed by requester.
+	signature: Option<Signature>,
+}
+
+/// Threshold change job partial request.
+pub struct ThresholdChangeAccessRequest {
+	/// New servers set.
+	pub new_servers_set: BTreeSet<NodeId>,
+	/// New key threshold.
+	pub new_threshold: usize,
+	/// Hash(key id, new threshold, new servers set), signed by requester.
+	pub signature: Signature,
+}
+
+impl<'a> From<&'a InitializeConsensusSessionOfThresholdChange> for ThresholdChangeAccessRequest {
+	fn from(message: &InitializeConsensusSessionOfThresholdChange) -> Self {
+		ThresholdChangeAccessRequest {
+			new_servers_set: message.new_nodes_map.keys().cloned().map(Into::into).collect(),
+			new_threshold: message.new_threshold,
+			signature: message.signature.clone().into(),
+		}
+	}
+}
+
+impl ThresholdChangeAccessJob {
+	pub fn new_on_slave(administrator: Public, key_id: SessionId) -> Self {
+		ThresholdChangeAccessJob {
+			administrator: administrator,
+			key_id: key_id,
+			new_servers_set: None,
+			new_threshold: None,
+			signature: None,
+		}
+	}
+
+	pub fn new_on_master(administrator: Public, key_id: SessionId, new_servers_set: BTreeSet<NodeId>, new_threshold: usize, signature: Signature) -> Self {
+		ThresholdChangeAccessJob {
+			administrator: administrator,
+			key_id: key_id,
+			new_servers_set: Some(new_servers_set),
+			new_threshold: Some(new_threshold),
+			signature: Some(signature),
+		}
+	}
+}
+
+impl JobExecutor for ThresholdChangeAccessJob {
+	type PartialJobRequest = ThresholdChangeAccessRequest;
+	type PartialJobResponse = bool;
+	type JobResponse = BTreeSet<NodeId>;
+
+	fn prepare_partial_request(&self, _node: &NodeId, _nodes: &BTreeSet<NodeId>) -> Result<ThresholdChangeAccessRequest, Error> {
+		let explanation = "prepare_partial_request is only called on master nodes; this field is filled on master nodes in constructor; qed";
+		Ok(ThresholdChangeAccessRequest {
+			new_servers_set: self.new_servers_set.clone().expect(explanation),
+			new_threshold: self.new_threshold.clone().expect(explanation),
+			signature: self.signature.clone().expect(explanation),
+		})
+	}
+
+	fn process_partial_request(&mut self, partial_request: ThresholdChangeAccessRequest) -> Result<JobPartialRequestAction<bool>, Error> {
+		let ThresholdChangeAccessRequest {
+			new_servers_set,
+			new_threshold,
+			signature,
+		} = partial_request;
+
+		// check that request is signed by administrator
+		let actual_public = recover(&signature, &threshold_change_hash(&self.key_id, new_threshold, &new_servers_set).into())?;
+		let is_administrator = actual_public == self.administrator;
+		self.new_servers_set = Some(new_servers_set);
+		self.new_threshold = Some(new_threshold);
+
+		Ok(if is_administrator { JobPartialRequestAction::Respond(true) } else { JobPartialRequestAction::Reject(false) })
+	}
+
+	fn check_partial_response(&mut self, _sender: &NodeId, partial_response: &bool) -> Result<JobPartialResponseAction, Error> {
+		Ok(if *partial_response { JobPartialResponseAction::Accept } else { JobPartialResponseAction::Reject })
+	}
+
+	fn compute_response(&self, partial_responses: &BTreeMap<NodeId, bool>) -> Result<BTreeSet<NodeId>, Error> {
+		Ok(partial_responses.keys().cloned().collect())
+	}
+}
+
+/// Hash of threshold change request, which must be signed by administrator.
+pub fn threshold_change_hash(key_id: &SessionId, new_threshold: usize, nodes: &BTreeSet<NodeId>) -> SessionId {
+	let mut request_keccak = Keccak::new_keccak256();
+	request_keccak.update(&**key_id);
+	request_keccak.update(&(new_threshold as u64).to_be_bytes());
+	for node in nodes {
+		request_keccak.update(&*node);
+	}
+
+	let mut request_keccak_value = [0u8; 32];
+	request_keccak.finalize(&mut request_keccak_value);
+
+	request_keccak_value.into()
+}
@@ -196,6 +196,33 @@ pub fn keys_verification(threshold: usize, derived_point: &Public, number_id: &S
 	Ok(left == right)
 }
 
+/// Compute public commitments to the coefficients of polynom.
+pub fn compute_polynom_commitments(polynom: &[Secret]) -> Result<Vec<Public>, Error> {
+	polynom.iter().map(compute_public_share).collect()
+}
+
+/// Check that value of polynom, computed at `node_number`, matches commitments to polynom coefficients.
+pub fn verify_polynom_value(commitments: &[Public], node_number: &Secret, value: &Secret) -> Result<bool, Error> {
+	debug_assert!(!commitments.is_empty());
+
+	// calculate left part
+	let left = compute_public_share(value)?;
+
+	// calculate right part
+	let mut right = commitments[0].clone();
+	for i in 1..commitments.len() {
+		let mut secret_pow = node_number.clone();
+		secret_pow.pow(i)?;
+
+		let mut commitment_k = commitments[i].clone();
+		math::public_mul_secret(&mut commitment_k, &secret_pow)?;
+
+		math::public_add(&mut right, &commitment_k)?;
+	}
+
+	Ok(left == right)
+}
+
 /// Compute secret subshare from passed secret value.
 pub fn compute_secret_subshare<'a, I>(threshold: usize, secret_value: &Secret, sender_id_number: &Secret, other_id_numbers: I) -> Result<Secret, Error> where I: Iterator<Item=&'a Secret> {
 	let mut subshare = compute_shadow_mul(secret_value, sender_id_number, other_id_numbers)?;
@@ -1010,6 +1037,45 @@ pub mod tests {
 		assert_eq!(joint_secret1, joint_secret3);
 	}
 
+	#[test]
+	fn full_generation_math_session_with_verifiable_resharing() {
+		let (old_t, new_t, n, new_n) = (1, 2, 3, 5);
+
+		// generate key using 2-of-3 session
+		let artifacts = run_key_generation(old_t, n, None, None);
+		let joint_secret = compute_joint_secret(artifacts.polynoms1.iter().map(|p1| &p1[0])).unwrap();
+		let id_numbers: Vec<_> = artifacts.id_numbers.iter().cloned()
+			.chain((n..new_n).map(|_| generate_random_scalar().unwrap()))
+			.collect();
+
+		// every node of consensus group shares its lagrange-weighted share && commits to polynom coefficients
+		let mut new_secret_shares = vec![Vec::new(); new_n];
+		let mut free_commitments = Vec::new();
+		for i in 0..old_t + 1 {
+			let other_id_numbers = (0..old_t + 1).filter(|j| *j != i).map(|j| &id_numbers[j]);
+			let mut polynom = generate_random_polynom(new_t).unwrap();
+			polynom[0] = compute_secret_subshare(old_t, &artifacts.secret_shares[i], &id_numbers[i], other_id_numbers).unwrap();
+			let commitments = compute_polynom_commitments(&polynom).unwrap();
+			free_commitments.push(commitments[0].clone());
+
+			for j in 0..new_n {
+				let subshare = compute_polynom(&polynom, &id_numbers[j]).unwrap();
+				assert!(verify_polynom_value(&commitments, &id_numbers[j], &subshare).unwrap());
+				assert!(!verify_polynom_value(&commitments, &id_numbers[(j + 1) % new_n], &subshare).unwrap());
+				new_secret_shares[j].push(subshare);
+			}
+		}
+
+		// sum of free commitments is the joint public
+		assert_eq!(compute_public_sum(free_commitments.iter()).unwrap(), artifacts.joint_public);
+
+		// any new_t + 1 of new shares are enough to restore the secret
+		let new_secret_shares: Vec<_> = new_secret_shares.iter().map(|s| compute_secret_share(s.iter()).unwrap()).collect();
+		let joint_secret2 = compute_joint_secret_from_shares(new_t, &new_secret_shares.iter().skip(2).collect::<Vec<_>>(),
+			&id_numbers.iter().skip(2).collect::<Vec<_>>()).unwrap();
+		assert_eq!(joint_secret, joint_secret2);
+	}
+
 	#[test]
 	fn full_zero_secret_generation_math_session() {
 		let test_cases = vec![(1, 4), (2, 4)];
@@ -45,6 +45,8 @@ pub enum Message {
 	ShareAdd(ShareAddMessage),
 	/// Servers set change message.
 	ServersSetChange(ServersSetChangeMessage),
+	/// Threshold change message.
+	ThresholdChange(ThresholdChangeMessage),
 }
 
 /// All possible cluster-level messages.
@@ -117,6 +119,15 @@ pub enum ConsensusMessageOfShareAdd {
 	ConfirmConsensusInitialization(ConfirmConsensusInitialization),
 }
 
+/// All possible messages that can be sent during threshold change consensus establishing.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ConsensusMessageOfThresholdChange {
+	/// Initialize consensus session.
+	InitializeConsensusSession(InitializeConsensusSessionOfThresholdChange),
+	/// Confirm/reject consensus session initialization.
+	ConfirmConsensusInitialization(ConfirmConsensusInitialization),
+}
+
 /// All possible messages that can be sent during decryption session.
 #[derive(Clone, Debug)]
 pub enum DecryptionMessage {
@@ -224,6 +235,27 @@ pub enum ShareAddMessage {
 	ShareAddError(ShareAddError),
 }
 
+/// All possible messages that can be sent during threshold change session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ThresholdChangeMessage {
+	/// Consensus establishing message.
+	ThresholdChangeConsensusMessage(ThresholdChangeConsensusMessage),
+	/// Common key share data is sent to nodes which do not have a share yet.
+	KeyShareCommon(KeyShareCommon),
+	/// Re-shared keys are sent to every node.
+	ReshareKeysDissemination(ReshareKeysDissemination),
+	/// New key share is computed && verified by node.
+	ConfirmNewKeyShare(ConfirmNewKeyShare),
+	/// New key shares are confirmed by all nodes and must be saved.
+	CommitNewKeyShares(CommitNewKeyShares),
+	/// When session error has occured.
+	ThresholdChangeError(ThresholdChangeError),
+	/// New key share is saved by node.
+	NewKeyShareCommitted(NewKeyShareCommitted),
+	/// New key shares are saved by all nodes.
+	ThresholdChangeCompleted(ThresholdChangeCompleted),
+}
+
 /// All possible messages that can be sent during key version negotiation message.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum KeyVersionNegotiationMessage {
@@ -443,6 +475,21 @@ pub struct InitializeConsensusSessionOfShareAdd {
 	pub new_set_signature: SerializableSignature,
 }
 
+/// Node is asked to be part of threshold change consensus group.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InitializeConsensusSessionOfThresholdChange {
+	/// Key version.
+	pub version: SerializableH256,
+	/// New key threshold.
+	pub new_threshold: usize,
+	/// threshold+1 owners of key version, selected for shares redistribution.
+	pub consensus_group: BTreeSet<MessageNodeId>,
+	/// New nodes map: node id => node id number.
+	pub new_nodes_map: BTreeMap<MessageNodeId, SerializableSecret>,
+	/// Hash(key id, new threshold, new nodes set), signed by requester.
+	pub signature: SerializableSignature,
+}
+
 /// Consensus-related Schnorr signing message.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SchnorrSigningConsensusMessage {
@@ -1006,6 +1053,77 @@ pub struct ShareAddError {
 	pub error: Error,
 }
 
+/// Consensus-related threshold change session message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ThresholdChangeConsensusMessage {
+	/// Threshold change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Consensus message.
+	pub message: ConsensusMessageOfThresholdChange,
+}
+
+/// Re-shared keys are sent to every node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReshareKeysDissemination {
+	/// Threshold change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Sub share of receiver' new secret share.
+	pub secret_subshare: SerializableSecret,
+	/// Commitments to the coefficients of sender' polynom.
+	pub commitments: Vec<SerializablePublic>,
+}
+
+/// New key share is computed && verified by node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfirmNewKeyShare {
+	/// Threshold change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// New key shares are confirmed by all nodes and must be saved.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommitNewKeyShares {
+	/// Threshold change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// When threshold change session error has occured.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ThresholdChangeError {
+	/// Threshold change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+	/// Error message.
+	pub error: Error,
+}
+
+/// New key share is saved by node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NewKeyShareCommitted {
+	/// Threshold change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
+/// New key shares are saved by all nodes and old key shares could be forgotten.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ThresholdChangeCompleted {
+	/// Threshold change session Id.
+	pub session: MessageSessionId,
+	/// Session-level nonce.
+	pub session_nonce: u64,
+}
+
 /// Key versions are requested.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RequestKeyVersions {
@@ -1082,6 +1200,10 @@ impl Message {
 				ConsensusMessageWithServersSet::InitializeConsensusSession(_) => true,
 				_ => false
 			},
+			Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeConsensusMessage(ref msg)) => match msg.message {
+				ConsensusMessageOfThresholdChange::InitializeConsensusSession(_) => true,
+				_ => false
+			},
 			_ => false,
 		}
 	}
@@ -1105,6 +1227,7 @@ impl Message {
 			Message::KeyVersionNegotiation(KeyVersionNegotiationMessage::KeyVersionsError(_)) => true,
 			Message::ShareAdd(ShareAddMessage::ShareAddError(_)) => true,
 			Message::ServersSetChange(ServersSetChangeMessage::ServersSetChangeError(_)) => true,
+			Message::ThresholdChange(ThresholdChangeMessage::ThresholdChangeError(_)) => true,
 			_ => false,
 		}
 	}
@@ -1126,6 +1249,7 @@ impl Message {
 			Message::EcdsaSigning(ref message) => Some(message.session_nonce()),
 			Message::ShareAdd(ref message) => Some(message.session_nonce()),
 			Message::ServersSetChange(ref message) => Some(message.session_nonce()),
+			Message::ThresholdChange(ref message) => Some(message.session_nonce()),
 			Message::KeyVersionNegotiation(ref message) => Some(message.session_nonce()),
 		}
 	}
@@ -1358,6 +1482,34 @@ impl ShareAddMessage {
 	}
 }
 
+impl ThresholdChangeMessage {
+	pub fn session_id(&self) -> &SessionId {
+		match *self {
+			ThresholdChangeMessage::ThresholdChangeConsensusMessage(ref msg) => &msg.session,
+			ThresholdChangeMessage::KeyShareCommon(ref msg) => &msg.session,
+			ThresholdChangeMessage::ReshareKeysDissemination(ref msg) => &msg.session,
+			ThresholdChangeMessage::ConfirmNewKeyShare(ref msg) => &msg.session,
+			ThresholdChangeMessage::CommitNewKeyShares(ref msg) => &msg.session,
+			ThresholdChangeMessage::ThresholdChangeError(ref msg) => &msg.session,
+			ThresholdChangeMessage::NewKeyShareCommitted(ref msg) => &msg.session,
+			ThresholdChangeMessage::ThresholdChangeCompleted(ref msg) => &msg.session,
+		}
+	}
+
+	pub fn session_nonce(&self) -> u64 {
+		match *self {
+			ThresholdChangeMessage::ThresholdChangeConsensusMessage(ref msg) => msg.session_nonce,
+			ThresholdChangeMessage::KeyShareCommon(ref msg) => msg.session_nonce,
+			ThresholdChangeMessage::ReshareKeysDissemination(ref msg) => msg.session_nonce,
+			ThresholdChangeMessage::ConfirmNewKeyShare(ref msg) => msg.session_nonce,
+			ThresholdChangeMessage::CommitNewKeyShares(ref msg) => msg.session_nonce,
+			ThresholdChangeMessage::ThresholdChangeError(ref msg) => msg.session_nonce,
+			ThresholdChangeMessage::NewKeyShareCommitted(ref msg) => msg.session_nonce,
+			ThresholdChangeMessage::ThresholdChangeCompleted(ref msg) => msg.session_nonce,
+		}
+	}
+}
+
 impl KeyVersionNegotiationMessage {
 	pub fn session_id(&self) -> &SessionId {
 		match *self {
@@ -1395,6 +1547,7 @@ impl fmt::Display for Message {
 			Message::EcdsaSigning(ref message) => write!(f, "EcdsaSigning.{}", message),
 			Message::ServersSetChange(ref message) => write!(f, "ServersSetChange.{}", message),
 			Message::ShareAdd(ref message) => write!(f, "ShareAdd.{}", message),
+			Message::ThresholdChange(ref message) => write!(f, "ThresholdChange.{}", message),
 			Message::KeyVersionNegotiation(ref message) => write!(f, "KeyVersionNegotiation.{}", message),
 		}
 	}
@@ -1462,6 +1615,15 @@ impl fmt::Display for ConsensusMessageOfShareAdd {
 	}
 }
 
+impl fmt::Display for ConsensusMessageOfThresholdChange {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ConsensusMessageOfThresholdChange::InitializeConsensusSession(_) => write!(f, "InitializeConsensusSession"),
+			ConsensusMessageOfThresholdChange::ConfirmConsensusInitialization(ref msg) => write!(f, "ConfirmConsensusInitialization({})", msg.is_confirmed),
+		}
+	}
+}
+
 impl fmt::Display for DecryptionMessage {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match *self {
@@ -1539,6 +1701,21 @@ impl fmt::Display for ShareAddMessage {
 	}
 }
 
+impl fmt::Display for ThresholdChangeMessage {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			ThresholdChangeMessage::ThresholdChangeConsensusMessage(ref m) => write!(f, "ThresholdChangeConsensusMessage.{}", m.message),
+			ThresholdChangeMessage::KeyShareCommon(_) => write!(f, "KeyShareCommon"),
+			ThresholdChangeMessage::ReshareKeysDissemination(_) => write!(f, "ReshareKeysDissemination"),
+			ThresholdChangeMessage::ConfirmNewKeyShare(_) => write!(f, "ConfirmNewKeyShare"),
+			ThresholdChangeMessage::CommitNewKeyShares(_) => write!(f, "CommitNewKeyShares"),
+			ThresholdChangeMessage::ThresholdChangeError(_) => write!(f, "ThresholdChangeError"),
+			ThresholdChangeMessage::NewKeyShareCommitted(_) => write!(f, "NewKeyShareCommitted"),
+			ThresholdChangeMessage::ThresholdChangeCompleted(_) => write!(f, "ThresholdChangeCompleted"),
+		}
+	}
+}
+
 impl fmt::Display for KeyVersionNegotiationMessage {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match *self {
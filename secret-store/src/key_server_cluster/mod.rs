@@ -17,7 +17,8 @@
 use super::types::ServerKeyId;
 
 pub use super::traits::NodeKeyPair;
-pub use super::types::{Error, NodeId, Requester, EncryptedDocumentKeyShadow};
+pub use super::types::{Error, NodeId, Requester, EncryptedDocumentKeyShadow, AdminSessionStatus, KeyServerStatus,
+	SessionsStatus, AdminMigrationMode};
 pub use super::acl_storage::AclStorage;
 pub use super::key_storage::{KeyStorage, DocumentKeyShare, DocumentKeyShareVersion};
 pub use super::key_server_set::{is_migration_required, KeyServerSet, KeyServerSetSnapshot, KeyServerSetMigration};
@@ -27,7 +28,9 @@ pub use self::cluster::{new_network_cluster, ClusterCore, ClusterConfiguration,
 pub use self::cluster_connections_net::NetConnectionsManagerConfig;
 pub use self::cluster_sessions::{ClusterSession, ClusterSessionsListener};
 #[cfg(test)]
-pub use self::cluster::tests::DummyClusterClient;
+pub use self::cluster::tests::{DummyClusterClient, MessageLoop, make_clusters};
+#[cfg(test)]
+pub use self::jobs::servers_set_change_access_job::ordered_nodes_hash;
 
 #[cfg(test)]
 pub use super::node_key_pair::PlainNodeKeyPair;
@@ -63,6 +66,7 @@ pub use self::admin_sessions::key_version_negotiation_session;
 pub use self::admin_sessions::servers_set_change_session;
 pub use self::admin_sessions::share_add_session;
 pub use self::admin_sessions::share_change_session;
+pub use self::admin_sessions::threshold_change_session;
 
 pub use self::client_sessions::decryption_session;
 pub use self::client_sessions::encryption_session;
@@ -77,6 +81,7 @@ mod cluster_message_processor;
 mod cluster_sessions;
 mod cluster_sessions_creator;
 mod connection_trigger;
+mod connection_trigger_with_admin_migration;
 mod connection_trigger_with_migration;
 mod io;
 mod jobs;
@@ -585,22 +585,28 @@ pub mod tests {
 	use std::net::SocketAddr;
 	use ethereum_types::H256;
 	use ethkey::Public;
+	use parking_lot::RwLock;
 	use super::{update_future_set, update_number_of_confirmations, FutureNewSet,
 		KeyServerSet, KeyServerSetSnapshot, MIGRATION_CONFIRMATIONS_REQUIRED};
 
 	#[derive(Default)]
 	pub struct MapKeyServerSet {
 		is_isolated: bool,
-		nodes: BTreeMap<Public, SocketAddr>,
+		nodes: RwLock<BTreeMap<Public, SocketAddr>>,
 	}
 
 	impl MapKeyServerSet {
 		pub fn new(is_isolated: bool, nodes: BTreeMap<Public, SocketAddr>) -> Self {
 			MapKeyServerSet {
 				is_isolated: is_isolated,
-				nodes: nodes,
+				nodes: RwLock::new(nodes),
 			}
 		}
+
+		/// Change servers set, as if it has been changed in the contract.
+		pub fn set_nodes(&self, nodes: BTreeMap<Public, SocketAddr>) {
+			*self.nodes.write() = nodes;
+		}
 	}
 
 	impl KeyServerSet for MapKeyServerSet {
@@ -609,9 +615,10 @@ pub mod tests {
 		}
 
 		fn snapshot(&self) -> KeyServerSetSnapshot {
+			let nodes = self.nodes.read();
 			KeyServerSetSnapshot {
-				current_set: self.nodes.clone(),
-				new_set: self.nodes.clone(),
+				current_set: nodes.clone(),
+				new_set: nodes.clone(),
 				..Default::default()
 			}
 		}
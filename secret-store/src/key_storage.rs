@@ -25,11 +25,11 @@ use types::{Error, ServerKeyId, NodeId};
 use serialization::{SerializablePublic, SerializableSecret, SerializableH256, SerializableAddress};
 
 /// Key of version value.
-const DB_META_KEY_VERSION: &'static [u8; 7] = b"version";
+pub const DB_META_KEY_VERSION: &'static [u8; 7] = b"version";
 /// Current db version.
 const CURRENT_VERSION: u8 = 3;
 /// Current type of serialized key shares.
-type CurrentSerializableDocumentKeyShare = SerializableDocumentKeyShareV3;
+pub type CurrentSerializableDocumentKeyShare = SerializableDocumentKeyShareV3;
 /// Current type of serialized key shares versions.
 type CurrentSerializableDocumentKeyVersion = SerializableDocumentKeyShareVersionV3;
 
@@ -141,7 +141,7 @@ struct SerializableDocumentKeyShareV2 {
 
 /// V2 of encrypted key share version, as it is stored by key storage on the single key server.
 #[derive(Serialize, Deserialize)]
-struct SerializableDocumentKeyShareVersionV2 {
+pub struct SerializableDocumentKeyShareVersionV2 {
 	/// Version hash.
 	pub hash: SerializableH256,
 	/// Nodes ids numbers.
@@ -152,7 +152,7 @@ struct SerializableDocumentKeyShareVersionV2 {
 
 /// V3 of encrypted key share, as it is stored by key storage on the single key server.
 #[derive(Serialize, Deserialize)]
-struct SerializableDocumentKeyShareV3 {
+pub struct SerializableDocumentKeyShareV3 {
 	/// Author of the entry.
 	pub author: SerializableAddress,
 	/// Decryption threshold (at least threshold + 1 nodes are required to decrypt data).
@@ -168,7 +168,7 @@ struct SerializableDocumentKeyShareV3 {
 }
 
 /// V3 of encrypted key share version, as it is stored by key storage on the single key server.
-type SerializableDocumentKeyShareVersionV3 = SerializableDocumentKeyShareVersionV2;
+pub type SerializableDocumentKeyShareVersionV3 = SerializableDocumentKeyShareVersionV2;
 
 impl PersistentKeyStorage {
 	/// Create new persistent document encryption keys storage
@@ -323,7 +323,6 @@ impl<'a> Iterator for PersistentKeyStorageIterator<'a> {
 
 impl DocumentKeyShare {
 	/// Get last version reference.
-	#[cfg(test)]
 	pub fn last_version(&self) -> Result<&DocumentKeyShareVersion, Error> {
 		self.versions.iter().rev()
 			.nth(0)
@@ -0,0 +1,330 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Backup, restore and verification of the key shares database.
+//!
+//! The backup is a JSON archive of all key shares, encrypted with the public key of the node,
+//! so that it could only be restored by the node which has created it.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use serde_json;
+use ethkey::crypto::ecies;
+use ethkey::math::public_is_valid;
+use ethkey::Public;
+use kvdb::KeyValueDB;
+use key_storage::{DB_META_KEY_VERSION, CurrentSerializableDocumentKeyShare, PersistentKeyStorage,
+	DocumentKeyShare, DocumentKeyShareVersion};
+use serialization::{SerializablePublic, SerializableH256};
+use traits::NodeKeyPair;
+use types::{Error, ServerKeyId};
+
+/// Current version of the backup archive.
+const BACKUP_VERSION: u8 = 1;
+
+/// Status of the key share, stored in the database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyShareStatus {
+	/// Server key id.
+	pub key_id: ServerKeyId,
+	/// Key threshold. None if the share can't be read.
+	pub threshold: Option<usize>,
+	/// Number of stored share versions.
+	pub versions: usize,
+	/// Integrity check error, if any.
+	pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializableKeyStorageBackup {
+	/// Archive version.
+	pub version: u8,
+	/// Public key of the node, which owns the key shares.
+	pub node: SerializablePublic,
+	/// Key shares.
+	pub keys: BTreeMap<SerializableH256, CurrentSerializableDocumentKeyShare>,
+}
+
+/// Create encrypted archive of all key shares, stored in the database.
+/// Fails if any of the shares can't be read.
+pub fn backup_key_storage(db: Arc<KeyValueDB>, self_key_pair: &NodeKeyPair) -> Result<Vec<u8>, Error> {
+	let mut keys = BTreeMap::new();
+	for (key_id, key_share) in read_key_shares(db)? {
+		let key_share = key_share.map_err(|e| Error::Database(format!("key share {:?} can't be read: {}", key_id, e)))?;
+		keys.insert(key_id.into(), key_share.into());
+	}
+
+	let backup = SerializableKeyStorageBackup {
+		version: BACKUP_VERSION,
+		node: self_key_pair.public().clone().into(),
+		keys: keys,
+	};
+	let backup = serde_json::to_vec(&backup).map_err(|e| Error::Serde(e.to_string()))?;
+	ecies::encrypt(self_key_pair.public(), &[], &backup).map_err(Into::into)
+}
+
+/// Restore key shares from the encrypted archive. Returns number of restored shares.
+/// Shares, having versions which are missing in the archive, are only overwritten if `force` is true.
+pub fn restore_key_storage(db: Arc<KeyValueDB>, self_key_pair: &NodeKeyPair, archive: &[u8], force: bool) -> Result<usize, Error> {
+	let backup = self_key_pair.decrypt(archive)?;
+	let backup: SerializableKeyStorageBackup = serde_json::from_slice(&backup).map_err(|e| Error::Serde(e.to_string()))?;
+	if backup.version != BACKUP_VERSION {
+		return Err(Error::Database(format!("unsupported backup version: {}", backup.version)));
+	}
+	if *backup.node != *self_key_pair.public() {
+		return Err(Error::Database(format!("backup belongs to node {:?}", *backup.node)));
+	}
+
+	let stored_key_shares = read_key_shares(db.clone())?;
+	let mut newer_key_ids = Vec::new();
+	let mut batch = db.transaction();
+	let mut restored = 0;
+	for (key_id, key_share) in backup.keys {
+		let key_id: ServerKeyId = key_id.into();
+		let db_value = serde_json::to_vec(&key_share).map_err(|e| Error::Serde(e.to_string()))?;
+		let key_share: DocumentKeyShare = key_share.into();
+		match stored_key_shares.get(&key_id) {
+			Some(&Ok(ref stored_key_share)) if *stored_key_share == key_share => continue,
+			Some(&Ok(ref stored_key_share)) if !force && has_newer_versions(stored_key_share, &key_share) => {
+				newer_key_ids.push(key_id);
+				continue;
+			},
+			_ => (),
+		}
+
+		batch.put(None, &*key_id, &db_value);
+		restored += 1;
+	}
+
+	if !newer_key_ids.is_empty() {
+		return Err(Error::Database(format!("database has newer versions of {} key share(s): {:?}", newer_key_ids.len(), newer_key_ids)));
+	}
+
+	db.write(batch)?;
+	Ok(restored)
+}
+
+/// List all key shares, stored in the database, checking integrity of every share.
+pub fn verify_key_storage(db: Arc<KeyValueDB>, self_public: &Public) -> Result<Vec<KeyShareStatus>, Error> {
+	Ok(read_key_shares(db)?.into_iter()
+		.map(|(key_id, key_share)| match key_share {
+			Ok(key_share) => KeyShareStatus {
+				key_id: key_id,
+				threshold: Some(key_share.threshold),
+				versions: key_share.versions.len(),
+				error: check_key_share(&key_share, self_public).err(),
+			},
+			Err(error) => KeyShareStatus {
+				key_id: key_id,
+				threshold: None,
+				versions: 0,
+				error: Some(error),
+			},
+		})
+		.collect())
+}
+
+/// Read all key shares from the database, keeping shares which can't be deserialized.
+fn read_key_shares(db: Arc<KeyValueDB>) -> Result<BTreeMap<ServerKeyId, Result<DocumentKeyShare, String>>, Error> {
+	// upgrade database to the current version first
+	PersistentKeyStorage::new(db.clone())?;
+
+	Ok(db.iter(None)
+		.filter(|&(ref db_key, _)| **db_key != *DB_META_KEY_VERSION && db_key.len() == 32)
+		.map(|(db_key, db_value)| {
+			let key_share = serde_json::from_slice::<CurrentSerializableDocumentKeyShare>(&db_value)
+				.map(Into::into)
+				.map_err(|e| e.to_string());
+			(ServerKeyId::from_slice(&db_key), key_share)
+		})
+		.collect())
+}
+
+/// Check if the stored share has versions, which are missing in the restored share.
+fn has_newer_versions(stored_key_share: &DocumentKeyShare, restored_key_share: &DocumentKeyShare) -> bool {
+	let restored_versions: BTreeSet<_> = restored_key_share.versions.iter().map(|v| &v.hash).collect();
+	stored_key_share.versions.iter().any(|v| !restored_versions.contains(&v.hash))
+}
+
+/// Check that the key share is consistent with its public portions.
+fn check_key_share(key_share: &DocumentKeyShare, self_public: &Public) -> Result<(), String> {
+	// public is unknown for keys, generated by the old versions
+	if key_share.public != Public::default() && !public_is_valid(&key_share.public) {
+		return Err("invalid server key public".into());
+	}
+	if key_share.common_point.as_ref().map(|p| !public_is_valid(p)).unwrap_or(false)
+		|| key_share.encrypted_point.as_ref().map(|p| !public_is_valid(p)).unwrap_or(false) {
+		return Err("invalid document key points".into());
+	}
+	if key_share.versions.is_empty() {
+		return Err("no share versions".into());
+	}
+
+	for version in &key_share.versions {
+		let hash = DocumentKeyShareVersion::data_hash(version.id_numbers.iter().map(|(k, v)| (&**k, &***v)));
+		if hash != version.hash {
+			return Err(format!("version {:?} hash mismatch", version.hash));
+		}
+		if !version.id_numbers.contains_key(self_public) {
+			return Err(format!("version {:?} doesn't include this node", version.hash));
+		}
+		if version.id_numbers.len() <= key_share.threshold {
+			return Err(format!("version {:?} has not enough nodes for threshold", version.hash));
+		}
+		if version.id_numbers.values().chain(::std::iter::once(&version.secret_share)).any(|s| s.check_validity().is_err()) {
+			return Err(format!("version {:?} has invalid secret share", version.hash));
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate tempdir;
+
+	use std::sync::Arc;
+	use self::tempdir::TempDir;
+	use ethkey::{Random, Generator, KeyPair};
+	use kvdb::KeyValueDB;
+	use kvdb_rocksdb::Database;
+	use key_storage::{KeyStorage, PersistentKeyStorage, DocumentKeyShare, DocumentKeyShareVersion};
+	use node_key_pair::PlainNodeKeyPair;
+	use types::{Error, ServerKeyId};
+	use super::{backup_key_storage, restore_key_storage, verify_key_storage, KeyShareStatus};
+
+	fn generate_key_share(self_key_pair: &KeyPair, threshold: usize) -> DocumentKeyShare {
+		let id_numbers = ::std::iter::once(self_key_pair.public().clone())
+			.chain((0..threshold).map(|_| Random.generate().unwrap().public().clone()))
+			.map(|n| (n, Random.generate().unwrap().secret().clone()))
+			.collect();
+		DocumentKeyShare {
+			author: Default::default(),
+			threshold: threshold,
+			public: Random.generate().unwrap().public().clone(),
+			common_point: None,
+			encrypted_point: None,
+			versions: vec![DocumentKeyShareVersion::new(id_numbers, Random.generate().unwrap().secret().clone())],
+		}
+	}
+
+	fn open_db(tempdir: &TempDir) -> Arc<KeyValueDB> {
+		Arc::new(Database::open_default(&tempdir.path().display().to_string()).unwrap())
+	}
+
+	#[test]
+	fn key_storage_backup_round_trip() {
+		let key_pair = Random.generate().unwrap();
+		let node_key_pair = PlainNodeKeyPair::new(key_pair.clone());
+		let key_shares: Vec<_> = (1..4).map(|t| (ServerKeyId::from(t as u64), generate_key_share(&key_pair, t))).collect();
+
+		let tempdir = TempDir::new("").unwrap();
+		let archive = {
+			let db = open_db(&tempdir);
+			let key_storage = PersistentKeyStorage::new(db.clone()).unwrap();
+			for &(ref key_id, ref key_share) in &key_shares {
+				key_storage.insert(key_id.clone(), key_share.clone()).unwrap();
+			}
+
+			backup_key_storage(db, &node_key_pair).unwrap()
+		};
+
+		// other nodes can't restore the archive
+		let other_tempdir = TempDir::new("").unwrap();
+		let other_node_key_pair = PlainNodeKeyPair::new(Random.generate().unwrap());
+		assert!(restore_key_storage(open_db(&other_tempdir), &other_node_key_pair, &archive, false).is_err());
+
+		// restore to the new database
+		let restored_tempdir = TempDir::new("").unwrap();
+		let db = open_db(&restored_tempdir);
+		assert_eq!(restore_key_storage(db.clone(), &node_key_pair, &archive, false), Ok(3));
+		let key_storage = PersistentKeyStorage::new(db.clone()).unwrap();
+		for &(ref key_id, ref key_share) in &key_shares {
+			assert_eq!(key_storage.get(key_id), Ok(Some(key_share.clone())));
+		}
+		assert_eq!(verify_key_storage(db.clone(), key_pair.public()).unwrap(), key_shares.iter()
+			.map(|&(ref key_id, ref key_share)| KeyShareStatus {
+				key_id: key_id.clone(),
+				threshold: Some(key_share.threshold),
+				versions: 1,
+				error: None,
+			})
+			.collect::<Vec<_>>());
+
+		// restoring the same archive again is a no-op
+		assert_eq!(restore_key_storage(db, &node_key_pair, &archive, false), Ok(0));
+	}
+
+	#[test]
+	fn key_storage_restore_does_not_overwrite_newer_versions() {
+		let key_pair = Random.generate().unwrap();
+		let node_key_pair = PlainNodeKeyPair::new(key_pair.clone());
+		let key_id = ServerKeyId::from(1);
+		let key_share = generate_key_share(&key_pair, 1);
+
+		let tempdir = TempDir::new("").unwrap();
+		let db = open_db(&tempdir);
+		let key_storage = PersistentKeyStorage::new(db.clone()).unwrap();
+		key_storage.insert(key_id.clone(), key_share.clone()).unwrap();
+		let archive = backup_key_storage(db.clone(), &node_key_pair).unwrap();
+
+		// share is updated after backup
+		let mut newer_key_share = key_share.clone();
+		newer_key_share.versions.push(generate_key_share(&key_pair, 1).versions.remove(0));
+		key_storage.update(key_id.clone(), newer_key_share.clone()).unwrap();
+
+		match restore_key_storage(db.clone(), &node_key_pair, &archive, false) {
+			Err(Error::Database(ref msg)) => assert!(msg.contains("newer versions")),
+			other => panic!("unexpected result: {:?}", other),
+		}
+		assert_eq!(key_storage.get(&key_id), Ok(Some(newer_key_share)));
+
+		assert_eq!(restore_key_storage(db, &node_key_pair, &archive, true), Ok(1));
+		assert_eq!(key_storage.get(&key_id), Ok(Some(key_share)));
+	}
+
+	#[test]
+	fn key_storage_verification_detects_corrupted_entries() {
+		let key_pair = Random.generate().unwrap();
+		let node_key_pair = PlainNodeKeyPair::new(key_pair.clone());
+
+		let tempdir = TempDir::new("").unwrap();
+		let db = open_db(&tempdir);
+		let key_storage = PersistentKeyStorage::new(db.clone()).unwrap();
+		key_storage.insert(ServerKeyId::from(1), generate_key_share(&key_pair, 1)).unwrap();
+
+		// share with the modified id numbers
+		let mut tampered_key_share = generate_key_share(&key_pair, 1);
+		tampered_key_share.versions[0].id_numbers.insert(Random.generate().unwrap().public().clone(),
+			Random.generate().unwrap().secret().clone());
+		key_storage.insert(ServerKeyId::from(2), tampered_key_share).unwrap();
+
+		// share which can't be deserialized
+		let mut batch = db.transaction();
+		batch.put(None, &*ServerKeyId::from(3), b"{\"threshold\":");
+		db.write(batch).unwrap();
+
+		let statuses = verify_key_storage(db.clone(), key_pair.public()).unwrap();
+		assert_eq!(statuses.len(), 3);
+		assert_eq!(statuses[0].error, None);
+		assert!(statuses[1].error.as_ref().unwrap().contains("hash mismatch"));
+		assert_eq!(statuses[2].threshold, None);
+		assert!(statuses[2].error.is_some());
+
+		// backup refuses to skip unreadable shares
+		assert!(backup_key_storage(db, &node_key_pair).is_err());
+	}
+}
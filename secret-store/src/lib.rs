@@ -30,13 +30,17 @@ extern crate parity_crypto as crypto;
 extern crate parity_runtime;
 extern crate parking_lot;
 extern crate rustc_hex;
+extern crate rustls;
 extern crate serde;
 extern crate serde_json;
 extern crate tiny_keccak;
 extern crate tokio;
 extern crate tokio_io;
+extern crate tokio_rustls;
 extern crate tokio_service;
+extern crate untrusted;
 extern crate url;
+extern crate webpki;
 extern crate jsonrpc_server_utils;
 
 #[macro_use]
@@ -68,6 +72,7 @@ mod traits;
 mod acl_storage;
 mod key_server;
 mod key_storage;
+mod key_storage_backup;
 mod serialization;
 mod key_server_set;
 mod node_key_pair;
@@ -82,9 +87,11 @@ use sync::SyncProvider;
 use parity_runtime::Executor;
 
 pub use types::{ServerKeyId, EncryptedDocumentKey, RequestSignature, Public,
-	Error, NodeAddress, ContractAddress, ServiceConfiguration, ClusterConfiguration};
-pub use traits::{NodeKeyPair, KeyServer};
+	Error, NodeAddress, ContractAddress, ServiceConfiguration, ClusterConfiguration, HttpTlsConfiguration,
+	ServiceContractKind, ServiceContractConfiguration, AdminMigrationMode, AdminSessionStatus, KeyServerStatus, SessionsStatus};
+pub use traits::{NodeKeyPair, KeyServer, AdminSessionsServer};
 pub use self::node_key_pair::PlainNodeKeyPair;
+pub use self::key_storage_backup::{KeyShareStatus, backup_key_storage, restore_key_storage, verify_key_storage};
 #[cfg(feature = "accounts")]
 pub use self::node_key_pair::KeyStoreNodeKeyPair;
 
@@ -94,7 +101,8 @@ pub fn start(client: Arc<Client>, sync: Arc<SyncProvider>, miner: Arc<Miner>, se
 {
 	let trusted_client = trusted_client::TrustedClient::new(self_key_pair.clone(), client.clone(), sync, miner);
 	let acl_storage: Arc<acl_storage::AclStorage> = match config.acl_check_contract_address.take() {
-		Some(acl_check_contract_address) => acl_storage::OnChainAclStorage::new(trusted_client.clone(), acl_check_contract_address)?,
+		Some(acl_check_contract_address) => acl_storage::OnChainAclStorage::new(trusted_client.clone(), acl_check_contract_address,
+			config.acl_check_cache_ttl)?,
 		None => Arc::new(acl_storage::DummyAclStorage::default()),
 	};
 
@@ -108,45 +116,19 @@ pub fn start(client: Arc<Client>, sync: Arc<SyncProvider>, miner: Arc<Miner>, se
 
 	// prepare HTTP listener
 	let http_listener = match config.listener_address {
-		Some(listener_address) => Some(listener::http_listener::KeyServerHttpListener::start(listener_address, config.cors, Arc::downgrade(&key_server), executor)?),
+		Some(listener_address) => Some(listener::http_listener::KeyServerHttpListener::start(listener_address, config.cors, config.http_tls, Arc::downgrade(&key_server), executor)?),
 		None => None,
 	};
 
 	// prepare service contract listeners
-	let create_service_contract = |address, name, api_mask|
-		Arc::new(listener::service_contract::OnChainServiceContract::new(
-			api_mask,
+	let mut contracts: Vec<Arc<listener::service_contract::ServiceContract>> = config.service_contracts.into_iter()
+		.map(|contract| Arc::new(listener::service_contract::OnChainServiceContract::new(
+			listener::ApiMask::from_kind(contract.kind),
 			trusted_client.clone(),
-			name,
-			address,
-			self_key_pair.clone()));
-
-	let mut contracts: Vec<Arc<listener::service_contract::ServiceContract>> = Vec::new();
-	config.service_contract_address.map(|address|
-		create_service_contract(address,
-			listener::service_contract::SERVICE_CONTRACT_REGISTRY_NAME.to_owned(),
-			listener::ApiMask::all()))
-		.map(|l| contracts.push(l));
-	config.service_contract_srv_gen_address.map(|address|
-		create_service_contract(address,
-			listener::service_contract::SRV_KEY_GEN_SERVICE_CONTRACT_REGISTRY_NAME.to_owned(),
-			listener::ApiMask { server_key_generation_requests: true, ..Default::default() }))
-		.map(|l| contracts.push(l));
-	config.service_contract_srv_retr_address.map(|address|
-		create_service_contract(address,
-			listener::service_contract::SRV_KEY_RETR_SERVICE_CONTRACT_REGISTRY_NAME.to_owned(),
-			listener::ApiMask { server_key_retrieval_requests: true, ..Default::default() }))
-		.map(|l| contracts.push(l));
-	config.service_contract_doc_store_address.map(|address|
-		create_service_contract(address,
-			listener::service_contract::DOC_KEY_STORE_SERVICE_CONTRACT_REGISTRY_NAME.to_owned(),
-			listener::ApiMask { document_key_store_requests: true, ..Default::default() }))
-		.map(|l| contracts.push(l));
-	config.service_contract_doc_sretr_address.map(|address|
-		create_service_contract(address,
-			listener::service_contract::DOC_KEY_SRETR_SERVICE_CONTRACT_REGISTRY_NAME.to_owned(),
-			listener::ApiMask { document_key_shadow_retrieval_requests: true, ..Default::default() }))
-		.map(|l| contracts.push(l));
+			listener::service_contract::registry_name(contract.kind).to_owned(),
+			contract.address,
+			self_key_pair.clone())) as Arc<listener::service_contract::ServiceContract>)
+		.collect();
 
 	let contract: Option<Arc<listener::service_contract::ServiceContract>> = match contracts.len() {
 		0 => None,
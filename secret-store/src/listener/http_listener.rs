@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::{Arc, Weak};
 use hyper::{self, Uri, Request as HttpRequest, Response as HttpResponse, Method as HttpMethod,
 	StatusCode as HttpStatusCode, Body,
@@ -25,15 +25,19 @@ use hyper::{self, Uri, Request as HttpRequest, Response as HttpResponse, Method
 use serde::Serialize;
 use serde_json;
 use tokio;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
 use parity_runtime::Executor;
 use futures::{future, Future, Stream};
 use url::percent_encoding::percent_decode;
 
 use traits::KeyServer;
-use serialization::{SerializableEncryptedDocumentKeyShadow, SerializableBytes, SerializablePublic};
+use serialization::{SerializableEncryptedDocumentKeyShadow, SerializableEncryptedDocumentKeyShadowResult,
+	SerializableBytes, SerializablePublic, SerializableH256, SerializableKeyServerStatus};
 use types::{Error, Public, MessageHash, NodeAddress, RequestSignature, ServerKeyId,
-	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId};
+	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId, HttpTlsConfiguration, KeyServerStatus};
+use super::http_tls;
 use jsonrpc_server_utils::cors::{self, AllowCors, AccessControlAllowOrigin};
 
 /// Key server http-requests listener. Available requests:
@@ -42,12 +46,18 @@ use jsonrpc_server_utils::cors::{self, AllowCors, AccessControlAllowOrigin};
 /// To generate server && document key:				POST		/{server_key_id}/{signature}/{threshold}
 /// To get document key:							GET			/{server_key_id}/{signature}
 /// To get document key shadow:						GET			/shadow/{server_key_id}/{signature}
+/// To get multiple document keys shadows:			POST		/shadow_batch/{signature} + BODY: json array of distinct hex-encoded server keys ids
 /// To generate Schnorr signature with server key:	GET			/schnorr/{server_key_id}/{signature}/{message_hash}
 /// To generate ECDSA signature with server key:	GET			/ecdsa/{server_key_id}/{signature}/{message_hash}
 /// To change servers set:							POST		/admin/servers_set_change/{old_signature}/{new_signature} + BODY: json array of hex-encoded nodes ids
+/// To change threshold of server key:				POST		/admin/threshold_change/{server_key_id}/{signature}/{threshold}
+/// To get key server status && sessions statistics:	GET			/status
 
 type CorsDomains = Option<Vec<AccessControlAllowOrigin>>;
 
+/// Maximal number of document keys shadows requested in a single batch.
+const MAX_DOCUMENT_KEYS_SHADOWS_BATCH: usize = 64;
+
 pub struct KeyServerHttpListener {
 	_executor: Executor,
 	_handler: Arc<KeyServerSharedHttpHandler>,
@@ -68,12 +78,18 @@ enum Request {
 	GetDocumentKey(ServerKeyId, RequestSignature),
 	/// Request shadow of encryption key of given document for given requestor.
 	GetDocumentKeyShadow(ServerKeyId, RequestSignature),
+	/// Request shadows of encryption keys of given documents for given requestor.
+	GetDocumentKeysShadows(Vec<ServerKeyId>, RequestSignature),
 	/// Generate Schnorr signature for the message.
 	SchnorrSignMessage(ServerKeyId, RequestSignature, MessageHash),
 	/// Generate ECDSA signature for the message.
 	EcdsaSignMessage(ServerKeyId, RequestSignature, MessageHash),
 	/// Change servers set.
 	ChangeServersSet(RequestSignature, RequestSignature, BTreeSet<NodeId>),
+	/// Change threshold of server key.
+	ChangeKeyThreshold(ServerKeyId, RequestSignature, usize),
+	/// Get key server status.
+	GetStatus,
 }
 
 /// Cloneable http handler
@@ -91,11 +107,17 @@ struct KeyServerSharedHttpHandler {
 
 impl KeyServerHttpListener {
 	/// Start KeyServer http listener
-	pub fn start(listener_address: NodeAddress, cors_domains: Option<Vec<String>>, key_server: Weak<KeyServer>, executor: Executor) -> Result<Self, Error> {
+	pub fn start(listener_address: NodeAddress, cors_domains: Option<Vec<String>>, tls: Option<HttpTlsConfiguration>,
+		key_server: Weak<KeyServer>, executor: Executor) -> Result<Self, Error>
+	{
 		let shared_handler = Arc::new(KeyServerSharedHttpHandler {
 			key_server: key_server,
 		});
 		let cors: CorsDomains = cors_domains.map(|domains| domains.into_iter().map(AccessControlAllowOrigin::from).collect());
+		let tls_acceptor = match tls {
+			Some(tls) => Some(TlsAcceptor::from(Arc::new(http_tls::server_config(&tls)?))),
+			None => None,
+		};
 		let listener_address = format!("{}:{}", listener_address.address, listener_address.port).parse()?;
 		let listener = TcpListener::bind(&listener_address)?;
 
@@ -104,14 +126,13 @@ impl KeyServerHttpListener {
 		let server = listener.incoming()
 			.map_err(|e| warn!("Key server listener error: {:?}", e))
 			.for_each(move |socket| {
-				let http = Http::new();
-				let serve = http.serve_connection(socket,
-					KeyServerHttpHandler { handler: shared_handler2.clone(), cors: cors.clone() }
-				).map(|_| ()).map_err(|e| {
-					warn!("Key server handler error: {:?}", e);
-				});
-
-				tokio::spawn(serve)
+				let handler = KeyServerHttpHandler { handler: shared_handler2.clone(), cors: cors.clone() };
+				match tls_acceptor {
+					Some(ref tls_acceptor) => tokio::spawn(tls_acceptor.accept(socket)
+						.map_err(|e| warn!("Key server TLS handshake error: {:?}", e))
+						.and_then(move |socket| serve_connection(socket, handler))),
+					None => tokio::spawn(serve_connection(socket, handler)),
+				}
 			});
 
 		executor.spawn(server);
@@ -125,6 +146,14 @@ impl KeyServerHttpListener {
 	}
 }
 
+fn serve_connection<S>(socket: S, handler: KeyServerHttpHandler) -> impl Future<Item=(), Error=()>
+	where S: AsyncRead + AsyncWrite + Send + 'static
+{
+	Http::new().serve_connection(socket, handler)
+		.map(|_| ())
+		.map_err(|e| warn!("Key server handler error: {:?}", e))
+}
+
 impl KeyServerHttpHandler {
 	fn process(self, req_method: HttpMethod, req_uri: Uri, path: &str, req_body: &[u8], cors: AllowCors<AccessControlAllowOrigin>) -> HttpResponse<Body> {
 		match parse_request(&req_method, &path, &req_body) {
@@ -173,6 +202,15 @@ impl KeyServerHttpHandler {
 						err
 					}))
 			},
+			Request::GetDocumentKeysShadows(documents, signature) => {
+				return_document_keys_shadows(&req_uri, cors, &documents, self.handler.key_server.upgrade()
+					.map(|key_server| key_server.restore_document_keys_shadows(&documents, &signature.into()))
+					.unwrap_or(Err(Error::Internal("KeyServer is already destroyed".into())))
+					.map_err(|err| {
+						warn!(target: "secretstore", "GetDocumentKeysShadows request {} has failed with: {}", req_uri, err);
+						err
+					}))
+			},
 			Request::SchnorrSignMessage(document, signature, message_hash) => {
 				return_message_signature(&req_uri, cors, self.handler.key_server.upgrade()
 					.map(|key_server| key_server.sign_message_schnorr(&document, &signature.into(), message_hash))
@@ -200,6 +238,24 @@ impl KeyServerHttpHandler {
 						err
 					}))
 				},
+			Request::ChangeKeyThreshold(document, signature, new_threshold) => {
+				return_empty(&req_uri, cors, self.handler.key_server.upgrade()
+					.map(|key_server| key_server.change_key_threshold(&document, new_threshold, signature))
+					.unwrap_or(Err(Error::Internal("KeyServer is already destroyed".into())))
+					.map_err(|err| {
+						warn!(target: "secretstore", "ChangeKeyThreshold request {} has failed with: {}", req_uri, err);
+						err
+					}))
+				},
+			Request::GetStatus => {
+				return_status(&req_uri, cors, self.handler.key_server.upgrade()
+					.map(|key_server| key_server.status())
+					.unwrap_or(Err(Error::Internal("KeyServer is already destroyed".into())))
+					.map_err(|err| {
+						warn!(target: "secretstore", "GetStatus request {} has failed with: {}", req_uri, err);
+						err
+					}))
+			},
 			Request::Invalid => {
 				warn!(target: "secretstore", "Ignoring invalid {}-request {}", req_method, req_uri);
 				HttpResponse::builder()
@@ -274,11 +330,22 @@ fn return_document_key(req_uri: &Uri, cors: AllowCors<AccessControlAllowOrigin>,
 fn return_document_key_shadow(req_uri: &Uri, cors: AllowCors<AccessControlAllowOrigin>, document_key_shadow: Result<EncryptedDocumentKeyShadow, Error>)
 	-> HttpResponse<Body>
 {
-	return_bytes(req_uri, cors, document_key_shadow.map(|k| Some(SerializableEncryptedDocumentKeyShadow {
-		decrypted_secret: k.decrypted_secret.into(),
-		common_point: k.common_point.expect("always filled when requesting document_key_shadow; qed").into(),
-		decrypt_shadows: k.decrypt_shadows.expect("always filled when requesting document_key_shadow; qed").into_iter().map(Into::into).collect()
-	})))
+	return_bytes(req_uri, cors, document_key_shadow.map(|k| Some(SerializableEncryptedDocumentKeyShadow::from(k))))
+}
+
+fn return_document_keys_shadows(req_uri: &Uri, cors: AllowCors<AccessControlAllowOrigin>, documents: &[ServerKeyId],
+	document_keys_shadows: Result<Vec<Result<EncryptedDocumentKeyShadow, Error>>, Error>) -> HttpResponse<Body>
+{
+	return_bytes(req_uri, cors, document_keys_shadows.map(|shadows| Some(documents.iter().cloned().zip(shadows)
+		.map(|(document, shadow)| (SerializableH256(document), match shadow {
+			Ok(shadow) => SerializableEncryptedDocumentKeyShadowResult::Shadow(shadow.into()),
+			Err(err) => SerializableEncryptedDocumentKeyShadowResult::Error(format!("{}", err)),
+		}))
+		.collect::<BTreeMap<_, _>>())))
+}
+
+fn return_status(req_uri: &Uri, cors: AllowCors<AccessControlAllowOrigin>, status: Result<KeyServerStatus, Error>) -> HttpResponse<Body> {
+	return_bytes(req_uri, cors, status.map(|status| Some(SerializableKeyServerStatus::from(status))))
 }
 
 fn return_bytes<T: Serialize>(req_uri: &Uri, cors: AllowCors<AccessControlAllowOrigin>, result: Result<Option<T>, Error>) -> HttpResponse<Body> {
@@ -361,6 +428,17 @@ fn parse_request(method: &HttpMethod, uri_path: &str, body: &[u8]) -> Request {
 		return parse_admin_request(method, path, body);
 	}
 
+	if path[0] == "shadow_batch" {
+		return parse_document_keys_shadows_request(method, path, body);
+	}
+
+	if path[0] == "status" {
+		return match (path.len(), method) {
+			(1, &HttpMethod::GET) => Request::GetStatus,
+			_ => Request::Invalid,
+		};
+	}
+
 	let (prefix, args_offset) = if &path[0] == "shadow" || &path[0] == "schnorr" || &path[0] == "ecdsa"
 		{ (&*path[0], 1) } else { ("", 0) };
 	let args_count = path.len() - args_offset;
@@ -402,6 +480,10 @@ fn parse_request(method: &HttpMethod, uri_path: &str, body: &[u8]) -> Request {
 
 fn parse_admin_request(method: &HttpMethod, path: Vec<String>, body: &[u8]) -> Request {
 	let args_count = path.len();
+	if args_count > 1 && path[1] == "threshold_change" {
+		return parse_threshold_change_request(method, path);
+	}
+
 	if *method != HttpMethod::POST || args_count != 4 || path[1] != "servers_set_change" {
 		return Request::Invalid;
 	}
@@ -425,27 +507,173 @@ fn parse_admin_request(method: &HttpMethod, path: Vec<String>, body: &[u8]) -> R
 		new_servers_set.into_iter().map(Into::into).collect())
 }
 
+fn parse_document_keys_shadows_request(method: &HttpMethod, path: Vec<String>, body: &[u8]) -> Request {
+	if *method != HttpMethod::POST || path.len() != 2 {
+		return Request::Invalid;
+	}
+
+	let signature = match path[1].parse() {
+		Ok(signature) => signature,
+		_ => return Request::Invalid,
+	};
+
+	let documents: Vec<SerializableH256> = match serde_json::from_slice(body) {
+		Ok(documents) => documents,
+		_ => return Request::Invalid,
+	};
+
+	// results are keyed by the document, so every document may only be requested once.
+	if documents.len() > MAX_DOCUMENT_KEYS_SHADOWS_BATCH || documents.iter().collect::<BTreeSet<_>>().len() != documents.len() {
+		return Request::Invalid;
+	}
+
+	Request::GetDocumentKeysShadows(documents.into_iter().map(Into::into).collect(), signature)
+}
+
+fn parse_threshold_change_request(method: &HttpMethod, path: Vec<String>) -> Request {
+	if *method != HttpMethod::POST || path.len() != 5 {
+		return Request::Invalid;
+	}
+
+	let document = match path[2].parse() {
+		Ok(document) => document,
+		_ => return Request::Invalid,
+	};
+
+	let signature = match path[3].parse() {
+		Ok(signature) => signature,
+		_ => return Request::Invalid,
+	};
+
+	let new_threshold = match path[4].parse() {
+		Ok(new_threshold) => new_threshold,
+		_ => return Request::Invalid,
+	};
+
+	Request::ChangeKeyThreshold(document, signature, new_threshold)
+}
+
 #[cfg(test)]
 mod tests {
+	use std::fs::File;
+	use std::io::{BufReader, Read, Write};
+	use std::net::TcpStream;
 	use std::sync::Arc;
 	use hyper::Method as HttpMethod;
-	use ethkey::Public;
-	use traits::KeyServer;
-	use key_server::tests::DummyKeyServer;
-	use types::NodeAddress;
+	use rustls::{self, ClientConfig, ClientSession};
+	use rustls::internal::pemfile;
+	use serde_json;
+	use webpki::DNSNameRef;
+	use crypto::DEFAULT_MAC;
+	use ethkey::{self, crypto, Public, Random, Generator};
+	use traits::{KeyServer, DocumentKeyServer};
+	use key_server::tests::{DummyKeyServer, make_key_servers};
+	use serialization::{SerializableBytes, SerializableH256};
+	use types::{NodeAddress, HttpTlsConfiguration};
 	use parity_runtime::Runtime;
-	use super::{parse_request, Request, KeyServerHttpListener};
+	use super::{parse_request, Request, KeyServerHttpListener, MAX_DOCUMENT_KEYS_SHADOWS_BATCH};
+
+	fn tls_resource(name: &str) -> String {
+		format!("{}/res/tls/{}", env!("CARGO_MANIFEST_DIR"), name)
+	}
+
+	fn tls_config(client_ca: bool) -> HttpTlsConfiguration {
+		HttpTlsConfiguration {
+			certificate_path: tls_resource("server.pem"),
+			private_key_path: tls_resource("server.key"),
+			client_ca_path: if client_ca { Some(tls_resource("ca.pem")) } else { None },
+		}
+	}
+
+	/// Send GET request over TLS and return the raw response. Response is empty if the TLS session has failed.
+	fn https_get(port: u16, path: &str, client_certificate: bool) -> String {
+		let mut config = ClientConfig::new();
+		let ca = File::open(tls_resource("ca.pem")).unwrap();
+		config.root_store.add_pem_file(&mut BufReader::new(ca)).unwrap();
+		if client_certificate {
+			let certificates = pemfile::certs(&mut BufReader::new(File::open(tls_resource("client.pem")).unwrap())).unwrap();
+			let mut keys = pemfile::pkcs8_private_keys(&mut BufReader::new(File::open(tls_resource("client.key")).unwrap())).unwrap();
+			config.set_single_client_cert(certificates, keys.remove(0));
+		}
+
+		let mut session = ClientSession::new(&Arc::new(config), DNSNameRef::try_from_ascii_str("localhost").unwrap());
+		let mut socket = TcpStream::connect(("127.0.0.1", port)).unwrap();
+		let mut stream = rustls::Stream::new(&mut session, &mut socket);
+		let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path);
+
+		let mut response = Vec::new();
+		if stream.write_all(request.as_bytes()).is_ok() {
+			let _ = stream.read_to_end(&mut response);
+		}
+		String::from_utf8_lossy(&response).into_owned()
+	}
 
 	#[test]
 	fn http_listener_successfully_drops() {
 		let key_server: Arc<KeyServer> = Arc::new(DummyKeyServer::default());
 		let address = NodeAddress { address: "127.0.0.1".into(), port: 9000 };
 		let runtime = Runtime::with_thread_count(1);
-		let listener = KeyServerHttpListener::start(address, None, Arc::downgrade(&key_server),
+		let listener = KeyServerHttpListener::start(address, None, None, Arc::downgrade(&key_server),
+			runtime.executor()).unwrap();
+		drop(listener);
+	}
+
+	#[test]
+	fn document_key_is_retrieved_over_https() {
+		let (key_servers, _, runtime) = make_key_servers(6130, 1);
+		let key_server: Arc<KeyServer> = Arc::new(key_servers.into_iter().next().unwrap());
+		let address = NodeAddress { address: "127.0.0.1".into(), port: 9001 };
+		let listener = KeyServerHttpListener::start(address, None, Some(tls_config(false)), Arc::downgrade(&key_server),
+			runtime.executor()).unwrap();
+
+		// generate document key
+		let document = Random.generate().unwrap().secret().clone();
+		let secret = Random.generate().unwrap().secret().clone();
+		let signature = ethkey::sign(&secret, &document).unwrap();
+		let generated_key = key_server.generate_document_key(&document, &signature.clone().into(), 0).unwrap();
+		let generated_key = crypto::ecies::decrypt(&secret, &DEFAULT_MAC, &generated_key).unwrap();
+
+		// and retrieve it back over HTTPS
+		let response = https_get(9001, &format!("/{:x}/{}", document, signature), false);
+		assert!(response.starts_with("HTTP/1.1 200"), "unexpected response: {}", response);
+		let body = &response[response.find("\r\n\r\n").unwrap() + 4..];
+		let retrieved_key: SerializableBytes = serde_json::from_str(body).unwrap();
+		let retrieved_key = crypto::ecies::decrypt(&secret, &DEFAULT_MAC, &retrieved_key).unwrap();
+		assert_eq!(retrieved_key, generated_key);
+
+		drop(listener);
+		drop(runtime);
+	}
+
+	#[test]
+	fn https_listener_requires_client_certificate_when_configured() {
+		let key_server: Arc<KeyServer> = Arc::new(DummyKeyServer::default());
+		let address = NodeAddress { address: "127.0.0.1".into(), port: 9002 };
+		let runtime = Runtime::with_thread_count(1);
+		let listener = KeyServerHttpListener::start(address, None, Some(tls_config(true)), Arc::downgrade(&key_server),
 			runtime.executor()).unwrap();
+
+		assert!(!https_get(9002, "/", false).starts_with("HTTP/1.1"));
+		let response = https_get(9002, "/", true);
+		assert!(response.starts_with("HTTP/1.1 400"), "unexpected response: {}", response);
+
 		drop(listener);
 	}
 
+	#[test]
+	fn http_listener_fails_to_start_with_mismatched_key() {
+		let key_server: Arc<KeyServer> = Arc::new(DummyKeyServer::default());
+		let address = NodeAddress { address: "127.0.0.1".into(), port: 9003 };
+		let runtime = Runtime::with_thread_count(1);
+		let tls = HttpTlsConfiguration {
+			private_key_path: tls_resource("client.key"),
+			..tls_config(false)
+		};
+		let error = KeyServerHttpListener::start(address, None, Some(tls), Arc::downgrade(&key_server), runtime.executor())
+			.err().unwrap();
+		assert!(error.to_string().contains("does not match"), "unexpected error: {}", error);
+	}
+
 	#[test]
 	fn parse_request_successful() {
 		// POST		/shadow/{server_key_id}/{signature}/{threshold}						=> generate server key
@@ -497,6 +725,24 @@ mod tests {
 				"b199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
 				nodes,
 			));
+		// POST		/admin/threshold_change/{server_key_id}/{signature}/{threshold}
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/threshold_change/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/3", Default::default()),
+			Request::ChangeKeyThreshold(
+				"0000000000000000000000000000000000000000000000000000000000000001".into(),
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+				3,
+			));
+		// GET		/status
+		assert_eq!(parse_request(&HttpMethod::GET, "/status", Default::default()), Request::GetStatus);
+		// POST		/shadow_batch/{signature} + body
+		assert_eq!(parse_request(&HttpMethod::POST, "/shadow_batch/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01",
+			&r#"["0x0000000000000000000000000000000000000000000000000000000000000001",
+				"0x0000000000000000000000000000000000000000000000000000000000000002"]"#.as_bytes()),
+			Request::GetDocumentKeysShadows(
+				vec!["0000000000000000000000000000000000000000000000000000000000000001".into(),
+					"0000000000000000000000000000000000000000000000000000000000000002".into()],
+				"a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01".parse().unwrap(),
+			));
 	}
 
 	#[test]
@@ -516,5 +762,26 @@ mod tests {
 			Request::Invalid);
 		assert_eq!(parse_request(&HttpMethod::POST, "/admin/servers_set_change/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01", "".as_bytes()),
 			Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::GET, "/admin/threshold_change/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/3", Default::default()),
+			Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/admin/threshold_change/0000000000000000000000000000000000000000000000000000000000000001/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01/x", Default::default()),
+			Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/status", Default::default()), Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::GET, "/shadow_batch/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01",
+			&r#"["0x0000000000000000000000000000000000000000000000000000000000000001"]"#.as_bytes()),
+			Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/shadow_batch/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01",
+			&r#"["0x01"]"#.as_bytes()),
+			Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::POST, "/shadow_batch/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01",
+			&r#"["0x0000000000000000000000000000000000000000000000000000000000000001",
+				"0x0000000000000000000000000000000000000000000000000000000000000001"]"#.as_bytes()),
+			Request::Invalid);
+		let too_many_documents = serde_json::to_vec(&(0..MAX_DOCUMENT_KEYS_SHADOWS_BATCH as u64 + 1)
+			.map(|i| SerializableH256(i.into())).collect::<Vec<_>>()).unwrap();
+		assert_eq!(parse_request(&HttpMethod::POST, "/shadow_batch/a199fb39e11eefb61c78a4074a53c0d4424600a3e74aad4fb9d93a26c30d067e1d4d29936de0c73f19827394a1dd049480a0d581aee7ae7546968da7d3d1c2fd01",
+			&too_many_documents),
+			Request::Invalid);
+		assert_eq!(parse_request(&HttpMethod::GET, "/status/0000000000000000000000000000000000000000000000000000000000000001", Default::default()), Request::Invalid);
 	}
 }
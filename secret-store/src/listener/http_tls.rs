@@ -0,0 +1,157 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! TLS configuration of the HTTP listener.
+
+use std::fs::File;
+use std::io::BufReader;
+use rustls::{ServerConfig, Certificate, PrivateKey, RootCertStore, SignatureScheme,
+	NoClientAuth, AllowAnyAuthenticatedClient};
+use rustls::internal::pemfile;
+use rustls::sign;
+use untrusted;
+use webpki;
+use types::{Error, HttpTlsConfiguration};
+
+/// Message signed to check that the private key belongs to the certificate.
+const KEY_CHECK_MESSAGE: &[u8] = b"secretstore http listener key check";
+
+/// Create TLS configuration of the HTTP listener.
+pub fn server_config(config: &HttpTlsConfiguration) -> Result<ServerConfig, Error> {
+	let certificates = load_certificates(&config.certificate_path)?;
+	let private_key = load_private_key(&config.private_key_path)?;
+	check_key_matches(&certificates[0], &private_key).map_err(|e| Error::Tls(format!(
+		"private key {} does not match certificate {}: {}", config.private_key_path, config.certificate_path, e)))?;
+
+	let client_verifier = match config.client_ca_path {
+		Some(ref path) => {
+			let mut roots = RootCertStore::empty();
+			for certificate in load_certificates(path)? {
+				roots.add(&certificate)
+					.map_err(|e| Error::Tls(format!("invalid CA certificate in {}: {:?}", path, e)))?;
+			}
+			AllowAnyAuthenticatedClient::new(roots)
+		},
+		None => NoClientAuth::new(),
+	};
+
+	let mut server_config = ServerConfig::new(client_verifier);
+	server_config.set_single_cert(certificates, private_key)
+		.map_err(|e| Error::Tls(format!("invalid certificate {}: {}", config.certificate_path, e)))?;
+	server_config.set_protocols(&["http/1.1".to_owned()]);
+
+	Ok(server_config)
+}
+
+/// Load PEM-encoded certificates from file. At least one certificate is required.
+fn load_certificates(path: &str) -> Result<Vec<Certificate>, Error> {
+	let file = File::open(path).map_err(|e| Error::Tls(format!("cannot read {}: {}", path, e)))?;
+	let certificates = pemfile::certs(&mut BufReader::new(file))
+		.map_err(|_| Error::Tls(format!("invalid PEM certificate in {}", path)))?;
+
+	match certificates.is_empty() {
+		true => Err(Error::Tls(format!("no certificates found in {}", path))),
+		false => Ok(certificates),
+	}
+}
+
+/// Load a PEM-encoded PKCS8 or RSA private key from file.
+fn load_private_key(path: &str) -> Result<PrivateKey, Error> {
+	let read_keys = |parse: fn(&mut ::std::io::BufRead) -> Result<Vec<PrivateKey>, ()>| {
+		let file = File::open(path).map_err(|e| Error::Tls(format!("cannot read {}: {}", path, e)))?;
+		parse(&mut BufReader::new(file)).map_err(|_| Error::Tls(format!("invalid PEM private key in {}", path)))
+	};
+
+	let mut keys = read_keys(pemfile::pkcs8_private_keys)?;
+	if keys.is_empty() {
+		keys = read_keys(pemfile::rsa_private_keys)?;
+	}
+
+	match keys.len() {
+		1 => Ok(keys.remove(0)),
+		0 => Err(Error::Tls(format!("no private key found in {}", path))),
+		_ => Err(Error::Tls(format!("more than one private key found in {}", path))),
+	}
+}
+
+/// Check that the key signs messages which verify against the public key of the certificate.
+fn check_key_matches(certificate: &Certificate, private_key: &PrivateKey) -> Result<(), String> {
+	let signing_key = sign::any_supported_type(private_key)
+		.map_err(|_| "unsupported private key type".to_owned())?;
+	let signer = signing_key.choose_scheme(&[
+		SignatureScheme::ECDSA_NISTP256_SHA256,
+		SignatureScheme::ECDSA_NISTP384_SHA384,
+		SignatureScheme::RSA_PKCS1_SHA256,
+		SignatureScheme::ED25519,
+	]).ok_or_else(|| "unsupported private key type".to_owned())?;
+	let algorithm: &webpki::SignatureAlgorithm = match signer.get_scheme() {
+		SignatureScheme::ECDSA_NISTP256_SHA256 => &webpki::ECDSA_P256_SHA256,
+		SignatureScheme::ECDSA_NISTP384_SHA384 => &webpki::ECDSA_P384_SHA384,
+		SignatureScheme::RSA_PKCS1_SHA256 => &webpki::RSA_PKCS1_2048_8192_SHA256,
+		SignatureScheme::ED25519 => &webpki::ED25519,
+		scheme => return Err(format!("unsupported signature scheme {:?}", scheme)),
+	};
+
+	let signature = signer.sign(KEY_CHECK_MESSAGE).map_err(|e| format!("{}", e))?;
+	let certificate = webpki::EndEntityCert::from(untrusted::Input::from(&certificate.0))
+		.map_err(|e| format!("{:?}", e))?;
+	certificate.verify_signature(algorithm, untrusted::Input::from(KEY_CHECK_MESSAGE), untrusted::Input::from(&signature))
+		.map_err(|e| format!("{:?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+	use types::{Error, HttpTlsConfiguration};
+	use super::server_config;
+
+	fn resource(name: &str) -> String {
+		format!("{}/res/tls/{}", env!("CARGO_MANIFEST_DIR"), name)
+	}
+
+	fn tls_config(certificate: &str, private_key: &str, client_ca: Option<&str>) -> HttpTlsConfiguration {
+		HttpTlsConfiguration {
+			certificate_path: resource(certificate),
+			private_key_path: resource(private_key),
+			client_ca_path: client_ca.map(resource),
+		}
+	}
+
+	#[test]
+	fn loads_certificate_and_key() {
+		assert!(server_config(&tls_config("server.pem", "server.key", None)).is_ok());
+		assert!(server_config(&tls_config("server.pem", "server.key", Some("ca.pem"))).is_ok());
+	}
+
+	#[test]
+	fn rejects_unreadable_files() {
+		match server_config(&tls_config("missing.pem", "server.key", None)) {
+			Err(Error::Tls(ref msg)) => assert!(msg.contains("missing.pem")),
+			other => panic!("unexpected result: {:?}", other.map(|_| ())),
+		}
+		match server_config(&tls_config("server.pem", "server.pem", None)) {
+			Err(Error::Tls(ref msg)) => assert!(msg.contains("no private key found")),
+			other => panic!("unexpected result: {:?}", other.map(|_| ())),
+		}
+	}
+
+	#[test]
+	fn rejects_mismatched_key() {
+		match server_config(&tls_config("server.pem", "client.key", None)) {
+			Err(Error::Tls(ref msg)) => assert!(msg.contains("does not match")),
+			other => panic!("unexpected result: {:?}", other.map(|_| ())),
+		}
+	}
+}
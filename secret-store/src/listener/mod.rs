@@ -15,16 +15,20 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 pub mod http_listener;
+mod http_tls;
 pub mod service_contract;
 pub mod service_contract_aggregate;
 pub mod service_contract_listener;
+mod retry_queue;
 mod tasks_queue;
 
 use std::collections::BTreeSet;
 use std::sync::Arc;
+use ethereum_types::H256;
 use traits::{ServerKeyGenerator, DocumentKeyServer, MessageSigner, AdminSessionsServer, KeyServer};
 use types::{Error, Public, MessageHash, EncryptedMessageSignature, RequestSignature, ServerKeyId,
-	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId, Requester};
+	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId, Requester, AdminSessionStatus, KeyServerStatus,
+	ServiceContractKind};
 
 /// Available API mask.
 #[derive(Debug, Default)]
@@ -56,6 +60,17 @@ impl ApiMask {
 			document_key_shadow_retrieval_requests: true,
 		}
 	}
+
+	/// Create mask that accepts requests of given service contract kind.
+	pub fn from_kind(kind: ServiceContractKind) -> Self {
+		match kind {
+			ServiceContractKind::General => ApiMask::all(),
+			ServiceContractKind::ServerKeyGeneration => ApiMask { server_key_generation_requests: true, ..Default::default() },
+			ServiceContractKind::ServerKeyRetrieval => ApiMask { server_key_retrieval_requests: true, ..Default::default() },
+			ServiceContractKind::DocumentKeyStore => ApiMask { document_key_store_requests: true, ..Default::default() },
+			ServiceContractKind::DocumentKeyShadowRetrieval => ApiMask { document_key_shadow_retrieval_requests: true, ..Default::default() },
+		}
+	}
 }
 
 impl Listener {
@@ -93,6 +108,10 @@ impl DocumentKeyServer for Listener {
 	fn restore_document_key_shadow(&self, key_id: &ServerKeyId, requester: &Requester) -> Result<EncryptedDocumentKeyShadow, Error> {
 		self.key_server.restore_document_key_shadow(key_id, requester)
 	}
+
+	fn restore_document_keys_shadows(&self, key_ids: &[ServerKeyId], requester: &Requester) -> Result<Vec<Result<EncryptedDocumentKeyShadow, Error>>, Error> {
+		self.key_server.restore_document_keys_shadows(key_ids, requester)
+	}
 }
 
 impl MessageSigner for Listener {
@@ -109,4 +128,24 @@ impl AdminSessionsServer for Listener {
 	fn change_servers_set(&self, old_set_signature: RequestSignature, new_set_signature: RequestSignature, new_servers_set: BTreeSet<NodeId>) -> Result<(), Error> {
 		self.key_server.change_servers_set(old_set_signature, new_set_signature, new_servers_set)
 	}
+
+	fn start_servers_set_change(&self, old_set_signature: RequestSignature, new_set_signature: RequestSignature, new_servers_set: BTreeSet<NodeId>) -> Result<H256, Error> {
+		self.key_server.start_servers_set_change(old_set_signature, new_set_signature, new_servers_set)
+	}
+
+	fn change_key_threshold(&self, key_id: &ServerKeyId, new_threshold: usize, signature: RequestSignature) -> Result<(), Error> {
+		self.key_server.change_key_threshold(key_id, new_threshold, signature)
+	}
+
+	fn admin_sessions(&self) -> Result<Vec<AdminSessionStatus>, Error> {
+		self.key_server.admin_sessions()
+	}
+
+	fn abort_admin_session(&self, session_id: &H256, signature: RequestSignature) -> Result<(), Error> {
+		self.key_server.abort_admin_session(session_id, signature)
+	}
+
+	fn status(&self) -> Result<KeyServerStatus, Error> {
+		self.key_server.status()
+	}
 }
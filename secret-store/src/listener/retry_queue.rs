@@ -0,0 +1,320 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Queue of service contract requests, which have failed with non-fatal errors and are waiting to be retried.
+//!
+//! Only sessions, started by this key server, are tracked. When the session fails with non-fatal error, the
+//! request is scheduled for retry with exponential backoff. Once all attempts are exhausted, the failure
+//! must be published on-chain.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::time::{Duration, Instant};
+use ethereum_types::Address;
+use ethkey::public_to_address;
+use listener::service_contract_listener::ServiceTask;
+use ServerKeyId;
+
+/// Max number of attempts to process request, including the first one.
+pub const MAX_ATTEMPTS: usize = 4;
+/// Delay before the first retry. It is doubled after every failed retry.
+pub const INITIAL_BACKOFF: Duration = Duration::from_secs(10);
+/// Max time to wait for the started session to report its result. When it elapses, the attempt is considered failed.
+pub const SESSION_DEADLINE: Duration = Duration::from_secs(5 * 60);
+/// Max number of tracked requests. Failures of untracked requests are left for the periodic retry of pending requests.
+pub const MAX_TRACKED_REQUESTS: usize = 256;
+
+/// Key of the request, which is retried.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RetryKey {
+	/// Server key generation (server_key_id).
+	ServerKeyGeneration(ServerKeyId),
+	/// Server key retrieval (server_key_id).
+	ServerKeyRetrieval(ServerKeyId),
+	/// Document key shadow retrieval (server_key_id, requester).
+	DocumentKeyShadowRetrieval(ServerKeyId, Address),
+}
+
+/// What to do with the failed request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailureAction {
+	/// Request is scheduled for retry.
+	Retry,
+	/// All attempts are exhausted => failure must be published.
+	Publish,
+	/// Request isn't tracked => it is left for the periodic retry of pending requests.
+	Ignore,
+}
+
+/// State of the tracked request.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RequestState {
+	/// Session is running and must report its result before given time.
+	Running(Instant),
+	/// Waiting for retry at given time.
+	Waiting(Instant),
+	/// Retry is due, but session isn't yet started.
+	Due,
+}
+
+/// Tracked request.
+#[derive(Debug)]
+struct TrackedRequest {
+	/// Request task.
+	task: ServiceTask,
+	/// Number of started attempts.
+	attempts: usize,
+	/// Request state.
+	state: RequestState,
+}
+
+/// Queue of requests to retry.
+#[derive(Debug)]
+pub struct RetryQueue {
+	/// Max number of attempts to process request.
+	max_attempts: usize,
+	/// Delay before the first retry.
+	initial_backoff: Duration,
+	/// Max time to wait for the started session to report its result.
+	session_deadline: Duration,
+	/// Max number of tracked requests.
+	max_tracked_requests: usize,
+	/// Tracked requests.
+	requests: HashMap<RetryKey, TrackedRequest>,
+}
+
+impl RetryKey {
+	/// Get retry key of the task. Returns None if task is never retried.
+	pub fn from_task(task: &ServiceTask) -> Option<Self> {
+		match *task {
+			ServiceTask::GenerateServerKey(_, ref server_key_id, _, _) =>
+				Some(RetryKey::ServerKeyGeneration(server_key_id.clone())),
+			ServiceTask::RetrieveServerKey(_, ref server_key_id) =>
+				Some(RetryKey::ServerKeyRetrieval(server_key_id.clone())),
+			ServiceTask::RetrieveShadowDocumentKeyPersonal(_, ref server_key_id, ref requester) =>
+				Some(RetryKey::DocumentKeyShadowRetrieval(server_key_id.clone(), public_to_address(requester))),
+			_ => None,
+		}
+	}
+}
+
+impl TrackedRequest {
+	/// Is session of this request running?
+	fn is_running(&self) -> bool {
+		match self.state {
+			RequestState::Running(_) => true,
+			RequestState::Waiting(_) | RequestState::Due => false,
+		}
+	}
+}
+
+impl Default for RetryQueue {
+	fn default() -> Self {
+		RetryQueue::new(MAX_ATTEMPTS, INITIAL_BACKOFF, SESSION_DEADLINE, MAX_TRACKED_REQUESTS)
+	}
+}
+
+impl RetryQueue {
+	/// Create new retry queue.
+	pub fn new(max_attempts: usize, initial_backoff: Duration, session_deadline: Duration, max_tracked_requests: usize) -> Self {
+		RetryQueue {
+			max_attempts: max_attempts,
+			initial_backoff: initial_backoff,
+			session_deadline: session_deadline,
+			max_tracked_requests: max_tracked_requests,
+			requests: HashMap::new(),
+		}
+	}
+
+	/// Called before session is started. Returns false if the same request is already being
+	/// processed or is waiting for retry => session must not be started.
+	pub fn start(&mut self, key: RetryKey, task: ServiceTask, now: Instant) -> bool {
+		let is_full = self.requests.len() >= self.max_tracked_requests;
+		let deadline = now + self.session_deadline;
+		match self.requests.entry(key) {
+			Entry::Occupied(mut entry) => {
+				let request = entry.get_mut();
+				match request.state {
+					RequestState::Running(_) | RequestState::Waiting(_) => false,
+					RequestState::Due => {
+						request.state = RequestState::Running(deadline);
+						request.attempts += 1;
+						true
+					},
+				}
+			},
+			Entry::Vacant(entry) => {
+				if !is_full {
+					entry.insert(TrackedRequest {
+						task: task,
+						attempts: 1,
+						state: RequestState::Running(deadline),
+					});
+				}
+				true
+			},
+		}
+	}
+
+	/// Called when request has been completed, or its failure has been published.
+	pub fn remove(&mut self, key: &RetryKey) {
+		self.requests.remove(key);
+	}
+
+	/// Called when session has failed with non-fatal error.
+	pub fn on_failure(&mut self, key: &RetryKey, now: Instant) -> FailureAction {
+		let (max_attempts, initial_backoff) = (self.max_attempts, self.initial_backoff);
+		let is_exhausted = match self.requests.get_mut(key) {
+			None => return FailureAction::Ignore,
+			// concurrent session has failed, while retry is already scheduled
+			Some(ref request) if !request.is_running() => return FailureAction::Retry,
+			Some(ref request) if request.attempts >= max_attempts => true,
+			Some(request) => {
+				let backoff = initial_backoff * 2u32.pow(request.attempts as u32 - 1);
+				request.state = RequestState::Waiting(now + backoff);
+				false
+			},
+		};
+
+		match is_exhausted {
+			true => {
+				self.requests.remove(key);
+				FailureAction::Publish
+			},
+			false => FailureAction::Retry,
+		}
+	}
+
+	/// Take requests, which are due to be retried. Sessions, which haven't reported their result
+	/// before the deadline, are considered failed.
+	pub fn take_due(&mut self, now: Instant) -> Vec<ServiceTask> {
+		// stalled requests without attempts left are left for the periodic retry of pending requests
+		let max_attempts = self.max_attempts;
+		self.requests.retain(|_, request| match request.state {
+			RequestState::Running(deadline) => deadline > now || request.attempts < max_attempts,
+			_ => true,
+		});
+
+		self.requests.values_mut()
+			.filter_map(|request| match request.state {
+				RequestState::Waiting(retry_at) | RequestState::Running(retry_at) if retry_at > now => None,
+				RequestState::Waiting(_) | RequestState::Running(_) | RequestState::Due => {
+					request.state = RequestState::Due;
+					Some(request.task.clone())
+				},
+			})
+			.collect()
+	}
+
+	/// Is request tracked by the queue?
+	#[cfg(test)]
+	pub fn contains(&self, key: &RetryKey) -> bool {
+		self.requests.contains_key(key)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use listener::service_contract_listener::ServiceTask;
+	use super::{RetryQueue, RetryKey, FailureAction};
+
+	fn generation_task(server_key_id: u64) -> (RetryKey, ServiceTask) {
+		let task = ServiceTask::GenerateServerKey(Default::default(), server_key_id.into(), Default::default(), 1);
+		(RetryKey::from_task(&task).unwrap(), task)
+	}
+
+	#[test]
+	fn failed_request_is_retried_with_exponential_backoff() {
+		let now = Instant::now();
+		let backoff = Duration::from_secs(10);
+		let mut queue = RetryQueue::new(3, backoff, Duration::from_secs(60), 16);
+		let (key, task) = generation_task(1);
+
+		// first attempt fails => retry is scheduled in 10 seconds
+		assert!(queue.start(key.clone(), task.clone(), now));
+		assert_eq!(queue.on_failure(&key, now), FailureAction::Retry);
+		assert_eq!(queue.take_due(now + backoff - Duration::from_secs(1)), vec![]);
+		assert_eq!(queue.take_due(now + backoff), vec![task.clone()]);
+
+		// second attempt fails => retry is scheduled in 20 seconds
+		assert!(queue.start(key.clone(), task.clone(), now));
+		assert_eq!(queue.on_failure(&key, now), FailureAction::Retry);
+		assert_eq!(queue.take_due(now + backoff * 2 - Duration::from_secs(1)), vec![]);
+		assert_eq!(queue.take_due(now + backoff * 2), vec![task.clone()]);
+
+		// third attempt fails => failure must be published
+		assert!(queue.start(key.clone(), task.clone(), now));
+		assert_eq!(queue.on_failure(&key, now), FailureAction::Publish);
+		assert!(!queue.contains(&key));
+	}
+
+	#[test]
+	fn concurrent_requests_for_the_same_key_are_deduplicated() {
+		let now = Instant::now();
+		let mut queue = RetryQueue::new(3, Duration::from_secs(10), Duration::from_secs(60), 16);
+		let (key, task) = generation_task(1);
+
+		assert!(queue.start(key.clone(), task.clone(), now));
+		assert!(!queue.start(key.clone(), task.clone(), now));
+		assert_eq!(queue.on_failure(&key, now), FailureAction::Retry);
+		assert!(!queue.start(key.clone(), task.clone(), now));
+		assert_eq!(queue.on_failure(&key, now), FailureAction::Retry);
+
+		// only single retry is started
+		let due = queue.take_due(now + Duration::from_secs(10));
+		assert_eq!(due, vec![task.clone()]);
+		assert!(queue.start(key.clone(), task.clone(), now));
+		assert!(!queue.start(key.clone(), task.clone(), now));
+	}
+
+	#[test]
+	fn untracked_requests_are_ignored() {
+		let now = Instant::now();
+		let mut queue = RetryQueue::new(3, Duration::from_secs(10), Duration::from_secs(60), 1);
+		let (key1, task1) = generation_task(1);
+		let (key2, task2) = generation_task(2);
+
+		assert_eq!(queue.on_failure(&key1, now), FailureAction::Ignore);
+
+		// queue is full => second request isn't tracked
+		assert!(queue.start(key1.clone(), task1, now));
+		assert!(queue.start(key2.clone(), task2, now));
+		assert_eq!(queue.on_failure(&key2, now), FailureAction::Ignore);
+
+		// completed requests are removed
+		queue.remove(&key1);
+		assert!(!queue.contains(&key1));
+	}
+
+	#[test]
+	fn stalled_session_is_retried_after_deadline() {
+		let now = Instant::now();
+		let deadline = Duration::from_secs(60);
+		let mut queue = RetryQueue::new(2, Duration::from_secs(10), deadline, 16);
+		let (key, task) = generation_task(1);
+
+		// session never reports its result => request is retried after the deadline
+		assert!(queue.start(key.clone(), task.clone(), now));
+		assert_eq!(queue.take_due(now + deadline - Duration::from_secs(1)), vec![]);
+		assert_eq!(queue.take_due(now + deadline), vec![task.clone()]);
+
+		// retry session also stalls => request is released, because all attempts are exhausted
+		assert!(queue.start(key.clone(), task.clone(), now + deadline));
+		assert_eq!(queue.take_due(now + deadline * 2), vec![]);
+		assert!(!queue.contains(&key));
+	}
+}
@@ -29,7 +29,7 @@ use listener::ApiMask;
 use listener::service_contract_listener::ServiceTask;
 use trusted_client::TrustedClient;
 use helpers::{get_confirmed_block_hash, REQUEST_CONFIRMATIONS_REQUIRED};
-use {ServerKeyId, NodeKeyPair, ContractAddress};
+use {ServerKeyId, NodeKeyPair, ContractAddress, ServiceContractKind};
 
 use_contract!(service, "res/service.json");
 
@@ -65,12 +65,16 @@ lazy_static! {
 
 /// Service contract trait.
 pub trait ServiceContract: Send + Sync {
+	/// Current address of the contract. None if contract isn't installed or is an aggregate of several contracts.
+	fn address(&self) -> Option<Address>;
 	/// Update contract when new blocks are enacted. Returns true if contract is installed && up-to-date (i.e. chain is synced).
 	fn update(&self) -> bool;
 	/// Read recent contract logs. Returns topics of every entry.
 	fn read_logs(&self) -> Box<Iterator<Item=ServiceTask>>;
 	/// Publish generated key.
 	fn read_pending_requests(&self) -> Box<Iterator<Item=(bool, ServiceTask)>>;
+	/// Check if response from this key server to given request is still required.
+	fn is_response_required(&self, task: &ServiceTask) -> bool;
 	/// Publish generated server key.
 	fn publish_generated_server_key(&self, origin: &Address, server_key_id: &ServerKeyId, server_key: Public) -> Result<(), String>;
 	/// Publish server key generation error.
@@ -234,6 +238,10 @@ impl OnChainServiceContract {
 }
 
 impl ServiceContract for OnChainServiceContract {
+	fn address(&self) -> Option<Address> {
+		self.data.read().contract_address
+	}
+
 	fn update(&self) -> bool {
 		self.update_contract_address() && self.client.get().is_some()
 	}
@@ -353,6 +361,29 @@ impl ServiceContract for OnChainServiceContract {
 		}
 	}
 
+	fn is_response_required(&self, task: &ServiceTask) -> bool {
+		// when trusted client is offline, we can't check it => assume that response is required
+		let client = match self.client.get() {
+			Some(client) => client,
+			None => return true,
+		};
+
+		let self_address = public_to_address(self.self_key_pair.public());
+		match *task {
+			ServiceTask::GenerateServerKey(ref origin, ref server_key_id, _, _) =>
+				ServerKeyGenerationService::is_response_required(&*client, origin, server_key_id, &self_address),
+			ServiceTask::RetrieveServerKey(ref origin, ref server_key_id) =>
+				ServerKeyRetrievalService::is_response_required(&*client, origin, server_key_id, &self_address),
+			ServiceTask::StoreDocumentKey(ref origin, ref server_key_id, _, _, _) =>
+				DocumentKeyStoreService::is_response_required(&*client, origin, server_key_id, &self_address),
+			ServiceTask::RetrieveShadowDocumentKeyCommon(ref origin, ref server_key_id, ref requester) =>
+				DocumentKeyShadowRetrievalService::is_response_required(&*client, origin, server_key_id, requester, &self_address),
+			ServiceTask::RetrieveShadowDocumentKeyPersonal(ref origin, ref server_key_id, ref requester) =>
+				DocumentKeyShadowRetrievalService::is_response_required(&*client, origin, server_key_id, &public_to_address(requester), &self_address),
+			ServiceTask::Retry | ServiceTask::Shutdown => false,
+		}
+	}
+
 	fn publish_generated_server_key(&self, origin: &Address, server_key_id: &ServerKeyId, server_key: Public) -> Result<(), String> {
 		self.send_contract_transaction("publish_generated_server_key", origin, server_key_id, ServerKeyGenerationService::is_response_required,
 			|_, _| Ok(ServerKeyGenerationService::prepare_pubish_tx_data(server_key_id, &server_key)))
@@ -426,6 +457,17 @@ impl<F> Iterator for PendingRequestsIterator<F> where F: Fn(U256) -> Option<(boo
 	}
 }
 
+/// Returns name of the service contract of given kind in the registry.
+pub fn registry_name(kind: ServiceContractKind) -> &'static str {
+	match kind {
+		ServiceContractKind::General => SERVICE_CONTRACT_REGISTRY_NAME,
+		ServiceContractKind::ServerKeyGeneration => SRV_KEY_GEN_SERVICE_CONTRACT_REGISTRY_NAME,
+		ServiceContractKind::ServerKeyRetrieval => SRV_KEY_RETR_SERVICE_CONTRACT_REGISTRY_NAME,
+		ServiceContractKind::DocumentKeyStore => DOC_KEY_STORE_SERVICE_CONTRACT_REGISTRY_NAME,
+		ServiceContractKind::DocumentKeyShadowRetrieval => DOC_KEY_SRETR_SERVICE_CONTRACT_REGISTRY_NAME,
+	}
+}
+
 /// Returns vector of logs topics to listen to.
 pub fn mask_topics(mask: &ApiMask) -> Vec<H256> {
 	let mut topics = Vec::new();
@@ -754,9 +796,11 @@ pub mod tests {
 
 	#[derive(Default)]
 	pub struct DummyServiceContract {
+		pub address: Address,
 		pub is_actual: bool,
 		pub logs: Vec<ServiceTask>,
 		pub pending_requests: Vec<(bool, ServiceTask)>,
+		pub completed_requests: Vec<ServiceTask>,
 		pub generated_server_keys: Mutex<Vec<(ServerKeyId, Public)>>,
 		pub server_keys_generation_failures: Mutex<Vec<ServerKeyId>>,
 		pub retrieved_server_keys: Mutex<Vec<(ServerKeyId, Public, usize)>>,
@@ -769,6 +813,10 @@ pub mod tests {
 	}
 
 	impl ServiceContract for DummyServiceContract {
+		fn address(&self) -> Option<Address> {
+			Some(self.address)
+		}
+
 		fn update(&self) -> bool {
 			true
 		}
@@ -781,6 +829,10 @@ pub mod tests {
 			Box::new(self.pending_requests.clone().into_iter())
 		}
 
+		fn is_response_required(&self, task: &ServiceTask) -> bool {
+			!self.completed_requests.contains(task)
+		}
+
 		fn publish_generated_server_key(&self, _origin: &Address, server_key_id: &ServerKeyId, server_key: Public) -> Result<(), String> {
 			self.generated_server_keys.lock().push((server_key_id.clone(), server_key.clone()));
 			Ok(())
@@ -36,9 +36,20 @@ impl OnChainServiceContractAggregate {
 			contracts: contracts,
 		}
 	}
+
+	/// Get the contract, which has received the request.
+	fn contract(&self, origin: &Address) -> Result<&Arc<ServiceContract>, String> {
+		self.contracts.iter()
+			.find(|contract| contract.address().as_ref() == Some(origin))
+			.ok_or_else(|| format!("service contract {} is not installed", origin))
+	}
 }
 
 impl ServiceContract for OnChainServiceContractAggregate {
+	fn address(&self) -> Option<Address> {
+		None
+	}
+
 	fn update(&self) -> bool {
 		let mut result = false;
 		for contract in &self.contracts {
@@ -59,42 +70,57 @@ impl ServiceContract for OnChainServiceContractAggregate {
 				Box::new(i.chain(c.read_pending_requests())))
 	}
 
-	// in current implementation all publish methods are independent of actual contract adddress
-	// (tx is sent to origin) => we do not care which contract to use for publish data in methods below
+	fn is_response_required(&self, task: &ServiceTask) -> bool {
+		let origin = match *task {
+			ServiceTask::GenerateServerKey(ref origin, _, _, _) |
+				ServiceTask::RetrieveServerKey(ref origin, _) |
+				ServiceTask::StoreDocumentKey(ref origin, _, _, _, _) |
+				ServiceTask::RetrieveShadowDocumentKeyCommon(ref origin, _, _) |
+				ServiceTask::RetrieveShadowDocumentKeyPersonal(ref origin, _, _) => origin,
+			ServiceTask::Retry | ServiceTask::Shutdown => return false,
+		};
+
+		// response is only required by the contract, which has received the request
+		self.contract(origin)
+			.map(|contract| contract.is_response_required(task))
+			.unwrap_or(false)
+	}
+
+	// responses are published to the contract, which has received the request
 
 	fn publish_generated_server_key(&self, origin: &Address, server_key_id: &ServerKeyId, server_key: Public) -> Result<(), String> {
-		self.contracts[0].publish_generated_server_key(origin, server_key_id, server_key)
+		self.contract(origin)?.publish_generated_server_key(origin, server_key_id, server_key)
 	}
 
 	fn publish_server_key_generation_error(&self, origin: &Address, server_key_id: &ServerKeyId) -> Result<(), String> {
-		self.contracts[0].publish_server_key_generation_error(origin, server_key_id)
+		self.contract(origin)?.publish_server_key_generation_error(origin, server_key_id)
 	}
 
 	fn publish_retrieved_server_key(&self, origin: &Address, server_key_id: &ServerKeyId, server_key: Public, threshold: usize) -> Result<(), String> {
-		self.contracts[0].publish_retrieved_server_key(origin, server_key_id, server_key, threshold)
+		self.contract(origin)?.publish_retrieved_server_key(origin, server_key_id, server_key, threshold)
 	}
 
 	fn publish_server_key_retrieval_error(&self, origin: &Address, server_key_id: &ServerKeyId) -> Result<(), String> {
-		self.contracts[0].publish_server_key_retrieval_error(origin, server_key_id)
+		self.contract(origin)?.publish_server_key_retrieval_error(origin, server_key_id)
 	}
 
 	fn publish_stored_document_key(&self, origin: &Address, server_key_id: &ServerKeyId) -> Result<(), String> {
-		self.contracts[0].publish_stored_document_key(origin, server_key_id)
+		self.contract(origin)?.publish_stored_document_key(origin, server_key_id)
 	}
 
 	fn publish_document_key_store_error(&self, origin: &Address, server_key_id: &ServerKeyId) -> Result<(), String> {
-		self.contracts[0].publish_document_key_store_error(origin, server_key_id)
+		self.contract(origin)?.publish_document_key_store_error(origin, server_key_id)
 	}
 
 	fn publish_retrieved_document_key_common(&self, origin: &Address, server_key_id: &ServerKeyId, requester: &Address, common_point: Public, threshold: usize) -> Result<(), String> {
-		self.contracts[0].publish_retrieved_document_key_common(origin, server_key_id, requester, common_point, threshold)
+		self.contract(origin)?.publish_retrieved_document_key_common(origin, server_key_id, requester, common_point, threshold)
 	}
 
 	fn publish_retrieved_document_key_personal(&self, origin: &Address, server_key_id: &ServerKeyId, requester: &Address, participants: &[Address], decrypted_secret: Public, shadow: Bytes) -> Result<(), String> {
-		self.contracts[0].publish_retrieved_document_key_personal(origin, server_key_id, requester, participants, decrypted_secret, shadow)
+		self.contract(origin)?.publish_retrieved_document_key_personal(origin, server_key_id, requester, participants, decrypted_secret, shadow)
 	}
 
 	fn publish_document_key_retrieval_error(&self, origin: &Address, server_key_id: &ServerKeyId, requester: &Address) -> Result<(), String> {
-		self.contracts[0].publish_document_key_retrieval_error(origin, server_key_id, requester)
+		self.contract(origin)?.publish_document_key_retrieval_error(origin, server_key_id, requester)
 	}
 }
@@ -18,6 +18,7 @@ use std::collections::HashSet;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread;
+use std::time::Instant;
 use parking_lot::Mutex;
 use ethcore::client::{ChainNotify, NewBlocks};
 use ethkey::{Public, public_to_address};
@@ -33,6 +34,7 @@ use key_server_cluster::key_version_negotiation_session::{SessionImpl as KeyVers
 	IsolatedSessionTransport as KeyVersionNegotiationTransport, FailedContinueAction};
 use key_storage::KeyStorage;
 use acl_storage::AclStorage;
+use listener::retry_queue::{RetryQueue, RetryKey, FailureAction};
 use listener::service_contract::ServiceContract;
 use listener::tasks_queue::TasksQueue;
 use {ServerKeyId, NodeKeyPair, Error};
@@ -80,6 +82,8 @@ struct ServiceContractListenerData {
 	pub last_retry: AtomicUsize,
 	/// Retry-related data.
 	pub retry_data: Mutex<ServiceContractRetryData>,
+	/// Requests, which have failed with non-fatal errors and are waiting to be retried.
+	pub retry_queue: Mutex<RetryQueue>,
 	/// Service tasks queue.
 	pub tasks_queue: Arc<TasksQueue<ServiceTask>>,
 	/// Service contract.
@@ -131,6 +135,7 @@ impl ServiceContractListener {
 		let data = Arc::new(ServiceContractListenerData {
 			last_retry: AtomicUsize::new(0),
 			retry_data: Default::default(),
+			retry_queue: Default::default(),
 			tasks_queue: Arc::new(TasksQueue::new()),
 			contract: params.contract,
 			acl_storage: params.acl_storage,
@@ -202,6 +207,28 @@ impl ServiceContractListener {
 		}
 	}
 
+	/// Schedule retry of failed sessions, which are due.
+	fn retry_failed_sessions(&self, now: Instant) {
+		// shortcut: do not retry if we're isolated from the cluster
+		if self.data.key_server_set.is_isolated() {
+			return;
+		}
+
+		let due_tasks = self.data.retry_queue.lock().take_due(now);
+		for task in due_tasks {
+			// do not retry requests, which have been already responded by other key servers
+			if !self.data.contract.is_response_required(&task) {
+				if let Some(retry_key) = RetryKey::from_task(&task) {
+					self.data.retry_queue.lock().remove(&retry_key);
+				}
+				continue;
+			}
+
+			trace!(target: "secretstore", "{}: retrying failed {} request", self.data.self_key_pair.public(), task);
+			self.data.tasks_queue.push(task);
+		}
+	}
+
 	/// Service thread procedure.
 	fn run_service_thread(data: Arc<ServiceContractListenerData>) {
 		loop {
@@ -310,18 +337,26 @@ impl ServiceContractListener {
 
 	/// Generate server key (start generation session).
 	fn generate_server_key(data: &Arc<ServiceContractListenerData>, origin: Address, server_key_id: &ServerKeyId, author: Address, threshold: usize) -> Result<(), String> {
+		// do not start session if the same request is already being processed or is waiting for retry
+		let task = ServiceTask::GenerateServerKey(origin, server_key_id.clone(), author, threshold);
+		if !data.retry_queue.lock().start(RetryKey::ServerKeyGeneration(server_key_id.clone()), task, Instant::now()) {
+			return Ok(());
+		}
+
 		Self::process_server_key_generation_result(data, origin, server_key_id, data.cluster.new_generation_session(
 			server_key_id.clone(), Some(origin), author, threshold).map(|_| None).map_err(Into::into))
 	}
 
 	/// Process server key generation result.
 	fn process_server_key_generation_result(data: &Arc<ServiceContractListenerData>, origin: Address, server_key_id: &ServerKeyId, result: Result<Option<Public>, Error>) -> Result<(), String> {
+		let retry_key = RetryKey::ServerKeyGeneration(server_key_id.clone());
 		match result {
 			Ok(None) => Ok(()),
 			Ok(Some(server_key)) => {
+				data.retry_queue.lock().remove(&retry_key);
 				data.contract.publish_generated_server_key(&origin, server_key_id, server_key)
 			},
-			Err(ref error) if error.is_non_fatal() => Err(format!("{}", error)),
+			Err(ref error) if !Self::is_final_failure(data, &retry_key, error) => Err(format!("{}", error)),
 			Err(ref error) => {
 				// ignore error as we're already processing an error
 				let _ = data.contract.publish_server_key_generation_error(&origin, server_key_id)
@@ -334,14 +369,26 @@ impl ServiceContractListener {
 
 	/// Retrieve server key.
 	fn retrieve_server_key(data: &Arc<ServiceContractListenerData>, origin: Address, server_key_id: &ServerKeyId) -> Result<(), String> {
-		match data.key_storage.get(server_key_id) {
+		// do not retrieve key if the same request is waiting for retry
+		let task = ServiceTask::RetrieveServerKey(origin, server_key_id.clone());
+		let retry_key = RetryKey::ServerKeyRetrieval(server_key_id.clone());
+		if !data.retry_queue.lock().start(retry_key.clone(), task, Instant::now()) {
+			return Ok(());
+		}
+
+		let retrieval_result = data.key_storage.get(server_key_id);
+		if retrieval_result.is_ok() {
+			data.retry_queue.lock().remove(&retry_key);
+		}
+
+		match retrieval_result {
 			Ok(Some(server_key_share)) => {
 				data.contract.publish_retrieved_server_key(&origin, server_key_id, server_key_share.public, server_key_share.threshold)
 			},
 			Ok(None) => {
 				data.contract.publish_server_key_retrieval_error(&origin, server_key_id)
 			}
-			Err(ref error) if error.is_non_fatal() => Err(format!("{}", error)),
+			Err(ref error) if !Self::is_final_failure(data, &retry_key, error) => Err(format!("{}", error)),
 			Err(ref error) => {
 				// ignore error as we're already processing an error
 				let _ = data.contract.publish_server_key_retrieval_error(&origin, server_key_id)
@@ -400,18 +447,26 @@ impl ServiceContractListener {
 
 	/// Retrieve personal part of document key (start decryption session).
 	fn retrieve_document_key_personal(data: &Arc<ServiceContractListenerData>, origin: Address, server_key_id: &ServerKeyId, requester: Public) -> Result<(), String> {
+		// do not start session if the same request is already being processed or is waiting for retry
+		let task = ServiceTask::RetrieveShadowDocumentKeyPersonal(origin, server_key_id.clone(), requester.clone());
+		if !data.retry_queue.lock().start(RetryKey::DocumentKeyShadowRetrieval(server_key_id.clone(), public_to_address(&requester)), task, Instant::now()) {
+			return Ok(());
+		}
+
 		Self::process_document_key_retrieval_result(data, origin, server_key_id, &public_to_address(&requester), data.cluster.new_decryption_session(
 			server_key_id.clone(), Some(origin), requester.clone().into(), None, true, true).map(|_| None).map_err(Into::into))
 	}
 
 	/// Process document key retrieval result.
 	fn process_document_key_retrieval_result(data: &Arc<ServiceContractListenerData>, origin: Address, server_key_id: &ServerKeyId, requester: &Address, result: Result<Option<(Vec<Address>, Public, Bytes)>, Error>) -> Result<(), String> {
+		let retry_key = RetryKey::DocumentKeyShadowRetrieval(server_key_id.clone(), requester.clone());
 		match result {
 			Ok(None) => Ok(()),
 			Ok(Some((participants, decrypted_secret, shadow))) => {
+				data.retry_queue.lock().remove(&retry_key);
 				data.contract.publish_retrieved_document_key_personal(&origin, server_key_id, &requester, &participants, decrypted_secret, shadow)
 			},
-			Err(ref error) if error.is_non_fatal() => Err(format!("{}", error)),
+			Err(ref error) if !Self::is_final_failure(data, &retry_key, error) => Err(format!("{}", error)),
 			Err(ref error) => {
 				// ignore error as we're already processing an error
 				let _ = data.contract.publish_document_key_retrieval_error(&origin, server_key_id, &requester)
@@ -421,6 +476,32 @@ impl ServiceContractListener {
 			}
 		}
 	}
+
+	/// Process session failure. Returns true if the failure is final and must be published.
+	fn is_final_failure(data: &Arc<ServiceContractListenerData>, retry_key: &RetryKey, error: &Error) -> bool {
+		let mut retry_queue = data.retry_queue.lock();
+
+		// fatal errors (like access denied) are never retried
+		if !error.is_non_fatal() {
+			retry_queue.remove(retry_key);
+			return true;
+		}
+
+		match retry_queue.on_failure(retry_key, Instant::now()) {
+			FailureAction::Retry => {
+				trace!(target: "secretstore", "{}: scheduled retry of failed {:?} request",
+					data.self_key_pair.public(), retry_key);
+				false
+			},
+			FailureAction::Publish => {
+				warn!(target: "secretstore", "{}: all attempts to process {:?} request have failed",
+					data.self_key_pair.public(), retry_key);
+				true
+			},
+			// the request will be retried by the periodic retry of pending requests
+			FailureAction::Ignore => false,
+		}
+	}
 }
 
 impl Drop for ServiceContractListener {
@@ -446,6 +527,7 @@ impl ChainNotify for ServiceContractListener {
 		}
 
 		self.process_service_contract_events();
+		self.retry_failed_sessions(Instant::now());
 
 		// schedule retry if received enough blocks since last retry
 		// it maybe inaccurate when switching syncing/synced states, but that's ok
@@ -586,16 +668,19 @@ fn is_processed_by_this_key_server(key_server_set: &KeyServerSet, node: &NodeId,
 mod tests {
 	use std::sync::Arc;
 	use std::sync::atomic::Ordering;
-	use ethkey::{Random, Generator, KeyPair};
+	use std::time::{Duration, Instant};
+	use ethkey::{Random, Generator, KeyPair, public_to_address};
+	use listener::retry_queue::{RetryKey, MAX_ATTEMPTS};
 	use listener::service_contract::ServiceContract;
 	use listener::service_contract::tests::DummyServiceContract;
-	use key_server_cluster::DummyClusterClient;
+	use listener::service_contract_aggregate::OnChainServiceContractAggregate;
+	use key_server_cluster::{DummyClusterClient, MessageLoop, make_clusters};
 	use acl_storage::{AclStorage, DummyAclStorage};
 	use key_storage::{KeyStorage, DocumentKeyShare};
 	use key_storage::tests::DummyKeyStorage;
 	use key_server_set::KeyServerSet;
 	use key_server_set::tests::MapKeyServerSet;
-	use {NodeKeyPair, PlainNodeKeyPair, ServerKeyId};
+	use {NodeKeyPair, PlainNodeKeyPair, ServerKeyId, Error};
 	use super::{ServiceTask, ServiceContractListener, ServiceContractListenerParams, is_processed_by_this_key_server};
 
 	fn create_non_empty_key_storage(has_doc_key: bool) -> Arc<DummyKeyStorage> {
@@ -1055,4 +1140,166 @@ mod tests {
 			Default::default(), Default::default(), Default::default())).unwrap_err();
 		assert_eq!(*contract.document_keys_shadow_retrieval_failures.lock(), vec![(Default::default(), Default::default())]);
 	}
+
+	// multiple service contracts tests
+
+	#[test]
+	fn responses_are_published_to_contracts_which_have_received_requests() {
+		// server key retrieval contract and the staging contract, serving all requests
+		let srv_retr = Arc::new(DummyServiceContract {
+			address: 1.into(),
+			logs: vec![ServiceTask::RetrieveServerKey(1.into(), Default::default())],
+			..Default::default()
+		});
+		let staging = Arc::new(DummyServiceContract {
+			address: 2.into(),
+			logs: vec![
+				ServiceTask::RetrieveShadowDocumentKeyCommon(2.into(), Default::default(), Default::default()),
+				ServiceTask::RetrieveServerKey(2.into(), Default::default()),
+			],
+			..Default::default()
+		});
+		let contract = Arc::new(OnChainServiceContractAggregate::new(vec![
+			srv_retr.clone() as Arc<ServiceContract>, staging.clone() as Arc<ServiceContract>]));
+		let key_storage = create_non_empty_key_storage(true);
+		let listener = make_service_contract_listener(Some(contract), None, Some(key_storage), None, None);
+
+		listener.process_service_contract_events();
+		let tasks = listener.data.tasks_queue.snapshot();
+		assert_eq!(tasks, vec![
+			ServiceTask::RetrieveServerKey(1.into(), Default::default()),
+			ServiceTask::RetrieveShadowDocumentKeyCommon(2.into(), Default::default(), Default::default()),
+			ServiceTask::RetrieveServerKey(2.into(), Default::default()),
+		].into_iter().collect::<::std::collections::VecDeque<_>>());
+
+		for task in tasks {
+			ServiceContractListener::process_service_task(&listener.data, task).unwrap();
+		}
+
+		let server_key = KeyPair::from_secret("0000000000000000000000000000000000000000000000000000000000000001"
+			.parse().unwrap()).unwrap().public().clone();
+		assert_eq!(*srv_retr.retrieved_server_keys.lock(), vec![(Default::default(), server_key.clone(), 0)]);
+		assert!(srv_retr.common_shadow_retrieved_document_keys.lock().is_empty());
+		assert_eq!(*staging.retrieved_server_keys.lock(), vec![(Default::default(), server_key, 0)]);
+		assert_eq!(*staging.common_shadow_retrieved_document_keys.lock(), vec![(Default::default(), Default::default(),
+			Default::default(), 0)]);
+	}
+
+	#[test]
+	fn response_is_not_published_when_requesting_contract_is_not_installed() {
+		let first = Arc::new(DummyServiceContract { address: 1.into(), ..Default::default() });
+		let second = Arc::new(DummyServiceContract { address: 2.into(), ..Default::default() });
+		let contract = Arc::new(OnChainServiceContractAggregate::new(vec![
+			first.clone() as Arc<ServiceContract>, second.clone() as Arc<ServiceContract>]));
+		let key_storage = create_non_empty_key_storage(false);
+		let listener = make_service_contract_listener(Some(contract), None, Some(key_storage), None, None);
+
+		ServiceContractListener::process_service_task(&listener.data, ServiceTask::RetrieveServerKey(
+			3.into(), Default::default())).unwrap_err();
+		assert!(first.retrieved_server_keys.lock().is_empty());
+		assert!(second.retrieved_server_keys.lock().is_empty());
+	}
+
+	// failed sessions retry tests
+
+	fn make_cluster_service_contract_listener(ml: &MessageLoop, contract: Arc<ServiceContract>) -> Arc<ServiceContractListener> {
+		ServiceContractListener::new(ServiceContractListenerParams {
+			contract: contract,
+			self_key_pair: ml.node_key_pair(0).clone(),
+			key_server_set: Arc::new(MapKeyServerSet::new(false, ml.nodes().into_iter()
+				.map(|node| (node, "127.0.0.1:8080".parse().unwrap()))
+				.collect())),
+			acl_storage: ml.acl_storage(0).clone(),
+			cluster: ml.cluster(0).client(),
+			key_storage: ml.key_storage(0).clone(),
+		}).unwrap()
+	}
+
+	#[test]
+	fn server_key_generation_is_retried_when_node_was_unreachable_on_first_attempt() {
+		let ml = make_clusters(3);
+		let contract = Arc::new(DummyServiceContract::default());
+		let listener = make_cluster_service_contract_listener(&ml, contract.clone());
+		let task = ServiceTask::GenerateServerKey(Default::default(), Default::default(), Default::default(), 1);
+		let retry_key = RetryKey::from_task(&task).unwrap();
+
+		// first attempt fails, because one of nodes is unreachable => failure isn't published
+		ml.disconnect(2);
+		ServiceContractListener::process_service_task(&listener.data, task.clone()).unwrap_err();
+		assert!(contract.server_keys_generation_failures.lock().is_empty());
+		assert!(listener.data.retry_queue.lock().contains(&retry_key));
+
+		// the same request isn't processed until retry is due
+		ServiceContractListener::process_service_task(&listener.data, task.clone()).unwrap();
+		listener.retry_failed_sessions(Instant::now());
+		assert_eq!(listener.data.tasks_queue.snapshot().len(), 0);
+
+		// node is reachable again => retry succeeds && generated key is published
+		ml.reconnect(2);
+		listener.retry_failed_sessions(Instant::now() + Duration::from_secs(3600));
+		let retried_task = listener.data.tasks_queue.wait();
+		assert_eq!(retried_task, task);
+		ServiceContractListener::process_service_task(&listener.data, retried_task).unwrap();
+		ml.loop_until(|| !contract.generated_server_keys.lock().is_empty());
+
+		let server_key = ml.key_storage(0).get(&Default::default()).unwrap().unwrap().public;
+		assert_eq!(*contract.generated_server_keys.lock(), vec![(Default::default(), server_key)]);
+		assert!(contract.server_keys_generation_failures.lock().is_empty());
+		assert!(!listener.data.retry_queue.lock().contains(&retry_key));
+	}
+
+	#[test]
+	fn server_key_generation_failure_is_published_when_all_attempts_have_failed() {
+		let ml = make_clusters(3);
+		let contract = Arc::new(DummyServiceContract::default());
+		let listener = make_cluster_service_contract_listener(&ml, contract.clone());
+		let task = ServiceTask::GenerateServerKey(Default::default(), Default::default(), Default::default(), 1);
+
+		ml.disconnect(2);
+		ServiceContractListener::process_service_task(&listener.data, task).unwrap_err();
+		for _ in 1..MAX_ATTEMPTS {
+			assert!(contract.server_keys_generation_failures.lock().is_empty());
+			listener.retry_failed_sessions(Instant::now() + Duration::from_secs(3600));
+			let retried_task = listener.data.tasks_queue.wait();
+			ServiceContractListener::process_service_task(&listener.data, retried_task).unwrap_err();
+		}
+
+		assert_eq!(*contract.server_keys_generation_failures.lock(), vec![Default::default()]);
+		assert_eq!(listener.data.tasks_queue.snapshot().len(), 0);
+	}
+
+	#[test]
+	fn server_key_generation_is_not_retried_when_response_is_not_required() {
+		let ml = make_clusters(3);
+		let task = ServiceTask::GenerateServerKey(Default::default(), Default::default(), Default::default(), 1);
+		let contract = Arc::new(DummyServiceContract {
+			completed_requests: vec![task.clone()],
+			..Default::default()
+		});
+		let listener = make_cluster_service_contract_listener(&ml, contract.clone());
+		let retry_key = RetryKey::from_task(&task).unwrap();
+
+		ml.disconnect(2);
+		ServiceContractListener::process_service_task(&listener.data, task).unwrap_err();
+		listener.retry_failed_sessions(Instant::now() + Duration::from_secs(3600));
+		assert_eq!(listener.data.tasks_queue.snapshot().len(), 0);
+		assert!(!listener.data.retry_queue.lock().contains(&retry_key));
+		assert!(contract.server_keys_generation_failures.lock().is_empty());
+	}
+
+	#[test]
+	fn document_key_retrieval_is_not_retried_when_access_is_denied() {
+		let contract = Arc::new(DummyServiceContract::default());
+		let listener = make_service_contract_listener(Some(contract.clone()), None, None, None, None);
+		let requester = Random.generate().unwrap().public().clone();
+		let task = ServiceTask::RetrieveShadowDocumentKeyPersonal(Default::default(), Default::default(), requester);
+		let retry_key = RetryKey::from_task(&task).unwrap();
+
+		assert!(listener.data.retry_queue.lock().start(retry_key.clone(), task, Instant::now()));
+		ServiceContractListener::process_document_key_retrieval_result(&listener.data, Default::default(),
+			&Default::default(), &public_to_address(&requester), Err(Error::AccessDenied)).unwrap_err();
+		assert_eq!(*contract.document_keys_shadow_retrieval_failures.lock(),
+			vec![(Default::default(), public_to_address(&requester))]);
+		assert!(!listener.data.retry_queue.lock().contains(&retry_key));
+	}
 }
@@ -15,6 +15,7 @@
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
 use ethkey::crypto::ecdh::agree;
+use ethkey::crypto::ecies;
 use ethkey::{KeyPair, Public, Signature, Error as EthKeyError, sign, public_to_address};
 use ethereum_types::{H256, Address};
 use traits::NodeKeyPair;
@@ -54,6 +55,11 @@ impl NodeKeyPair for PlainNodeKeyPair {
 			.map_err(|e| EthKeyError::Custom(e.to_string()))
 			.and_then(KeyPair::from_secret)
 	}
+
+	fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, EthKeyError> {
+		ecies::decrypt(self.key_pair.secret(), &[], data)
+			.map_err(|e| EthKeyError::Custom(e.to_string()))
+	}
 }
 
 #[cfg(feature = "accounts")]
@@ -100,6 +106,11 @@ mod accounts {
 			KeyPair::from_secret(self.account_provider.agree(self.address.clone(), Some(self.password.clone()), peer_public)
 				.map_err(|e| EthKeyError::Custom(format!("{}", e)))?)
 		}
+
+		fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, EthKeyError> {
+			self.account_provider.decrypt(self.address.clone(), Some(self.password.clone()), &[], data)
+				.map_err(|e| EthKeyError::Custom(format!("{}", e)))
+		}
 	}
 }
 
@@ -22,7 +22,7 @@ use serde::de::{Visitor, Error as SerdeError};
 use ethkey::{Public, Secret, Signature};
 use ethereum_types::{H160, H256};
 use bytes::Bytes;
-use types::Requester;
+use types::{Requester, EncryptedDocumentKeyShadow, KeyServerStatus, SessionsStatus};
 
 macro_rules! impl_bytes_deserialize {
 	($name: ident, $value: expr, true) => {
@@ -125,6 +125,17 @@ pub struct SerializableEncryptedDocumentKeyShadow {
 	pub decrypt_shadows: Vec<SerializableBytes>,
 }
 
+/// Serializable result of single shadow decryption from the batch.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SerializableEncryptedDocumentKeyShadowResult {
+	/// Shadow decryption has completed successfully.
+	#[serde(rename = "shadow")]
+	Shadow(SerializableEncryptedDocumentKeyShadow),
+	/// Shadow decryption has failed.
+	#[serde(rename = "error")]
+	Error(String),
+}
+
 /// Serializable requester identification data.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SerializableRequester {
@@ -156,6 +167,68 @@ impl From<Requester> for SerializableRequester {
 	}
 }
 
+/// Serializable key server status.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableKeyServerStatus {
+	/// Key servers from the current servers set.
+	pub configured_nodes: Vec<SerializablePublic>,
+	/// Key servers, which this node is currently connected to.
+	pub connected_nodes: Vec<SerializablePublic>,
+	/// Hash of the current servers set.
+	pub servers_set_hash: SerializableH256,
+	/// Statistics of sessions, by session type.
+	pub sessions: Vec<SerializableSessionsStatus>,
+}
+
+/// Serializable statistics of sessions of single type.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SerializableSessionsStatus {
+	/// Session type name.
+	pub session_type: String,
+	/// Number of currently active sessions.
+	pub active: usize,
+	/// Number of sessions, which have completed successfully within the statistics window.
+	pub completed: usize,
+	/// Number of sessions, which have failed within the statistics window.
+	pub failed: usize,
+	/// Average duration (in milliseconds) of sessions, which have finished within the statistics window.
+	pub average_duration_ms: Option<u64>,
+}
+
+impl From<EncryptedDocumentKeyShadow> for SerializableEncryptedDocumentKeyShadow {
+	fn from(shadow: EncryptedDocumentKeyShadow) -> SerializableEncryptedDocumentKeyShadow {
+		SerializableEncryptedDocumentKeyShadow {
+			decrypted_secret: shadow.decrypted_secret.into(),
+			common_point: shadow.common_point.expect("always filled when requesting document_key_shadow; qed").into(),
+			decrypt_shadows: shadow.decrypt_shadows.expect("always filled when requesting document_key_shadow; qed").into_iter().map(Into::into).collect()
+		}
+	}
+}
+
+impl From<KeyServerStatus> for SerializableKeyServerStatus {
+	fn from(status: KeyServerStatus) -> SerializableKeyServerStatus {
+		SerializableKeyServerStatus {
+			configured_nodes: status.configured_nodes.into_iter().map(Into::into).collect(),
+			connected_nodes: status.connected_nodes.into_iter().map(Into::into).collect(),
+			servers_set_hash: status.servers_set_hash.into(),
+			sessions: status.sessions.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+impl From<SessionsStatus> for SerializableSessionsStatus {
+	fn from(status: SessionsStatus) -> SerializableSessionsStatus {
+		SerializableSessionsStatus {
+			session_type: status.session_type,
+			active: status.active,
+			completed: status.completed,
+			failed: status.failed,
+			average_duration_ms: status.average_duration
+				.map(|duration| duration.as_secs() * 1000 + duration.subsec_millis() as u64),
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
@@ -18,7 +18,7 @@ use std::collections::BTreeSet;
 use ethkey::{KeyPair, Signature, Error as EthKeyError};
 use ethereum_types::{H256, Address};
 use types::{Error, Public, ServerKeyId, MessageHash, EncryptedMessageSignature, RequestSignature, Requester,
-	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId};
+	EncryptedDocumentKey, EncryptedDocumentKeyShadow, NodeId, AdminSessionStatus, KeyServerStatus};
 
 /// Node key pair.
 pub trait NodeKeyPair: Send + Sync {
@@ -30,6 +30,8 @@ pub trait NodeKeyPair: Send + Sync {
 	fn sign(&self, data: &H256) -> Result<Signature, EthKeyError>;
 	/// Compute shared key to encrypt channel between two nodes.
 	fn compute_shared_key(&self, peer_public: &Public) -> Result<KeyPair, EthKeyError>;
+	/// Decrypt data, encrypted with node public key.
+	fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, EthKeyError>;
 }
 
 /// Server key (SK) generator.
@@ -72,6 +74,12 @@ pub trait DocumentKeyServer: ServerKeyGenerator {
 	/// 4) calculate decrypted_secret: result.decrypted_secret + decrypt_shadow_point
 	/// Result is a DK shadow.
 	fn restore_document_key_shadow(&self, key_id: &ServerKeyId, requester: &Requester) -> Result<EncryptedDocumentKeyShadow, Error>;
+	/// Restore shadows of multiple previously stored DKs (see `restore_document_key_shadow`).
+	/// `key_ids` are identifiers of previously generated SKs.
+	/// `requester` is the one who requests access to document keys. Signature must be made over `document_keys_batch_hash(key_ids)`.
+	/// Result is a DK shadow or an error for every key, in the same order as `key_ids`. Failure to restore
+	/// single DK shadow doesn't fail the whole batch.
+	fn restore_document_keys_shadows(&self, key_ids: &[ServerKeyId], requester: &Requester) -> Result<Vec<Result<EncryptedDocumentKeyShadow, Error>>, Error>;
 }
 
 /// Message signer.
@@ -98,6 +106,20 @@ pub trait AdminSessionsServer {
 	/// WARNING: newly generated keys will be distributed among all cluster nodes. So this session
 	/// must be followed with cluster nodes change (either via contract, or config files).
 	fn change_servers_set(&self, old_set_signature: RequestSignature, new_set_signature: RequestSignature, new_servers_set: BTreeSet<NodeId>) -> Result<(), Error>;
+	/// Start servers set change session (see `change_servers_set`) without waiting for its completion.
+	/// Returns id of the started session.
+	fn start_servers_set_change(&self, old_set_signature: RequestSignature, new_set_signature: RequestSignature, new_servers_set: BTreeSet<NodeId>) -> Result<H256, Error>;
+	/// Re-share existing key among all cluster nodes, so that any new_threshold + 1 of them are able to restore it.
+	/// Signature is the signature of Hash(key_id, new_threshold, ordered set of all cluster nodes), made with administrator key.
+	/// Old key shares are kept until all nodes have computed their new shares.
+	fn change_key_threshold(&self, key_id: &ServerKeyId, new_threshold: usize, signature: RequestSignature) -> Result<(), Error>;
+	/// Get status of administrative sessions, which are currently active on this node.
+	fn admin_sessions(&self) -> Result<Vec<AdminSessionStatus>, Error>;
+	/// Abort active administrative session. Other session participants are notified about the abort.
+	/// Signature is the signature of session id, made with administrator key.
+	fn abort_admin_session(&self, session_id: &H256, signature: RequestSignature) -> Result<(), Error>;
+	/// Get status of this key server: connections to other key servers && sessions statistics.
+	fn status(&self) -> Result<KeyServerStatus, Error>;
 }
 
 /// Key server.
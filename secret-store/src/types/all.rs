@@ -14,7 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
 
 use {ethkey, bytes, ethereum_types};
 
@@ -51,27 +52,69 @@ pub enum ContractAddress {
 	Address(ethkey::Address),
 }
 
+/// Kind of requests, served by the service contract.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServiceContractKind {
+	/// General contract, serving all kinds of requests.
+	General,
+	/// Server key generation requests.
+	ServerKeyGeneration,
+	/// Server key retrieval requests.
+	ServerKeyRetrieval,
+	/// Document key store requests.
+	DocumentKeyStore,
+	/// Document key shadow retrieval requests.
+	DocumentKeyShadowRetrieval,
+}
+
+/// Mode of servers set change sessions, which are started by the node holding administrator key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AdminMigrationMode {
+	/// Servers set change sessions are only started manually.
+	Disabled,
+	/// Servers set change session, required after servers set change, is only logged.
+	DryRun,
+	/// Servers set change session is started automatically after servers set change.
+	Enabled,
+}
+
+/// Service contract configuration.
+#[derive(Debug, Clone)]
+pub struct ServiceContractConfiguration {
+	/// Kind of requests, served by the contract.
+	pub kind: ServiceContractKind,
+	/// Contract address. When read from registry, the registry name of the contract kind is used.
+	pub address: ContractAddress,
+}
+
 /// Secret store configuration
 #[derive(Debug)]
 pub struct ServiceConfiguration {
 	/// HTTP listener address. If None, HTTP API is disabled.
 	pub listener_address: Option<NodeAddress>,
-	/// Service contract address.
-	pub service_contract_address: Option<ContractAddress>,
-	/// Server key generation service contract address.
-	pub service_contract_srv_gen_address: Option<ContractAddress>,
-	/// Server key retrieval service contract address.
-	pub service_contract_srv_retr_address: Option<ContractAddress>,
-	/// Document key store service contract address.
-	pub service_contract_doc_store_address: Option<ContractAddress>,
-	/// Document key shadow retrieval service contract address.
-	pub service_contract_doc_sretr_address: Option<ContractAddress>,
+	/// Service contracts to listen to.
+	pub service_contracts: Vec<ServiceContractConfiguration>,
 	/// ACL check contract address. If None, everyone has access to all keys. Useful for tests only.
 	pub acl_check_contract_address: Option<ContractAddress>,
+	/// Lifetime of cached ACL checks results. Denied checks are cached for a tenth of this time.
+	pub acl_check_cache_ttl: Duration,
 	/// Cluster configuration.
 	pub cluster_config: ClusterConfiguration,
 	// Allowed CORS domains
 	pub cors: Option<Vec<String>>,
+	/// HTTP listener TLS configuration. If None, HTTP API is served over plain HTTP.
+	pub http_tls: Option<HttpTlsConfiguration>,
+}
+
+/// HTTP listener TLS configuration.
+#[derive(Debug, Clone)]
+pub struct HttpTlsConfiguration {
+	/// Path to PEM-encoded certificate chain of the listener.
+	pub certificate_path: String,
+	/// Path to PEM-encoded private key of the listener.
+	pub private_key_path: String,
+	/// Path to PEM-encoded CA certificates. If set, clients are required to present a certificate signed by one of these.
+	pub client_ca_path: Option<String>,
 }
 
 /// Key server cluster configuration
@@ -91,6 +134,11 @@ pub struct ClusterConfiguration {
 	/// Should key servers set change session should be started when servers set changes.
 	/// This will only work when servers set is configured using KeyServerSet contract.
 	pub auto_migrate_enabled: bool,
+	/// Should servers set change session be started by this node when servers set changes.
+	/// This will only work when this node holds the administrator key.
+	pub admin_migration_mode: AdminMigrationMode,
+	/// Maximal number of sessions, which are running concurrently to serve single batch request.
+	pub batch_sessions_parallelism: usize,
 }
 
 /// Shadow decryption result.
@@ -104,6 +152,52 @@ pub struct EncryptedDocumentKeyShadow {
 	pub decrypt_shadows: Option<Vec<Vec<u8>>>,
 }
 
+/// Status of running administrative session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdminSessionStatus {
+	/// Session id.
+	pub id: ethereum_types::H256,
+	/// Session type name.
+	pub session_type: String,
+	/// Node which has started the session.
+	pub master: NodeId,
+	/// Is session finished.
+	pub is_finished: bool,
+	/// Number of keys to process by servers set change session. Known on master node only,
+	/// once all nodes have agreed to start the session.
+	pub keys_total: Option<usize>,
+	/// Number of keys processed by servers set change session. Known on master node only.
+	pub keys_processed: Option<usize>,
+}
+
+/// Status of the key server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyServerStatus {
+	/// Key servers from the current servers set.
+	pub configured_nodes: BTreeSet<NodeId>,
+	/// Key servers, which this node is currently connected to.
+	pub connected_nodes: BTreeSet<NodeId>,
+	/// Hash of the current servers set.
+	pub servers_set_hash: ethereum_types::H256,
+	/// Statistics of sessions, by session type.
+	pub sessions: Vec<SessionsStatus>,
+}
+
+/// Statistics of sessions of single type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionsStatus {
+	/// Session type name.
+	pub session_type: String,
+	/// Number of currently active sessions.
+	pub active: usize,
+	/// Number of sessions, which have completed successfully within the statistics window.
+	pub completed: usize,
+	/// Number of sessions, which have failed within the statistics window.
+	pub failed: usize,
+	/// Average duration of sessions, which have finished within the statistics window.
+	pub average_duration: Option<Duration>,
+}
+
 /// Requester identification data.
 #[derive(Debug, Clone)]
 pub enum Requester {
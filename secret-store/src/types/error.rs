@@ -74,6 +74,8 @@ pub enum Error {
 	ExclusiveSessionActive,
 	/// Can't start exclusive session, because there are other active sessions.
 	HasActiveSessions,
+	/// Session has been aborted by administrator.
+	SessionAborted,
 	/// Insufficient requester data.
 	InsufficientRequesterData(String),
 	/// Cryptographic error.
@@ -84,6 +86,8 @@ pub enum Error {
 	Serde(String),
 	/// Hyper error.
 	Hyper(String),
+	/// TLS configuration error.
+	Tls(String),
 	/// Database-related error.
 	Database(String),
 	/// Internal error.
@@ -120,9 +124,11 @@ impl Error {
 				Error::DocumentKeyAlreadyStored | Error::DocumentKeyIsNotFound | Error::InsufficientRequesterData(_) |
 			// access denied/consensus error
 			Error::AccessDenied | Error::ConsensusUnreachable |
+			// session has been aborted on purpose
+			Error::SessionAborted |
 			// indeterminate internal errors, which could be either fatal (db failure, invalid request), or not (network error),
 			// but we still consider these errors as fatal
-			Error::EthKey(_) | Error::Serde(_) | Error::Hyper(_) | Error::Database(_) | Error::Internal(_) | Error::Io(_) => false,
+			Error::EthKey(_) | Error::Serde(_) | Error::Hyper(_) | Error::Tls(_) | Error::Database(_) | Error::Internal(_) | Error::Io(_) => false,
 		}
 	}
 }
@@ -151,9 +157,11 @@ impl fmt::Display for Error {
 			Error::AccessDenied => write!(f, "Access denied"),
 			Error::ExclusiveSessionActive => write!(f, "Exclusive session active"),
 			Error::HasActiveSessions => write!(f, "Unable to start exclusive session"),
+			Error::SessionAborted => write!(f, "Session has been aborted by administrator"),
 			Error::InsufficientRequesterData(ref e) => write!(f, "Insufficient requester data: {}", e),
 			Error::EthKey(ref e) => write!(f, "cryptographic error {}", e),
 			Error::Hyper(ref msg) => write!(f, "Hyper error: {}", msg),
+			Error::Tls(ref msg) => write!(f, "TLS error: {}", msg),
 			Error::Serde(ref msg) => write!(f, "Serialization error: {}", msg),
 			Error::Database(ref msg) => write!(f, "Database error: {}", msg),
 			Error::Internal(ref msg) => write!(f, "Internal error: {}", msg),
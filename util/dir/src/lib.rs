@@ -218,6 +218,11 @@ impl DatabaseDirectories {
 	pub fn network_path(&self) -> PathBuf {
 		self.spec_root_path().join("network")
 	}
+
+	/// Get the path for the whisper mail server directory.
+	pub fn whisper_mail_path(&self) -> PathBuf {
+		self.spec_root_path().join("whisper_mail")
+	}
 }
 
 /// Default data path
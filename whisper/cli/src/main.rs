@@ -116,6 +116,10 @@ impl whisper::rpc::PoolHandle for WhisperPoolHandle {
 	fn pool_status(&self) -> whisper::net::PoolStatus {
 		self.handle.pool_status()
 	}
+
+	fn peer_stats(&self) -> Vec<whisper::net::PeerStats> {
+		self.handle.peer_stats()
+	}
 }
 
 impl WhisperPoolHandle {
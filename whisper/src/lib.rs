@@ -25,6 +25,7 @@ extern crate ethcore_network as network;
 extern crate ethereum_types;
 extern crate ethkey;
 extern crate hex;
+extern crate kvdb;
 extern crate memzero;
 extern crate ordered_float;
 extern crate parking_lot;
@@ -54,9 +55,19 @@ extern crate time_utils;
 #[cfg(test)]
 extern crate serde_json;
 
+#[cfg(test)]
+extern crate kvdb_memorydb;
+
+#[cfg(test)]
+extern crate kvdb_rocksdb;
+
+#[cfg(test)]
+extern crate tempdir;
+
 pub use self::message::Message;
 pub use self::net::{Network, MessageHandler};
 
+pub mod mail_server;
 pub mod message;
 pub mod net;
 pub mod rpc;
@@ -0,0 +1,523 @@
+// Copyright 2015-2019 Parity Technologies (UK) Ltd.
+// This file is part of Parity Ethereum.
+
+// Parity Ethereum is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity Ethereum is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity Ethereum.  If not, see <http://www.gnu.org/licenses/>.
+
+
+//! Mail server: persistent storage of envelopes for peers which were offline.
+//!
+//! Envelopes matching the configured bloom are stored in a key-value database,
+//! grouped into buckets by their issue time. Peers knowing the symmetric key of the
+//! mail server may request envelopes of a time range, even after their TTL has passed.
+//! Once the storage grows over its target size, the oldest envelopes are evicted.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use byteorder::{ByteOrder, BigEndian};
+use crypto::{hmac, is_equal};
+use ethereum_types::{H256, H512};
+use kvdb::KeyValueDB;
+use parking_lot::Mutex;
+use rlp::{self, DecoderError, Rlp, RlpStream};
+
+use message::{bloom_topics, Envelope, Message};
+
+/// Envelopes issued within the same number of seconds share the key prefix.
+const BUCKET_SECS: u64 = 3600;
+
+/// Max number of envelopes removed by a single pruning.
+const MAX_PRUNED: usize = 1024;
+
+/// Requests issued longer ago, or later, than this number of seconds are rejected.
+const REQUEST_WINDOW_SECS: u64 = 60;
+
+/// Errors of the mail server.
+#[derive(Debug)]
+pub enum Error {
+	/// Failed to decode request or stored envelope.
+	Decoder(DecoderError),
+	/// Database error.
+	Io(io::Error),
+	/// Request isn't authenticated with the key of the mail server.
+	Unauthorized,
+	/// Request is issued outside of the accepted time window, or has already been served.
+	Replayed,
+}
+
+impl From<DecoderError> for Error {
+	fn from(err: DecoderError) -> Self {
+		Error::Decoder(err)
+	}
+}
+
+impl From<io::Error> for Error {
+	fn from(err: io::Error) -> Self {
+		Error::Io(err)
+	}
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::Decoder(ref err) => write!(f, "Failed to decode: {}", err),
+			Error::Io(ref err) => write!(f, "Database error: {}", err),
+			Error::Unauthorized => write!(f, "Request is not authorized."),
+			Error::Replayed => write!(f, "Request is stale or replayed."),
+		}
+	}
+}
+
+/// Request for historical envelopes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+	/// Lower bound of issue time of requested envelopes, in seconds since unix epoch.
+	pub from: u64,
+	/// Upper bound of issue time of requested envelopes, inclusive.
+	pub to: u64,
+	/// Bloom of topics of requested envelopes.
+	pub bloom: H512,
+	/// Maximum number of returned envelopes.
+	pub limit: u32,
+	/// Time the request is issued at, in seconds since unix epoch.
+	pub timestamp: u64,
+}
+
+impl Request {
+	/// Encode the request, authenticated with the given symmetric key.
+	pub fn encode_authenticated(&self, key: &[u8; 32]) -> Vec<u8> {
+		let payload = self.payload();
+		let mac = hmac::sign(&hmac::SigKey::sha256(key), &payload);
+
+		let mut stream = RlpStream::new_list(2);
+		stream.append(&payload).append(&mac.as_ref().to_vec());
+		stream.out()
+	}
+
+	/// Decode the request, checking that it is authenticated with the given symmetric key.
+	pub fn decode_authenticated(rlp: &Rlp, key: &[u8; 32]) -> Result<Self, Error> {
+		if rlp.item_count()? != 2 { return Err(DecoderError::RlpIncorrectListLen.into()) }
+
+		let payload: Vec<u8> = rlp.val_at(0)?;
+		let mac: Vec<u8> = rlp.val_at(1)?;
+		let expected_mac = hmac::sign(&hmac::SigKey::sha256(key), &payload);
+		if !is_equal(expected_mac.as_ref(), &mac) {
+			return Err(Error::Unauthorized);
+		}
+
+		let payload = Rlp::new(&payload);
+		if payload.item_count()? != 5 { return Err(DecoderError::RlpIncorrectListLen.into()) }
+
+		Ok(Request {
+			from: payload.val_at(0)?,
+			to: payload.val_at(1)?,
+			bloom: payload.val_at(2)?,
+			limit: payload.val_at(3)?,
+			timestamp: payload.val_at(4)?,
+		})
+	}
+
+	fn payload(&self) -> Vec<u8> {
+		let mut stream = RlpStream::new_list(5);
+		stream.append(&self.from).append(&self.to).append(&self.bloom).append(&self.limit).append(&self.timestamp);
+		stream.out()
+	}
+}
+
+/// Mail server configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+	/// Only envelopes with topics matching this bloom are stored.
+	pub bloom: H512,
+	/// Symmetric key authenticating requests for historical envelopes.
+	pub key: [u8; 32],
+	/// Target size of stored envelopes, in bytes.
+	pub max_size: usize,
+	/// For how long envelopes are kept after they expire.
+	pub retention: Duration,
+}
+
+// size and time bounds of stored envelopes.
+struct Storage {
+	size: usize,
+	newest_bucket: u64,
+}
+
+/// Persistent storage of envelopes.
+pub struct MailServer {
+	db: Arc<KeyValueDB>,
+	column: Option<u32>,
+	config: Config,
+	storage: Mutex<Storage>,
+	// MACs of served requests, with their timestamps.
+	served: Mutex<HashMap<Vec<u8>, u64>>,
+}
+
+impl MailServer {
+	/// Create a mail server storing envelopes in the given database column.
+	pub fn new(db: Arc<KeyValueDB>, column: Option<u32>, config: Config) -> Self {
+		let mut storage = Storage { size: 0, newest_bucket: 0 };
+		for (key, value) in db.iter(column) {
+			storage.size += value.len();
+			storage.newest_bucket = cmp::max(storage.newest_bucket, BigEndian::read_u64(&key[..8]));
+		}
+
+		MailServer {
+			db,
+			column,
+			config,
+			storage: Mutex::new(storage),
+			served: Mutex::new(HashMap::new()),
+		}
+	}
+
+	/// Total size of stored envelopes.
+	pub fn size(&self) -> usize {
+		self.storage.lock().size
+	}
+
+	/// Decode the request, checking that it is authenticated with the key of the mail server,
+	/// issued recently and not served before.
+	pub fn authenticate(&self, rlp: &Rlp, now: SystemTime) -> Result<Request, Error> {
+		let request = Request::decode_authenticated(rlp, &self.config.key)?;
+
+		let now = now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+		let is_recent = |timestamp: u64| cmp::max(timestamp, now) - cmp::min(timestamp, now) <= REQUEST_WINDOW_SECS;
+		if !is_recent(request.timestamp) {
+			return Err(Error::Replayed);
+		}
+
+		// requests outside of the window are rejected anyway, so there's no need to remember them.
+		let mut served = self.served.lock();
+		served.retain(|_, timestamp| is_recent(*timestamp));
+
+		let mac: Vec<u8> = rlp.val_at(1)?;
+		if served.insert(mac, request.timestamp).is_some() {
+			return Err(Error::Replayed);
+		}
+
+		Ok(request)
+	}
+
+	/// Store messages matching the configured bloom, evicting the oldest ones
+	/// if the storage grows over the target size.
+	pub fn archive(&self, messages: &[Message]) -> Result<(), Error> {
+		let mut storage = self.storage.lock();
+		let mut transaction = self.db.transaction();
+		let mut added = 0;
+		let mut newest_bucket = storage.newest_bucket;
+
+		for message in messages {
+			if &(&self.config.bloom & message.bloom()) != message.bloom() { continue }
+
+			let key = db_key(message.envelope(), message.hash());
+			if self.db.get(self.column, &key)?.is_some() { continue }
+
+			let value = rlp::encode(message.envelope());
+			added += value.len();
+			newest_bucket = cmp::max(newest_bucket, BigEndian::read_u64(&key[..8]));
+			transaction.put_vec(self.column, &key, value);
+		}
+
+		if added == 0 { return Ok(()) }
+
+		self.db.write(transaction)?;
+		storage.size += added;
+		storage.newest_bucket = newest_bucket;
+
+		if storage.size > self.config.max_size {
+			storage.size = self.evict_oldest(storage.size)?;
+		}
+
+		Ok(())
+	}
+
+	/// Remove envelopes which expired longer ago than the retention period.
+	/// Returns the number of removed envelopes, at most `MAX_PRUNED` at once.
+	pub fn prune(&self, now: SystemTime) -> Result<usize, Error> {
+		let now = now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+		let retention = self.config.retention.as_secs();
+
+		// envelopes never expire before they're issued, so only the oldest ones,
+		// issued before the retention period, are visited.
+		let horizon = now.saturating_sub(retention);
+		let candidates = self.db.iter(self.column)
+			.take_while(|&(ref key, _)| BigEndian::read_u64(&key[8..16]) <= horizon);
+
+		let mut storage = self.storage.lock();
+		let mut transaction = self.db.transaction();
+		let mut removed = 0;
+		let mut removed_size = 0;
+
+		for (key, value) in candidates {
+			if removed >= MAX_PRUNED { break }
+
+			let expiry: u64 = Rlp::new(&value).val_at(0)?;
+			if expiry.saturating_add(retention) > now { continue }
+
+			transaction.delete(self.column, &key);
+			removed += 1;
+			removed_size += value.len();
+		}
+
+		if removed == 0 { return Ok(0) }
+
+		self.db.write(transaction)?;
+		storage.size -= removed_size;
+
+		Ok(removed)
+	}
+
+	/// Get stored envelopes issued within the requested time range and matching the requested bloom,
+	/// ordered by issue time.
+	pub fn query(&self, request: &Request) -> Result<Vec<Envelope>, Error> {
+		let mut envelopes = Vec::new();
+
+		// only visit buckets of the requested range which may contain stored envelopes.
+		let oldest_bucket = match self.db.iter(self.column).next() {
+			Some((key, _)) => BigEndian::read_u64(&key[..8]),
+			None => return Ok(envelopes),
+		};
+		let newest_bucket = self.storage.lock().newest_bucket;
+		let first_bucket = cmp::max(request.from / BUCKET_SECS, oldest_bucket);
+		let last_bucket = cmp::min(request.to / BUCKET_SECS, newest_bucket);
+
+		for bucket in first_bucket..last_bucket + 1 {
+			let mut prefix = [0u8; 8];
+			BigEndian::write_u64(&mut prefix, bucket);
+
+			// envelopes of the bucket are ordered by issue time.
+			let entries = self.db.iter_from_prefix(self.column, &prefix)
+				.take_while(|&(ref key, _)| key.starts_with(&prefix));
+			for (key, value) in entries {
+				if envelopes.len() >= request.limit as usize { return Ok(envelopes) }
+
+				let issued = BigEndian::read_u64(&key[8..16]);
+				if issued < request.from { continue }
+				if issued > request.to { break }
+
+				let envelope: Envelope = rlp::decode(&value)?;
+				let bloom = bloom_topics(&envelope.topics);
+				if (&request.bloom & &bloom) == bloom {
+					envelopes.push(envelope);
+				}
+			}
+		}
+
+		Ok(envelopes)
+	}
+
+	// evict the oldest envelopes until the size is at most the target size.
+	// returns the new size.
+	fn evict_oldest(&self, mut size: usize) -> Result<usize, Error> {
+		let mut transaction = self.db.transaction();
+		for (key, value) in self.db.iter(self.column) {
+			if size <= self.config.max_size { break }
+
+			transaction.delete(self.column, &key);
+			size -= value.len();
+		}
+
+		self.db.write(transaction)?;
+		Ok(size)
+	}
+}
+
+// envelopes are keyed by the bucket of their issue time and the issue time itself,
+// so they're iterated from the oldest one.
+fn db_key(envelope: &Envelope, hash: &H256) -> [u8; 48] {
+	let issued = envelope.expiry.saturating_sub(envelope.ttl);
+	let mut key = [0u8; 48];
+	BigEndian::write_u64(&mut key[..8], issued / BUCKET_SECS);
+	BigEndian::write_u64(&mut key[8..16], issued);
+	key[16..].copy_from_slice(&**hash);
+	key
+}
+
+#[cfg(test)]
+mod tests {
+	use kvdb_rocksdb::Database;
+	use message::Topic;
+	use tempdir::TempDir;
+	use super::*;
+
+	fn message(issued: u64, ttl: u64, topic: [u8; 4], data: &[u8]) -> Message {
+		let envelope = Envelope {
+			expiry: issued + ttl,
+			ttl,
+			topics: vec![Topic(topic)].into_iter().collect(),
+			data: data.to_vec(),
+			nonce: 0,
+		};
+
+		Message::decode(Rlp::new(&rlp::encode(&envelope)), SystemTime::now()).unwrap()
+	}
+
+	fn config(max_size: usize) -> Config {
+		Config {
+			bloom: H512::from([0xff; 64]),
+			key: [7; 32],
+			max_size,
+			retention: Duration::from_secs(100),
+		}
+	}
+
+	fn request(from: u64, to: u64) -> Request {
+		Request { from, to, bloom: H512::from([0xff; 64]), limit: 100, timestamp: 1000 }
+	}
+
+	fn database(tempdir: &TempDir) -> Arc<KeyValueDB> {
+		Arc::new(Database::open_default(&tempdir.path().display().to_string()).unwrap())
+	}
+
+	fn mail_server(tempdir: &TempDir, config: Config) -> MailServer {
+		MailServer::new(database(tempdir), None, config)
+	}
+
+	#[test]
+	fn stores_messages_matching_bloom() {
+		let mut config = config(1024 * 1024);
+		config.bloom = bloom_topics(&[Topic([1, 2, 3, 4])]);
+		let tempdir = TempDir::new("").unwrap();
+		let server = mail_server(&tempdir, config);
+
+		let stored = message(1000, 10, [1, 2, 3, 4], b"stored");
+		server.archive(&[stored.clone(), message(1001, 10, [5, 6, 7, 8], b"ignored")]).unwrap();
+
+		// the same message isn't stored twice.
+		server.archive(&[stored.clone()]).unwrap();
+
+		assert_eq!(server.query(&request(0, u64::max_value())).unwrap(), vec![stored.envelope().clone()]);
+		assert_eq!(server.size(), stored.encoded_size());
+	}
+
+	#[test]
+	fn prunes_messages_after_retention() {
+		let tempdir = TempDir::new("").unwrap();
+		let server = mail_server(&tempdir, config(1024 * 1024));
+		let old = message(1000, 10, [1, 2, 3, 4], b"old");
+		let recent = message(1050, 10, [1, 2, 3, 4], b"recent");
+		server.archive(&[old.clone(), recent.clone()]).unwrap();
+
+		// old one expired at 1010, so it's removed 100 seconds later.
+		assert_eq!(server.prune(UNIX_EPOCH + Duration::from_secs(1109)).unwrap(), 0);
+		assert_eq!(server.prune(UNIX_EPOCH + Duration::from_secs(1110)).unwrap(), 1);
+
+		assert_eq!(server.query(&request(0, u64::max_value())).unwrap(), vec![recent.envelope().clone()]);
+		assert_eq!(server.size(), recent.encoded_size());
+	}
+
+	#[test]
+	fn evicts_oldest_messages_when_full() {
+		let messages: Vec<_> = (0..5).map(|i| message(1000 + i, 10, [1, 2, 3, 4], b"same size")).collect();
+		let tempdir = TempDir::new("").unwrap();
+		let server = mail_server(&tempdir, config(3 * messages[0].encoded_size()));
+
+		// insert in reverse order, eviction is still by issue time.
+		for message in messages.iter().rev() {
+			server.archive(&[message.clone()]).unwrap();
+		}
+
+		assert_eq!(server.query(&request(0, u64::max_value())).unwrap(),
+			messages[2..].iter().map(|m| m.envelope().clone()).collect::<Vec<_>>());
+		assert_eq!(server.size(), 3 * messages[0].encoded_size());
+
+		// size is restored from the database.
+		let tempdir = TempDir::new("").unwrap();
+		let db = database(&tempdir);
+		MailServer::new(db.clone(), None, config(1024 * 1024)).archive(&messages[..2]).unwrap();
+		assert_eq!(MailServer::new(db, None, config(1024 * 1024)).size(), 2 * messages[0].encoded_size());
+	}
+
+	#[test]
+	fn serves_time_range_and_topics() {
+		let tempdir = TempDir::new("").unwrap();
+		let server = mail_server(&tempdir, config(1024 * 1024));
+		let messages = vec![
+			message(1000, 10, [1, 2, 3, 4], b"first"),
+			message(1010, 10, [5, 6, 7, 8], b"second"),
+			message(1020, 10, [1, 2, 3, 4], b"third"),
+			message(1030, 10, [1, 2, 3, 4], b"fourth"),
+		];
+		server.archive(&messages).unwrap();
+
+		let envelopes = |request: Request| server.query(&request).unwrap().into_iter()
+			.map(|envelope| envelope.data)
+			.collect::<Vec<_>>();
+
+		assert_eq!(envelopes(request(1010, 1020)), vec![b"second".to_vec(), b"third".to_vec()]);
+		assert_eq!(envelopes(Request { bloom: bloom_topics(&[Topic([1, 2, 3, 4])]), ..request(0, 2000) }),
+			vec![b"first".to_vec(), b"third".to_vec(), b"fourth".to_vec()]);
+		assert_eq!(envelopes(Request { limit: 1, ..request(1001, 2000) }), vec![b"second".to_vec()]);
+	}
+
+	#[test]
+	fn serves_time_range_spanning_buckets() {
+		// in-memory database only supports iteration by prefix.
+		let server = MailServer::new(Arc::new(::kvdb_memorydb::create(0)), None, config(1024 * 1024));
+		let messages = vec![
+			message(BUCKET_SECS - 1, 10, [1, 2, 3, 4], b"first"),
+			message(BUCKET_SECS, 10, [1, 2, 3, 4], b"second"),
+			message(3 * BUCKET_SECS + 1, 10, [1, 2, 3, 4], b"third"),
+			message(5 * BUCKET_SECS, 10, [1, 2, 3, 4], b"fourth"),
+		];
+		server.archive(&messages).unwrap();
+
+		let envelopes = |request: Request| server.query(&request).unwrap().into_iter()
+			.map(|envelope| envelope.data)
+			.collect::<Vec<_>>();
+
+		assert_eq!(envelopes(request(BUCKET_SECS - 1, 4 * BUCKET_SECS)),
+			vec![b"first".to_vec(), b"second".to_vec(), b"third".to_vec()]);
+		assert_eq!(envelopes(request(BUCKET_SECS + 1, u64::max_value())), vec![b"third".to_vec(), b"fourth".to_vec()]);
+		assert_eq!(envelopes(request(4 * BUCKET_SECS, 5 * BUCKET_SECS - 1)), Vec::<Vec<u8>>::new());
+	}
+
+	#[test]
+	fn replayed_requests_are_rejected() {
+		let tempdir = TempDir::new("").unwrap();
+		let server = mail_server(&tempdir, config(1024 * 1024));
+		let now = UNIX_EPOCH + Duration::from_secs(1000);
+		let encoded = request(0, 2000).encode_authenticated(&[7; 32]);
+
+		assert_eq!(server.authenticate(&Rlp::new(&encoded), now).unwrap(), request(0, 2000));
+		match server.authenticate(&Rlp::new(&encoded), now) {
+			Err(Error::Replayed) => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+
+		// stale requests are rejected as well.
+		let stale = Request { timestamp: 1000 - REQUEST_WINDOW_SECS - 1, ..request(0, 2000) }.encode_authenticated(&[7; 32]);
+		match server.authenticate(&Rlp::new(&stale), now) {
+			Err(Error::Replayed) => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn requests_are_authenticated() {
+		let request = request(1000, 2000);
+		let encoded = request.encode_authenticated(&[7; 32]);
+
+		assert_eq!(Request::decode_authenticated(&Rlp::new(&encoded), &[7; 32]).unwrap(), request);
+		match Request::decode_authenticated(&Rlp::new(&encoded), &[8; 32]) {
+			Err(Error::Unauthorized) => {},
+			other => panic!("unexpected result: {:?}", other),
+		}
+	}
+}
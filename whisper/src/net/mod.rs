@@ -19,7 +19,7 @@
 use std::collections::{HashMap, HashSet};
 use std::cmp::Ordering;
 use std::fmt;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use std::sync::Arc;
 
 use ethereum_types::{H256, H512};
@@ -28,6 +28,7 @@ use ordered_float::OrderedFloat;
 use parking_lot::{Mutex, RwLock};
 use rlp::{DecoderError, RlpStream, Rlp};
 
+use mail_server::{MailServer, Request as HistoryRequest, Error as MailServerError};
 use message::{Message, Error as MessageError};
 
 #[cfg(test)]
@@ -38,6 +39,9 @@ mod tests;
 const RALLY_TOKEN: TimerToken = 1;
 const RALLY_TIMEOUT: Duration = Duration::from_millis(2500);
 
+const PRUNE_TOKEN: TimerToken = 2;
+const PRUNE_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// Current protocol version.
 pub const PROTOCOL_VERSION: usize = 6;
 
@@ -52,6 +56,22 @@ pub const SUPPORTED_VERSIONS: &'static [(u8, u8)] = &[
 // maximum tolerated delay between messages packets.
 const MAX_TOLERATED_DELAY: Duration = Duration::from_millis(5000);
 
+// cannot be greater than 16MB (protocol limitation)
+const MAX_MESSAGES_PACKET_SIZE: usize = 8 * 1024 * 1024;
+
+/// Default minimum PoW of envelopes accepted for relaying.
+pub const DEFAULT_MIN_POW: f64 = 0.0;
+
+/// Default maximum encoded size of a single envelope.
+pub const DEFAULT_MAX_ENVELOPE_SIZE: usize = MAX_MESSAGES_PACKET_SIZE;
+
+// length of a window in which envelopes received from a peer are counted.
+const PEER_RATE_WINDOW: Duration = Duration::from_secs(1);
+
+// number of bits the advertised topic bloom of a light node may set in excess
+// of the bloom of its filters before it's re-advertised.
+const BLOOM_SLACK_BITS: u32 = 24;
+
 /// Whisper protocol ID
 pub const PROTOCOL_ID: ::network::ProtocolId = *b"shh";
 
@@ -66,7 +86,9 @@ mod packet {
 	pub const POW_REQUIREMENT: u8 = 2;
 	pub const TOPIC_FILTER: u8 = 3;
 
-	// 126, 127 for mail server stuff we will never implement here.
+	// mail server packets.
+	pub const P2P_REQUEST: u8 = 126;
+	pub const P2P_MESSAGES: u8 = 127;
 }
 
 /// Handles messages within a single packet.
@@ -78,6 +100,30 @@ pub trait MessageHandler: Send + Sync {
 	/// If there is a significant overhead in this thread, then an attacker
 	/// can determine which kinds of messages we are listening for.
 	fn handle_messages(&self, message: &[Message]);
+
+	/// Handle historical messages, delivered by a mail server upon request.
+	///
+	/// These may have expired already. By default, they're handled as relayed messages.
+	fn handle_historical_messages(&self, messages: &[Message]) {
+		self.handle_messages(messages)
+	}
+
+	/// Bloom of the topics the handler is interested in.
+	///
+	/// Light nodes only request envelopes matching it from peers.
+	/// By default, all envelopes are of interest.
+	fn topic_bloom(&self) -> H512 {
+		H512::from([0xff; 64])
+	}
+}
+
+// whether all bits of `inner` are set in `outer`.
+fn bloom_covers(outer: &H512, inner: &H512) -> bool {
+	&(outer & inner) == inner
+}
+
+fn bloom_bits(bloom: &H512) -> u32 {
+	bloom.iter().map(|byte| byte.count_ones()).sum()
 }
 
 // errors in importing a whisper message.
@@ -86,9 +132,11 @@ enum Error {
 	Decoder(DecoderError),
 	Network(network::Error),
 	Message(MessageError),
+	MailServer(MailServerError),
 	UnknownPeer(PeerId),
 	UnexpectedMessage,
 	InvalidPowReq,
+	RateLimitExceeded,
 }
 
 impl From<DecoderError> for Error {
@@ -109,15 +157,23 @@ impl From<MessageError> for Error {
 	}
 }
 
+impl From<MailServerError> for Error {
+	fn from(err: MailServerError) -> Self {
+		Error::MailServer(err)
+	}
+}
+
 impl fmt::Display for Error {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		match *self {
 			Error::Decoder(ref err) => write!(f, "Failed to decode packet: {}", err),
 			Error::Network(ref err) => write!(f, "Network error: {}", err),
 			Error::Message(ref err) => write!(f, "Error decoding message: {}", err),
+			Error::MailServer(ref err) => write!(f, "Mail server error: {}", err),
 			Error::UnknownPeer(ref id) => write!(f, "Message received from unknown peer: {}", id),
 			Error::UnexpectedMessage => write!(f, "Unexpected message."),
 			Error::InvalidPowReq => write!(f, "Peer sent invalid PoW requirement."),
+			Error::RateLimitExceeded => write!(f, "Peer repeatedly exceeded its envelope budgets."),
 		}
 	}
 }
@@ -295,6 +351,12 @@ impl Messages {
 		self.slab.iter()
 	}
 
+	// lowest PoW of the stored messages.
+	fn min_work_proved(&self) -> Option<f64> {
+		self.slab.iter().map(|message| message.work_proved())
+			.fold(None, |min: Option<f64>, work| Some(min.map_or(work, |min| min.min(work))))
+	}
+
 	fn is_full(&self) -> bool {
 		self.cumulative_size >= self.ideal_size
 	}
@@ -306,6 +368,7 @@ impl Messages {
 			} else {
 				None
 			},
+			max_message_size: self.ideal_size,
 			message_count: self.sorted.len(),
 			cumulative_size: self.cumulative_size,
 			target_size: self.ideal_size,
@@ -318,14 +381,103 @@ enum State {
 	Confirmed,
 }
 
-#[allow(dead_code)] // for node key. this will be useful for topic routing.
+// accounting of envelopes received from a peer.
+#[derive(Default)]
+struct PeerRate {
+	window_start: Option<Instant>,
+	window_envelopes: u64,
+	window_bytes: u64,
+	cool_down_until: Option<Instant>,
+	offenses: u32,
+	last_offense: Option<Instant>,
+	accepted_envelopes: u64,
+	accepted_bytes: u64,
+	dropped_envelopes: u64,
+}
+
+impl PeerRate {
+	// account envelopes of a packet, given their sizes, received at `now`.
+	// returns how many of them, in order, are within the budgets, or `None`
+	// if the peer should be disconnected for exceeding them repeatedly.
+	fn admit(&mut self, budgets: &PeerBudgets, sizes: &[usize], now: Instant) -> Option<usize> {
+		self.forgive_offenses(budgets, now);
+
+		if self.is_cooling_down(now) {
+			self.dropped_envelopes += sizes.len() as u64;
+			return Some(0);
+		}
+
+		if self.window_start.map_or(true, |start| start + PEER_RATE_WINDOW <= now) {
+			self.window_start = Some(now);
+			self.window_envelopes = 0;
+			self.window_bytes = 0;
+		}
+
+		let exceeds = |budget: u64, value: u64| budget != 0 && value > budget;
+
+		let mut admitted = 0;
+		for &size in sizes {
+			let envelopes = self.window_envelopes + 1;
+			let bytes = self.window_bytes + size as u64;
+			if exceeds(budgets.envelopes_per_second, envelopes) || exceeds(budgets.bytes_per_second, bytes) {
+				break;
+			}
+
+			self.window_envelopes = envelopes;
+			self.window_bytes = bytes;
+			self.accepted_envelopes += 1;
+			self.accepted_bytes += size as u64;
+			admitted += 1;
+		}
+
+		if admitted < sizes.len() {
+			self.dropped_envelopes += (sizes.len() - admitted) as u64;
+			self.offenses += 1;
+			self.last_offense = Some(now);
+			self.cool_down_until = Some(now + budgets.cool_down);
+
+			if self.offenses > budgets.max_offenses {
+				return None;
+			}
+		}
+
+		Some(admitted)
+	}
+
+	// forgive one offense for every `offense_decay` passed since the last one.
+	fn forgive_offenses(&mut self, budgets: &PeerBudgets, now: Instant) {
+		let mut last_offense = match self.last_offense {
+			Some(last_offense) => last_offense,
+			None => return,
+		};
+
+		while self.offenses > 0 && last_offense + budgets.offense_decay <= now {
+			self.offenses -= 1;
+			last_offense += budgets.offense_decay;
+		}
+
+		self.last_offense = match self.offenses {
+			0 => None,
+			_ => Some(last_offense),
+		};
+	}
+
+	fn is_cooling_down(&self, now: Instant) -> bool {
+		self.cool_down_until.map_or(false, |until| now < until)
+	}
+}
+
 struct Peer {
 	node_key: NodeId,
 	state: State,
 	known_messages: HashSet<H256>,
 	topic_filter: Option<H512>,
+	advertised_bloom: Option<H512>,
 	pow_requirement: f64,
+	requested_history: bool,
+	rate: PeerRate,
 	is_parity: bool,
+	is_light: bool,
 	_protocol_version: usize,
 }
 
@@ -345,8 +497,11 @@ impl Peer {
 		if message.envelope().is_multitopic() && !self.is_parity { return false }
 		if message.work_proved() < self.pow_requirement { return false }
 
-		self.topic_filter.as_ref()
-			.map_or(true, |filter| &(filter & message.bloom()) == message.bloom())
+		// light peers only accept envelopes they asked for.
+		match self.topic_filter {
+			Some(ref filter) => bloom_covers(filter, message.bloom()),
+			None => !self.is_light,
+		}
 	}
 
 	// note a message as known. returns false if it was already
@@ -371,10 +526,67 @@ impl Peer {
 	}
 }
 
+/// Budgets of envelopes accepted from a single peer. Zero budget means no limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PeerBudgets {
+	/// Envelopes accepted from a peer per second.
+	pub envelopes_per_second: u64,
+	/// Bytes of envelopes accepted from a peer per second.
+	pub bytes_per_second: u64,
+	/// For how long envelopes of a peer are dropped after it exceeds a budget.
+	pub cool_down: Duration,
+	/// How many times a peer may exceed its budgets before it's disconnected.
+	pub max_offenses: u32,
+	/// After how long a single offense of a peer is forgiven.
+	pub offense_decay: Duration,
+}
+
+impl Default for PeerBudgets {
+	fn default() -> Self {
+		PeerBudgets {
+			envelopes_per_second: 0,
+			bytes_per_second: 0,
+			cool_down: Duration::from_secs(10),
+			max_offenses: 3,
+			offense_decay: Duration::from_secs(60),
+		}
+	}
+}
+
+/// Limits on envelopes accepted for relaying.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnvelopeLimits {
+	/// Minimum PoW of an envelope. Advertised to peers.
+	pub min_pow: f64,
+	/// Maximum encoded size of an envelope, in bytes.
+	pub max_size: usize,
+	/// Budgets of envelopes accepted from each peer.
+	pub per_peer: PeerBudgets,
+}
+
+impl Default for EnvelopeLimits {
+	fn default() -> Self {
+		EnvelopeLimits {
+			min_pow: DEFAULT_MIN_POW,
+			max_size: DEFAULT_MAX_ENVELOPE_SIZE,
+			per_peer: PeerBudgets::default(),
+		}
+	}
+}
+
+impl EnvelopeLimits {
+	/// Whether the message is within the limits.
+	pub fn admits(&self, message: &Message) -> bool {
+		message.encoded_size() <= self.max_size && message.work_proved() >= self.min_pow
+	}
+}
+
 /// Pool status.
 pub struct PoolStatus {
 	/// Required PoW to be accepted into the pool
 	pub required_pow: Option<f64>,
+	/// Maximum encoded size of a message accepted into the pool.
+	pub max_message_size: usize,
 	/// Number of messages in the pool.
 	pub message_count: usize,
 	/// Cumulative size of the messages in the pool
@@ -383,6 +595,25 @@ pub struct PoolStatus {
 	pub target_size: usize,
 }
 
+/// Statistics of envelopes received from a peer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerStats {
+	/// Node key of the peer.
+	pub node_key: NodeId,
+	/// Number of envelopes accepted from the peer.
+	pub accepted_envelopes: u64,
+	/// Total size of envelopes accepted from the peer.
+	pub accepted_bytes: u64,
+	/// Number of envelopes dropped for exceeding the budgets.
+	pub dropped_envelopes: u64,
+	/// Number of times the peer exceeded its budgets.
+	pub offenses: u32,
+	/// Whether envelopes of the peer are currently dropped.
+	pub cooling_down: bool,
+	/// PoW requirement advertised by the peer.
+	pub pow_requirement: f64,
+}
+
 /// Generic network context.
 pub trait Context {
 	/// Disconnect a peer.
@@ -424,6 +655,11 @@ impl<T> Context for T where T: ?Sized + NetworkContext {
 /// The whisper network protocol handler.
 pub struct Network<T> {
 	messages: Arc<RwLock<Messages>>,
+	limits: EnvelopeLimits,
+	mail_server: Option<Arc<MailServer>>,
+	light: bool,
+	advertised_bloom: Mutex<Option<H512>>,
+	local_messages: RwLock<HashSet<H256>>,
 	handler: T,
 	peers: RwLock<HashMap<PeerId, Mutex<Peer>>>,
 }
@@ -432,42 +668,174 @@ pub struct Network<T> {
 impl<T> Network<T> {
 	/// Create a new network handler.
 	pub fn new(messages_size_bytes: usize, handler: T) -> Self {
+		Network::with_limits(messages_size_bytes, EnvelopeLimits::default(), handler)
+	}
+
+	/// Create a new network handler, which only relays envelopes within the given limits.
+	pub fn with_limits(messages_size_bytes: usize, limits: EnvelopeLimits, handler: T) -> Self {
 		Network {
 			messages: Arc::new(RwLock::new(Messages::new(messages_size_bytes))),
+			limits: limits,
+			mail_server: None,
+			light: false,
+			advertised_bloom: Mutex::new(None),
+			local_messages: RwLock::new(HashSet::new()),
 			handler: handler,
 			peers: RwLock::new(HashMap::new()),
 		}
 	}
 
+	/// Store relayed envelopes in the mail server and serve them to peers upon request.
+	pub fn with_mail_server(mut self, mail_server: Arc<MailServer>) -> Self {
+		self.mail_server = Some(mail_server);
+		self
+	}
+
+	/// Run as a light node: only envelopes matching the topic bloom of the handler
+	/// are requested from peers, and only locally posted envelopes are relayed.
+	pub fn with_light_mode(mut self) -> Self {
+		self.light = true;
+		self
+	}
+
 	/// Post a message to the whisper network to be relayed.
+	/// Returns false if the message is outside of the configured limits.
 	pub fn post_message<C: ?Sized + Context>(&self, message: Message, context: &C) -> bool
 		where T: MessageHandler
 	{
+		if !self.limits.admits(&message) { return false }
+
+		self.archive(::std::slice::from_ref(&message));
+		let hash = message.hash().clone();
 		let ok = self.messages.write().insert(message);
-		if ok { self.rally(context) }
+		if ok {
+			if self.light { self.local_messages.write().insert(hash); }
+			self.rally(context)
+		}
 		ok
 	}
 
+	/// Request historical envelopes from the mail server of a peer, authenticating
+	/// with the symmetric key of that mail server. Returns false if the peer isn't confirmed.
+	pub fn request_history<C: ?Sized + Context>(&self, peer: PeerId, request: &HistoryRequest, key: &[u8; 32], context: &C) -> bool {
+		match self.peers.read().get(&peer) {
+			Some(peer_data) => {
+				let mut peer_data = peer_data.lock();
+				if !peer_data.can_send_messages() { return false }
+				peer_data.requested_history = true;
+			}
+			None => return false,
+		}
+
+		context.send(peer, packet::P2P_REQUEST, request.encode_authenticated(key));
+		true
+	}
+
 	/// Get number of messages and amount of memory used by them.
 	pub fn pool_status(&self) -> PoolStatus {
-		self.messages.read().status()
+		let mut status = self.messages.read().status();
+		let min_pow = self.limits.min_pow;
+
+		status.required_pow = match status.required_pow {
+			Some(pow) => Some(pow.max(min_pow)),
+			None if min_pow > 0.0 => Some(min_pow),
+			None => None,
+		};
+		status.max_message_size = ::std::cmp::min(status.max_message_size, self.limits.max_size);
+		status
+	}
+
+	/// Get statistics of envelopes received from each peer.
+	pub fn peer_stats(&self) -> Vec<PeerStats> {
+		let now = Instant::now();
+		self.peers.read().values().map(|peer| {
+			let peer = peer.lock();
+			PeerStats {
+				node_key: peer.node_key.clone(),
+				accepted_envelopes: peer.rate.accepted_envelopes,
+				accepted_bytes: peer.rate.accepted_bytes,
+				dropped_envelopes: peer.rate.dropped_envelopes,
+				offenses: peer.rate.offenses,
+				cooling_down: peer.rate.is_cooling_down(now),
+				pow_requirement: peer.pow_requirement,
+			}
+		}).collect()
+	}
+
+	// remove envelopes which expired longer ago than the retention period from the mail server, if any.
+	fn prune_mail_server(&self) {
+		if let Some(ref mail_server) = self.mail_server {
+			match mail_server.prune(SystemTime::now()) {
+				Ok(removed) => trace!(target: "whisper", "Pruned {} envelopes from mail server", removed),
+				Err(e) => warn!(target: "whisper", "Failed to prune mail server: {}", e),
+			}
+		}
+	}
+
+	// store messages in the mail server, if any.
+	fn archive(&self, messages: &[Message]) {
+		if let Some(ref mail_server) = self.mail_server {
+			if let Err(e) = mail_server.archive(messages) {
+				warn!(target: "whisper", "Failed to store envelopes in mail server: {}", e);
+			}
+		}
 	}
 }
 
 impl<T: MessageHandler> Network<T> {
-	fn rally<C: ?Sized + Context>(&self, io: &C) {
-		// cannot be greater than 16MB (protocol limitation)
-		const MAX_MESSAGES_PACKET_SIZE: usize = 8 * 1024 * 1024;
+	// get the topic bloom to advertise in light mode. it's recomputed from the handler,
+	// but only replaced once it changed materially, to avoid flooding peers with updates.
+	fn advertised_bloom(&self) -> H512 {
+		let bloom = self.handler.topic_bloom();
+		let mut advertised = self.advertised_bloom.lock();
+
+		if let Some(current) = *advertised {
+			if bloom_covers(&current, &bloom) && bloom_bits(&current) < bloom_bits(&bloom) + BLOOM_SLACK_BITS {
+				return current;
+			}
+		}
 
+		*advertised = Some(bloom);
+		bloom
+	}
+
+	fn rally<C: ?Sized + Context>(&self, io: &C) {
 		// prune messages.
 		let now = SystemTime::now();
 		let pruned_hashes = self.messages.write().prune(now);
 
+		let advertised_bloom = match self.light {
+			true => {
+				let mut local_messages = self.local_messages.write();
+				for hash in &pruned_hashes {
+					local_messages.remove(hash);
+				}
+				Some(self.advertised_bloom())
+			}
+			false => None,
+		};
+		let local_messages = self.local_messages.read();
+
 		let messages = self.messages.read();
 		let peers = self.peers.read();
 
+		// peers requiring more PoW than some of our messages prove are served last,
+		// with smaller packets, since those messages are of no use to them.
+		let min_work_proved = messages.min_work_proved();
+		let mut peers_by_priority: Vec<_> = peers.iter()
+			.map(|(peer_id, peer)| {
+				let pow_requirement = peer.lock().pow_requirement;
+				(min_work_proved.map_or(false, |work| pow_requirement > work), peer_id, peer)
+			})
+			.collect();
+		peers_by_priority.sort_by_key(|&(deprioritized, peer_id, _)| (deprioritized, *peer_id));
+
 		// send each peer a packet with new messages it may find relevant.
-		for (peer_id, peer) in peers.iter() {
+		for (deprioritized, peer_id, peer) in peers_by_priority {
+			let max_packet_size = match deprioritized {
+				true => MAX_MESSAGES_PACKET_SIZE / 4,
+				false => MAX_MESSAGES_PACKET_SIZE,
+			};
 			let mut peer_data = peer.lock();
 			peer_data.note_evicted(&pruned_hashes);
 
@@ -487,14 +855,24 @@ impl<T: MessageHandler> Network<T> {
 				State::Confirmed => {}
 			}
 
+			// let the peer know about changes of our topic bloom.
+			if let Some(bloom) = advertised_bloom {
+				if peer_data.advertised_bloom != Some(bloom) {
+					peer_data.advertised_bloom = Some(bloom);
+					io.send(*peer_id, packet::TOPIC_FILTER, ::rlp::encode(&bloom));
+				}
+			}
+
 			// construct packet, skipping messages the peer won't accept.
+			// light nodes only relay messages posted locally.
 			let mut stream = RlpStream::new();
 			stream.begin_unbounded_list();
 
 			for message in messages.iter() {
+				if self.light && !local_messages.contains(message.hash()) { continue }
 				if !peer_data.will_accept(message) { continue }
 
-				if stream.estimate_size(message.encoded_size()) > MAX_MESSAGES_PACKET_SIZE {
+				if stream.estimate_size(message.encoded_size()) > max_packet_size {
 					break;
 				}
 
@@ -509,14 +887,39 @@ impl<T: MessageHandler> Network<T> {
 	}
 
 	// handle status packet from peer.
-	fn on_status(&self, peer: &PeerId, _status: Rlp)
+	// peers may advertise their PoW requirement as the second item of the status,
+	// their topic bloom as the third one and whether they're light nodes as the fourth one.
+	// items of unexpected type are treated as absent.
+	fn on_status(&self, peer: &PeerId, status: Rlp)
 		-> Result<(), Error>
 	{
 		let peers = self.peers.read();
 
 		match peers.get(peer) {
 			Some(peer) => {
-				peer.lock().state = State::Confirmed;
+				let item_count = status.item_count()?;
+				let pow_requirement = match item_count {
+					0 | 1 => None,
+					_ => Some(decode_pow_requirement(status.at(1)?)?),
+				};
+				let bloom = match item_count {
+					0 | 1 | 2 => None,
+					_ => status.val_at::<H512>(2).ok(),
+				};
+				let is_light = match item_count {
+					0 | 1 | 2 | 3 => false,
+					_ => status.val_at(3).unwrap_or(false),
+				};
+
+				let mut peer = peer.lock();
+				peer.state = State::Confirmed;
+				peer.is_light = is_light;
+				if let Some(bloom) = bloom {
+					peer.set_topic_filter(bloom);
+				}
+				if let Some(pow_requirement) = pow_requirement {
+					peer.set_pow_requirement(pow_requirement);
+				}
 				Ok(())
 			}
 			None => {
@@ -545,24 +948,56 @@ impl<T: MessageHandler> Network<T> {
 				return Err(Error::UnexpectedMessage);
 			}
 
+			// account envelopes before decoding, so flooding peers don't drain the CPU.
+			let sizes: Vec<_> = message_packet.iter().map(|rlp| rlp.as_raw().len()).collect();
+			if sizes.is_empty() { return Ok(()) }
+
+			let admitted = match peer.rate.admit(&self.limits.per_peer, &sizes, Instant::now()) {
+				Some(admitted) => admitted,
+				None => return Err(Error::RateLimitExceeded),
+			};
+			if admitted < sizes.len() {
+				trace!(target: "whisper", "Dropping {} envelopes of peer {} exceeding its budgets",
+					sizes.len() - admitted, peer.node_key);
+			}
+
 			let now = SystemTime::now();
-			let mut messages_vec = message_packet.iter().map(|rlp| Message::decode(rlp, now))
+			let mut messages_vec = message_packet.iter().take(admitted).map(|rlp| Message::decode(rlp, now))
 				.collect::<Result<Vec<_>, _>>()?;
 
 			if messages_vec.is_empty() { return Ok(()) }
 
 			// disallow duplicates in packet.
 			messages_vec.retain(|message| peer.note_known(&message));
+
+			// peers may not know our requirements yet, so just drop envelopes outside of them.
+			let limits = &self.limits;
+			messages_vec.retain(|message| {
+				let admitted = limits.admits(message);
+				if !admitted {
+					trace!(target: "whisper", "Dropping envelope {} from peer: size {}, PoW {}",
+						message.hash(), message.encoded_size(), message.work_proved());
+				}
+				admitted
+			});
+
+			// as well as envelopes a light node didn't ask for.
+			if self.light {
+				let advertised = *self.advertised_bloom.lock();
+				let bloom = advertised.unwrap_or_else(|| self.handler.topic_bloom());
+				messages_vec.retain(|message| bloom_covers(&bloom, message.bloom()));
+			}
 			messages_vec
 		};
 
-		// import for relaying.
+		// import for relaying. light nodes keep them only to avoid handling duplicates.
 		let mut messages = self.messages.write();
 
 		messages_vec.retain(|message| messages.may_accept(&message));
 		messages.reserve(messages_vec.len());
 
 		self.handler.handle_messages(&messages_vec);
+		self.archive(&messages_vec);
 
 		for message in messages_vec {
 			messages.insert(message);
@@ -574,8 +1009,6 @@ impl<T: MessageHandler> Network<T> {
 	fn on_pow_requirement(&self, peer: &PeerId, requirement: Rlp)
 		-> Result<(), Error>
 	{
-		use byteorder::{ByteOrder, BigEndian};
-
 		let peers = self.peers.read();
 		match peers.get(peer) {
 			Some(peer) => {
@@ -584,19 +1017,8 @@ impl<T: MessageHandler> Network<T> {
 				if let State::Unconfirmed(_) = peer.state {
 					return Err(Error::UnexpectedMessage);
 				}
-				let bytes: Vec<u8> = requirement.as_val()?;
-				if bytes.len() != ::std::mem::size_of::<f64>() {
-					return Err(Error::InvalidPowReq);
-				}
-
-				// as of byteorder 1.1.0, this is always defined.
-				let req = BigEndian::read_f64(&bytes[..]);
-
-				if !req.is_normal() {
-					return Err(Error::InvalidPowReq);
-				}
 
-				peer.set_pow_requirement(req);
+				peer.set_pow_requirement(decode_pow_requirement(requirement)?);
 			}
 			None => {
 				debug!(target: "whisper", "Received message from unknown peer.");
@@ -630,6 +1052,82 @@ impl<T: MessageHandler> Network<T> {
 		Ok(())
 	}
 
+	// serve request of historical envelopes from the mail server.
+	fn on_p2p_request<C: ?Sized + Context>(&self, io: &C, peer: &PeerId, request: Rlp)
+		-> Result<(), Error>
+	{
+		{
+			let peers = self.peers.read();
+			match peers.get(peer) {
+				Some(peer) => if !peer.lock().can_send_messages() {
+					return Err(Error::UnexpectedMessage);
+				},
+				None => {
+					debug!(target: "whisper", "Received message from unknown peer.");
+					return Err(Error::UnknownPeer(*peer));
+				}
+			}
+		}
+
+		let mail_server = match self.mail_server {
+			Some(ref mail_server) => mail_server,
+			None => return Ok(()), // not a mail server, ignore the request.
+		};
+
+		let request = mail_server.authenticate(&request, SystemTime::now())?;
+		let envelopes = mail_server.query(&request)?;
+
+		let mut stream = RlpStream::new();
+		stream.begin_unbounded_list();
+
+		for envelope in &envelopes {
+			let encoded = ::rlp::encode(envelope);
+			if stream.estimate_size(encoded.len()) > MAX_MESSAGES_PACKET_SIZE {
+				break;
+			}
+
+			stream.append_raw(&encoded, 1);
+		}
+
+		stream.complete_unbounded_list();
+
+		trace!(target: "whisper", "Serving {} historical envelopes to peer {}", envelopes.len(), peer);
+		io.send(*peer, packet::P2P_MESSAGES, stream.out());
+		Ok(())
+	}
+
+	// handle historical envelopes, only accepted when requested.
+	fn on_p2p_messages(&self, peer: &PeerId, message_packet: Rlp)
+		-> Result<(), Error>
+	{
+		{
+			let peers = self.peers.read();
+			match peers.get(peer) {
+				Some(peer) => {
+					let mut peer = peer.lock();
+					if !peer.requested_history {
+						return Err(Error::UnexpectedMessage);
+					}
+					peer.requested_history = false;
+				}
+				None => {
+					debug!(target: "whisper", "Received message from unknown peer.");
+					return Err(Error::UnknownPeer(*peer));
+				}
+			}
+		}
+
+		let now = SystemTime::now();
+		let messages = message_packet.iter().map(|rlp| Message::decode(rlp, now))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		if !messages.is_empty() {
+			self.handler.handle_historical_messages(&messages);
+		}
+
+		Ok(())
+	}
+
 	fn on_connect<C: ?Sized + Context>(&self, io: &C, peer: &PeerId) {
 		trace!(target: "whisper", "Connecting peer {}", peer);
 
@@ -650,17 +1148,38 @@ impl<T: MessageHandler> Network<T> {
 			}
 		};
 
+		let advertised_bloom = match self.light {
+			true => Some(self.advertised_bloom()),
+			false => None,
+		};
+
 		self.peers.write().insert(*peer, Mutex::new(Peer {
 			node_key: node_key,
 			state: State::Unconfirmed(SystemTime::now()),
 			known_messages: HashSet::new(),
 			topic_filter: None,
+			advertised_bloom: advertised_bloom,
 			pow_requirement: 0f64,
+			requested_history: false,
+			rate: PeerRate::default(),
 			is_parity: io.protocol_version(PARITY_PROTOCOL_ID, *peer).is_some(),
+			is_light: false,
 			_protocol_version: version,
 		}));
 
-		io.send(*peer, packet::STATUS, ::rlp::EMPTY_LIST_RLP.to_vec());
+		let mut status = RlpStream::new_list(if advertised_bloom.is_some() { 4 } else { 2 });
+		status.append(&PROTOCOL_VERSION)
+			.append(&encode_pow_requirement(self.limits.min_pow));
+		if let Some(ref bloom) = advertised_bloom {
+			status.append(bloom).append(&self.light);
+		}
+		io.send(*peer, packet::STATUS, status.out());
+
+		// light nodes ask for envelopes matching their topics right away,
+		// since peers may ignore the bloom of the status.
+		if let Some(bloom) = advertised_bloom {
+			io.send(*peer, packet::TOPIC_FILTER, ::rlp::encode(&bloom));
+		}
 	}
 
 	fn on_packet<C: ?Sized + Context>(&self, io: &C, peer: &PeerId, packet_id: u8, data: &[u8]) {
@@ -670,6 +1189,8 @@ impl<T: MessageHandler> Network<T> {
 			packet::MESSAGES => self.on_messages(peer, rlp),
 			packet::POW_REQUIREMENT => self.on_pow_requirement(peer, rlp),
 			packet::TOPIC_FILTER => self.on_topic_filter(peer, rlp),
+			packet::P2P_REQUEST => self.on_p2p_request(io, peer, rlp),
+			packet::P2P_MESSAGES => self.on_p2p_messages(peer, rlp),
 			_ => Ok(()), // ignore unknown packets.
 		};
 
@@ -690,6 +1211,12 @@ impl<T: MessageHandler> ::network::NetworkProtocolHandler for Network<T> {
 		// set up broadcast timer (< 1s)
 		io.register_timer(RALLY_TOKEN, RALLY_TIMEOUT)
 			.expect("Failed to initialize message rally timer");
+
+		// periodically remove stale envelopes from the mail server.
+		if self.mail_server.is_some() {
+			io.register_timer(PRUNE_TOKEN, PRUNE_TIMEOUT)
+				.expect("Failed to initialize mail server pruning timer");
+		}
 	}
 
 	fn read(&self, io: &NetworkContext, peer: &PeerId, packet_id: u8, data: &[u8]) {
@@ -709,11 +1236,40 @@ impl<T: MessageHandler> ::network::NetworkProtocolHandler for Network<T> {
 		// rally with each peer and handle timeouts.
 		match timer {
 			RALLY_TOKEN => self.rally(io),
+			PRUNE_TOKEN => self.prune_mail_server(),
 			other => debug!(target: "whisper", "Timeout triggered on unknown token {}", other),
 		}
 	}
 }
 
+// encode PoW requirement as big-endian bytes of a float.
+fn encode_pow_requirement(requirement: f64) -> Vec<u8> {
+	use byteorder::{ByteOrder, BigEndian};
+
+	let mut bytes = vec![0; ::std::mem::size_of::<f64>()];
+	BigEndian::write_f64(&mut bytes, requirement);
+	bytes
+}
+
+// decode PoW requirement. zero means that the peer has no requirement.
+fn decode_pow_requirement(requirement: Rlp) -> Result<f64, Error> {
+	use byteorder::{ByteOrder, BigEndian};
+
+	let bytes: Vec<u8> = requirement.as_val()?;
+	if bytes.len() != ::std::mem::size_of::<f64>() {
+		return Err(Error::InvalidPowReq);
+	}
+
+	// as of byteorder 1.1.0, this is always defined.
+	let req = BigEndian::read_f64(&bytes[..]);
+
+	if req != 0.0 && !(req.is_normal() && req > 0.0) {
+		return Err(Error::InvalidPowReq);
+	}
+
+	Ok(req)
+}
+
 /// Dummy subprotocol used for parity extensions.
 #[derive(Debug, Copy, Clone)]
 pub struct ParityExtensions;
@@ -19,36 +19,81 @@
 use std::collections::HashSet;
 use std::sync::mpsc;
 
+use kvdb_rocksdb::Database;
 use parking_lot::Mutex;
 use network::{NodeId, PeerId};
+use tempdir::TempDir;
 
-use message::{CreateParams, Message};
+use mail_server::Config as MailServerConfig;
+use message::{CreateParams, Envelope, Message, Topic};
 use super::*;
 
-struct TestHandler(Mutex<mpsc::Sender<Message>>);
+struct TestHandler {
+	messages: Mutex<mpsc::Sender<Message>>,
+	historical: Mutex<mpsc::Sender<Message>>,
+	bloom: Mutex<H512>,
+}
 
 impl MessageHandler for TestHandler {
 	fn handle_messages(&self, messages: &[Message]) {
-		let tx = self.0.lock();
+		let tx = self.messages.lock();
+		for message in messages {
+			let _ = tx.send(message.clone());
+		}
+	}
+
+	fn handle_historical_messages(&self, messages: &[Message]) {
+		let tx = self.historical.lock();
 		for message in messages {
 			let _ = tx.send(message.clone());
 		}
 	}
+
+	fn topic_bloom(&self) -> H512 {
+		*self.bloom.lock()
+	}
 }
 
 struct TestPeer {
 	network: Network<TestHandler>,
 	recv: mpsc::Receiver<Message>,
+	recv_historical: mpsc::Receiver<Message>,
 	disconnected: Mutex<HashSet<PeerId>>,
 }
 
 impl TestPeer {
 	fn create() -> Self {
+		TestPeer::with_network(|handler| Network::new(10 * 1024 * 1024, handler))
+	}
+
+	fn with_limits(limits: EnvelopeLimits) -> Self {
+		TestPeer::with_network(|handler| Network::with_limits(10 * 1024 * 1024, limits, handler))
+	}
+
+	fn with_mail_server(mail_server: Arc<MailServer>) -> Self {
+		TestPeer::with_network(|handler| Network::new(10 * 1024 * 1024, handler).with_mail_server(mail_server))
+	}
+
+	fn light(bloom: H512) -> Self {
+		let peer = TestPeer::with_network(|handler| Network::new(10 * 1024 * 1024, handler).with_light_mode());
+		*peer.network.handler.bloom.lock() = bloom;
+		peer
+	}
+
+	fn with_network<F>(make_network: F) -> Self
+		where F: FnOnce(TestHandler) -> Network<TestHandler>
+	{
 		let (tx, rx) = mpsc::channel();
+		let (historical_tx, historical_rx) = mpsc::channel();
 
 		TestPeer {
-			network: Network::new(10 * 1024 * 1024, TestHandler(Mutex::new(tx))),
+			network: make_network(TestHandler {
+				messages: Mutex::new(tx),
+				historical: Mutex::new(historical_tx),
+				bloom: Mutex::new(H512::from([0xff; 64])),
+			}),
 			recv: rx,
+			recv_historical: historical_rx,
 			disconnected: Mutex::new(HashSet::new()),
 		}
 	}
@@ -60,16 +105,26 @@ struct TestNetwork {
 
 impl TestNetwork {
 	fn new(n_peers: usize) -> Self {
-		let unconnected_peers: Vec<_> = (0..n_peers).map(|_| TestPeer::create()).collect();
-		for i in 0..n_peers {
-			for j in (i + 1)..n_peers {
-				let (peer1, peer2) = (&unconnected_peers[i], &unconnected_peers[j]);
-				let ctx1 = TestContext::new(&unconnected_peers, i);
-				let ctx2 = TestContext::new(&unconnected_peers, j);
-
-				peer1.network.on_connect(&ctx1, &j);
-				peer2.network.on_connect(&ctx2, &i);
-			}
+		TestNetwork::with_peers((0..n_peers).map(|_| TestPeer::create()).collect())
+	}
+
+	fn with_peers(unconnected_peers: Vec<TestPeer>) -> Self {
+		let n_peers = unconnected_peers.len();
+		let links: Vec<_> = (0..n_peers)
+			.flat_map(|i| ((i + 1)..n_peers).map(move |j| (i, j)))
+			.collect();
+
+		TestNetwork::with_links(unconnected_peers, &links)
+	}
+
+	fn with_links(unconnected_peers: Vec<TestPeer>, links: &[(PeerId, PeerId)]) -> Self {
+		for &(i, j) in links {
+			let (peer1, peer2) = (&unconnected_peers[i], &unconnected_peers[j]);
+			let ctx1 = TestContext::new(&unconnected_peers, i);
+			let ctx2 = TestContext::new(&unconnected_peers, j);
+
+			peer1.network.on_connect(&ctx1, &j);
+			peer2.network.on_connect(&ctx2, &i);
 		}
 
 		TestNetwork {
@@ -190,3 +245,397 @@ fn message_gets_relayed() {
 		assert_eq!(network.peers[i].recv.try_recv().unwrap(), message);
 	}
 }
+
+fn test_message(payload: &[u8]) -> Message {
+	Message::create(CreateParams {
+		ttl: 500,
+		payload: payload.to_vec(),
+		topics: vec![[0, 1, 2, 3].into()],
+		work: 25,
+	}).unwrap()
+}
+
+#[test]
+fn envelope_limits_are_inclusive() {
+	let message = test_message(b"this is my payload, pal");
+	let limits = EnvelopeLimits {
+		min_pow: message.work_proved(),
+		max_size: message.encoded_size(),
+		..Default::default()
+	};
+
+	assert!(limits.admits(&message));
+	assert!(!EnvelopeLimits { min_pow: message.work_proved() * 2.0, ..limits }.admits(&message));
+	assert!(!EnvelopeLimits { max_size: message.encoded_size() - 1, ..limits }.admits(&message));
+}
+
+#[test]
+fn message_outside_of_local_limits_is_not_posted() {
+	let message = test_message(b"this is my payload, pal");
+	let network = TestNetwork::with_peers(vec![
+		TestPeer::with_limits(EnvelopeLimits { max_size: message.encoded_size() - 1, ..Default::default() }),
+		TestPeer::create(),
+	]);
+
+	assert!(!network.peers[0].network.post_message(message.clone(), &TestContext::new(&network.peers, 0)));
+	assert!(network.peers[1].recv.try_recv().is_err());
+	assert_eq!(network.peers[0].network.pool_status().max_message_size, message.encoded_size() - 1);
+}
+
+#[test]
+fn pow_requirement_is_advertised_with_status() {
+	let message = test_message(b"this is my payload, pal");
+	let min_pow = message.work_proved() * 2.0;
+	let network = TestNetwork::with_peers(vec![
+		TestPeer::create(),
+		TestPeer::with_limits(EnvelopeLimits { min_pow, ..Default::default() }),
+		TestPeer::create(),
+	]);
+
+	assert_eq!(network.peers[0].network.peers.read()[&1].lock().pow_requirement, min_pow);
+	assert_eq!(network.peers[0].network.peers.read()[&2].lock().pow_requirement, 0.0);
+	assert_eq!(network.peers[1].network.pool_status().required_pow, Some(min_pow));
+
+	network.post_message_from(0, message.clone());
+	assert!(network.peers[1].recv.try_recv().is_err());
+	assert_eq!(network.peers[2].recv.try_recv().unwrap(), message);
+}
+
+#[test]
+fn envelope_below_local_pow_requirement_is_dropped() {
+	let message = test_message(b"this is my payload, pal");
+	let network = TestNetwork::with_peers(vec![
+		TestPeer::create(),
+		TestPeer::with_limits(EnvelopeLimits { min_pow: message.work_proved() * 2.0, ..Default::default() }),
+	]);
+
+	// send the envelope ignoring the advertised requirement.
+	{
+		let mut stream = RlpStream::new_list(1);
+		stream.append(message.envelope());
+		TestContext::new(&network.peers, 0).send(1, packet::MESSAGES, stream.out());
+	}
+
+	assert!(network.peers[1].recv.try_recv().is_err());
+	assert_eq!(network.peers[1].network.pool_status().message_count, 0);
+	assert!(network.peers[1].disconnected.lock().is_empty());
+}
+
+#[test]
+fn mail_server_serves_historical_envelopes() {
+	let key = [7; 32];
+	let tempdir = TempDir::new("").unwrap();
+	let db = Database::open_default(&tempdir.path().display().to_string()).unwrap();
+	let mail_server = Arc::new(MailServer::new(Arc::new(db), None, MailServerConfig {
+		bloom: H512::from([0xff; 64]),
+		key,
+		max_size: 1024 * 1024,
+		retention: Duration::from_secs(3600),
+	}));
+	let network = TestNetwork::with_peers(vec![
+		TestPeer::with_mail_server(mail_server.clone()),
+		TestPeer::create(),
+		TestPeer::create(),
+	]);
+
+	// envelope relayed through the mail server is stored.
+	let message = test_message(b"this is my payload, pal");
+	network.post_message_from(1, message.clone());
+	assert_eq!(network.peers[0].recv.try_recv().unwrap(), message);
+
+	// while the one which expired long ago is pruned.
+	let expired = {
+		let envelope = Envelope {
+			expiry: 1010,
+			ttl: 10,
+			topics: vec![Topic([0, 1, 2, 3])].into_iter().collect(),
+			data: b"expired".to_vec(),
+			nonce: 0,
+		};
+		Message::decode(Rlp::new(&::rlp::encode(&envelope)), SystemTime::now()).unwrap()
+	};
+	mail_server.archive(&[expired]).unwrap();
+	assert_eq!(mail_server.prune(SystemTime::now()).unwrap(), 1);
+
+	// peer 2 requests envelopes of the last hour.
+	let now = SystemTime::now().duration_since(::std::time::UNIX_EPOCH).unwrap().as_secs();
+	let request = HistoryRequest { from: now - 3600, to: now + 3600, bloom: *message.bloom(), limit: 10, timestamp: now };
+	assert!(network.peers[2].network.request_history(0, &request, &key, &TestContext::new(&network.peers, 2)));
+
+	assert_eq!(network.peers[2].recv_historical.try_recv().unwrap(), message);
+	assert!(network.peers[2].recv_historical.try_recv().is_err());
+
+	// requests with another key are rejected.
+	assert!(network.peers[1].network.request_history(0, &request, &[8; 32], &TestContext::new(&network.peers, 1)));
+	assert!(network.peers[1].recv_historical.try_recv().is_err());
+	assert!(network.peers[0].disconnected.lock().contains(&1));
+}
+
+#[test]
+fn unrequested_historical_envelopes_are_rejected() {
+	let network = TestNetwork::new(2);
+	let message = test_message(b"this is my payload, pal");
+
+	{
+		let mut stream = RlpStream::new_list(1);
+		stream.append(message.envelope());
+		TestContext::new(&network.peers, 0).send(1, packet::P2P_MESSAGES, stream.out());
+	}
+
+	assert!(network.peers[1].recv_historical.try_recv().is_err());
+	assert!(network.peers[1].disconnected.lock().contains(&0));
+}
+
+fn messages_packet(messages: &[Message]) -> Vec<u8> {
+	let mut stream = RlpStream::new_list(messages.len());
+	for message in messages {
+		stream.append(message.envelope());
+	}
+	stream.out()
+}
+
+#[test]
+fn peer_budgets_drop_envelopes_during_cool_down() {
+	let budgets = PeerBudgets {
+		envelopes_per_second: 3,
+		bytes_per_second: 250,
+		cool_down: Duration::from_secs(5),
+		max_offenses: 1,
+		offense_decay: Duration::from_secs(60),
+	};
+	let start = Instant::now();
+	let mut rate = PeerRate::default();
+
+	// envelope budget.
+	assert_eq!(rate.admit(&budgets, &[10, 10], start), Some(2));
+	assert_eq!(rate.admit(&budgets, &[10, 10], start), Some(1));
+
+	// everything is dropped until the cool-down is over, which doesn't count as an offense.
+	assert_eq!(rate.admit(&budgets, &[10], start + Duration::from_secs(4)), Some(0));
+	assert_eq!(rate.admit(&budgets, &[10], start + Duration::from_secs(5)), Some(1));
+
+	// byte budget, exceeded for the second time.
+	assert_eq!(rate.admit(&budgets, &[200, 100], start + Duration::from_secs(6)), None);
+
+	assert_eq!(rate.accepted_envelopes, 5);
+	assert_eq!(rate.accepted_bytes, 240);
+	assert_eq!(rate.dropped_envelopes, 3);
+	assert_eq!(rate.offenses, 2);
+}
+
+#[test]
+fn peer_offenses_are_forgiven_over_time() {
+	let budgets = PeerBudgets {
+		envelopes_per_second: 1,
+		bytes_per_second: 0,
+		cool_down: Duration::from_secs(1),
+		max_offenses: 1,
+		offense_decay: Duration::from_secs(10),
+	};
+	let start = Instant::now();
+	let mut rate = PeerRate::default();
+
+	assert_eq!(rate.admit(&budgets, &[10, 10], start), Some(1));
+	assert_eq!(rate.offenses, 1);
+
+	// the first offense is forgiven before the second one, so the peer isn't disconnected.
+	assert_eq!(rate.admit(&budgets, &[10, 10], start + Duration::from_secs(10)), Some(1));
+	assert_eq!(rate.offenses, 1);
+
+	// while offenses in quick succession still add up.
+	assert_eq!(rate.admit(&budgets, &[10, 10], start + Duration::from_secs(12)), None);
+	assert_eq!(rate.offenses, 2);
+
+	// and all of them are eventually forgiven.
+	assert_eq!(rate.admit(&budgets, &[10], start + Duration::from_secs(32)), Some(1));
+	assert_eq!(rate.offenses, 0);
+	assert_eq!(rate.last_offense, None);
+}
+
+#[test]
+fn flooding_peer_is_disconnected() {
+	let per_peer = PeerBudgets {
+		envelopes_per_second: 2,
+		bytes_per_second: 0,
+		cool_down: Duration::from_secs(0),
+		max_offenses: 2,
+		offense_decay: Duration::from_secs(60),
+	};
+	let network = TestNetwork::with_peers(vec![
+		TestPeer::create(),
+		TestPeer::with_limits(EnvelopeLimits { per_peer, ..Default::default() }),
+		TestPeer::create(),
+	]);
+	let flood: Vec<_> = (0..9).map(|i| test_message(&[i])).collect();
+
+	let send_flood = |messages: &[Message]| {
+		TestContext::new(&network.peers, 0).send(1, packet::MESSAGES, messages_packet(messages));
+	};
+
+	// only envelopes within the budget are accepted.
+	send_flood(&flood[0..3]);
+	send_flood(&flood[3..6]);
+	assert_eq!(network.peers[1].recv.try_iter().collect::<Vec<_>>(), flood[0..2].to_vec());
+
+	let stats = network.peers[1].network.peer_stats();
+	let flooder = stats.iter().find(|stats| stats.node_key[0] == 0).unwrap();
+	assert_eq!((flooder.accepted_envelopes, flooder.dropped_envelopes, flooder.offenses), (2, 4, 2));
+
+	// while another peer is unaffected.
+	let message = test_message(b"this is my payload, pal");
+	network.post_message_from(2, message.clone());
+	assert_eq!(network.peers[1].recv.try_recv().unwrap(), message);
+
+	// until the flooding peer is disconnected.
+	send_flood(&flood[6..9]);
+	assert!(network.peers[1].recv.try_recv().is_err());
+	assert!(network.peers[1].disconnected.lock().contains(&0));
+	assert!(!network.peers[1].disconnected.lock().contains(&2));
+}
+
+#[test]
+fn peers_requiring_more_pow_are_served_last() {
+	let message = test_message(b"this is my payload, pal");
+	let network = TestNetwork::with_peers(vec![
+		TestPeer::create(),
+		TestPeer::with_limits(EnvelopeLimits { min_pow: message.work_proved() * 2.0, ..Default::default() }),
+		TestPeer::with_limits(EnvelopeLimits { min_pow: message.work_proved() / 2.0, ..Default::default() }),
+		TestPeer::create(),
+	]);
+	network.post_message_from(0, message.clone());
+
+	// only the peer requiring more PoW than the message proves is deprioritized.
+	let context = TestContext::new(&network.peers, 0);
+	network.peers[0].network.rally(&context);
+	let receivers: Vec<_> = context.events.lock().iter().filter_map(|event| match *event {
+		Event::Send(_, to, packet::MESSAGES, _) => Some(to),
+		_ => None,
+	}).collect();
+
+	assert_eq!(receivers, vec![2, 3, 1]);
+}
+
+fn message_with_topic(topic: [u8; 4]) -> Message {
+	Message::create(CreateParams {
+		ttl: 500,
+		payload: b"this is my payload, pal".to_vec(),
+		topics: vec![topic.into()],
+		work: 25,
+	}).unwrap()
+}
+
+#[test]
+fn status_items_are_decoded_at_geth_positions() {
+	let network = TestNetwork::new(2);
+	let bloom = H512::from([0x0f; 64]);
+	let status = |is_light: Vec<u8>| {
+		let mut status = RlpStream::new_list(4);
+		status.append(&PROTOCOL_VERSION)
+			.append(&encode_pow_requirement(0.0))
+			.append(&bloom)
+			.append_raw(&is_light, 1);
+		status.out()
+	};
+
+	network.peers[0].network.on_status(&1, Rlp::new(&status(::rlp::encode(&true)))).unwrap();
+	assert!(network.peers[0].network.peers.read()[&1].lock().is_light);
+	assert_eq!(network.peers[0].network.peers.read()[&1].lock().topic_filter, Some(bloom));
+
+	// light flag of unexpected type is treated as absent.
+	network.peers[0].network.on_status(&1, Rlp::new(&status(::rlp::encode(&"light".to_owned())))).unwrap();
+	assert!(!network.peers[0].network.peers.read()[&1].lock().is_light);
+}
+
+#[test]
+fn light_node_does_not_relay() {
+	let chain = |middle: TestPeer| TestNetwork::with_links(
+		vec![TestPeer::create(), middle, TestPeer::create()],
+		&[(0, 1), (1, 2)],
+	);
+	let message = test_message(b"this is my payload, pal");
+
+	// a full node relays the envelope on the next rally.
+	let network = chain(TestPeer::create());
+	network.post_message_from(0, message.clone());
+	network.peers[1].network.rally(&TestContext::new(&network.peers, 1));
+	assert_eq!(network.peers[1].recv.try_recv().unwrap(), message);
+	assert_eq!(network.peers[2].recv.try_recv().unwrap(), message);
+
+	// while a light one only handles it.
+	let network = chain(TestPeer::light(H512::from([0xff; 64])));
+	assert!(network.peers[0].network.peers.read()[&1].lock().is_light);
+
+	network.post_message_from(0, message.clone());
+	network.peers[1].network.rally(&TestContext::new(&network.peers, 1));
+	assert_eq!(network.peers[1].recv.try_recv().unwrap(), message);
+	assert!(network.peers[2].recv.try_recv().is_err());
+
+	// but still relays envelopes posted locally.
+	let local = test_message(b"local payload");
+	network.post_message_from(1, local.clone());
+	assert_eq!(network.peers[0].recv.try_recv().unwrap(), local);
+	assert_eq!(network.peers[2].recv.try_recv().unwrap(), local);
+}
+
+#[test]
+fn only_matching_envelopes_are_sent_to_light_peers() {
+	let matching = message_with_topic([0, 1, 2, 3]);
+	let other = message_with_topic([9, 9, 9, 9]);
+	let network = TestNetwork::with_peers(vec![
+		TestPeer::create(),
+		TestPeer::light(matching.bloom().clone()),
+		TestPeer::create(),
+	]);
+
+	assert_eq!(network.peers[0].network.peers.read()[&1].lock().topic_filter, Some(matching.bloom().clone()));
+
+	network.post_message_from(0, matching.clone());
+	network.post_message_from(0, other.clone());
+
+	assert_eq!(network.peers[1].recv.try_iter().collect::<Vec<_>>(), vec![matching.clone()]);
+	assert_eq!(network.peers[2].recv.try_iter().collect::<Vec<_>>(), vec![matching, other]);
+}
+
+#[test]
+fn light_node_drops_envelopes_it_did_not_ask_for() {
+	let matching = message_with_topic([0, 1, 2, 3]);
+	let other = message_with_topic([9, 9, 9, 9]);
+	let network = TestNetwork::with_peers(vec![
+		TestPeer::create(),
+		TestPeer::light(matching.bloom().clone()),
+	]);
+
+	// send envelopes ignoring the advertised topic filter.
+	TestContext::new(&network.peers, 0).send(1, packet::MESSAGES, messages_packet(&[other, matching.clone()]));
+
+	assert_eq!(network.peers[1].recv.try_iter().collect::<Vec<_>>(), vec![matching]);
+	assert_eq!(network.peers[1].network.pool_status().message_count, 1);
+	assert!(network.peers[1].disconnected.lock().is_empty());
+}
+
+#[test]
+fn topic_bloom_is_readvertised_when_it_changes_materially() {
+	let first = Topic([0, 1, 2, 3]).bloom();
+	let second = Topic([9, 9, 9, 9]).bloom();
+	let network = TestNetwork::with_peers(vec![
+		TestPeer::create(),
+		TestPeer::light(first),
+	]);
+
+	let rally_with_bloom = |bloom: H512| {
+		*network.peers[1].network.handler.bloom.lock() = bloom;
+		network.peers[1].network.rally(&TestContext::new(&network.peers, 1));
+		network.peers[0].network.peers.read()[&1].lock().topic_filter.unwrap()
+	};
+
+	// new topics are requested right away.
+	assert_eq!(rally_with_bloom(&first | &second), &first | &second);
+
+	// while dropping a few topics isn't worth an update.
+	assert_eq!(rally_with_bloom(first), &first | &second);
+
+	// unlike dropping most of them.
+	let wide = H512::from([0x0f; 64]);
+	assert_eq!(rally_with_bloom(&wide | &first), &wide | &first);
+	assert_eq!(rally_with_bloom(first), first);
+}
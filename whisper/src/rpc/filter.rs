@@ -189,6 +189,20 @@ impl ::net::MessageHandler for Arc<Manager> {
 			}
 		}
 	}
+
+	fn topic_bloom(&self) -> H512 {
+		let mut bloom = H512::default();
+		for filter in self.filters.read().values() {
+			match *filter {
+				FilterEntry::Poll(ref filter, _) | FilterEntry::Subscription(ref filter, _) => {
+					for &(_, ref topic_bloom, _) in &filter.topics {
+						bloom = &bloom | topic_bloom;
+					}
+				}
+			}
+		}
+		bloom
+	}
 }
 
 impl Drop for Manager {
@@ -75,6 +75,10 @@ pub trait Whisper {
 	#[rpc(name = "shh_info")]
 	fn info(&self) -> Result<types::NodeInfo, Error>;
 
+	/// Statistics of envelopes received from connected peers.
+	#[rpc(name = "shh_peerStats")]
+	fn peer_stats(&self) -> Result<Vec<types::PeerStats>, Error>;
+
 	/// Generate a new asymmetric key pair and return an identity.
 	#[rpc(name = "shh_newKeyPair")]
 	fn new_key_pair(&self) -> Result<types::Identity, Error>;
@@ -150,6 +154,9 @@ pub trait PoolHandle: Send + Sync {
 
 	/// Number of messages and memory used by resident messages.
 	fn pool_status(&self) -> ::net::PoolStatus;
+
+	/// Statistics of envelopes received from each peer.
+	fn peer_stats(&self) -> Vec<::net::PeerStats>;
 }
 
 /// Default, simple metadata implementation.
@@ -208,12 +215,25 @@ impl<P: PoolHandle + 'static, M: Send + Sync + 'static> Whisper for WhisperClien
 
 		Ok(types::NodeInfo {
 			required_pow: status.required_pow,
+			max_message_size: status.max_message_size,
 			messages: status.message_count,
 			memory: status.cumulative_size,
 			target_memory: status.target_size,
 		})
 	}
 
+	fn peer_stats(&self) -> Result<Vec<types::PeerStats>, Error> {
+		Ok(self.pool.peer_stats().into_iter().map(|stats| types::PeerStats {
+			id: HexEncode(stats.node_key),
+			accepted_envelopes: stats.accepted_envelopes,
+			accepted_bytes: stats.accepted_bytes,
+			dropped_envelopes: stats.dropped_envelopes,
+			offenses: stats.offenses,
+			cooling_down: stats.cooling_down,
+			pow_requirement: stats.pow_requirement,
+		}).collect())
+	}
+
 	fn new_key_pair(&self) -> Result<types::Identity, Error> {
 		let mut store = self.store.write();
 		let key_pair = Key::new_asymmetric(store.rng());
@@ -243,6 +243,10 @@ pub struct NodeInfo {
 	#[serde(rename = "minPow")]
 	pub required_pow: Option<f64>,
 
+	/// max size of a message to be accepted into the local pool.
+	#[serde(rename = "maxMessageSize")]
+	pub max_message_size: usize,
+
 	/// Number of messages in the pool.
 	pub messages: usize,
 
@@ -253,6 +257,32 @@ pub struct NodeInfo {
 	pub target_memory: usize,
 }
 
+/// Statistics of envelopes received from a peer.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerStats {
+	/// Node key of the peer.
+	pub id: Public,
+
+	/// Number of envelopes accepted from the peer.
+	pub accepted_envelopes: u64,
+
+	/// Total size of envelopes accepted from the peer.
+	pub accepted_bytes: u64,
+
+	/// Number of envelopes dropped for exceeding the peer's budgets.
+	pub dropped_envelopes: u64,
+
+	/// Number of times the peer exceeded its budgets.
+	pub offenses: u32,
+
+	/// Whether envelopes of the peer are currently dropped.
+	pub cooling_down: bool,
+
+	/// PoW requirement advertised by the peer.
+	pub pow_requirement: f64,
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;